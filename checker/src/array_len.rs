@@ -0,0 +1,348 @@
+//! Compile-time checking of array lengths known from literals and `[T; N]` annotations.
+
+use std::collections::HashMap;
+
+use db::Database;
+use modules::{Def, ModuleTree};
+use parser::{
+    ast::{
+        ArrayExpr, ArrayRepeatExpr, AstNode, BinaryExpr, BlockExpr, CallExpr, ClosureExpr, Expr,
+        FunctionExpr, IndexExpr, LetStmt, Param, ReturnStmt, Type,
+    },
+    syntax::{SyntaxKind, SyntaxNode},
+};
+use source_map::{Diagnostic, FileId, Span};
+
+use crate::{is_local, literal, prelude};
+
+pub(crate) fn check(
+    db: &Database,
+    file: FileId,
+    root: &SyntaxNode,
+    module_tree: &ModuleTree,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut checker = Checker {
+        db,
+        file,
+        module_tree,
+        scopes: vec![HashMap::new()],
+        returns: Vec::new(),
+        diagnostics,
+    };
+    checker.visit(root);
+}
+
+struct Checker<'a> {
+    /// Where the signatures of the functions called are read.
+    db: &'a Database,
+    file: FileId,
+    module_tree: &'a ModuleTree,
+    /// The statically known length of the arrays bound to variables, `None` when unknown.
+    scopes: Vec<HashMap<String, Option<u64>>>,
+    /// The annotated lengths of the arrays returned by the functions and closures being
+    /// checked, innermost last.
+    returns: Vec<Option<u64>>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn visit(&mut self, node: &SyntaxNode) {
+        let is_scope = matches!(
+            node.kind(),
+            SyntaxKind::BlockExpr | SyntaxKind::FunctionExpr
+        );
+        if is_scope {
+            self.scopes.push(HashMap::new());
+        }
+        let return_type = if let Some(function) = FunctionExpr::cast(node.clone()) {
+            Some(function.return_type())
+        } else {
+            ClosureExpr::cast(node.clone()).map(|closure| closure.return_type())
+        };
+        if let Some(ty) = &return_type {
+            self.returns.push(ty.as_ref().and_then(type_len));
+        }
+
+        if let Some(index) = IndexExpr::cast(node.clone()) {
+            self.check_index(&index);
+        } else if let Some(binary) = BinaryExpr::cast(node.clone()) {
+            self.check_assignment(&binary);
+        } else if let Some(call) = CallExpr::cast(node.clone()) {
+            self.check_call(&call);
+        } else if let Some(stmt) = ReturnStmt::cast(node.clone()) {
+            self.check_return(stmt.value());
+        }
+
+        for child in node.children() {
+            self.visit(&child);
+        }
+
+        // the tail of a body is returned, and sees the locals of the body
+        let is_body = node.parent().is_some_and(|parent| {
+            matches!(
+                parent.kind(),
+                SyntaxKind::FunctionExpr | SyntaxKind::ClosureExpr
+            )
+        });
+        if let Some(block) = BlockExpr::cast(node.clone()).filter(|_| is_body) {
+            self.check_return(block.tail_expr());
+        }
+        if return_type.is_some() {
+            self.returns.pop();
+        }
+
+        if let Some(stmt) = LetStmt::cast(node.clone()) {
+            self.bind_let(&stmt);
+        } else if let Some(param) = Param::cast(node.clone()) {
+            if let Some(name) = param.name() {
+                let len = param.ty().and_then(|ty| type_len(&ty));
+                self.bind(name.text(), len);
+            }
+        }
+
+        if is_scope {
+            self.scopes.pop();
+        }
+    }
+
+    fn bind(&mut self, name: &str, len: Option<u64>) {
+        let scope = self.scopes.last_mut().expect("there's always a scope");
+        scope.insert(name.to_string(), len);
+    }
+
+    fn lookup(&self, name: &str) -> Option<u64> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+            .flatten()
+    }
+
+    fn error(&mut self, node: &SyntaxNode, message: String) {
+        let span = Span::new(self.file, node.text_range());
+        self.diagnostics.push(Diagnostic::error(span, message));
+    }
+
+    fn bind_let(&mut self, stmt: &LetStmt) {
+        let expected = stmt.ty().and_then(|ty| type_len(&ty));
+        let initializer = stmt.initializer();
+        let found = initializer.as_ref().and_then(|expr| self.expr_len(expr));
+
+        if let (Some(expected), Some(found), Some(initializer)) = (expected, found, &initializer) {
+            self.check_lengths(initializer.syntax(), expected, found);
+        }
+
+        if let Some(name) = stmt.name() {
+            self.bind(name.text(), expected.or(found));
+        }
+    }
+
+    fn check_assignment(&mut self, binary: &BinaryExpr) {
+        if binary
+            .op()
+            .is_none_or(|op| op.kind() != SyntaxKind::SingleEqual)
+        {
+            return;
+        }
+
+        let (Some(lhs), Some(rhs)) = (binary.lhs(), binary.rhs()) else {
+            return;
+        };
+        if let (Some(expected), Some(found)) = (self.expr_len(&lhs), self.expr_len(&rhs)) {
+            self.check_lengths(rhs.syntax(), expected, found);
+        }
+    }
+
+    fn check_lengths(&mut self, node: &SyntaxNode, expected: u64, found: u64) {
+        if expected != found {
+            self.error(
+                node,
+                format!(
+                    "mismatched types: expected an array of length {expected}, found one of length {found}"
+                ),
+            );
+        }
+    }
+
+    /// Checks the arguments of a call to a function of the program against the lengths
+    /// annotated on its parameters.
+    fn check_call(&mut self, call: &CallExpr) {
+        let Some(Expr::VariableRef(callee)) = call.callee() else {
+            return;
+        };
+        let segments = callee.segments().collect::<Vec<_>>();
+        if let [name] = segments.as_slice() {
+            if is_local(callee.syntax(), name.text())
+                || prelude::is_local_function(callee.syntax(), name.text())
+            {
+                return;
+            }
+        }
+        let names = segments
+            .iter()
+            .map(|segment| segment.text())
+            .collect::<Vec<_>>();
+        let module = self
+            .module_tree
+            .module_at(Span::new(self.file, callee.syntax().text_range()));
+        let Ok(Def::Function(span)) = self.module_tree.resolve_path(module, &names) else {
+            return;
+        };
+        let Some(function) = crate::item::<FunctionExpr>(self.db, span) else {
+            return;
+        };
+
+        let params = function
+            .param_list()
+            .into_iter()
+            .flat_map(|list| list.params());
+        let args = call.arg_list().into_iter().flat_map(|list| list.args());
+        for (param, arg) in params.zip(args) {
+            let expected = param.ty().and_then(|ty| type_len(&ty));
+            if let (Some(expected), Some(found)) = (expected, self.expr_len(&arg)) {
+                self.check_lengths(arg.syntax(), expected, found);
+            }
+        }
+    }
+
+    /// Checks a value returned by the innermost function against its annotated length.
+    fn check_return(&mut self, value: Option<Expr>) {
+        let (Some(Some(expected)), Some(value)) = (self.returns.last().copied(), value) else {
+            return;
+        };
+        if let Some(found) = self.expr_len(&value) {
+            self.check_lengths(value.syntax(), expected, found);
+        }
+    }
+
+    fn check_index(&mut self, index_expr: &IndexExpr) {
+        let Some(len) = index_expr.base().and_then(|base| self.expr_len(&base)) else {
+            return;
+        };
+        let Some(index) = index_expr.index().as_ref().and_then(const_int) else {
+            return;
+        };
+
+        if index >= len {
+            self.error(
+                index_expr.syntax(),
+                format!("index out of bounds: the length is {len} but the index is {index}"),
+            );
+        }
+    }
+
+    /// The length of the array an expression evaluates to, when known without running it.
+    fn expr_len(&self, expr: &Expr) -> Option<u64> {
+        match expr {
+            Expr::Array(array) => Some(array_len(array)),
+            Expr::ArrayRepeat(repeat) => repeat_len(repeat),
+            Expr::VariableRef(variable) => {
+                let [name] = variable.segments().collect::<Vec<_>>().try_into().ok()?;
+                self.lookup(name.text())
+            }
+            Expr::Paren(paren) => self.expr_len(&paren.expr()?),
+            _ => None,
+        }
+    }
+}
+
+fn array_len(array: &ArrayExpr) -> u64 {
+    array.elements().count() as u64
+}
+
+fn repeat_len(repeat: &ArrayRepeatExpr) -> Option<u64> {
+    const_int(&repeat.count()?)
+}
+
+fn type_len(ty: &Type) -> Option<u64> {
+    match ty {
+        Type::Array(array) => const_int(&array.len()?),
+        Type::Ref(reference) => type_len(&reference.ty()?),
+        _ => None,
+    }
+}
+
+/// The value of a non-negative integer literal.
+fn const_int(expr: &Expr) -> Option<u64> {
+    let Expr::Literal(literal) = expr else {
+        return None;
+    };
+    let token = literal
+        .token()
+        .filter(|token| token.kind() == SyntaxKind::Number)?;
+
+    literal::int(token.text())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check_source;
+
+    #[test]
+    fn annotated_length() {
+        assert_eq!(
+            check_source("fn f() { let a: [i32; 3] = [1, 2]; let b: [u8; 0x2] = [0; 2]; }"),
+            ["[1, 2]: mismatched types: expected an array of length 3, found one of length 2"]
+        );
+    }
+
+    #[test]
+    fn constant_index() {
+        assert_eq!(
+            check_source(
+                "fn f(p: [i32; 2]) { let a = [0; 4]; a[3]; a[4]; p[2]; [1][0]; { let a = 1; a[9]; } }"
+            ),
+            [
+                "a[4]: index out of bounds: the length is 4 but the index is 4",
+                "p[2]: index out of bounds: the length is 2 but the index is 2",
+                "a[9]: cannot index into a value of type `integer`",
+            ]
+        );
+    }
+
+    #[test]
+    fn referenced_length() {
+        assert_eq!(
+            check_source("fn f(p: &[i32; 2], q: &mut [i32]) { p[2]; q[2]; }"),
+            ["p[2]: index out of bounds: the length is 2 but the index is 2"]
+        );
+    }
+
+    #[test]
+    fn assigned_length() {
+        assert_eq!(
+            check_source("fn f() { let mut a = [1, 2]; a = [3, 4]; a = [5]; }"),
+            ["[5]: mismatched types: expected an array of length 2, found one of length 1"]
+        );
+    }
+
+    #[test]
+    fn signature_lengths() {
+        assert_eq!(
+            check_source(
+                "fn sum(a: [i64; 3]) -> i64 { a[0] }
+                fn f() -> [i64; 2] { [1, 2, 3] }
+                fn g(x: bool) -> [i64; 2] { if x { return [1]; } let b = [0; 2]; b }
+                fn main() { let a = [1, 2]; sum(a); sum([1, 2, 3]); let h = |a: [i64; 3]| -> [i64; 1] { a }; }"
+            ),
+            [
+                "[1, 2, 3]: mismatched types: expected an array of length 2, found one of length 3",
+                "[1]: mismatched types: expected an array of length 2, found one of length 1",
+                "a: mismatched types: expected an array of length 3, found one of length 2",
+                "a: mismatched types: expected an array of length 1, found one of length 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_lengths() {
+        assert_eq!(
+            check_source(
+                "fn f(n, p: [i32], g) { let a = [0; n]; a[9]; p[9]; let b: [i32; 2] = g(); }"
+            ),
+            Vec::<String>::new()
+        );
+    }
+}
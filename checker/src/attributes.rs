@@ -0,0 +1,412 @@
+//! Checking that attributes are known and fit the items they're applied to, and warning
+//! about uses of items marked `#[deprecated]`.
+
+use std::collections::HashMap;
+
+use modules::{Def, ModuleTree};
+use parser::{
+    ast::{
+        AstNode, Attr, AttrArg, EnumStmt, FunctionExpr, PathPattern, PathType, RecordExpr,
+        StructPattern, StructStmt, TraitStmt, TupleStructPattern, VariableRef,
+    },
+    syntax::{SyntaxKind, SyntaxNode, SyntaxToken},
+};
+use source_map::{Diagnostic, FileId, Span};
+
+use crate::{is_local, literal};
+
+/// The items marked `#[deprecated]`, by the span of their name, along with their notes.
+pub(crate) type Deprecations = HashMap<Span, Option<String>>;
+
+/// Collects the deprecated items of a file, which can be used from any file.
+pub(crate) fn collect_deprecations(
+    file: FileId,
+    root: &SyntaxNode,
+    deprecations: &mut Deprecations,
+) {
+    for attr in root.descendants().filter_map(Attr::cast) {
+        if attr.is_inner() || attr.name().is_none_or(|name| name.text() != "deprecated") {
+            continue;
+        }
+
+        let Some(name) = attr.syntax().parent().and_then(|item| item_name(&item)) else {
+            continue;
+        };
+        deprecations.insert(Span::new(file, name.text_range()), note(&attr));
+    }
+}
+
+pub(crate) fn check(
+    file: FileId,
+    root: &SyntaxNode,
+    module_tree: &ModuleTree,
+    deprecations: &Deprecations,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut checker = Checker {
+        file,
+        module_tree,
+        deprecations,
+        diagnostics,
+    };
+
+    for node in root.descendants() {
+        match Attr::cast(node.clone()) {
+            Some(attr) => checker.check_attr(&attr),
+            None => checker.check_use(&node),
+        }
+    }
+}
+
+struct Checker<'a> {
+    file: FileId,
+    module_tree: &'a ModuleTree,
+    deprecations: &'a Deprecations,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn span(&self, node: &SyntaxNode) -> Span {
+        Span::new(self.file, node.text_range())
+    }
+
+    fn error(&mut self, node: &SyntaxNode, message: impl Into<String>) {
+        let span = self.span(node);
+        self.diagnostics.push(Diagnostic::error(span, message));
+    }
+
+    fn check_attr(&mut self, attr: &Attr) {
+        let Some(name) = attr.name() else {
+            return;
+        };
+        let target = target(attr);
+        let args = attr
+            .arg_list()
+            .map(|list| list.args().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        match name.text() {
+            "test" => {
+                let function = target
+                    .and_then(FunctionExpr::cast)
+                    .filter(|function| is_free_function(function.syntax()));
+                let Some(function) = function else {
+                    let message =
+                        "`#[test]` can only be applied to functions outside of traits and impls";
+                    self.error(attr.syntax(), message);
+                    return;
+                };
+
+                if let Some(list) = attr.arg_list() {
+                    self.error(list.syntax(), "`#[test]` takes no arguments");
+                }
+                if let Some(list) = function.param_list() {
+                    if list.params().next().is_some() {
+                        self.error(list.syntax(), "test functions can't take parameters");
+                    }
+                }
+            }
+            "ignore" => {
+                let is_test = target.and_then(FunctionExpr::cast).is_some_and(|function| {
+                    function
+                        .attrs()
+                        .any(|attr| attr.name().is_some_and(|name| name.text() == "test"))
+                });
+                if !is_test {
+                    self.error(
+                        attr.syntax(),
+                        "`#[ignore]` can only be applied to functions marked `#[test]`",
+                    );
+                    return;
+                }
+
+                if let Some(list) = attr.arg_list() {
+                    self.error(list.syntax(), "`#[ignore]` takes no arguments");
+                }
+            }
+            "inline" => {
+                if target.and_then(FunctionExpr::cast).is_none() {
+                    self.error(
+                        attr.syntax(),
+                        "`#[inline]` can only be applied to functions",
+                    );
+                    return;
+                }
+
+                let valid = match args.as_slice() {
+                    [] => true,
+                    [arg] => {
+                        arg.value().is_none()
+                            && arg
+                                .name()
+                                .is_some_and(|name| matches!(name.text(), "always" | "never"))
+                    }
+                    _ => false,
+                };
+                if !valid {
+                    self.error(
+                        attr.syntax(),
+                        "expected `#[inline]`, `#[inline(always)]` or `#[inline(never)]`",
+                    );
+                }
+            }
+            "deprecated" => {
+                if target.and_then(|target| item_name(&target)).is_none() {
+                    let message =
+                        "`#[deprecated]` can only be applied to functions, enums and traits";
+                    self.error(attr.syntax(), message);
+                    return;
+                }
+
+                for arg in args {
+                    if arg.name().is_none_or(|name| name.text() != "note") {
+                        self.error(arg.syntax(), "expected `note = \"...\"`");
+                    } else if note_token(&arg).is_none() {
+                        self.error(arg.syntax(), "`note` expects a string literal");
+                    }
+                }
+            }
+            unknown => {
+                let span = Span::new(self.file, name.text_range());
+                let message = format!("unknown attribute `{unknown}`");
+                self.diagnostics.push(Diagnostic::warning(span, message));
+            }
+        }
+    }
+
+    /// Warns if `node` is a path to a deprecated item.
+    fn check_use(&mut self, node: &SyntaxNode) {
+        let Some(segments) = path_segments(node) else {
+            return;
+        };
+        if let [name] = segments.as_slice() {
+            if node.kind() == SyntaxKind::VariableRef && is_local(node, name.text()) {
+                return;
+            }
+        }
+
+        let module = self.module_tree.module_at(self.span(node));
+        let path = segments.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+        let (item, kind, name) = match self.module_tree.resolve_path(module, &path) {
+            Ok(Def::Function(span)) => (span, "function", path.join("::")),
+            Ok(Def::Enum(span)) => (span, "enum", path.join("::")),
+            // the variants of a deprecated enum are deprecated along with it
+            Ok(Def::Variant(span, _)) => (span, "enum", path[..path.len() - 1].join("::")),
+            Ok(Def::Struct(span)) => (span, "struct", path.join("::")),
+            Ok(Def::Trait(span)) => (span, "trait", path.join("::")),
+            Ok(Def::Module(_)) | Err(_) => return,
+        };
+        let Some(note) = self.deprecations.get(&item) else {
+            return;
+        };
+
+        // deprecated items can use each other without warnings
+        let in_deprecated = node
+            .ancestors()
+            .filter_map(|ancestor| item_name(&ancestor))
+            .any(|name| self.deprecations.contains_key(&self.span_of(&name)));
+        if in_deprecated {
+            return;
+        }
+
+        let message = match note {
+            Some(note) => format!("use of deprecated {kind} `{name}`: {note}"),
+            None => format!("use of deprecated {kind} `{name}`"),
+        };
+        let span = self.span(node);
+        self.diagnostics.push(Diagnostic::warning(span, message));
+    }
+
+    fn span_of(&self, token: &SyntaxToken) -> Span {
+        Span::new(self.file, token.text_range())
+    }
+}
+
+/// What an attribute applies to: the item following it, or what encloses an inner
+/// attribute, such as the function whose body starts with it. `None` for outer attributes
+/// that aren't followed by an item, which don't parse.
+fn target(attr: &Attr) -> Option<SyntaxNode> {
+    let parent = attr.syntax().parent()?;
+
+    if !attr.is_inner() {
+        let dangling = matches!(
+            parent.kind(),
+            SyntaxKind::Root | SyntaxKind::ItemList | SyntaxKind::BlockExpr
+        );
+        return (!dangling).then_some(parent);
+    }
+
+    match parent.kind() {
+        SyntaxKind::Root => Some(parent),
+        _ => parent.parent(),
+    }
+}
+
+/// The name of an item that paths can refer to, which identifies it.
+fn item_name(node: &SyntaxNode) -> Option<SyntaxToken> {
+    match node.kind() {
+        SyntaxKind::FunctionExpr => FunctionExpr::cast(node.clone())?.name(),
+        SyntaxKind::EnumStmt => EnumStmt::cast(node.clone())?.name(),
+        SyntaxKind::StructStmt => StructStmt::cast(node.clone())?.name(),
+        SyntaxKind::TraitStmt => TraitStmt::cast(node.clone())?.name(),
+        _ => None,
+    }
+}
+
+/// The argument of `note = "..."`, if any.
+fn note(attr: &Attr) -> Option<String> {
+    attr.arg_list()?
+        .args()
+        .filter(|arg| arg.name().is_some_and(|name| name.text() == "note"))
+        .find_map(|arg| literal::string(note_token(&arg)?.text()))
+}
+
+/// The string literal given to `note`, `None` if the value is anything else.
+fn note_token(arg: &AttrArg) -> Option<SyntaxToken> {
+    arg.value()?
+        .token()
+        .filter(|token| token.kind() == SyntaxKind::String)
+}
+
+/// Whether the function is declared in a module rather than in a trait, an impl or a body.
+fn is_free_function(function: &SyntaxNode) -> bool {
+    let Some(list) = function.parent().and_then(|stmt| stmt.parent()) else {
+        return false;
+    };
+
+    match list.kind() {
+        SyntaxKind::Root => true,
+        SyntaxKind::ItemList => list
+            .parent()
+            .is_some_and(|item| item.kind() == SyntaxKind::ModStmt),
+        _ => false,
+    }
+}
+
+fn path_segments(node: &SyntaxNode) -> Option<Vec<SyntaxToken>> {
+    let segments = match node.kind() {
+        SyntaxKind::VariableRef => VariableRef::cast(node.clone())?.segments().collect(),
+        SyntaxKind::PathType => PathType::cast(node.clone())?.segments().collect(),
+        SyntaxKind::RecordExpr => RecordExpr::cast(node.clone())?.segments().collect(),
+        SyntaxKind::PathPattern => PathPattern::cast(node.clone())?.segments().collect(),
+        SyntaxKind::TupleStructPattern => {
+            TupleStructPattern::cast(node.clone())?.segments().collect()
+        }
+        SyntaxKind::StructPattern => StructPattern::cast(node.clone())?.segments().collect(),
+        _ => return None,
+    };
+
+    Some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check_source;
+
+    #[test]
+    fn known_attributes() {
+        let source = "#[test]
+        fn t() {}
+        #[test]
+        #[ignore]
+        fn slow() {}
+        #[inline]
+        #[inline(always)]
+        fn f() {
+            #![inline]
+        }
+        #[deprecated(note = \"use F\")]
+        enum D { A }
+        enum E { A }
+        mod m {
+            #[test]
+            fn t() {}
+        }
+        impl E {
+            #[inline(never)]
+            fn g(self) {}
+        }";
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn misplaced_attributes() {
+        let source = "#[test] enum E { A }
+        #[test(a)] fn t(a: i64) {}
+        impl E { #[test] fn u(self) {} }
+        #[inline] mod m {}
+        #[inline(sometimes)] fn f() {}
+        #[deprecated] impl E {}
+        #[deprecated(\"old\", since = 1)] fn g() {}
+        #[deprecated(note = 5)] fn j() { j() }
+        #[ignore] fn h() {}
+        #[test] #[ignore(why)] fn i() {}";
+        assert_eq!(
+            check_source(source),
+            [
+                "#[test]: `#[test]` can only be applied to functions outside of traits and impls",
+                "(a): `#[test]` takes no arguments",
+                "(a: i64): test functions can't take parameters",
+                "#[test]: `#[test]` can only be applied to functions outside of traits and impls",
+                "#[inline]: `#[inline]` can only be applied to functions",
+                "#[inline(sometimes)]: expected `#[inline]`, `#[inline(always)]` or `#[inline(never)]`",
+                "#[deprecated]: `#[deprecated]` can only be applied to functions, enums and traits",
+                "\"old\": expected `note = \"...\"`",
+                "since = 1: expected `note = \"...\"`",
+                "note = 5: `note` expects a string literal",
+                "#[ignore]: `#[ignore]` can only be applied to functions marked `#[test]`",
+                "(why): `#[ignore]` takes no arguments",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_attributes() {
+        let source = "#![no_std] #[derive(Eq)] fn f() {}";
+        assert_eq!(
+            check_source(source),
+            [
+                "no_std: unknown attribute `no_std`",
+                "derive: unknown attribute `derive`",
+            ]
+        );
+    }
+
+    #[test]
+    fn deprecated_uses() {
+        let source = "#[deprecated(note = \"use `g` instead\")]
+        fn f() { f() }
+        fn g() {}
+        mod m {
+            #[deprecated]
+            pub enum E { A, B(i64) }
+            #[deprecated]
+            pub trait T {}
+        }
+        use m::E;
+        fn main() {
+            f();
+            m::E::A;
+            let e: E = E::B(1);
+            match e {
+                E::B(_) => {}
+                _ => {}
+            }
+            let f = g;
+            f();
+        }
+        impl<U: m::T> m::T for U {}";
+        assert_eq!(
+            check_source(source),
+            [
+                "f: use of deprecated function `f`: use `g` instead",
+                "m::E::A: use of deprecated enum `m::E`",
+                "E: use of deprecated enum `E`",
+                "E::B: use of deprecated enum `E`",
+                "E::B(_): use of deprecated enum `E`",
+                "m::T: use of deprecated trait `m::T`",
+                "m::T: use of deprecated trait `m::T`",
+            ]
+        );
+    }
+}
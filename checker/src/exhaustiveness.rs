@@ -0,0 +1,962 @@
+//! Reports `match` expressions missing cases and arms that can never be reached.
+//!
+//! This is the usefulness algorithm from "Warnings for pattern matching" (Maranget, 2007):
+//! an arm is unreachable when its pattern isn't useful after the arms above it, and a
+//! match is exhaustive when a wildcard isn't useful after all of its arms. As there is no
+//! type checking yet, the type of each column is inferred from the constructors of its
+//! patterns. The integers a column can hold are the ones of the integer type the scrutinee
+//! is annotated or suffixed with, and of `i64`, the type of integers at runtime, otherwise.
+
+use std::{collections::HashMap, rc::Rc};
+
+use modules::{Def, ModuleTree, VariantFields};
+use parser::{
+    ast::{
+        AstNode, Expr, LetStmt, LiteralPattern, MatchExpr, Param, Pattern, RangePattern, Type,
+        VariableRef,
+    },
+    literal::IntTy,
+    syntax::{SyntaxKind, SyntaxNode, SyntaxToken},
+};
+use source_map::{Diagnostic, FileId, Span};
+
+use crate::{bindings_before, literal};
+
+pub(crate) fn check(
+    file: FileId,
+    root: &SyntaxNode,
+    module_tree: &ModuleTree,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut checker = Checker {
+        file,
+        module_tree,
+        diagnostics,
+        enums: HashMap::new(),
+        next_opaque: 0,
+    };
+
+    for match_expr in root.descendants().filter_map(MatchExpr::cast) {
+        checker.check_match(&match_expr);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RangeKind {
+    Int,
+    /// Characters, by code point.
+    Char,
+}
+
+/// An enum or struct as seen by the checker, identified by the span of its name.
+#[derive(Debug)]
+struct EnumInfo {
+    span: Span,
+    name: String,
+    is_struct: bool,
+    /// The names of the variants and how many fields they have.
+    variants: Vec<(String, VariantFields)>,
+}
+
+impl PartialEq for EnumInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.span == other.span
+    }
+}
+
+impl Eq for EnumInfo {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Constructor {
+    Bool(bool),
+    /// An inclusive range, a single value when both ends are the same.
+    Range(RangeKind, i128, i128),
+    /// A string literal, compared by its source text.
+    Str(String),
+    Tuple(usize),
+    /// A variant of an enum, by index, whose fields are in declaration order.
+    Variant(Rc<EnumInfo>, usize),
+    /// A value which is only equal to itself, such as a float, as the checker can't
+    /// tell which values it covers.
+    Opaque(usize),
+}
+
+impl Constructor {
+    fn arity(&self) -> usize {
+        match self {
+            Self::Tuple(arity) => *arity,
+            Self::Variant(enum_info, variant) => match &enum_info.variants[*variant].1 {
+                VariantFields::Unit => 0,
+                VariantFields::Tuple(arity) => *arity,
+                VariantFields::Record(names) => names.len(),
+            },
+            _ => 0,
+        }
+    }
+
+    /// Whether every value of `other` is covered by `self`. Ranges have been split so that
+    /// they either contain each other or don't overlap.
+    fn covers(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Range(kind, lo, hi), Self::Range(other_kind, other_lo, other_hi)) => {
+                kind == other_kind && lo <= other_lo && other_hi <= hi
+            }
+            (Self::Tuple(_), Self::Tuple(_)) => true,
+            _ => self == other,
+        }
+    }
+}
+
+/// What the annotations tell of the type of a column.
+#[derive(Debug, Clone)]
+enum ColumnTy {
+    Unknown,
+    Int(IntTy),
+    Tuple(Vec<ColumnTy>),
+}
+
+impl ColumnTy {
+    /// The types of the fields of `constructor` in a column of this type.
+    fn fields(&self, constructor: &Constructor) -> Vec<ColumnTy> {
+        match (self, constructor) {
+            (Self::Tuple(fields), Constructor::Tuple(arity)) if fields.len() == *arity => {
+                fields.clone()
+            }
+            _ => vec![Self::Unknown; constructor.arity()],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Pat {
+    Wild,
+    Constructor(Constructor, Vec<Pat>),
+    /// A tuple with `..` between the given fields, whose arity depends on the other rows.
+    TupleRest(Vec<Pat>, Vec<Pat>),
+    Or(Vec<Pat>),
+}
+
+impl Pat {
+    fn constructor(&self) -> Option<&Constructor> {
+        match self {
+            Self::Constructor(constructor, _) => Some(constructor),
+            _ => None,
+        }
+    }
+
+    /// The fields of this pattern when matched against `constructor`,
+    /// or `None` if it doesn't match.
+    fn specialize(&self, constructor: &Constructor) -> Option<Vec<Pat>> {
+        match self {
+            Self::Wild => Some(vec![Self::Wild; constructor.arity()]),
+            Self::Constructor(own, fields) => own.covers(constructor).then(|| fields.clone()),
+            Self::TupleRest(before, after) => {
+                let Constructor::Tuple(arity) = constructor else {
+                    return None;
+                };
+                let wildcards = arity.saturating_sub(before.len() + after.len());
+                let mut fields = before.clone();
+                fields.extend(std::iter::repeat_n(Self::Wild, wildcards));
+                fields.extend(after.iter().cloned());
+                Some(fields)
+            }
+            Self::Or(_) => unreachable!("or-patterns are expanded before specializing"),
+        }
+    }
+}
+
+impl std::fmt::Display for Pat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self::Constructor(constructor, fields) = self else {
+            return f.write_str("_");
+        };
+
+        match constructor {
+            Constructor::Bool(b) => write!(f, "{b}"),
+            Constructor::Range(kind, lo, hi) => {
+                write_bound(f, *kind, *lo)?;
+                if lo != hi {
+                    f.write_str("..=")?;
+                    write_bound(f, *kind, *hi)?;
+                }
+                Ok(())
+            }
+            Constructor::Str(text) => f.write_str(text),
+            Constructor::Tuple(_) => {
+                f.write_str("(")?;
+                for (index, field) in fields.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{field}")?;
+                }
+                f.write_str(")")
+            }
+            Constructor::Variant(enum_info, variant) => {
+                let (name, variant_fields) = &enum_info.variants[*variant];
+                if enum_info.is_struct {
+                    f.write_str(name)?;
+                } else {
+                    write!(f, "{}::{name}", enum_info.name)?;
+                }
+                match variant_fields {
+                    VariantFields::Unit => Ok(()),
+                    VariantFields::Tuple(_) => {
+                        f.write_str("(")?;
+                        for (index, field) in fields.iter().enumerate() {
+                            if index > 0 {
+                                f.write_str(", ")?;
+                            }
+                            write!(f, "{field}")?;
+                        }
+                        f.write_str(")")
+                    }
+                    VariantFields::Record(names) => {
+                        f.write_str(" {")?;
+                        for (index, (name, field)) in names.iter().zip(fields).enumerate() {
+                            let separator = if index > 0 { "," } else { "" };
+                            write!(f, "{separator} {name}: {field}")?;
+                        }
+                        f.write_str(" }")
+                    }
+                }
+            }
+            Constructor::Opaque(_) => f.write_str("_"),
+        }
+    }
+}
+
+fn write_bound(f: &mut std::fmt::Formatter<'_>, kind: RangeKind, value: i128) -> std::fmt::Result {
+    match kind {
+        RangeKind::Int if value == i64::MIN.into() => f.write_str("i64::MIN"),
+        RangeKind::Int if value == i64::MAX.into() => f.write_str("i64::MAX"),
+        RangeKind::Int => write!(f, "{value}"),
+        RangeKind::Char => {
+            let c = u32::try_from(value).ok().and_then(char::from_u32);
+            write!(f, "'{}'", c.unwrap_or_default().escape_debug())
+        }
+    }
+}
+
+/// The code points of valid characters, which exclude the surrogates.
+const CHAR_RANGES: [(i128, i128); 2] = [(0, 0xD7FF), (0xE000, 0x10FFFF)];
+
+/// Every constructor of the type `ty` of a column, or `None` if there are too many to
+/// enumerate, e.g. for strings. Ranges are split at the bounds of the `used` ones.
+fn all_constructors(used: &[&Constructor], ty: &ColumnTy) -> Option<Vec<Constructor>> {
+    let first = used
+        .iter()
+        .find(|constructor| !matches!(constructor, Constructor::Opaque(_)))?;
+
+    match first {
+        Constructor::Bool(_) => Some(vec![Constructor::Bool(false), Constructor::Bool(true)]),
+        Constructor::Range(RangeKind::Int, ..) => {
+            let (lo, hi) = match *ty {
+                ColumnTy::Int(ty) => (ty.min(), ty.max()),
+                _ => (i64::MIN.into(), i64::MAX.into()),
+            };
+            Some(split_range(RangeKind::Int, lo, hi, used))
+        }
+        Constructor::Range(RangeKind::Char, ..) => Some(
+            CHAR_RANGES
+                .iter()
+                .flat_map(|&(lo, hi)| split_range(RangeKind::Char, lo, hi, used))
+                .collect(),
+        ),
+        Constructor::Tuple(arity) => Some(vec![Constructor::Tuple(*arity)]),
+        Constructor::Variant(enum_info, _) => Some(
+            (0..enum_info.variants.len())
+                .map(|variant| Constructor::Variant(enum_info.clone(), variant))
+                .collect(),
+        ),
+        Constructor::Str(_) | Constructor::Opaque(_) => None,
+    }
+}
+
+/// Splits `lo..=hi` so that each part is either inside or outside of every used range.
+fn split_range(kind: RangeKind, lo: i128, hi: i128, used: &[&Constructor]) -> Vec<Constructor> {
+    let mut bounds = vec![lo, hi + 1];
+    for constructor in used {
+        if let Constructor::Range(used_kind, used_lo, used_hi) = constructor {
+            if *used_kind == kind {
+                bounds.extend(
+                    [*used_lo, used_hi + 1]
+                        .into_iter()
+                        .filter(|&bound| lo < bound && bound <= hi),
+                );
+            }
+        }
+    }
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    bounds
+        .windows(2)
+        .map(|window| Constructor::Range(kind, window[0], window[1] - 1))
+        .collect()
+}
+
+/// The constructors `constructor` has to be split into to be compared with the `used` ones.
+fn split(constructor: &Constructor, used: &[&Constructor]) -> Vec<Constructor> {
+    match constructor {
+        Constructor::Range(kind, lo, hi) => split_range(*kind, *lo, *hi, used),
+        _ => vec![constructor.clone()],
+    }
+}
+
+/// Replaces the rows starting with an or-pattern by one row per alternative.
+fn expand_or_rows(matrix: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    let mut expanded = Vec::new();
+    for row in matrix {
+        match row.first() {
+            Some(Pat::Or(alternatives)) => {
+                let alternatives = alternatives
+                    .iter()
+                    .map(|alternative| {
+                        let mut row = row.clone();
+                        row[0] = alternative.clone();
+                        row
+                    })
+                    .collect::<Vec<_>>();
+                expanded.extend(expand_or_rows(&alternatives));
+            }
+            _ => expanded.push(row.clone()),
+        }
+    }
+    expanded
+}
+
+/// Returns the patterns of a value matched by `row` but by no row of the matrix,
+/// or `None` if the row is useless. The columns have the `types`.
+fn useful(matrix: &[Vec<Pat>], row: &[Pat], types: &[ColumnTy]) -> Option<Vec<Pat>> {
+    let Some((head, rest)) = row.split_first() else {
+        return matrix.is_empty().then(Vec::new);
+    };
+    let (ty, rest_types) = types.split_first()?;
+
+    if let Pat::Or(alternatives) = head {
+        return alternatives.iter().find_map(|alternative| {
+            let mut row = vec![alternative.clone()];
+            row.extend_from_slice(rest);
+            useful(matrix, &row, types)
+        });
+    }
+
+    let matrix = expand_or_rows(matrix);
+    let used = matrix
+        .iter()
+        .filter_map(|row| row[0].constructor())
+        .chain(head.constructor())
+        .collect::<Vec<_>>();
+
+    let constructors = match head {
+        Pat::Constructor(constructor, _) => split(constructor, &used),
+        Pat::TupleRest(before, after) => {
+            vec![Constructor::Tuple(tuple_arity(
+                &matrix,
+                before.len() + after.len(),
+            ))]
+        }
+        _ => match all_constructors(&used, ty) {
+            Some(constructors) => constructors,
+            None if matrix
+                .iter()
+                .any(|row| matches!(row[0], Pat::TupleRest(..))) =>
+            {
+                vec![Constructor::Tuple(tuple_arity(&matrix, 0))]
+            }
+            None => {
+                // the type has too many values, so only the rows matching anything apply
+                let default = matrix
+                    .iter()
+                    .filter(|row| matches!(row[0], Pat::Wild))
+                    .map(|row| row[1..].to_vec())
+                    .collect::<Vec<_>>();
+                let mut witness = useful(&default, rest, rest_types)?;
+                witness.insert(0, Pat::Wild);
+                return Some(witness);
+            }
+        },
+    };
+
+    constructors.into_iter().find_map(|constructor| {
+        let specialized = matrix
+            .iter()
+            .filter_map(|row| {
+                let mut fields = row[0].specialize(&constructor)?;
+                fields.extend_from_slice(&row[1..]);
+                Some(fields)
+            })
+            .collect::<Vec<_>>();
+        let mut fields = head.specialize(&constructor)?;
+        fields.extend_from_slice(rest);
+        let mut field_types = ty.fields(&constructor);
+        field_types.extend_from_slice(rest_types);
+
+        let mut witness = useful(&specialized, &fields, &field_types)?;
+        let arity = constructor.arity();
+        let rest = witness.split_off(arity);
+        let mut result = vec![Pat::Constructor(constructor, witness)];
+        result.extend(rest);
+        Some(result)
+    })
+}
+
+/// The arity of the tuples in the first column, at least `min` when it only has `..` patterns.
+fn tuple_arity(matrix: &[Vec<Pat>], min: usize) -> usize {
+    let mut arity = min;
+    for row in matrix {
+        match &row[0] {
+            Pat::Constructor(Constructor::Tuple(n), _) => return *n,
+            Pat::TupleRest(before, after) => arity = arity.max(before.len() + after.len()),
+            _ => {}
+        }
+    }
+    arity
+}
+
+/// How many missing values a match is searched for, of which the first
+/// [`LISTED_WITNESSES`] are listed.
+const MAX_WITNESSES: usize = 64;
+const LISTED_WITNESSES: usize = 3;
+
+/// Lists patterns the way rustc does: `` `a` ``, `` `a` and `b` `` or
+/// `` `a`, `b`, `c` and 2 more ``.
+fn list(patterns: &[Pat]) -> String {
+    let quoted = |patterns: &[Pat]| {
+        patterns
+            .iter()
+            .map(|pattern| format!("`{pattern}`"))
+            .collect::<Vec<_>>()
+    };
+
+    match patterns.len() {
+        0..=1 => quoted(patterns).join(""),
+        n if n <= LISTED_WITNESSES => {
+            let quoted = quoted(patterns);
+            format!("{} and {}", quoted[..n - 1].join(", "), quoted[n - 1])
+        }
+        n => format!(
+            "{} and {} more",
+            quoted(&patterns[..LISTED_WITNESSES]).join(", "),
+            if n == MAX_WITNESSES {
+                "many".to_string()
+            } else {
+                (n - LISTED_WITNESSES).to_string()
+            }
+        ),
+    }
+}
+
+struct Checker<'a> {
+    file: FileId,
+    module_tree: &'a ModuleTree,
+    diagnostics: &'a mut Vec<Diagnostic>,
+    enums: HashMap<Span, Rc<EnumInfo>>,
+    next_opaque: usize,
+}
+
+impl Checker<'_> {
+    fn span(&self, node: &SyntaxNode) -> Span {
+        Span::new(self.file, node.text_range())
+    }
+
+    fn check_match(&mut self, match_expr: &MatchExpr) {
+        let mut matrix = Vec::new();
+        let ty = match_expr
+            .scrutinee()
+            .map_or(ColumnTy::Unknown, |scrutinee| scrutinee_ty(&scrutinee));
+        let types = std::slice::from_ref(&ty);
+        let reachable = |rows: &[Vec<Pat>], pattern: &Pat| {
+            useful(rows, std::slice::from_ref(pattern), types).is_some()
+        };
+
+        for arm in match_expr
+            .arm_list()
+            .into_iter()
+            .flat_map(|list| list.arms())
+        {
+            let Some(pattern) = arm.pattern() else {
+                return;
+            };
+            let lowered = self.lower(&pattern);
+
+            match (&pattern, &lowered) {
+                (Pattern::Or(or), Pat::Or(alternatives)) => {
+                    let mut rows = matrix.clone();
+                    let mut unreachable = Vec::new();
+                    for (syntax, alternative) in or.patterns().zip(alternatives) {
+                        if !reachable(&rows, alternative) {
+                            unreachable.push(syntax);
+                        }
+                        rows.push(vec![alternative.clone()]);
+                    }
+
+                    if unreachable.len() == alternatives.len() {
+                        self.warn_unreachable(pattern.syntax());
+                    } else {
+                        for syntax in unreachable {
+                            self.warn_unreachable(syntax.syntax());
+                        }
+                    }
+                }
+                _ => {
+                    if !reachable(&matrix, &lowered) {
+                        self.warn_unreachable(pattern.syntax());
+                    }
+                }
+            }
+
+            // a guard can fail, so the arm doesn't rule anything out
+            if arm.guard().is_none() {
+                matrix.push(vec![lowered]);
+            }
+        }
+
+        // each value found missing is ruled out in turn to find the next one
+        let mut missing = Vec::new();
+        while missing.len() < MAX_WITNESSES {
+            let Some(mut witness) = useful(&matrix, &[Pat::Wild], types) else {
+                break;
+            };
+            matrix.push(witness.clone());
+            missing.push(witness.remove(0));
+        }
+
+        if !missing.is_empty() {
+            let node = match_expr
+                .scrutinee()
+                .map_or_else(|| match_expr.syntax().clone(), |expr| expr.syntax().clone());
+            let message = format!("non-exhaustive patterns: {} not covered", list(&missing));
+            self.diagnostics
+                .push(Diagnostic::error(self.span(&node), message));
+        }
+    }
+
+    fn warn_unreachable(&mut self, node: &SyntaxNode) {
+        let span = self.span(node);
+        self.diagnostics
+            .push(Diagnostic::warning(span, "unreachable pattern"));
+    }
+
+    fn opaque(&mut self) -> Pat {
+        self.next_opaque += 1;
+        Pat::Constructor(Constructor::Opaque(self.next_opaque), Vec::new())
+    }
+
+    /// The variant named by the path of a pattern, the only one of a struct.
+    fn variant(&mut self, node: &SyntaxNode, segments: &[SyntaxToken]) -> Option<Constructor> {
+        let module = self.module_tree.module_at(self.span(node));
+        let path = segments.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+        let (span, variant) = match self.module_tree.resolve_path(module, &path) {
+            Ok(Def::Variant(span, variant)) => (span, variant),
+            Ok(Def::Struct(span)) => (span, 0),
+            _ => return None,
+        };
+
+        let enum_info = self.enums.entry(span).or_insert_with(|| {
+            let data = self.module_tree.enum_data(span);
+            Rc::new(EnumInfo {
+                span,
+                name: data.name.clone(),
+                is_struct: data.is_struct,
+                variants: data
+                    .variants
+                    .iter()
+                    .map(|variant| (variant.name.clone(), variant.fields.clone()))
+                    .collect(),
+            })
+        });
+        Some(Constructor::Variant(enum_info.clone(), variant))
+    }
+
+    fn lower(&mut self, pattern: &Pattern) -> Pat {
+        match pattern {
+            Pattern::Ident(ident) => {
+                // a name referring to a unit variant matches it instead of binding
+                let Some(name) = ident.name() else {
+                    return Pat::Wild;
+                };
+                match self.variant(pattern.syntax(), &[name]) {
+                    Some(constructor) if constructor.arity() == 0 => {
+                        Pat::Constructor(constructor, Vec::new())
+                    }
+                    _ => Pat::Wild,
+                }
+            }
+            Pattern::Wildcard(_) | Pattern::Rest(_) => Pat::Wild,
+            Pattern::Literal(literal) => match literal_value(literal) {
+                Some(constructor) => Pat::Constructor(constructor, Vec::new()),
+                None => self.opaque(),
+            },
+            Pattern::Range(range) => match self.range(range) {
+                Some(constructor) => Pat::Constructor(constructor, Vec::new()),
+                None => self.opaque(),
+            },
+            Pattern::Or(or) => Pat::Or(or.patterns().map(|pattern| self.lower(&pattern)).collect()),
+            Pattern::Tuple(tuple) => {
+                let patterns = tuple.patterns().collect::<Vec<_>>();
+                match patterns
+                    .iter()
+                    .position(|pattern| matches!(pattern, Pattern::Rest(_)))
+                {
+                    Some(rest) => Pat::TupleRest(
+                        patterns[..rest]
+                            .iter()
+                            .map(|pattern| self.lower(pattern))
+                            .collect(),
+                        patterns[rest + 1..]
+                            .iter()
+                            .map(|pattern| self.lower(pattern))
+                            .collect(),
+                    ),
+                    None => Pat::Constructor(
+                        Constructor::Tuple(patterns.len()),
+                        patterns.iter().map(|pattern| self.lower(pattern)).collect(),
+                    ),
+                }
+            }
+            // patterns not matching the shape of their variant are reported at runtime
+            Pattern::Path(path) => {
+                let segments = path.segments().collect::<Vec<_>>();
+                match self.variant(pattern.syntax(), &segments) {
+                    Some(constructor) if constructor.arity() == 0 => {
+                        Pat::Constructor(constructor, Vec::new())
+                    }
+                    _ => self.opaque(),
+                }
+            }
+            Pattern::TupleStruct(tuple) => {
+                let segments = tuple.segments().collect::<Vec<_>>();
+                let constructor = self.variant(pattern.syntax(), &segments);
+                let Some(constructor @ Constructor::Variant(..)) = constructor else {
+                    return self.opaque();
+                };
+                let patterns = tuple
+                    .pattern_list()
+                    .into_iter()
+                    .flat_map(|list| list.patterns())
+                    .collect::<Vec<_>>();
+                let arity = constructor.arity();
+
+                let fields = match patterns
+                    .iter()
+                    .position(|pattern| matches!(pattern, Pattern::Rest(_)))
+                {
+                    Some(rest) if patterns.len() - 1 <= arity => {
+                        let mut fields = patterns[..rest]
+                            .iter()
+                            .map(|pattern| self.lower(pattern))
+                            .collect::<Vec<_>>();
+                        fields.extend(std::iter::repeat_n(Pat::Wild, arity + 1 - patterns.len()));
+                        fields.extend(
+                            patterns[rest + 1..]
+                                .iter()
+                                .map(|pattern| self.lower(pattern)),
+                        );
+                        fields
+                    }
+                    None if patterns.len() == arity => {
+                        patterns.iter().map(|pattern| self.lower(pattern)).collect()
+                    }
+                    _ => return self.opaque(),
+                };
+                Pat::Constructor(constructor, fields)
+            }
+            Pattern::Struct(record) => {
+                let segments = record.segments().collect::<Vec<_>>();
+                let constructor = self.variant(pattern.syntax(), &segments);
+                let Some(Constructor::Variant(enum_info, variant)) = constructor else {
+                    return self.opaque();
+                };
+                let VariantFields::Record(names) = &enum_info.variants[variant].1 else {
+                    return self.opaque();
+                };
+
+                // fields which aren't mentioned match anything
+                let mut fields = vec![Pat::Wild; names.len()];
+                for field in record.field_list().iter().flat_map(|list| list.fields()) {
+                    let Some(name) = field.name() else {
+                        continue;
+                    };
+                    let Some(index) = names.iter().position(|other| other == name.text()) else {
+                        return self.opaque();
+                    };
+                    if let Some(pattern) = field.pattern() {
+                        fields[index] = self.lower(&pattern);
+                    }
+                }
+                Pat::Constructor(Constructor::Variant(enum_info.clone(), variant), fields)
+            }
+        }
+    }
+
+    /// Lowers a range pattern, reporting empty ones.
+    fn range(&mut self, range: &RangePattern) -> Option<Constructor> {
+        let start = literal_value(&range.start()?)?;
+        let end = literal_value(&range.end()?)?;
+        let (Constructor::Range(kind, lo, _), Constructor::Range(end_kind, _, hi)) = (start, end)
+        else {
+            return None;
+        };
+        if kind != end_kind {
+            return None;
+        }
+
+        let hi = if range.is_inclusive() { hi } else { hi - 1 };
+        if lo > hi {
+            let message = if range.is_inclusive() {
+                "lower range bound must be less than or equal to upper"
+            } else {
+                "lower range bound must be less than upper"
+            };
+            self.diagnostics
+                .push(Diagnostic::error(self.span(range.syntax()), message));
+            return None;
+        }
+
+        Some(Constructor::Range(kind, lo, hi))
+    }
+}
+
+/// The type of the scrutinee of a match as far as annotations and suffixes tell.
+fn scrutinee_ty(expr: &Expr) -> ColumnTy {
+    match expr {
+        Expr::VariableRef(variable) => variable_annotation(variable)
+            .map_or(ColumnTy::Unknown, |annotation| annotated_ty(&annotation)),
+        Expr::Literal(literal) => literal
+            .token()
+            .and_then(|token| literal::int_suffix(token.text()))
+            .map_or(ColumnTy::Unknown, ColumnTy::Int),
+        Expr::Paren(paren) => paren
+            .expr()
+            .map_or(ColumnTy::Unknown, |expr| scrutinee_ty(&expr)),
+        Expr::Tuple(tuple) => {
+            ColumnTy::Tuple(tuple.elements().map(|expr| scrutinee_ty(&expr)).collect())
+        }
+        _ => ColumnTy::Unknown,
+    }
+}
+
+/// The annotation of the binding `variable` refers to, if it's a single name.
+fn variable_annotation(variable: &VariableRef) -> Option<Type> {
+    let [name] = variable.segments().collect::<Vec<_>>().try_into().ok()?;
+    // the last binding of the name is the one it refers to, as far as scopes go
+    let (_, binding) = bindings_before(variable.syntax())
+        .filter(|(binding, _)| binding.text() == name.text())
+        .last()?;
+    match LetStmt::cast(binding.clone()) {
+        Some(stmt) => stmt.ty(),
+        None => Param::cast(binding)?.ty(),
+    }
+}
+
+/// The type of a column whose values have the type of `annotation`.
+fn annotated_ty(annotation: &Type) -> ColumnTy {
+    match annotation {
+        Type::Path(path) => match path.segments().collect::<Vec<_>>().as_slice() {
+            [name] => IntTy::from_name(name.text()).map_or(ColumnTy::Unknown, ColumnTy::Int),
+            _ => ColumnTy::Unknown,
+        },
+        Type::Tuple(tuple) => {
+            let mut fields = tuple
+                .fields()
+                .map(|ty| annotated_ty(&ty))
+                .collect::<Vec<_>>();
+            match fields.len() {
+                1 if tuple.is_paren() => fields.remove(0),
+                _ => ColumnTy::Tuple(fields),
+            }
+        }
+        Type::Ref(reference) => reference
+            .ty()
+            .map_or(ColumnTy::Unknown, |ty| annotated_ty(&ty)),
+        _ => ColumnTy::Unknown,
+    }
+}
+
+/// The constructor of a literal, `None` for floats and invalid literals.
+fn literal_value(literal: &LiteralPattern) -> Option<Constructor> {
+    let token = literal.token()?;
+    let constructor = match token.kind() {
+        SyntaxKind::Number => {
+            let value = i128::from(literal::int(token.text())?);
+            let value = if literal.is_negative() { -value } else { value };
+            Constructor::Range(RangeKind::Int, value, value)
+        }
+        SyntaxKind::Character => {
+            let value = u32::from(literal::char(token.text())?).into();
+            Constructor::Range(RangeKind::Char, value, value)
+        }
+        SyntaxKind::Bool => Constructor::Bool(token.text() == "true"),
+        SyntaxKind::String => Constructor::Str(token.text().to_string()),
+        _ => return None,
+    };
+
+    Some(constructor)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check_source;
+
+    #[test]
+    fn exhaustive_matches() {
+        let source = "
+            fn f(b, n, c, s, t) {
+                match b { true => 0, false => 1 };
+                match n { -9223372036854775808..=-1 => 0, 0 => 1, 1..=9223372036854775807 => 2 };
+                match n { -5..=5 => 0, _ => 1 };
+                match c { '\\0'..='a' | 'b'..='\\u{10FFFF}' => 0 };
+                match s { \"a\" => 0, other => 1 };
+                match t { (true, _) => 0, (false, 0) => 1, (_, ..) => 2 };
+            }
+        ";
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn missing_patterns() {
+        let source = "
+            fn f(b, n: i64, c, s, t, x) {
+                match b { true => 0 };
+                match n { 0..=9 => 0, 11..=9223372036854775807 => 1, -1 => 2 };
+                match n { 0..=9 => 0 };
+                match c { 'a' => 0 };
+                match s { \"a\" => 0 };
+                match t { (true, _) => 0, (_, true) => 1 };
+                match x { n if n > 0 => 0 };
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "b: non-exhaustive patterns: `false` not covered",
+                "n: non-exhaustive patterns: `i64::MIN..=-2` and `10` not covered",
+                "n: non-exhaustive patterns: `i64::MIN..=-1` and `10..=i64::MAX` not covered",
+                "c: non-exhaustive patterns: `'\\0'..='`'`, `'b'..='\\u{d7ff}'` and `'\\u{e000}'..='\\u{10ffff}'` not covered",
+                "s: non-exhaustive patterns: `_` not covered",
+                "t: non-exhaustive patterns: `(false, false)` not covered",
+                "x: non-exhaustive patterns: `_` not covered",
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_widths() {
+        let source = "
+            fn f(n, m: u8, t: (i8, bool), g) {
+                match m { 0..=127 => 0, 128..=255 => 1 };
+                match 7u16 { 0..=9 => 0 };
+                match t { (-128..=0, _) => 0, (1..=127, true) => 1 };
+                match m { 0..=255 => 0, _ => 1 };
+                match n { 0..=9 => 0, _ => 1 };
+                let x = g();
+                match x { 0 => 1 };
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "7u16: non-exhaustive patterns: `10..=65535` not covered",
+                "t: non-exhaustive patterns: `(1..=127, false)` not covered",
+                "_: unreachable pattern",
+                "x: non-exhaustive patterns: `i64::MIN..=-1` and `1..=i64::MAX` not covered",
+            ]
+        );
+    }
+
+    #[test]
+    fn unreachable_arms() {
+        let source = "
+            fn f(b, n) {
+                match n { 0..=9 => 0, 5 => 1, 3 | 10 => 2, _ => 3, 7 => 4 };
+                match b { true => 0, false => 1, _ => 2 };
+                match n { x if x > 0 => 0, 1 => 1, _ => 2 };
+                match n { 1 | 1 => 0, _ => 1 };
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "5: unreachable pattern",
+                "3: unreachable pattern",
+                "7: unreachable pattern",
+                "_: unreachable pattern",
+                "1: unreachable pattern",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_range() {
+        assert_eq!(
+            check_source("fn f(n) { match n { 5..=1 => 0, 2..2 => 1, _ => 2 } }"),
+            [
+                "5..=1: lower range bound must be less than or equal to upper",
+                "2..2: lower range bound must be less than upper",
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_matches() {
+        let source = "
+            enum Shape { Circle(bool), Rect { w: i64, h: bool }, Empty }
+            use Shape::Empty;
+            fn f(s) {
+                match s { Shape::Circle(_) => 0, Shape::Rect { .. } => 1, Empty => 2 };
+                match s { Shape::Circle(true) => 0, Shape::Rect { h: true, .. } => 1 };
+                match s { Shape::Rect { w: 0, h } => 0, Shape::Circle(false) => 1 };
+                match s { _ => 0, Shape::Empty => 1 };
+                match s { Shape::Circle(_) | Empty => 0 };
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "s: non-exhaustive patterns: `Shape::Circle(false)`, `Shape::Rect { w: _, h: false }` and `Shape::Empty` not covered",
+                "s: non-exhaustive patterns: `Shape::Circle(true)`, `Shape::Rect { w: i64::MIN..=-1, h: _ }`, `Shape::Rect { w: 1..=i64::MAX, h: _ }` and 1 more not covered",
+                "Shape::Empty: unreachable pattern",
+                "s: non-exhaustive patterns: `Shape::Rect { w: _, h: _ }` not covered",
+            ]
+        );
+    }
+
+    #[test]
+    fn every_missing_variant() {
+        let source = "
+            enum E { A, B, C }
+            enum F { A, B, C, D, G, H }
+            fn f(e, f) {
+                match e { E::A => 1 };
+                match f { F::A => 1 };
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "e: non-exhaustive patterns: `E::B` and `E::C` not covered",
+                "f: non-exhaustive patterns: `F::B`, `F::C`, `F::D` and 2 more not covered",
+            ]
+        );
+    }
+
+    #[test]
+    fn struct_matches() {
+        let source = "
+            struct P { x: bool, y: bool }
+            fn f(p) {
+                match p { P { x: true, .. } => 0, P { y: true, .. } => 1 };
+                match p { P { .. } => 0, P { x: true, y } => 1 };
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "p: non-exhaustive patterns: `P { x: false, y: false }` not covered",
+                "P { x: true, y }: unreachable pattern",
+            ]
+        );
+    }
+}
@@ -0,0 +1,553 @@
+//! Impls: finding impls of the same trait for overlapping types, which make the calls of
+//! the trait's methods on values of those types ambiguous, and finding the methods of
+//! values and the traits types implement, as far as their types are told.
+
+use std::collections::HashMap;
+
+use db::Database;
+use modules::{Def, ImplData, ModuleId, ModuleTree};
+use parser::{
+    ast::{AstNode, FunctionExpr, ImplStmt, PathType, Type},
+    literal::IntTy,
+};
+use source_map::{Diagnostic, Span, TextRange};
+
+use crate::{names, HostNames, Ty};
+
+/// The self type of an impl, as far as it tells impls apart.
+#[derive(Debug)]
+enum SelfTy {
+    /// A generic parameter of the impl by its index, which stands for any type. `None` for
+    /// the types left out of the syntax.
+    Param(Option<usize>),
+    /// A builtin or host type by name, along with its generic arguments. Each integer type
+    /// is a type of its own, while the float types are a single one like at runtime.
+    Named(String, Vec<SelfTy>),
+    Enum(Span, Vec<SelfTy>),
+    Array(Box<SelfTy>),
+    /// Tuples, with `()` being the one without elements.
+    Tuple(Vec<SelfTy>),
+}
+
+pub(crate) fn check(
+    db: &Database,
+    module_tree: &ModuleTree,
+    host: &HostNames,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    // the traits of the impls checked so far, by the span of their name
+    let mut impls = Vec::<(Span, SelfTy)>::new();
+    for data in module_tree.impls() {
+        // the types nested in the self type are resolved along with the other types
+        if let Some(Type::Path(path)) =
+            crate::item::<ImplStmt>(db, data.span).and_then(|stmt| stmt.self_type())
+        {
+            if let Some(message) = names::type_error(data.span.file, module_tree, host, &path) {
+                let span = Span::new(data.span.file, path.syntax().text_range());
+                diagnostics.push(Diagnostic::error(span, message));
+                continue;
+            }
+        }
+
+        let Some(trait_def) = data.trait_def else {
+            continue;
+        };
+        let Some((self_type, self_ty)) = self_type(db, module_tree, data) else {
+            continue;
+        };
+
+        // the impl that comes second is the one reported, from its keyword to its type
+        if impls
+            .iter()
+            .any(|(other, other_ty)| *other == trait_def && overlaps(other_ty, &self_ty))
+        {
+            let end = self_type.syntax().text_range().end();
+            let range = TextRange::new(data.span.range.start(), end);
+            let message = format!(
+                "conflicting implementations of trait `{}` for type `{}`",
+                module_tree.trait_data(trait_def).name,
+                self_type.syntax()
+            );
+            diagnostics.push(Diagnostic::error(Span::new(data.span.file, range), message));
+        }
+        impls.push((trait_def, self_ty));
+    }
+}
+
+/// Whether an impl of the enum or struct whose name is at `def` has a function `name`, which
+/// `Enum::name` then names.
+pub(crate) fn has_associated_function(
+    db: &Database,
+    module_tree: &ModuleTree,
+    def: Span,
+    name: &str,
+) -> bool {
+    module_tree.impls().iter().any(|data| {
+        data.functions.iter().any(|(function, _)| function == name)
+            && matches!(
+                self_type(db, module_tree, data),
+                Some((_, SelfTy::Enum(span, _))) if span == def
+            )
+    })
+}
+
+/// The method `name` of the values of type `ty`, by the span of its name, picked like the
+/// interpreter picks it. `Ok(None)` if which impls apply to the values can't be told before
+/// running the program, and the error of the call if none or several of them apply.
+pub(crate) fn method(
+    db: &Database,
+    module_tree: &ModuleTree,
+    ty: &Ty,
+    name: &str,
+) -> Result<Option<Span>, String> {
+    // the values of host types have the methods registered for them
+    if matches!(ty, Ty::Unknown | Ty::Var(_)) {
+        return Ok(None);
+    }
+    let mut candidates = Vec::new();
+    for data in module_tree.impls() {
+        let Some(function) = impl_function(module_tree, data, name) else {
+            continue;
+        };
+        let Some((_, self_ty)) = self_type(db, module_tree, data) else {
+            continue;
+        };
+        match applies(db, module_tree, data, &self_ty, ty, &mut Vec::new()) {
+            Some(true) => candidates.push((data, self_ty, function)),
+            Some(false) => {}
+            None => return Ok(None),
+        }
+    }
+
+    if candidates.iter().any(|(data, ..)| data.trait_def.is_none()) {
+        candidates.retain(|(data, ..)| data.trait_def.is_none());
+    }
+    // an integer without a type is an `i64` when the impls of several types apply to it
+    if *ty == Ty::Int(None) {
+        let is_i64 = |self_ty: &SelfTy| matches!(self_ty, SelfTy::Named(name, _) if name == "i64");
+        if candidates.iter().any(|(_, self_ty, _)| is_i64(self_ty)) {
+            candidates.retain(|(_, self_ty, _)| is_i64(self_ty));
+        }
+    }
+
+    let ty_name = ty.display(module_tree);
+    match candidates.as_slice() {
+        [] => Err(format!("no method named `{name}` found for `{ty_name}`")),
+        [(_, _, function)] => {
+            let is_method = crate::item::<FunctionExpr>(db, *function)
+                .and_then(|function| function.param_list())
+                .and_then(|list| list.params().next())
+                .and_then(|param| param.name())
+                .is_some_and(|param| param.text() == "self");
+            match is_method {
+                true => Ok(Some(*function)),
+                false => Err(format!(
+                    "`{name}` is an associated function of `{ty_name}`, not a method"
+                )),
+            }
+        }
+        // integers without a type may get one before the call, and the impls of a trait
+        // for overlapping types are reported along with the impls
+        _ if *ty == Ty::Int(None) => Ok(None),
+        candidates => {
+            let traits = candidates
+                .iter()
+                .filter_map(|(data, ..)| data.trait_def)
+                .collect::<Vec<_>>();
+            if traits.is_empty() {
+                return Err(format!(
+                    "duplicate definitions with name `{name}` for type `{ty_name}`"
+                ));
+            }
+            if traits.iter().all(|trait_def| *trait_def == traits[0]) {
+                return Ok(None);
+            }
+            let traits = traits
+                .iter()
+                .map(|trait_def| format!("`{}`", module_tree.trait_data(*trait_def).name))
+                .collect::<Vec<_>>();
+            Err(format!(
+                "multiple applicable items in scope: `{name}` is defined by the traits {}",
+                traits.join(", ")
+            ))
+        }
+    }
+}
+
+/// Whether the type implements the trait whose name is at `trait_def`, `None` if it can't
+/// be told before running the program.
+pub(crate) fn implements(
+    db: &Database,
+    module_tree: &ModuleTree,
+    ty: &Ty,
+    trait_def: Span,
+) -> Option<bool> {
+    implements_visiting(db, module_tree, ty, trait_def, &mut Vec::new())
+}
+
+/// [`implements`], with `visiting` holding the types and traits being checked, as an impl
+/// whose bounds need the impl itself doesn't apply.
+fn implements_visiting(
+    db: &Database,
+    module_tree: &ModuleTree,
+    ty: &Ty,
+    trait_def: Span,
+    visiting: &mut Vec<(Ty, Span)>,
+) -> Option<bool> {
+    if matches!(ty, Ty::Unknown | Ty::Var(_)) {
+        return None;
+    }
+    if visiting
+        .iter()
+        .any(|(other, span)| other == ty && *span == trait_def)
+    {
+        return Some(false);
+    }
+
+    visiting.push((ty.clone(), trait_def));
+    let mut implemented = Some(false);
+    for data in module_tree.impls() {
+        if data.trait_def != Some(trait_def) {
+            continue;
+        }
+        let Some((_, self_ty)) = self_type(db, module_tree, data) else {
+            continue;
+        };
+        match applies(db, module_tree, data, &self_ty, ty, visiting) {
+            Some(true) => {
+                implemented = Some(true);
+                break;
+            }
+            Some(false) => {}
+            None => implemented = None,
+        }
+    }
+    visiting.pop();
+    implemented
+}
+
+/// The function `name` of an impl, or the one its trait provides if it leaves it out.
+fn impl_function(module_tree: &ModuleTree, data: &ImplData, name: &str) -> Option<Span> {
+    if let Some(&(_, span)) = data.functions.iter().find(|(function, _)| function == name) {
+        return Some(span);
+    }
+    let functions = &module_tree.trait_data(data.trait_def?).functions;
+    functions
+        .iter()
+        .find(|function| function.has_body && function.name == name)
+        .map(|function| function.span)
+}
+
+/// Whether an impl whose self type is `self_ty` applies to the values of type `ty`, with its
+/// generic parameters satisfying their bounds.
+fn applies(
+    db: &Database,
+    module_tree: &ModuleTree,
+    data: &ImplData,
+    self_ty: &SelfTy,
+    ty: &Ty,
+    visiting: &mut Vec<(Ty, Span)>,
+) -> Option<bool> {
+    let mut bindings = HashMap::new();
+    if !unify(self_ty, ty, &mut bindings)? {
+        return Some(false);
+    }
+
+    let Some(stmt) = crate::item::<ImplStmt>(db, data.span) else {
+        return Some(true);
+    };
+    let params = stmt
+        .generic_param_list()
+        .into_iter()
+        .flat_map(|list| list.params());
+    let mut told = true;
+    for (index, param) in params.enumerate() {
+        for bound in param.bounds() {
+            let names = bound.segments().collect::<Vec<_>>();
+            let names = names.iter().map(|name| name.text()).collect::<Vec<_>>();
+            let Ok(Def::Trait(trait_def)) = module_tree.resolve_path(data.module, &names) else {
+                continue;
+            };
+            let ty = bindings.get(&index).cloned().unwrap_or(Ty::Unknown);
+            match implements_visiting(db, module_tree, &ty, trait_def, visiting) {
+                Some(true) => {}
+                Some(false) => return Some(false),
+                None => told = false,
+            }
+        }
+    }
+    told.then_some(true)
+}
+
+/// Whether values of type `ty` have the self type of an impl, `None` if it can't be told,
+/// binding the impl's generic parameters to the parts of `ty` they stand for.
+fn unify(self_ty: &SelfTy, ty: &Ty, bindings: &mut HashMap<usize, Ty>) -> Option<bool> {
+    let unifies = match (self_ty, ty) {
+        (SelfTy::Param(index), ty) => {
+            if let Some(index) = index {
+                bindings.insert(*index, ty.clone());
+            }
+            true
+        }
+        (_, Ty::Unknown | Ty::Var(_)) => return None,
+        (SelfTy::Named(name, _), Ty::Int(int)) => match int {
+            Some(int) => name == int.name(),
+            None => IntTy::from_name(name).is_some(),
+        },
+        (SelfTy::Named(name, _), Ty::Float) => name == "float",
+        (SelfTy::Named(name, _), Ty::Bool) => name == "bool",
+        (SelfTy::Named(name, _), Ty::Char) => name == "char",
+        (SelfTy::Named(name, _), Ty::String) => name == "string",
+        (SelfTy::Named(name, _), Ty::Function) => name == "fn",
+        (SelfTy::Named(name, _), Ty::Map) => name == "Map",
+        (SelfTy::Tuple(types), Ty::Unit) => types.is_empty(),
+        (SelfTy::Array(element), Ty::Array(ty)) => return unify(element, ty, bindings),
+        (SelfTy::Tuple(types), Ty::Tuple(tys)) if types.len() == tys.len() => {
+            return unify_all(types, tys, bindings)
+        }
+        (SelfTy::Enum(span, args), Ty::Enum(other, tys)) if span == other => {
+            // the generic arguments left out are unknown
+            if tys.is_empty() {
+                let tys = vec![Ty::Unknown; args.len()];
+                return unify_all(args, &tys, bindings);
+            }
+            return unify_all(args, tys, bindings);
+        }
+        _ => false,
+    };
+    Some(unifies)
+}
+
+fn unify_all(types: &[SelfTy], tys: &[Ty], bindings: &mut HashMap<usize, Ty>) -> Option<bool> {
+    let mut unifies = Some(true);
+    for (self_ty, ty) in types.iter().zip(tys) {
+        match unify(self_ty, ty, bindings) {
+            Some(true) => {}
+            Some(false) => return Some(false),
+            None => unifies = None,
+        }
+    }
+    unifies
+}
+
+/// The self type of an impl, as written and lowered in the impl's module.
+fn self_type(db: &Database, module_tree: &ModuleTree, data: &ImplData) -> Option<(Type, SelfTy)> {
+    let stmt = crate::item::<ImplStmt>(db, data.span)?;
+    let self_type = stmt.self_type()?;
+
+    let generics = stmt
+        .generic_param_list()
+        .into_iter()
+        .flat_map(|list| list.params())
+        .filter_map(|param| Some(param.name()?.text().to_string()))
+        .collect::<Vec<_>>();
+    let lowering = Lowering {
+        module_tree,
+        module: data.module,
+        generics: &generics,
+    };
+    let self_ty = lowering.lower(&self_type);
+    Some((self_type, self_ty))
+}
+
+/// Whether a value can have both types, with generic parameters matching any type.
+fn overlaps(a: &SelfTy, b: &SelfTy) -> bool {
+    let all = |a: &[SelfTy], b: &[SelfTy]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| overlaps(a, b))
+    };
+    match (a, b) {
+        (SelfTy::Param(_), _) | (_, SelfTy::Param(_)) => true,
+        (SelfTy::Named(a, a_args), SelfTy::Named(b, b_args)) => a == b && all(a_args, b_args),
+        (SelfTy::Enum(a, a_args), SelfTy::Enum(b, b_args)) => a == b && all(a_args, b_args),
+        (SelfTy::Array(a), SelfTy::Array(b)) => overlaps(a, b),
+        (SelfTy::Tuple(a), SelfTy::Tuple(b)) => all(a, b),
+        _ => false,
+    }
+}
+
+/// Lowers the types written in an impl's header, in the impl's module.
+struct Lowering<'a> {
+    module_tree: &'a ModuleTree,
+    module: ModuleId,
+    generics: &'a [String],
+}
+
+impl Lowering<'_> {
+    fn lower(&self, ty: &Type) -> SelfTy {
+        match ty {
+            Type::Path(path) => self.lower_path(path),
+            Type::Array(array) => SelfTy::Array(Box::new(self.lower_opt(array.element_type()))),
+            Type::Slice(slice) => SelfTy::Array(Box::new(self.lower_opt(slice.element_type()))),
+            Type::Fn(_) => SelfTy::Named("fn".to_string(), Vec::new()),
+            Type::Tuple(tuple) => {
+                let mut fields = tuple.fields().map(|ty| self.lower(&ty)).collect::<Vec<_>>();
+                match fields.len() {
+                    1 if tuple.is_paren() => fields.remove(0),
+                    _ => SelfTy::Tuple(fields),
+                }
+            }
+            // references are the values themselves at runtime
+            Type::Ref(reference) => self.lower_opt(reference.ty()),
+            Type::Optional(optional) => {
+                let ty = self.lower_opt(optional.ty());
+                match self.module_tree.resolve_path(self.module, &["Option"]) {
+                    Ok(Def::Enum(span)) => SelfTy::Enum(span, vec![ty]),
+                    _ => SelfTy::Named("Option".to_string(), vec![ty]),
+                }
+            }
+        }
+    }
+
+    /// Lowers a type left out of the syntax as any type, so it isn't told apart.
+    fn lower_opt(&self, ty: Option<Type>) -> SelfTy {
+        ty.map_or(SelfTy::Param(None), |ty| self.lower(&ty))
+    }
+
+    fn lower_path(&self, path: &PathType) -> SelfTy {
+        let segments = path.segments().collect::<Vec<_>>();
+        let args = path
+            .generic_arg_list()
+            .into_iter()
+            .flat_map(|list| list.types())
+            .map(|ty| self.lower(&ty))
+            .collect();
+        let names = segments
+            .iter()
+            .map(|segment| segment.text())
+            .collect::<Vec<_>>();
+
+        if let [name] = names.as_slice() {
+            if let Some(index) = self.generics.iter().position(|param| param == name) {
+                return SelfTy::Param(Some(index));
+            }
+            if let Some(name) = primitive(name) {
+                return SelfTy::Named(name.to_string(), args);
+            }
+        }
+        match self.module_tree.resolve_path(self.module, &names) {
+            Ok(Def::Enum(span) | Def::Struct(span)) => SelfTy::Enum(span, args),
+            // host types and `Map` aren't items of the program
+            _ => SelfTy::Named(names.join("::"), args),
+        }
+    }
+}
+
+/// The name of the primitive type `name` at runtime, `None` if `name` isn't primitive.
+fn primitive(name: &str) -> Option<&str> {
+    if let Some(ty) = IntTy::from_name(name) {
+        return Some(ty.name());
+    }
+    match name {
+        "f32" | "f64" => Some("float"),
+        "str" | "String" => Some("string"),
+        "bool" | "char" => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check_source;
+
+    #[test]
+    fn distinct_impls() {
+        let source = "
+            trait Show { fn show(self) -> String; }
+            enum Point<T> { P(T) }
+            impl Show for i32 { fn show(self) -> String { \"i32\" } }
+            impl Show for u8 { fn show(self) -> String { \"u8\" } }
+            impl Show for Point<i64> { fn show(self) -> String { \"p\" } }
+            impl Show for Point<bool> { fn show(self) -> String { \"p\" } }
+            impl<T> Show for [T] { fn show(self) -> String { \"a\" } }
+            impl Show for (i32, u8) { fn show(self) -> String { \"t\" } }
+            impl Point<i64> { fn show(self) -> String { \"inherent\" } }
+        ";
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn conflicting_impls() {
+        let source = "
+            trait Show { fn show(self) -> String; }
+            enum Point<T> { P(T) }
+            impl Show for i32 { fn show(self) -> String { \"a\" } }
+            impl Show for i32 { fn show(self) -> String { \"b\" } }
+            impl<T> Show for Point<T> { fn show(self) -> String { \"c\" } }
+            impl Show for Point<u8> { fn show(self) -> String { \"d\" } }
+            impl Show for &str { fn show(self) -> String { \"e\" } }
+            impl Show for String { fn show(self) -> String { \"f\" } }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "impl Show for i32: conflicting implementations of trait `Show` for type `i32`",
+                "impl Show for Point<u8>: conflicting implementations of trait `Show` for type `Point<u8>`",
+                "impl Show for String: conflicting implementations of trait `Show` for type `String`",
+            ]
+        );
+    }
+
+    #[test]
+    fn valid_method_calls() {
+        let source = "
+            trait Show { fn show(self) -> String; fn twice(self) -> String { self.show() } }
+            enum Wrap<T> { W(T) }
+            impl Show for bool { fn show(self) -> String { \"b\" } }
+            impl<T: Show> Show for Wrap<T> { fn show(self) -> String { \"w\" } }
+            impl Show for u8 { fn show(self) -> String { \"u8\" } }
+            impl Show for i32 { fn show(self) -> String { \"i32\" } }
+            impl bool { fn show(self) -> String { \"inherent\" } }
+            fn print_it<T: Show>(x: T) { println(x.show()) }
+            fn main() {
+                Wrap::W(true).twice();
+                true.show();
+                let a: u8 = 1;
+                a.show();
+                print_it(Wrap::W(Wrap::W(false)));
+                print_it(2);
+                let f = |x| x.anything();
+            }
+        ";
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn invalid_method_calls() {
+        let source = "
+            trait Show { fn show(self) -> String; }
+            trait Disp { fn show(self) -> String; }
+            struct P { x: i64 }
+            enum Wrap<T: Show> { W(T) }
+            impl Show for P { fn show(self) -> String { \"s\" } }
+            impl Disp for P { fn show(self) -> String { \"d\" } }
+            impl P { fn new() -> P { P { x: 1 } } fn get(self, a: i64) -> i64 { a } }
+            impl Show for Nope { fn show(self) -> String { \"n\" } }
+            impl Missing {}
+            fn print_it<T: Show>(x: T) { println(x.show()) }
+            fn main() {
+                let p = P { x: 1 };
+                p.nope();
+                p.show();
+                p.new();
+                p.get();
+                print_it(5);
+                Wrap::W(true);
+                1.show();
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "Nope: cannot find type `Nope` in this scope",
+                "Missing: cannot find type `Missing` in this scope",
+                "p.nope(): no method named `nope` found for `P`",
+                "p.show(): multiple applicable items in scope: `show` is defined by the traits `Show`, `Disp`",
+                "p.new(): `new` is an associated function of `P`, not a method",
+                "p.get(): method `get` takes 1 argument but 0 were supplied",
+                "print_it(5): the trait bound `integer: Show` is not satisfied",
+                "Wrap::W(true): the trait bound `bool: Show` is not satisfied",
+                "1.show(): no method named `show` found for `integer`",
+            ]
+        );
+    }
+}
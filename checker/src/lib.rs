@@ -0,0 +1,289 @@
+//! Static checks reporting errors in programs that parse and resolve, before they run.
+
+mod array_len;
+mod attributes;
+mod exhaustiveness;
+mod impls;
+mod literal;
+mod mutability;
+mod names;
+mod prelude;
+mod types;
+
+pub use types::Ty;
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+use attributes::Deprecations;
+use db::Database;
+use modules::ModuleTree;
+use parser::{
+    ast::{AstNode, FieldPattern, FunctionExpr, IdentPattern, LetStmt, Param},
+    syntax::{SyntaxNode, SyntaxToken},
+};
+use source_map::{Diagnostic, FileId, Span};
+
+/// Runs every check on the files of the program.
+pub fn check(db: &Database, module_tree: &ModuleTree) -> Vec<Diagnostic> {
+    check_with_host(db, module_tree, &HostNames::default())
+}
+
+/// The names of the functions and the types an embedding program registered, which the
+/// programs it runs can use besides their items and the prelude.
+#[derive(Debug, Default)]
+pub struct HostNames {
+    pub functions: HashSet<String>,
+    pub types: HashSet<String>,
+}
+
+/// [`check`] for a program which can use the functions and the types of `host`.
+pub fn check_with_host(
+    db: &Database,
+    module_tree: &ModuleTree,
+    host: &HostNames,
+) -> Vec<Diagnostic> {
+    let files = module_tree
+        .modules()
+        .map(|module| module_tree.get(module).file)
+        .collect::<BTreeSet<_>>();
+
+    let roots = files
+        .into_iter()
+        .map(|file| (file, db.parse(file).syntax()))
+        .collect::<Vec<_>>();
+
+    // deprecated items can be used from other files
+    let mut deprecations = Deprecations::new();
+    for (file, root) in &roots {
+        attributes::collect_deprecations(*file, root, &mut deprecations);
+    }
+
+    let mut diagnostics = Vec::new();
+    impls::check(db, module_tree, host, &mut diagnostics);
+    for (file, root) in &roots {
+        check_file(
+            db,
+            *file,
+            root,
+            module_tree,
+            host,
+            &deprecations,
+            &mut diagnostics,
+        );
+    }
+
+    diagnostics
+}
+
+/// A function declared in a module, or in an impl or a trait, of the program whose root
+/// file is `root`, by the span of its name.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FunctionId {
+    pub root: FileId,
+    pub name: Span,
+}
+
+impl fmt::Debug for FunctionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?}", self.name.file, self.name.range)
+    }
+}
+
+/// What's told of the body of a function before running it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Inference {
+    pub diagnostics: Vec<Diagnostic>,
+    /// The generic arguments of the generic functions, enums and structs the body uses, by
+    /// the span of the call, the path or the record using them.
+    pub type_args: HashMap<Span, Vec<Ty>>,
+}
+
+/// Checks the types in the body of a function, memoized in the database until the module
+/// tree or the syntax of the items the function reads change.
+pub fn infer(db: &Database, function: FunctionId) -> Arc<Inference> {
+    db.query::<types::Infer>(&function)
+}
+
+/// The generic arguments of the generic functions, enums and structs used in the program,
+/// by the span of the call, the path or the record using them, whether they're written or
+/// inferred. The uses whose generic arguments can't be inferred, which `check` reports,
+/// are left out, and the parts of types which are only told at runtime are [`Ty::Unknown`].
+pub fn type_args(db: &Database, module_tree: &ModuleTree) -> HashMap<Span, Vec<Ty>> {
+    let files = module_tree
+        .modules()
+        .map(|module| module_tree.get(module).file)
+        .collect::<BTreeSet<_>>();
+
+    let mut type_args = HashMap::new();
+    for file in files {
+        let root = db.parse(file).syntax();
+        // the errors are those `check` reports
+        let mut diagnostics = Vec::new();
+        type_args.extend(types::check(db, file, &root, module_tree, &mut diagnostics));
+    }
+    type_args
+}
+
+fn check_file(
+    db: &Database,
+    file: FileId,
+    root: &SyntaxNode,
+    module_tree: &ModuleTree,
+    host: &HostNames,
+    deprecations: &Deprecations,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    array_len::check(db, file, root, module_tree, diagnostics);
+    attributes::check(file, root, module_tree, deprecations, diagnostics);
+    exhaustiveness::check(file, root, module_tree, diagnostics);
+    mutability::check(file, root, module_tree, diagnostics);
+    names::check(file, root, module_tree, host, diagnostics);
+    prelude::check(file, root, module_tree, diagnostics);
+    types::check(db, file, root, module_tree, diagnostics);
+}
+
+/// Whether a local binding named `name` comes before `node` in its function, in which case
+/// the name refers to the binding rather than to an item. Scopes are ignored, which errs
+/// on the side of not warning.
+pub(crate) fn is_local(node: &SyntaxNode, name: &str) -> bool {
+    bindings_before(node).any(|(binding, _)| binding.text() == name)
+}
+
+/// The names of the local bindings coming before `node` in its function, in order, along
+/// with the `let`, parameter or pattern binding them. The shorthand `field` of a struct
+/// pattern binds the name of the field.
+pub(crate) fn bindings_before(
+    node: &SyntaxNode,
+) -> impl Iterator<Item = (SyntaxToken, SyntaxNode)> {
+    let function = node
+        .ancestors()
+        .find(|ancestor| {
+            FunctionExpr::cast(ancestor.clone()).is_some_and(|function| function.name().is_some())
+        })
+        .or_else(|| node.ancestors().last());
+
+    let start = node.text_range().start();
+    function
+        .into_iter()
+        .flat_map(|function| function.descendants())
+        .take_while(move |descendant| descendant.text_range().start() < start)
+        .filter_map(|descendant| {
+            let name = if let Some(stmt) = LetStmt::cast(descendant.clone()) {
+                stmt.name()
+            } else if let Some(param) = Param::cast(descendant.clone()) {
+                param.name()
+            } else if let Some(field) = FieldPattern::cast(descendant.clone()) {
+                field.pattern().is_none().then(|| field.name())?
+            } else {
+                IdentPattern::cast(descendant.clone())?.name()
+            };
+            Some((name?, descendant))
+        })
+}
+
+/// The declaration of the item whose name is at `span`.
+pub(crate) fn item<N: AstNode>(db: &Database, span: Span) -> Option<N> {
+    db.item_syntax(span)?
+        .covering_element(span.range)
+        .into_token()?
+        .parent()?
+        .ancestors()
+        .find_map(N::cast)
+}
+
+#[cfg(test)]
+fn check_source(source: &str) -> Vec<String> {
+    let mut db = Database::new();
+    let root = db.add_file("/p/main.lamia", source);
+    let (module_tree, diagnostics) = modules::build(&mut db, root);
+    assert_eq!(diagnostics, []);
+
+    check(&db, &module_tree)
+        .into_iter()
+        .map(|diagnostic| {
+            format!(
+                "{}: {}",
+                db.source_map().snippet(diagnostic.span),
+                diagnostic.message
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use db::Database;
+
+    /// The functions inferred since the last call, by the span of their name.
+    fn inferred(db: &Database) -> Vec<String> {
+        db.take_executed()
+            .into_iter()
+            .filter(|query| query.starts_with("infer("))
+            .collect()
+    }
+
+    #[test]
+    fn files_are_parsed_once() {
+        let mut db = Database::new();
+        let root = db.add_file("/p/main.lamia", "mod m { fn f() { let q: u8 = 300; } }");
+        let (module_tree, _) = modules::build(&mut db, root);
+        db.take_executed();
+
+        // the checks share the syntax trees the module tree was built from
+        let diagnostics = super::check(&db, &module_tree);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(!db
+            .take_executed()
+            .iter()
+            .any(|query| query.starts_with("parse(")));
+    }
+
+    #[test]
+    fn functions_are_inferred_once() {
+        let mut db = Database::new();
+        let main = "fn f() -> u8 { 1 }\nfn g() -> u8 { 2 }\nmod m;";
+        let root = db.add_file("/p/main.lamia", main);
+        let m = db.add_file("/p/m.lamia", "fn h() -> u8 { 3 }");
+        let (module_tree, _) = modules::build(&mut db, root);
+        super::check(&db, &module_tree);
+        assert_eq!(
+            inferred(&db),
+            [
+                "infer(FileId(0)@3..4)",
+                "infer(FileId(0)@22..23)",
+                "infer(FileId(1)@3..4)"
+            ]
+        );
+
+        super::check(&db, &module_tree);
+        assert_eq!(inferred(&db), Vec::<String>::new());
+
+        // an edit inside a body only reinfers its function, even when it doesn't parse
+        db.set_file_text(root, main.replace("{ 2 }", "{ 2.}"));
+        let (module_tree, _) = modules::build(&mut db, root);
+        super::check(&db, &module_tree);
+        assert_eq!(inferred(&db), ["infer(FileId(0)@22..23)"]);
+
+        db.set_file_text(root, main.replace("{ 2 }", "{'a'}"));
+        let (module_tree, _) = modules::build(&mut db, root);
+        let diagnostics = super::check(&db, &module_tree);
+        assert_eq!(inferred(&db), ["infer(FileId(0)@22..23)"]);
+        assert_eq!(diagnostics.len(), 1);
+
+        // and so does an edit in another file
+        db.set_file_text(m, "fn h() -> u8 { 4 }");
+        let (module_tree, _) = modules::build(&mut db, root);
+        super::check(&db, &module_tree);
+        assert_eq!(inferred(&db), ["infer(FileId(1)@3..4)"]);
+
+        // while renaming an item changes the module tree, which every function reads
+        db.set_file_text(m, "fn k() -> u8 { 4 }");
+        let (module_tree, _) = modules::build(&mut db, root);
+        super::check(&db, &module_tree);
+        assert_eq!(inferred(&db).len(), 3);
+    }
+}
@@ -0,0 +1,42 @@
+//! Values of the literals the checks can reason about, as decoded by the parser, with
+//! invalid literals left to the interpreter and the compiler to report.
+
+use parser::literal::{self, IntTy, Number};
+
+/// The value of an integer literal, ignoring its suffix.
+pub(crate) fn int(text: &str) -> Option<u64> {
+    match literal::number(text) {
+        Ok(Number::Int(value, _)) => Some(value),
+        _ => None,
+    }
+}
+
+/// The type of the suffix of an integer literal, `None` if it has none.
+pub(crate) fn int_suffix(text: &str) -> Option<IntTy> {
+    match literal::number(text) {
+        Ok(Number::Int(_, suffix)) => suffix,
+        _ => None,
+    }
+}
+
+/// The value of a character literal, including its quotes.
+pub(crate) fn char(text: &str) -> Option<char> {
+    literal::char(text).ok()
+}
+
+/// The value of a string literal, including its quotes.
+pub(crate) fn string(text: &str) -> Option<String> {
+    literal::string(text).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn int() {
+        assert_eq!(super::int("1_000u8"), Some(1000));
+        assert_eq!(super::int("0xff"), Some(255));
+        assert_eq!(super::int("1.5"), None);
+        assert_eq!(super::int_suffix("1_000u8"), Some(super::IntTy::U8));
+        assert_eq!(super::int_suffix("1"), None);
+    }
+}
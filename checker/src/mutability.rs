@@ -0,0 +1,351 @@
+//! Checking that assignments and the builtins modifying their first argument, such as
+//! `push`, write to places of bindings declared with `mut`.
+
+use std::collections::HashMap;
+
+use modules::ModuleTree;
+use parser::{
+    ast::{
+        AstNode, BinaryExpr, CallExpr, Expr, FieldPattern, FunctionExpr, IdentPattern, LetStmt,
+        Param,
+    },
+    syntax::{SyntaxKind, SyntaxNode, SyntaxToken},
+};
+use source_map::{Diagnostic, FileId, Span};
+
+use crate::prelude;
+
+pub(crate) fn check(
+    file: FileId,
+    root: &SyntaxNode,
+    module_tree: &ModuleTree,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut checker = Checker {
+        file,
+        module_tree,
+        scopes: vec![HashMap::new()],
+        diagnostics,
+    };
+    checker.visit(root);
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    mutable: bool,
+    is_param: bool,
+}
+
+struct Checker<'a> {
+    file: FileId,
+    module_tree: &'a ModuleTree,
+    scopes: Vec<HashMap<String, Binding>>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn visit(&mut self, node: &SyntaxNode) {
+        // named functions don't see the locals around them, unlike closures
+        let outer = FunctionExpr::cast(node.clone())
+            .filter(|function| function.name().is_some())
+            .map(|_| std::mem::replace(&mut self.scopes, vec![HashMap::new()]));
+
+        let is_scope = matches!(
+            node.kind(),
+            SyntaxKind::BlockExpr
+                | SyntaxKind::FunctionExpr
+                | SyntaxKind::ClosureExpr
+                | SyntaxKind::MatchArm
+        );
+        if is_scope {
+            self.scopes.push(HashMap::new());
+        }
+
+        match node.kind() {
+            // the arm's bindings are visible in its guard and its body
+            SyntaxKind::MatchArm => self.bind_pattern(node),
+            SyntaxKind::BinaryExpr => {
+                let binary = BinaryExpr::cast(node.clone()).expect("the kind is checked");
+                self.check_assignment(&binary);
+            }
+            SyntaxKind::CallExpr => {
+                let call = CallExpr::cast(node.clone()).expect("the kind is checked");
+                self.check_builtin_call(&call);
+            }
+            _ => {}
+        }
+
+        for child in node.children() {
+            self.visit(&child);
+        }
+
+        // a `let` binds after its initializer, which can refer to a shadowed binding
+        if let Some(stmt) = LetStmt::cast(node.clone()) {
+            if let Some(name) = stmt.name() {
+                let mutable = stmt.mut_keyword().is_some();
+                self.bind(&name, mutable, false);
+            } else if let Some(pattern) = stmt.pattern() {
+                self.bind_pattern(pattern.syntax());
+            }
+        } else if let Some(param) = Param::cast(node.clone()) {
+            if let Some(name) = param.name() {
+                self.bind(&name, param.mut_keyword().is_some(), true);
+            }
+        }
+
+        if is_scope {
+            self.scopes.pop();
+        }
+        if let Some(outer) = outer {
+            self.scopes = outer;
+        }
+    }
+
+    fn bind(&mut self, name: &SyntaxToken, mutable: bool, is_param: bool) {
+        let scope = self.scopes.last_mut().expect("there's always a scope");
+        scope.insert(name.text().to_string(), Binding { mutable, is_param });
+    }
+
+    /// Binds the names introduced by the patterns in `node`.
+    fn bind_pattern(&mut self, node: &SyntaxNode) {
+        for descendant in node.descendants() {
+            if let Some(pattern) = IdentPattern::cast(descendant.clone()) {
+                if let Some(name) = pattern.name() {
+                    self.bind(&name, pattern.mut_keyword().is_some(), false);
+                }
+            } else if let Some(field) = FieldPattern::cast(descendant) {
+                if let (Some(name), None) = (field.name(), field.pattern()) {
+                    self.bind(&name, false, false);
+                }
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Binding> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+    }
+
+    fn check_assignment(&mut self, binary: &BinaryExpr) {
+        if binary.op().is_none_or(|op| !op.kind().is_assignment()) {
+            return;
+        }
+        let Some(lhs) = binary.lhs() else {
+            return;
+        };
+
+        let Some(name) = place_root(&lhs) else {
+            let span = Span::new(self.file, lhs.syntax().text_range());
+            let diagnostic = Diagnostic::error(span, "invalid left-hand side of assignment");
+            self.diagnostics.push(diagnostic);
+            return;
+        };
+
+        // names that aren't local are reported when the program runs
+        let Some(binding) = self.lookup(name.text()) else {
+            return;
+        };
+        if binding.mutable {
+            return;
+        }
+
+        let name = name.text();
+        let message = if !matches!(lhs, Expr::VariableRef(_)) {
+            format!(
+                "cannot assign to `{}`, as `{name}` is not declared as mutable",
+                lhs.syntax()
+            )
+        } else if binding.is_param {
+            format!("cannot assign to immutable argument `{name}`")
+        } else {
+            format!("cannot assign twice to immutable variable `{name}`")
+        };
+        let span = Span::new(self.file, binary.syntax().text_range());
+        self.diagnostics
+            .push(Diagnostic::error(span, message).with_help(format!(
+                "consider making this binding mutable: `mut {name}`"
+            )));
+    }
+
+    /// Checks that the builtins which modify their first argument are given a place of a
+    /// mutable binding.
+    fn check_builtin_call(&mut self, call: &CallExpr) {
+        let Some(builtin) = prelude::builtin(self.file, self.module_tree, call) else {
+            return;
+        };
+        if !builtin.mutates() {
+            return;
+        }
+        let Some(place) = call.arg_list().and_then(|list| list.args().next()) else {
+            return;
+        };
+
+        let span = Span::new(self.file, place.syntax().text_range());
+        let Some(name) = place_root(&place) else {
+            let message = format!(
+                "`{}` modifies its first argument, which must be a variable or an element of one",
+                builtin.name()
+            );
+            self.diagnostics.push(Diagnostic::error(span, message));
+            return;
+        };
+
+        let Some(binding) = self.lookup(name.text()) else {
+            return;
+        };
+        if binding.mutable {
+            return;
+        }
+
+        let name = name.text();
+        let message = format!(
+            "cannot modify `{}` with `{}`, as `{name}` is not declared as mutable",
+            place.syntax(),
+            builtin.name()
+        );
+        self.diagnostics
+            .push(Diagnostic::error(span, message).with_help(format!(
+                "consider making this binding mutable: `mut {name}`"
+            )));
+    }
+}
+
+/// The variable a place expression belongs to, looking through indexing and fields,
+/// `None` when the expression isn't a place.
+fn place_root(place: &Expr) -> Option<SyntaxToken> {
+    match place {
+        Expr::Index(index) => place_root(&index.base()?),
+        Expr::Field(field) => place_root(&field.receiver()?),
+        Expr::Paren(paren) => place_root(&paren.expr()?),
+        Expr::VariableRef(variable) => {
+            let segments = variable.segments().collect::<Vec<_>>();
+            match segments.as_slice() {
+                [name] => Some(name.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check_source;
+
+    #[test]
+    fn mutable_bindings() {
+        let source = "fn main() {
+            let mut a = 1;
+            a = 2;
+            a += 3;
+            let mut xs = [1, 2];
+            xs[0] *= 2;
+            let mut t = (1, (2, 3));
+            t.1.0 = 4;
+            (a) = 5;
+        }
+        fn f(mut n: i64) { n -= 1; }";
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn immutable_bindings() {
+        let source = "fn main() {
+            let a = 1;
+            a = 2;
+            let xs = [1, 2];
+            xs[0] += 1;
+            let t = (1, 2);
+            t.0 = 3;
+        }
+        fn f(n: i64) { n = 1; }";
+        assert_eq!(
+            check_source(source),
+            [
+                "a = 2: cannot assign twice to immutable variable `a`",
+                "xs[0] += 1: cannot assign to `xs[0]`, as `xs` is not declared as mutable",
+                "t.0 = 3: cannot assign to `t.0`, as `t` is not declared as mutable",
+                "n = 1: cannot assign to immutable argument `n`",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_left_hand_side() {
+        let source = "fn main() {
+            let mut a = 1;
+            a + 1 = 2;
+            f() = 3;
+            1 += 1;
+        }
+        fn f() {}";
+        assert_eq!(
+            check_source(source),
+            [
+                "a + 1: invalid left-hand side of assignment",
+                "f(): invalid left-hand side of assignment",
+                "1: invalid left-hand side of assignment",
+            ]
+        );
+    }
+
+    #[test]
+    fn scopes() {
+        let source = "enum Option<T> { Some(T), None }
+        use Option::Some;
+        use Option::None;
+        fn main() {
+            let mut a = 1;
+            {
+                let a = 2;
+                a = 3;
+            }
+            a = 4;
+            let a = a;
+            let f = || a = 5;
+            match Some(1) {
+                Some(mut b) if b > 0 => b += 1,
+                Some(c) => c = 2,
+                None => {}
+            }
+            let (mut d, e) = (1, 2);
+            d = e;
+        }
+        fn g() { a = 1; }";
+        assert_eq!(
+            check_source(source),
+            [
+                "a = 3: cannot assign twice to immutable variable `a`",
+                "a = 5: cannot assign twice to immutable variable `a`",
+                "c = 2: cannot assign twice to immutable variable `c`",
+                "a: cannot find value `a` in this scope",
+            ]
+        );
+    }
+
+    #[test]
+    fn builtins_modifying_arguments() {
+        let source = "fn main() {
+            let mut xs = [1];
+            push(xs, 2);
+            let ys = [[1]];
+            pop(ys[0]);
+            let m = new_map();
+            insert(m, 1, 2);
+            remove(new_map(), 1);
+            let push = |a, b| a;
+            push(ys, 1);
+        }";
+        assert_eq!(
+            check_source(source),
+            [
+                "ys[0]: cannot modify `ys[0]` with `pop`, as `ys` is not declared as mutable",
+                "m: cannot modify `m` with `insert`, as `m` is not declared as mutable",
+                "new_map(): `remove` modifies its first argument, which must be a variable or an element of one",
+            ]
+        );
+    }
+}
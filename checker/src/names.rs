@@ -0,0 +1,228 @@
+//! Resolving the paths of the values and the types the functions use, which the interpreter
+//! would otherwise only fail to find once it gets to them.
+
+use modules::{prelude::Builtin, Def, ModuleTree, ResolveError};
+use parser::{
+    ast::{AstNode, CallExpr, Expr, PathType, VariableRef},
+    syntax::{SyntaxKind, SyntaxNode, SyntaxToken},
+};
+use source_map::{Diagnostic, FileId, Span};
+
+use crate::{is_local, prelude::is_local_function, types::is_generic, types::primitive, HostNames};
+
+pub(crate) fn check(
+    file: FileId,
+    root: &SyntaxNode,
+    module_tree: &ModuleTree,
+    host: &HostNames,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut error = |node: &SyntaxNode, message: String| {
+        let span = Span::new(file, node.text_range());
+        diagnostics.push(Diagnostic::error(span, message));
+    };
+
+    for variable in root.descendants().filter_map(VariableRef::cast) {
+        let in_function = variable.syntax().ancestors().any(|ancestor| {
+            matches!(
+                ancestor.kind(),
+                SyntaxKind::FunctionExpr | SyntaxKind::ClosureExpr
+            )
+        });
+        if in_function {
+            if let Some(message) = value_error(file, module_tree, host, &variable) {
+                error(variable.syntax(), message);
+            }
+        }
+    }
+
+    for path in root.descendants().filter_map(PathType::cast) {
+        let message = match path.syntax().parent().map(|parent| parent.kind()) {
+            // the traits and the self types of impls are resolved along with the impls
+            Some(SyntaxKind::ImplStmt) => None,
+            Some(SyntaxKind::TypeBoundList) => trait_error(file, module_tree, &path),
+            _ => type_error(file, module_tree, host, &path),
+        };
+        if let Some(message) = message {
+            error(path.syntax(), message);
+        }
+    }
+}
+
+/// The error for the path of `variable` if it names neither a local nor a value.
+fn value_error(
+    file: FileId,
+    module_tree: &ModuleTree,
+    host: &HostNames,
+    variable: &VariableRef,
+) -> Option<String> {
+    let segments = variable.segments().collect::<Vec<_>>();
+    let node = variable.syntax();
+    if let [name] = segments.as_slice() {
+        let name = name.text();
+        if is_local(node, name) || is_local_function(node, name) {
+            return None;
+        }
+        // the functions of the prelude and of the host can only be called
+        let is_callee = CallExpr::cast(node.parent()?)
+            .and_then(|call| call.callee())
+            .is_some_and(
+                |callee| matches!(callee, Expr::VariableRef(callee) if callee == *variable),
+            );
+        if is_callee && (Builtin::from_name(name).is_some() || host.functions.contains(name)) {
+            return None;
+        }
+    }
+
+    let names = segments.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+    let module = module_tree.module_at(Span::new(file, node.text_range()));
+    match module_tree.resolve_path(module, &names) {
+        Ok(_) => None,
+        Err(ResolveError::Private(index)) => Some(format!("`{}` is private", names[index])),
+        Err(ResolveError::Unresolved(_) | ResolveError::NotAModule(_)) => {
+            // the types check reports the paths `Enum::name` naming no function of its impls
+            let (_, prefix) = names.split_last()?;
+            let is_associated = !prefix.is_empty()
+                && matches!(
+                    module_tree.resolve_path(module, prefix),
+                    Ok(Def::Enum(_) | Def::Struct(_))
+                );
+            (!is_associated)
+                .then(|| format!("cannot find value `{}` in this scope", names.join("::")))
+        }
+    }
+}
+
+/// The error for `path` if it names no type.
+pub(crate) fn type_error(
+    file: FileId,
+    module_tree: &ModuleTree,
+    host: &HostNames,
+    path: &PathType,
+) -> Option<String> {
+    let names = path
+        .segments()
+        .map(|segment| segment.text().to_string())
+        .collect::<Vec<_>>();
+    let node = path.syntax();
+    if let [name] = names.as_slice() {
+        let is_self = name == "Self"
+            && node.ancestors().any(|ancestor| {
+                matches!(
+                    ancestor.kind(),
+                    SyntaxKind::ImplStmt | SyntaxKind::TraitStmt
+                )
+            });
+        if is_self
+            || is_generic(node, name)
+            || primitive(name).is_some()
+            || host.types.contains(name)
+            || name == "Map"
+        {
+            return None;
+        }
+    }
+
+    let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+    let module = module_tree.module_at(Span::new(file, node.text_range()));
+    match module_tree.resolve_path(module, &names) {
+        Ok(Def::Enum(_) | Def::Struct(_)) => None,
+        Err(ResolveError::Private(index)) => Some(format!("`{}` is private", names[index])),
+        _ => Some(format!(
+            "cannot find type `{}` in this scope",
+            names.join("::")
+        )),
+    }
+}
+
+/// The error for the trait `path` of a bound if it names no trait.
+fn trait_error(file: FileId, module_tree: &ModuleTree, path: &PathType) -> Option<String> {
+    let names = path.segments().collect::<Vec<_>>();
+    let names = names.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+    let module = module_tree.module_at(Span::new(file, path.syntax().text_range()));
+    let path = names.join("::");
+    match module_tree.resolve_path(module, &names) {
+        Ok(Def::Trait(_)) => None,
+        Ok(def) => {
+            let kind = match def {
+                Def::Module(_) => "module",
+                Def::Function(_) => "function",
+                Def::Enum(_) => "enum",
+                Def::Variant(..) => "variant",
+                Def::Struct(_) => "struct",
+                Def::Trait(_) => "trait",
+            };
+            Some(format!("expected trait, found {kind} `{path}`"))
+        }
+        Err(ResolveError::Private(_)) => Some(format!("trait `{path}` is private")),
+        Err(_) => Some(format!("cannot find trait `{path}` in this scope")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check_source;
+
+    #[test]
+    fn resolved_names() {
+        let source = "struct P<T> { x: T }
+        enum Shape { Rect { w: i64, h: i64 }, Dot }
+        impl<T> P<T> {
+            fn new(x: T) -> Self { P { x } }
+        }
+        mod m {
+            pub fn f() -> Map<str, i64> { new_map() }
+        }
+        fn id<T>(x: T) -> T { x }
+        fn main() {
+            let p: P<i64> = P::new(1);
+            let area = match Shape::Dot {
+                Shape::Rect { w, h } => w * h,
+                Shape::Dot => 0,
+            };
+            fn double(n: i64) -> i64 { n * 2 }
+            let f = |x: u8| x;
+            println(double(area) + id(p.x));
+            println(len(m::f()));
+            f(1);
+        }";
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unresolved_names() {
+        let source = "mod m {
+            fn hidden() {}
+            struct Hidden {}
+        }
+        fn f(a: i64) {}
+        fn g(a: Foo) -> Bar { a }
+        fn h<T: Missing>(x: T) {}
+        fn main() {
+            println(undefined_var);
+            nope(1);
+            let x: Foo = 1;
+            let c = |y: m::Hidden| y;
+            m::hidden();
+            n::f();
+            let l = len;
+            fn inner() { x }
+        }";
+        assert_eq!(
+            check_source(source),
+            [
+                "undefined_var: cannot find value `undefined_var` in this scope",
+                "nope: cannot find value `nope` in this scope",
+                "m::hidden: `hidden` is private",
+                "n::f: cannot find value `n::f` in this scope",
+                "len: cannot find value `len` in this scope",
+                "x: cannot find value `x` in this scope",
+                "Foo: cannot find type `Foo` in this scope",
+                "Bar: cannot find type `Bar` in this scope",
+                "Missing: cannot find trait `Missing` in this scope",
+                "Foo: cannot find type `Foo` in this scope",
+                "m::Hidden: `Hidden` is private",
+            ]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Checking the number of arguments of calls to the functions of the prelude. The types of
+//! the arguments are checked along with the other types, in [`crate::types`].
+
+use modules::{prelude::Builtin, ModuleTree};
+use parser::{
+    ast::{AstNode, CallExpr, Expr, FunctionExpr},
+    syntax::{SyntaxKind, SyntaxNode},
+};
+use source_map::{Diagnostic, FileId, Span};
+
+use crate::is_local;
+
+pub(crate) fn check(
+    file: FileId,
+    root: &SyntaxNode,
+    module_tree: &ModuleTree,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for call in root.descendants().filter_map(CallExpr::cast) {
+        let Some(builtin) = builtin(file, module_tree, &call) else {
+            continue;
+        };
+
+        let args = call
+            .arg_list()
+            .into_iter()
+            .flat_map(|list| list.args())
+            .collect::<Vec<_>>();
+        if let Err(message) = builtin.check_arity(args.len()) {
+            let span = Span::new(file, call.syntax().text_range());
+            diagnostics.push(Diagnostic::error(span, message));
+        }
+    }
+}
+
+/// The builtin called by `call`, if its callee is a builtin's name that doesn't refer to a
+/// local or an item.
+pub(crate) fn builtin(file: FileId, module_tree: &ModuleTree, call: &CallExpr) -> Option<Builtin> {
+    let Some(Expr::VariableRef(variable)) = call.callee() else {
+        return None;
+    };
+    let [name] = variable.segments().collect::<Vec<_>>().try_into().ok()?;
+    let builtin = Builtin::from_name(name.text())?;
+
+    let node = variable.syntax();
+    let module = module_tree.module_at(Span::new(file, node.text_range()));
+    let shadowed = is_local(node, name.text())
+        || is_local_function(node, name.text())
+        || module_tree.resolve_path(module, &[name.text()]).is_ok();
+    (!shadowed).then_some(builtin)
+}
+
+/// Whether a function named `name` is declared in a body enclosing `node`.
+pub(crate) fn is_local_function(node: &SyntaxNode, name: &str) -> bool {
+    node.ancestors()
+        .filter(|ancestor| ancestor.kind() == SyntaxKind::BlockExpr)
+        .flat_map(|block| block.children())
+        .filter_map(|stmt| stmt.children().find_map(FunctionExpr::cast))
+        .any(|function| function.name().is_some_and(|n| n.text() == name))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check_source;
+
+    #[test]
+    fn valid_calls() {
+        let source = r#"fn main() {
+            let s = concat(trim(" a "), f"{1}");
+            println(len(split(s, ",")));
+            println();
+            let x = sqrt(abs(-2.0)) + pow(2, 3);
+            let mut m = new_map();
+            insert(m, s, [x]);
+            assert(contains_key(m, s), "present");
+            assert_eq(len(m), 1);
+        }"#;
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn invalid_calls() {
+        let source = r#"fn main() {
+            len(1, 2);
+            sqrt(2);
+            trim('a');
+            concat("a", len("b"));
+            assert(());
+            min(1, "a");
+            write_file("out", [1]);
+            abs(-"a");
+        }"#;
+        assert_eq!(
+            check_source(source),
+            [
+                "len(1, 2): function `len` takes 1 argument but 2 were supplied",
+                "2: expected `float`, found `integer`",
+                "'a': expected `string`, found `char`",
+                "len(\"b\"): expected `string`, found `integer`",
+                "(): expected `bool`, found `()`",
+                "\"a\": expected `integer` or `float`, found `string`",
+                "[1]: expected `string`, found `array`",
+                "-\"a\": cannot apply unary operator `-` to `string`",
+            ]
+        );
+    }
+
+    #[test]
+    fn shadowed_builtins() {
+        let source = "fn len(a: i64) -> i64 { a }
+        mod m {
+            fn main() {
+                let sqrt = |x| x;
+                fn trim(x: i64) {}
+                sqrt(2);
+                trim(1);
+                abs(\"a\");
+            }
+        }
+        fn main() { len(1) }";
+        assert_eq!(
+            check_source(source),
+            ["\"a\": expected `integer` or `float`, found `string`"]
+        );
+    }
+}
@@ -0,0 +1,2087 @@
+//! Checking the types of operands, annotations, payloads and builtin arguments, as far as
+//! they can be told without running the program, by the rules the interpreter applies to
+//! values at runtime. Whatever can't be told is taken to be of any type.
+
+use std::{collections::HashMap, mem};
+
+use db::{Database, Query};
+
+use modules::{
+    prelude::{Builtin, Kind},
+    Def, ModuleTree, ResolveError, VariantFields,
+};
+use parser::{
+    ast::{
+        AstNode, BinaryExpr, BlockExpr, CallExpr, ClosureExpr, ElseBranch, EnumStmt, Expr,
+        FieldExpr, FieldPattern, FunctionExpr, GenericArgList, GenericParamList, IdentPattern,
+        IfExpr, IndexExpr, InterpolatedStringPart, LetStmt, MatchExpr, MethodCallExpr,
+        OptionalType, Param, PathType, Pattern, PrefixExpr, RecordExpr, ReturnStmt, Root, Stmt,
+        StructStmt, Type, VariableRef, Variant,
+    },
+    literal::{self, IntTy, Number},
+    syntax::{SyntaxKind, SyntaxNode, SyntaxToken},
+};
+use source_map::{Diagnostic, FileId, Span};
+
+use crate::{impls, is_local, prelude, FunctionId, Inference};
+
+/// The type of the values of an expression, as far as it's told before running the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ty {
+    /// A type which can't be told, which values of any type have.
+    Unknown,
+    /// An integer type, `None` for integers whose type isn't told yet, which take the type
+    /// of the integers they meet.
+    Int(Option<IntTy>),
+    Float,
+    Bool,
+    Char,
+    String,
+    Unit,
+    /// Arrays and slices of elements of a type.
+    Array(Box<Ty>),
+    /// Tuples of at least one element, as `()` is [`Ty::Unit`].
+    Tuple(Vec<Ty>),
+    /// Functions and closures, whatever their signature.
+    Function,
+    Map,
+    /// An enum or a struct by the span of its name, with its generic arguments, which are
+    /// left out where they aren't told.
+    Enum(Span, Vec<Ty>),
+    /// A generic argument being inferred while a function is checked, which the types
+    /// given out of the checker never contain.
+    Var(usize),
+}
+
+impl Ty {
+    /// The name of the type in errors, as the interpreter names the types of values.
+    fn name<'a>(&self, module_tree: &'a ModuleTree) -> &'a str {
+        match self {
+            Self::Unknown | Self::Var(_) => "_",
+            Self::Int(ty) => ty.map_or("integer", IntTy::name),
+            Self::Float => "float",
+            Self::Bool => "bool",
+            Self::Char => "char",
+            Self::String => "string",
+            Self::Unit => "()",
+            Self::Array(_) => "array",
+            Self::Tuple(_) => "tuple",
+            Self::Function => "function",
+            Self::Map => "map",
+            Self::Enum(span, _) => &module_tree.enum_data(*span).name,
+        }
+    }
+
+    /// The type as the interpreter writes the types of annotations, such as `Option<i64>`.
+    pub(crate) fn display(&self, module_tree: &ModuleTree) -> String {
+        match self {
+            Self::Array(element) => format!("[{}]", element.display(module_tree)),
+            Self::Tuple(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| element.display(module_tree))
+                    .collect::<Vec<_>>();
+                match elements.as_slice() {
+                    [element] => format!("({element},)"),
+                    elements => format!("({})", elements.join(", ")),
+                }
+            }
+            Self::Enum(_, args) if !args.is_empty() => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.display(module_tree))
+                    .collect::<Vec<_>>();
+                format!("{}<{}>", self.name(module_tree), args.join(", "))
+            }
+            ty => ty.name(module_tree).to_string(),
+        }
+    }
+
+    /// The kind of the values of the type in the signatures of builtins, `None` for the
+    /// types only [`Kind::Any`] takes.
+    fn kind(&self) -> Option<Kind> {
+        let kind = match self {
+            Self::Unknown | Self::Var(_) => Kind::Any,
+            Self::Int(_) => Kind::Int,
+            Self::Float => Kind::Float,
+            Self::Bool => Kind::Bool,
+            Self::Char => Kind::Char,
+            Self::String => Kind::String,
+            Self::Unit => Kind::Unit,
+            Self::Array(_) => Kind::Array,
+            Self::Map => Kind::Map,
+            Self::Tuple(_) | Self::Function | Self::Enum(..) => return None,
+        };
+        Some(kind)
+    }
+
+    fn contains(&self, var: usize) -> bool {
+        match self {
+            Self::Var(other) => *other == var,
+            Self::Array(element) => element.contains(var),
+            Self::Tuple(types) | Self::Enum(_, types) => types.iter().any(|ty| ty.contains(var)),
+            _ => false,
+        }
+    }
+}
+
+/// A use of a generic function, enum or struct, whose generic arguments are inferred.
+struct Use {
+    /// The call, the path or the record using the item.
+    node: SyntaxNode,
+    /// What the item is, `function`, `enum` or `struct`, and its name.
+    kind: &'static str,
+    name: String,
+    generics: Vec<String>,
+    args: Vec<Ty>,
+}
+
+/// Checks the file and returns the inferred generic arguments of the items used in it, by
+/// the span of the call, the path or the record using them.
+pub(crate) fn check(
+    db: &Database,
+    file: FileId,
+    root: &SyntaxNode,
+    module_tree: &ModuleTree,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<Span, Vec<Ty>> {
+    // `T?` is short for the `Option<T>` of the program, which the prelude doesn't declare
+    for optional in root.descendants().filter_map(OptionalType::cast) {
+        let module = module_tree.module_at(Span::new(file, optional.syntax().text_range()));
+        if !matches!(
+            module_tree.resolve_path(module, &["Option"]),
+            Ok(Def::Enum(_) | Def::Struct(_))
+        ) {
+            let span = Span::new(file, optional.syntax().text_range());
+            let message = "cannot find type `Option` in this scope".to_string();
+            diagnostics.push(Diagnostic::error(span, message));
+        }
+    }
+
+    let Some(root) = Root::cast(root.clone()) else {
+        return HashMap::new();
+    };
+    let program = module_tree.get(module_tree.root()).file;
+    let mut checker = Checker::new(db, file, module_tree, diagnostics);
+    checker.items(program, root.stmts());
+    checker.finish()
+}
+
+/// Checks the body of a function of the program, along with the functions and closures
+/// it contains.
+pub(crate) struct Infer;
+
+impl Query for Infer {
+    type Key = FunctionId;
+    type Value = Inference;
+    const NAME: &'static str = "infer";
+
+    fn execute(db: &Database, function: &FunctionId) -> Inference {
+        let module_tree = modules::module_tree(db, function.root);
+        let mut diagnostics = Vec::new();
+        let mut type_args = HashMap::new();
+        if let Some(syntax) = crate::item::<FunctionExpr>(db, function.name) {
+            let mut checker = Checker::new(db, function.name.file, &module_tree, &mut diagnostics);
+            checker.function(&syntax);
+            type_args = checker.finish();
+        }
+        Inference {
+            diagnostics,
+            type_args,
+        }
+    }
+}
+
+struct Checker<'a> {
+    /// Where the declarations of the items used are read.
+    db: &'a Database,
+    file: FileId,
+    module_tree: &'a ModuleTree,
+    /// The types of the locals in scope in the function being checked.
+    scopes: Vec<HashMap<String, Ty>>,
+    /// The annotated return types of the functions and closures being checked, innermost
+    /// last, `None` for those without one.
+    returns: Vec<Option<(Type, Ty)>>,
+    /// The types of the generic arguments being inferred, by the index of their
+    /// [`Ty::Var`], once they're told.
+    vars: Vec<Option<Ty>>,
+    uses: Vec<Use>,
+    /// The generic arguments inferred in the functions of the module, which are checked
+    /// on their own.
+    type_args: HashMap<Span, Vec<Ty>>,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(
+        db: &'a Database,
+        file: FileId,
+        module_tree: &'a ModuleTree,
+        diagnostics: &'a mut Vec<Diagnostic>,
+    ) -> Self {
+        Self {
+            db,
+            file,
+            module_tree,
+            scopes: vec![HashMap::new()],
+            returns: Vec::new(),
+            vars: Vec::new(),
+            uses: Vec::new(),
+            type_args: HashMap::new(),
+            diagnostics,
+        }
+    }
+
+    /// Checks the items of a module of the program whose root file is `program`, with
+    /// each of its functions inferred on its own.
+    fn items(&mut self, program: FileId, stmts: impl Iterator<Item = Stmt>) {
+        for stmt in stmts {
+            let list = match &stmt {
+                Stmt::Mod(stmt) => stmt.item_list(),
+                Stmt::Trait(stmt) => stmt.item_list(),
+                Stmt::Impl(stmt) => stmt.item_list(),
+                Stmt::Expr(expr) => {
+                    let name = match expr.expr() {
+                        Some(Expr::Function(function)) => function.name(),
+                        _ => None,
+                    };
+                    if let Some(name) = name {
+                        let function = FunctionId {
+                            root: program,
+                            name: Span::new(self.file, name.text_range()),
+                        };
+                        let inference = crate::infer(self.db, function);
+                        self.diagnostics
+                            .extend(inference.diagnostics.iter().cloned());
+                        self.type_args.extend(
+                            inference
+                                .type_args
+                                .iter()
+                                .map(|(span, args)| (*span, args.clone())),
+                        );
+                        continue;
+                    }
+                    None
+                }
+                _ => None,
+            };
+            match list {
+                Some(list) => self.items(program, list.stmts()),
+                None => self.stmts([stmt].into_iter()),
+            }
+        }
+    }
+
+    fn stmts(&mut self, stmts: impl Iterator<Item = Stmt>) {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Let(stmt) => self.let_stmt(&stmt),
+                Stmt::Return(stmt) => self.return_stmt(&stmt),
+                Stmt::Expr(stmt) => {
+                    if let Some(expr) = stmt.expr() {
+                        self.expr(&expr);
+                    }
+                }
+                Stmt::Mod(stmt) => {
+                    if let Some(list) = stmt.item_list() {
+                        self.stmts(list.stmts());
+                    }
+                }
+                Stmt::Trait(stmt) => {
+                    if let Some(list) = stmt.item_list() {
+                        self.stmts(list.stmts());
+                    }
+                }
+                Stmt::Impl(stmt) => {
+                    if let Some(list) = stmt.item_list() {
+                        self.stmts(list.stmts());
+                    }
+                }
+                Stmt::Use(_) | Stmt::Enum(_) | Stmt::Struct(_) => {}
+            }
+        }
+    }
+
+    fn let_stmt(&mut self, stmt: &LetStmt) {
+        let initializer = stmt.initializer();
+        let found = self.expr_opt(initializer.as_ref());
+        let annotation = stmt.ty().map(|ty| (self.lower(self.file, &ty), ty));
+
+        // the initializer is given the annotated type, such as the type of its integers
+        let ty = match annotation {
+            Some((expected, ty)) => {
+                if let Some(initializer) = &initializer {
+                    let text = ty.syntax().to_string();
+                    self.expect(&expected, &text, initializer.syntax(), &found);
+                }
+                if expected == Ty::Unknown {
+                    found
+                } else {
+                    expected
+                }
+            }
+            None => found,
+        };
+
+        if let Some(name) = stmt.name() {
+            self.bind(name.text(), ty);
+        } else if let Some(pattern) = stmt.pattern() {
+            self.bind_pattern(&pattern, ty);
+        }
+    }
+
+    fn return_stmt(&mut self, stmt: &ReturnStmt) {
+        let Some(value) = stmt.value() else {
+            return;
+        };
+        let found = self.expr(&value);
+        if let Some(Some((ty, expected))) = self.returns.last().cloned() {
+            self.expect(&expected, &ty.syntax().to_string(), value.syntax(), &found);
+        }
+    }
+
+    fn expr_opt(&mut self, expr: Option<&Expr>) -> Ty {
+        expr.map_or(Ty::Unknown, |expr| self.expr(expr))
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Ty {
+        match expr {
+            Expr::Literal(literal) => match literal.token() {
+                Some(token) => self.literal(&token, literal.syntax(), false),
+                None => Ty::Unknown,
+            },
+            Expr::InterpolatedString(string) => {
+                for part in string.parts() {
+                    if let InterpolatedStringPart::Expr(expr) = part {
+                        self.expr(&expr);
+                    }
+                }
+                Ty::String
+            }
+            Expr::VariableRef(variable) => {
+                let segments = variable.segments().collect::<Vec<_>>();
+                if let [name] = segments.as_slice() {
+                    if let Some(ty) = self.lookup(name.text()) {
+                        return ty.clone();
+                    }
+                }
+                match self.value_path(variable) {
+                    Some(Def::Variant(span, index)) => {
+                        let kind = match self.module_tree.enum_data(span).variants[index].fields {
+                            VariantFields::Unit => {
+                                let list = variable.generic_arg_list();
+                                let env = self.enum_env(variable.syntax(), span, list);
+                                return enum_ty(span, env);
+                            }
+                            VariantFields::Tuple(_) => "tuple",
+                            VariantFields::Record(_) => "struct",
+                        };
+                        let path = segments.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+                        let message =
+                            format!("expected value, found {kind} variant `{}`", path.join("::"));
+                        self.error(variable.syntax(), message);
+                        Ty::Unknown
+                    }
+                    Some(Def::Function(_)) => Ty::Function,
+                    _ => Ty::Unknown,
+                }
+            }
+            Expr::Paren(paren) => self.expr_opt(paren.expr().as_ref()),
+            Expr::Tuple(tuple) => {
+                let elements = tuple
+                    .elements()
+                    .map(|element| self.expr(&element))
+                    .collect::<Vec<_>>();
+                if elements.is_empty() {
+                    Ty::Unit
+                } else {
+                    Ty::Tuple(elements)
+                }
+            }
+            Expr::Prefix(prefix) => self.prefix(prefix),
+            Expr::Binary(binary) => self.binary(binary),
+            Expr::Call(call) => self.call(call),
+            Expr::MethodCall(call) => self.method_call(call),
+            Expr::Field(field) => self.field(field),
+            Expr::Block(block) => self.block(block),
+            Expr::Function(function) => self.function(function),
+            Expr::If(if_expr) => self.if_expr(if_expr),
+            // the elements of an empty array are of a type to infer, as for generic items
+            Expr::Array(array) if array.elements().next().is_none() => {
+                self.vars.push(None);
+                Ty::Array(Box::new(Ty::Var(self.vars.len() - 1)))
+            }
+            Expr::Array(array) => {
+                let mut element = None;
+                for expr in array.elements() {
+                    let ty = self.expr(&expr);
+                    element = Some(match element {
+                        Some(element) => self.join(element, ty),
+                        None => ty,
+                    });
+                }
+                Ty::Array(Box::new(element.unwrap_or(Ty::Unknown)))
+            }
+            Expr::ArrayRepeat(repeat) => {
+                let element = self.expr_opt(repeat.value().as_ref());
+                self.expr_opt(repeat.count().as_ref());
+                Ty::Array(Box::new(element))
+            }
+            Expr::Index(index) => self.index(index),
+            Expr::Closure(closure) => self.closure(closure),
+            Expr::Match(match_expr) => self.match_expr(match_expr),
+            Expr::Record(record) => self.record(record),
+        }
+    }
+
+    /// The type of a literal, checking that integers fit in their type. A literal following
+    /// a `-` is negated first, so that the minimum of signed types can be written.
+    fn literal(&mut self, token: &SyntaxToken, node: &SyntaxNode, negative: bool) -> Ty {
+        match token.kind() {
+            SyntaxKind::Number => match literal::number(token.text()) {
+                Ok(Number::Int(_, Some(ty))) if negative && !ty.is_signed() => {
+                    let message = format!(
+                        "cannot apply unary operator {} to `{}`",
+                        SyntaxKind::Minus,
+                        ty.name()
+                    );
+                    self.error(node, message);
+                    Ty::Int(Some(ty))
+                }
+                Ok(Number::Int(value, suffix)) => {
+                    let value = if negative {
+                        -i128::from(value)
+                    } else {
+                        i128::from(value)
+                    };
+                    match suffix {
+                        Some(ty) if !ty.contains(value) => {
+                            self.error(node, format!("literal out of range for `{}`", ty.name()));
+                        }
+                        // literals without a suffix are `i64`s unless they meet another type
+                        None if !IntTy::I64.contains(value) => {
+                            let sign = if negative { "-" } else { "" };
+                            let message =
+                                format!("integer literal `{sign}{}` is too large", token.text());
+                            self.error(node, message);
+                        }
+                        _ => {}
+                    }
+                    Ty::Int(suffix)
+                }
+                Ok(Number::Float(..)) => Ty::Float,
+                Err(_) => Ty::Unknown,
+            },
+            SyntaxKind::Bool => self.negate(Ty::Bool, node, negative),
+            SyntaxKind::String => self.negate(Ty::String, node, negative),
+            SyntaxKind::Character => self.negate(Ty::Char, node, negative),
+            _ => Ty::Unknown,
+        }
+    }
+
+    fn negate(&mut self, ty: Ty, node: &SyntaxNode, negative: bool) -> Ty {
+        if !negative {
+            return ty;
+        }
+        self.prefix_ty(SyntaxKind::Minus, ty, node)
+    }
+
+    fn prefix(&mut self, prefix: &PrefixExpr) -> Ty {
+        let Some(op) = prefix.op() else {
+            return Ty::Unknown;
+        };
+        if op.kind() == SyntaxKind::Minus {
+            if let Some(Expr::Literal(literal)) = prefix.operand() {
+                if let Some(token) = literal.token() {
+                    return self.literal(&token, prefix.syntax(), true);
+                }
+            }
+        }
+
+        let operand = self.expr_opt(prefix.operand().as_ref());
+        let operand = self.known(&operand);
+        self.prefix_ty(op.kind(), operand, prefix.syntax())
+    }
+
+    fn prefix_ty(&mut self, op: SyntaxKind, operand: Ty, node: &SyntaxNode) -> Ty {
+        match (op, operand) {
+            (SyntaxKind::Minus, Ty::Int(Some(ty))) if !ty.is_signed() => {
+                let message = format!("cannot apply unary operator {op} to `{}`", ty.name());
+                self.error(node, message);
+                Ty::Int(Some(ty))
+            }
+            (SyntaxKind::Minus | SyntaxKind::Tilde, Ty::Int(ty)) => Ty::Int(ty),
+            (SyntaxKind::Minus, Ty::Float) => Ty::Float,
+            (SyntaxKind::Bang, Ty::Bool) => Ty::Bool,
+            (_, Ty::Unknown) => Ty::Unknown,
+            (op, operand) => {
+                let message = format!(
+                    "cannot apply unary operator {op} to `{}`",
+                    operand.name(self.module_tree)
+                );
+                self.error(node, message);
+                Ty::Unknown
+            }
+        }
+    }
+
+    fn binary(&mut self, binary: &BinaryExpr) -> Ty {
+        let lhs = binary.lhs();
+        let rhs = binary.rhs();
+        let lhs_ty = self.expr_opt(lhs.as_ref());
+        let rhs_ty = self.expr_opt(rhs.as_ref());
+        let Some(op) = binary.op() else {
+            return Ty::Unknown;
+        };
+        if op.kind() == SyntaxKind::SingleEqual {
+            // any value can be assigned at runtime, after which the place has its type
+            if !self.unify(&lhs_ty, &rhs_ty) {
+                self.forget(binary.lhs());
+            }
+            return Ty::Unit;
+        }
+        let (lhs_ty, rhs_ty) = (self.known(&lhs_ty), self.known(&rhs_ty));
+
+        if matches!(op.kind(), SyntaxKind::LogicalAnd | SyntaxKind::LogicalOr) {
+            if let Some(ty) = [&lhs_ty, &rhs_ty]
+                .into_iter()
+                .find(|ty| !fits(&Ty::Bool, ty))
+            {
+                let message = format!("expected `bool`, found `{}`", ty.name(self.module_tree));
+                self.error(binary.syntax(), message);
+            }
+            return Ty::Bool;
+        }
+
+        let lhs = (&lhs_ty, lhs.as_ref().and_then(int_value));
+        let rhs = (&rhs_ty, rhs.as_ref().and_then(int_value));
+        if let Some(op) = compound_assignment(op.kind()) {
+            if let Err(message) = self.binary_ty(op, lhs, rhs) {
+                self.error(binary.syntax(), message);
+            }
+            return Ty::Unit;
+        }
+
+        self.binary_ty(op.kind(), lhs, rhs)
+            .unwrap_or_else(|message| {
+                self.error(binary.syntax(), message);
+                Ty::Unknown
+            })
+    }
+
+    /// The type of the result of `op` on operands of these types and values, which are
+    /// known for integer literals, as `ops::binary` of the interpreter computes it.
+    fn binary_ty(
+        &self,
+        op: SyntaxKind,
+        (lhs, lhs_value): (&Ty, Option<i128>),
+        (rhs, rhs_value): (&Ty, Option<i128>),
+    ) -> Result<Ty, String> {
+        let is_comparison = matches!(
+            op,
+            SyntaxKind::Equal
+                | SyntaxKind::NotEqual
+                | SyntaxKind::LessThan
+                | SyntaxKind::GreaterThan
+                | SyntaxKind::LessThanEqual
+                | SyntaxKind::GreaterThanEqual
+        );
+        if *lhs == Ty::Unknown || *rhs == Ty::Unknown {
+            return Ok(if is_comparison { Ty::Bool } else { Ty::Unknown });
+        }
+
+        let mismatch = || {
+            format!(
+                "cannot apply {op} to `{}` and `{}`",
+                lhs.name(self.module_tree),
+                rhs.name(self.module_tree)
+            )
+        };
+
+        // the operands of operators other than shifts are integers of the same type
+        let ty = match (lhs, rhs) {
+            (Ty::Int(a_ty), Ty::Int(b_ty))
+                if !matches!(op, SyntaxKind::ShiftLeft | SyntaxKind::ShiftRight) =>
+            {
+                common_ty(op, (lhs_value, *a_ty), (rhs_value, *b_ty))?
+            }
+            _ => None,
+        };
+
+        match op {
+            SyntaxKind::Equal | SyntaxKind::NotEqual => {
+                if mem::discriminant(lhs) != mem::discriminant(rhs) {
+                    return Err(mismatch());
+                }
+                Ok(Ty::Bool)
+            }
+            SyntaxKind::LessThan
+            | SyntaxKind::GreaterThan
+            | SyntaxKind::LessThanEqual
+            | SyntaxKind::GreaterThanEqual => match (lhs, rhs) {
+                (Ty::Int(_), Ty::Int(_))
+                | (Ty::Float, Ty::Float)
+                | (Ty::Char, Ty::Char)
+                | (Ty::String, Ty::String) => Ok(Ty::Bool),
+                _ => Err(mismatch()),
+            },
+            SyntaxKind::Plus
+            | SyntaxKind::Minus
+            | SyntaxKind::Star
+            | SyntaxKind::Slash
+            | SyntaxKind::Modulo => match (lhs, rhs) {
+                (Ty::Int(_), Ty::Int(_)) => Ok(Ty::Int(ty)),
+                (Ty::Float, Ty::Float) => Ok(Ty::Float),
+                (Ty::String, Ty::String) if op == SyntaxKind::Plus => Ok(Ty::String),
+                _ => Err(mismatch()),
+            },
+            SyntaxKind::Ampersand
+            | SyntaxKind::Pipe
+            | SyntaxKind::Caret
+            | SyntaxKind::ShiftLeft
+            | SyntaxKind::ShiftRight => match (lhs, rhs) {
+                (Ty::Int(a_ty), Ty::Int(_)) => Ok(Ty::Int(ty.or(*a_ty))),
+                _ => Err(mismatch()),
+            },
+            _ => Ok(Ty::Unknown),
+        }
+    }
+
+    /// Forgets the type of the local assigned by `place`, which a value of another type
+    /// replaced.
+    fn forget(&mut self, place: Option<Expr>) {
+        let Some(Expr::VariableRef(variable)) = place else {
+            return;
+        };
+        let Ok([name]) = <[_; 1]>::try_from(variable.segments().collect::<Vec<_>>()) else {
+            return;
+        };
+        if let Some(scope) = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.contains_key(name.text()))
+        {
+            scope.insert(name.text().to_string(), Ty::Unknown);
+        }
+    }
+
+    fn call(&mut self, call: &CallExpr) -> Ty {
+        let (def, list) = match call.callee() {
+            Some(Expr::VariableRef(variable)) => {
+                (self.value_path(&variable), variable.generic_arg_list())
+            }
+            Some(callee) => {
+                self.expr(&callee);
+                (None, None)
+            }
+            None => (None, None),
+        };
+        let args = call
+            .arg_list()
+            .into_iter()
+            .flat_map(|list| list.args())
+            .map(|arg| {
+                let ty = self.expr(&arg);
+                (arg, ty)
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(builtin) = prelude::builtin(self.file, self.module_tree, call) {
+            return self.builtin_call(builtin, &args);
+        }
+        match def {
+            Some(Def::Function(span)) => {
+                let Some(function) = self.item::<FunctionExpr>(span) else {
+                    return Ty::Unknown;
+                };
+                let name = function
+                    .name()
+                    .map_or(String::new(), |name| name.text().to_string());
+                let arity = function
+                    .param_list()
+                    .map_or(0, |list| list.params().count());
+                if args.len() != arity {
+                    let message = format!(
+                        "function `{name}` takes {arity} argument{} but {} {} supplied",
+                        if arity == 1 { "" } else { "s" },
+                        args.len(),
+                        if args.len() == 1 { "was" } else { "were" },
+                    );
+                    self.error(call.syntax(), message);
+                    return Ty::Unknown;
+                }
+                let generics = generic_names(function.generic_param_list());
+                let env = self.instantiate(call.syntax(), list, ("function", &name), generics);
+
+                let params = function
+                    .param_list()
+                    .into_iter()
+                    .flat_map(|list| list.params())
+                    .map(|param| param.ty())
+                    .collect::<Vec<_>>();
+                let annotations = params
+                    .iter()
+                    .flatten()
+                    .chain(function.return_type().as_ref())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                self.erase(&env, span.file, &annotations);
+
+                for (param, (arg, found)) in params.iter().zip(&args) {
+                    if let Some(ty) = param {
+                        let expected = self.lower_in(span.file, ty, &env);
+                        self.expect_in(&expected, ty, &env, arg.syntax(), found);
+                    }
+                }
+                self.check_bounds(
+                    call.syntax(),
+                    function.generic_param_list(),
+                    span.file,
+                    &env,
+                );
+                function
+                    .return_type()
+                    .map_or(Ty::Unknown, |ty| self.lower_in(span.file, &ty, &env))
+            }
+            Some(Def::Variant(span, index)) => {
+                let data = self.module_tree.enum_data(span);
+                let path = format!("{}::{}", data.name, data.variants[index].name);
+                let arity = match data.variants[index].fields {
+                    VariantFields::Tuple(arity) => arity,
+                    VariantFields::Unit => {
+                        let message = format!("expected function, found unit variant `{path}`");
+                        self.error(call.syntax(), message);
+                        return Ty::Unknown;
+                    }
+                    VariantFields::Record(_) => {
+                        let message = format!("expected function, found struct variant `{path}`");
+                        self.error(call.syntax(), message);
+                        return Ty::Unknown;
+                    }
+                };
+                if args.len() != arity {
+                    let message = format!(
+                        "variant `{path}` takes {arity} argument{} but {} {} supplied",
+                        if arity == 1 { "" } else { "s" },
+                        args.len(),
+                        if args.len() == 1 { "was" } else { "were" },
+                    );
+                    self.error(call.syntax(), message);
+                    return Ty::Enum(span, Vec::new());
+                }
+
+                let env = self.enum_env(call.syntax(), span, list);
+                let fields = self
+                    .variant(span, index)
+                    .and_then(|variant| variant.tuple_fields())
+                    .into_iter()
+                    .flat_map(|list| list.types())
+                    .collect::<Vec<_>>();
+                self.erase(&env, span.file, &fields);
+                for (ty, (arg, found)) in fields.iter().zip(&args) {
+                    let expected = self.lower_in(span.file, ty, &env);
+                    self.expect_in(&expected, ty, &env, arg.syntax(), found);
+                }
+                let list = self.generic_params(span);
+                self.check_bounds(call.syntax(), list, span.file, &env);
+                enum_ty(span, env)
+            }
+            _ => Ty::Unknown,
+        }
+    }
+
+    /// Checks that the receiver of a method call has the method, when its type is told. The
+    /// types of the arguments and of the result are only told at runtime.
+    fn method_call(&mut self, call: &MethodCallExpr) -> Ty {
+        let receiver = self.expr_opt(call.receiver().as_ref());
+        let mut supplied = 0;
+        for arg in call.arg_list().into_iter().flat_map(|list| list.args()) {
+            self.expr(&arg);
+            supplied += 1;
+        }
+        let Some(name) = call.name() else {
+            return Ty::Unknown;
+        };
+
+        let receiver = self.known(&receiver);
+        match impls::method(self.db, self.module_tree, &receiver, name.text()) {
+            Ok(Some(function)) => {
+                let expected = self
+                    .item::<FunctionExpr>(function)
+                    .and_then(|function| function.param_list())
+                    .map_or(0, |list| list.params().count())
+                    .saturating_sub(1);
+                if expected != supplied {
+                    let message = format!(
+                        "method `{name}` takes {expected} argument{} but {supplied} {} supplied",
+                        if expected == 1 { "" } else { "s" },
+                        if supplied == 1 { "was" } else { "were" },
+                    );
+                    self.error(call.syntax(), message);
+                }
+            }
+            Ok(None) => {}
+            Err(message) => self.error(call.syntax(), message),
+        }
+        Ty::Unknown
+    }
+
+    /// Reports the generic arguments in `env` which don't implement the traits bounding their
+    /// parameters, declared in `list` written in `file`.
+    fn check_bounds(
+        &mut self,
+        node: &SyntaxNode,
+        list: Option<GenericParamList>,
+        file: FileId,
+        env: &[(String, Ty)],
+    ) {
+        let Some(list) = list else {
+            return;
+        };
+        let module = self.module_at(file, list.syntax());
+        for (param, (_, ty)) in list.params().zip(env) {
+            for bound in param.bounds() {
+                let names = bound.segments().collect::<Vec<_>>();
+                let names = names.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+                let Ok(Def::Trait(trait_def)) = self.module_tree.resolve_path(module, &names)
+                else {
+                    continue;
+                };
+                let ty = self.known(ty);
+                if impls::implements(self.db, self.module_tree, &ty, trait_def) == Some(false) {
+                    let message = format!(
+                        "the trait bound `{}: {}` is not satisfied",
+                        ty.display(self.module_tree),
+                        self.module_tree.trait_data(trait_def).name
+                    );
+                    self.error(node, message);
+                }
+            }
+        }
+    }
+
+    /// Checks the arguments of a call to a builtin against its signature, unless their
+    /// number is wrong, which the prelude check reports.
+    fn builtin_call(&mut self, builtin: Builtin, args: &[(Expr, Ty)]) -> Ty {
+        let signature = builtin.signature();
+        if builtin.check_arity(args.len()).is_ok() {
+            for ((arg, ty), &expected) in args.iter().zip(signature.params) {
+                let ty = self.known(ty);
+                let fits = match ty.kind() {
+                    Some(kind) => expected.overlaps(kind),
+                    None => expected == Kind::Any,
+                };
+                if !fits {
+                    let found = ty.name(self.module_tree);
+                    self.error(
+                        arg.syntax(),
+                        format!("expected {expected}, found `{found}`"),
+                    );
+                }
+            }
+        }
+
+        match signature.ret {
+            Kind::Unit => Ty::Unit,
+            // lengths are integers without a type
+            Kind::Int => Ty::Int(None),
+            Kind::Float => Ty::Float,
+            Kind::Bool => Ty::Bool,
+            Kind::Char => Ty::Char,
+            Kind::String => Ty::String,
+            Kind::Array => Ty::Array(Box::new(Ty::Unknown)),
+            Kind::Map => Ty::Map,
+            // `abs` keeps the type of its argument
+            Kind::Number => match args {
+                [(_, ty)] => match self.known(ty) {
+                    ty @ (Ty::Int(_) | Ty::Float) => ty,
+                    _ => Ty::Unknown,
+                },
+                _ => Ty::Unknown,
+            },
+            Kind::Any | Kind::Collection => Ty::Unknown,
+        }
+    }
+
+    fn field(&mut self, field: &FieldExpr) -> Ty {
+        let receiver = self.expr_opt(field.receiver().as_ref());
+        let Some(name) = field.field() else {
+            return Ty::Unknown;
+        };
+        match self.shallow(&receiver) {
+            Ty::Tuple(elements) => name
+                .text()
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| elements.get(index).cloned())
+                .unwrap_or(Ty::Unknown),
+            Ty::Enum(span, args) if self.module_tree.enum_data(span).is_struct => {
+                // the fields of a struct whose generic arguments aren't told are of any type
+                let generics = &self.module_tree.enum_data(span).generics;
+                let env: Vec<_> = match args.len() == generics.len() {
+                    true => generics.iter().cloned().zip(args).collect(),
+                    false => generics
+                        .iter()
+                        .map(|name| (name.clone(), Ty::Unknown))
+                        .collect(),
+                };
+                self.record_fields(span, 0)
+                    .into_iter()
+                    .find(|(field, _)| field == name.text())
+                    .map_or(Ty::Unknown, |(_, ty)| self.lower_in(span.file, &ty, &env))
+            }
+            _ => Ty::Unknown,
+        }
+    }
+
+    fn block(&mut self, block: &BlockExpr) -> Ty {
+        self.scopes.push(HashMap::new());
+        let tail = block.tail_expr();
+        let mut stmts = block.stmts().collect::<Vec<_>>();
+        if tail.is_some() {
+            stmts.pop();
+        }
+        self.stmts(stmts.into_iter());
+        let ty = match tail {
+            Some(tail) => self.expr(&tail),
+            // a block ending in a `return` doesn't give a value
+            None if block
+                .syntax()
+                .descendants()
+                .any(|node| node.kind() == SyntaxKind::ReturnStmt) =>
+            {
+                Ty::Unknown
+            }
+            None => Ty::Unit,
+        };
+        self.scopes.pop();
+        ty
+    }
+
+    fn function(&mut self, function: &FunctionExpr) -> Ty {
+        // named functions don't capture the locals around them
+        let scopes = if function.name().is_some() {
+            mem::replace(&mut self.scopes, vec![HashMap::new()])
+        } else {
+            self.scopes.push(HashMap::new());
+            Vec::new()
+        };
+        self.bind_params(
+            function
+                .param_list()
+                .into_iter()
+                .flat_map(|list| list.params()),
+        );
+
+        let return_type = function
+            .return_type()
+            .map(|ty| (ty.clone(), self.lower(self.file, &ty)));
+        self.returns.push(return_type.clone());
+        if let Some(body) = function.body() {
+            let found = self.block(&body);
+            if let Some((ty, expected)) = return_type {
+                let node = body
+                    .tail_expr()
+                    .map_or(body.syntax().clone(), |tail| tail.syntax().clone());
+                self.expect(&expected, &ty.syntax().to_string(), &node, &found);
+            }
+        }
+        self.returns.pop();
+
+        if function.name().is_some() {
+            self.scopes = scopes;
+        } else {
+            self.scopes.pop();
+        }
+        Ty::Function
+    }
+
+    fn closure(&mut self, closure: &ClosureExpr) -> Ty {
+        self.scopes.push(HashMap::new());
+        self.bind_params(
+            closure
+                .param_list()
+                .into_iter()
+                .flat_map(|list| list.params()),
+        );
+
+        let return_type = closure
+            .return_type()
+            .map(|ty| (ty.clone(), self.lower(self.file, &ty)));
+        self.returns.push(return_type.clone());
+        if let Some(body) = closure.body() {
+            let found = self.expr(&body);
+            if let Some((ty, expected)) = return_type {
+                self.expect(&expected, &ty.syntax().to_string(), body.syntax(), &found);
+            }
+        }
+        self.returns.pop();
+
+        self.scopes.pop();
+        Ty::Function
+    }
+
+    fn bind_params(&mut self, params: impl Iterator<Item = Param>) {
+        for param in params {
+            if let Some(name) = param.name() {
+                let ty = param
+                    .ty()
+                    .map_or(Ty::Unknown, |ty| self.lower(self.file, &ty));
+                self.bind(name.text(), ty);
+            }
+        }
+    }
+
+    fn if_expr(&mut self, if_expr: &IfExpr) -> Ty {
+        let condition = self.expr_opt(if_expr.condition().as_ref());
+        let condition = self.known(&condition);
+        if !fits(&Ty::Bool, &condition) {
+            let found = condition.name(self.module_tree);
+            self.error(
+                if_expr.syntax(),
+                format!("expected `bool`, found `{found}`"),
+            );
+        }
+
+        let then_ty = match if_expr.then_branch() {
+            Some(block) => self.block(&block),
+            None => Ty::Unknown,
+        };
+        let else_ty = match if_expr.else_branch() {
+            Some(ElseBranch::Block(block)) => self.block(&block),
+            Some(ElseBranch::If(if_expr)) => self.if_expr(&if_expr),
+            None => Ty::Unit,
+        };
+        self.join(then_ty, else_ty)
+    }
+
+    fn index(&mut self, index: &IndexExpr) -> Ty {
+        let base = self.expr_opt(index.base().as_ref());
+        let base = self.known(&base);
+        let index_ty = self.expr_opt(index.index().as_ref());
+        let index_ty = self.known(&index_ty);
+        if !matches!(base, Ty::Array(_) | Ty::Unknown) {
+            let message = format!(
+                "cannot index into a value of type `{}`",
+                base.name(self.module_tree)
+            );
+            self.error(index.syntax(), message);
+        } else if !fits(&Ty::Int(None), &index_ty) {
+            let message = format!(
+                "array indices must be integers, found `{}`",
+                index_ty.name(self.module_tree)
+            );
+            self.error(index.syntax(), message);
+        }
+        Ty::Unknown
+    }
+
+    fn match_expr(&mut self, match_expr: &MatchExpr) -> Ty {
+        self.expr_opt(match_expr.scrutinee().as_ref());
+
+        let mut ty = None;
+        for arm in match_expr
+            .arm_list()
+            .into_iter()
+            .flat_map(|list| list.arms())
+        {
+            self.scopes.push(HashMap::new());
+            if let Some(pattern) = arm.pattern() {
+                self.bind_pattern(&pattern, Ty::Unknown);
+            }
+            if let Some(guard) = arm.guard() {
+                let condition = self.expr_opt(guard.condition().as_ref());
+                let condition = self.known(&condition);
+                if !fits(&Ty::Bool, &condition) {
+                    let found = condition.name(self.module_tree);
+                    self.error(guard.syntax(), format!("expected `bool`, found `{found}`"));
+                }
+            }
+            let arm_ty = self.expr_opt(arm.expr().as_ref());
+            self.scopes.pop();
+
+            ty = Some(match ty {
+                Some(ty) => self.join(ty, arm_ty),
+                None => arm_ty,
+            });
+        }
+        ty.unwrap_or(Ty::Unknown)
+    }
+
+    /// Checks a record against the fields of the struct or the variant it builds, which
+    /// must all be given once.
+    fn record(&mut self, record: &RecordExpr) -> Ty {
+        let segments = record.segments().collect::<Vec<_>>();
+        let path = segments
+            .iter()
+            .map(SyntaxToken::text)
+            .collect::<Vec<_>>()
+            .join("::");
+        let def = match self.resolve_item(record.syntax(), &segments) {
+            Ok(Def::Struct(span)) => Some((span, 0)),
+            Ok(Def::Variant(span, index)) => Some((span, index)),
+            Err(ResolveError::Private(index)) => {
+                let message = format!("`{}` is private", segments[index].text());
+                self.error(record.syntax(), message);
+                None
+            }
+            _ => {
+                let message = format!("cannot find struct or variant `{path}` in this scope");
+                self.error(record.syntax(), message);
+                None
+            }
+        };
+        let names = match def {
+            Some((span, index)) => {
+                let data = self.module_tree.enum_data(span);
+                match &data.variants[index].fields {
+                    VariantFields::Record(names) => Some(names.clone()),
+                    fields => {
+                        let kind = match fields {
+                            VariantFields::Unit => "unit",
+                            _ => "tuple",
+                        };
+                        let message =
+                            format!("expected struct variant, found {kind} variant `{path}`");
+                        self.error(record.syntax(), message);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        let declared = def.map_or_else(Vec::new, |(span, index)| self.record_fields(span, index));
+        let env = match def {
+            Some((span, _)) => {
+                let env = self.enum_env(record.syntax(), span, record.generic_arg_list());
+                let types = declared
+                    .iter()
+                    .map(|(_, ty)| ty.clone())
+                    .collect::<Vec<_>>();
+                self.erase(&env, span.file, &types);
+                env
+            }
+            None => Vec::new(),
+        };
+
+        let mut given = Vec::new();
+        for field in record
+            .field_list()
+            .into_iter()
+            .flat_map(|list| list.fields())
+        {
+            let Some(name) = field.name() else {
+                continue;
+            };
+            // `field` is short for `field: field`
+            let (node, found) = match field.expr() {
+                Some(expr) => {
+                    let ty = self.expr(&expr);
+                    (expr.syntax().clone(), ty)
+                }
+                None => {
+                    let ty = self.lookup(name.text()).cloned().unwrap_or(Ty::Unknown);
+                    (field.syntax().clone(), ty)
+                }
+            };
+            let (Some((span, _)), Some(names)) = (def, &names) else {
+                continue;
+            };
+            if !names.iter().any(|other| other == name.text()) {
+                let kind = if self.module_tree.enum_data(span).is_struct {
+                    "struct"
+                } else {
+                    "variant"
+                };
+                let message = format!("{kind} `{path}` has no field named `{name}`");
+                self.error(field.syntax(), message);
+                continue;
+            }
+            if given.contains(&name.text().to_string()) {
+                let message = format!("field `{name}` specified more than once");
+                self.error(field.syntax(), message);
+                continue;
+            }
+            given.push(name.text().to_string());
+
+            if let Some((_, ty)) = declared.iter().find(|(field, _)| field == name.text()) {
+                let expected = self.lower_in(span.file, ty, &env);
+                self.expect_in(&expected, ty, &env, &node, &found);
+            }
+        }
+
+        let missing = names
+            .iter()
+            .flatten()
+            .filter(|name| !given.contains(name))
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let message = format!(
+                "missing field{} {} in initializer of `{path}`",
+                if missing.len() == 1 { "" } else { "s" },
+                missing.join(", ")
+            );
+            self.error(record.syntax(), message);
+        }
+        if let Some((span, _)) = def {
+            let list = self.generic_params(span);
+            self.check_bounds(record.syntax(), list, span.file, &env);
+        }
+
+        def.map_or(Ty::Unknown, |(span, _)| enum_ty(span, env))
+    }
+
+    /// Binds the names of a pattern matching values of type `ty`.
+    fn bind_pattern(&mut self, pattern: &Pattern, ty: Ty) {
+        match (pattern, self.shallow(&ty)) {
+            (Pattern::Ident(ident), ty) => {
+                if let Some(name) = ident.name() {
+                    self.bind(name.text(), ty);
+                }
+            }
+            (Pattern::Tuple(tuple), Ty::Tuple(types))
+                if !tuple
+                    .patterns()
+                    .any(|pattern| matches!(pattern, Pattern::Rest(_))) =>
+            {
+                for (pattern, ty) in tuple.patterns().zip(types) {
+                    self.bind_pattern(&pattern, ty);
+                }
+            }
+            // the types of the payloads of enums aren't told, nor the elements of arrays
+            (pattern, _) => {
+                for node in pattern.syntax().descendants() {
+                    let name = if let Some(ident) = IdentPattern::cast(node.clone()) {
+                        ident.name()
+                    } else {
+                        FieldPattern::cast(node)
+                            .filter(|field| field.pattern().is_none())
+                            .and_then(|field| field.name())
+                    };
+                    if let Some(name) = name {
+                        self.bind(name.text(), Ty::Unknown);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks that a value of type `found` has the annotated type `expected`, written as
+    /// `text`, or that the integer literals of `node` fit in it. The generic arguments the
+    /// types are made of are inferred from each other.
+    fn expect(&mut self, expected: &Ty, text: &str, node: &SyntaxNode, found: &Ty) {
+        let known = self.known(expected);
+        if let Some(ty) = Expr::cast(node.clone()).and_then(|expr| out_of_range(&known, &expr)) {
+            self.error(node, format!("literal out of range for `{}`", ty.name()));
+        } else if !self.unify(expected, found) {
+            let found = self.known(found);
+            let message = format!(
+                "mismatched types: expected `{text}`, found `{}`",
+                found.name(self.module_tree)
+            );
+            self.error(node, message);
+        }
+    }
+
+    /// [`Self::expect`] for an annotation `ty` lowered with the generic arguments `env`,
+    /// which is written with the types they stand for if it refers to them.
+    fn expect_in(
+        &mut self,
+        expected: &Ty,
+        ty: &Type,
+        env: &[(String, Ty)],
+        node: &SyntaxNode,
+        found: &Ty,
+    ) {
+        let is_generic = ty
+            .syntax()
+            .descendants()
+            .filter_map(PathType::cast)
+            .filter(|path| path.segments().count() == 1)
+            .filter_map(|path| path.name())
+            .any(|name| env.iter().any(|(param, _)| param == name.text()));
+        // the generic arguments are told by the arguments before
+        let text = match is_generic {
+            true => self.known(expected).display(self.module_tree),
+            false => ty.syntax().to_string(),
+        };
+        self.expect(expected, &text, node, found);
+    }
+
+    /// Lowers a type annotation written in `file`, with generic parameters and the types
+    /// which aren't items of the program, such as host types, being of any type.
+    fn lower(&self, file: FileId, ty: &Type) -> Ty {
+        self.lower_in(file, ty, &[])
+    }
+
+    /// [`Self::lower`] for the annotations of a generic item, in which its generic
+    /// parameters stand for the types in `env`.
+    fn lower_in(&self, file: FileId, ty: &Type, env: &[(String, Ty)]) -> Ty {
+        match ty {
+            Type::Path(path) => self.lower_path(file, path, env),
+            Type::Array(array) => {
+                let element = array.element_type();
+                Ty::Array(Box::new(
+                    element.map_or(Ty::Unknown, |ty| self.lower_in(file, &ty, env)),
+                ))
+            }
+            Type::Slice(slice) => {
+                let element = slice.element_type();
+                Ty::Array(Box::new(
+                    element.map_or(Ty::Unknown, |ty| self.lower_in(file, &ty, env)),
+                ))
+            }
+            Type::Fn(_) => Ty::Function,
+            Type::Tuple(tuple) => {
+                let mut fields = tuple
+                    .fields()
+                    .map(|ty| self.lower_in(file, &ty, env))
+                    .collect::<Vec<_>>();
+                match fields.len() {
+                    0 => Ty::Unit,
+                    1 if tuple.is_paren() => fields.remove(0),
+                    _ => Ty::Tuple(fields),
+                }
+            }
+            // references are the values themselves at runtime
+            Type::Ref(reference) => reference
+                .ty()
+                .map_or(Ty::Unknown, |ty| self.lower_in(file, &ty, env)),
+            Type::Optional(optional) => {
+                let module = self.module_at(file, optional.syntax());
+                match self.module_tree.resolve_path(module, &["Option"]) {
+                    Ok(Def::Enum(span) | Def::Struct(span)) => {
+                        let inner = optional
+                            .ty()
+                            .map_or(Ty::Unknown, |ty| self.lower_in(file, &ty, env));
+                        let args = match self.module_tree.enum_data(span).generics.len() {
+                            1 => vec![inner],
+                            _ => Vec::new(),
+                        };
+                        Ty::Enum(span, args)
+                    }
+                    _ => Ty::Unknown,
+                }
+            }
+        }
+    }
+
+    fn lower_path(&self, file: FileId, path: &PathType, env: &[(String, Ty)]) -> Ty {
+        let segments = path.segments().collect::<Vec<_>>();
+        let names = segments
+            .iter()
+            .map(|segment| segment.text())
+            .collect::<Vec<_>>();
+
+        if let [name] = names.as_slice() {
+            if let Some((_, ty)) = env.iter().find(|(param, _)| param == name) {
+                return ty.clone();
+            }
+            if is_generic(path.syntax(), name) {
+                return Ty::Unknown;
+            }
+            if let Some(ty) = primitive(name) {
+                return ty;
+            }
+        }
+        let module = self.module_at(file, path.syntax());
+        match self.module_tree.resolve_path(module, &names) {
+            Ok(Def::Enum(span) | Def::Struct(span)) => {
+                let args = path
+                    .generic_arg_list()
+                    .into_iter()
+                    .flat_map(|list| list.types())
+                    .map(|ty| self.lower_in(file, &ty, env))
+                    .collect::<Vec<_>>();
+                if args.len() == self.module_tree.enum_data(span).generics.len() {
+                    Ty::Enum(span, args)
+                } else {
+                    Ty::Enum(span, Vec::new())
+                }
+            }
+            // enums named `Map` take precedence over the builtin type
+            Err(_) if names == ["Map"] => Ty::Map,
+            _ => Ty::Unknown,
+        }
+    }
+
+    /// The generic parameters of the item used at `node`, a function, an enum or a struct as
+    /// told by `kind`, named `name`, along with the types they stand for: those written
+    /// after its path in `list`, or else variables to infer.
+    fn instantiate(
+        &mut self,
+        node: &SyntaxNode,
+        list: Option<GenericArgList>,
+        (kind, name): (&'static str, &str),
+        generics: Vec<String>,
+    ) -> Vec<(String, Ty)> {
+        let args = match list {
+            Some(list) => {
+                let types = list
+                    .types()
+                    .map(|ty| self.lower(self.file, &ty))
+                    .collect::<Vec<_>>();
+                if types.len() == generics.len() {
+                    types
+                } else {
+                    let message = generic_count_mismatch(kind, name, generics.len(), types.len());
+                    self.error(list.syntax(), message);
+                    vec![Ty::Unknown; generics.len()]
+                }
+            }
+            None => generics
+                .iter()
+                .map(|_| {
+                    self.vars.push(None);
+                    Ty::Var(self.vars.len() - 1)
+                })
+                .collect(),
+        };
+        if !generics.is_empty() {
+            self.uses.push(Use {
+                node: node.clone(),
+                kind,
+                name: name.to_string(),
+                generics: generics.clone(),
+                args: args.clone(),
+            });
+        }
+        generics.into_iter().zip(args).collect()
+    }
+
+    /// [`Self::instantiate`] for the enum or struct at `def`.
+    fn enum_env(
+        &mut self,
+        node: &SyntaxNode,
+        def: Span,
+        list: Option<GenericArgList>,
+    ) -> Vec<(String, Ty)> {
+        let data = self.module_tree.enum_data(def);
+        let kind = if data.is_struct { "struct" } else { "enum" };
+        let (name, generics) = (data.name.clone(), data.generics.clone());
+        self.instantiate(node, list, (kind, &name), generics)
+    }
+
+    /// Gives up inferring the generic arguments of `env` which the annotations written in
+    /// `file` refer to where their types aren't told, such as in function types, as the
+    /// types of the values there are only told at runtime.
+    fn erase(&mut self, env: &[(String, Ty)], file: FileId, annotations: &[Type]) {
+        let lowered = annotations
+            .iter()
+            .map(|ty| self.lower_in(file, ty, env))
+            .collect::<Vec<_>>();
+        for (param, ty) in env {
+            let Ty::Var(var) = ty else {
+                continue;
+            };
+            let is_referred = annotations.iter().any(|ty| {
+                ty.syntax()
+                    .descendants()
+                    .filter_map(PathType::cast)
+                    .filter(|path| path.segments().count() == 1)
+                    .filter_map(|path| path.name())
+                    .any(|name| name.text() == param)
+            });
+            if is_referred && !lowered.iter().any(|ty| ty.contains(*var)) {
+                self.vars[*var] = Some(Ty::Unknown);
+            }
+        }
+    }
+
+    /// Unifies the types, binding the variables of each to the parts of the other. Nothing
+    /// is bound if they don't unify.
+    fn unify(&mut self, expected: &Ty, found: &Ty) -> bool {
+        let saved = self.vars.clone();
+        let unified = self.unify_parts(expected, found);
+        if !unified {
+            self.vars = saved;
+        }
+        unified
+    }
+
+    fn unify_parts(&mut self, expected: &Ty, found: &Ty) -> bool {
+        match (self.shallow(expected), self.shallow(found)) {
+            (Ty::Var(a), Ty::Var(b)) if a == b => true,
+            (Ty::Var(a), Ty::Var(b)) => {
+                // a variable whose type is only partly told takes the type of the other
+                let (var, other) = match (&self.vars[a], &self.vars[b]) {
+                    (None | Some(Ty::Unknown), _) => (a, b),
+                    (_, None | Some(Ty::Unknown)) => (b, a),
+                    _ => (a, b),
+                };
+                self.vars[var] = Some(Ty::Var(other));
+                true
+            }
+            (Ty::Var(var), ty) | (ty, Ty::Var(var)) => {
+                if ty.contains(var) {
+                    return false;
+                }
+                match (&self.vars[var], &ty) {
+                    (_, Ty::Unknown) => {
+                        self.vars[var].get_or_insert(Ty::Unknown);
+                        true
+                    }
+                    (Some(Ty::Int(None)), ty) if !matches!(ty, Ty::Int(_)) => false,
+                    _ => {
+                        self.vars[var] = Some(ty);
+                        true
+                    }
+                }
+            }
+            (Ty::Unknown, _) | (_, Ty::Unknown) => true,
+            (Ty::Int(expected), Ty::Int(found)) => {
+                expected.is_none() || found.is_none() || expected == found
+            }
+            (Ty::Array(expected), Ty::Array(found)) => self.unify_parts(&expected, &found),
+            (Ty::Tuple(expected), Ty::Tuple(found)) => {
+                expected.len() == found.len()
+                    && expected
+                        .iter()
+                        .zip(&found)
+                        .all(|(expected, found)| self.unify_parts(expected, found))
+            }
+            (Ty::Enum(expected, expected_args), Ty::Enum(found, found_args)) => {
+                expected == found
+                    && (expected_args.is_empty()
+                        || found_args.is_empty()
+                        || expected_args
+                            .iter()
+                            .zip(&found_args)
+                            .all(|(expected, found)| self.unify_parts(expected, found)))
+            }
+            (expected, found) => expected == found,
+        }
+    }
+
+    /// The type, with the variables it is made of replaced by their types as far as they
+    /// are told. The variables whose types are partly told, only as some integer or as
+    /// any type, are kept so that the rest can still be told.
+    fn shallow(&self, ty: &Ty) -> Ty {
+        let mut ty = ty;
+        while let Ty::Var(var) = ty {
+            match &self.vars[*var] {
+                Some(Ty::Unknown | Ty::Int(None)) | None => break,
+                Some(bound) => ty = bound,
+            }
+        }
+        ty.clone()
+    }
+
+    /// The type as far as it's told, with the variables it's made of replaced by their
+    /// types, or by [`Ty::Unknown`] if they aren't told.
+    fn known(&self, ty: &Ty) -> Ty {
+        match self.shallow(ty) {
+            Ty::Var(var) => self.vars[var].clone().unwrap_or(Ty::Unknown),
+            Ty::Array(element) => Ty::Array(Box::new(self.known(&element))),
+            Ty::Tuple(types) => Ty::Tuple(types.iter().map(|ty| self.known(ty)).collect()),
+            Ty::Enum(span, args) => Ty::Enum(span, args.iter().map(|ty| self.known(ty)).collect()),
+            ty => ty,
+        }
+    }
+
+    /// The type of the values of either of two branches.
+    fn join(&mut self, a: Ty, b: Ty) -> Ty {
+        match (a, b) {
+            (a, b) if a == b => a,
+            (Ty::Unknown, _) | (_, Ty::Unknown) => Ty::Unknown,
+            (Ty::Int(_), Ty::Int(_)) => Ty::Int(None),
+            (a, b) if self.unify(&a, &b) => a,
+            _ => Ty::Unknown,
+        }
+    }
+
+    /// Reports the uses of generic items whose generic arguments can't be inferred, and
+    /// returns the generic arguments of the other uses by their spans, with the parts not
+    /// told being of any type.
+    fn finish(mut self) -> HashMap<Span, Vec<Ty>> {
+        let uses = mem::take(&mut self.uses);
+        let mut reported = Vec::new();
+        for use_ in &uses {
+            let unknown = use_
+                .generics
+                .iter()
+                .zip(&use_.args)
+                .find_map(|(param, arg)| match self.shallow(arg) {
+                    Ty::Var(var) if self.vars[var].is_none() => Some((param, var)),
+                    _ => None,
+                });
+            if let Some((param, var)) = unknown {
+                if !reported.contains(&var) {
+                    reported.push(var);
+                    let message = format!(
+                        "type annotations needed: cannot infer type of the type parameter `{param}` declared on the {} `{}`",
+                        use_.kind, use_.name
+                    );
+                    self.error(&use_.node, message);
+                }
+                continue;
+            }
+
+            let args = use_.args.iter().map(|arg| self.known(arg)).collect();
+            let span = Span::new(self.file, use_.node.text_range());
+            self.type_args.insert(span, args);
+        }
+        self.type_args
+    }
+
+    /// The item the path at `node` names, `None` if its first segment is a local.
+    fn resolve(&self, node: &SyntaxNode, segments: &[SyntaxToken]) -> Option<Def> {
+        if let [name] = segments {
+            let name = name.text();
+            if self.lookup(name).is_some()
+                || is_local(node, name)
+                || prelude::is_local_function(node, name)
+            {
+                return None;
+            }
+        }
+        self.resolve_item(node, segments).ok()
+    }
+
+    /// The item the path at `node` names, locals aside.
+    fn resolve_item(
+        &self,
+        node: &SyntaxNode,
+        segments: &[SyntaxToken],
+    ) -> Result<Def, ResolveError> {
+        let names = segments
+            .iter()
+            .map(|segment| segment.text())
+            .collect::<Vec<_>>();
+        let module = self.module_at(self.file, node);
+        self.module_tree.resolve_path(module, &names)
+    }
+
+    /// The item a path used as a value names, reporting the paths `Enum::name` naming
+    /// neither a variant of the enum nor a function of its impls.
+    fn value_path(&mut self, variable: &VariableRef) -> Option<Def> {
+        let segments = variable.segments().collect::<Vec<_>>();
+        let def = self.resolve(variable.syntax(), &segments);
+        if def.is_some() {
+            return def;
+        }
+        let (name, prefix) = segments.split_last()?;
+        if prefix.is_empty() {
+            return None;
+        }
+        if let Ok(Def::Enum(span) | Def::Struct(span)) =
+            self.resolve_item(variable.syntax(), prefix)
+        {
+            if !impls::has_associated_function(self.db, self.module_tree, span, name.text()) {
+                let path = segments.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+                let message = format!("cannot find value `{}` in this scope", path.join("::"));
+                self.error(variable.syntax(), message);
+            }
+        }
+        None
+    }
+
+    fn module_at(&self, file: FileId, node: &SyntaxNode) -> modules::ModuleId {
+        self.module_tree
+            .module_at(Span::new(file, node.text_range()))
+    }
+
+    /// The declaration of the item whose name is at `span`.
+    fn item<N: AstNode>(&self, span: Span) -> Option<N> {
+        crate::item(self.db, span)
+    }
+
+    /// The generic parameters of the enum or the struct whose name is at `def`.
+    fn generic_params(&self, def: Span) -> Option<GenericParamList> {
+        if self.module_tree.enum_data(def).is_struct {
+            self.item::<StructStmt>(def)?.generic_param_list()
+        } else {
+            self.item::<EnumStmt>(def)?.generic_param_list()
+        }
+    }
+
+    fn variant(&self, def: Span, index: usize) -> Option<Variant> {
+        self.item::<EnumStmt>(def)?
+            .variant_list()?
+            .variants()
+            .nth(index)
+    }
+
+    /// The names and the annotated types of the fields of a record variant, or of a struct
+    /// with `index` 0.
+    fn record_fields(&self, def: Span, index: usize) -> Vec<(String, Type)> {
+        let list = if self.module_tree.enum_data(def).is_struct {
+            self.item::<StructStmt>(def)
+                .and_then(|stmt| stmt.record_fields())
+        } else {
+            self.variant(def, index)
+                .and_then(|variant| variant.record_fields())
+        };
+        list.into_iter()
+            .flat_map(|list| list.fields())
+            .filter_map(|field| Some((field.name()?.text().to_string(), field.ty()?)))
+            .collect()
+    }
+
+    fn bind(&mut self, name: &str, ty: Ty) {
+        let scope = self.scopes.last_mut().expect("there's always a scope");
+        scope.insert(name.to_string(), ty);
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Ty> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn error(&mut self, node: &SyntaxNode, message: String) {
+        let span = Span::new(self.file, node.text_range());
+        self.diagnostics.push(Diagnostic::error(span, message));
+    }
+}
+
+/// Whether values of type `found` have type `expected`.
+fn fits(expected: &Ty, found: &Ty) -> bool {
+    match (expected, found) {
+        (Ty::Unknown, _) | (_, Ty::Unknown) => true,
+        (Ty::Int(expected), Ty::Int(found)) => {
+            expected.is_none() || found.is_none() || expected == found
+        }
+        (Ty::Tuple(expected), Ty::Tuple(found)) => {
+            expected.len() == found.len()
+                && expected
+                    .iter()
+                    .zip(found)
+                    .all(|(expected, found)| fits(expected, found))
+        }
+        _ => expected == found,
+    }
+}
+
+/// The type of an enum or a struct whose generic arguments are those of `env`.
+fn enum_ty(def: Span, env: Vec<(String, Ty)>) -> Ty {
+    Ty::Enum(def, env.into_iter().map(|(_, ty)| ty).collect())
+}
+
+/// The type of the integer operands of `op`: an integer without a type takes the type of
+/// the other operand, which the value of a literal must fit in.
+fn common_ty(
+    op: SyntaxKind,
+    (a, a_ty): (Option<i128>, Option<IntTy>),
+    (b, b_ty): (Option<i128>, Option<IntTy>),
+) -> Result<Option<IntTy>, String> {
+    let (value, ty) = match (a_ty, b_ty) {
+        (Some(a_ty), Some(b_ty)) if a_ty != b_ty => {
+            return Err(format!(
+                "cannot apply {op} to `{}` and `{}`",
+                a_ty.name(),
+                b_ty.name()
+            ))
+        }
+        (None, Some(ty)) => (a, ty),
+        (Some(ty), None) => (b, ty),
+        _ => return Ok(a_ty),
+    };
+
+    if value.is_some_and(|value| !ty.contains(value)) {
+        return Err(format!("literal out of range for `{}`", ty.name()));
+    }
+    Ok(Some(ty))
+}
+
+/// The value of an integer literal without a suffix, negated if it follows a `-`.
+fn int_value(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Literal(literal) => {
+            let token = literal.token()?;
+            match literal::number(token.text()) {
+                Ok(Number::Int(value, None)) if token.kind() == SyntaxKind::Number => {
+                    Some(value.into())
+                }
+                _ => None,
+            }
+        }
+        Expr::Prefix(prefix) if prefix.op()?.kind() == SyntaxKind::Minus => {
+            match prefix.operand()? {
+                operand @ Expr::Literal(_) => int_value(&operand).map(|value| -value),
+                _ => None,
+            }
+        }
+        Expr::Paren(paren) => int_value(&paren.expr()?),
+        _ => None,
+    }
+}
+
+/// The integer type of `expected` which an integer literal of `expr` doesn't fit in.
+fn out_of_range(expected: &Ty, expr: &Expr) -> Option<IntTy> {
+    match (expected, expr) {
+        (Ty::Int(Some(ty)), expr) => int_value(expr)
+            .filter(|&value| !ty.contains(value))
+            .map(|_| *ty),
+        (Ty::Tuple(types), Expr::Tuple(tuple)) => types
+            .iter()
+            .zip(tuple.elements())
+            .find_map(|(ty, element)| out_of_range(ty, &element)),
+        (_, Expr::Paren(paren)) => out_of_range(expected, &paren.expr()?),
+        _ => None,
+    }
+}
+
+/// Whether `name` is a generic parameter in scope at `node`, of a function, an impl or
+/// the type being declared.
+pub(crate) fn is_generic(node: &SyntaxNode, name: &str) -> bool {
+    node.ancestors()
+        .filter_map(|ancestor| ancestor.children().find_map(GenericParamList::cast))
+        .flat_map(|list| list.params())
+        .any(|param| param.name().is_some_and(|param| param.text() == name))
+}
+
+fn generic_names(list: Option<GenericParamList>) -> Vec<String> {
+    list.iter()
+        .flat_map(|list| list.params())
+        .map(|param| {
+            param
+                .name()
+                .map_or(String::new(), |name| name.text().to_string())
+        })
+        .collect()
+}
+
+/// The error for the wrong number of generic arguments given to a function or an enum.
+fn generic_count_mismatch(kind: &str, name: &str, expected: usize, found: usize) -> String {
+    format!(
+        "{kind} `{name}` takes {expected} generic argument{} but {found} generic argument{} {} supplied",
+        if expected == 1 { "" } else { "s" },
+        if found == 1 { "" } else { "s" },
+        if found == 1 { "was" } else { "were" },
+    )
+}
+
+/// The primitive type `name`, or `None` if `name` isn't primitive.
+pub(crate) fn primitive(name: &str) -> Option<Ty> {
+    if let Some(ty) = IntTy::from_name(name) {
+        return Some(Ty::Int(Some(ty)));
+    }
+    let ty = match name {
+        "f32" | "f64" => Ty::Float,
+        "bool" => Ty::Bool,
+        "char" => Ty::Char,
+        "str" | "String" => Ty::String,
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// The operator a compound assignment such as `+=` applies, `None` for `=`.
+fn compound_assignment(op: SyntaxKind) -> Option<SyntaxKind> {
+    let op = match op {
+        SyntaxKind::PlusEqual => SyntaxKind::Plus,
+        SyntaxKind::MinusEqual => SyntaxKind::Minus,
+        SyntaxKind::StarEqual => SyntaxKind::Star,
+        SyntaxKind::SlashEqual => SyntaxKind::Slash,
+        SyntaxKind::ModuloEqual => SyntaxKind::Modulo,
+        SyntaxKind::AmpersandEqual => SyntaxKind::Ampersand,
+        SyntaxKind::PipeEqual => SyntaxKind::Pipe,
+        SyntaxKind::CaretEqual => SyntaxKind::Caret,
+        SyntaxKind::ShiftLeftEqual => SyntaxKind::ShiftLeft,
+        SyntaxKind::ShiftRightEqual => SyntaxKind::ShiftRight,
+        _ => return None,
+    };
+    Some(op)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check_source;
+
+    #[test]
+    fn valid_types() {
+        let source = r#"
+            enum Shape { Circle(f64), Rect { w: u8, h: u8 } }
+            struct Point<T> { x: T, y: i32 }
+            fn area(shape: Shape) -> f64 {
+                match shape {
+                    Shape::Circle(r) => r * r * 3.14,
+                    Shape::Rect { w, h } => { let a: u8 = w * h; 1.0 }
+                }
+            }
+            fn id<T>(x: T) -> T { x }
+            fn main() {
+                let a: u8 = 255;
+                let b = a / 2 - 1;
+                let c = -128i8;
+                let mut d = 1;
+                d = 1.5;
+                d += 2.0;
+                let (e, f) = (1u16, "s");
+                let g = e << 3u8 == 8 && f + "t" != "st";
+                let p = Point { x: "any", y: -1 };
+                let q = p.y + 1i32;
+                let r = id(1.5) + id(2.0);
+                let s = if g { 1u8 } else { 2 } + 3;
+                println(area(Shape::Rect { w: 2, h: 3 }) + sqrt(abs(-2.0)));
+                println(len("ab") + 1u8);
+            }
+        "#;
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn invalid_types() {
+        let source = r#"
+            enum Shape { Circle(f64), Rect { w: u8, h: u8 } }
+            struct Point { x: i32, y: i32 }
+            fn half(x: f64) -> f64 { x / 2 }
+            fn count() -> u8 { 256 }
+            fn main() {
+                1.5 & 2;
+                1i32 + 2i64;
+                let q: u8 = 300;
+                let r: (u8, bool) = (1, 2);
+                let s: String = 'c';
+                let t = 200u8 + -1;
+                let u = 128i8;
+                let v = -1u32;
+                let w = !1;
+                let x = 1;
+                sqrt(x);
+                Shape::Circle(1);
+                Shape::Rect { w: 1, h: 1000 };
+                let p = Point { x: 1.5, y: 2 };
+                p.x + 1i64;
+                half(1);
+                if x { 1 } else { 2 };
+                true || 1;
+                let mut y = 1u8;
+                y += 1.0;
+                x[0];
+            }
+        "#;
+        assert_eq!(
+            check_source(source),
+            [
+                "x / 2: cannot apply `/` to `float` and `integer`",
+                "256: literal out of range for `u8`",
+                "1.5 & 2: cannot apply `&` to `float` and `integer`",
+                "1i32 + 2i64: cannot apply `+` to `i32` and `i64`",
+                "300: literal out of range for `u8`",
+                "(1, 2): mismatched types: expected `(u8, bool)`, found `tuple`",
+                "'c': mismatched types: expected `String`, found `char`",
+                "200u8 + -1: literal out of range for `u8`",
+                "128i8: literal out of range for `i8`",
+                "-1u32: cannot apply unary operator `-` to `u32`",
+                "!1: cannot apply unary operator `!` to `integer`",
+                "x: expected `float`, found `integer`",
+                "1: mismatched types: expected `f64`, found `integer`",
+                "1000: literal out of range for `u8`",
+                "1.5: mismatched types: expected `i32`, found `float`",
+                "p.x + 1i64: cannot apply `+` to `i32` and `i64`",
+                "1: mismatched types: expected `f64`, found `integer`",
+                "if x { 1 } else { 2 }: expected `bool`, found `integer`",
+                "true || 1: expected `bool`, found `integer`",
+                "y += 1.0: cannot apply `+` to `u8` and `float`",
+                "x[0]: cannot index into a value of type `integer`",
+            ]
+        );
+    }
+
+    #[test]
+    fn construction() {
+        let source = r#"
+            enum Shape { Circle(f64), Rect { w: f64, h: f64 }, Empty }
+            struct P { x: i64, y: i64 }
+            impl Shape { fn unit() -> Shape { Shape::Empty } }
+            mod m { struct H { a: i64 } pub fn f(a: i64) {} }
+            fn main() {
+                Shape::Rect { w: 1.0 };
+                Shape::Rect { w: 1.0, h: 2.0, d: 3.0 };
+                Shape::Rect { w: 1.0, w: 2.0, h: 3.0 };
+                Shape::Circle(1.0, 2.0);
+                Shape::Square(1.0);
+                Shape::Empty(1);
+                Shape::Circle { r: 1.0 };
+                Shape::Rect(1.0);
+                let c = Shape::Circle;
+                P { x: 1 };
+                Q { x: 1 };
+                Shape::unit();
+                Shape::Rect { h: 1.0, w: 2.0 };
+                P { x: 1, y: 2 };
+                m::H { a: 1 };
+                m::f(1, 2);
+                m::f();
+            }
+        "#;
+        assert_eq!(
+            check_source(source),
+            [
+                "Shape::Rect { w: 1.0 }: missing field `h` in initializer of `Shape::Rect`",
+                "d: 3.0: variant `Shape::Rect` has no field named `d`",
+                "w: 2.0: field `w` specified more than once",
+                "Shape::Circle(1.0, 2.0): variant `Shape::Circle` takes 1 argument but 2 were supplied",
+                "Shape::Square: cannot find value `Shape::Square` in this scope",
+                "Shape::Empty(1): expected function, found unit variant `Shape::Empty`",
+                "Shape::Circle { r: 1.0 }: expected struct variant, found tuple variant `Shape::Circle`",
+                "Shape::Rect(1.0): expected function, found struct variant `Shape::Rect`",
+                "Shape::Circle: expected value, found tuple variant `Shape::Circle`",
+                "P { x: 1 }: missing field `y` in initializer of `P`",
+                "Q { x: 1 }: cannot find struct or variant `Q` in this scope",
+                "m::H { a: 1 }: `H` is private",
+                "m::f(1, 2): function `f` takes 1 argument but 2 were supplied",
+                "m::f(): function `f` takes 1 argument but 0 were supplied",
+            ]
+        );
+    }
+
+    #[test]
+    fn optional_types() {
+        let source = "
+            mod a { pub enum Option<T> { Some(T), None } fn f(x: i64?) {} }
+            fn g(c: i64?) -> [bool?] { [] }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "i64?: cannot find type `Option` in this scope",
+                "bool?: cannot find type `Option` in this scope",
+            ]
+        );
+    }
+
+    #[test]
+    fn generic_arguments() {
+        let source = "
+            enum Opt<T> { Some(T), None }
+            struct Pair<T> { a: T, b: T }
+            fn none<T>() -> Opt<T> { Opt::None }
+            fn id<T>(x: T) -> T { x }
+            fn take(o: Opt<i64>) {}
+            fn first<T>(items: [T]) -> T { items[0] }
+            fn main() {
+                let o: Opt<i64> = none();
+                take(none());
+                let p = none();
+                take(p);
+                let q = Pair { a: 1u8, b: 2 };
+                let r: [u8] = [first([]), q.a];
+                let s = if true { Opt::None } else { Opt::Some('c') };
+            }
+            fn errors() {
+                let o = none();
+                let p = Pair { a: 1, b: true };
+                let x = id::<bool>(3);
+                let y = id::<i64, bool>(3);
+                let z = id(2.0) + id(1);
+                take(Opt::Some(true));
+                let e = first([]);
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "true: mismatched types: expected `integer`, found `bool`",
+                "3: mismatched types: expected `bool`, found `integer`",
+                "<i64, bool>: function `id` takes 1 generic argument but 2 generic arguments were supplied",
+                "id(2.0) + id(1): cannot apply `+` to `float` and `integer`",
+                "Opt::Some(true): mismatched types: expected `Opt<i64>`, found `Opt`",
+                "none(): type annotations needed: cannot infer type of the type parameter `T` declared on the function `none`",
+                "first([]): type annotations needed: cannot infer type of the type parameter `T` declared on the function `first`",
+            ]
+        );
+    }
+}
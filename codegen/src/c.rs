@@ -0,0 +1,1347 @@
+//! Generation of C99 from the MIR. Every lamia function becomes a static C function whose
+//! blocks are joined by `goto`s, tuples and enums become structs, and the checks the
+//! interpreter does at runtime, such as overflow on arithmetic, call helpers which stop the
+//! program with the same messages.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use source_map::{SourceMap, Span};
+
+use crate::mir::{
+    BinaryOp, BlockId, FloatTy, Function, FunctionId, IntTy, Literal, LocalId, Operand, Output,
+    Place, Program, Projection, Rvalue, Statement, StatementKind, Terminator, TerminatorKind, Type,
+    UnaryOp,
+};
+
+const PRELUDE: &str = "\
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+";
+
+/// Names C reserves or the generated code uses, which locals are renamed not to shadow.
+const RESERVED: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+    "union", "unsigned", "void", "volatile", "while", "bool", "true", "false", "main", "abort",
+    "exit", "fmod", "fmodf", "fputc", "fputs", "fprintf", "fwrite", "memcmp", "stdout", "stderr",
+    "errno", "NULL", "EOF",
+];
+
+/// Generates a C99 program which runs `main` of `program`, printing its result unless it's
+/// `()`. The locations in runtime errors are taken from `source_map`.
+pub fn emit(program: &Program, source_map: &SourceMap) -> String {
+    let mut emitter = Emitter {
+        program,
+        source_map,
+        helpers: BTreeSet::new(),
+        type_names: HashMap::new(),
+        type_defs: Vec::new(),
+        defined: Vec::new(),
+        eq_types: HashSet::new(),
+        print_types: HashSet::new(),
+        function_names: Vec::new(),
+    };
+
+    let mut taken = HashSet::new();
+    for function in &program.functions {
+        let base = format!("fn_{}", identifier(&function.name.replace("::", "__")));
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while !taken.insert(name.clone()) {
+            name = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        emitter.function_names.push(name);
+    }
+
+    let prototypes = program
+        .functions
+        .iter()
+        .enumerate()
+        .map(|(index, function)| {
+            let id = FunctionId(index as u32);
+            format!("{};\n", emitter.signature(id, function, None))
+        })
+        .collect::<String>();
+    let functions = program
+        .functions
+        .iter()
+        .enumerate()
+        .map(|(index, function)| {
+            FunctionEmitter::new(&mut emitter, FunctionId(index as u32), function).emit()
+        })
+        .collect::<Vec<_>>();
+    let main = emitter.main();
+
+    let mut c = PRELUDE.to_string();
+    for def in &emitter.type_defs {
+        c.push('\n');
+        c.push_str(def);
+    }
+    for helper in &emitter.helpers {
+        c.push('\n');
+        c.push_str(&helper.definition());
+    }
+    for ty in emitter.defined.clone() {
+        if emitter.eq_types.contains(&ty) {
+            c.push('\n');
+            c.push_str(&emitter.eq_function(&ty));
+        }
+        if emitter.print_types.contains(&ty) {
+            c.push('\n');
+            c.push_str(&emitter.print_function(&ty));
+        }
+    }
+    c.push('\n');
+    c.push_str(&prototypes);
+    for function in functions {
+        c.push('\n');
+        c.push_str(&function);
+    }
+    c.push('\n');
+    c.push_str(&main);
+    c
+}
+
+/// The functions of the runtime, which are only generated when the program uses them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Helper {
+    /// `lm_panic_begin` and `lm_panic_end`, between which the message is written.
+    PanicParts,
+    Panic,
+    Arithmetic(Arithmetic, IntTy),
+    /// A shift of a value of the first type by an amount of the second type.
+    Shift(bool, IntTy, IntTy),
+    StrEq,
+    StrCmp,
+    PrintChar,
+    PrintStr,
+    PrintFloat,
+    PrintF32,
+    PrintF64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Neg,
+}
+
+impl Helper {
+    /// The helpers the definition of this one calls.
+    fn dependencies(self) -> &'static [Helper] {
+        match self {
+            Self::Panic | Self::Shift(..) => &[Self::PanicParts],
+            Self::Arithmetic(..) => &[Self::PanicParts, Self::Panic],
+            Self::PrintF32 | Self::PrintF64 => &[Self::PrintFloat],
+            _ => &[],
+        }
+    }
+
+    fn definition(self) -> String {
+        match self {
+            Self::PanicParts => "\
+static void lm_panic_begin(void) {
+    fflush(stdout);
+    fputs(\"error: \", stderr);
+}
+
+static void lm_panic_end(const char *at) {
+    fprintf(stderr, \"\\n  --> %s\\n\", at);
+    exit(EXIT_FAILURE);
+}
+"
+            .to_string(),
+            Self::Panic => "\
+static void lm_panic(const char *message, const char *at) {
+    lm_panic_begin();
+    fputs(message, stderr);
+    lm_panic_end(at);
+}
+"
+            .to_string(),
+            Self::Arithmetic(op, ty) => arithmetic_helper(op, ty),
+            Self::Shift(left, ty, amount) => shift_helper(left, ty, amount),
+            Self::StrEq => "\
+static bool lm_str_eq(lm_str a, lm_str b) {
+    return a.len == b.len && memcmp(a.ptr, b.ptr, a.len) == 0;
+}
+"
+            .to_string(),
+            Self::StrCmp => "\
+static int lm_str_cmp(lm_str a, lm_str b) {
+    int result = memcmp(a.ptr, b.ptr, a.len < b.len ? a.len : b.len);
+    if (result != 0) {
+        return result;
+    }
+    return a.len < b.len ? -1 : a.len > b.len;
+}
+"
+            .to_string(),
+            Self::PrintChar => "\
+static void lm_print_char(FILE *out, uint32_t c) {
+    if (c < 0x80) {
+        fputc((int)c, out);
+    } else if (c < 0x800) {
+        fputc((int)(0xc0 | c >> 6), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else if (c < 0x10000) {
+        fputc((int)(0xe0 | c >> 12), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else {
+        fputc((int)(0xf0 | c >> 18), out);
+        fputc((int)(0x80 | (c >> 12 & 0x3f)), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    }
+}
+"
+            .to_string(),
+            Self::PrintStr => "\
+static void lm_print_str(FILE *out, lm_str s) {
+    fwrite(s.ptr, 1, s.len, out);
+}
+"
+            .to_string(),
+            // floats are written like the interpreter writes them, with the fewest digits
+            // reading back as the same value and in scientific notation when very large or
+            // small
+            Self::PrintFloat => "\
+static void lm_print_float(FILE *out, const char *formatted) {
+    char digits[32];
+    int count = 0;
+    int exponent;
+    int i;
+    if (*formatted == '-') {
+        fputc('-', out);
+        formatted++;
+    }
+    for (; *formatted != 'e'; formatted++) {
+        if (*formatted != '.') {
+            digits[count++] = *formatted;
+        }
+    }
+    exponent = atoi(formatted + 1);
+    while (count > 1 && digits[count - 1] == '0') {
+        count--;
+    }
+
+    if (exponent < -4 || exponent >= 16) {
+        fputc(digits[0], out);
+        if (count > 1) {
+            fputc('.', out);
+            fwrite(digits + 1, 1, (size_t)(count - 1), out);
+        }
+        fprintf(out, \"e%d\", exponent);
+    } else if (exponent < 0) {
+        fputs(\"0.\", out);
+        for (i = -1; i > exponent; i--) {
+            fputc('0', out);
+        }
+        fwrite(digits, 1, (size_t)count, out);
+    } else {
+        for (i = 0; i <= exponent; i++) {
+            fputc(i < count ? digits[i] : '0', out);
+        }
+        fputc('.', out);
+        if (count > exponent + 1) {
+            fwrite(digits + exponent + 1, 1, (size_t)(count - exponent - 1), out);
+        } else {
+            fputc('0', out);
+        }
+    }
+}
+"
+            .to_string(),
+            Self::PrintF32 => float_printer("f32", "float", 9),
+            Self::PrintF64 => float_printer("f64", "double", 17),
+        }
+    }
+}
+
+fn float_printer(name: &str, c_type: &str, max_digits: u32) -> String {
+    format!(
+        "\
+static void lm_print_{name}(FILE *out, {c_type} x) {{
+    char formatted[32];
+    int precision;
+    if (isnan(x)) {{
+        fputs(\"NaN\", out);
+        return;
+    }}
+    if (isinf(x)) {{
+        fputs(x < 0 ? \"-inf\" : \"inf\", out);
+        return;
+    }}
+    if (x == 0) {{
+        fputs(signbit(x) ? \"-0.0\" : \"0.0\", out);
+        return;
+    }}
+    for (precision = 0; precision < {max_digits}; precision++) {{
+        snprintf(formatted, sizeof formatted, \"%.*e\", precision, (double)x);
+        if (({c_type})strtod(formatted, NULL) == x) {{
+            break;
+        }}
+    }}
+    lm_print_float(out, formatted);
+}}
+"
+    )
+}
+
+fn arithmetic_helper(op: Arithmetic, ty: IntTy) -> String {
+    let c_type = int_type(ty);
+    let (min, max) = int_limits(ty);
+    let name = ty.name();
+    let signed = ty.is_signed();
+
+    let (verb, checks, result) = match op {
+        Arithmetic::Add if signed => (
+            "add",
+            vec![(format!("(b > 0 && a > {max} - b) || (b < 0 && a < {min} - b)"), "attempt to add with overflow")],
+            "a + b",
+        ),
+        Arithmetic::Add => (
+            "add",
+            vec![(format!("a > {max} - b"), "attempt to add with overflow")],
+            "a + b",
+        ),
+        Arithmetic::Sub if signed => (
+            "sub",
+            vec![(format!("(b < 0 && a > {max} + b) || (b > 0 && a < {min} + b)"), "attempt to subtract with overflow")],
+            "a - b",
+        ),
+        Arithmetic::Sub => (
+            "sub",
+            vec![("a < b".to_string(), "attempt to subtract with overflow")],
+            "a - b",
+        ),
+        Arithmetic::Mul if signed => (
+            "mul",
+            vec![(
+                format!("a > 0 ? (b > 0 ? a > {max} / b : b < {min} / a) : (b > 0 ? a < {min} / b : a != 0 && b < {max} / a)"),
+                "attempt to multiply with overflow",
+            )],
+            "a * b",
+        ),
+        Arithmetic::Mul => (
+            "mul",
+            vec![(format!("b != 0 && a > {max} / b"), "attempt to multiply with overflow")],
+            "a * b",
+        ),
+        Arithmetic::Div => {
+            let mut checks = vec![("b == 0".to_string(), "attempt to divide by zero")];
+            if signed {
+                checks.push((format!("a == {min} && b == -1"), "attempt to divide with overflow"));
+            }
+            ("div", checks, "a / b")
+        }
+        Arithmetic::Rem => {
+            let mut checks = vec![(
+                "b == 0".to_string(),
+                "attempt to calculate the remainder with a divisor of zero",
+            )];
+            if signed {
+                checks.push((
+                    format!("a == {min} && b == -1"),
+                    "attempt to calculate the remainder with overflow",
+                ));
+            }
+            ("rem", checks, "a % b")
+        }
+        Arithmetic::Neg => (
+            "neg",
+            vec![(format!("a == {min}"), "attempt to negate with overflow")],
+            "-a",
+        ),
+    };
+
+    let params = match op {
+        Arithmetic::Neg => format!("{c_type} a"),
+        _ => format!("{c_type} a, {c_type} b"),
+    };
+    let mut c = format!("static {c_type} lm_{verb}_{name}({params}, const char *at) {{\n");
+    for (condition, message) in checks {
+        c.push_str(&format!(
+            "    if ({condition}) {{\n        lm_panic(\"{message}\", at);\n    }}\n"
+        ));
+    }
+    c.push_str(&format!("    return ({c_type})({result});\n}}\n"));
+    c
+}
+
+fn shift_helper(left: bool, ty: IntTy, amount: IntTy) -> String {
+    let c_type = int_type(ty);
+    let amount_type = int_type(amount);
+    let direction = if left { "shl" } else { "shr" };
+    let bits = ty.bits();
+
+    let (condition, format, cast) = if amount.is_signed() {
+        (format!("b < 0 || b >= {bits}"), "PRId64", "int64_t")
+    } else {
+        (format!("b >= {bits}"), "PRIu64", "uint64_t")
+    };
+    let result = match (left, ty.is_signed()) {
+        // shifting negative values left is undefined, unlike shifting unsigned ones
+        (true, _) => format!("({c_type})((uint64_t)a << b)"),
+        // and shifting them right is implementation-defined
+        (false, true) => format!("a < 0 ? ({c_type})~(~a >> b) : ({c_type})(a >> b)"),
+        (false, false) => format!("({c_type})(a >> b)"),
+    };
+
+    format!(
+        "\
+static {c_type} lm_{direction}_{}_{}({c_type} a, {amount_type} b, const char *at) {{
+    if ({condition}) {{
+        lm_panic_begin();
+        fprintf(stderr, \"attempt to shift by `%\" {format} \"`, which is out of range\", ({cast})b);
+        lm_panic_end(at);
+    }}
+    return {result};
+}}
+",
+        ty.name(),
+        amount.name(),
+    )
+}
+
+fn int_type(ty: IntTy) -> &'static str {
+    match ty {
+        IntTy::I8 => "int8_t",
+        IntTy::I16 => "int16_t",
+        IntTy::I32 => "int32_t",
+        IntTy::I64 => "int64_t",
+        IntTy::Isize => "ptrdiff_t",
+        IntTy::U8 => "uint8_t",
+        IntTy::U16 => "uint16_t",
+        IntTy::U32 => "uint32_t",
+        IntTy::U64 => "uint64_t",
+        IntTy::Usize => "size_t",
+    }
+}
+
+fn int_limits(ty: IntTy) -> (&'static str, &'static str) {
+    match ty {
+        IntTy::I8 => ("INT8_MIN", "INT8_MAX"),
+        IntTy::I16 => ("INT16_MIN", "INT16_MAX"),
+        IntTy::I32 => ("INT32_MIN", "INT32_MAX"),
+        IntTy::I64 => ("INT64_MIN", "INT64_MAX"),
+        IntTy::Isize => ("PTRDIFF_MIN", "PTRDIFF_MAX"),
+        IntTy::U8 => ("0", "UINT8_MAX"),
+        IntTy::U16 => ("0", "UINT16_MAX"),
+        IntTy::U32 => ("0", "UINT32_MAX"),
+        IntTy::U64 => ("0", "UINT64_MAX"),
+        IntTy::Usize => ("0", "SIZE_MAX"),
+    }
+}
+
+/// `text` with the characters C doesn't allow in identifiers replaced.
+pub(crate) fn identifier(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// A C string literal of `text`, escaping `?` so that trigraphs aren't formed.
+fn c_string(text: &str) -> String {
+    let mut c = String::from('"');
+    for byte in text.bytes() {
+        match byte {
+            b'"' => c.push_str("\\\""),
+            b'\\' => c.push_str("\\\\"),
+            b'?' => c.push_str("\\?"),
+            b'\n' => c.push_str("\\n"),
+            b'\t' => c.push_str("\\t"),
+            0x20..=0x7e => c.push(byte as char),
+            _ => c.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    c.push('"');
+    c
+}
+
+fn literal(literal: &Literal, ty: &Type) -> String {
+    match (literal, ty) {
+        (Literal::Int(value), Type::Int(ty)) => {
+            let value = *value;
+            if (0..=0x7fff_ffff).contains(&value) {
+                value.to_string()
+            } else if (-0x7fff_ffff..0).contains(&value) {
+                format!("({value})")
+            } else if value == i128::from(i64::MIN) {
+                "(-INT64_C(9223372036854775807) - 1)".to_string()
+            } else if value < 0 {
+                format!("(-INT64_C({}))", -value)
+            } else if ty.is_signed() {
+                format!("INT64_C({value})")
+            } else {
+                format!("UINT64_C({value})")
+            }
+        }
+        (Literal::Float(value), Type::Float(ty)) => {
+            let suffix = match ty {
+                FloatTy::F32 => "F",
+                FloatTy::F64 => "",
+            };
+            if value.is_infinite() {
+                let sign = if *value < 0.0 { "-" } else { "" };
+                format!("({sign}HUGE_VAL{suffix})")
+            } else {
+                let suffix = suffix.to_lowercase();
+                match value.is_sign_negative() {
+                    true => format!("({value:?}{suffix})"),
+                    false => format!("{value:?}{suffix}"),
+                }
+            }
+        }
+        (Literal::Bool(value), _) => value.to_string(),
+        (Literal::Char(c), _) => match c {
+            ' '..='~' if !matches!(c, '\'' | '\\') => format!("'{c}'"),
+            _ => format!("{}u", u32::from(*c)),
+        },
+        (Literal::Str(s), _) => format!("LM_STR({})", c_string(s)),
+        _ => "0".to_string(),
+    }
+}
+
+struct Emitter<'a> {
+    program: &'a Program,
+    source_map: &'a SourceMap,
+    helpers: BTreeSet<Helper>,
+    /// The names of the tuple and enum types which are defined.
+    type_names: HashMap<Type, String>,
+    type_defs: Vec<String>,
+    /// The tuple and enum types in the order of their definitions, where the types of
+    /// their fields come first.
+    defined: Vec<Type>,
+    /// The tuple and enum types compared and printed, which get functions doing it.
+    eq_types: HashSet<Type>,
+    print_types: HashSet<Type>,
+    function_names: Vec<String>,
+}
+
+impl Emitter<'_> {
+    fn need(&mut self, helper: Helper) {
+        for &dependency in helper.dependencies() {
+            self.need(dependency);
+        }
+        self.helpers.insert(helper);
+    }
+
+    fn location(&self, span: Span) -> String {
+        c_string(&self.source_map.location(span))
+    }
+
+    /// The C type of `ty`, defining it the first time it's used.
+    fn c_type(&mut self, ty: &Type) -> String {
+        match ty {
+            Type::Int(ty) => int_type(*ty).to_string(),
+            Type::Float(FloatTy::F32) => "float".to_string(),
+            Type::Float(FloatTy::F64) => "double".to_string(),
+            Type::Bool => "bool".to_string(),
+            Type::Char => "uint32_t".to_string(),
+            Type::Str => "lm_str".to_string(),
+            Type::Unit | Type::Never => "lm_unit".to_string(),
+            Type::Tuple(_) | Type::Enum(_) => {
+                if let Some(name) = self.type_names.get(ty) {
+                    return name.clone();
+                }
+                let (name, def) = self.define(ty);
+                self.type_names.insert(ty.clone(), name.clone());
+                self.type_defs.push(def);
+                self.defined.push(ty.clone());
+                name
+            }
+        }
+    }
+
+    fn define(&mut self, ty: &Type) -> (String, String) {
+        match ty {
+            Type::Tuple(elements) => {
+                let mut def = "typedef struct {\n".to_string();
+                for (index, element) in elements.iter().enumerate() {
+                    def.push_str(&format!("    {} f{index};\n", self.c_type(element)));
+                }
+                // numbered once the tuples among their elements are
+                let tuples = self
+                    .defined
+                    .iter()
+                    .filter(|ty| matches!(ty, Type::Tuple(_)));
+                let name = format!("tuple{}", tuples.count());
+                def.push_str(&format!("}} {name};\n"));
+                (name, def)
+            }
+            Type::Enum(id) => {
+                let enum_def = self.program.enum_def(*id);
+                let mut def = "typedef struct {\n    uint32_t tag;\n".to_string();
+                let mut variants = String::new();
+                for (index, variant) in enum_def.variants.iter().enumerate() {
+                    if variant.fields.is_empty() {
+                        continue;
+                    }
+                    variants.push_str(&format!("        struct {{ // {}\n", variant.name));
+                    for (field, ty) in variant.fields.iter().enumerate() {
+                        variants.push_str(&format!("            {} f{field};\n", self.c_type(ty)));
+                    }
+                    variants.push_str(&format!("        }} v{index};\n"));
+                }
+                if !variants.is_empty() {
+                    def.push_str(&format!("    union {{\n{variants}    }} as;\n"));
+                }
+                // instances of generic enums such as `Pair<a::B>` and `Pair<a__B>` can share
+                // an identifier
+                let base = format!("enum_{}", identifier(&enum_def.name.replace("::", "__")));
+                let mut name = base.clone();
+                let mut suffix = 1;
+                while self.type_names.values().any(|taken| *taken == name) {
+                    name = format!("{base}_{suffix}");
+                    suffix += 1;
+                }
+                def.push_str(&format!("}} {name};\n"));
+                (name, def)
+            }
+            _ => unreachable!("only tuples and enums are defined"),
+        }
+    }
+
+    /// The types of the elements of a tuple, or of the fields of every variant of an enum.
+    fn fields(&self, ty: &Type) -> Vec<Type> {
+        match ty {
+            Type::Tuple(elements) => elements.clone(),
+            Type::Enum(id) => {
+                let variants = &self.program.enum_def(*id).variants;
+                variants
+                    .iter()
+                    .flat_map(|variant| variant.fields.clone())
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Marks the functions comparing values of `ty` as used.
+    fn need_eq(&mut self, ty: &Type) {
+        match ty {
+            Type::Str => self.need(Helper::StrEq),
+            Type::Tuple(_) | Type::Enum(_) if self.eq_types.insert(ty.clone()) => {
+                for field in self.fields(ty) {
+                    self.need_eq(&field);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Marks the functions printing values of `ty` as used.
+    fn need_print(&mut self, ty: &Type) {
+        match ty {
+            Type::Float(FloatTy::F32) => self.need(Helper::PrintF32),
+            Type::Float(FloatTy::F64) => self.need(Helper::PrintF64),
+            Type::Char => self.need(Helper::PrintChar),
+            Type::Str => self.need(Helper::PrintStr),
+            Type::Tuple(_) | Type::Enum(_) if self.print_types.insert(ty.clone()) => {
+                for field in self.fields(ty) {
+                    self.need_print(&field);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// An expression comparing the values `a` and `b` of type `ty`.
+    fn eq(&mut self, a: &str, b: &str, ty: &Type) -> String {
+        self.need_eq(ty);
+        match ty {
+            Type::Str => format!("lm_str_eq({a}, {b})"),
+            Type::Tuple(_) | Type::Enum(_) => format!("lm_eq_{}({a}, {b})", self.c_type(ty)),
+            _ => format!("{a} == {b}"),
+        }
+    }
+
+    /// A statement writing `value` of type `ty` to `out`.
+    fn print(&mut self, out: &str, value: &str, ty: &Type) -> String {
+        self.need_print(ty);
+        match ty {
+            Type::Int(ty) if ty.is_signed() => {
+                format!("fprintf({out}, \"%\" PRId64, (int64_t){value});")
+            }
+            Type::Int(_) => format!("fprintf({out}, \"%\" PRIu64, (uint64_t){value});"),
+            Type::Float(ty) => format!("lm_print_{}({out}, {value});", ty.name()),
+            Type::Bool => format!("fputs({value} ? \"true\" : \"false\", {out});"),
+            Type::Char => format!("lm_print_char({out}, {value});"),
+            Type::Str => format!("lm_print_str({out}, {value});"),
+            Type::Unit | Type::Never => format!("fputs(\"()\", {out});"),
+            Type::Tuple(_) | Type::Enum(_) => {
+                format!("lm_print_{}({out}, {value});", self.c_type(ty))
+            }
+        }
+    }
+
+    fn eq_function(&mut self, ty: &Type) -> String {
+        let name = self.c_type(ty);
+        let mut c = format!("static bool lm_eq_{name}({name} a, {name} b) {{\n");
+        match ty {
+            Type::Tuple(elements) => {
+                let fields = elements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, element)| {
+                        self.eq(&format!("a.f{index}"), &format!("b.f{index}"), element)
+                    })
+                    .collect::<Vec<_>>();
+                c.push_str(&format!("    return {};\n", fields.join(" && ")));
+            }
+            Type::Enum(id) => {
+                c.push_str("    if (a.tag != b.tag) {\n        return false;\n    }\n");
+                c.push_str("    switch (a.tag) {\n");
+                let enum_def = self.program.enum_def(*id);
+                for (index, variant) in enum_def.variants.iter().enumerate() {
+                    if variant.fields.is_empty() {
+                        continue;
+                    }
+                    let fields = variant
+                        .fields
+                        .iter()
+                        .enumerate()
+                        .map(|(field, ty)| {
+                            let a = format!("a.as.v{index}.f{field}");
+                            let b = format!("b.as.v{index}.f{field}");
+                            self.eq(&a, &b, ty)
+                        })
+                        .collect::<Vec<_>>();
+                    c.push_str(&format!(
+                        "    case {index}:\n        return {};\n",
+                        fields.join(" && ")
+                    ));
+                }
+                c.push_str("    default:\n        return true;\n    }\n");
+            }
+            _ => unreachable!("only tuples and enums are compared by functions"),
+        }
+        c.push_str("}\n");
+        c
+    }
+
+    fn print_function(&mut self, ty: &Type) -> String {
+        let name = self.c_type(ty);
+        let mut c = format!("static void lm_print_{name}(FILE *out, {name} v) {{\n");
+        match ty {
+            Type::Tuple(elements) => {
+                c.push_str("    fputc('(', out);\n");
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        c.push_str("    fputs(\", \", out);\n");
+                    }
+                    c.push_str(&format!(
+                        "    {}\n",
+                        self.print("out", &format!("v.f{index}"), element)
+                    ));
+                }
+                // `(1,)` tells a tuple of one element apart from a parenthesized value
+                let end = if elements.len() == 1 { ",)" } else { ")" };
+                c.push_str(&format!("    fputs(\"{end}\", out);\n"));
+            }
+            Type::Enum(id) => {
+                c.push_str("    switch (v.tag) {\n");
+                let enum_def = self.program.enum_def(*id);
+                for (index, variant) in enum_def.variants.iter().enumerate() {
+                    c.push_str(&format!("    case {index}:\n"));
+                    c.push_str(&format!(
+                        "        fputs({}, out);\n",
+                        c_string(&variant.name)
+                    ));
+                    let fields = variant.fields.iter().enumerate();
+                    match &variant.field_names {
+                        _ if variant.fields.is_empty() => {}
+                        Some(names) => {
+                            for ((field, ty), name) in fields.zip(names) {
+                                let separator = if field > 0 { "," } else { " {" };
+                                c.push_str(&format!(
+                                    "        fputs(\"{separator} {name}: \", out);\n"
+                                ));
+                                let value = format!("v.as.v{index}.f{field}");
+                                c.push_str(&format!("        {}\n", self.print("out", &value, ty)));
+                            }
+                            c.push_str("        fputs(\" }\", out);\n");
+                        }
+                        None => {
+                            c.push_str("        fputc('(', out);\n");
+                            for (field, ty) in fields {
+                                if field > 0 {
+                                    c.push_str("        fputs(\", \", out);\n");
+                                }
+                                let value = format!("v.as.v{index}.f{field}");
+                                c.push_str(&format!("        {}\n", self.print("out", &value, ty)));
+                            }
+                            c.push_str("        fputc(')', out);\n");
+                        }
+                    }
+                    c.push_str("        break;\n");
+                }
+                c.push_str("    }\n");
+            }
+            _ => unreachable!("only tuples and enums are printed by functions"),
+        }
+        c.push_str("}\n");
+        c
+    }
+
+    /// The declaration of `function`, with the names of its parameters if given.
+    fn signature(
+        &mut self,
+        id: FunctionId,
+        function: &Function,
+        params: Option<&[String]>,
+    ) -> String {
+        let ret = self.c_type(&function.ret);
+        let mut list = Vec::new();
+        for (index, &param) in function.params.iter().enumerate() {
+            let ty = self.c_type(&function.local(param).ty);
+            list.push(match params {
+                Some(names) => format!("{ty} {}", names[index]),
+                None => ty,
+            });
+        }
+        let list = if list.is_empty() {
+            "void".to_string()
+        } else {
+            list.join(", ")
+        };
+        format!(
+            "static {ret} {}({list})",
+            self.function_names[id.0 as usize]
+        )
+    }
+
+    fn main(&mut self) -> String {
+        let main = self.program.main;
+        let function = self.program.function(main);
+        let call = format!("{}()", self.function_names[main.0 as usize]);
+        let mut c = "int main(void) {\n".to_string();
+        match &function.ret {
+            Type::Unit | Type::Never => c.push_str(&format!("    {call};\n")),
+            ty => {
+                let print = self.print("stdout", &call, ty);
+                c.push_str(&format!("    {print}\n    fputc('\\n', stdout);\n"));
+            }
+        }
+        c.push_str("    return 0;\n}\n");
+        c
+    }
+}
+
+/// A C expression, which is `pure` if evaluating it has no effects.
+struct Code {
+    text: String,
+    pure: bool,
+}
+
+impl Code {
+    fn pure(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            pure: true,
+        }
+    }
+
+    fn effect(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            pure: false,
+        }
+    }
+}
+
+/// The generation of a function from its MIR. Each block becomes a list of statements,
+/// labelled if a `goto` leads to it, and control falls through to the next block where the
+/// terminator allows it.
+struct FunctionEmitter<'e, 'a> {
+    emitter: &'e mut Emitter<'a>,
+    id: FunctionId,
+    function: &'a Function,
+    /// The C names of the locals.
+    names: Vec<String>,
+    /// The locals whose values are read, which are the only ones declared and assigned.
+    read: HashSet<LocalId>,
+    /// The blocks a `goto` leads to, which get labels.
+    targets: HashSet<BlockId>,
+    lines: Vec<String>,
+}
+
+impl<'e, 'a> FunctionEmitter<'e, 'a> {
+    fn new(emitter: &'e mut Emitter<'a>, id: FunctionId, function: &'a Function) -> Self {
+        let mut taken = HashSet::new();
+        let mut names = vec![String::new(); function.locals.len()];
+        for (index, local) in function.locals.iter().enumerate() {
+            let Some(name) = &local.name else { continue };
+            let base = identifier(name);
+            let reserved = RESERVED.contains(&base.as_str())
+                || ["lm_", "fn_", "enum_", "tuple"]
+                    .iter()
+                    .any(|prefix| base.starts_with(prefix))
+                || base.ends_with("_t")
+                || base.starts_with(|c: char| c.is_ascii_digit());
+            let base = if reserved { format!("{base}_") } else { base };
+            let mut name = base.clone();
+            let mut suffix = 1;
+            while !taken.insert(name.clone()) {
+                name = format!("{base}_{suffix}");
+                suffix += 1;
+            }
+            names[index] = name;
+        }
+        // the temporaries are named after the locals of the source, so that they don't
+        // take their names
+        let mut temps = 0;
+        for (index, local) in function.locals.iter().enumerate() {
+            if local.name.is_some() {
+                continue;
+            }
+            names[index] = loop {
+                let name = format!("t{temps}");
+                temps += 1;
+                if taken.insert(name.clone()) {
+                    break name;
+                }
+            };
+        }
+
+        let mut this = Self {
+            emitter,
+            id,
+            function,
+            names,
+            read: HashSet::new(),
+            targets: HashSet::new(),
+            lines: Vec::new(),
+        };
+        this.read = this.reads();
+        this
+    }
+
+    /// The locals read by the code generated for the function. Assignments to locals which
+    /// aren't read are left out unless they have effects, so the locals they read are only
+    /// read if they're kept.
+    fn reads(&self) -> HashSet<LocalId> {
+        let mut read = HashSet::new();
+        loop {
+            let count = read.len();
+            for block in &self.function.blocks {
+                for statement in &block.statements {
+                    let kept = match &statement.kind {
+                        StatementKind::Assign(local, rvalue) => {
+                            read.contains(local) || self.has_effects(rvalue)
+                        }
+                        StatementKind::Print(..) => true,
+                    };
+                    if kept {
+                        read.extend(statement.kind.places().iter().map(|place| place.local));
+                    }
+                }
+                let places = block.terminator.kind.places();
+                read.extend(places.iter().map(|place| place.local));
+            }
+            if read.len() == count {
+                return read;
+            }
+        }
+    }
+
+    /// Whether evaluating `rvalue` can stop the program, as checked arithmetic does.
+    fn has_effects(&self, rvalue: &Rvalue) -> bool {
+        match rvalue {
+            Rvalue::Unary(UnaryOp::Neg, operand) => {
+                matches!(self.operand_type(operand), Type::Int(_))
+            }
+            Rvalue::Binary(
+                BinaryOp::Add
+                | BinaryOp::Sub
+                | BinaryOp::Mul
+                | BinaryOp::Div
+                | BinaryOp::Rem
+                | BinaryOp::Shl
+                | BinaryOp::Shr,
+                lhs,
+                _,
+            ) => matches!(self.operand_type(lhs), Type::Int(_)),
+            _ => false,
+        }
+    }
+
+    fn emit(mut self) -> String {
+        let function = self.function;
+        let params = function
+            .params
+            .iter()
+            .map(|param| self.names[param.0 as usize].clone())
+            .collect::<Vec<_>>();
+        let signature = self.emitter.signature(self.id, function, Some(&params));
+
+        let mut declarations = Vec::new();
+        for (index, local) in function.locals.iter().enumerate() {
+            let id = LocalId(index as u32);
+            let name = &self.names[index];
+            if function.params.contains(&id) {
+                if !self.read.contains(&id) {
+                    declarations.push(format!("    (void){name};\n"));
+                }
+            } else if self.read.contains(&id) {
+                let ty = self.emitter.c_type(&local.ty);
+                declarations.push(format!("    {ty} {name};\n"));
+            }
+        }
+
+        let mut blocks = Vec::new();
+        for (index, block) in function.blocks.iter().enumerate() {
+            for statement in &block.statements {
+                self.statement(statement);
+            }
+            self.terminator(&block.terminator, BlockId(index as u32 + 1));
+            blocks.push(std::mem::take(&mut self.lines));
+        }
+
+        let mut c = format!("{signature} {{\n");
+        for declaration in &declarations {
+            c.push_str(declaration);
+        }
+        if !declarations.is_empty() {
+            c.push('\n');
+        }
+        for (index, lines) in blocks.iter().enumerate() {
+            if self.targets.contains(&BlockId(index as u32)) {
+                c.push_str(&format!("bb{index}:\n"));
+            }
+            for line in lines {
+                c.push_str(line);
+                c.push('\n');
+            }
+        }
+        c.push_str("}\n");
+        c
+    }
+
+    fn line(&mut self, text: impl AsRef<str>) {
+        self.lines.push(format!("    {}", text.as_ref()));
+    }
+
+    fn location(&self, span: Span) -> String {
+        self.emitter.location(span)
+    }
+
+    /// Evaluates `code` of type `ty` for its effects.
+    fn discard(&mut self, code: Code, ty: &Type) {
+        if code.pure {
+            return;
+        }
+        match ty {
+            Type::Unit | Type::Never => self.line(format!("{};", code.text)),
+            _ => self.line(format!("(void){};", code.text)),
+        }
+    }
+
+    /// Assigns `code` to `local`, or only evaluates it if the local isn't read.
+    fn assign(&mut self, local: LocalId, code: Code) {
+        if self.read.contains(&local) {
+            let name = &self.names[local.0 as usize];
+            self.line(format!("{name} = {};", code.text));
+        } else {
+            let ty = &self.function.local(local).ty;
+            self.discard(code, ty);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::Assign(local, rvalue) => {
+                let ty = &self.function.local(*local).ty;
+                let code = self.rvalue(rvalue, ty, statement.span);
+                self.assign(*local, code);
+            }
+            StatementKind::Print(output, parts) => {
+                let out = match output {
+                    Output::Stdout { .. } => "stdout",
+                    Output::Stderr => "stderr",
+                };
+                for part in parts {
+                    let value = self.operand(part);
+                    self.print_part(out, &value, &self.operand_type(part));
+                }
+                if *output != (Output::Stdout { newline: false }) {
+                    self.line(format!("fputc('\\n', {out});"));
+                }
+            }
+        }
+    }
+
+    /// Ends a block, `next` being the block control falls through to.
+    fn terminator(&mut self, terminator: &Terminator, next: BlockId) {
+        match &terminator.kind {
+            TerminatorKind::Goto(target) => self.goto(*target, next),
+            TerminatorKind::Branch(condition, then, otherwise) => {
+                let condition = self.operand(condition);
+                if then == otherwise {
+                    self.goto(*then, next);
+                } else if *then == next {
+                    self.targets.insert(*otherwise);
+                    self.line(format!("if (!{condition}) goto bb{};", otherwise.0));
+                } else {
+                    self.targets.insert(*then);
+                    self.line(format!("if ({condition}) goto bb{};", then.0));
+                    self.goto(*otherwise, next);
+                }
+            }
+            TerminatorKind::Call {
+                function,
+                args,
+                result,
+                next: target,
+            } => {
+                let args = args.iter().map(|arg| self.operand(arg)).collect::<Vec<_>>();
+                let name = &self.emitter.function_names[function.0 as usize];
+                let call = Code::effect(format!("{name}({})", args.join(", ")));
+                self.assign(*result, call);
+                self.goto(*target, next);
+            }
+            TerminatorKind::Return(value) => {
+                let value = self.operand(value);
+                self.line(format!("return {value};"));
+            }
+            TerminatorKind::Panic(parts) => {
+                self.emitter.need(Helper::PanicParts);
+                self.line("lm_panic_begin();");
+                for part in parts {
+                    let value = self.operand(part);
+                    self.print_part("stderr", &value, &self.operand_type(part));
+                }
+                let at = self.location(terminator.span);
+                self.line(format!("lm_panic_end({at});"));
+                // C compilers don't know that `lm_panic_end` exits, and would warn about
+                // the end of the function being reached
+                if next.0 as usize == self.function.blocks.len() {
+                    self.line("abort();");
+                }
+            }
+            TerminatorKind::Unreachable => self.line("abort();"),
+        }
+    }
+
+    fn goto(&mut self, target: BlockId, next: BlockId) {
+        if target != next {
+            self.targets.insert(target);
+            self.line(format!("goto bb{};", target.0));
+        }
+    }
+
+    fn place(&self, place: &Place) -> String {
+        let mut text = self.names[place.local.0 as usize].clone();
+        for projection in &place.projections {
+            match projection {
+                Projection::Field(index) => text.push_str(&format!(".f{index}")),
+                Projection::VariantField { variant, field } => {
+                    text.push_str(&format!(".as.v{variant}.f{field}"))
+                }
+            }
+        }
+        text
+    }
+
+    fn place_type(&self, place: &Place) -> Type {
+        let mut ty = self.function.local(place.local).ty.clone();
+        for projection in &place.projections {
+            ty = match (projection, &ty) {
+                (Projection::Field(index), Type::Tuple(elements)) => elements[*index].clone(),
+                (Projection::VariantField { variant, field }, Type::Enum(id)) => {
+                    let variants = &self.emitter.program.enum_def(*id).variants;
+                    variants[*variant].fields[*field].clone()
+                }
+                _ => unreachable!("projections are of tuples and enums"),
+            };
+        }
+        ty
+    }
+
+    fn operand(&self, operand: &Operand) -> String {
+        match operand {
+            Operand::Place(place) => self.place(place),
+            Operand::Const(constant) => literal(&constant.literal, &constant.ty),
+        }
+    }
+
+    fn operand_type(&self, operand: &Operand) -> Type {
+        match operand {
+            Operand::Place(place) => self.place_type(place),
+            Operand::Const(constant) => constant.ty.clone(),
+        }
+    }
+
+    /// The value of `rvalue`, which is assigned to a local of type `ty`.
+    fn rvalue(&mut self, rvalue: &Rvalue, ty: &Type, span: Span) -> Code {
+        match rvalue {
+            Rvalue::Use(operand) => Code::pure(self.operand(operand)),
+            Rvalue::Unary(op, operand) => {
+                let value = self.operand(operand);
+                match (op, self.operand_type(operand)) {
+                    (UnaryOp::Neg, Type::Int(ty)) => {
+                        self.emitter.need(Helper::Arithmetic(Arithmetic::Neg, ty));
+                        let at = self.location(span);
+                        Code::effect(format!("lm_neg_{}({value}, {at})", ty.name()))
+                    }
+                    (UnaryOp::Neg, _) => Code::pure(format!("-{value}")),
+                    (UnaryOp::Not, _) => Code::pure(format!("!{value}")),
+                    (UnaryOp::BitNot, ty) => {
+                        let ty = self.emitter.c_type(&ty);
+                        Code::pure(format!("({ty})~{value}"))
+                    }
+                }
+            }
+            Rvalue::Binary(op, lhs, rhs) => {
+                let (a, b) = (self.operand(lhs), self.operand(rhs));
+                let (lhs_ty, rhs_ty) = (self.operand_type(lhs), self.operand_type(rhs));
+                self.binary(*op, &a, &b, &lhs_ty, &rhs_ty, span)
+            }
+            Rvalue::Tuple(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| self.operand(element))
+                    .collect::<Vec<_>>();
+                let ty = self.emitter.c_type(ty);
+                Code::pure(format!("(({ty}){{{}}})", elements.join(", ")))
+            }
+            Rvalue::Variant(_, variant, fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| self.operand(field))
+                    .collect::<Vec<_>>();
+                let ty = self.emitter.c_type(ty);
+                if fields.is_empty() {
+                    Code::pure(format!("(({ty}){{.tag = {variant}}})"))
+                } else {
+                    Code::pure(format!(
+                        "(({ty}){{.tag = {variant}, .as.v{variant} = {{{}}}}})",
+                        fields.join(", ")
+                    ))
+                }
+            }
+            Rvalue::Discriminant(place) => Code::pure(format!("{}.tag", self.place(place))),
+        }
+    }
+
+    /// Writes a value, with string literals written as they are.
+    fn print_part(&mut self, out: &str, value: &str, ty: &Type) {
+        if let Some(text) = value
+            .strip_prefix("LM_STR(")
+            .and_then(|s| s.strip_suffix(')'))
+        {
+            if !text.contains("\\000") {
+                self.line(format!("fputs({text}, {out});"));
+                return;
+            }
+        }
+        let print = self.emitter.print(out, value, ty);
+        self.line(print);
+    }
+
+    fn binary(
+        &mut self,
+        op: BinaryOp,
+        a: &str,
+        b: &str,
+        ty: &Type,
+        rhs_ty: &Type,
+        span: Span,
+    ) -> Code {
+        let symbol = match op {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        };
+
+        match (op, ty) {
+            (BinaryOp::Eq, _) => Code::pure(format!("({})", self.emitter.eq(a, b, ty))),
+            (BinaryOp::Ne, _) => Code::pure(format!("!({})", self.emitter.eq(a, b, ty))),
+            (_, Type::Str) => {
+                self.emitter.need(Helper::StrCmp);
+                Code::pure(format!("(lm_str_cmp({a}, {b}) {symbol} 0)"))
+            }
+            (
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem,
+                Type::Int(int),
+            ) => {
+                let arithmetic = match op {
+                    BinaryOp::Add => Arithmetic::Add,
+                    BinaryOp::Sub => Arithmetic::Sub,
+                    BinaryOp::Mul => Arithmetic::Mul,
+                    BinaryOp::Div => Arithmetic::Div,
+                    _ => Arithmetic::Rem,
+                };
+                self.emitter.need(Helper::Arithmetic(arithmetic, *int));
+                let name = format!("{:?}", arithmetic).to_lowercase();
+                let at = self.location(span);
+                Code::effect(format!("lm_{name}_{}({a}, {b}, {at})", int.name()))
+            }
+            (BinaryOp::Shl | BinaryOp::Shr, Type::Int(int)) => {
+                let Type::Int(amount) = rhs_ty else {
+                    unreachable!("shift amounts are integers")
+                };
+                let left = op == BinaryOp::Shl;
+                self.emitter.need(Helper::Shift(left, *int, *amount));
+                let at = self.location(span);
+                let name = if left { "shl" } else { "shr" };
+                Code::effect(format!(
+                    "lm_{name}_{}_{}({a}, {b}, {at})",
+                    int.name(),
+                    amount.name()
+                ))
+            }
+            (BinaryOp::Rem, Type::Float(FloatTy::F32)) => Code::pure(format!("fmodf({a}, {b})")),
+            (BinaryOp::Rem, Type::Float(FloatTy::F64)) => Code::pure(format!("fmod({a}, {b})")),
+            (BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor, _) => {
+                let ty = self.emitter.c_type(ty);
+                Code::pure(format!("({ty})({a} {symbol} {b})"))
+            }
+            _ => Code::pure(format!("({a} {symbol} {b})")),
+        }
+    }
+}
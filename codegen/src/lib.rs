@@ -0,0 +1,42 @@
+//! Compilation of lamia programs ahead of time. The functions reachable from `main` are
+//! lowered to a typed IR, from which the control-flow graphs of the MIR are built. The C
+//! backend generates code from the MIR, while the WebAssembly backend, whose structured
+//! control flow the typed IR maps onto directly, still generates code from the typed IR.
+
+pub mod mir;
+pub mod tir;
+
+mod c;
+mod lower;
+mod wasm;
+
+use source_map::SourceMap;
+
+pub use lower::lower;
+
+/// Generates a C99 program which runs `main` of `program`, from its MIR.
+pub fn emit_c(program: &tir::Program, source_map: &SourceMap) -> String {
+    c::emit(&mir::build(program), source_map)
+}
+
+/// Generates a WebAssembly module which runs `main` of `program` when its export `main` is
+/// called.
+pub fn emit_wasm(program: &tir::Program, source_map: &SourceMap) -> Vec<u8> {
+    wasm::emit_binary(program, source_map)
+}
+
+/// Generates the WebAssembly module [`emit_wasm`] does, in the text format.
+pub fn emit_wat(program: &tir::Program, source_map: &SourceMap) -> String {
+    wasm::emit_text(program, source_map)
+}
+
+/// Writes the MIR of `program` in SSA form, as text. The MIR is verified before and after
+/// the conversion to SSA form, and the errors found, which are bugs of the lowering, are
+/// returned instead.
+pub fn emit_mir(program: &tir::Program) -> Result<String, Vec<mir::VerifyError>> {
+    let mir = mir::build(program);
+    mir::verify(&mir)?;
+    let mir = mir.into_ssa();
+    mir::verify(&mir)?;
+    Ok(mir.to_string())
+}
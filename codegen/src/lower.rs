@@ -0,0 +1,2578 @@
+//! Lowering of the functions reachable from `main` to the typed IR. The types of locals and
+//! literals are inferred from the annotations of the signatures, which are required. Generic
+//! functions and enums are lowered once for each list of type arguments they're used with,
+//! which are inferred from the arguments and the expected types at their uses, or else
+//! are those the checker inferred.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use db::Database;
+use modules::{prelude::Builtin, Def, ModuleId, ModuleTree, ResolveError, VariantFields};
+use parser::{
+    ast::{
+        self, AstNode, BinaryExpr, BlockExpr, CallExpr, ElseBranch, EnumStmt, FunctionExpr,
+        GenericArgList, GenericParamList, IfExpr, ImplStmt, InterpolatedStringPart, MatchExpr,
+        PrefixExpr, RecordExpr, RecordFieldList, StructStmt,
+    },
+    literal::{self, Number},
+    syntax::{SyntaxKind, SyntaxNode, SyntaxToken},
+};
+use source_map::{Diagnostic, FileId, Span, TextRange};
+
+use crate::tir::{
+    Arm, BinaryOp, EnumDef, EnumId, Expr, ExprKind, FloatTy, Function, FunctionId, IntTy, Literal,
+    Local, LocalId, Output, Pattern, Place, Program, Stmt, Type, UnaryOp, VariantDef,
+};
+
+/// Lowers the program whose module tree was built and checked without errors, starting
+/// from its `main` function.
+pub fn lower(
+    db: &Database,
+    module_tree: &ModuleTree,
+) -> std::result::Result<Program, Vec<Diagnostic>> {
+    let files = module_tree
+        .modules()
+        .map(|module| module_tree.get(module).file)
+        .map(|file| (file, db.parse(file).syntax()))
+        .collect();
+    let mut lowerer = Lowerer {
+        module_tree,
+        files,
+        function_ids: HashMap::new(),
+        signatures: Vec::new(),
+        failed: HashSet::new(),
+        queue: VecDeque::new(),
+        enum_ids: HashMap::new(),
+        enum_keys: Vec::new(),
+        enums: Vec::new(),
+        lowering_enums: Vec::new(),
+        displayed_with_to_string: None,
+        type_args: checker::type_args(db, module_tree),
+        diagnostics: Vec::new(),
+    };
+
+    let root = module_tree.root();
+    let main = match module_tree.resolve_path(root, &["main"]) {
+        Ok(Def::Function(span)) => match lowerer.function_node(span).generic_param_list() {
+            Some(list) => {
+                let span = Span::new(span.file, list.syntax().text_range());
+                lowerer.error(
+                    span,
+                    "`main` function is not allowed to have generic parameters",
+                );
+                None
+            }
+            None => lowerer.function_id(span, Vec::new()).ok(),
+        },
+        _ => {
+            let file = module_tree.get(root).file;
+            let span = Span::new(file, TextRange::default());
+            lowerer.error(span, "`main` function not found");
+            None
+        }
+    };
+
+    let mut functions = Vec::new();
+    while let Some(id) = lowerer.queue.pop_front() {
+        functions.push((id, lowerer.lower_function(id)));
+    }
+
+    if let Some(main) = main {
+        let signature = &lowerer.signatures[main.0 as usize];
+        if !signature.params.is_empty() {
+            let span = signature.span;
+            lowerer.error(span, "`main` can't take arguments");
+        }
+    }
+
+    match main {
+        Some(main) if lowerer.diagnostics.is_empty() => {
+            functions.sort_by_key(|(id, _)| *id);
+            let functions = functions
+                .into_iter()
+                .map(|(_, function)| function.expect("errors are reported"))
+                .collect();
+            Ok(Program {
+                functions,
+                enums: lowerer.enums,
+                main,
+            })
+        }
+        _ => Err(lowerer.diagnostics),
+    }
+}
+
+/// An error which was reported, so the lowering of the function can stop.
+#[derive(Debug)]
+struct Reported;
+
+type Result<T> = std::result::Result<T, Reported>;
+
+/// The types of the generic parameters of the instance of a function or an enum being
+/// lowered, by name.
+type Generics = [(String, Type)];
+
+/// The parameters and the return type of a function, known before its body is lowered.
+struct Signature {
+    /// The path of the function, followed by its type arguments if it's generic.
+    name: String,
+    span: Span,
+    generics: Vec<(String, Type)>,
+    params: Vec<(String, Type)>,
+    ret: Type,
+}
+
+/// The type arguments of a use of a generic function or enum, which are inferred from the
+/// types of the arguments, or else from the type which the result is expected to have.
+struct Inference {
+    /// Where the item is declared, which the types of its parameters are resolved in.
+    file: FileId,
+    module: ModuleId,
+    generics: Vec<String>,
+    /// The types told by the arguments, or written after the path.
+    args: Vec<Option<Type>>,
+    /// The types told by the expected type of the result.
+    hints: Vec<Option<Type>>,
+}
+
+struct Lowerer<'a> {
+    module_tree: &'a ModuleTree,
+    files: HashMap<FileId, SyntaxNode>,
+    /// The instances of functions by the span of their names and their type arguments,
+    /// whose signatures are lowered.
+    function_ids: HashMap<(Span, Vec<Type>), FunctionId>,
+    signatures: Vec<Signature>,
+    /// The instances of functions whose signatures have errors, which are reported once.
+    failed: HashSet<(Span, Vec<Type>)>,
+    /// The functions whose bodies are left to lower.
+    queue: VecDeque<FunctionId>,
+    /// The instances of enums by the span of their names and their type arguments.
+    enum_ids: HashMap<(Span, Vec<Type>), EnumId>,
+    /// The keys of `enum_ids`, indexed by [`EnumId`].
+    enum_keys: Vec<(Span, Vec<Type>)>,
+    enums: Vec<EnumDef>,
+    /// The enums whose fields are being lowered, which their fields can't contain.
+    lowering_enums: Vec<Span>,
+    /// The enums with a `to_string` method by the span of their names, which the
+    /// interpreter displays with it.
+    displayed_with_to_string: Option<HashSet<Span>>,
+    /// The generic arguments the checker inferred from the whole function using a generic
+    /// item, by the span of the call, the path or the record using it.
+    type_args: HashMap<Span, Vec<checker::Ty>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// The state of the function being lowered.
+struct FunctionCx {
+    file: FileId,
+    module: ModuleId,
+    generics: Vec<(String, Type)>,
+    ret: Type,
+    locals: Vec<Local>,
+    scopes: Vec<HashMap<String, LocalId>>,
+}
+
+impl FunctionCx {
+    fn span(&self, node: &SyntaxNode) -> Span {
+        Span::new(self.file, node.text_range())
+    }
+
+    fn declare(&mut self, name: &str, ty: Type) -> LocalId {
+        let scope = self.scopes.last_mut().expect("functions have a scope");
+        // alternatives of or-patterns bind the same names
+        if let Some(&id) = scope.get(name) {
+            if self.locals[id.0 as usize].ty == ty {
+                return id;
+            }
+        }
+
+        let id = LocalId(self.locals.len() as u32);
+        self.locals.push(Local {
+            name: name.to_string(),
+            ty,
+        });
+        scope.insert(name.to_string(), id);
+        id
+    }
+
+    fn lookup(&self, name: &str) -> Option<LocalId> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+    }
+}
+
+/// Whether a value of type `found` can be used where `expected` is.
+fn fits(expected: &Type, found: &Type) -> bool {
+    found == expected || *found == Type::Never
+}
+
+/// The type of a value which is either of type `a` or of type `b`, if they fit each other.
+fn join(a: &Type, b: &Type) -> Option<Type> {
+    match (a, b) {
+        (Type::Never, ty) | (ty, Type::Never) => Some(ty.clone()),
+        (a, b) if a == b => Some(a.clone()),
+        _ => None,
+    }
+}
+
+fn unit(span: Span) -> Expr {
+    Expr {
+        kind: ExprKind::Literal(Literal::Unit),
+        ty: Type::Unit,
+        span,
+    }
+}
+
+fn string(text: impl Into<String>, span: Span) -> Expr {
+    Expr {
+        kind: ExprKind::Literal(Literal::Str(text.into())),
+        ty: Type::Str,
+        span,
+    }
+}
+
+impl Lowerer<'_> {
+    fn error(&mut self, span: Span, message: impl Into<String>) -> Reported {
+        self.diagnostics.push(Diagnostic::error(span, message));
+        Reported
+    }
+
+    fn unsupported(&mut self, span: Span, what: &str) -> Reported {
+        self.error(span, format!("{what} can't be compiled yet"))
+    }
+
+    fn mismatch(&mut self, span: Span, expected: &Type, found: &Type) -> Reported {
+        let message = format!(
+            "mismatched types: expected `{}`, found `{}`",
+            self.type_name(expected),
+            self.type_name(found)
+        );
+        self.error(span, message)
+    }
+
+    fn type_name(&self, ty: &Type) -> String {
+        ty.display(&self.enums)
+    }
+
+    /// The path of an item of `module` from the root module.
+    fn qualified(&self, module: ModuleId, name: &str) -> String {
+        if module == self.module_tree.root() {
+            return name.to_string();
+        }
+        format!("{}::{name}", self.module_tree.path(module))
+    }
+
+    /// The token at `span` in the files of the program.
+    fn token(&self, span: Span) -> Option<SyntaxToken> {
+        self.files[&span.file]
+            .covering_element(span.range)
+            .into_token()
+    }
+
+    fn function_node(&self, span: Span) -> FunctionExpr {
+        self.token(span)
+            .and_then(|name| name.parent())
+            .and_then(FunctionExpr::cast)
+            .expect("functions are identified by their names")
+    }
+
+    /// The instance for the type arguments `args` of the function whose name is at `span`,
+    /// whose body is queued for lowering.
+    fn function_id(&mut self, span: Span, args: Vec<Type>) -> Result<FunctionId> {
+        let key = (span, args);
+        if let Some(&id) = self.function_ids.get(&key) {
+            return Ok(id);
+        }
+        if self.failed.contains(&key) {
+            return Err(Reported);
+        }
+
+        let signature = self.signature(span, &key.1);
+        let Ok(signature) = signature else {
+            self.failed.insert(key);
+            return Err(Reported);
+        };
+        let id = FunctionId(self.signatures.len() as u32);
+        self.signatures.push(signature);
+        self.function_ids.insert(key, id);
+        self.queue.push_back(id);
+        Ok(id)
+    }
+
+    fn signature(&mut self, span: Span, args: &[Type]) -> Result<Signature> {
+        let node = self.function_node(span);
+        let module = self.module_tree.module_at(span);
+        let generics = generic_names(node.generic_param_list())
+            .into_iter()
+            .zip(args.iter().cloned())
+            .collect::<Vec<_>>();
+
+        let mut params = Vec::new();
+        for param in node.param_list().into_iter().flat_map(|list| list.params()) {
+            let name = param
+                .name()
+                .map_or("_".to_string(), |name| name.text().to_string());
+            let Some(ty) = param.ty() else {
+                let span = Span::new(span.file, param.syntax().text_range());
+                let message = format!("the parameter `{name}` needs a type to be compiled");
+                return Err(self.error(span, message));
+            };
+            let ty = self.ty(span.file, module, &generics, &ty)?;
+            params.push((name, ty));
+        }
+        let ret = match node.return_type() {
+            Some(ty) => self.ty(span.file, module, &generics, &ty)?,
+            None => Type::Unit,
+        };
+
+        let name = self
+            .token(span)
+            .expect("functions are identified by their names");
+        Ok(Signature {
+            name: self.instance_name(module, name.text(), args),
+            span,
+            generics,
+            params,
+            ret,
+        })
+    }
+
+    /// The path of an item of `module` from the root module, followed by the type
+    /// arguments of the instance, such as `Option<i64>`.
+    fn instance_name(&self, module: ModuleId, name: &str, args: &[Type]) -> String {
+        let name = self.qualified(module, name);
+        if args.is_empty() {
+            return name;
+        }
+        let args = args
+            .iter()
+            .map(|arg| self.type_name(arg))
+            .collect::<Vec<_>>();
+        format!("{name}<{}>", args.join(", "))
+    }
+
+    fn lower_function(&mut self, id: FunctionId) -> Result<Function> {
+        let signature = &self.signatures[id.0 as usize];
+        let span = signature.span;
+        let ret = signature.ret.clone();
+        let name = signature.name.clone();
+        let generics = signature.generics.clone();
+        let params = signature.params.clone();
+
+        let mut cx = FunctionCx {
+            file: span.file,
+            module: self.module_tree.module_at(span),
+            generics,
+            ret: ret.clone(),
+            locals: Vec::new(),
+            scopes: vec![HashMap::new()],
+        };
+        let params = params
+            .into_iter()
+            .map(|(name, ty)| cx.declare(&name, ty))
+            .collect();
+
+        let node = self.function_node(span);
+        let Some(body) = node.body() else {
+            return Err(self.error(span, "expected function body"));
+        };
+        let body = self.block(&mut cx, &body, Some(&ret))?;
+        if !fits(&ret, &body.ty) {
+            return Err(self.mismatch(body.span, &ret, &body.ty));
+        }
+
+        Ok(Function {
+            name,
+            span,
+            params,
+            ret,
+            locals: cx.locals,
+            body,
+        })
+    }
+
+    /// The type written as `ty` in `module`, where the generic parameters stand for the
+    /// types of `generics`.
+    fn ty(
+        &mut self,
+        file: FileId,
+        module: ModuleId,
+        generics: &Generics,
+        ty: &ast::Type,
+    ) -> Result<Type> {
+        let span = Span::new(file, ty.syntax().text_range());
+        match ty {
+            ast::Type::Path(path) => {
+                let segments = path.segments().collect::<Vec<_>>();
+                let segments = segments.iter().map(|s| s.text()).collect::<Vec<_>>();
+                let list = path.generic_arg_list();
+                if let ([name], None) = (segments.as_slice(), &list) {
+                    if let Some((_, ty)) = generics.iter().find(|(param, _)| param == name) {
+                        return Ok(ty.clone());
+                    }
+                    if let Some(ty) = IntTy::from_name(name) {
+                        return Ok(Type::Int(ty));
+                    }
+                    match *name {
+                        "f32" => return Ok(Type::Float(FloatTy::F32)),
+                        "f64" => return Ok(Type::Float(FloatTy::F64)),
+                        "bool" => return Ok(Type::Bool),
+                        "char" => return Ok(Type::Char),
+                        "String" | "str" => return Ok(Type::Str),
+                        "i128" | "u128" => {
+                            return Err(self.unsupported(span, &format!("`{name}` values")))
+                        }
+                        _ => {}
+                    }
+                }
+
+                match self.module_tree.resolve_path(module, &segments) {
+                    Ok(Def::Enum(def) | Def::Struct(def)) => {
+                        let mut args = Vec::new();
+                        for arg in list.iter().flat_map(|list| list.types()) {
+                            args.push(self.ty(file, module, generics, &arg)?);
+                        }
+                        let data = self.module_tree.enum_data(def);
+                        let kind = if data.is_struct { "struct" } else { "enum" };
+                        let expected = data.generics.len();
+                        if args.len() != expected {
+                            let path = segments.join("::");
+                            let message = if args.is_empty() {
+                                format!("missing generics for {kind} `{path}`")
+                            } else {
+                                generic_count_mismatch(kind, &path, expected, args.len())
+                            };
+                            return Err(self.error(span, message));
+                        }
+                        Ok(Type::Enum(self.enum_id(def, args, span)?))
+                    }
+                    _ if segments == ["Map"] => Err(self.unsupported(span, "maps")),
+                    _ => {
+                        let message =
+                            format!("cannot find type `{}` in this scope", segments.join("::"));
+                        Err(self.error(span, message))
+                    }
+                }
+            }
+            ast::Type::Tuple(tuple) => {
+                let fields = tuple.fields().collect::<Vec<_>>();
+                if tuple.is_paren() {
+                    if let [field] = fields.as_slice() {
+                        return self.ty(file, module, generics, field);
+                    }
+                }
+                if fields.is_empty() {
+                    return Ok(Type::Unit);
+                }
+                let fields = fields
+                    .iter()
+                    .map(|field| self.ty(file, module, generics, field))
+                    .collect::<Result<_>>()?;
+                Ok(Type::Tuple(fields))
+            }
+            // references are values like any other
+            ast::Type::Ref(reference) => match reference.ty() {
+                Some(ty) => self.ty(file, module, generics, &ty),
+                None => Err(self.error(span, "expected type")),
+            },
+            ast::Type::Array(_) | ast::Type::Slice(_) => Err(self.unsupported(span, "arrays")),
+            ast::Type::Fn(_) => Err(self.unsupported(span, "function types")),
+            ast::Type::Optional(_) => Err(self.unsupported(span, "optional types")),
+        }
+    }
+
+    /// The instance for the type arguments `args` of the enum or struct whose name is at
+    /// `def`, lowering it the first time, when it's used at `span`.
+    fn enum_id(&mut self, def: Span, args: Vec<Type>, span: Span) -> Result<EnumId> {
+        let key = (def, args);
+        if let Some(&id) = self.enum_ids.get(&key) {
+            return Ok(id);
+        }
+
+        let data = self.module_tree.enum_data(def);
+        if self.lowering_enums.contains(&def) {
+            let name = self.qualified(data.module, &data.name);
+            return Err(self.unsupported(span, &format!("recursive enums such as `{name}`")));
+        }
+
+        let name = self.instance_name(data.module, &data.name, &key.1);
+        let module = data.module;
+        let is_struct = data.is_struct;
+        let generics = data
+            .generics
+            .iter()
+            .cloned()
+            .zip(key.1.iter().cloned())
+            .collect::<Vec<_>>();
+        let node = self.item_node(def);
+
+        self.lowering_enums.push(def);
+        // a struct is lowered as an enum whose only variant is named after it
+        let variants = match StructStmt::cast(node.clone()) {
+            Some(node) => self
+                .record_fields(def.file, module, &generics, node.record_fields())
+                .map(|(names, fields)| {
+                    vec![VariantDef {
+                        name: data.name.clone(),
+                        fields,
+                        field_names: Some(names),
+                    }]
+                }),
+            None => {
+                let node = EnumStmt::cast(node).expect("enums are identified by their names");
+                self.variants(def.file, module, &generics, &node)
+            }
+        };
+        self.lowering_enums.pop();
+
+        let id = EnumId(self.enums.len() as u32);
+        self.enums.push(EnumDef {
+            name,
+            variants: variants?,
+            is_struct,
+        });
+        self.enum_keys.push(key.clone());
+        self.enum_ids.insert(key, id);
+        Ok(id)
+    }
+
+    /// The declaration of the enum or struct whose name is at `def`.
+    fn item_node(&self, def: Span) -> SyntaxNode {
+        self.token(def)
+            .and_then(|name| name.parent())
+            .expect("enums are identified by their names")
+    }
+
+    /// The types of the fields of a variant as written in its enum, which can refer to the
+    /// enum's generic parameters.
+    fn field_types(&self, def: Span, variant: usize) -> Vec<Option<ast::Type>> {
+        let node = self.item_node(def);
+        if let Some(node) = StructStmt::cast(node.clone()) {
+            let fields = node.record_fields();
+            return fields
+                .iter()
+                .flat_map(|list| list.fields())
+                .map(|field| field.ty())
+                .collect();
+        }
+        let variant = EnumStmt::cast(node)
+            .and_then(|node| node.variant_list())
+            .and_then(|list| list.variants().nth(variant));
+        let Some(variant) = variant else {
+            return Vec::new();
+        };
+        if let Some(list) = variant.tuple_fields() {
+            list.types().map(Some).collect()
+        } else if let Some(list) = variant.record_fields() {
+            list.fields().map(|field| field.ty()).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn variants(
+        &mut self,
+        file: FileId,
+        module: ModuleId,
+        generics: &Generics,
+        node: &EnumStmt,
+    ) -> Result<Vec<VariantDef>> {
+        let mut variants = Vec::new();
+        for variant in node
+            .variant_list()
+            .into_iter()
+            .flat_map(|list| list.variants())
+        {
+            let name = variant
+                .name()
+                .map_or(String::new(), |name| name.text().to_string());
+            let mut fields = Vec::new();
+            let mut field_names = None;
+            if let Some(list) = variant.tuple_fields() {
+                for ty in list.types() {
+                    fields.push(self.ty(file, module, generics, &ty)?);
+                }
+            } else if let Some(list) = variant.record_fields() {
+                let (names, types) = self.record_fields(file, module, generics, Some(list))?;
+                fields = types;
+                field_names = Some(names);
+            }
+            variants.push(VariantDef {
+                name,
+                fields,
+                field_names,
+            });
+        }
+        Ok(variants)
+    }
+
+    /// The names and the types of the fields of a struct or a struct variant.
+    fn record_fields(
+        &mut self,
+        file: FileId,
+        module: ModuleId,
+        generics: &Generics,
+        list: Option<RecordFieldList>,
+    ) -> Result<(Vec<String>, Vec<Type>)> {
+        let mut names = Vec::new();
+        let mut types = Vec::new();
+        for field in list.iter().flat_map(|list| list.fields()) {
+            names.push(field.name().map_or(String::new(), |n| n.text().to_string()));
+            let Some(ty) = field.ty() else {
+                let span = Span::new(file, field.syntax().text_range());
+                return Err(self.error(span, "expected type"));
+            };
+            types.push(self.ty(file, module, generics, &ty)?);
+        }
+        Ok((names, types))
+    }
+
+    /// The path of a variant, such as `Shape::Circle`, or the path of a struct.
+    fn variant_name(&self, def: Span, variant: usize) -> String {
+        let data = self.module_tree.enum_data(def);
+        let name = self.qualified(data.module, &data.name);
+        if data.is_struct {
+            return name;
+        }
+        format!("{name}::{}", data.variants[variant].name)
+    }
+
+    /// The index and the type of the field `name` of a value of `ty`, which is a tuple or a
+    /// struct.
+    fn field(&self, ty: &Type, name: &str) -> Option<(usize, Type)> {
+        match ty {
+            Type::Tuple(types) => {
+                let index = name.parse::<usize>().ok()?;
+                Some((index, types.get(index)?.clone()))
+            }
+            Type::Enum(id) if self.enums[id.0 as usize].is_struct => {
+                let variant = &self.enums[id.0 as usize].variants[0];
+                let names = variant.field_names.as_ref()?;
+                let index = names.iter().position(|field| field == name)?;
+                Some((index, variant.fields[index].clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Starts inferring the type arguments of the item at `def` named `name`, a function,
+    /// an enum or a struct as told by `kind`, whose generic parameters are `generics`. The
+    /// types written after its path in `list` are taken as they are.
+    fn inference(
+        &mut self,
+        cx: &FunctionCx,
+        def: Span,
+        generics: Vec<String>,
+        list: Option<GenericArgList>,
+        (kind, name): (&str, &str),
+    ) -> Result<Inference> {
+        let mut args = vec![None; generics.len()];
+        if let Some(list) = list {
+            let types = list.types().collect::<Vec<_>>();
+            if types.len() != generics.len() {
+                let message = generic_count_mismatch(kind, name, generics.len(), types.len());
+                return Err(self.error(cx.span(list.syntax()), message));
+            }
+            for (arg, ty) in args.iter_mut().zip(&types) {
+                *arg = Some(self.ty(cx.file, cx.module, &cx.generics, ty)?);
+            }
+        }
+        Ok(Inference {
+            file: def.file,
+            module: self.module_tree.module_at(def),
+            hints: vec![None; generics.len()],
+            args,
+            generics,
+        })
+    }
+
+    /// Starts inferring the type arguments of the enum or struct at `def`, which are those
+    /// of `expected` if it's an instance of it.
+    fn enum_inference(
+        &mut self,
+        cx: &FunctionCx,
+        def: Span,
+        list: Option<GenericArgList>,
+        expected: Option<&Type>,
+    ) -> Result<Inference> {
+        let data = self.module_tree.enum_data(def);
+        let kind = if data.is_struct { "struct" } else { "enum" };
+        let (generics, name) = (data.generics.clone(), data.name.clone());
+        let mut inference = self.inference(cx, def, generics, list, (kind, &name))?;
+        if let Some(Type::Enum(id)) = expected {
+            let (expected_def, args) = &self.enum_keys[id.0 as usize];
+            if *expected_def == def {
+                inference.hints = args.iter().cloned().map(Some).collect();
+            }
+        }
+        Ok(inference)
+    }
+
+    /// The instance of the enum or struct at `def` with the inferred type arguments.
+    fn enum_instance(&mut self, def: Span, inference: Inference, span: Span) -> Result<EnumId> {
+        let data = self.module_tree.enum_data(def);
+        let kind = if data.is_struct { "struct" } else { "enum" };
+        let name = data.name.clone();
+        let args = self.type_args(inference, (kind, &name), span)?;
+        self.enum_id(def, args, span)
+    }
+
+    /// Lowers `arg`, which is passed to a parameter of type `param`, binding the generic
+    /// parameters which `param` refers to. The type of the result can differ from `param`,
+    /// which is only known once every type argument is.
+    fn infer_arg(
+        &mut self,
+        cx: &mut FunctionCx,
+        inference: &mut Inference,
+        param: Option<&ast::Type>,
+        arg: &ast::Expr,
+    ) -> Result<Expr> {
+        let mut known = Vec::new();
+        let mut unknown = Vec::new();
+        for (index, name) in inference.generics.iter().enumerate() {
+            match inference.args[index]
+                .as_ref()
+                .or(inference.hints[index].as_ref())
+            {
+                Some(ty) => known.push((name.clone(), ty.clone())),
+                None => unknown.push(name.as_str()),
+            }
+        }
+
+        let expected = match param {
+            Some(param) if !refers_to(param, &unknown) => {
+                let (file, module) = (inference.file, inference.module);
+                Some(self.ty(file, module, &known, param)?)
+            }
+            _ => None,
+        };
+        let value = self.expr(cx, arg, expected.as_ref())?;
+        if let Some(param) = param {
+            self.unify(inference, param, &value.ty, false);
+        }
+        Ok(value)
+    }
+
+    /// Binds the generic parameters which `param` refers to, to the parts of `ty` they
+    /// stand for, as hints if `hint` is set. The parameters which are bound are left alone.
+    fn unify(&self, inference: &mut Inference, param: &ast::Type, ty: &Type, hint: bool) {
+        match param {
+            ast::Type::Path(path) => {
+                let segments = path.segments().collect::<Vec<_>>();
+                let segments = segments.iter().map(|s| s.text()).collect::<Vec<_>>();
+                let list = path.generic_arg_list();
+                if let ([name], None) = (segments.as_slice(), &list) {
+                    if let Some(index) = inference.generics.iter().position(|g| g == name) {
+                        let bindings = if hint {
+                            &mut inference.hints
+                        } else {
+                            &mut inference.args
+                        };
+                        // a value which is never produced tells nothing of its type
+                        if bindings[index].is_none() && *ty != Type::Never {
+                            bindings[index] = Some(ty.clone());
+                        }
+                        return;
+                    }
+                }
+
+                let (Some(list), Type::Enum(id)) = (list, ty) else {
+                    return;
+                };
+                let (def, args) = &self.enum_keys[id.0 as usize];
+                if let Ok(Def::Enum(found) | Def::Struct(found)) =
+                    self.module_tree.resolve_path(inference.module, &segments)
+                {
+                    if found == *def {
+                        for (param, ty) in list.types().zip(args) {
+                            self.unify(inference, &param, ty, hint);
+                        }
+                    }
+                }
+            }
+            ast::Type::Tuple(tuple) => {
+                let fields = tuple.fields().collect::<Vec<_>>();
+                match (fields.as_slice(), ty) {
+                    ([field], _) if tuple.is_paren() => self.unify(inference, field, ty, hint),
+                    (fields, Type::Tuple(types)) => {
+                        for (field, ty) in fields.iter().zip(types) {
+                            self.unify(inference, field, ty, hint);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            ast::Type::Ref(reference) => {
+                if let Some(param) = reference.ty() {
+                    self.unify(inference, &param, ty, hint);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The inferred type arguments of the item, a function, an enum or a struct as told by
+    /// `kind`, named `name` and used at `span`.
+    fn type_args(
+        &mut self,
+        inference: Inference,
+        (kind, name): (&str, &str),
+        span: Span,
+    ) -> Result<Vec<Type>> {
+        let checked = self.type_args.get(&span).cloned().unwrap_or_default();
+        let mut types = Vec::new();
+        let bindings = inference.args.into_iter().zip(inference.hints);
+        for (index, ((arg, hint), param)) in bindings.zip(&inference.generics).enumerate() {
+            let ty = match (arg.or(hint), checked.get(index)) {
+                (Some(ty), _) => Some(ty),
+                (None, Some(ty)) => self.checked_type(ty, span)?,
+                (None, None) => None,
+            };
+            let Some(ty) = ty else {
+                let message = format!(
+                    "type annotations needed: cannot infer type of the type parameter `{param}` declared on the {kind} `{name}`"
+                );
+                return Err(self.error(span, message));
+            };
+            types.push(ty);
+        }
+        Ok(types)
+    }
+
+    /// The type a type the checker told stands for, `None` if the checker doesn't tell it
+    /// completely, as for integers whose type it doesn't know.
+    fn checked_type(&mut self, ty: &checker::Ty, span: Span) -> Result<Option<Type>> {
+        let ty = match ty {
+            checker::Ty::Int(Some(ty)) => Type::Int(*ty),
+            checker::Ty::Bool => Type::Bool,
+            checker::Ty::Char => Type::Char,
+            checker::Ty::String => Type::Str,
+            checker::Ty::Unit => Type::Unit,
+            checker::Ty::Tuple(types) => {
+                let mut fields = Vec::new();
+                for ty in types {
+                    let Some(ty) = self.checked_type(ty, span)? else {
+                        return Ok(None);
+                    };
+                    fields.push(ty);
+                }
+                Type::Tuple(fields)
+            }
+            checker::Ty::Enum(def, args) => {
+                if args.len() != self.module_tree.enum_data(*def).generics.len() {
+                    return Ok(None);
+                }
+                let mut types = Vec::new();
+                for ty in args {
+                    let Some(ty) = self.checked_type(ty, span)? else {
+                        return Ok(None);
+                    };
+                    types.push(ty);
+                }
+                Type::Enum(self.enum_id(*def, types, span)?)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(ty))
+    }
+
+    /// Checks that the arguments lowered by [`Self::infer_arg`] fit the types of the
+    /// parameters of the instance.
+    fn check_args(&mut self, values: &[Expr], types: &[Type]) -> Result<()> {
+        for (value, ty) in values.iter().zip(types) {
+            if !fits(ty, &value.ty) {
+                return Err(self.mismatch(value.span, ty, &value.ty));
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowers `expr`, which must be of type `ty`.
+    fn expect(&mut self, cx: &mut FunctionCx, expr: &ast::Expr, ty: &Type) -> Result<Expr> {
+        let lowered = self.expr(cx, expr, Some(ty))?;
+        if !fits(ty, &lowered.ty) {
+            return Err(self.mismatch(lowered.span, ty, &lowered.ty));
+        }
+        Ok(lowered)
+    }
+
+    fn expect_opt(
+        &mut self,
+        cx: &mut FunctionCx,
+        expr: Option<ast::Expr>,
+        parent: &SyntaxNode,
+        ty: &Type,
+    ) -> Result<Expr> {
+        match expr {
+            Some(expr) => self.expect(cx, &expr, ty),
+            None => Err(self.error(cx.span(parent), "expected expression")),
+        }
+    }
+
+    fn expr_opt(
+        &mut self,
+        cx: &mut FunctionCx,
+        expr: Option<ast::Expr>,
+        parent: &SyntaxNode,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        match expr {
+            Some(expr) => self.expr(cx, &expr, expected),
+            None => Err(self.error(cx.span(parent), "expected expression")),
+        }
+    }
+
+    /// Lowers `expr`, inferring the types of its literals from `expected` if they don't have
+    /// suffixes. The type of the result can differ from `expected`.
+    fn expr(
+        &mut self,
+        cx: &mut FunctionCx,
+        expr: &ast::Expr,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        let span = cx.span(expr.syntax());
+        let (kind, ty) = match expr {
+            ast::Expr::Literal(literal) => {
+                let Some(token) = literal.token() else {
+                    return Err(self.error(span, "expected literal"));
+                };
+                let (literal, ty) = self.literal(&token, false, expected, span)?;
+                (ExprKind::Literal(literal), ty)
+            }
+            ast::Expr::InterpolatedString(_) => {
+                return Err(self.unsupported(span, "formatted strings outside of `print` calls"))
+            }
+            ast::Expr::VariableRef(variable) => {
+                let segments = variable.segments().collect::<Vec<_>>();
+                let segments = segments.iter().map(|s| s.text()).collect::<Vec<_>>();
+                if let [name] = segments.as_slice() {
+                    if let Some(local) = cx.lookup(name) {
+                        let ty = cx.locals[local.0 as usize].ty.clone();
+                        return Ok(Expr {
+                            kind: ExprKind::Local(local),
+                            ty,
+                            span,
+                        });
+                    }
+                }
+
+                match self.module_tree.resolve_path(cx.module, &segments) {
+                    Ok(Def::Variant(def, variant)) => {
+                        if !self.field_types(def, variant).is_empty() {
+                            let message = format!(
+                                "expected value, found variant `{}` with fields",
+                                self.variant_name(def, variant)
+                            );
+                            return Err(self.error(span, message));
+                        }
+                        let list = variable.generic_arg_list();
+                        let inference = self.enum_inference(cx, def, list, expected)?;
+                        let id = self.enum_instance(def, inference, span)?;
+                        (ExprKind::Variant(id, variant, Vec::new()), Type::Enum(id))
+                    }
+                    Ok(Def::Function(_)) => {
+                        return Err(self.unsupported(span, "functions used as values"))
+                    }
+                    Ok(Def::Struct(_)) => {
+                        let message =
+                            format!("expected value, found struct `{}`", segments.join("::"));
+                        return Err(self.error(span, message));
+                    }
+                    _ => {
+                        let message =
+                            format!("cannot find value `{}` in this scope", segments.join("::"));
+                        return Err(self.error(span, message));
+                    }
+                }
+            }
+            ast::Expr::Paren(paren) => {
+                return self.expr_opt(cx, paren.expr(), paren.syntax(), expected)
+            }
+            ast::Expr::Tuple(tuple) => {
+                let elements = tuple.elements().collect::<Vec<_>>();
+                if elements.is_empty() {
+                    return Ok(unit(span));
+                }
+                let expected = match expected {
+                    Some(Type::Tuple(types)) if types.len() == elements.len() => Some(types),
+                    _ => None,
+                };
+                let mut lowered = Vec::new();
+                for (index, element) in elements.iter().enumerate() {
+                    let expected = expected.map(|types| &types[index]);
+                    lowered.push(self.expr(cx, element, expected)?);
+                }
+                let ty = Type::Tuple(lowered.iter().map(|element| element.ty.clone()).collect());
+                (ExprKind::Tuple(lowered), ty)
+            }
+            ast::Expr::Prefix(prefix) => return self.prefix(cx, prefix, expected),
+            ast::Expr::Binary(binary) => return self.binary(cx, binary, expected),
+            ast::Expr::Call(call) => return self.call(cx, call, expected),
+            ast::Expr::MethodCall(_) => return Err(self.unsupported(span, "method calls")),
+            ast::Expr::Field(field) => {
+                let receiver = self.expr_opt(cx, field.receiver(), field.syntax(), None)?;
+                let name = field
+                    .field()
+                    .map_or(String::new(), |name| name.text().to_string());
+                let Some((index, ty)) = self.field(&receiver.ty, &name) else {
+                    let message = format!(
+                        "no field `{name}` on type `{}`",
+                        self.type_name(&receiver.ty)
+                    );
+                    return Err(self.error(span, message));
+                };
+                (ExprKind::Field(Box::new(receiver), index), ty)
+            }
+            ast::Expr::Block(block) => return self.block(cx, block, expected),
+            ast::Expr::Function(_) => return Err(self.unsupported(span, "nested functions")),
+            ast::Expr::Closure(_) => return Err(self.unsupported(span, "closures")),
+            ast::Expr::Match(match_expr) => return self.match_expr(cx, match_expr, expected),
+            ast::Expr::If(if_expr) => return self.if_expr(cx, if_expr, expected),
+            ast::Expr::Array(_) | ast::Expr::ArrayRepeat(_) | ast::Expr::Index(_) => {
+                return Err(self.unsupported(span, "arrays"))
+            }
+            ast::Expr::Record(record) => return self.record(cx, record, expected),
+        };
+
+        Ok(Expr { kind, ty, span })
+    }
+
+    /// The value and the type of a literal, negated if it follows a `-`.
+    fn literal(
+        &mut self,
+        token: &SyntaxToken,
+        negative: bool,
+        expected: Option<&Type>,
+        span: Span,
+    ) -> Result<(Literal, Type)> {
+        let text = token.text();
+        let (literal, ty) = match token.kind() {
+            SyntaxKind::Number => match literal::number(text) {
+                Ok(Number::Int(value, suffix)) => {
+                    let ty = suffix
+                        .or(match expected {
+                            Some(Type::Int(ty)) => Some(*ty),
+                            _ => None,
+                        })
+                        .unwrap_or(IntTy::I64);
+                    let value = if negative {
+                        -i128::from(value)
+                    } else {
+                        i128::from(value)
+                    };
+                    if negative && !ty.is_signed() {
+                        let message =
+                            format!("cannot apply unary operator `-` to type `{}`", ty.name());
+                        return Err(self.error(span, message));
+                    }
+                    if value < ty.min() || value > ty.max() {
+                        let message = format!("literal out of range for `{}`", ty.name());
+                        return Err(self.error(span, message));
+                    }
+                    (Literal::Int(value), Type::Int(ty))
+                }
+                Ok(Number::Float(value, suffix)) => {
+                    let ty = suffix
+                        .or(match expected {
+                            Some(Type::Float(ty)) => Some(*ty),
+                            _ => None,
+                        })
+                        .unwrap_or(FloatTy::F64);
+                    let value = if negative { -value } else { value };
+                    (Literal::Float(value), Type::Float(ty))
+                }
+                Err(message) => return Err(self.error(span, message)),
+            },
+            SyntaxKind::Bool => (Literal::Bool(text == "true"), Type::Bool),
+            SyntaxKind::Character => match literal::char(text) {
+                Ok(c) => (Literal::Char(c), Type::Char),
+                Err(message) => return Err(self.error(span, message)),
+            },
+            SyntaxKind::String => match literal::string(text) {
+                Ok(s) => (Literal::Str(s), Type::Str),
+                Err(message) => return Err(self.error(span, message)),
+            },
+            kind => return Err(self.error(span, format!("{kind} is not a literal"))),
+        };
+
+        if negative && !matches!(ty, Type::Int(_) | Type::Float(_)) {
+            let message = format!(
+                "cannot apply unary operator `-` to type `{}`",
+                self.type_name(&ty)
+            );
+            return Err(self.error(span, message));
+        }
+        Ok((literal, ty))
+    }
+
+    fn prefix(
+        &mut self,
+        cx: &mut FunctionCx,
+        prefix: &PrefixExpr,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        let span = cx.span(prefix.syntax());
+        let Some(op) = prefix.op() else {
+            return Err(self.error(span, "expected operator"));
+        };
+
+        // `-1` is a literal, so that the minimum of signed types can be written
+        if op.kind() == SyntaxKind::Minus {
+            if let Some(ast::Expr::Literal(literal)) = prefix.operand() {
+                if let Some(token) = literal.token() {
+                    let (literal, ty) = self.literal(&token, true, expected, span)?;
+                    return Ok(Expr {
+                        kind: ExprKind::Literal(literal),
+                        ty,
+                        span,
+                    });
+                }
+            }
+        }
+
+        let operand = self.expr_opt(cx, prefix.operand(), prefix.syntax(), expected)?;
+        let op = match (op.kind(), &operand.ty) {
+            (SyntaxKind::Minus, Type::Int(ty)) if ty.is_signed() => UnaryOp::Neg,
+            (SyntaxKind::Minus, Type::Float(_)) => UnaryOp::Neg,
+            (SyntaxKind::Bang, Type::Bool) => UnaryOp::Not,
+            (SyntaxKind::Tilde, Type::Int(_)) => UnaryOp::BitNot,
+            (op, ty) => {
+                let message = format!(
+                    "cannot apply unary operator {op} to type `{}`",
+                    self.type_name(ty)
+                );
+                return Err(self.error(span, message));
+            }
+        };
+        let ty = operand.ty.clone();
+        Ok(Expr {
+            kind: ExprKind::Unary(op, Box::new(operand)),
+            ty,
+            span,
+        })
+    }
+
+    fn binary(
+        &mut self,
+        cx: &mut FunctionCx,
+        binary: &BinaryExpr,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        let span = cx.span(binary.syntax());
+        let Some(op_token) = binary.op() else {
+            return Err(self.error(span, "expected operator"));
+        };
+        if op_token.kind().is_assignment() {
+            return self.assignment(cx, binary, &op_token);
+        }
+        let Some(op) = binary_op(op_token.kind()) else {
+            return Err(self.error(
+                span,
+                format!("{} is not a binary operator", op_token.kind()),
+            ));
+        };
+
+        if matches!(op, BinaryOp::And | BinaryOp::Or) {
+            let lhs = self.expect_opt(cx, binary.lhs(), binary.syntax(), &Type::Bool)?;
+            let rhs = self.expect_opt(cx, binary.rhs(), binary.syntax(), &Type::Bool)?;
+            return Ok(Expr {
+                kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+                ty: Type::Bool,
+                span,
+            });
+        }
+
+        // the operands have the same type, which unsuffixed literals take from the other one
+        let (Some(lhs), Some(rhs)) = (binary.lhs(), binary.rhs()) else {
+            return Err(self.error(span, "expected expression"));
+        };
+        let expected = match expected {
+            Some(ty @ (Type::Int(_) | Type::Float(_))) if !op.is_comparison() => Some(ty),
+            _ => None,
+        };
+        let (lhs, rhs) = if is_unsuffixed_number(&lhs) && !is_unsuffixed_number(&rhs) {
+            let rhs = self.expr(cx, &rhs, expected)?;
+            let lhs = self.expr(cx, &lhs, Some(&rhs.ty))?;
+            (lhs, rhs)
+        } else {
+            let lhs = self.expr(cx, &lhs, expected)?;
+            let rhs = self.expr(cx, &rhs, Some(&lhs.ty))?;
+            (lhs, rhs)
+        };
+
+        let Some(ty) = self.binary_type(op, &lhs.ty, &rhs.ty) else {
+            let message = format!(
+                "cannot apply {} to `{}` and `{}`",
+                op_token.kind(),
+                self.type_name(&lhs.ty),
+                self.type_name(&rhs.ty)
+            );
+            return Err(self.error(span, message));
+        };
+        if op == BinaryOp::Add && ty == Type::Str {
+            return Err(self.unsupported(span, "string concatenation"));
+        }
+        Ok(Expr {
+            kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+            ty,
+            span,
+        })
+    }
+
+    /// The type of the result of `op`, if it applies to operands of these types.
+    fn binary_type(&self, op: BinaryOp, lhs: &Type, rhs: &Type) -> Option<Type> {
+        // an operand which never produces a value takes the type of the other one
+        let ty = join(lhs, rhs);
+        match op {
+            BinaryOp::Shl | BinaryOp::Shr => match (lhs, rhs) {
+                (Type::Int(_), Type::Int(_)) => Some(lhs.clone()),
+                _ => None,
+            },
+            BinaryOp::Eq | BinaryOp::Ne => ty.map(|_| Type::Bool),
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => match ty? {
+                Type::Int(_) | Type::Float(_) | Type::Char | Type::Str => Some(Type::Bool),
+                _ => None,
+            },
+            BinaryOp::Add => match ty? {
+                ty @ (Type::Int(_) | Type::Float(_) | Type::Str) => Some(ty),
+                _ => None,
+            },
+            BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => match ty? {
+                ty @ (Type::Int(_) | Type::Float(_)) => Some(ty),
+                _ => None,
+            },
+            BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor => match ty? {
+                ty @ Type::Int(_) => Some(ty),
+                _ => None,
+            },
+            BinaryOp::And | BinaryOp::Or => Some(Type::Bool),
+        }
+    }
+
+    fn assignment(
+        &mut self,
+        cx: &mut FunctionCx,
+        binary: &BinaryExpr,
+        op_token: &SyntaxToken,
+    ) -> Result<Expr> {
+        let span = cx.span(binary.syntax());
+        let place = match binary.lhs() {
+            Some(lhs) => self.place(cx, &lhs)?,
+            None => return Err(self.error(span, "expected expression")),
+        };
+        let op = compound_assignment(op_token.kind());
+        let value = match op {
+            Some(BinaryOp::Shl | BinaryOp::Shr) => {
+                self.expr_opt(cx, binary.rhs(), binary.syntax(), Some(&place.ty))?
+            }
+            _ => self.expect_opt(cx, binary.rhs(), binary.syntax(), &place.ty)?,
+        };
+        if let Some(op) = op {
+            let result = self.binary_type(op, &place.ty, &value.ty);
+            if result.as_ref() != Some(&place.ty) || place.ty == Type::Str {
+                let message = format!(
+                    "cannot apply {} to `{}` and `{}`",
+                    op_token.kind(),
+                    self.type_name(&place.ty),
+                    self.type_name(&value.ty)
+                );
+                return Err(self.error(span, message));
+            }
+        }
+
+        Ok(Expr {
+            kind: ExprKind::Assign(place, op, Box::new(value)),
+            ty: Type::Unit,
+            span,
+        })
+    }
+
+    /// The place an assignment writes to: a local or an element of a tuple or a field of a
+    /// struct stored in one.
+    fn place(&mut self, cx: &mut FunctionCx, expr: &ast::Expr) -> Result<Place> {
+        let span = cx.span(expr.syntax());
+        match expr {
+            ast::Expr::VariableRef(variable) => {
+                let segments = variable.segments().collect::<Vec<_>>();
+                if let [name] = segments.as_slice() {
+                    if let Some(local) = cx.lookup(name.text()) {
+                        let ty = cx.locals[local.0 as usize].ty.clone();
+                        return Ok(Place {
+                            local,
+                            fields: Vec::new(),
+                            ty,
+                        });
+                    }
+                }
+            }
+            ast::Expr::Paren(paren) => {
+                if let Some(expr) = paren.expr() {
+                    return self.place(cx, &expr);
+                }
+            }
+            ast::Expr::Field(field) => {
+                if let Some(receiver) = field.receiver() {
+                    let mut place = self.place(cx, &receiver)?;
+                    let name = field
+                        .field()
+                        .map_or(String::new(), |name| name.text().to_string());
+                    let Some((index, ty)) = self.field(&place.ty, &name) else {
+                        let message =
+                            format!("no field `{name}` on type `{}`", self.type_name(&place.ty));
+                        return Err(self.error(span, message));
+                    };
+                    place.fields.push(index);
+                    place.ty = ty;
+                    return Ok(place);
+                }
+            }
+            ast::Expr::Index(_) => return Err(self.unsupported(span, "arrays")),
+            _ => {}
+        }
+        Err(self.error(span, "invalid left-hand side of assignment"))
+    }
+
+    fn block(
+        &mut self,
+        cx: &mut FunctionCx,
+        block: &BlockExpr,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        cx.scopes.push(HashMap::new());
+        let result = self.block_stmts(cx, block, expected);
+        cx.scopes.pop();
+        result
+    }
+
+    fn block_stmts(
+        &mut self,
+        cx: &mut FunctionCx,
+        block: &BlockExpr,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        let span = cx.span(block.syntax());
+        let tail = block.tail_expr();
+        let mut stmts = Vec::new();
+        let mut diverges = false;
+
+        let mut ast_stmts = block.stmts().peekable();
+        while let Some(stmt) = ast_stmts.next() {
+            // the tail expression is the last statement
+            if tail.is_some() && ast_stmts.peek().is_none() {
+                break;
+            }
+
+            match stmt {
+                ast::Stmt::Let(stmt) => {
+                    let ty = match stmt.ty() {
+                        Some(ty) => Some(self.ty(cx.file, cx.module, &cx.generics, &ty)?),
+                        None => None,
+                    };
+                    let value = match &ty {
+                        Some(ty) => self.expect_opt(cx, stmt.initializer(), stmt.syntax(), ty)?,
+                        None => self.expr_opt(cx, stmt.initializer(), stmt.syntax(), None)?,
+                    };
+                    let ty = ty.unwrap_or_else(|| value.ty.clone());
+                    diverges |= value.ty == Type::Never;
+
+                    let pattern = if let Some(name) = stmt.name() {
+                        Pattern::Binding(cx.declare(name.text(), ty))
+                    } else if let Some(pattern) = stmt.pattern() {
+                        let lowered = self.pattern(cx, &pattern, &ty)?;
+                        if !lowered.is_irrefutable() {
+                            let span = cx.span(pattern.syntax());
+                            return Err(self.error(span, "refutable pattern in local binding"));
+                        }
+                        lowered
+                    } else {
+                        Pattern::Wildcard
+                    };
+                    stmts.push(Stmt::Let(pattern, value));
+                }
+                ast::Stmt::Return(stmt) => {
+                    let ret = cx.ret.clone();
+                    let value = match stmt.value() {
+                        Some(value) => self.expect(cx, &value, &ret)?,
+                        None if ret == Type::Unit => unit(cx.span(stmt.syntax())),
+                        None => {
+                            return Err(self.mismatch(cx.span(stmt.syntax()), &ret, &Type::Unit))
+                        }
+                    };
+                    stmts.push(Stmt::Return(value));
+                    diverges = true;
+                }
+                ast::Stmt::Expr(stmt) => {
+                    let Some(expr) = stmt.expr() else {
+                        continue;
+                    };
+                    let expr = self.expr(cx, &expr, None)?;
+                    diverges |= expr.ty == Type::Never;
+                    stmts.push(Stmt::Expr(expr));
+                }
+                // items are collected when the module tree is built
+                ast::Stmt::Mod(_)
+                | ast::Stmt::Use(_)
+                | ast::Stmt::Enum(_)
+                | ast::Stmt::Struct(_)
+                | ast::Stmt::Trait(_)
+                | ast::Stmt::Impl(_) => {}
+            }
+        }
+
+        let (tail, ty) = match tail {
+            Some(tail) => {
+                let tail = self.expr(cx, &tail, expected)?;
+                let ty = if diverges {
+                    Type::Never
+                } else {
+                    tail.ty.clone()
+                };
+                (Some(Box::new(tail)), ty)
+            }
+            None if diverges => (None, Type::Never),
+            None => (None, Type::Unit),
+        };
+        Ok(Expr {
+            kind: ExprKind::Block(stmts, tail),
+            ty,
+            span,
+        })
+    }
+
+    fn if_expr(
+        &mut self,
+        cx: &mut FunctionCx,
+        if_expr: &IfExpr,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        let span = cx.span(if_expr.syntax());
+        let condition = self.expect_opt(cx, if_expr.condition(), if_expr.syntax(), &Type::Bool)?;
+        let Some(then_branch) = if_expr.then_branch() else {
+            return Err(self.error(span, "expected block"));
+        };
+        let then_branch = self.block(cx, &then_branch, expected)?;
+
+        let expected = match (expected, &then_branch.ty) {
+            (Some(ty), _) => Some(ty.clone()),
+            (None, Type::Never) => None,
+            (None, ty) => Some(ty.clone()),
+        };
+        let else_branch = match if_expr.else_branch() {
+            Some(ElseBranch::Block(block)) => Some(self.block(cx, &block, expected.as_ref())?),
+            Some(ElseBranch::If(if_expr)) => Some(self.if_expr(cx, &if_expr, expected.as_ref())?),
+            None => None,
+        };
+
+        let ty = match &else_branch {
+            Some(else_branch) => match join(&then_branch.ty, &else_branch.ty) {
+                Some(ty) => ty,
+                None => {
+                    let message = format!(
+                        "`if` and `else` have incompatible types: expected `{}`, found `{}`",
+                        self.type_name(&then_branch.ty),
+                        self.type_name(&else_branch.ty)
+                    );
+                    return Err(self.error(else_branch.span, message));
+                }
+            },
+            None if fits(&Type::Unit, &then_branch.ty) => Type::Unit,
+            None => {
+                let message = format!(
+                    "`if` without `else` must evaluate to `()`, found `{}`",
+                    self.type_name(&then_branch.ty)
+                );
+                return Err(self.error(then_branch.span, message));
+            }
+        };
+
+        Ok(Expr {
+            kind: ExprKind::If(
+                Box::new(condition),
+                Box::new(then_branch),
+                else_branch.map(Box::new),
+            ),
+            ty,
+            span,
+        })
+    }
+
+    fn match_expr(
+        &mut self,
+        cx: &mut FunctionCx,
+        match_expr: &MatchExpr,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        let span = cx.span(match_expr.syntax());
+        let scrutinee = self.expr_opt(cx, match_expr.scrutinee(), match_expr.syntax(), None)?;
+
+        let mut ty = Type::Never;
+        let mut arms = Vec::new();
+        for arm in match_expr
+            .arm_list()
+            .into_iter()
+            .flat_map(|list| list.arms())
+        {
+            cx.scopes.push(HashMap::new());
+            let arm = self.arm(
+                cx,
+                &arm,
+                &scrutinee.ty,
+                expected.or(match ty {
+                    Type::Never => None,
+                    ref ty => Some(ty),
+                }),
+            );
+            cx.scopes.pop();
+            let arm = arm?;
+
+            ty = match join(&ty, &arm.body.ty) {
+                Some(ty) => ty,
+                None => {
+                    let message = format!(
+                        "`match` arms have incompatible types: expected `{}`, found `{}`",
+                        self.type_name(&ty),
+                        self.type_name(&arm.body.ty)
+                    );
+                    return Err(self.error(arm.body.span, message));
+                }
+            };
+            arms.push(arm);
+        }
+
+        Ok(Expr {
+            kind: ExprKind::Match(Box::new(scrutinee), arms),
+            ty,
+            span,
+        })
+    }
+
+    fn arm(
+        &mut self,
+        cx: &mut FunctionCx,
+        arm: &ast::MatchArm,
+        scrutinee: &Type,
+        expected: Option<&Type>,
+    ) -> Result<Arm> {
+        let Some(pattern) = arm.pattern() else {
+            return Err(self.error(cx.span(arm.syntax()), "expected pattern"));
+        };
+        let pattern = self.pattern(cx, &pattern, scrutinee)?;
+        let guard = match arm.guard() {
+            Some(guard) => {
+                Some(self.expect_opt(cx, guard.condition(), guard.syntax(), &Type::Bool)?)
+            }
+            None => None,
+        };
+        let body = self.expr_opt(cx, arm.expr(), arm.syntax(), expected)?;
+        Ok(Arm {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
+    fn pattern(
+        &mut self,
+        cx: &mut FunctionCx,
+        pattern: &ast::Pattern,
+        ty: &Type,
+    ) -> Result<Pattern> {
+        let span = cx.span(pattern.syntax());
+        match pattern {
+            ast::Pattern::Wildcard(_) => Ok(Pattern::Wildcard),
+            ast::Pattern::Rest(_) => {
+                Err(self.error(span, "`..` can only be used in tuple and variant patterns"))
+            }
+            ast::Pattern::Ident(ident) => {
+                let Some(name) = ident.name() else {
+                    return Ok(Pattern::Wildcard);
+                };
+
+                // like in Rust, a name matches the unit variant it refers to instead of binding
+                if let Ok(Def::Variant(def, variant)) =
+                    self.module_tree.resolve_path(cx.module, &[name.text()])
+                {
+                    let fields = &self.module_tree.enum_data(def).variants[variant].fields;
+                    if *fields == VariantFields::Unit {
+                        return self.variant_pattern(cx, span, def, variant, &[], ty);
+                    }
+                }
+                Ok(Pattern::Binding(cx.declare(name.text(), ty.clone())))
+            }
+            ast::Pattern::Literal(literal) => {
+                let Some(token) = literal.token() else {
+                    return Err(self.error(span, "expected literal"));
+                };
+                let (literal, found) =
+                    self.literal(&token, literal.is_negative(), Some(ty), span)?;
+                if found != *ty {
+                    return Err(self.mismatch(span, ty, &found));
+                }
+                Ok(Pattern::Literal(literal))
+            }
+            ast::Pattern::Range(range) => {
+                let (Some(start), Some(end)) = (range.start(), range.end()) else {
+                    return Err(self.error(span, "expected range end"));
+                };
+                let mut bound = |literal: &ast::LiteralPattern| {
+                    let span = cx.span(literal.syntax());
+                    let Some(token) = literal.token() else {
+                        return Err(self.error(span, "expected literal"));
+                    };
+                    let (value, found) =
+                        self.literal(&token, literal.is_negative(), Some(ty), span)?;
+                    if found != *ty || !matches!(ty, Type::Int(_) | Type::Char) {
+                        return Err(self.mismatch(span, ty, &found));
+                    }
+                    Ok(value)
+                };
+                Ok(Pattern::Range {
+                    start: bound(&start)?,
+                    end: bound(&end)?,
+                    inclusive: range.is_inclusive(),
+                })
+            }
+            ast::Pattern::Or(or) => {
+                let alternatives = or
+                    .patterns()
+                    .map(|alternative| self.pattern(cx, &alternative, ty))
+                    .collect::<Result<_>>()?;
+                Ok(Pattern::Or(alternatives))
+            }
+            ast::Pattern::Tuple(tuple) => {
+                let patterns = tuple.patterns().collect::<Vec<_>>();
+                let types = match ty {
+                    Type::Tuple(types) => types.clone(),
+                    Type::Unit => Vec::new(),
+                    _ => {
+                        let message = format!(
+                            "mismatched types: expected `{}`, found tuple",
+                            self.type_name(ty)
+                        );
+                        return Err(self.error(span, message));
+                    }
+                };
+                let elements = self.positional(cx, span, &patterns, &types)?;
+                if types.is_empty() {
+                    return Ok(Pattern::Literal(Literal::Unit));
+                }
+                Ok(Pattern::Tuple(elements))
+            }
+            ast::Pattern::TupleStruct(tuple) => {
+                let segments = tuple.segments().collect::<Vec<_>>();
+                let (def, variant) = self.pattern_variant(cx, span, &segments)?;
+                let patterns = tuple
+                    .pattern_list()
+                    .into_iter()
+                    .flat_map(|list| list.patterns())
+                    .collect::<Vec<_>>();
+                if !matches!(
+                    self.module_tree.enum_data(def).variants[variant].fields,
+                    VariantFields::Tuple(_)
+                ) {
+                    return Err(self.error(span, "expected tuple variant"));
+                }
+                self.variant_pattern(cx, span, def, variant, &patterns, ty)
+            }
+            ast::Pattern::Path(path) => {
+                let segments = path.segments().collect::<Vec<_>>();
+                let (def, variant) = self.pattern_variant(cx, span, &segments)?;
+                if self.module_tree.enum_data(def).variants[variant].fields != VariantFields::Unit {
+                    return Err(self.error(span, "expected unit variant"));
+                }
+                self.variant_pattern(cx, span, def, variant, &[], ty)
+            }
+            ast::Pattern::Struct(record) => {
+                let segments = record.segments().collect::<Vec<_>>();
+                let (def, variant) = self.pattern_variant(cx, span, &segments)?;
+                let VariantFields::Record(names) = self.module_tree.enum_data(def).variants
+                    [variant]
+                    .fields
+                    .clone()
+                else {
+                    return Err(self.error(span, "expected struct variant"));
+                };
+
+                // the fields are put in declaration order, the missing ones being wildcards
+                let list = record.field_list();
+                let mut patterns = vec![None; names.len()];
+                for field in list.iter().flat_map(|list| list.fields()) {
+                    let Some(name) = field.name() else {
+                        continue;
+                    };
+                    let Some(index) = names.iter().position(|n| n == name.text()) else {
+                        let message = format!("variant has no field named `{}`", name.text());
+                        return Err(self.error(cx.span(field.syntax()), message));
+                    };
+                    patterns[index] = Some(field.pattern().map_or_else(
+                        // `Variant { x }` binds `x`
+                        || PatternSyntax::Binding(name.clone()),
+                        PatternSyntax::Pattern,
+                    ));
+                }
+                let has_rest = list.as_ref().is_some_and(|list| list.has_rest());
+                if let Some(index) = patterns.iter().position(Option::is_none) {
+                    if !has_rest {
+                        let message = format!("pattern does not mention field `{}`", names[index]);
+                        return Err(self.error(span, message));
+                    }
+                }
+
+                let id = self.pattern_enum(def, ty, span)?;
+                let types = self.enums[id.0 as usize].variants[variant].fields.clone();
+                let mut fields = Vec::new();
+                for (pattern, ty) in patterns.into_iter().zip(&types) {
+                    fields.push(match pattern {
+                        Some(PatternSyntax::Pattern(pattern)) => self.pattern(cx, &pattern, ty)?,
+                        Some(PatternSyntax::Binding(name)) => {
+                            Pattern::Binding(cx.declare(name.text(), ty.clone()))
+                        }
+                        None => Pattern::Wildcard,
+                    });
+                }
+                Ok(Pattern::Variant(id, variant, fields))
+            }
+        }
+    }
+
+    /// The patterns of the elements of a tuple or a tuple variant of `types`, where `..`
+    /// stands for any number of wildcards.
+    fn positional(
+        &mut self,
+        cx: &mut FunctionCx,
+        span: Span,
+        patterns: &[ast::Pattern],
+        types: &[Type],
+    ) -> Result<Vec<Pattern>> {
+        let rest = patterns
+            .iter()
+            .position(|pattern| matches!(pattern, ast::Pattern::Rest(_)));
+        let len = patterns.len() - usize::from(rest.is_some());
+        if len > types.len() || (rest.is_none() && len < types.len()) {
+            let message = format!(
+                "this pattern has {len} field{}, but the type has {} field{}",
+                if len == 1 { "" } else { "s" },
+                types.len(),
+                if types.len() == 1 { "" } else { "s" },
+            );
+            return Err(self.error(span, message));
+        }
+
+        let mut lowered = Vec::new();
+        let (before, after) = match rest {
+            Some(rest) => (&patterns[..rest], &patterns[rest + 1..]),
+            None => (patterns, &[][..]),
+        };
+        for (pattern, ty) in before.iter().zip(types) {
+            lowered.push(self.pattern(cx, pattern, ty)?);
+        }
+        let skipped = types.len() - before.len() - after.len();
+        lowered.extend((0..skipped).map(|_| Pattern::Wildcard));
+        for (pattern, ty) in after.iter().zip(&types[before.len() + skipped..]) {
+            lowered.push(self.pattern(cx, pattern, ty)?);
+        }
+        Ok(lowered)
+    }
+
+    fn pattern_variant(
+        &mut self,
+        cx: &FunctionCx,
+        span: Span,
+        segments: &[SyntaxToken],
+    ) -> Result<(Span, usize)> {
+        let path = segments.iter().map(|s| s.text()).collect::<Vec<_>>();
+        match self.module_tree.resolve_path(cx.module, &path) {
+            Ok(Def::Variant(def, variant)) => Ok((def, variant)),
+            Ok(Def::Struct(def)) => Ok((def, 0)),
+            _ => {
+                let message = format!("cannot find variant `{}` in this scope", path.join("::"));
+                Err(self.error(span, message))
+            }
+        }
+    }
+
+    /// The instance of the enum or struct at `def` which a pattern matching values of `ty`
+    /// refers to, which must be `ty` itself.
+    fn pattern_enum(&mut self, def: Span, ty: &Type, span: Span) -> Result<EnumId> {
+        if let Type::Enum(id) = ty {
+            if self.enum_keys[id.0 as usize].0 == def {
+                return Ok(*id);
+            }
+        }
+        let data = self.module_tree.enum_data(def);
+        if !data.generics.is_empty() {
+            let message = format!(
+                "mismatched types: expected `{}`, found `{}`",
+                self.type_name(ty),
+                self.qualified(data.module, &data.name)
+            );
+            return Err(self.error(span, message));
+        }
+        let id = self.enum_id(def, Vec::new(), span)?;
+        Err(self.mismatch(span, ty, &Type::Enum(id)))
+    }
+
+    fn variant_pattern(
+        &mut self,
+        cx: &mut FunctionCx,
+        span: Span,
+        def: Span,
+        variant: usize,
+        patterns: &[ast::Pattern],
+        ty: &Type,
+    ) -> Result<Pattern> {
+        let id = self.pattern_enum(def, ty, span)?;
+        let types = self.enums[id.0 as usize].variants[variant].fields.clone();
+        let fields = self.positional(cx, span, patterns, &types)?;
+        Ok(Pattern::Variant(id, variant, fields))
+    }
+
+    fn record(
+        &mut self,
+        cx: &mut FunctionCx,
+        record: &RecordExpr,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        let span = cx.span(record.syntax());
+        let segments = record.segments().collect::<Vec<_>>();
+        let path = segments.iter().map(|s| s.text()).collect::<Vec<_>>();
+        let (def, variant) = match self.module_tree.resolve_path(cx.module, &path) {
+            Ok(Def::Variant(def, variant)) => (def, variant),
+            Ok(Def::Struct(def)) => (def, 0),
+            _ => {
+                let message = format!(
+                    "cannot find struct or variant `{}` in this scope",
+                    path.join("::")
+                );
+                return Err(self.error(span, message));
+            }
+        };
+        let VariantFields::Record(names) = self.module_tree.enum_data(def).variants[variant]
+            .fields
+            .clone()
+        else {
+            let message = format!("`{}` is not a struct variant", path.join("::"));
+            return Err(self.error(span, message));
+        };
+        let is_struct = self.module_tree.enum_data(def).is_struct;
+        let mut inference = self.enum_inference(cx, def, record.generic_arg_list(), expected)?;
+        let params = self.field_types(def, variant);
+        // the instance of a generic enum is known once the fields are lowered
+        let id = if inference.generics.is_empty() {
+            Some(self.enum_id(def, Vec::new(), span)?)
+        } else {
+            None
+        };
+
+        let mut fields = vec![None; names.len()];
+        for field in record
+            .field_list()
+            .into_iter()
+            .flat_map(|list| list.fields())
+        {
+            let Some(name) = field.name() else {
+                continue;
+            };
+            let field_span = cx.span(field.syntax());
+            let Some(index) = names.iter().position(|n| n == name.text()) else {
+                let kind = if is_struct { "struct" } else { "variant" };
+                let message = format!(
+                    "{kind} `{}` has no field named `{}`",
+                    self.variant_name(def, variant),
+                    name.text()
+                );
+                return Err(self.error(field_span, message));
+            };
+            if fields[index].is_some() {
+                let message = format!("field `{}` specified more than once", name.text());
+                return Err(self.error(field_span, message));
+            }
+            // `Variant { x }` takes the value of the variable `x`
+            let value = match (field.expr(), id) {
+                (Some(expr), Some(id)) => {
+                    let ty = self.enums[id.0 as usize].variants[variant].fields[index].clone();
+                    self.expect(cx, &expr, &ty)?
+                }
+                (Some(expr), None) => {
+                    self.infer_arg(cx, &mut inference, params[index].as_ref(), &expr)?
+                }
+                (None, _) => {
+                    let Some(local) = cx.lookup(name.text()) else {
+                        let message = format!("cannot find value `{}` in this scope", name.text());
+                        return Err(self.error(field_span, message));
+                    };
+                    let ty = cx.locals[local.0 as usize].ty.clone();
+                    if let Some(param) = &params[index] {
+                        self.unify(&mut inference, param, &ty, false);
+                    }
+                    Expr {
+                        kind: ExprKind::Local(local),
+                        ty,
+                        span: field_span,
+                    }
+                }
+            };
+            fields[index] = Some(value);
+        }
+
+        let mut values = Vec::new();
+        for (name, value) in names.iter().zip(fields) {
+            let Some(value) = value else {
+                let message = format!(
+                    "missing field `{name}` in initializer of `{}`",
+                    self.variant_name(def, variant)
+                );
+                return Err(self.error(span, message));
+            };
+            values.push(value);
+        }
+        let id = match id {
+            Some(id) => id,
+            None => self.enum_instance(def, inference, span)?,
+        };
+        let types = self.enums[id.0 as usize].variants[variant].fields.clone();
+        self.check_args(&values, &types)?;
+        Ok(Expr {
+            kind: ExprKind::Variant(id, variant, values),
+            ty: Type::Enum(id),
+            span,
+        })
+    }
+
+    fn call(
+        &mut self,
+        cx: &mut FunctionCx,
+        call: &CallExpr,
+        expected: Option<&Type>,
+    ) -> Result<Expr> {
+        let span = cx.span(call.syntax());
+        let Some(ast::Expr::VariableRef(variable)) = call.callee() else {
+            return Err(self.unsupported(span, "calls of values other than functions"));
+        };
+        let segments = variable.segments().collect::<Vec<_>>();
+        let path = segments.iter().map(|s| s.text()).collect::<Vec<_>>();
+        let args = call
+            .arg_list()
+            .into_iter()
+            .flat_map(|list| list.args())
+            .collect::<Vec<_>>();
+
+        if let [name] = path.as_slice() {
+            if cx.lookup(name).is_some() {
+                return Err(self.unsupported(span, "closures"));
+            }
+        }
+        let resolved = self.module_tree.resolve_path(cx.module, &path);
+        if let ([name], Err(ResolveError::Unresolved(_))) = (path.as_slice(), &resolved) {
+            if let Some(builtin) = Builtin::from_name(name) {
+                return self.builtin(cx, call, builtin, &args);
+            }
+        }
+        let list = variable.generic_arg_list();
+
+        match resolved {
+            Ok(Def::Function(def)) => {
+                let node = self.function_node(def);
+                let params = node
+                    .param_list()
+                    .into_iter()
+                    .flat_map(|list| list.params())
+                    .map(|param| param.ty())
+                    .collect::<Vec<_>>();
+                if params.len() != args.len() {
+                    let message = format!(
+                        "function `{}` takes {} argument{} but {} {} supplied",
+                        path.join("::"),
+                        params.len(),
+                        if params.len() == 1 { "" } else { "s" },
+                        args.len(),
+                        if args.len() == 1 { "was" } else { "were" },
+                    );
+                    return Err(self.error(span, message));
+                }
+
+                // the arguments of a generic function are lowered before its instance is known
+                let name = path.last().copied().unwrap_or_default();
+                let generics = generic_names(node.generic_param_list());
+                let mut inference = self.inference(cx, def, generics, list, ("function", name))?;
+                let is_generic = !inference.generics.is_empty();
+                let mut values = Vec::new();
+                if is_generic {
+                    if let (Some(ret), Some(expected)) = (node.return_type(), expected) {
+                        self.unify(&mut inference, &ret, expected, true);
+                    }
+                    for (arg, param) in args.iter().zip(&params) {
+                        values.push(self.infer_arg(cx, &mut inference, param.as_ref(), arg)?);
+                    }
+                }
+                let type_args = self.type_args(inference, ("function", name), span)?;
+
+                let id = self.function_id(def, type_args)?;
+                let signature = &self.signatures[id.0 as usize];
+                let types = signature.params.iter().map(|(_, ty)| ty.clone());
+                let types = types.collect::<Vec<_>>();
+                let ret = signature.ret.clone();
+                if is_generic {
+                    self.check_args(&values, &types)?;
+                } else {
+                    for (arg, ty) in args.iter().zip(&types) {
+                        values.push(self.expect(cx, arg, ty)?);
+                    }
+                }
+                Ok(Expr {
+                    kind: ExprKind::Call(id, values),
+                    ty: ret,
+                    span,
+                })
+            }
+            Ok(Def::Variant(def, variant)) => {
+                let params = self.field_types(def, variant);
+                let fields = &self.module_tree.enum_data(def).variants[variant].fields;
+                if !matches!(fields, VariantFields::Tuple(_)) || params.is_empty() {
+                    let message = format!(
+                        "`{}` is not a tuple variant",
+                        self.variant_name(def, variant)
+                    );
+                    return Err(self.error(span, message));
+                }
+                if params.len() != args.len() {
+                    let message = format!(
+                        "variant `{}` takes {} field{} but {} {} supplied",
+                        self.variant_name(def, variant),
+                        params.len(),
+                        if params.len() == 1 { "" } else { "s" },
+                        args.len(),
+                        if args.len() == 1 { "was" } else { "were" },
+                    );
+                    return Err(self.error(span, message));
+                }
+
+                let mut inference = self.enum_inference(cx, def, list, expected)?;
+                let mut values = Vec::new();
+                let id = if inference.generics.is_empty() {
+                    let id = self.enum_id(def, Vec::new(), span)?;
+                    let types = self.enums[id.0 as usize].variants[variant].fields.clone();
+                    for (arg, ty) in args.iter().zip(&types) {
+                        values.push(self.expect(cx, arg, ty)?);
+                    }
+                    id
+                } else {
+                    for (arg, param) in args.iter().zip(&params) {
+                        values.push(self.infer_arg(cx, &mut inference, param.as_ref(), arg)?);
+                    }
+                    let id = self.enum_instance(def, inference, span)?;
+                    let types = self.enums[id.0 as usize].variants[variant].fields.clone();
+                    self.check_args(&values, &types)?;
+                    id
+                };
+                Ok(Expr {
+                    kind: ExprKind::Variant(id, variant, values),
+                    ty: Type::Enum(id),
+                    span,
+                })
+            }
+            Ok(Def::Struct(_)) => {
+                let message = format!("expected function, found struct `{}`", path.join("::"));
+                Err(self.error(span, message))
+            }
+            _ => {
+                let message = format!("cannot find function `{}` in this scope", path.join("::"));
+                Err(self.error(span, message))
+            }
+        }
+    }
+
+    /// Lowers a call to a function of the prelude, of which only printing and assertions
+    /// can be compiled.
+    fn builtin(
+        &mut self,
+        cx: &mut FunctionCx,
+        call: &CallExpr,
+        builtin: Builtin,
+        args: &[ast::Expr],
+    ) -> Result<Expr> {
+        let span = cx.span(call.syntax());
+        if let Err(message) = builtin.check_arity(args.len()) {
+            return Err(self.error(span, message));
+        }
+
+        let kind = match builtin {
+            Builtin::Print | Builtin::Println | Builtin::Eprintln => {
+                let output = match builtin {
+                    Builtin::Print => Output::Stdout { newline: false },
+                    Builtin::Println => Output::Stdout { newline: true },
+                    _ => Output::Stderr,
+                };
+                let parts = match args.first() {
+                    Some(arg) => self.display(cx, arg)?,
+                    None => Vec::new(),
+                };
+                ExprKind::Print(output, parts)
+            }
+            Builtin::Assert => {
+                let condition = self.expect(cx, &args[0], &Type::Bool)?;
+                let mut message = vec![];
+                match args.get(1) {
+                    Some(arg) => {
+                        message.push(string("assertion failed: ", span));
+                        message.extend(self.display(cx, arg)?);
+                    }
+                    None => {
+                        let text = format!("assertion failed: `{}`", args[0].syntax());
+                        message.push(string(text, span));
+                    }
+                }
+                let panic = Expr {
+                    kind: ExprKind::Panic(message),
+                    ty: Type::Never,
+                    span,
+                };
+                ExprKind::If(
+                    Box::new(condition),
+                    Box::new(unit(span)),
+                    Some(Box::new(panic)),
+                )
+            }
+            Builtin::AssertEq => return self.assert_eq(cx, span, args),
+            _ => {
+                let what = format!("the prelude function `{}`", builtin.name());
+                return Err(self.unsupported(span, &what));
+            }
+        };
+
+        Ok(Expr {
+            kind,
+            ty: Type::Unit,
+            span,
+        })
+    }
+
+    /// Lowers `assert_eq(left, right)` to a block comparing the values, which are only
+    /// evaluated once.
+    fn assert_eq(&mut self, cx: &mut FunctionCx, span: Span, args: &[ast::Expr]) -> Result<Expr> {
+        let left = self.expr(cx, &args[0], None)?;
+        let right = self.expect(cx, &args[1], &left.ty)?;
+        let ty = left.ty.clone();
+        self.check_displayable(span, &ty)?;
+
+        let mut message = vec![string("assertion `left == right` failed", span)];
+        if let Some(arg) = args.get(2) {
+            message.push(string(": ", span));
+            message.extend(self.display(cx, arg)?);
+        }
+
+        let left_local = cx.declare("left", ty.clone());
+        let right_local = cx.declare("right", ty.clone());
+        let local = |id: LocalId| Expr {
+            kind: ExprKind::Local(id),
+            ty: ty.clone(),
+            span,
+        };
+        message.push(string("\n  left: ", span));
+        message.push(local(left_local));
+        message.push(string("\n right: ", span));
+        message.push(local(right_local));
+
+        let condition = Expr {
+            kind: ExprKind::Binary(
+                BinaryOp::Eq,
+                Box::new(local(left_local)),
+                Box::new(local(right_local)),
+            ),
+            ty: Type::Bool,
+            span,
+        };
+        let panic = Expr {
+            kind: ExprKind::Panic(message),
+            ty: Type::Never,
+            span,
+        };
+        let check = Expr {
+            kind: ExprKind::If(
+                Box::new(condition),
+                Box::new(unit(span)),
+                Some(Box::new(panic)),
+            ),
+            ty: Type::Unit,
+            span,
+        };
+        Ok(Expr {
+            kind: ExprKind::Block(
+                vec![
+                    Stmt::Let(Pattern::Binding(left_local), left),
+                    Stmt::Let(Pattern::Binding(right_local), right),
+                ],
+                Some(Box::new(check)),
+            ),
+            ty: Type::Unit,
+            span,
+        })
+    }
+
+    /// The values `arg` displays, which are its pieces if it's a formatted string.
+    fn display(&mut self, cx: &mut FunctionCx, arg: &ast::Expr) -> Result<Vec<Expr>> {
+        let span = cx.span(arg.syntax());
+        let ast::Expr::InterpolatedString(string) = arg else {
+            let value = self.expr(cx, arg, None)?;
+            self.check_displayable(span, &value.ty)?;
+            return Ok(vec![value]);
+        };
+
+        let mut parts = Vec::new();
+        for part in string.parts() {
+            match part {
+                InterpolatedStringPart::Text(text) => {
+                    let text = literal::interpolated_text(text.text())
+                        .map_err(|message| self.error(span, message))?;
+                    if !text.is_empty() {
+                        parts.push(self::string(text, span));
+                    }
+                }
+                InterpolatedStringPart::Expr(expr) => {
+                    let value = self.expr(cx, &expr, None)?;
+                    self.check_displayable(value.span, &value.ty)?;
+                    parts.push(value);
+                }
+            }
+        }
+        Ok(parts)
+    }
+
+    /// Reports values whose enums have a `to_string` method, which displays them in the
+    /// interpreter but can't be compiled.
+    fn check_displayable(&mut self, span: Span, ty: &Type) -> Result<()> {
+        let displayed_with_to_string = match &self.displayed_with_to_string {
+            Some(enums) => enums,
+            None => {
+                let enums = self.enums_with_to_string();
+                self.displayed_with_to_string.insert(enums)
+            }
+        };
+
+        let mut types = vec![ty.clone()];
+        while let Some(ty) = types.pop() {
+            match ty {
+                Type::Tuple(elements) => types.extend(elements),
+                Type::Enum(id)
+                    if displayed_with_to_string.contains(&self.enum_keys[id.0 as usize].0) =>
+                {
+                    let what = format!(
+                        "displaying `{}` with its `to_string` method",
+                        self.enums[id.0 as usize].name
+                    );
+                    return Err(self.unsupported(span, &what));
+                }
+                Type::Enum(id) => types.extend(
+                    self.enums[id.0 as usize]
+                        .variants
+                        .iter()
+                        .flat_map(|variant| variant.fields.iter().cloned()),
+                ),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn enums_with_to_string(&mut self) -> HashSet<Span> {
+        let mut enums = HashSet::new();
+        for data in self.module_tree.impls() {
+            if !data.functions.iter().any(|(name, _)| name == "to_string") {
+                continue;
+            }
+            let self_type = self
+                .token(data.span)
+                .and_then(|token| token.parent())
+                .and_then(ImplStmt::cast)
+                .and_then(|node| node.self_type());
+            let Some(ast::Type::Path(path)) = self_type else {
+                continue;
+            };
+            let segments = path.segments().collect::<Vec<_>>();
+            let segments = segments.iter().map(|s| s.text()).collect::<Vec<_>>();
+            if let Ok(Def::Enum(def) | Def::Struct(def)) =
+                self.module_tree.resolve_path(data.module, &segments)
+            {
+                enums.insert(def);
+            }
+        }
+        enums
+    }
+}
+
+/// A field of a struct pattern, which is a pattern or a name binding the field.
+#[derive(Clone)]
+enum PatternSyntax {
+    Pattern(ast::Pattern),
+    Binding(SyntaxToken),
+}
+
+/// The names of the generic parameters in `list`.
+fn generic_names(list: Option<GenericParamList>) -> Vec<String> {
+    list.iter()
+        .flat_map(|list| list.params())
+        .map(|param| {
+            param
+                .name()
+                .map_or(String::new(), |name| name.text().to_string())
+        })
+        .collect()
+}
+
+/// Whether the type `ty` refers to any of the generic parameters `names`.
+fn refers_to(ty: &ast::Type, names: &[&str]) -> bool {
+    ty.syntax()
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .any(|token| token.kind() == SyntaxKind::Identifier && names.contains(&token.text()))
+}
+
+/// The error for the wrong number of generic arguments given to a function or an enum.
+fn generic_count_mismatch(kind: &str, name: &str, expected: usize, found: usize) -> String {
+    format!(
+        "{kind} `{name}` takes {expected} generic argument{} but {found} generic argument{} {} supplied",
+        if expected == 1 { "" } else { "s" },
+        if found == 1 { "" } else { "s" },
+        if found == 1 { "was" } else { "were" },
+    )
+}
+
+/// Whether `expr` is a number literal without a suffix, whose type is inferred.
+fn is_unsuffixed_number(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Literal(literal) => literal.token().is_some_and(|token| {
+            token.kind() == SyntaxKind::Number
+                && matches!(
+                    literal::number(token.text()),
+                    Ok(Number::Int(_, None) | Number::Float(_, None))
+                )
+        }),
+        ast::Expr::Prefix(prefix) => prefix
+            .operand()
+            .is_some_and(|operand| is_unsuffixed_number(&operand)),
+        ast::Expr::Paren(paren) => paren.expr().is_some_and(|expr| is_unsuffixed_number(&expr)),
+        _ => false,
+    }
+}
+
+fn binary_op(kind: SyntaxKind) -> Option<BinaryOp> {
+    let op = match kind {
+        SyntaxKind::Plus => BinaryOp::Add,
+        SyntaxKind::Minus => BinaryOp::Sub,
+        SyntaxKind::Star => BinaryOp::Mul,
+        SyntaxKind::Slash => BinaryOp::Div,
+        SyntaxKind::Modulo => BinaryOp::Rem,
+        SyntaxKind::Ampersand => BinaryOp::BitAnd,
+        SyntaxKind::Pipe => BinaryOp::BitOr,
+        SyntaxKind::Caret => BinaryOp::BitXor,
+        SyntaxKind::ShiftLeft => BinaryOp::Shl,
+        SyntaxKind::ShiftRight => BinaryOp::Shr,
+        SyntaxKind::Equal => BinaryOp::Eq,
+        SyntaxKind::NotEqual => BinaryOp::Ne,
+        SyntaxKind::LessThan => BinaryOp::Lt,
+        SyntaxKind::LessThanEqual => BinaryOp::Le,
+        SyntaxKind::GreaterThan => BinaryOp::Gt,
+        SyntaxKind::GreaterThanEqual => BinaryOp::Ge,
+        SyntaxKind::LogicalAnd => BinaryOp::And,
+        SyntaxKind::LogicalOr => BinaryOp::Or,
+        _ => return None,
+    };
+    Some(op)
+}
+
+/// The operator a compound assignment such as `+=` applies, `None` for `=`.
+fn compound_assignment(kind: SyntaxKind) -> Option<BinaryOp> {
+    let op = match kind {
+        SyntaxKind::PlusEqual => BinaryOp::Add,
+        SyntaxKind::MinusEqual => BinaryOp::Sub,
+        SyntaxKind::StarEqual => BinaryOp::Mul,
+        SyntaxKind::SlashEqual => BinaryOp::Div,
+        SyntaxKind::ModuloEqual => BinaryOp::Rem,
+        SyntaxKind::AmpersandEqual => BinaryOp::BitAnd,
+        SyntaxKind::PipeEqual => BinaryOp::BitOr,
+        SyntaxKind::CaretEqual => BinaryOp::BitXor,
+        SyntaxKind::ShiftLeftEqual => BinaryOp::Shl,
+        SyntaxKind::ShiftRightEqual => BinaryOp::Shr,
+        _ => return None,
+    };
+    Some(op)
+}
+
+#[cfg(test)]
+mod tests {
+    use db::Database;
+
+    use crate::tir::{ExprKind, Type};
+
+    fn lower(source: &str) -> Result<crate::tir::Program, Vec<String>> {
+        let mut db = Database::new();
+        let root = db.add_file("main.lamia", source);
+        let (module_tree, diagnostics) = modules::build(&mut db, root);
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        super::lower(&db, &module_tree).map_err(|diagnostics| {
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect()
+        })
+    }
+
+    fn errors(source: &str) -> Vec<String> {
+        lower(source).expect_err("the program shouldn't compile")
+    }
+
+    #[test]
+    fn files_are_parsed_once() {
+        let mut db = Database::new();
+        let root = db.add_file("main.lamia", "fn main() { println(1) }");
+        let (module_tree, _) = modules::build(&mut db, root);
+        db.take_executed();
+
+        assert!(super::lower(&db, &module_tree).is_ok());
+        assert!(!db
+            .take_executed()
+            .iter()
+            .any(|query| query.starts_with("parse(")));
+    }
+
+    #[test]
+    fn infers_literal_types() {
+        let program = lower("fn f(x: u8) -> u8 { 2 * x } fn main() { f(3); }").unwrap();
+        let f = &program.functions[1];
+        assert_eq!(f.name, "f");
+        let ExprKind::Block(_, Some(tail)) = &f.body.kind else {
+            panic!("expected block");
+        };
+        let ExprKind::Binary(_, lhs, _) = &tail.kind else {
+            panic!("expected binary expression");
+        };
+        assert_eq!(lhs.ty, Type::Int(crate::tir::IntTy::U8));
+    }
+
+    #[test]
+    fn only_reachable_functions() {
+        let program = lower("fn unused(x) { x } fn main() -> i64 { 1 }").unwrap();
+        assert_eq!(program.functions.len(), 1);
+    }
+
+    #[test]
+    fn unsupported_programs() {
+        assert_eq!(
+            errors("fn main() { f(1) } fn f(x) { x }"),
+            ["the parameter `x` needs a type to be compiled"]
+        );
+        assert_eq!(
+            errors("fn main() { let f = |x: i64| x; }"),
+            ["closures can't be compiled yet"]
+        );
+        assert_eq!(
+            errors("fn main() { let a = [1, 2]; }"),
+            ["arrays can't be compiled yet"]
+        );
+        assert_eq!(
+            errors("fn main() { let n = len(\"abc\"); }"),
+            ["the prelude function `len` can't be compiled yet"]
+        );
+        assert_eq!(
+            errors("fn main() { let x: u8 = 256; }"),
+            ["literal out of range for `u8`"]
+        );
+        assert_eq!(
+            errors("fn main() { let x = 1u8 + 1i32; }"),
+            ["cannot apply `+` to `u8` and `i32`"]
+        );
+        assert_eq!(
+            errors("enum List { Cons(i64, List), Nil } fn main() { let l = List::Nil; }"),
+            ["recursive enums such as `List` can't be compiled yet"]
+        );
+        assert_eq!(
+            errors("fn main() -> String { \"a\" + \"b\" }"),
+            ["string concatenation can't be compiled yet"]
+        );
+        assert_eq!(
+            errors("fn main() { f(); } fn f() -> i32 { 1i64 }"),
+            ["mismatched types: expected `i32`, found `i64`"]
+        );
+    }
+
+    #[test]
+    fn instantiates_generics() {
+        let source = "
+            enum Option<T> { Some(T), None }
+            fn id<T>(x: T) -> T { x }
+            fn main() {
+                let a: Option<u8> = Option::Some(id(1));
+                let b = Option::Some(id(true));
+                let c: u8 = id(2);
+            }
+        ";
+        let program = lower(source).unwrap();
+        let names = program.functions.iter().map(|f| f.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["main", "id<u8>", "id<bool>"]);
+        let names = program.enums.iter().map(|e| e.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["Option<u8>", "Option<bool>"]);
+
+        // the checker infers the type arguments the values only tell later
+        let source = "
+            enum Option<T> { Some(T), None }
+            fn none<T>() -> Option<T> { Option::None }
+            fn take(o: Option<u8>) {}
+            fn main() { let o = none(); take(o); }
+        ";
+        let program = lower(source).unwrap();
+        let names = program.functions.iter().map(|f| f.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["main", "none<u8>", "take"]);
+    }
+
+    #[test]
+    fn generic_errors() {
+        let items = "enum Option<T> { Some(T), None } fn id<T>(x: T) -> T { x }";
+        let cases = [
+            (
+                "let x = Option::None;",
+                "type annotations needed: cannot infer type of the type parameter `T` declared on the enum `Option`",
+            ),
+            ("let x: Option = Option::None;", "missing generics for enum `Option`"),
+            (
+                "let x: Option<i64, i64> = Option::None;",
+                "enum `Option` takes 1 generic argument but 2 generic arguments were supplied",
+            ),
+            (
+                "id::<i64, bool>(1);",
+                "function `id` takes 1 generic argument but 2 generic arguments were supplied",
+            ),
+            ("id::<bool>(1);", "mismatched types: expected `bool`, found `i64`"),
+            (
+                "let x: Option<i64> = Option::Some(true);",
+                "mismatched types: expected `Option<i64>`, found `Option<bool>`",
+            ),
+        ];
+        for (stmt, message) in cases {
+            let source = format!("{items} fn main() {{ {stmt} }}");
+            assert_eq!(errors(&source), [message], "{stmt}");
+        }
+        assert_eq!(
+            errors("fn main<T>() {}"),
+            ["`main` function is not allowed to have generic parameters"]
+        );
+    }
+
+    #[test]
+    fn struct_errors() {
+        let cases = [
+            ("P { x: 1, z: 2 }", "struct `P` has no field named `z`"),
+            ("P { x: 1 }.z", "no field `z` on type `P`"),
+            ("P { x: 1 }.0", "no field `0` on type `P`"),
+            ("P", "expected value, found struct `P`"),
+            ("P(1)", "expected function, found struct `P`"),
+            ("P {}", "missing field `x` in initializer of `P`"),
+        ];
+        for (expr, message) in cases {
+            let source = format!("struct P {{ x: i64 }} fn main() {{ {expr}; }}");
+            assert_eq!(errors(&source), [message], "{expr}");
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! The mid-level IR: functions as control-flow graphs of basic blocks, lowered from the
+//! typed IR. Blocks are lists of statements assigning rvalues to locals, ended by a
+//! terminator which goes to other blocks, calls a function or leaves the function.
+//!
+//! Programs are lowered in the [`Form::Locals`] form, where the locals of the source are
+//! variables assigned any number of times, and then converted to [`Form::Ssa`], where each
+//! local is assigned once and phis merge their values where control flow joins.
+
+mod build;
+mod cfg;
+mod display;
+mod ssa;
+mod verify;
+
+use source_map::Span;
+
+pub use crate::tir::{
+    BinaryOp, EnumDef, EnumId, FloatTy, FunctionId, IntTy, Literal, Output, Type, UnaryOp,
+    VariantDef,
+};
+pub use verify::{verify, VerifyError};
+
+/// Lowers the functions of `program` to control-flow graphs, in the [`Form::Locals`] form.
+pub fn build(program: &crate::tir::Program) -> Program {
+    Program {
+        functions: program
+            .functions
+            .iter()
+            .map(|function| build::function(program, function))
+            .collect(),
+        enums: program.enums.clone(),
+        main: program.main,
+        form: Form::Locals,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LocalId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlockId(pub u32);
+
+impl BlockId {
+    /// The block where functions start, which no block goes to.
+    pub const ENTRY: Self = Self(0);
+}
+
+/// How locals are assigned in a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Form {
+    /// Locals can be assigned any number of times, and blocks have no phis.
+    Locals,
+    /// Each local is assigned once, by a phi, a statement or a call, and its assignment
+    /// dominates its uses.
+    Ssa,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    /// The functions, indexed by the [`FunctionId`]s of the typed IR.
+    pub functions: Vec<Function>,
+    pub enums: Vec<EnumDef>,
+    pub main: FunctionId,
+    pub form: Form,
+}
+
+impl Program {
+    pub fn function(&self, id: FunctionId) -> &Function {
+        &self.functions[id.0 as usize]
+    }
+
+    pub fn enum_def(&self, id: EnumId) -> &EnumDef {
+        &self.enums[id.0 as usize]
+    }
+
+    /// Converts the program from the [`Form::Locals`] form to [`Form::Ssa`].
+    pub fn into_ssa(mut self) -> Self {
+        assert_eq!(
+            self.form,
+            Form::Locals,
+            "the program is already in SSA form"
+        );
+        for function in &mut self.functions {
+            ssa::convert(function);
+        }
+        self.form = Form::Ssa;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    /// The path of the function from the root module, such as `math::fib`.
+    pub name: String,
+    /// The span of the function's name.
+    pub span: Span,
+    pub params: Vec<LocalId>,
+    pub ret: Type,
+    /// Every local of the function, including its parameters, indexed by [`LocalId`].
+    pub locals: Vec<Local>,
+    /// The blocks of the function, indexed by [`BlockId`], starting with the entry.
+    pub blocks: Vec<Block>,
+}
+
+impl Function {
+    pub fn local(&self, id: LocalId) -> &Local {
+        &self.locals[id.0 as usize]
+    }
+
+    pub fn block(&self, id: BlockId) -> &Block {
+        &self.blocks[id.0 as usize]
+    }
+
+    pub fn block_ids(&self) -> impl Iterator<Item = BlockId> {
+        (0..self.blocks.len() as u32).map(BlockId)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Local {
+    /// The name of the local in the source, `None` for temporaries.
+    pub name: Option<String>,
+    pub ty: Type,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    /// The phis, which take their values at the start of the block, all at once.
+    pub phis: Vec<Phi>,
+    pub statements: Vec<Statement>,
+    pub terminator: Terminator,
+}
+
+/// Assigns a local the value of another one, depending on the block control came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phi {
+    pub local: LocalId,
+    /// The value for each predecessor of the block.
+    pub sources: Vec<(BlockId, LocalId)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub kind: StatementKind,
+    /// The span of the expression, which runtime errors point to.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementKind {
+    Assign(LocalId, Rvalue),
+    /// Writes the values one after another, as `print` and `println` do.
+    Print(Output, Vec<Operand>),
+}
+
+impl StatementKind {
+    /// The places the statement reads.
+    pub fn places(&self) -> Vec<&Place> {
+        match self {
+            Self::Assign(_, rvalue) => rvalue.places(),
+            Self::Print(_, operands) => operands.iter().filter_map(Operand::place).collect(),
+        }
+    }
+
+    pub fn places_mut(&mut self) -> Vec<&mut Place> {
+        match self {
+            Self::Assign(_, rvalue) => rvalue.places_mut(),
+            Self::Print(_, operands) => {
+                operands.iter_mut().filter_map(Operand::place_mut).collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Terminator {
+    pub kind: TerminatorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerminatorKind {
+    Goto(BlockId),
+    /// Goes to the first block if the boolean holds, and to the second one otherwise.
+    Branch(Operand, BlockId, BlockId),
+    /// Calls the function and assigns its result to the local before going to the block.
+    Call {
+        function: FunctionId,
+        args: Vec<Operand>,
+        result: LocalId,
+        next: BlockId,
+    },
+    Return(Operand),
+    /// Stops the program with an error made of the values, as failed assertions do.
+    Panic(Vec<Operand>),
+    /// Ends blocks which can't be reached, such as after the arms of a `match`.
+    Unreachable,
+}
+
+impl TerminatorKind {
+    /// The blocks control can go to next, without repeats.
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Self::Goto(block) | Self::Call { next: block, .. } => vec![*block],
+            Self::Branch(_, then, otherwise) if then == otherwise => vec![*then],
+            Self::Branch(_, then, otherwise) => vec![*then, *otherwise],
+            Self::Return(_) | Self::Panic(_) | Self::Unreachable => Vec::new(),
+        }
+    }
+
+    /// The places the terminator reads.
+    pub fn places(&self) -> Vec<&Place> {
+        match self {
+            Self::Branch(operand, ..) | Self::Return(operand) => {
+                operand.place().into_iter().collect()
+            }
+            Self::Call { args: operands, .. } | Self::Panic(operands) => {
+                operands.iter().filter_map(Operand::place).collect()
+            }
+            Self::Goto(_) | Self::Unreachable => Vec::new(),
+        }
+    }
+
+    pub fn places_mut(&mut self) -> Vec<&mut Place> {
+        match self {
+            Self::Branch(operand, ..) | Self::Return(operand) => {
+                operand.place_mut().into_iter().collect()
+            }
+            Self::Call { args: operands, .. } | Self::Panic(operands) => {
+                operands.iter_mut().filter_map(Operand::place_mut).collect()
+            }
+            Self::Goto(_) | Self::Unreachable => Vec::new(),
+        }
+    }
+}
+
+/// A local, or a value within it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Place {
+    pub local: LocalId,
+    /// The steps from the value of the local to the value of the place, outermost first.
+    pub projections: Vec<Projection>,
+}
+
+impl From<LocalId> for Place {
+    fn from(local: LocalId) -> Self {
+        Self {
+            local,
+            projections: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    /// An element of a tuple, by index.
+    Field(usize),
+    /// A field of an enum value of the variant, which it must be of.
+    VariantField { variant: usize, field: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// The value of a place, read when the operand is used.
+    Place(Place),
+    Const(Constant),
+}
+
+impl Operand {
+    pub fn place(&self) -> Option<&Place> {
+        match self {
+            Self::Place(place) => Some(place),
+            Self::Const(_) => None,
+        }
+    }
+
+    pub fn place_mut(&mut self) -> Option<&mut Place> {
+        match self {
+            Self::Place(place) => Some(place),
+            Self::Const(_) => None,
+        }
+    }
+}
+
+impl From<LocalId> for Operand {
+    fn from(local: LocalId) -> Self {
+        Self::Place(local.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constant {
+    pub literal: Literal,
+    pub ty: Type,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rvalue {
+    Use(Operand),
+    Unary(UnaryOp, Operand),
+    /// Arithmetic on integers is checked for overflow. `&&` and `||` are lowered to
+    /// branches instead.
+    Binary(BinaryOp, Operand, Operand),
+    Tuple(Vec<Operand>),
+    /// A value of a variant, with its fields in declaration order.
+    Variant(EnumId, usize, Vec<Operand>),
+    /// The index of the variant of an enum value, as a `usize`.
+    Discriminant(Place),
+}
+
+impl Rvalue {
+    /// The places the rvalue reads.
+    pub fn places(&self) -> Vec<&Place> {
+        match self {
+            Self::Use(operand) | Self::Unary(_, operand) => operand.place().into_iter().collect(),
+            Self::Binary(_, lhs, rhs) => lhs.place().into_iter().chain(rhs.place()).collect(),
+            Self::Tuple(operands) | Self::Variant(_, _, operands) => {
+                operands.iter().filter_map(Operand::place).collect()
+            }
+            Self::Discriminant(place) => vec![place],
+        }
+    }
+
+    pub fn places_mut(&mut self) -> Vec<&mut Place> {
+        match self {
+            Self::Use(operand) | Self::Unary(_, operand) => {
+                operand.place_mut().into_iter().collect()
+            }
+            Self::Binary(_, lhs, rhs) => {
+                lhs.place_mut().into_iter().chain(rhs.place_mut()).collect()
+            }
+            Self::Tuple(operands) | Self::Variant(_, _, operands) => {
+                operands.iter_mut().filter_map(Operand::place_mut).collect()
+            }
+            Self::Discriminant(place) => vec![place],
+        }
+    }
+}
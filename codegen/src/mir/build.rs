@@ -0,0 +1,691 @@
+//! Lowering of the functions of the typed IR to control-flow graphs. The locals of the typed
+//! IR keep their ids, and the temporaries holding intermediate values follow them.
+
+use source_map::Span;
+
+use super::{
+    cfg::Cfg, Block, BlockId, Constant, Function, Local, LocalId, Operand, Place, Projection,
+    Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
+};
+use crate::tir::{
+    self, Arm, BinaryOp, Expr, ExprKind, IntTy, Literal, Pattern, Program, Stmt, Type,
+};
+
+pub(super) fn function(program: &Program, function: &tir::Function) -> Function {
+    let mut builder = Builder {
+        program,
+        locals: function
+            .locals
+            .iter()
+            .map(|local| Local {
+                name: Some(local.name.clone()),
+                ty: local.ty.clone(),
+            })
+            .collect(),
+        blocks: Vec::new(),
+        current: BlockId::ENTRY,
+    };
+    builder.current = builder.new_block();
+    if let Some(value) = builder.expr(&function.body) {
+        builder.terminate(TerminatorKind::Return(value), function.body.span);
+    }
+    // the block control would be in after the body diverges
+    builder.terminate(TerminatorKind::Unreachable, function.body.span);
+
+    let mut function = Function {
+        name: function.name.clone(),
+        span: function.span,
+        params: function
+            .params
+            .iter()
+            .map(|param| LocalId(param.0))
+            .collect(),
+        ret: function.ret.clone(),
+        locals: builder.locals,
+        blocks: builder
+            .blocks
+            .into_iter()
+            .map(|block| Block {
+                phis: Vec::new(),
+                statements: block.statements,
+                // blocks started after control diverged are left without terminators, and
+                // are removed with the other blocks which can't be reached
+                terminator: block.terminator.unwrap_or(Terminator {
+                    kind: TerminatorKind::Unreachable,
+                    span: function.body.span,
+                }),
+            })
+            .collect(),
+    };
+    merge_blocks(&mut function);
+    order_blocks(&mut function);
+    function
+}
+
+/// A block whose terminator may not be known yet.
+struct PartialBlock {
+    statements: Vec<Statement>,
+    terminator: Option<Terminator>,
+}
+
+struct Builder<'a> {
+    program: &'a Program,
+    locals: Vec<Local>,
+    blocks: Vec<PartialBlock>,
+    /// The block statements are added to.
+    current: BlockId,
+}
+
+impl Builder<'_> {
+    fn new_block(&mut self) -> BlockId {
+        let id = BlockId(self.blocks.len() as u32);
+        self.blocks.push(PartialBlock {
+            statements: Vec::new(),
+            terminator: None,
+        });
+        id
+    }
+
+    fn temp(&mut self, ty: &Type) -> LocalId {
+        let id = LocalId(self.locals.len() as u32);
+        self.locals.push(Local {
+            name: None,
+            ty: ty.clone(),
+        });
+        id
+    }
+
+    fn push(&mut self, kind: StatementKind, span: Span) {
+        let block = &mut self.blocks[self.current.0 as usize];
+        if block.terminator.is_none() {
+            block.statements.push(Statement { kind, span });
+        }
+    }
+
+    fn assign(&mut self, local: LocalId, rvalue: Rvalue, span: Span) {
+        self.push(StatementKind::Assign(local, rvalue), span);
+    }
+
+    /// Assigns `rvalue` to a new temporary of type `ty`, which is returned.
+    fn value(&mut self, ty: &Type, rvalue: Rvalue, span: Span) -> LocalId {
+        let temp = self.temp(ty);
+        self.assign(temp, rvalue, span);
+        temp
+    }
+
+    /// Ends the current block, unless it's ended already because control can't reach this
+    /// point.
+    fn terminate(&mut self, kind: TerminatorKind, span: Span) {
+        let block = &mut self.blocks[self.current.0 as usize];
+        if block.terminator.is_none() {
+            block.terminator = Some(Terminator { kind, span });
+        }
+    }
+
+    /// Ends the current block with `kind` and continues in `next`.
+    fn goto(&mut self, kind: TerminatorKind, span: Span, next: BlockId) {
+        self.terminate(kind, span);
+        self.current = next;
+    }
+
+    /// Ends the current block after control diverged, continuing in a block which no block
+    /// goes to.
+    fn diverge(&mut self, kind: TerminatorKind, span: Span) {
+        self.terminate(kind, span);
+        self.current = self.new_block();
+    }
+
+    /// The operand as a place, stored in a temporary if it's a constant.
+    fn place(&mut self, operand: Operand, ty: &Type, span: Span) -> Place {
+        match operand {
+            Operand::Place(place) => place,
+            operand @ Operand::Const(_) => self.value(ty, Rvalue::Use(operand), span).into(),
+        }
+    }
+
+    /// Lowers `expr`, returning its value unless control doesn't continue after it.
+    fn expr(&mut self, expr: &Expr) -> Option<Operand> {
+        let span = expr.span;
+        let operand = match &expr.kind {
+            ExprKind::Literal(literal) => Operand::Const(Constant {
+                literal: literal.clone(),
+                ty: expr.ty.clone(),
+            }),
+            ExprKind::Local(local) => LocalId(local.0).into(),
+            ExprKind::Call(function, args) => {
+                let args = self.operands(args)?;
+                let result = self.temp(&expr.ty);
+                let next = self.new_block();
+                let call = TerminatorKind::Call {
+                    function: *function,
+                    args,
+                    result,
+                    next,
+                };
+                self.goto(call, span, next);
+                result.into()
+            }
+            ExprKind::Unary(op, operand) => {
+                let operand = self.expr(operand)?;
+                self.value(&expr.ty, Rvalue::Unary(*op, operand), span)
+                    .into()
+            }
+            ExprKind::Binary(op @ (BinaryOp::And | BinaryOp::Or), lhs, rhs) => {
+                let lhs = self.expr(lhs)?;
+                let result = self.temp(&Type::Bool);
+                let (rhs_block, short_block, join) =
+                    (self.new_block(), self.new_block(), self.new_block());
+                let branch = match op {
+                    BinaryOp::And => TerminatorKind::Branch(lhs, rhs_block, short_block),
+                    _ => TerminatorKind::Branch(lhs, short_block, rhs_block),
+                };
+                self.goto(branch, span, rhs_block);
+                if let Some(rhs) = self.expr(rhs) {
+                    self.assign(result, Rvalue::Use(rhs), span);
+                    self.goto(TerminatorKind::Goto(join), span, short_block);
+                } else {
+                    self.current = short_block;
+                }
+                let short = Operand::Const(Constant {
+                    literal: Literal::Bool(*op == BinaryOp::Or),
+                    ty: Type::Bool,
+                });
+                self.assign(result, Rvalue::Use(short), span);
+                self.goto(TerminatorKind::Goto(join), span, join);
+                result.into()
+            }
+            ExprKind::Binary(op, lhs, rhs) => {
+                let Ok([lhs, rhs]) = <[_; 2]>::try_from(self.operands([&**lhs, &**rhs])?) else {
+                    unreachable!("binary operations have two operands");
+                };
+                self.value(&expr.ty, Rvalue::Binary(*op, lhs, rhs), span)
+                    .into()
+            }
+            ExprKind::Assign(place, op, value) => {
+                self.assign_place(place, *op, value, span)?;
+                unit()
+            }
+            ExprKind::Tuple(elements) => {
+                let elements = self.operands(elements)?;
+                self.value(&expr.ty, Rvalue::Tuple(elements), span).into()
+            }
+            ExprKind::Field(value, index) => {
+                let operand = self.expr(value)?;
+                let mut place = self.place(operand, &value.ty, value.span);
+                place.projections.push(projection(&value.ty, *index));
+                Operand::Place(place)
+            }
+            ExprKind::Variant(id, variant, fields) => {
+                let fields = self.operands(fields)?;
+                let rvalue = Rvalue::Variant(*id, *variant, fields);
+                self.value(&expr.ty, rvalue, span).into()
+            }
+            ExprKind::Block(stmts, tail) => {
+                for stmt in stmts {
+                    self.stmt(stmt)?;
+                }
+                match tail {
+                    Some(tail) => self.expr(tail)?,
+                    None => unit(),
+                }
+            }
+            ExprKind::If(condition, then_branch, else_branch) => {
+                self.if_expr(&expr.ty, condition, then_branch, else_branch.as_deref())?
+            }
+            ExprKind::Match(scrutinee, arms) => self.match_expr(&expr.ty, scrutinee, arms)?,
+            ExprKind::Print(output, parts) => {
+                let parts = self.operands(parts)?;
+                self.push(StatementKind::Print(*output, parts), span);
+                unit()
+            }
+            ExprKind::Panic(parts) => {
+                let parts = self.operands(parts)?;
+                self.diverge(TerminatorKind::Panic(parts), span);
+                return None;
+            }
+        };
+
+        if expr.ty == Type::Never {
+            self.diverge(TerminatorKind::Unreachable, span);
+            return None;
+        }
+        Some(operand)
+    }
+
+    /// The values of `exprs`, evaluated from left to right.
+    fn operands<'e>(&mut self, exprs: impl IntoIterator<Item = &'e Expr>) -> Option<Vec<Operand>> {
+        let exprs = exprs.into_iter().collect::<Vec<_>>();
+        let mut operands = Vec::new();
+        for (index, expr) in exprs.iter().enumerate() {
+            let mut operand = self.expr(expr)?;
+            // a place read before it's assigned by a later operand keeps its value
+            if matches!(operand, Operand::Place(_))
+                && exprs[index + 1..]
+                    .iter()
+                    .any(|expr| contains_assignment(expr))
+            {
+                operand = self.value(&expr.ty, Rvalue::Use(operand), expr.span).into();
+            }
+            operands.push(operand);
+        }
+        Some(operands)
+    }
+
+    /// Assigns the value to the place, rebuilding the tuples and structs around it when it's
+    /// within the local.
+    fn assign_place(
+        &mut self,
+        place: &tir::Place,
+        op: Option<BinaryOp>,
+        value: &Expr,
+        span: Span,
+    ) -> Option<()> {
+        let local = LocalId(place.local.0);
+        let mut value = self.expr(value)?;
+
+        // the types of the tuples and structs from the local to the innermost one
+        let mut types = vec![self.locals[local.0 as usize].ty.clone()];
+        for &field in &place.fields[..place.fields.len().saturating_sub(1)] {
+            let element = self.program.fields(&types[types.len() - 1])[field].clone();
+            types.push(element);
+        }
+        let projections = place
+            .fields
+            .iter()
+            .zip(&types)
+            .map(|(&field, ty)| projection(ty, field))
+            .collect::<Vec<_>>();
+        if let Some(op) = op {
+            let target = Operand::Place(Place {
+                local,
+                projections: projections.clone(),
+            });
+            let rvalue = Rvalue::Binary(op, target, value);
+            value = self.value(&place.ty, rvalue, span).into();
+        }
+
+        for depth in (0..place.fields.len()).rev() {
+            let ty = &types[depth];
+            let elements = (0..self.program.fields(ty).len())
+                .map(|index| {
+                    if index == place.fields[depth] {
+                        return value.clone();
+                    }
+                    let mut projections = projections[..depth].to_vec();
+                    projections.push(projection(ty, index));
+                    Operand::Place(Place { local, projections })
+                })
+                .collect();
+            // a struct is an enum of one variant
+            let rvalue = match ty {
+                Type::Enum(id) => Rvalue::Variant(*id, 0, elements),
+                _ => Rvalue::Tuple(elements),
+            };
+            if depth == 0 {
+                self.assign(local, rvalue, span);
+                return Some(());
+            }
+            value = self.value(ty, rvalue, span).into();
+        }
+        self.assign(local, Rvalue::Use(value), span);
+        Some(())
+    }
+
+    /// Lowers `stmt`, returning `None` if control doesn't continue after it.
+    fn stmt(&mut self, stmt: &Stmt) -> Option<()> {
+        match stmt {
+            Stmt::Let(Pattern::Binding(local), value) => {
+                let value_span = value.span;
+                let value = self.expr(value)?;
+                self.assign(LocalId(local.0), Rvalue::Use(value), value_span);
+            }
+            Stmt::Let(pattern, value) => {
+                let operand = self.expr(value)?;
+                let place = self.place(operand, &value.ty, value.span);
+                // the pattern can't fail to match
+                let unmatched = self.new_block();
+                self.pattern(pattern, place, &value.ty, unmatched, value.span);
+                let next = self.current;
+                self.current = unmatched;
+                self.terminate(TerminatorKind::Unreachable, value.span);
+                self.current = next;
+            }
+            Stmt::Expr(expr) => {
+                self.expr(expr)?;
+            }
+            Stmt::Return(value) => {
+                let span = value.span;
+                let value = self.expr(value)?;
+                self.diverge(TerminatorKind::Return(value), span);
+                return None;
+            }
+        }
+        Some(())
+    }
+
+    fn if_expr(
+        &mut self,
+        ty: &Type,
+        condition: &Expr,
+        then_branch: &Expr,
+        else_branch: Option<&Expr>,
+    ) -> Option<Operand> {
+        let span = condition.span;
+        let condition = self.expr(condition)?;
+        let result = self.result(ty);
+        let (then_block, else_block, join) = (self.new_block(), self.new_block(), self.new_block());
+        let branch = TerminatorKind::Branch(condition, then_block, else_block);
+        self.goto(branch, span, then_block);
+
+        let mut joined = false;
+        for (block, branch) in [(then_block, Some(then_branch)), (else_block, else_branch)] {
+            self.current = block;
+            let value = match branch {
+                Some(branch) => self.expr(branch),
+                None => Some(unit()),
+            };
+            if let Some(value) = value {
+                if let Some(result) = result {
+                    self.assign(result, Rvalue::Use(value), span);
+                }
+                self.terminate(TerminatorKind::Goto(join), span);
+                joined = true;
+            }
+        }
+        self.current = join;
+        joined.then(|| result.map_or_else(unit, Operand::from))
+    }
+
+    fn match_expr(&mut self, ty: &Type, scrutinee: &Expr, arms: &[Arm]) -> Option<Operand> {
+        let span = scrutinee.span;
+        let mut value = self.expr(scrutinee)?;
+        // guards run between the tests of the arms, which must see the value matched
+        let guards_assign = arms
+            .iter()
+            .any(|arm| arm.guard.as_ref().is_some_and(contains_assignment));
+        if guards_assign && matches!(value, Operand::Place(_)) {
+            value = self.value(&scrutinee.ty, Rvalue::Use(value), span).into();
+        }
+        let place = self.place(value, &scrutinee.ty, span);
+        let result = self.result(ty);
+        let join = self.new_block();
+
+        let mut joined = false;
+        for arm in arms {
+            let next_arm = self.new_block();
+            self.pattern(&arm.pattern, place.clone(), &scrutinee.ty, next_arm, span);
+            let guarded = match &arm.guard {
+                Some(guard) => self.expr(guard).map(|guard| {
+                    let body = self.new_block();
+                    let branch = TerminatorKind::Branch(guard, body, next_arm);
+                    self.goto(branch, arm.body.span, body);
+                }),
+                None => Some(()),
+            };
+            if guarded.is_some() {
+                if let Some(value) = self.expr(&arm.body) {
+                    if let Some(result) = result {
+                        self.assign(result, Rvalue::Use(value), arm.body.span);
+                    }
+                    self.terminate(TerminatorKind::Goto(join), arm.body.span);
+                    joined = true;
+                }
+            }
+            self.current = next_arm;
+        }
+        // the checker ensures that the arms are exhaustive
+        self.goto(TerminatorKind::Unreachable, span, join);
+        joined.then(|| result.map_or_else(unit, Operand::from))
+    }
+
+    /// The local holding the value of an `if` or `match` of type `ty`, unless it has no
+    /// value.
+    fn result(&mut self, ty: &Type) -> Option<LocalId> {
+        match ty {
+            Type::Unit | Type::Never => None,
+            ty => Some(self.temp(ty)),
+        }
+    }
+
+    /// Tests whether the value at `place` matches `pattern`, going to `unmatched` if it
+    /// doesn't, and assigns the locals the pattern binds as it goes. Control continues in
+    /// the current block when the value matches.
+    fn pattern(
+        &mut self,
+        pattern: &Pattern,
+        place: Place,
+        ty: &Type,
+        unmatched: BlockId,
+        span: Span,
+    ) {
+        match pattern {
+            Pattern::Wildcard | Pattern::Literal(Literal::Unit) => {}
+            Pattern::Binding(local) => {
+                let value = Rvalue::Use(Operand::Place(place));
+                self.assign(LocalId(local.0), value, span);
+            }
+            Pattern::Literal(Literal::Bool(value)) => {
+                let matched = self.new_block();
+                let operand = Operand::Place(place);
+                let branch = match value {
+                    true => TerminatorKind::Branch(operand, matched, unmatched),
+                    false => TerminatorKind::Branch(operand, unmatched, matched),
+                };
+                self.goto(branch, span, matched);
+            }
+            Pattern::Literal(literal) => {
+                let literal = constant(literal, ty);
+                self.test(BinaryOp::Eq, place, literal, unmatched, span);
+            }
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let (min, max) = match ty {
+                    Type::Int(ty) => (IntTy::min(*ty), IntTy::max(*ty)),
+                    _ => (0, i128::from(u32::from(char::MAX))),
+                };
+                let bound = |literal: &Literal| match literal {
+                    Literal::Int(value) => *value,
+                    Literal::Char(c) => i128::from(u32::from(*c)),
+                    _ => unreachable!("ranges are of integers and characters"),
+                };
+                // bounds which every value is within aren't tested
+                if bound(start) > min {
+                    let start = constant(start, ty);
+                    self.test(BinaryOp::Ge, place.clone(), start, unmatched, span);
+                }
+                match inclusive {
+                    true if bound(end) < max => {
+                        self.test(BinaryOp::Le, place, constant(end, ty), unmatched, span)
+                    }
+                    false => self.test(BinaryOp::Lt, place, constant(end, ty), unmatched, span),
+                    true => {}
+                }
+            }
+            Pattern::Or(alternatives) => {
+                let matched = self.new_block();
+                for (index, alternative) in alternatives.iter().enumerate() {
+                    let next = match index + 1 < alternatives.len() {
+                        true => self.new_block(),
+                        false => unmatched,
+                    };
+                    self.pattern(alternative, place.clone(), ty, next, span);
+                    self.goto(TerminatorKind::Goto(matched), span, next);
+                }
+                self.current = matched;
+            }
+            Pattern::Tuple(patterns) => {
+                for (index, (pattern, ty)) in patterns.iter().zip(tuple_elements(ty)).enumerate() {
+                    let mut place = place.clone();
+                    place.projections.push(Projection::Field(index));
+                    self.pattern(pattern, place, ty, unmatched, span);
+                }
+            }
+            Pattern::Variant(id, variant, patterns) => {
+                let usize = Type::Int(IntTy::Usize);
+                let discriminant = self.value(&usize, Rvalue::Discriminant(place.clone()), span);
+                let index = Operand::Const(Constant {
+                    literal: Literal::Int(*variant as i128),
+                    ty: usize,
+                });
+                self.test(BinaryOp::Eq, discriminant.into(), index, unmatched, span);
+
+                let types = &self.program.enum_def(*id).variants[*variant].fields;
+                for (field, (pattern, ty)) in patterns.iter().zip(types).enumerate() {
+                    let mut place = place.clone();
+                    place.projections.push(Projection::VariantField {
+                        variant: *variant,
+                        field,
+                    });
+                    self.pattern(pattern, place, ty, unmatched, span);
+                }
+            }
+        }
+    }
+
+    /// Compares the value at `place` with `value`, going to `unmatched` unless the
+    /// comparison holds.
+    fn test(&mut self, op: BinaryOp, place: Place, value: Operand, unmatched: BlockId, span: Span) {
+        let rvalue = Rvalue::Binary(op, Operand::Place(place), value);
+        let holds = self.value(&Type::Bool, rvalue, span);
+        let matched = self.new_block();
+        let branch = TerminatorKind::Branch(holds.into(), matched, unmatched);
+        self.goto(branch, span, matched);
+    }
+}
+
+fn unit() -> Operand {
+    Operand::Const(Constant {
+        literal: Literal::Unit,
+        ty: Type::Unit,
+    })
+}
+
+fn constant(literal: &Literal, ty: &Type) -> Operand {
+    Operand::Const(Constant {
+        literal: literal.clone(),
+        ty: ty.clone(),
+    })
+}
+
+/// The projection to the field `index` of a tuple or a struct of type `ty`.
+fn projection(ty: &Type, index: usize) -> Projection {
+    match ty {
+        Type::Enum(_) => Projection::VariantField {
+            variant: 0,
+            field: index,
+        },
+        _ => Projection::Field(index),
+    }
+}
+
+fn tuple_elements(ty: &Type) -> &[Type] {
+    match ty {
+        Type::Tuple(elements) => elements,
+        _ => unreachable!("places with fields are within tuples"),
+    }
+}
+
+/// Merges each block only reached by a `goto` into the block it's reached from, so that
+/// `if`s and `match`es don't leave chains of `goto`s behind. The merged blocks are left
+/// unreachable.
+fn merge_blocks(function: &mut Function) {
+    let mut predecessors = vec![0; function.blocks.len()];
+    for block in &function.blocks {
+        for successor in block.terminator.kind.successors() {
+            predecessors[successor.0 as usize] += 1;
+        }
+    }
+    for index in 0..function.blocks.len() {
+        while let TerminatorKind::Goto(target) = function.blocks[index].terminator.kind {
+            let target = target.0 as usize;
+            if target == index || predecessors[target] != 1 {
+                break;
+            }
+            let span = function.blocks[target].terminator.span;
+            let merged = std::mem::replace(
+                &mut function.blocks[target],
+                Block {
+                    phis: Vec::new(),
+                    statements: Vec::new(),
+                    terminator: Terminator {
+                        kind: TerminatorKind::Unreachable,
+                        span,
+                    },
+                },
+            );
+            predecessors[target] = 0;
+            let block = &mut function.blocks[index];
+            block.statements.extend(merged.statements);
+            block.terminator = merged.terminator;
+        }
+    }
+}
+
+/// Removes the blocks which can't be reached from the entry, and orders the rest so that
+/// blocks come before the blocks they go to.
+fn order_blocks(function: &mut Function) {
+    let order = Cfg::new(function).reverse_postorder;
+    let mut ids = vec![None; function.blocks.len()];
+    for (index, block) in order.iter().enumerate() {
+        ids[block.0 as usize] = Some(BlockId(index as u32));
+    }
+    let id = |block: &BlockId| ids[block.0 as usize].expect("successors are reachable");
+
+    let mut blocks = std::mem::take(&mut function.blocks)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    for block in order {
+        let mut block = blocks[block.0 as usize]
+            .take()
+            .expect("blocks are ordered once");
+        match &mut block.terminator.kind {
+            TerminatorKind::Goto(target) | TerminatorKind::Call { next: target, .. } => {
+                *target = id(target);
+            }
+            TerminatorKind::Branch(_, then, otherwise) => {
+                *then = id(then);
+                *otherwise = id(otherwise);
+            }
+            TerminatorKind::Return(_) | TerminatorKind::Panic(_) | TerminatorKind::Unreachable => {}
+        }
+        function.blocks.push(block);
+    }
+}
+
+/// Whether evaluating `expr` can assign a local.
+fn contains_assignment(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Assign(..) => true,
+        ExprKind::Literal(_) | ExprKind::Local(_) => false,
+        ExprKind::Call(_, exprs)
+        | ExprKind::Tuple(exprs)
+        | ExprKind::Variant(_, _, exprs)
+        | ExprKind::Print(_, exprs)
+        | ExprKind::Panic(exprs) => exprs.iter().any(contains_assignment),
+        ExprKind::Unary(_, operand) | ExprKind::Field(operand, _) => contains_assignment(operand),
+        ExprKind::Binary(_, lhs, rhs) => contains_assignment(lhs) || contains_assignment(rhs),
+        ExprKind::Block(stmts, tail) => {
+            stmts.iter().any(|stmt| match stmt {
+                Stmt::Let(_, expr) | Stmt::Expr(expr) | Stmt::Return(expr) => {
+                    contains_assignment(expr)
+                }
+            }) || tail.as_deref().is_some_and(contains_assignment)
+        }
+        ExprKind::If(condition, then_branch, else_branch) => {
+            contains_assignment(condition)
+                || contains_assignment(then_branch)
+                || else_branch.as_deref().is_some_and(contains_assignment)
+        }
+        ExprKind::Match(scrutinee, arms) => {
+            contains_assignment(scrutinee)
+                || arms.iter().any(|arm| {
+                    arm.guard.as_ref().is_some_and(contains_assignment)
+                        || contains_assignment(&arm.body)
+                })
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! The shape of the control-flow graph of a function: predecessors and dominators.
+
+use super::{BlockId, Function};
+
+/// The edges of the control-flow graph of a function, and its dominator tree.
+pub(super) struct Cfg {
+    /// The blocks each block is reached from, in order and without repeats.
+    pub(super) predecessors: Vec<Vec<BlockId>>,
+    /// The blocks reachable from the entry, each before the blocks it goes to, except
+    /// along back edges.
+    pub(super) reverse_postorder: Vec<BlockId>,
+    /// The immediate dominator of each reachable block, with the entry dominating itself.
+    idoms: Vec<Option<BlockId>>,
+}
+
+impl Cfg {
+    pub(super) fn new(function: &Function) -> Self {
+        let count = function.blocks.len();
+        let mut predecessors = vec![Vec::new(); count];
+        for block in function.block_ids() {
+            for successor in function.block(block).terminator.kind.successors() {
+                if let Some(predecessors) = predecessors.get_mut(successor.0 as usize) {
+                    predecessors.push(block);
+                }
+            }
+        }
+
+        // a depth-first search, with an explicit stack of blocks and the number of their
+        // successors visited, which are visited last to first so that in reverse postorder,
+        // the blocks branches go to when their conditions hold come first
+        let mut postorder = Vec::new();
+        let mut visited = vec![false; count];
+        let mut stack = Vec::new();
+        if count > 0 {
+            visited[0] = true;
+            stack.push((BlockId::ENTRY, 0));
+        }
+        while let Some((block, next)) = stack.last_mut() {
+            let mut successors = function.block(*block).terminator.kind.successors();
+            successors.reverse();
+            match successors.get(*next) {
+                Some(&successor) => {
+                    *next += 1;
+                    if successor.0 as usize >= count || visited[successor.0 as usize] {
+                        continue;
+                    }
+                    visited[successor.0 as usize] = true;
+                    stack.push((successor, 0));
+                }
+                None => {
+                    postorder.push(*block);
+                    stack.pop();
+                }
+            }
+        }
+        let reverse_postorder = postorder.into_iter().rev().collect::<Vec<_>>();
+
+        // "A Simple, Fast Dominance Algorithm" by Cooper, Harvey and Kennedy
+        let mut order = vec![usize::MAX; count];
+        for (index, block) in reverse_postorder.iter().enumerate() {
+            order[block.0 as usize] = index;
+        }
+        let mut idoms = vec![None; count];
+        if count > 0 {
+            idoms[0] = Some(BlockId::ENTRY);
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in reverse_postorder.iter().skip(1) {
+                let mut idom = None;
+                for &predecessor in &predecessors[block.0 as usize] {
+                    if idoms[predecessor.0 as usize].is_none() {
+                        continue;
+                    }
+                    idom = Some(match idom {
+                        None => predecessor,
+                        Some(idom) => intersect(&idoms, &order, predecessor, idom),
+                    });
+                }
+                if idom.is_some() && idoms[block.0 as usize] != idom {
+                    idoms[block.0 as usize] = idom;
+                    changed = true;
+                }
+            }
+        }
+
+        Self {
+            predecessors,
+            reverse_postorder,
+            idoms,
+        }
+    }
+
+    pub(super) fn is_reachable(&self, block: BlockId) -> bool {
+        self.idoms[block.0 as usize].is_some()
+    }
+
+    /// The immediate dominator of a reachable block other than the entry.
+    pub(super) fn idom(&self, block: BlockId) -> Option<BlockId> {
+        match block {
+            BlockId::ENTRY => None,
+            block => self.idoms[block.0 as usize],
+        }
+    }
+
+    /// Whether every path from the entry to `b` goes through `a`, which holds when they're
+    /// the same block.
+    pub(super) fn dominates(&self, a: BlockId, mut b: BlockId) -> bool {
+        loop {
+            if a == b {
+                return true;
+            }
+            match self.idom(b) {
+                Some(idom) => b = idom,
+                None => return false,
+            }
+        }
+    }
+
+    /// The blocks each block immediately dominates, in reverse postorder.
+    pub(super) fn dominator_tree(&self) -> Vec<Vec<BlockId>> {
+        let mut children = vec![Vec::new(); self.idoms.len()];
+        for &block in &self.reverse_postorder {
+            if let Some(idom) = self.idom(block) {
+                children[idom.0 as usize].push(block);
+            }
+        }
+        children
+    }
+
+    /// The dominance frontier of each block: the blocks where paths from it meet paths
+    /// which don't go through it.
+    pub(super) fn dominance_frontiers(&self) -> Vec<Vec<BlockId>> {
+        let mut frontiers = vec![Vec::new(); self.idoms.len()];
+        for &block in &self.reverse_postorder {
+            let predecessors = &self.predecessors[block.0 as usize];
+            if predecessors.len() < 2 {
+                continue;
+            }
+            let idom = self.idom(block);
+            for &predecessor in predecessors {
+                let mut runner = Some(predecessor);
+                while let Some(current) = runner {
+                    if Some(current) == idom || !self.is_reachable(current) {
+                        break;
+                    }
+                    let frontier: &mut Vec<BlockId> = &mut frontiers[current.0 as usize];
+                    if !frontier.contains(&block) {
+                        frontier.push(block);
+                    }
+                    runner = self.idom(current);
+                }
+            }
+        }
+        frontiers
+    }
+}
+
+fn intersect(idoms: &[Option<BlockId>], order: &[usize], a: BlockId, b: BlockId) -> BlockId {
+    let (mut a, mut b) = (a, b);
+    while a != b {
+        while order[a.0 as usize] > order[b.0 as usize] {
+            a = idoms[a.0 as usize].expect("processed blocks have dominators");
+        }
+        while order[b.0 as usize] > order[a.0 as usize] {
+            b = idoms[b.0 as usize].expect("processed blocks have dominators");
+        }
+    }
+    a
+}
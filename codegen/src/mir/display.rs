@@ -0,0 +1,297 @@
+//! The textual form of programs, as `lamia build --emit=mir` writes it. Locals are written
+//! `_0`, `_1` and so on, blocks `bb0`, `bb1` and so on, and constants with `const` and the
+//! suffix of their type.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::{
+    BinaryOp, Constant, EnumDef, Function, Literal, Operand, Output, Place, Program, Projection,
+    Rvalue, StatementKind, TerminatorKind, Type, UnaryOp,
+};
+
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for def in &self.enums {
+            if !first {
+                writeln!(f)?;
+            }
+            first = false;
+            write_enum(f, def, &self.enums)?;
+        }
+        for function in &self.functions {
+            if !first {
+                writeln!(f)?;
+            }
+            first = false;
+            write_function(f, self, function)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_enum(f: &mut Formatter<'_>, def: &EnumDef, enums: &[EnumDef]) -> fmt::Result {
+    if def.is_struct {
+        writeln!(f, "struct {} {{", def.name)?;
+        let variant = &def.variants[0];
+        let names = variant.field_names.iter().flatten();
+        for (name, ty) in names.zip(&variant.fields) {
+            writeln!(f, "    {name}: {},", ty.display(enums))?;
+        }
+        return writeln!(f, "}}");
+    }
+
+    writeln!(f, "enum {} {{", def.name)?;
+    for variant in &def.variants {
+        let fields = variant.fields.iter().map(|ty| ty.display(enums));
+        match &variant.field_names {
+            _ if variant.fields.is_empty() => writeln!(f, "    {},", variant.name)?,
+            Some(names) => {
+                let fields = names
+                    .iter()
+                    .zip(fields)
+                    .map(|(name, ty)| format!("{name}: {ty}"))
+                    .collect::<Vec<_>>();
+                writeln!(f, "    {} {{ {} }},", variant.name, fields.join(", "))?;
+            }
+            None => {
+                let fields = fields.collect::<Vec<_>>();
+                writeln!(f, "    {}({}),", variant.name, fields.join(", "))?;
+            }
+        }
+    }
+    writeln!(f, "}}")
+}
+
+fn write_function(f: &mut Formatter<'_>, program: &Program, function: &Function) -> fmt::Result {
+    let ty = |ty: &Type| ty.display(&program.enums);
+    let params = function
+        .params
+        .iter()
+        .map(|&param| format!("_{}: {}", param.0, ty(&function.local(param).ty)))
+        .collect::<Vec<_>>();
+    write!(f, "fn {}({})", function.name, params.join(", "))?;
+    if function.ret != Type::Unit {
+        write!(f, " -> {}", ty(&function.ret))?;
+    }
+    writeln!(f, " {{")?;
+
+    for (index, local) in function.locals.iter().enumerate() {
+        if let Some(name) = &local.name {
+            writeln!(f, "    debug {name} => _{index};")?;
+        }
+    }
+    for (index, local) in function.locals.iter().enumerate() {
+        if !function
+            .params
+            .iter()
+            .any(|param| param.0 as usize == index)
+        {
+            writeln!(f, "    let _{index}: {};", ty(&local.ty))?;
+        }
+    }
+
+    for id in function.block_ids() {
+        let block = function.block(id);
+        writeln!(f)?;
+        writeln!(f, "    bb{}: {{", id.0)?;
+        for phi in &block.phis {
+            let sources = phi
+                .sources
+                .iter()
+                .map(|(block, local)| format!("bb{}: _{}", block.0, local.0))
+                .collect::<Vec<_>>();
+            writeln!(
+                f,
+                "        _{} = phi [{}];",
+                phi.local.0,
+                sources.join(", ")
+            )?;
+        }
+        for statement in &block.statements {
+            let text = match &statement.kind {
+                StatementKind::Assign(local, rvalue) => {
+                    format!("_{} = {}", local.0, rvalue_text(program, function, rvalue))
+                }
+                StatementKind::Print(output, operands) => {
+                    let name = match output {
+                        Output::Stdout { newline: true } => "println",
+                        Output::Stdout { newline: false } => "print",
+                        Output::Stderr => "eprintln",
+                    };
+                    format!("{name}({})", operands_text(program, function, operands))
+                }
+            };
+            writeln!(f, "        {text};")?;
+        }
+        let text = match &block.terminator.kind {
+            TerminatorKind::Goto(target) => format!("goto -> bb{}", target.0),
+            TerminatorKind::Branch(condition, then, otherwise) => format!(
+                "branch {} -> [true: bb{}, false: bb{}]",
+                operand_text(program, function, condition),
+                then.0,
+                otherwise.0
+            ),
+            TerminatorKind::Call {
+                function: callee,
+                args,
+                result,
+                next,
+            } => format!(
+                "_{} = {}({}) -> bb{}",
+                result.0,
+                program.function(*callee).name,
+                operands_text(program, function, args),
+                next.0
+            ),
+            TerminatorKind::Return(value) => {
+                format!("return {}", operand_text(program, function, value))
+            }
+            TerminatorKind::Panic(operands) => {
+                format!("panic({})", operands_text(program, function, operands))
+            }
+            TerminatorKind::Unreachable => "unreachable".to_string(),
+        };
+        writeln!(f, "        {text};")?;
+        writeln!(f, "    }}")?;
+    }
+    writeln!(f, "}}")
+}
+
+fn rvalue_text(program: &Program, function: &Function, rvalue: &Rvalue) -> String {
+    match rvalue {
+        Rvalue::Use(operand) => operand_text(program, function, operand),
+        Rvalue::Unary(op, operand) => {
+            let symbol = match op {
+                UnaryOp::Neg => "-",
+                UnaryOp::Not => "!",
+                UnaryOp::BitNot => "~",
+            };
+            format!("{symbol}{}", operand_text(program, function, operand))
+        }
+        Rvalue::Binary(op, lhs, rhs) => format!(
+            "{} {} {}",
+            operand_text(program, function, lhs),
+            binary_symbol(*op),
+            operand_text(program, function, rhs)
+        ),
+        Rvalue::Tuple(elements) => match elements.as_slice() {
+            [element] => format!("({},)", operand_text(program, function, element)),
+            elements => format!("({})", operands_text(program, function, elements)),
+        },
+        Rvalue::Variant(id, variant, fields) => {
+            let def = program.enum_def(*id);
+            let variant = &def.variants[*variant];
+            let name = format!("{}::{}", def.name, variant.name);
+            match &variant.field_names {
+                _ if fields.is_empty() => name,
+                Some(names) => {
+                    let fields = names
+                        .iter()
+                        .zip(fields)
+                        .map(|(field, value)| {
+                            format!("{field}: {}", operand_text(program, function, value))
+                        })
+                        .collect::<Vec<_>>();
+                    format!("{name} {{ {} }}", fields.join(", "))
+                }
+                None => format!("{name}({})", operands_text(program, function, fields)),
+            }
+        }
+        Rvalue::Discriminant(place) => {
+            format!("discriminant({})", place_text(program, function, place))
+        }
+    }
+}
+
+fn binary_symbol(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Rem => "%",
+        BinaryOp::BitAnd => "&",
+        BinaryOp::BitOr => "|",
+        BinaryOp::BitXor => "^",
+        BinaryOp::Shl => "<<",
+        BinaryOp::Shr => ">>",
+        BinaryOp::Eq => "==",
+        BinaryOp::Ne => "!=",
+        BinaryOp::Lt => "<",
+        BinaryOp::Le => "<=",
+        BinaryOp::Gt => ">",
+        BinaryOp::Ge => ">=",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+    }
+}
+
+fn operands_text(program: &Program, function: &Function, operands: &[Operand]) -> String {
+    let operands = operands
+        .iter()
+        .map(|operand| operand_text(program, function, operand))
+        .collect::<Vec<_>>();
+    operands.join(", ")
+}
+
+fn operand_text(program: &Program, function: &Function, operand: &Operand) -> String {
+    match operand {
+        Operand::Place(place) => place_text(program, function, place),
+        Operand::Const(constant) => format!("const {}", constant_text(constant)),
+    }
+}
+
+/// A place, as `_1.0` for an element of a tuple and `(_1 as Shape::Circle).0` for a field
+/// of a variant.
+fn place_text(program: &Program, function: &Function, place: &Place) -> String {
+    let mut text = format!("_{}", place.local.0);
+    // the type of the value so far, which names the variants of enums
+    let mut ty = function
+        .locals
+        .get(place.local.0 as usize)
+        .map(|local| &local.ty);
+    for projection in &place.projections {
+        match projection {
+            Projection::Field(index) => {
+                let _ = write!(text, ".{index}");
+                ty = match ty {
+                    Some(Type::Tuple(elements)) => elements.get(*index),
+                    _ => None,
+                };
+            }
+            Projection::VariantField { variant, field } => {
+                let def = match ty {
+                    Some(Type::Enum(id)) => program.enums.get(id.0 as usize),
+                    _ => None,
+                };
+                let variant_def = def.and_then(|def| def.variants.get(*variant));
+                let name = match (def, variant_def) {
+                    (Some(def), Some(variant)) => format!("{}::{}", def.name, variant.name),
+                    _ => format!("#{variant}"),
+                };
+                let field_name = variant_def
+                    .and_then(|variant| variant.field_names.as_ref())
+                    .and_then(|names| names.get(*field))
+                    .cloned()
+                    .unwrap_or_else(|| field.to_string());
+                text = format!("({text} as {name}).{field_name}");
+                ty = variant_def.and_then(|variant| variant.fields.get(*field));
+            }
+        }
+    }
+    text
+}
+
+fn constant_text(constant: &Constant) -> String {
+    match (&constant.literal, &constant.ty) {
+        (Literal::Int(value), Type::Int(ty)) => format!("{value}_{}", ty.name()),
+        (Literal::Float(value), Type::Float(ty)) => format!("{value:?}_{}", ty.name()),
+        (Literal::Int(value), _) => value.to_string(),
+        (Literal::Float(value), _) => format!("{value:?}"),
+        (Literal::Bool(value), _) => value.to_string(),
+        (Literal::Char(c), _) => format!("{c:?}"),
+        (Literal::Str(text), _) => format!("{text:?}"),
+        (Literal::Unit, _) => "()".to_string(),
+    }
+}
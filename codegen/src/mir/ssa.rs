@@ -0,0 +1,238 @@
+//! Conversion of functions to SSA form, after "Efficiently Computing Static Single
+//! Assignment Form and the Control Dependence Graph" by Cytron et al. Phis are only placed
+//! where the local is live, so each of them merges values which are read.
+
+use super::{cfg::Cfg, BlockId, Function, Local, LocalId, Phi, StatementKind, TerminatorKind};
+
+pub(super) fn convert(function: &mut Function) {
+    let cfg = Cfg::new(function);
+    let live_in = live_in(function, &cfg);
+    let phis = place_phis(function, &cfg, &live_in);
+    rename(function, &cfg, phis);
+}
+
+/// The locals whose values on entry to each block may be read before they're assigned.
+fn live_in(function: &Function, cfg: &Cfg) -> Vec<Vec<bool>> {
+    let count = function.locals.len();
+    // the locals each block reads before assigning them, and the locals it assigns
+    let mut uses = vec![vec![false; count]; function.blocks.len()];
+    let mut defs = vec![vec![false; count]; function.blocks.len()];
+    for block in function.block_ids() {
+        let (uses, defs) = (&mut uses[block.0 as usize], &mut defs[block.0 as usize]);
+        let mut read = |local: LocalId, defs: &[bool]| {
+            if !defs[local.0 as usize] {
+                uses[local.0 as usize] = true;
+            }
+        };
+        let block = function.block(block);
+        for statement in &block.statements {
+            for place in statement.kind.places() {
+                read(place.local, defs);
+            }
+            if let StatementKind::Assign(local, _) = statement.kind {
+                defs[local.0 as usize] = true;
+            }
+        }
+        for place in block.terminator.kind.places() {
+            read(place.local, defs);
+        }
+        if let TerminatorKind::Call { result, .. } = block.terminator.kind {
+            defs[result.0 as usize] = true;
+        }
+    }
+
+    let mut live_in = uses.clone();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &block in cfg.reverse_postorder.iter().rev() {
+            let index = block.0 as usize;
+            for successor in function.block(block).terminator.kind.successors() {
+                for local in 0..count {
+                    let live = live_in[successor.0 as usize][local] && !defs[index][local];
+                    if live && !live_in[index][local] {
+                        live_in[index][local] = true;
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+    live_in
+}
+
+/// The locals each block needs a phi for, in order: those assigned in more than one block
+/// which are live on entry to a block where their assignments meet.
+fn place_phis(function: &Function, cfg: &Cfg, live_in: &[Vec<bool>]) -> Vec<Vec<LocalId>> {
+    let mut assigned_in = vec![Vec::new(); function.locals.len()];
+    for &param in &function.params {
+        assigned_in[param.0 as usize].push(BlockId::ENTRY);
+    }
+    for block in function.block_ids() {
+        let block_data = function.block(block);
+        let statements =
+            block_data
+                .statements
+                .iter()
+                .filter_map(|statement| match statement.kind {
+                    StatementKind::Assign(local, _) => Some(local),
+                    StatementKind::Print(..) => None,
+                });
+        let call = match block_data.terminator.kind {
+            TerminatorKind::Call { result, .. } => Some(result),
+            _ => None,
+        };
+        for local in statements.chain(call) {
+            let blocks: &mut Vec<BlockId> = &mut assigned_in[local.0 as usize];
+            if blocks.last() != Some(&block) {
+                blocks.push(block);
+            }
+        }
+    }
+
+    let frontiers = cfg.dominance_frontiers();
+    let mut phis = vec![Vec::new(); function.blocks.len()];
+    for (local, blocks) in assigned_in.into_iter().enumerate() {
+        let mut has_phi = vec![false; function.blocks.len()];
+        let mut queued = vec![false; function.blocks.len()];
+        for &block in &blocks {
+            queued[block.0 as usize] = true;
+        }
+        let mut worklist = blocks;
+        while let Some(block) = worklist.pop() {
+            for &frontier in &frontiers[block.0 as usize] {
+                let index = frontier.0 as usize;
+                if has_phi[index] || !live_in[index][local] {
+                    continue;
+                }
+                has_phi[index] = true;
+                phis[index].push(LocalId(local as u32));
+                // the phi assigns the local too
+                if !queued[index] {
+                    queued[index] = true;
+                    worklist.push(frontier);
+                }
+            }
+        }
+    }
+    phis
+}
+
+/// Gives every assignment a local of its own, replacing each read of a local with the
+/// one assigned last on the way to it. The dominator tree is walked depth first, with a
+/// stack for each original local of the locals standing for it.
+fn rename(function: &mut Function, cfg: &Cfg, phis: Vec<Vec<LocalId>>) {
+    let mut renamer = Renamer {
+        stacks: vec![Vec::new(); function.locals.len()],
+        old_locals: std::mem::take(&mut function.locals),
+        locals: Vec::new(),
+    };
+
+    for param in &mut function.params {
+        *param = renamer.fresh(*param);
+    }
+    for (block, originals) in function.blocks.iter_mut().zip(&phis) {
+        block.phis = originals
+            .iter()
+            .map(|&local| Phi {
+                local,
+                sources: Vec::new(),
+            })
+            .collect();
+    }
+
+    let children = cfg.dominator_tree();
+    // blocks to rename, and the original locals to pop once their subtrees are renamed
+    enum Step {
+        Enter(BlockId),
+        Leave(Vec<LocalId>),
+    }
+    let mut steps = vec![Step::Enter(BlockId::ENTRY)];
+    while let Some(step) = steps.pop() {
+        let block_id = match step {
+            Step::Enter(block) => block,
+            Step::Leave(assigned) => {
+                for local in assigned {
+                    renamer.stacks[local.0 as usize].pop();
+                }
+                continue;
+            }
+        };
+        let mut assigned = Vec::new();
+        let block = &mut function.blocks[block_id.0 as usize];
+        for phi in &mut block.phis {
+            assigned.push(phi.local);
+            phi.local = renamer.fresh(phi.local);
+        }
+        for statement in &mut block.statements {
+            for place in statement.kind.places_mut() {
+                place.local = renamer.current(place.local);
+            }
+            if let StatementKind::Assign(local, _) = &mut statement.kind {
+                assigned.push(*local);
+                *local = renamer.fresh(*local);
+            }
+        }
+        for place in block.terminator.kind.places_mut() {
+            place.local = renamer.current(place.local);
+        }
+        if let TerminatorKind::Call { result, .. } = &mut block.terminator.kind {
+            assigned.push(*result);
+            *result = renamer.fresh(*result);
+        }
+
+        for successor in block.terminator.kind.successors() {
+            let originals = &phis[successor.0 as usize];
+            let successor = &mut function.blocks[successor.0 as usize];
+            for (phi, &original) in successor.phis.iter_mut().zip(originals) {
+                phi.sources.push((block_id, renamer.current(original)));
+            }
+        }
+
+        steps.push(Step::Leave(assigned));
+        for &child in children[block_id.0 as usize].iter().rev() {
+            steps.push(Step::Enter(child));
+        }
+    }
+
+    for block in &mut function.blocks {
+        for phi in &mut block.phis {
+            phi.sources.sort_by_key(|(block, _)| *block);
+        }
+    }
+    function.locals = renamer.locals;
+}
+
+struct Renamer {
+    old_locals: Vec<Local>,
+    /// The new locals, indexed by their ids.
+    locals: Vec<Local>,
+    /// The new locals standing for each original local, the current one last.
+    stacks: Vec<Vec<LocalId>>,
+}
+
+impl Renamer {
+    /// A new local for an assignment of `original`, which stands for it from then on.
+    fn fresh(&mut self, original: LocalId) -> LocalId {
+        let id = self.new_local(original);
+        self.stacks[original.0 as usize].push(id);
+        id
+    }
+
+    /// The local standing for `original` where it's read. Reading a local before it's
+    /// assigned is a bug of the lowering, and the read gets a local which is never
+    /// assigned, for the verifier to report.
+    fn current(&mut self, original: LocalId) -> LocalId {
+        match self.stacks[original.0 as usize].last() {
+            Some(&current) => current,
+            None => self.new_local(original),
+        }
+    }
+
+    fn new_local(&mut self, original: LocalId) -> LocalId {
+        let id = LocalId(self.locals.len() as u32);
+        self.locals
+            .push(self.old_locals[original.0 as usize].clone());
+        id
+    }
+}
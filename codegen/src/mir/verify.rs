@@ -0,0 +1,570 @@
+//! Checks that programs are well-formed: that blocks, locals, functions and variants exist,
+//! that every block is reachable from the entry, which no block goes to, that values have
+//! the types they're used at, and for programs in SSA form, that each local is assigned
+//! once, before it's read, and that phis have a value for each predecessor of their block.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::{
+    cfg::Cfg, BinaryOp, BlockId, Constant, Form, Function, IntTy, Literal, LocalId, Operand, Place,
+    Program, Projection, Rvalue, StatementKind, TerminatorKind, Type, UnaryOp,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    /// The name of the function the error is in.
+    pub function: String,
+    /// The block the error is in, `None` for errors in the function as a whole.
+    pub block: Option<BlockId>,
+    pub message: String,
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.block {
+            Some(block) => write!(f, "in `{}`, bb{}: {}", self.function, block.0, self.message),
+            None => write!(f, "in `{}`: {}", self.function, self.message),
+        }
+    }
+}
+
+/// Checks every function of `program`, returning the errors found in order.
+pub fn verify(program: &Program) -> Result<(), Vec<VerifyError>> {
+    let mut errors = Vec::new();
+    if program.functions.get(program.main.0 as usize).is_none() {
+        errors.push(VerifyError {
+            function: "main".to_string(),
+            block: None,
+            message: "`main` isn't a function of the program".to_string(),
+        });
+    }
+    for function in &program.functions {
+        let mut verifier = Verifier {
+            program,
+            function,
+            block: None,
+            errors: &mut errors,
+        };
+        verifier.function();
+    }
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors),
+    }
+}
+
+struct Verifier<'a> {
+    program: &'a Program,
+    function: &'a Function,
+    /// The block being checked.
+    block: Option<BlockId>,
+    errors: &'a mut Vec<VerifyError>,
+}
+
+/// Where a local is assigned or read: in a block, at the phis (0), at a statement (1 for
+/// the first one) or at the terminator, after which calls assign their results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Location {
+    block: BlockId,
+    index: usize,
+}
+
+impl Verifier<'_> {
+    fn error(&mut self, message: impl Into<String>) {
+        self.errors.push(VerifyError {
+            function: self.function.name.clone(),
+            block: self.block,
+            message: message.into(),
+        });
+    }
+
+    fn display(&self, ty: &Type) -> String {
+        ty.display(&self.program.enums)
+    }
+
+    fn function(&mut self) {
+        let function = self.function;
+        if function.blocks.is_empty() {
+            self.error("the function has no blocks");
+            return;
+        }
+        for (index, param) in function.params.iter().enumerate() {
+            self.local_type(*param);
+            if function.params[..index].contains(param) {
+                self.error(format!("_{} is a parameter more than once", param.0));
+            }
+        }
+
+        // the rest of the checks rely on the edges between blocks being valid
+        let count = function.blocks.len();
+        let mut valid_edges = true;
+        for block in function.block_ids() {
+            self.block = Some(block);
+            for successor in function.block(block).terminator.kind.successors() {
+                if successor.0 as usize >= count {
+                    self.error(format!("bb{} doesn't exist", successor.0));
+                    valid_edges = false;
+                }
+            }
+        }
+        self.block = None;
+        if !valid_edges {
+            return;
+        }
+
+        let cfg = Cfg::new(function);
+        if let Some(predecessor) = cfg.predecessors[0].first() {
+            self.error(format!(
+                "the entry block is the target of bb{}",
+                predecessor.0
+            ));
+            return;
+        }
+        for block in function.block_ids() {
+            if !cfg.is_reachable(block) {
+                self.error(format!("bb{} can't be reached from the entry", block.0));
+            }
+        }
+
+        for block in function.block_ids() {
+            self.block = Some(block);
+            self.block_types(block, &cfg);
+        }
+        self.block = None;
+
+        if self.program.form == Form::Ssa {
+            self.single_assignments(&cfg);
+        }
+    }
+
+    /// Checks the types of the values in the block, and that its phis match its
+    /// predecessors.
+    fn block_types(&mut self, id: BlockId, cfg: &Cfg) {
+        let function = self.function;
+        let block = function.block(id);
+
+        if !block.phis.is_empty() && self.program.form == Form::Locals {
+            self.error("phis are only allowed in SSA form");
+        }
+        let predecessors = &cfg.predecessors[id.0 as usize];
+        for phi in &block.phis {
+            let ty = self.local_type(phi.local);
+            for (index, (source, local)) in phi.sources.iter().enumerate() {
+                if !predecessors.contains(source) {
+                    self.error(format!(
+                        "the phi of _{} has a value for bb{}, which doesn't go to bb{}",
+                        phi.local.0, source.0, id.0
+                    ));
+                } else if phi.sources[..index]
+                    .iter()
+                    .any(|(block, _)| block == source)
+                {
+                    self.error(format!(
+                        "the phi of _{} has more than one value for bb{}",
+                        phi.local.0, source.0
+                    ));
+                }
+                let source_ty = self.local_type(*local);
+                self.expect_type(ty.as_ref(), source_ty.as_ref(), || {
+                    format!(
+                        "the value of the phi of _{} from bb{}",
+                        phi.local.0, source.0
+                    )
+                });
+            }
+            for predecessor in predecessors {
+                if !phi.sources.iter().any(|(block, _)| block == predecessor) {
+                    self.error(format!(
+                        "the phi of _{} has no value for bb{}",
+                        phi.local.0, predecessor.0
+                    ));
+                }
+            }
+        }
+
+        for statement in &block.statements {
+            match &statement.kind {
+                StatementKind::Assign(local, rvalue) => {
+                    let ty = self.local_type(*local);
+                    let value_ty = self.rvalue_type(rvalue);
+                    self.expect_type(ty.as_ref(), value_ty.as_ref(), || {
+                        format!("the value assigned to _{}", local.0)
+                    });
+                }
+                StatementKind::Print(_, operands) => {
+                    for operand in operands {
+                        self.operand_type(operand);
+                    }
+                }
+            }
+        }
+
+        match &block.terminator.kind {
+            TerminatorKind::Goto(_) | TerminatorKind::Unreachable => {}
+            TerminatorKind::Branch(condition, ..) => {
+                let ty = self.operand_type(condition);
+                self.expect_type(Some(&Type::Bool), ty.as_ref(), || {
+                    "the condition of the branch".to_string()
+                });
+            }
+            TerminatorKind::Call {
+                function: callee,
+                args,
+                result,
+                ..
+            } => {
+                let arg_types = args
+                    .iter()
+                    .map(|arg| self.operand_type(arg))
+                    .collect::<Vec<_>>();
+                let result_ty = self.local_type(*result);
+                let Some(callee) = self.program.functions.get(callee.0 as usize) else {
+                    self.error(format!("function #{} doesn't exist", callee.0));
+                    return;
+                };
+                if args.len() != callee.params.len() {
+                    self.error(format!(
+                        "`{}` takes {} arguments, but is given {}",
+                        callee.name,
+                        callee.params.len(),
+                        args.len()
+                    ));
+                }
+                for (index, (param, arg_ty)) in callee.params.iter().zip(&arg_types).enumerate() {
+                    let param_ty = callee.locals.get(param.0 as usize).map(|local| &local.ty);
+                    self.expect_type(param_ty, arg_ty.as_ref(), || {
+                        format!("argument {} of `{}`", index + 1, callee.name)
+                    });
+                }
+                self.expect_type(Some(&callee.ret), result_ty.as_ref(), || {
+                    format!("the result of `{}` assigned to _{}", callee.name, result.0)
+                });
+            }
+            TerminatorKind::Return(value) => {
+                let ty = self.operand_type(value);
+                self.expect_type(Some(&function.ret), ty.as_ref(), || {
+                    "the returned value".to_string()
+                });
+            }
+            TerminatorKind::Panic(operands) => {
+                for operand in operands {
+                    self.operand_type(operand);
+                }
+            }
+        }
+    }
+
+    /// Reports a value of type `found` where one of type `expected` is needed, unless
+    /// either isn't known because of another error.
+    fn expect_type(
+        &mut self,
+        expected: Option<&Type>,
+        found: Option<&Type>,
+        what: impl FnOnce() -> String,
+    ) {
+        if let (Some(expected), Some(found)) = (expected, found) {
+            if expected != found {
+                let message = format!(
+                    "{} is of type `{}`, but `{}` is expected",
+                    what(),
+                    self.display(found),
+                    self.display(expected)
+                );
+                self.error(message);
+            }
+        }
+    }
+
+    fn local_type(&mut self, local: LocalId) -> Option<Type> {
+        match self.function.locals.get(local.0 as usize) {
+            Some(decl) => Some(decl.ty.clone()),
+            None => {
+                self.error(format!("_{} doesn't exist", local.0));
+                None
+            }
+        }
+    }
+
+    fn place_type(&mut self, place: &Place) -> Option<Type> {
+        let mut ty = self.local_type(place.local)?;
+        for projection in &place.projections {
+            ty = match (projection, &ty) {
+                (Projection::Field(index), Type::Tuple(elements)) if *index < elements.len() => {
+                    elements[*index].clone()
+                }
+                (Projection::VariantField { variant, field }, Type::Enum(id)) => {
+                    let fields = self
+                        .program
+                        .enums
+                        .get(id.0 as usize)
+                        .and_then(|def| def.variants.get(*variant))
+                        .map(|variant| &variant.fields);
+                    match fields.and_then(|fields| fields.get(*field)) {
+                        Some(field) => field.clone(),
+                        None => {
+                            self.error(format!(
+                                "field {field} of variant {variant} of `{}` doesn't exist",
+                                self.display(&ty)
+                            ));
+                            return None;
+                        }
+                    }
+                }
+                (Projection::Field(index), _) => {
+                    self.error(format!(
+                        "element {index} of a value of type `{}` doesn't exist",
+                        self.display(&ty)
+                    ));
+                    return None;
+                }
+                (Projection::VariantField { .. }, _) => {
+                    self.error(format!(
+                        "a value of type `{}` has no variants",
+                        self.display(&ty)
+                    ));
+                    return None;
+                }
+            };
+        }
+        Some(ty)
+    }
+
+    fn operand_type(&mut self, operand: &Operand) -> Option<Type> {
+        match operand {
+            Operand::Place(place) => self.place_type(place),
+            Operand::Const(constant) => {
+                self.constant(constant);
+                Some(constant.ty.clone())
+            }
+        }
+    }
+
+    fn constant(&mut self, constant: &Constant) {
+        let fits = match (&constant.literal, &constant.ty) {
+            (Literal::Int(value), Type::Int(ty)) => {
+                (IntTy::min(*ty)..=IntTy::max(*ty)).contains(value)
+            }
+            (Literal::Float(_), Type::Float(_))
+            | (Literal::Bool(_), Type::Bool)
+            | (Literal::Char(_), Type::Char)
+            | (Literal::Str(_), Type::Str)
+            | (Literal::Unit, Type::Unit) => true,
+            _ => false,
+        };
+        if !fits {
+            let message = format!(
+                "the constant {:?} isn't a value of type `{}`",
+                constant.literal,
+                self.display(&constant.ty)
+            );
+            self.error(message);
+        }
+    }
+
+    fn rvalue_type(&mut self, rvalue: &Rvalue) -> Option<Type> {
+        match rvalue {
+            Rvalue::Use(operand) => self.operand_type(operand),
+            Rvalue::Unary(op, operand) => {
+                let ty = self.operand_type(operand)?;
+                let applies = match op {
+                    UnaryOp::Neg => matches!(ty, Type::Int(_) | Type::Float(_)),
+                    UnaryOp::Not => ty == Type::Bool,
+                    UnaryOp::BitNot => matches!(ty, Type::Int(_)),
+                };
+                if !applies {
+                    self.error(format!("{op:?} doesn't apply to `{}`", self.display(&ty)));
+                    return None;
+                }
+                Some(ty)
+            }
+            Rvalue::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.operand_type(lhs)?, self.operand_type(rhs)?);
+                let result = match op {
+                    BinaryOp::Shl | BinaryOp::Shr => match (&lhs, &rhs) {
+                        (Type::Int(_), Type::Int(_)) => Some(lhs.clone()),
+                        _ => None,
+                    },
+                    _ if lhs != rhs => None,
+                    BinaryOp::Eq | BinaryOp::Ne => Some(Type::Bool),
+                    BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => match lhs {
+                        Type::Int(_) | Type::Float(_) | Type::Char | Type::Str => Some(Type::Bool),
+                        _ => None,
+                    },
+                    BinaryOp::Add
+                    | BinaryOp::Sub
+                    | BinaryOp::Mul
+                    | BinaryOp::Div
+                    | BinaryOp::Rem => match lhs {
+                        Type::Int(_) | Type::Float(_) => Some(lhs.clone()),
+                        _ => None,
+                    },
+                    BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor => match lhs {
+                        Type::Int(_) => Some(lhs.clone()),
+                        _ => None,
+                    },
+                    BinaryOp::And | BinaryOp::Or => {
+                        self.error(format!("{op:?} isn't lowered to branches"));
+                        return None;
+                    }
+                };
+                if result.is_none() {
+                    self.error(format!(
+                        "{op:?} doesn't apply to `{}` and `{}`",
+                        self.display(&lhs),
+                        self.display(&rhs)
+                    ));
+                }
+                result
+            }
+            Rvalue::Tuple(elements) => {
+                let types = elements
+                    .iter()
+                    .map(|element| self.operand_type(element))
+                    .collect::<Option<Vec<_>>>()?;
+                if types.is_empty() {
+                    self.error("a tuple has no elements");
+                    return None;
+                }
+                Some(Type::Tuple(types))
+            }
+            Rvalue::Variant(id, variant, fields) => {
+                let types = fields
+                    .iter()
+                    .map(|field| self.operand_type(field))
+                    .collect::<Vec<_>>();
+                let Some(def) = self.program.enums.get(id.0 as usize) else {
+                    self.error(format!("enum #{} doesn't exist", id.0));
+                    return None;
+                };
+                let Some(variant_def) = def.variants.get(*variant) else {
+                    self.error(format!("variant {variant} of `{}` doesn't exist", def.name));
+                    return None;
+                };
+                if fields.len() != variant_def.fields.len() {
+                    self.error(format!(
+                        "`{}::{}` has {} fields, but is given {}",
+                        def.name,
+                        variant_def.name,
+                        variant_def.fields.len(),
+                        fields.len()
+                    ));
+                }
+                for (index, (expected, found)) in variant_def.fields.iter().zip(&types).enumerate()
+                {
+                    self.expect_type(Some(expected), found.as_ref(), || {
+                        format!("field {index} of `{}::{}`", def.name, variant_def.name)
+                    });
+                }
+                Some(Type::Enum(*id))
+            }
+            Rvalue::Discriminant(place) => {
+                let ty = self.place_type(place)?;
+                if !matches!(ty, Type::Enum(_)) {
+                    self.error(format!(
+                        "a value of type `{}` has no discriminant",
+                        self.display(&ty)
+                    ));
+                }
+                Some(Type::Int(IntTy::Usize))
+            }
+        }
+    }
+
+    /// Checks that each local is assigned once, and that its assignment dominates the places
+    /// it's read.
+    fn single_assignments(&mut self, cfg: &Cfg) {
+        let function = self.function;
+        let mut assignments = vec![None; function.locals.len()];
+        let mut assign = |this: &mut Self, local: LocalId, location: Location| {
+            let Some(assignment) = assignments.get_mut(local.0 as usize) else {
+                return;
+            };
+            match assignment {
+                Some(_) => {
+                    this.block = Some(location.block);
+                    this.error(format!("_{} is assigned more than once", local.0));
+                }
+                None => *assignment = Some(location),
+            }
+        };
+
+        let entry = Location {
+            block: BlockId::ENTRY,
+            index: 0,
+        };
+        for &param in &function.params {
+            assign(self, param, entry);
+        }
+        for id in function.block_ids() {
+            let block = function.block(id);
+            let at = |index| Location { block: id, index };
+            for phi in &block.phis {
+                assign(self, phi.local, at(0));
+            }
+            for (index, statement) in block.statements.iter().enumerate() {
+                if let StatementKind::Assign(local, _) = statement.kind {
+                    assign(self, local, at(index + 1));
+                }
+            }
+            if let TerminatorKind::Call { result, .. } = block.terminator.kind {
+                assign(self, result, at(block.statements.len() + 2));
+            }
+        }
+
+        for id in function.block_ids() {
+            self.block = Some(id);
+            let block = function.block(id);
+            let at = |index| Location { block: id, index };
+            for phi in &block.phis {
+                for &(source, local) in &phi.sources {
+                    // the value is read at the end of the block control comes from
+                    let end = Location {
+                        block: source,
+                        index: usize::MAX,
+                    };
+                    self.read(cfg, &assignments, local, end);
+                }
+            }
+            for (index, statement) in block.statements.iter().enumerate() {
+                for place in statement.kind.places() {
+                    self.read(cfg, &assignments, place.local, at(index + 1));
+                }
+            }
+            for place in block.terminator.kind.places() {
+                self.read(
+                    cfg,
+                    &assignments,
+                    place.local,
+                    at(block.statements.len() + 1),
+                );
+            }
+        }
+        self.block = None;
+    }
+
+    fn read(
+        &mut self,
+        cfg: &Cfg,
+        assignments: &[Option<Location>],
+        local: LocalId,
+        read: Location,
+    ) {
+        let Some(assignment) = assignments.get(local.0 as usize) else {
+            return;
+        };
+        let Some(assignment) = assignment else {
+            self.error(format!("_{} is read, but never assigned", local.0));
+            return;
+        };
+        let dominates = match assignment.block == read.block {
+            true => assignment.index < read.index,
+            false => cfg.dominates(assignment.block, read.block),
+        };
+        if !dominates {
+            self.error(format!(
+                "_{} is read where its assignment in bb{} doesn't always come first",
+                local.0, assignment.block.0
+            ));
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! The typed IR: the functions reachable from `main`, where every expression has a known
+//! type, names are resolved to locals, functions and variants, and sugar such as
+//! `assert` is expanded. Backends generate code from it.
+
+pub use parser::literal::{FloatTy, IntTy};
+use source_map::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FunctionId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EnumId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LocalId(pub u32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub functions: Vec<Function>,
+    pub enums: Vec<EnumDef>,
+    pub main: FunctionId,
+}
+
+impl Program {
+    pub fn function(&self, id: FunctionId) -> &Function {
+        &self.functions[id.0 as usize]
+    }
+
+    pub fn enum_def(&self, id: EnumId) -> &EnumDef {
+        &self.enums[id.0 as usize]
+    }
+
+    /// The types of the fields of a value of `ty`, which [`ExprKind::Field`] and [`Place`]
+    /// index: the elements of a tuple, or the fields of a struct.
+    pub fn fields<'a>(&'a self, ty: &'a Type) -> &'a [Type] {
+        match ty {
+            Type::Tuple(elements) => elements,
+            Type::Enum(id) => &self.enum_def(*id).variants[0].fields,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    /// The path of the function from the root module, such as `math::fib`.
+    pub name: String,
+    /// The span of the function's name.
+    pub span: Span,
+    pub params: Vec<LocalId>,
+    pub ret: Type,
+    /// Every local of the function, including its parameters, indexed by [`LocalId`].
+    pub locals: Vec<Local>,
+    pub body: Expr,
+}
+
+impl Function {
+    pub fn local(&self, id: LocalId) -> &Local {
+        &self.locals[id.0 as usize]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Local {
+    pub name: String,
+    pub ty: Type,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumDef {
+    /// The path of the enum from the root module.
+    pub name: String,
+    pub variants: Vec<VariantDef>,
+    /// Whether it's a struct, which is an enum of one variant named after it.
+    pub is_struct: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantDef {
+    pub name: String,
+    pub fields: Vec<Type>,
+    /// The names of the fields of record variants, `None` for tuple and unit variants.
+    pub field_names: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Int(IntTy),
+    Float(FloatTy),
+    Bool,
+    Char,
+    /// Immutable strings, which only come from literals.
+    Str,
+    Unit,
+    /// The type of expressions that never produce a value, such as a block ending with
+    /// `return`. It fits wherever a value is expected.
+    Never,
+    /// Tuples of at least one element, as `()` is [`Type::Unit`].
+    Tuple(Vec<Type>),
+    Enum(EnumId),
+}
+
+impl Type {
+    /// The type as written in lamia, naming enums after their definitions in `enums`.
+    pub fn display(&self, enums: &[EnumDef]) -> String {
+        match self {
+            Self::Int(ty) => ty.name().to_string(),
+            Self::Float(ty) => ty.name().to_string(),
+            Self::Bool => "bool".to_string(),
+            Self::Char => "char".to_string(),
+            Self::Str => "String".to_string(),
+            Self::Unit => "()".to_string(),
+            Self::Never => "!".to_string(),
+            Self::Tuple(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| element.display(enums))
+                    .collect::<Vec<_>>();
+                match elements.as_slice() {
+                    [element] => format!("({element},)"),
+                    _ => format!("({})", elements.join(", ")),
+                }
+            }
+            Self::Enum(id) => enums[id.0 as usize].name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub ty: Type,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Literal(Literal),
+    Local(LocalId),
+    Call(FunctionId, Vec<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    /// Arithmetic on integers is checked for overflow, and `&&` and `||` short-circuit.
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// `place = value`, or `place op= value` with the operator.
+    Assign(Place, Option<BinaryOp>, Box<Expr>),
+    Tuple(Vec<Expr>),
+    /// An element of a tuple or a field of a struct, by index.
+    Field(Box<Expr>, usize),
+    /// A value of a variant, with its fields in declaration order.
+    Variant(EnumId, usize, Vec<Expr>),
+    Block(Vec<Stmt>, Option<Box<Expr>>),
+    If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    Match(Box<Expr>, Vec<Arm>),
+    /// Writes the values one after another, as `print` and `println` do.
+    Print(Output, Vec<Expr>),
+    /// Stops the program with an error made of the values, as failed assertions do.
+    Panic(Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    Str(String),
+    Unit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    /// `!` on booleans.
+    Not,
+    /// `~` on integers.
+    BitNot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    pub fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            Self::Eq | Self::Ne | Self::Lt | Self::Le | Self::Gt | Self::Ge
+        )
+    }
+}
+
+/// Where an assignment writes: a local, or an element of a tuple or a field of a struct
+/// stored in one, possibly nested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Place {
+    pub local: LocalId,
+    /// The indices of the elements and fields, outermost first.
+    pub fields: Vec<usize>,
+    pub ty: Type,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Stdout { newline: bool },
+    Stderr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    /// Binds the value to the pattern, which can't fail to match.
+    Let(Pattern, Expr),
+    Expr(Expr),
+    Return(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Wildcard,
+    Binding(LocalId),
+    Literal(Literal),
+    /// `start..end` or `start..=end`, on integers and characters.
+    Range {
+        start: Literal,
+        end: Literal,
+        inclusive: bool,
+    },
+    /// Alternatives binding the same locals.
+    Or(Vec<Pattern>),
+    /// A pattern for each element, with `..` expanded to wildcards.
+    Tuple(Vec<Pattern>),
+    /// A pattern for each field of the variant, in declaration order.
+    Variant(EnumId, usize, Vec<Pattern>),
+}
+
+impl Pattern {
+    /// Whether every value of its type matches the pattern.
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Self::Wildcard | Self::Binding(_) => true,
+            Self::Literal(Literal::Unit) => true,
+            Self::Literal(_) | Self::Range { .. } | Self::Variant(..) => false,
+            Self::Or(alternatives) => alternatives.iter().any(Self::is_irrefutable),
+            Self::Tuple(elements) => elements.iter().all(Self::is_irrefutable),
+        }
+    }
+}
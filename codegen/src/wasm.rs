@@ -0,0 +1,271 @@
+//! Generation of WebAssembly from the typed IR, as a binary module or in the text format.
+//! Integers of up to 32 bits are `i32`s and wider ones `i64`s, floats keep their width, and
+//! strings, tuples and enums live in linear memory, where values refer to them by address.
+//!
+//! Modules export their `memory` and a `main` function, which runs `main` of the program
+//! and prints its result unless it's `()`. The host does the output through the functions
+//! they import from the `lamia` module:
+//!
+//! - `write(fd: i32, ptr: i32, len: i32)` writes bytes of memory to stdout (1) or stderr (2).
+//! - `write_f32(fd: i32, x: f32)` and `write_f64(fd: i32, x: f64)` write floats like the
+//!   interpreter does, with the fewest digits reading back as the same value.
+//! - `exit(code: i32)` stops the program, as runtime errors do after writing their message.
+
+/// A sequence of instructions, where [`Op`]s can be written as they are.
+macro_rules! code {
+    ($($instr:expr),* $(,)?) => {
+        vec![$($crate::wasm::Instr::from($instr)),*]
+    };
+}
+
+mod binary;
+mod emit;
+mod runtime;
+mod text;
+
+use source_map::SourceMap;
+
+use crate::tir::Program;
+
+/// The module the functions of the host are imported from.
+const HOST: &str = "lamia";
+
+/// Generates a binary module running `main` of `program`.
+pub fn emit_binary(program: &Program, source_map: &SourceMap) -> Vec<u8> {
+    binary::encode(&emit::module(program, source_map))
+}
+
+/// Generates a module in the text format, which is the binary one written for people.
+pub fn emit_text(program: &Program, source_map: &SourceMap) -> String {
+    text::write(&emit::module(program, source_map))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ValType {
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+impl ValType {
+    fn name(self) -> &'static str {
+        match self {
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FuncType {
+    params: Vec<ValType>,
+    results: Vec<ValType>,
+}
+
+/// The values a block leaves on the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockType {
+    Empty,
+    Value(ValType),
+}
+
+/// Defines [`Op`], the instructions without immediates, with their opcodes and names.
+macro_rules! ops {
+    ($($op:ident = $opcode:literal $name:literal,)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Op {
+            $($op,)*
+        }
+
+        impl Op {
+            fn opcode(self) -> u8 {
+                match self {
+                    $(Self::$op => $opcode,)*
+                }
+            }
+
+            fn name(self) -> &'static str {
+                match self {
+                    $(Self::$op => $name,)*
+                }
+            }
+        }
+    };
+}
+
+ops! {
+    Unreachable = 0x00 "unreachable",
+    Return = 0x0f "return",
+    Drop = 0x1a "drop",
+    Select = 0x1b "select",
+    I32Eqz = 0x45 "i32.eqz",
+    I32Eq = 0x46 "i32.eq",
+    I32Ne = 0x47 "i32.ne",
+    I32LtS = 0x48 "i32.lt_s",
+    I32LtU = 0x49 "i32.lt_u",
+    I32GtS = 0x4a "i32.gt_s",
+    I32GtU = 0x4b "i32.gt_u",
+    I32LeS = 0x4c "i32.le_s",
+    I32LeU = 0x4d "i32.le_u",
+    I32GeS = 0x4e "i32.ge_s",
+    I32GeU = 0x4f "i32.ge_u",
+    I64Eqz = 0x50 "i64.eqz",
+    I64Eq = 0x51 "i64.eq",
+    I64Ne = 0x52 "i64.ne",
+    I64LtS = 0x53 "i64.lt_s",
+    I64LtU = 0x54 "i64.lt_u",
+    I64GtS = 0x55 "i64.gt_s",
+    I64GtU = 0x56 "i64.gt_u",
+    I64LeS = 0x57 "i64.le_s",
+    I64LeU = 0x58 "i64.le_u",
+    I64GeS = 0x59 "i64.ge_s",
+    I64GeU = 0x5a "i64.ge_u",
+    F32Eq = 0x5b "f32.eq",
+    F32Ne = 0x5c "f32.ne",
+    F32Lt = 0x5d "f32.lt",
+    F32Gt = 0x5e "f32.gt",
+    F32Le = 0x5f "f32.le",
+    F32Ge = 0x60 "f32.ge",
+    F64Eq = 0x61 "f64.eq",
+    F64Ne = 0x62 "f64.ne",
+    F64Lt = 0x63 "f64.lt",
+    F64Gt = 0x64 "f64.gt",
+    F64Le = 0x65 "f64.le",
+    F64Ge = 0x66 "f64.ge",
+    I32Add = 0x6a "i32.add",
+    I32Sub = 0x6b "i32.sub",
+    I32DivS = 0x6d "i32.div_s",
+    I32DivU = 0x6e "i32.div_u",
+    I32RemS = 0x6f "i32.rem_s",
+    I32RemU = 0x70 "i32.rem_u",
+    I32And = 0x71 "i32.and",
+    I32Or = 0x72 "i32.or",
+    I32Xor = 0x73 "i32.xor",
+    I32Shl = 0x74 "i32.shl",
+    I32ShrS = 0x75 "i32.shr_s",
+    I32ShrU = 0x76 "i32.shr_u",
+    I64Add = 0x7c "i64.add",
+    I64Sub = 0x7d "i64.sub",
+    I64Mul = 0x7e "i64.mul",
+    I64DivS = 0x7f "i64.div_s",
+    I64DivU = 0x80 "i64.div_u",
+    I64RemS = 0x81 "i64.rem_s",
+    I64RemU = 0x82 "i64.rem_u",
+    I64And = 0x83 "i64.and",
+    I64Or = 0x84 "i64.or",
+    I64Xor = 0x85 "i64.xor",
+    I64Shl = 0x86 "i64.shl",
+    I64ShrS = 0x87 "i64.shr_s",
+    I64ShrU = 0x88 "i64.shr_u",
+    F32Abs = 0x8b "f32.abs",
+    F32Neg = 0x8c "f32.neg",
+    F32Add = 0x92 "f32.add",
+    F32Sub = 0x93 "f32.sub",
+    F32Mul = 0x94 "f32.mul",
+    F32Div = 0x95 "f32.div",
+    F32Copysign = 0x98 "f32.copysign",
+    F64Abs = 0x99 "f64.abs",
+    F64Neg = 0x9a "f64.neg",
+    F64Add = 0xa0 "f64.add",
+    F64Sub = 0xa1 "f64.sub",
+    F64Mul = 0xa2 "f64.mul",
+    F64Div = 0xa3 "f64.div",
+    F64Copysign = 0xa6 "f64.copysign",
+    I32WrapI64 = 0xa7 "i32.wrap_i64",
+    I64ExtendI32S = 0xac "i64.extend_i32_s",
+    I64ExtendI32U = 0xad "i64.extend_i32_u",
+    I32Extend8S = 0xc0 "i32.extend8_s",
+    I32Extend16S = 0xc1 "i32.extend16_s",
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Instr {
+    Op(Op),
+    Block(BlockType),
+    Loop(BlockType),
+    If(BlockType),
+    Else,
+    End,
+    /// A branch to the label of the enclosing block this many levels out.
+    Br(u32),
+    BrIf(u32),
+    /// A call of the function with this index, where the imports come first.
+    Call(u32),
+    LocalGet(u32),
+    LocalSet(u32),
+    LocalTee(u32),
+    GlobalGet(u32),
+    GlobalSet(u32),
+    /// A load of a value of the type from the address on the stack plus the offset.
+    Load(ValType, u32),
+    Store(ValType, u32),
+    Load8U(u32),
+    Store8(u32),
+    MemorySize,
+    MemoryGrow,
+    MemoryCopy,
+    I32Const(i32),
+    I64Const(i64),
+    F32Const(f32),
+    F64Const(f64),
+}
+
+impl From<Op> for Instr {
+    fn from(op: Op) -> Self {
+        Self::Op(op)
+    }
+}
+
+/// A function of the host, imported from the `lamia` module.
+#[derive(Debug, Clone)]
+struct Import {
+    name: &'static str,
+    ty: FuncType,
+}
+
+#[derive(Debug, Clone)]
+struct Func {
+    name: String,
+    ty: FuncType,
+    /// The types of the locals following the parameters.
+    locals: Vec<ValType>,
+    /// The names of the parameters and then of the locals.
+    local_names: Vec<String>,
+    body: Vec<Instr>,
+}
+
+#[derive(Debug, Clone)]
+struct Global {
+    name: &'static str,
+    ty: ValType,
+    init: i32,
+}
+
+#[derive(Debug, Clone)]
+struct Module {
+    imports: Vec<Import>,
+    /// The functions defined by the module, whose indices follow those of the imports.
+    functions: Vec<Func>,
+    /// The initial size of the memory, in pages of 64 KiB.
+    pages: u32,
+    globals: Vec<Global>,
+    /// The index of the function exported as `main`.
+    main: u32,
+    /// The initial contents of the memory from [`Module::data_start`].
+    data: Vec<u8>,
+    data_start: u32,
+}
+
+impl Module {
+    /// The name of the imported or defined function with the index.
+    fn function_name(&self, index: u32) -> &str {
+        let index = index as usize;
+        match self.imports.get(index) {
+            Some(import) => import.name,
+            None => &self.functions[index - self.imports.len()].name,
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! The binary format of modules.
+
+use std::collections::HashMap;
+
+use super::{BlockType, FuncType, Instr, Module, ValType, HOST};
+
+pub(super) fn encode(module: &Module) -> Vec<u8> {
+    let mut types = Types::default();
+    let imports = module
+        .imports
+        .iter()
+        .map(|import| types.index(&import.ty))
+        .collect::<Vec<_>>();
+    let functions = module
+        .functions
+        .iter()
+        .map(|func| types.index(&func.ty))
+        .collect::<Vec<_>>();
+
+    let mut wasm = b"\0asm".to_vec();
+    wasm.extend(1u32.to_le_bytes());
+
+    section(&mut wasm, 1, |out| {
+        vec(out, &types.types, |out, ty| {
+            out.push(0x60);
+            vec(out, &ty.params, |out, ty| out.push(val_type(*ty)));
+            vec(out, &ty.results, |out, ty| out.push(val_type(*ty)));
+        })
+    });
+    section(&mut wasm, 2, |out| {
+        vec(
+            out,
+            &module.imports.iter().zip(&imports).collect::<Vec<_>>(),
+            |out, (import, ty)| {
+                name(out, HOST);
+                name(out, import.name);
+                out.push(0x00);
+                unsigned(out, u64::from(**ty));
+            },
+        )
+    });
+    section(&mut wasm, 3, |out| {
+        vec(out, &functions, |out, ty| unsigned(out, u64::from(*ty)))
+    });
+    section(&mut wasm, 5, |out| {
+        unsigned(out, 1);
+        out.push(0x00);
+        unsigned(out, u64::from(module.pages));
+    });
+    section(&mut wasm, 6, |out| {
+        vec(out, &module.globals, |out, global| {
+            out.push(val_type(global.ty));
+            out.push(0x01);
+            instr(out, Instr::I32Const(global.init));
+            out.push(0x0b);
+        })
+    });
+    section(&mut wasm, 7, |out| {
+        unsigned(out, 2);
+        name(out, "memory");
+        out.push(0x02);
+        unsigned(out, 0);
+        name(out, "main");
+        out.push(0x00);
+        unsigned(out, u64::from(module.main));
+    });
+    section(&mut wasm, 10, |out| {
+        vec(out, &module.functions, |out, func| {
+            let mut body = Vec::new();
+            // runs of locals of the same type are declared together
+            let mut runs: Vec<(u32, ValType)> = Vec::new();
+            for &ty in &func.locals {
+                match runs.last_mut() {
+                    Some((count, last)) if *last == ty => *count += 1,
+                    _ => runs.push((1, ty)),
+                }
+            }
+            vec(&mut body, &runs, |out, (count, ty)| {
+                unsigned(out, u64::from(*count));
+                out.push(val_type(*ty));
+            });
+            for &instruction in &func.body {
+                instr(&mut body, instruction);
+            }
+            body.push(0x0b);
+            unsigned(out, body.len() as u64);
+            out.extend(body);
+        })
+    });
+    section(&mut wasm, 11, |out| {
+        unsigned(out, 1);
+        out.push(0x00);
+        instr(out, Instr::I32Const(module.data_start as i32));
+        out.push(0x0b);
+        unsigned(out, module.data.len() as u64);
+        out.extend(&module.data);
+    });
+
+    // the names of the functions and locals, for debuggers and disassemblers
+    section(&mut wasm, 0, |out| {
+        name(out, "name");
+        let count = module.imports.len() + module.functions.len();
+        let indices = (0..count as u32).collect::<Vec<_>>();
+        section(out, 1, |out| {
+            vec(out, &indices, |out, &index| {
+                unsigned(out, u64::from(index));
+                name(out, module.function_name(index));
+            })
+        });
+        section(out, 2, |out| {
+            let offset = module.imports.len() as u32;
+            let functions = module.functions.iter().enumerate().collect::<Vec<_>>();
+            vec(out, &functions, |out, (index, func)| {
+                unsigned(out, u64::from(offset + *index as u32));
+                let locals = func.local_names.iter().enumerate().collect::<Vec<_>>();
+                vec(out, &locals, |out, (index, local)| {
+                    unsigned(out, *index as u64);
+                    name(out, local);
+                });
+            })
+        });
+    });
+    wasm
+}
+
+/// The distinct function types, which functions refer to by index.
+#[derive(Default)]
+struct Types {
+    types: Vec<FuncType>,
+    indices: HashMap<FuncType, u32>,
+}
+
+impl Types {
+    fn index(&mut self, ty: &FuncType) -> u32 {
+        if let Some(&index) = self.indices.get(ty) {
+            return index;
+        }
+        let index = self.types.len() as u32;
+        self.types.push(ty.clone());
+        self.indices.insert(ty.clone(), index);
+        index
+    }
+}
+
+fn section(out: &mut Vec<u8>, id: u8, f: impl FnOnce(&mut Vec<u8>)) {
+    let mut contents = Vec::new();
+    f(&mut contents);
+    out.push(id);
+    unsigned(out, contents.len() as u64);
+    out.extend(contents);
+}
+
+fn vec<T>(out: &mut Vec<u8>, items: &[T], mut f: impl FnMut(&mut Vec<u8>, &T)) {
+    unsigned(out, items.len() as u64);
+    for item in items {
+        f(out, item);
+    }
+}
+
+fn name(out: &mut Vec<u8>, name: &str) {
+    unsigned(out, name.len() as u64);
+    out.extend(name.as_bytes());
+}
+
+fn unsigned(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn signed(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        // done once the rest is all sign bits, which the last byte's sign bit extends
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn val_type(ty: ValType) -> u8 {
+    match ty {
+        ValType::I32 => 0x7f,
+        ValType::I64 => 0x7e,
+        ValType::F32 => 0x7d,
+        ValType::F64 => 0x7c,
+    }
+}
+
+fn block_type(out: &mut Vec<u8>, ty: BlockType) {
+    match ty {
+        BlockType::Empty => out.push(0x40),
+        BlockType::Value(ty) => out.push(val_type(ty)),
+    }
+}
+
+/// The alignment of an access of `size` bytes, as a power of two.
+fn mem_arg(out: &mut Vec<u8>, size: u32, offset: u32) {
+    unsigned(out, u64::from(size.trailing_zeros()));
+    unsigned(out, u64::from(offset));
+}
+
+fn instr(out: &mut Vec<u8>, instr: Instr) {
+    match instr {
+        Instr::Op(op) => out.push(op.opcode()),
+        Instr::Block(ty) => {
+            out.push(0x02);
+            block_type(out, ty);
+        }
+        Instr::Loop(ty) => {
+            out.push(0x03);
+            block_type(out, ty);
+        }
+        Instr::If(ty) => {
+            out.push(0x04);
+            block_type(out, ty);
+        }
+        Instr::Else => out.push(0x05),
+        Instr::End => out.push(0x0b),
+        Instr::Br(depth) => {
+            out.push(0x0c);
+            unsigned(out, u64::from(depth));
+        }
+        Instr::BrIf(depth) => {
+            out.push(0x0d);
+            unsigned(out, u64::from(depth));
+        }
+        Instr::Call(index) => {
+            out.push(0x10);
+            unsigned(out, u64::from(index));
+        }
+        Instr::LocalGet(index) => {
+            out.push(0x20);
+            unsigned(out, u64::from(index));
+        }
+        Instr::LocalSet(index) => {
+            out.push(0x21);
+            unsigned(out, u64::from(index));
+        }
+        Instr::LocalTee(index) => {
+            out.push(0x22);
+            unsigned(out, u64::from(index));
+        }
+        Instr::GlobalGet(index) => {
+            out.push(0x23);
+            unsigned(out, u64::from(index));
+        }
+        Instr::GlobalSet(index) => {
+            out.push(0x24);
+            unsigned(out, u64::from(index));
+        }
+        Instr::Load(ty, offset) => {
+            let (opcode, size) = match ty {
+                ValType::I32 => (0x28, 4),
+                ValType::I64 => (0x29, 8),
+                ValType::F32 => (0x2a, 4),
+                ValType::F64 => (0x2b, 8),
+            };
+            out.push(opcode);
+            mem_arg(out, size, offset);
+        }
+        Instr::Store(ty, offset) => {
+            let (opcode, size) = match ty {
+                ValType::I32 => (0x36, 4),
+                ValType::I64 => (0x37, 8),
+                ValType::F32 => (0x38, 4),
+                ValType::F64 => (0x39, 8),
+            };
+            out.push(opcode);
+            mem_arg(out, size, offset);
+        }
+        Instr::Load8U(offset) => {
+            out.push(0x2d);
+            mem_arg(out, 1, offset);
+        }
+        Instr::Store8(offset) => {
+            out.push(0x3a);
+            mem_arg(out, 1, offset);
+        }
+        Instr::MemorySize => out.extend([0x3f, 0x00]),
+        Instr::MemoryGrow => out.extend([0x40, 0x00]),
+        Instr::MemoryCopy => {
+            out.push(0xfc);
+            unsigned(out, 10);
+            out.extend([0x00, 0x00]);
+        }
+        Instr::I32Const(value) => {
+            out.push(0x41);
+            signed(out, i64::from(value));
+        }
+        Instr::I64Const(value) => {
+            out.push(0x42);
+            signed(out, value);
+        }
+        Instr::F32Const(value) => {
+            out.push(0x43);
+            out.extend(value.to_le_bytes());
+        }
+        Instr::F64Const(value) => {
+            out.push(0x44);
+            out.extend(value.to_le_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{signed, unsigned};
+
+    #[test]
+    fn leb128() {
+        let encode = |f: &dyn Fn(&mut Vec<u8>)| {
+            let mut out = Vec::new();
+            f(&mut out);
+            out
+        };
+        assert_eq!(encode(&|out| unsigned(out, 0)), [0x00]);
+        assert_eq!(encode(&|out| unsigned(out, 624_485)), [0xe5, 0x8e, 0x26]);
+        assert_eq!(encode(&|out| signed(out, 63)), [0x3f]);
+        assert_eq!(encode(&|out| signed(out, 64)), [0xc0, 0x00]);
+        assert_eq!(encode(&|out| signed(out, -64)), [0x40]);
+        assert_eq!(encode(&|out| signed(out, -123_456)), [0xc0, 0xbb, 0x78]);
+        assert_eq!(
+            encode(&|out| signed(out, i64::MIN)),
+            [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f]
+        );
+    }
+}
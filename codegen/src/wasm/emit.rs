@@ -0,0 +1,1290 @@
+//! The generation of a module from the typed IR. Functions are only generated once they're
+//! called, so the module holds the helpers the program uses and nothing more.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use source_map::{SourceMap, Span};
+
+use super::{
+    runtime::{Arithmetic, Helper},
+    BlockType, Func, FuncType, Global, Import, Instr, Module, Op, ValType,
+};
+use crate::{
+    c::identifier,
+    tir::{
+        Arm, BinaryOp, Expr, ExprKind, FloatTy, Function, FunctionId, IntTy, Literal, Output,
+        Pattern, Place, Program, Stmt, Type, UnaryOp,
+    },
+};
+
+/// The indices of the functions imported from the host.
+pub(super) const WRITE: u32 = 0;
+pub(super) const WRITE_F32: u32 = 1;
+pub(super) const WRITE_F64: u32 = 2;
+pub(super) const EXIT: u32 = 3;
+
+/// The memory where numbers and characters are formatted before being written, which
+/// follows the bytes at 0 no value is stored at.
+pub(super) const SCRATCH: i32 = 8;
+pub(super) const SCRATCH_END: i32 = 40;
+
+/// Where the string literals start, which are followed by the memory allocated at runtime.
+const DATA_START: u32 = SCRATCH_END as u32;
+
+/// The global holding the address the next allocation starts at.
+pub(super) const HEAP: u32 = 0;
+
+/// The size of the elements of tuples and the fields of variants, which follow the tag of
+/// variants in a slot of the same size.
+pub(super) const SLOT: u32 = 8;
+
+const PAGE_SIZE: u32 = 65536;
+
+fn imports() -> Vec<Import> {
+    let import = |name, params: &[ValType]| Import {
+        name,
+        ty: FuncType {
+            params: params.to_vec(),
+            results: Vec::new(),
+        },
+    };
+    vec![
+        import("write", &[ValType::I32, ValType::I32, ValType::I32]),
+        import("write_f32", &[ValType::I32, ValType::F32]),
+        import("write_f64", &[ValType::I32, ValType::F64]),
+        import("exit", &[ValType::I32]),
+    ]
+}
+
+/// The type values of `ty` have on the stack, where the value of `()` is 0.
+pub(super) fn val_type(ty: &Type) -> ValType {
+    match ty {
+        Type::Int(ty) if ty.bits() == 64 => ValType::I64,
+        Type::Float(FloatTy::F32) => ValType::F32,
+        Type::Float(FloatTy::F64) => ValType::F64,
+        _ => ValType::I32,
+    }
+}
+
+/// A condition holding when all of `conditions` do, which are only evaluated until one
+/// doesn't, or `None` if there are none.
+pub(super) fn all(conditions: Vec<Vec<Instr>>) -> Option<Vec<Instr>> {
+    let mut conditions = conditions.into_iter();
+    let mut code = conditions.next()?;
+    for condition in conditions {
+        code.push(Instr::If(BlockType::Value(ValType::I32)));
+        code.extend(condition);
+        code.extend([Instr::Else, Instr::I32Const(0), Instr::End]);
+    }
+    Some(code)
+}
+
+/// A condition holding when any of `conditions` does, which are only evaluated until one
+/// does.
+fn any(conditions: Vec<Vec<Instr>>) -> Option<Vec<Instr>> {
+    let mut conditions = conditions.into_iter();
+    let mut code = conditions.next()?;
+    for condition in conditions {
+        code.extend([
+            Instr::If(BlockType::Value(ValType::I32)),
+            Instr::I32Const(1),
+            Instr::Else,
+        ]);
+        code.extend(condition);
+        code.push(Instr::End);
+    }
+    Some(code)
+}
+
+/// The comparison of two values of `ty` on the stack.
+fn comparison(op: BinaryOp, ty: &Type) -> Op {
+    let signed = matches!(ty, Type::Int(int) if int.is_signed());
+    let ops = match (val_type(ty), signed) {
+        (ValType::I32, true) => [Op::I32LtS, Op::I32LeS, Op::I32GtS, Op::I32GeS],
+        (ValType::I32, false) => [Op::I32LtU, Op::I32LeU, Op::I32GtU, Op::I32GeU],
+        (ValType::I64, true) => [Op::I64LtS, Op::I64LeS, Op::I64GtS, Op::I64GeS],
+        (ValType::I64, false) => [Op::I64LtU, Op::I64LeU, Op::I64GtU, Op::I64GeU],
+        (ValType::F32, _) => [Op::F32Lt, Op::F32Le, Op::F32Gt, Op::F32Ge],
+        (ValType::F64, _) => [Op::F64Lt, Op::F64Le, Op::F64Gt, Op::F64Ge],
+    };
+    match op {
+        BinaryOp::Lt => ops[0],
+        BinaryOp::Le => ops[1],
+        BinaryOp::Gt => ops[2],
+        _ => ops[3],
+    }
+}
+
+/// The functions of a module, which are called by index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Callee {
+    Function(FunctionId),
+    /// The function exported as `main`.
+    Start,
+    Helper(Helper),
+    /// The comparison of values of a tuple or enum type.
+    Eq(Type),
+    /// The writing of values of a tuple or enum type.
+    Print(Type),
+}
+
+pub(super) fn module(program: &Program, source_map: &SourceMap) -> Module {
+    let mut emitter = Emitter {
+        program,
+        source_map,
+        data: Vec::new(),
+        strings: HashMap::new(),
+        indices: HashMap::new(),
+        pending: VecDeque::new(),
+        functions: Vec::new(),
+        function_names: Vec::new(),
+        type_names: HashMap::new(),
+    };
+
+    let mut taken = HashSet::new();
+    for function in &program.functions {
+        let base = format!("fn_{}", identifier(&function.name.replace("::", "__")));
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while !taken.insert(name.clone()) {
+            name = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        emitter.function_names.push(name);
+    }
+
+    // the functions of the program come first, in the order they're in the program
+    for index in 0..program.functions.len() {
+        emitter.call(Callee::Function(FunctionId(index as u32)));
+    }
+    let Instr::Call(main) = emitter.call(Callee::Start) else {
+        unreachable!("functions are referred to by calls")
+    };
+    while let Some((callee, index)) = emitter.pending.pop_front() {
+        let func = emitter.define(callee);
+        emitter.functions[index] = Some(func);
+    }
+
+    let heap = (DATA_START + emitter.data.len() as u32).next_multiple_of(SLOT);
+    Module {
+        imports: imports(),
+        functions: emitter.functions.into_iter().flatten().collect(),
+        pages: heap.div_ceil(PAGE_SIZE).max(1),
+        globals: vec![Global {
+            name: "heap",
+            ty: ValType::I32,
+            init: heap as i32,
+        }],
+        main,
+        data: emitter.data,
+        data_start: DATA_START,
+    }
+}
+
+pub(super) struct Emitter<'a> {
+    program: &'a Program,
+    source_map: &'a SourceMap,
+    /// The memory from [`DATA_START`] holding the string literals.
+    data: Vec<u8>,
+    /// The addresses of the string literals in memory.
+    strings: HashMap<String, i32>,
+    indices: HashMap<Callee, u32>,
+    /// The functions which are called but not yet generated, with their slots in
+    /// `functions`.
+    pending: VecDeque<(Callee, usize)>,
+    functions: Vec<Option<Func>>,
+    function_names: Vec<String>,
+    /// The names of the tuple and enum types, which name the functions comparing and
+    /// writing them.
+    type_names: HashMap<Type, String>,
+}
+
+impl Emitter<'_> {
+    /// A call of `callee`, which is generated later if it's the first.
+    fn call(&mut self, callee: Callee) -> Instr {
+        if let Some(&index) = self.indices.get(&callee) {
+            return Instr::Call(index);
+        }
+        let slot = self.functions.len();
+        let index = (imports().len() + slot) as u32;
+        self.functions.push(None);
+        self.pending.push_back((callee.clone(), slot));
+        self.indices.insert(callee, index);
+        Instr::Call(index)
+    }
+
+    pub(super) fn call_helper(&mut self, helper: Helper) -> Instr {
+        self.call(Callee::Helper(helper))
+    }
+
+    fn define(&mut self, callee: Callee) -> Func {
+        match callee {
+            Callee::Function(id) => FunctionEmitter::new(self, id).emit(),
+            Callee::Start => self.start(),
+            Callee::Helper(helper) => self.helper(helper),
+            Callee::Eq(ty) => self.eq_function(&ty),
+            Callee::Print(ty) => self.print_function(&ty),
+        }
+    }
+
+    /// The address of a string holding `text`, which is its length followed by its bytes.
+    pub(super) fn string(&mut self, text: &str) -> i32 {
+        if let Some(&address) = self.strings.get(text) {
+            return address;
+        }
+        self.data.resize(self.data.len().next_multiple_of(4), 0);
+        let address = (DATA_START as usize + self.data.len()) as i32;
+        self.data.extend((text.len() as u32).to_le_bytes());
+        self.data.extend(text.as_bytes());
+        self.strings.insert(text.to_string(), address);
+        address
+    }
+
+    fn location(&mut self, span: Span) -> i32 {
+        let location = self.source_map.location(span);
+        self.string(&location)
+    }
+
+    fn type_name(&mut self, ty: &Type) -> String {
+        if let Some(name) = self.type_names.get(ty) {
+            return name.clone();
+        }
+        let name = match ty {
+            Type::Enum(id) => {
+                let name = &self.program.enum_def(*id).name;
+                let base = format!("enum_{}", identifier(&name.replace("::", "__")));
+                // instances of generic enums such as `Pair<a::B>` and `Pair<a__B>` can share
+                // an identifier
+                let mut name = base.clone();
+                let mut suffix = 1;
+                while self.type_names.values().any(|taken| *taken == name) {
+                    name = format!("{base}_{suffix}");
+                    suffix += 1;
+                }
+                name
+            }
+            _ => {
+                let tuples = self.type_names.keys();
+                let count = tuples.filter(|ty| matches!(ty, Type::Tuple(_))).count();
+                format!("tuple{count}")
+            }
+        };
+        self.type_names.insert(ty.clone(), name.clone());
+        name
+    }
+
+    /// The instruction pushing the value of a literal of type `ty`.
+    fn literal(&mut self, literal: &Literal, ty: &Type) -> Instr {
+        match (literal, ty) {
+            // the bits of the value, as integers of up to 32 bits are extended to 32
+            (Literal::Int(value), Type::Int(int)) if int.bits() == 64 => {
+                Instr::I64Const(*value as i64)
+            }
+            (Literal::Int(value), _) => Instr::I32Const(*value as i32),
+            (Literal::Float(value), Type::Float(FloatTy::F32)) => Instr::F32Const(*value as f32),
+            (Literal::Float(value), _) => Instr::F64Const(*value),
+            (Literal::Bool(value), _) => Instr::I32Const(i32::from(*value)),
+            (Literal::Char(c), _) => Instr::I32Const(u32::from(*c) as i32),
+            (Literal::Str(s), _) => Instr::I32Const(self.string(s)),
+            (Literal::Unit, _) => Instr::I32Const(0),
+        }
+    }
+
+    /// Adds to `out` the comparison of two values of `ty` on the stack.
+    fn eq(&mut self, out: &mut Vec<Instr>, ty: &Type) {
+        let instr = match ty {
+            Type::Str => self.call_helper(Helper::StrEq),
+            Type::Tuple(_) | Type::Enum(_) => self.call(Callee::Eq(ty.clone())),
+            _ => match val_type(ty) {
+                ValType::I32 => Op::I32Eq.into(),
+                ValType::I64 => Op::I64Eq.into(),
+                ValType::F32 => Op::F32Eq.into(),
+                ValType::F64 => Op::F64Eq.into(),
+            },
+        };
+        out.push(instr);
+    }
+
+    /// Adds to `out` the writing of `text` to the file descriptor `fd` pushes.
+    pub(super) fn write_str(&mut self, out: &mut Vec<Instr>, fd: Instr, text: &str) {
+        let address = self.string(text);
+        let write = self.call_helper(Helper::WriteStr);
+        out.extend([fd, Instr::I32Const(address), write]);
+    }
+
+    /// Adds to `out` the writing of the value of `ty` which `value` pushes.
+    fn print(&mut self, out: &mut Vec<Instr>, fd: Instr, value: &[Instr], ty: &Type) {
+        out.push(fd);
+        match ty {
+            Type::Int(int) => {
+                out.extend(value);
+                if val_type(ty) == ValType::I32 {
+                    out.push(match int.is_signed() {
+                        true => Op::I64ExtendI32S.into(),
+                        false => Op::I64ExtendI32U.into(),
+                    });
+                }
+                let helper = match int.is_signed() {
+                    true => Helper::WriteI64,
+                    false => Helper::WriteU64,
+                };
+                out.push(self.call_helper(helper));
+            }
+            Type::Float(FloatTy::F32) => {
+                out.extend(value);
+                out.push(Instr::Call(WRITE_F32));
+            }
+            Type::Float(FloatTy::F64) => {
+                out.extend(value);
+                out.push(Instr::Call(WRITE_F64));
+            }
+            Type::Bool => {
+                out.extend(value);
+                out.extend([
+                    Instr::If(BlockType::Value(ValType::I32)),
+                    Instr::I32Const(self.string("true")),
+                    Instr::Else,
+                    Instr::I32Const(self.string("false")),
+                    Instr::End,
+                ]);
+                out.push(self.call_helper(Helper::WriteStr));
+            }
+            Type::Char => {
+                out.extend(value);
+                out.push(self.call_helper(Helper::WriteChar));
+            }
+            Type::Str => {
+                out.extend(value);
+                out.push(self.call_helper(Helper::WriteStr));
+            }
+            Type::Unit | Type::Never => {
+                out.push(Instr::I32Const(self.string("()")));
+                out.push(self.call_helper(Helper::WriteStr));
+            }
+            Type::Tuple(_) | Type::Enum(_) => {
+                out.extend(value);
+                out.push(self.call(Callee::Print(ty.clone())));
+            }
+        }
+    }
+
+    fn start(&mut self) -> Func {
+        let main = self.program.main;
+        let call = self.call(Callee::Function(main));
+        let mut body = Vec::new();
+        match &self.program.function(main).ret {
+            Type::Unit | Type::Never => body.extend([call, Op::Drop.into()]),
+            ty => {
+                self.print(&mut body, Instr::I32Const(1), &[call], ty);
+                self.write_str(&mut body, Instr::I32Const(1), "\n");
+            }
+        }
+        func("main", &[], &[], &[], body)
+    }
+
+    fn eq_function(&mut self, ty: &Type) -> Func {
+        let name = format!("eq_{}", self.type_name(ty));
+        let field = |index: u32, offset: u32, ty: &Type| {
+            vec![Instr::LocalGet(index), Instr::Load(val_type(ty), offset)]
+        };
+        let fields = |this: &mut Self, types: &[Type], start: u32| {
+            let mut conditions = Vec::new();
+            for (index, ty) in types.iter().enumerate() {
+                let offset = start + SLOT * index as u32;
+                let mut condition = field(0, offset, ty);
+                condition.extend(field(1, offset, ty));
+                this.eq(&mut condition, ty);
+                conditions.push(condition);
+            }
+            all(conditions).unwrap_or_else(|| vec![Instr::I32Const(1)])
+        };
+
+        let body = match ty {
+            Type::Tuple(elements) => fields(self, elements, 0),
+            Type::Enum(id) => {
+                let mut body = field(0, 0, &Type::Bool);
+                body.extend(field(1, 0, &Type::Bool));
+                body.extend([
+                    Op::I32Ne.into(),
+                    Instr::If(BlockType::Empty),
+                    Instr::I32Const(0),
+                    Op::Return.into(),
+                    Instr::End,
+                ]);
+                let variants = &self.program.enum_def(*id).variants;
+                for (index, variant) in variants.iter().enumerate() {
+                    if variant.fields.is_empty() {
+                        continue;
+                    }
+                    body.extend(field(0, 0, &Type::Bool));
+                    body.extend([
+                        Instr::I32Const(index as i32),
+                        Op::I32Eq.into(),
+                        Instr::If(BlockType::Empty),
+                    ]);
+                    body.extend(fields(self, &variant.fields, SLOT));
+                    body.extend([Op::Return.into(), Instr::End]);
+                }
+                body.push(Instr::I32Const(1));
+                body
+            }
+            _ => unreachable!("only tuples and enums are compared by functions"),
+        };
+        let params = [("a", ValType::I32), ("b", ValType::I32)];
+        func(&name, &params, &[ValType::I32], &[], body)
+    }
+
+    fn print_function(&mut self, ty: &Type) -> Func {
+        let name = format!("print_{}", self.type_name(ty));
+        let fd = Instr::LocalGet(0);
+        let field =
+            |offset: u32, ty: &Type| [Instr::LocalGet(1), Instr::Load(val_type(ty), offset)];
+
+        let mut body = Vec::new();
+        match ty {
+            Type::Tuple(elements) => {
+                self.write_str(&mut body, fd, "(");
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        self.write_str(&mut body, fd, ", ");
+                    }
+                    let value = field(SLOT * index as u32, element);
+                    self.print(&mut body, fd, &value, element);
+                }
+                // `(1,)` tells a tuple of one element apart from a parenthesized value
+                let end = if elements.len() == 1 { ",)" } else { ")" };
+                self.write_str(&mut body, fd, end);
+            }
+            Type::Enum(id) => {
+                let variants = &self.program.enum_def(*id).variants;
+                for (index, variant) in variants.iter().enumerate() {
+                    body.extend([
+                        Instr::LocalGet(1),
+                        Instr::Load(ValType::I32, 0),
+                        Instr::I32Const(index as i32),
+                        Op::I32Eq.into(),
+                        Instr::If(BlockType::Empty),
+                    ]);
+                    self.write_str(&mut body, fd, &variant.name);
+                    let fields = variant.fields.iter().enumerate();
+                    match &variant.field_names {
+                        _ if variant.fields.is_empty() => {}
+                        Some(names) => {
+                            for ((index, ty), name) in fields.zip(names) {
+                                let separator = if index > 0 { "," } else { " {" };
+                                self.write_str(&mut body, fd, &format!("{separator} {name}: "));
+                                let value = field(SLOT + SLOT * index as u32, ty);
+                                self.print(&mut body, fd, &value, ty);
+                            }
+                            self.write_str(&mut body, fd, " }");
+                        }
+                        None => {
+                            self.write_str(&mut body, fd, "(");
+                            for (index, ty) in fields {
+                                if index > 0 {
+                                    self.write_str(&mut body, fd, ", ");
+                                }
+                                let value = field(SLOT + SLOT * index as u32, ty);
+                                self.print(&mut body, fd, &value, ty);
+                            }
+                            self.write_str(&mut body, fd, ")");
+                        }
+                    }
+                    body.push(Instr::End);
+                }
+            }
+            _ => unreachable!("only tuples and enums are printed by functions"),
+        }
+        let params = [("fd", ValType::I32), ("v", ValType::I32)];
+        func(&name, &params, &[], &[], body)
+    }
+}
+
+/// A function with named parameters and locals.
+pub(super) fn func(
+    name: &str,
+    params: &[(&str, ValType)],
+    results: &[ValType],
+    locals: &[(&str, ValType)],
+    body: Vec<Instr>,
+) -> Func {
+    Func {
+        name: name.to_string(),
+        ty: FuncType {
+            params: params.iter().map(|(_, ty)| *ty).collect(),
+            results: results.to_vec(),
+        },
+        locals: locals.iter().map(|(_, ty)| *ty).collect(),
+        local_names: params
+            .iter()
+            .chain(locals)
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        body,
+    }
+}
+
+/// The offset of the field `index` of a tuple or a struct of type `ty` from its address,
+/// which is past the tag for a struct, as it's an enum of one variant.
+fn field_offset(ty: &Type, index: usize) -> u32 {
+    let start = if matches!(ty, Type::Enum(_)) { SLOT } else { 0 };
+    start + SLOT * index as u32
+}
+
+/// Where a value is while a pattern is matched against it: in a local, or in a field
+/// reached from one through loads.
+#[derive(Debug, Clone)]
+struct Access {
+    local: u32,
+    loads: Vec<(ValType, u32)>,
+}
+
+impl Access {
+    fn field(&self, ty: &Type, offset: u32) -> Self {
+        let mut loads = self.loads.clone();
+        loads.push((val_type(ty), offset));
+        Self {
+            local: self.local,
+            loads,
+        }
+    }
+
+    fn code(&self) -> Vec<Instr> {
+        let loads = self
+            .loads
+            .iter()
+            .map(|&(ty, offset)| Instr::Load(ty, offset));
+        std::iter::once(Instr::LocalGet(self.local))
+            .chain(loads)
+            .collect()
+    }
+}
+
+/// The generation of a function, where generating an expression returns whether the code
+/// following it is reachable, which it isn't after expressions which don't produce a value.
+struct FunctionEmitter<'e, 'a> {
+    emitter: &'e mut Emitter<'a>,
+    id: FunctionId,
+    function: &'a Function,
+    /// The indices of the locals of the function, by [`LocalId`].
+    ///
+    /// [`LocalId`]: crate::tir::LocalId
+    locals: Vec<u32>,
+    types: Vec<ValType>,
+    names: Vec<String>,
+    taken: HashSet<String>,
+    /// The locals holding intermediate values which are free to be reused.
+    free: Vec<u32>,
+    body: Vec<Instr>,
+}
+
+impl<'e, 'a> FunctionEmitter<'e, 'a> {
+    fn new(emitter: &'e mut Emitter<'a>, id: FunctionId) -> Self {
+        let function = emitter.program.function(id);
+        let mut this = Self {
+            emitter,
+            id,
+            function,
+            locals: vec![0; function.locals.len()],
+            types: Vec::new(),
+            names: Vec::new(),
+            taken: HashSet::new(),
+            free: Vec::new(),
+            body: Vec::new(),
+        };
+
+        // the parameters come first, as they're the first locals of wasm functions
+        let others = (0..function.locals.len() as u32)
+            .map(crate::tir::LocalId)
+            .filter(|local| !function.params.contains(local));
+        for local in function.params.iter().copied().chain(others) {
+            let name = identifier(&function.local(local).name);
+            let ty = val_type(&function.local(local).ty);
+            this.locals[local.0 as usize] = this.add_local(&name, ty);
+        }
+        this
+    }
+
+    fn add_local(&mut self, base: &str, ty: ValType) -> u32 {
+        let mut name = base.to_string();
+        let mut suffix = 1;
+        while !self.taken.insert(name.clone()) {
+            name = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        self.names.push(name);
+        self.types.push(ty);
+        self.types.len() as u32 - 1
+    }
+
+    /// A local to hold an intermediate value until it's released.
+    fn temp(&mut self, ty: ValType) -> u32 {
+        if let Some(position) = self
+            .free
+            .iter()
+            .position(|&local| self.types[local as usize] == ty)
+        {
+            return self.free.remove(position);
+        }
+        let name = format!("t{}", self.types.len());
+        self.add_local(&name, ty)
+    }
+
+    fn release(&mut self, local: u32) {
+        self.free.push(local);
+    }
+
+    fn emit(mut self) -> Func {
+        let function = self.function;
+        // the value of the body is left on the stack, which returns it
+        self.expr(&function.body);
+
+        let params = function.params.len();
+        Func {
+            name: self.emitter.function_names[self.id.0 as usize].clone(),
+            ty: FuncType {
+                params: self.types[..params].to_vec(),
+                results: vec![val_type(&function.ret)],
+            },
+            locals: self.types[params..].to_vec(),
+            local_names: self.names,
+            body: self.body,
+        }
+    }
+
+    fn push(&mut self, instr: impl Into<Instr>) {
+        self.body.push(instr.into());
+    }
+
+    fn call_helper(&mut self, helper: Helper) {
+        let call = self.emitter.call_helper(helper);
+        self.push(call);
+    }
+
+    fn location(&mut self, span: Span) -> Instr {
+        Instr::I32Const(self.emitter.location(span))
+    }
+
+    fn expr(&mut self, expr: &Expr) -> bool {
+        let reachable = match &expr.kind {
+            ExprKind::Literal(value) => {
+                let instr = self.emitter.literal(value, &expr.ty);
+                self.push(instr);
+                true
+            }
+            ExprKind::Local(local) => {
+                self.push(Instr::LocalGet(self.locals[local.0 as usize]));
+                true
+            }
+            ExprKind::Call(id, args) => {
+                if !args.iter().all(|arg| self.expr(arg)) {
+                    return false;
+                }
+                let call = self.emitter.call(Callee::Function(*id));
+                self.push(call);
+                true
+            }
+            ExprKind::Unary(op, operand) => {
+                if !self.expr(operand) {
+                    return false;
+                }
+                self.unary(*op, &operand.ty, expr.span);
+                true
+            }
+            ExprKind::Binary(op @ (BinaryOp::And | BinaryOp::Or), lhs, rhs) => {
+                if !self.expr(lhs) {
+                    return false;
+                }
+                // the right operand is only evaluated if the left one doesn't decide
+                self.push(Instr::If(BlockType::Value(ValType::I32)));
+                if *op == BinaryOp::Or {
+                    self.push(Instr::I32Const(1));
+                    self.push(Instr::Else);
+                }
+                self.expr(rhs);
+                if *op == BinaryOp::And {
+                    self.push(Instr::Else);
+                    self.push(Instr::I32Const(0));
+                }
+                self.push(Instr::End);
+                true
+            }
+            ExprKind::Binary(op, lhs, rhs) => {
+                if !self.expr(lhs) || !self.expr(rhs) {
+                    return false;
+                }
+                self.binary(*op, &lhs.ty, &rhs.ty, expr.span);
+                true
+            }
+            ExprKind::Assign(place, op, value) => self.assign(place, *op, value, expr.span),
+            ExprKind::Tuple(elements) => self.aggregate(None, elements),
+            ExprKind::Field(value, index) => {
+                if !self.expr(value) {
+                    return false;
+                }
+                self.push(Instr::Load(
+                    val_type(&expr.ty),
+                    field_offset(&value.ty, *index),
+                ));
+                true
+            }
+            ExprKind::Variant(_, variant, fields) => self.aggregate(Some(*variant), fields),
+            ExprKind::Block(stmts, tail) => {
+                if !stmts.iter().all(|stmt| self.stmt(stmt)) {
+                    return false;
+                }
+                match tail {
+                    Some(tail) => self.expr(tail),
+                    None => {
+                        self.push(Instr::I32Const(0));
+                        true
+                    }
+                }
+            }
+            ExprKind::If(condition, then_branch, else_branch) => {
+                self.if_expr(&expr.ty, condition, then_branch, else_branch.as_deref())
+            }
+            ExprKind::Match(scrutinee, arms) => self.match_expr(&expr.ty, scrutinee, arms),
+            ExprKind::Print(output, parts) => {
+                let Some((values, temps)) = self.values(parts) else {
+                    return false;
+                };
+                let fd = match output {
+                    Output::Stdout { .. } => Instr::I32Const(1),
+                    Output::Stderr => Instr::I32Const(2),
+                };
+                for (value, part) in values.iter().zip(parts) {
+                    self.emitter.print(&mut self.body, fd, &[*value], &part.ty);
+                }
+                if *output != (Output::Stdout { newline: false }) {
+                    self.emitter.write_str(&mut self.body, fd, "\n");
+                }
+                temps.into_iter().for_each(|temp| self.release(temp));
+                self.push(Instr::I32Const(0));
+                true
+            }
+            ExprKind::Panic(parts) => {
+                let Some((values, _)) = self.values(parts) else {
+                    return false;
+                };
+                self.call_helper(Helper::PanicBegin);
+                for (value, part) in values.iter().zip(parts) {
+                    self.emitter
+                        .print(&mut self.body, Instr::I32Const(2), &[*value], &part.ty);
+                }
+                let at = self.location(expr.span);
+                self.push(at);
+                self.call_helper(Helper::PanicEnd);
+                self.push(Op::Unreachable);
+                return false;
+            }
+        };
+
+        if reachable && expr.ty == Type::Never {
+            self.push(Op::Unreachable);
+            return false;
+        }
+        reachable
+    }
+
+    /// Evaluates `exprs` from left to right, giving the instructions pushing their values,
+    /// and the locals holding them which are to be released.
+    fn values(&mut self, exprs: &[Expr]) -> Option<(Vec<Instr>, Vec<u32>)> {
+        let mut values = Vec::new();
+        let mut temps = Vec::new();
+        for expr in exprs {
+            if let ExprKind::Literal(value) = &expr.kind {
+                values.push(self.emitter.literal(value, &expr.ty));
+                continue;
+            }
+            if !self.expr(expr) {
+                return None;
+            }
+            let temp = self.temp(val_type(&expr.ty));
+            self.push(Instr::LocalSet(temp));
+            values.push(Instr::LocalGet(temp));
+            temps.push(temp);
+        }
+        Some((values, temps))
+    }
+
+    fn unary(&mut self, op: UnaryOp, ty: &Type, span: Span) {
+        match (op, ty) {
+            (UnaryOp::Neg, Type::Int(int)) => {
+                let at = self.location(span);
+                self.push(at);
+                self.call_helper(Helper::Arithmetic(Arithmetic::Neg, *int));
+            }
+            (UnaryOp::Neg, Type::Float(FloatTy::F32)) => self.push(Op::F32Neg),
+            (UnaryOp::Neg, _) => self.push(Op::F64Neg),
+            (UnaryOp::Not, _) => self.push(Op::I32Eqz),
+            (UnaryOp::BitNot, ty) if val_type(ty) == ValType::I64 => {
+                self.push(Instr::I64Const(-1));
+                self.push(Op::I64Xor);
+            }
+            (UnaryOp::BitNot, ty) => {
+                self.push(Instr::I32Const(-1));
+                self.push(Op::I32Xor);
+                // the bits above those of unsigned types stay clear
+                let mask = match ty {
+                    Type::Int(IntTy::U8) => 0xff,
+                    Type::Int(IntTy::U16) => 0xffff,
+                    _ => return,
+                };
+                self.push(Instr::I32Const(mask));
+                self.push(Op::I32And);
+            }
+        }
+    }
+
+    /// Generates the operation on the values of `ty` and `rhs_ty` on the stack.
+    fn binary(&mut self, op: BinaryOp, ty: &Type, rhs_ty: &Type, span: Span) {
+        let wide = val_type(ty) == ValType::I64;
+        let pick = |narrow: Op, wide_op: Op| if wide { wide_op } else { narrow };
+        match (op, ty) {
+            (BinaryOp::Eq, _) => self.emitter.eq(&mut self.body, ty),
+            (BinaryOp::Ne, _) => {
+                self.emitter.eq(&mut self.body, ty);
+                self.push(Op::I32Eqz);
+            }
+            (_, Type::Str) => {
+                self.call_helper(Helper::StrCmp);
+                self.push(Instr::I32Const(0));
+                self.push(comparison(op, &Type::Int(IntTy::I32)));
+            }
+            (BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge, _) => {
+                self.push(comparison(op, ty))
+            }
+            (
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem,
+                Type::Int(int),
+            ) => {
+                let arithmetic = match op {
+                    BinaryOp::Add => Arithmetic::Add,
+                    BinaryOp::Sub => Arithmetic::Sub,
+                    BinaryOp::Mul => Arithmetic::Mul,
+                    BinaryOp::Div => Arithmetic::Div,
+                    _ => Arithmetic::Rem,
+                };
+                let at = self.location(span);
+                self.push(at);
+                self.call_helper(Helper::Arithmetic(arithmetic, *int));
+            }
+            (BinaryOp::Shl | BinaryOp::Shr, Type::Int(int)) => {
+                let Type::Int(amount) = rhs_ty else {
+                    unreachable!("shift amounts are integers")
+                };
+                let at = self.location(span);
+                self.push(at);
+                self.call_helper(Helper::Shift(op == BinaryOp::Shl, *int, *amount));
+            }
+            (BinaryOp::Rem, Type::Float(ty)) => self.call_helper(Helper::Fmod(*ty)),
+            (_, Type::Float(FloatTy::F32)) => self.push(match op {
+                BinaryOp::Add => Op::F32Add,
+                BinaryOp::Sub => Op::F32Sub,
+                BinaryOp::Mul => Op::F32Mul,
+                _ => Op::F32Div,
+            }),
+            (_, Type::Float(FloatTy::F64)) => self.push(match op {
+                BinaryOp::Add => Op::F64Add,
+                BinaryOp::Sub => Op::F64Sub,
+                BinaryOp::Mul => Op::F64Mul,
+                _ => Op::F64Div,
+            }),
+            (BinaryOp::BitAnd, _) => self.push(pick(Op::I32And, Op::I64And)),
+            (BinaryOp::BitOr, _) => self.push(pick(Op::I32Or, Op::I64Or)),
+            _ => self.push(pick(Op::I32Xor, Op::I64Xor)),
+        }
+    }
+
+    fn assign(&mut self, place: &Place, op: Option<BinaryOp>, value: &Expr, span: Span) -> bool {
+        let local = self.locals[place.local.0 as usize];
+        let Some((&last, path)) = place.fields.split_last() else {
+            if op.is_some() {
+                self.push(Instr::LocalGet(local));
+            }
+            if !self.expr(value) {
+                return false;
+            }
+            if let Some(op) = op {
+                self.binary(op, &place.ty, &value.ty, span);
+            }
+            self.push(Instr::LocalSet(local));
+            self.push(Instr::I32Const(0));
+            return true;
+        };
+
+        if !self.expr(value) {
+            return false;
+        }
+        let value_local = self.temp(val_type(&value.ty));
+        self.push(Instr::LocalSet(value_local));
+
+        // tuples and structs are shared by the values they were copied to, so those along
+        // the path are copied before one is changed
+        let program = self.emitter.program;
+        let mut ty = &self.function.local(place.local).ty;
+        // the size is the offset of the field past the last one
+        let size = |ty: &Type| Instr::I32Const(field_offset(ty, program.fields(ty).len()) as i32);
+        self.push(Instr::LocalGet(local));
+        self.push(size(ty));
+        self.call_helper(Helper::Copy);
+        self.push(Instr::LocalSet(local));
+        let tuple = self.temp(ValType::I32);
+        self.push(Instr::LocalGet(local));
+        self.push(Instr::LocalSet(tuple));
+
+        for &index in path {
+            let offset = field_offset(ty, index);
+            ty = &program.fields(ty)[index];
+            let copy = self.temp(ValType::I32);
+            self.body.extend([
+                Instr::LocalGet(tuple),
+                Instr::Load(ValType::I32, offset),
+                size(ty),
+            ]);
+            self.call_helper(Helper::Copy);
+            self.body.extend([
+                Instr::LocalSet(copy),
+                Instr::LocalGet(tuple),
+                Instr::LocalGet(copy),
+                Instr::Store(ValType::I32, offset),
+                Instr::LocalGet(copy),
+                Instr::LocalSet(tuple),
+            ]);
+            self.release(copy);
+        }
+
+        let offset = field_offset(ty, last);
+        let element_ty = &program.fields(ty)[last];
+        self.push(Instr::LocalGet(tuple));
+        if let Some(op) = op {
+            self.push(Instr::LocalGet(tuple));
+            self.push(Instr::Load(val_type(element_ty), offset));
+            self.push(Instr::LocalGet(value_local));
+            self.binary(op, element_ty, &value.ty, span);
+        } else {
+            self.push(Instr::LocalGet(value_local));
+        }
+        self.push(Instr::Store(val_type(element_ty), offset));
+        self.release(tuple);
+        self.release(value_local);
+        self.push(Instr::I32Const(0));
+        true
+    }
+
+    /// Allocates a tuple, or a variant with the tag if given, holding the values of
+    /// `fields`.
+    fn aggregate(&mut self, tag: Option<usize>, fields: &[Expr]) -> bool {
+        let start = if tag.is_some() { SLOT } else { 0 };
+        let size = start + SLOT * fields.len() as u32;
+        let address = self.temp(ValType::I32);
+        self.push(Instr::I32Const(size as i32));
+        self.call_helper(Helper::Alloc);
+        self.push(Instr::LocalSet(address));
+        if let Some(tag) = tag {
+            self.body.extend([
+                Instr::LocalGet(address),
+                Instr::I32Const(tag as i32),
+                Instr::Store(ValType::I32, 0),
+            ]);
+        }
+        for (index, field) in fields.iter().enumerate() {
+            self.push(Instr::LocalGet(address));
+            if !self.expr(field) {
+                self.release(address);
+                return false;
+            }
+            let offset = start + SLOT * index as u32;
+            self.push(Instr::Store(val_type(&field.ty), offset));
+        }
+        self.push(Instr::LocalGet(address));
+        self.release(address);
+        true
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Let(Pattern::Binding(local), value) => {
+                if !self.expr(value) {
+                    return false;
+                }
+                self.push(Instr::LocalSet(self.locals[local.0 as usize]));
+            }
+            Stmt::Let(pattern, value) => {
+                if !self.expr(value) {
+                    return false;
+                }
+                let temp = self.temp(val_type(&value.ty));
+                self.push(Instr::LocalSet(temp));
+                let access = Access {
+                    local: temp,
+                    loads: Vec::new(),
+                };
+                self.bind(pattern, &access, &value.ty);
+                self.release(temp);
+            }
+            Stmt::Expr(expr) => {
+                if !self.expr(expr) {
+                    return false;
+                }
+                // a constant such as the value of `()` is left out rather than dropped
+                match self.body.last() {
+                    Some(Instr::I32Const(_)) => {
+                        self.body.pop();
+                    }
+                    _ => self.push(Op::Drop),
+                }
+            }
+            Stmt::Return(value) => {
+                if self.expr(value) {
+                    self.push(Op::Return);
+                }
+                return false;
+            }
+        }
+        true
+    }
+
+    fn if_expr(
+        &mut self,
+        ty: &Type,
+        condition: &Expr,
+        then_branch: &Expr,
+        else_branch: Option<&Expr>,
+    ) -> bool {
+        if !self.expr(condition) {
+            return false;
+        }
+        let block = match (ty, else_branch) {
+            (Type::Never, _) | (_, None) => BlockType::Empty,
+            (ty, Some(_)) => BlockType::Value(val_type(ty)),
+        };
+
+        let branch = |this: &mut Self, expr: &Expr| {
+            if this.expr(expr) && block == BlockType::Empty {
+                this.push(Op::Drop);
+            }
+        };
+        self.push(Instr::If(block));
+        branch(self, then_branch);
+        if let Some(else_branch) = else_branch {
+            self.push(Instr::Else);
+            branch(self, else_branch);
+        }
+        self.push(Instr::End);
+        if block == BlockType::Empty {
+            self.push(Instr::I32Const(0));
+        }
+        true
+    }
+
+    fn match_expr(&mut self, ty: &Type, scrutinee: &Expr, arms: &[Arm]) -> bool {
+        if !self.expr(scrutinee) {
+            return false;
+        }
+        let value = self.temp(val_type(&scrutinee.ty));
+        self.push(Instr::LocalSet(value));
+        let access = Access {
+            local: value,
+            loads: Vec::new(),
+        };
+
+        // the arms are tried in turn inside a block, which the one matching branches out of
+        // with its value
+        let block = match ty {
+            Type::Never => BlockType::Empty,
+            ty => BlockType::Value(val_type(ty)),
+        };
+        self.push(Instr::Block(block));
+        let mut exhausted = false;
+        for arm in arms {
+            let mut depth = 0;
+            let test = self.test(&arm.pattern, &access, &scrutinee.ty);
+            let refutable = test.is_some();
+            if let Some(test) = test {
+                self.body.extend(test);
+                self.push(Instr::If(BlockType::Empty));
+                depth += 1;
+            }
+            self.bind(&arm.pattern, &access, &scrutinee.ty);
+            let reachable = match &arm.guard {
+                Some(guard) => {
+                    let reachable = self.expr(guard);
+                    if reachable {
+                        self.push(Instr::If(BlockType::Empty));
+                        depth += 1;
+                    }
+                    reachable
+                }
+                None => true,
+            };
+            if reachable && self.expr(&arm.body) {
+                self.push(Instr::Br(depth));
+            }
+            for _ in 0..depth {
+                self.push(Instr::End);
+            }
+            if !refutable && arm.guard.is_none() {
+                exhausted = true;
+                break;
+            }
+        }
+        if !exhausted {
+            // the checker ensures that the arms are exhaustive
+            self.push(Op::Unreachable);
+        }
+        self.push(Instr::End);
+        self.release(value);
+        true
+    }
+
+    /// Whether matching `pattern` assigns a local.
+    fn binds(pattern: &Pattern) -> bool {
+        match pattern {
+            Pattern::Binding(_) => true,
+            Pattern::Or(patterns) | Pattern::Tuple(patterns) | Pattern::Variant(_, _, patterns) => {
+                patterns.iter().any(Self::binds)
+            }
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range { .. } => false,
+        }
+    }
+
+    /// Assigns the locals `pattern` binds from the value at `access`, which matches it.
+    fn bind(&mut self, pattern: &Pattern, access: &Access, ty: &Type) {
+        match pattern {
+            Pattern::Binding(local) => {
+                self.body.extend(access.code());
+                self.push(Instr::LocalSet(self.locals[local.0 as usize]));
+            }
+            Pattern::Tuple(patterns) => {
+                let Type::Tuple(types) = ty else {
+                    return;
+                };
+                for (index, (pattern, ty)) in patterns.iter().zip(types).enumerate() {
+                    self.bind(pattern, &access.field(ty, SLOT * index as u32), ty);
+                }
+            }
+            Pattern::Variant(id, variant, patterns) => {
+                let types = &self.emitter.program.enum_def(*id).variants[*variant].fields;
+                for (index, (pattern, ty)) in patterns.iter().zip(types).enumerate() {
+                    let offset = SLOT + SLOT * index as u32;
+                    self.bind(pattern, &access.field(ty, offset), ty);
+                }
+            }
+            Pattern::Or(alternatives) if Self::binds(pattern) => {
+                let mut depth = 0;
+                for (index, alternative) in alternatives.iter().enumerate() {
+                    let test = match index + 1 < alternatives.len() {
+                        true => self.test(alternative, access, ty),
+                        false => None,
+                    };
+                    match test {
+                        Some(test) => {
+                            self.body.extend(test);
+                            self.push(Instr::If(BlockType::Empty));
+                            self.bind(alternative, access, ty);
+                            self.push(Instr::Else);
+                            depth += 1;
+                        }
+                        None => {
+                            self.bind(alternative, access, ty);
+                            break;
+                        }
+                    }
+                }
+                for _ in 0..depth {
+                    self.push(Instr::End);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// A condition which holds if the value at `access` matches `pattern`, or `None` if it
+    /// always does.
+    fn test(&mut self, pattern: &Pattern, access: &Access, ty: &Type) -> Option<Vec<Instr>> {
+        match pattern {
+            Pattern::Wildcard | Pattern::Binding(_) | Pattern::Literal(Literal::Unit) => None,
+            Pattern::Literal(Literal::Bool(true)) => Some(access.code()),
+            Pattern::Literal(Literal::Bool(false)) => {
+                let mut code = access.code();
+                code.push(Op::I32Eqz.into());
+                Some(code)
+            }
+            Pattern::Literal(literal) => {
+                let mut code = access.code();
+                code.push(self.emitter.literal(literal, ty));
+                self.emitter.eq(&mut code, ty);
+                Some(code)
+            }
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let (min, max) = match ty {
+                    Type::Int(ty) => (IntTy::min(*ty), IntTy::max(*ty)),
+                    _ => (0, i128::from(u32::from(char::MAX))),
+                };
+                let bound = |literal: &Literal| match literal {
+                    Literal::Int(value) => *value,
+                    Literal::Char(c) => i128::from(u32::from(*c)),
+                    _ => unreachable!("ranges are of integers and characters"),
+                };
+
+                let mut conditions = Vec::new();
+                let mut compare = |this: &mut Self, op: BinaryOp, literal: &Literal| {
+                    let mut code = access.code();
+                    code.push(this.emitter.literal(literal, ty));
+                    code.push(comparison(op, ty).into());
+                    conditions.push(code);
+                };
+                if bound(start) > min {
+                    compare(self, BinaryOp::Ge, start);
+                }
+                match inclusive {
+                    true if bound(end) < max => compare(self, BinaryOp::Le, end),
+                    false => compare(self, BinaryOp::Lt, end),
+                    true => {}
+                }
+                all(conditions)
+            }
+            Pattern::Or(alternatives) => {
+                let mut tests = Vec::new();
+                for alternative in alternatives {
+                    tests.push(self.test(alternative, access, ty)?);
+                }
+                any(tests)
+            }
+            Pattern::Tuple(patterns) => {
+                let Type::Tuple(types) = ty else {
+                    return None;
+                };
+                let tests = patterns
+                    .iter()
+                    .zip(types)
+                    .enumerate()
+                    .filter_map(|(index, (pattern, ty))| {
+                        self.test(pattern, &access.field(ty, SLOT * index as u32), ty)
+                    })
+                    .collect();
+                all(tests)
+            }
+            Pattern::Variant(id, variant, patterns) => {
+                let types = self.emitter.program.enum_def(*id).variants[*variant]
+                    .fields
+                    .clone();
+                let mut tag = access.field(&Type::Bool, 0).code();
+                tag.extend([Instr::I32Const(*variant as i32), Op::I32Eq.into()]);
+                // the fields are only read once the tag says which variant the value is
+                let mut tests = vec![tag];
+                for (index, (pattern, ty)) in patterns.iter().zip(&types).enumerate() {
+                    let field = access.field(ty, SLOT + SLOT * index as u32);
+                    tests.extend(self.test(pattern, &field, ty));
+                }
+                all(tests)
+            }
+        }
+    }
+}
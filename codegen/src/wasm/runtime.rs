@@ -0,0 +1,756 @@
+//! The functions of the runtime: allocation, the writing of values and the checks which
+//! stop the program with the same messages as the interpreter.
+
+use super::{
+    emit::{func, val_type, Emitter, EXIT, HEAP, SCRATCH, SCRATCH_END, WRITE},
+    BlockType::{Empty, Value},
+    Func, Instr,
+    Instr::*,
+    Op,
+    Op::*,
+    ValType::{I32, I64},
+};
+use crate::tir::{FloatTy, IntTy, Type};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum Helper {
+    /// Allocates memory, growing it when needed.
+    Alloc,
+    /// Copies a tuple of the size into memory allocated for it.
+    Copy,
+    WriteStr,
+    WriteI64,
+    WriteU64,
+    /// Writes a character encoded in UTF-8.
+    WriteChar,
+    StrEq,
+    /// Compares strings by their bytes, giving -1, 0 or 1.
+    StrCmp,
+    /// Writes the start of the message of a runtime error, which `PanicEnd` finishes with
+    /// its location before stopping the program.
+    PanicBegin,
+    PanicEnd,
+    Panic,
+    Arithmetic(Arithmetic, IntTy),
+    /// A shift of a value of the first type by an amount of the second type.
+    Shift(bool, IntTy, IntTy),
+    /// The remainder of a float division, which has the sign of the dividend.
+    Fmod(FloatTy),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Neg,
+}
+
+/// The file descriptor of stderr, where runtime errors are written.
+const STDERR: Instr = I32Const(2);
+
+impl Emitter<'_> {
+    pub(super) fn helper(&mut self, helper: Helper) -> Func {
+        match helper {
+            Helper::Alloc => func(
+                "alloc",
+                &[("size", I32)],
+                &[I32],
+                &[("address", I32)],
+                code![
+                    GlobalGet(HEAP),
+                    LocalSet(1),
+                    // allocations are aligned for the 64-bit values they hold
+                    GlobalGet(HEAP),
+                    LocalGet(0),
+                    I32Add,
+                    I32Const(7),
+                    I32Add,
+                    I32Const(-8),
+                    I32And,
+                    GlobalSet(HEAP),
+                    Block(Empty),
+                    Loop(Empty),
+                    GlobalGet(HEAP),
+                    MemorySize,
+                    I32Const(16),
+                    I32Shl,
+                    I32LeU,
+                    BrIf(1),
+                    I32Const(1),
+                    MemoryGrow,
+                    I32Const(-1),
+                    I32Eq,
+                    If(Empty),
+                    Unreachable,
+                    End,
+                    Br(0),
+                    End,
+                    End,
+                    LocalGet(1),
+                ],
+            ),
+            Helper::Copy => {
+                let alloc = self.call_helper(Helper::Alloc);
+                func(
+                    "copy",
+                    &[("tuple", I32), ("size", I32)],
+                    &[I32],
+                    &[("copy", I32)],
+                    code![
+                        LocalGet(1),
+                        alloc,
+                        LocalTee(2),
+                        LocalGet(0),
+                        LocalGet(1),
+                        MemoryCopy,
+                        LocalGet(2),
+                    ],
+                )
+            }
+            Helper::WriteStr => func(
+                "write_str",
+                &[("fd", I32), ("s", I32)],
+                &[],
+                &[],
+                code![
+                    LocalGet(0),
+                    LocalGet(1),
+                    I32Const(4),
+                    I32Add,
+                    LocalGet(1),
+                    Load(I32, 0),
+                    Call(WRITE),
+                ],
+            ),
+            Helper::WriteU64 => func(
+                "write_u64",
+                &[("fd", I32), ("x", I64)],
+                &[],
+                &[("p", I32)],
+                // the digits are written from the last, at the end of the scratch memory
+                code![
+                    I32Const(SCRATCH_END),
+                    LocalSet(2),
+                    Loop(Empty),
+                    LocalGet(2),
+                    I32Const(1),
+                    I32Sub,
+                    LocalTee(2),
+                    LocalGet(1),
+                    I64Const(10),
+                    I64RemU,
+                    I32WrapI64,
+                    I32Const(i32::from(b'0')),
+                    I32Add,
+                    Store8(0),
+                    LocalGet(1),
+                    I64Const(10),
+                    I64DivU,
+                    LocalTee(1),
+                    I64Const(0),
+                    I64Ne,
+                    BrIf(0),
+                    End,
+                    LocalGet(0),
+                    LocalGet(2),
+                    I32Const(SCRATCH_END),
+                    LocalGet(2),
+                    I32Sub,
+                    Call(WRITE),
+                ],
+            ),
+            Helper::WriteI64 => {
+                let minus = self.string("-");
+                let write_str = self.call_helper(Helper::WriteStr);
+                let write_u64 = self.call_helper(Helper::WriteU64);
+                func(
+                    "write_i64",
+                    &[("fd", I32), ("x", I64)],
+                    &[],
+                    &[],
+                    // negating the minimum leaves it as is, which is its magnitude unsigned
+                    code![
+                        LocalGet(1),
+                        I64Const(0),
+                        I64LtS,
+                        If(Empty),
+                        LocalGet(0),
+                        I32Const(minus),
+                        write_str,
+                        I64Const(0),
+                        LocalGet(1),
+                        I64Sub,
+                        LocalSet(1),
+                        End,
+                        LocalGet(0),
+                        LocalGet(1),
+                        write_u64,
+                    ],
+                )
+            }
+            Helper::WriteChar => {
+                let mut body = Vec::new();
+                for (len, limit) in [(1, 0x80), (2, 0x800), (3, 0x10000)] {
+                    body.extend(code![LocalGet(1), I32Const(limit), I32LtU, If(Empty)]);
+                    body.extend(utf8(len));
+                    body.push(Else);
+                }
+                body.extend(utf8(4));
+                body.extend([End, End, End]);
+                body.extend(code![
+                    LocalGet(0),
+                    I32Const(SCRATCH),
+                    LocalGet(2),
+                    Call(WRITE)
+                ]);
+                func(
+                    "write_char",
+                    &[("fd", I32), ("c", I32)],
+                    &[],
+                    &[("len", I32)],
+                    body,
+                )
+            }
+            Helper::StrEq => func(
+                "str_eq",
+                &[("a", I32), ("b", I32)],
+                &[I32],
+                &[("len", I32), ("i", I32)],
+                code![
+                    LocalGet(0),
+                    Load(I32, 0),
+                    LocalTee(2),
+                    LocalGet(1),
+                    Load(I32, 0),
+                    I32Ne,
+                    If(Empty),
+                    I32Const(0),
+                    Return,
+                    End,
+                    Block(Empty),
+                    Loop(Empty),
+                    LocalGet(3),
+                    LocalGet(2),
+                    I32GeU,
+                    BrIf(1),
+                    LocalGet(0),
+                    LocalGet(3),
+                    I32Add,
+                    Load8U(4),
+                    LocalGet(1),
+                    LocalGet(3),
+                    I32Add,
+                    Load8U(4),
+                    I32Ne,
+                    If(Empty),
+                    I32Const(0),
+                    Return,
+                    End,
+                    LocalGet(3),
+                    I32Const(1),
+                    I32Add,
+                    LocalSet(3),
+                    Br(0),
+                    End,
+                    End,
+                    I32Const(1),
+                ],
+            ),
+            Helper::StrCmp => func(
+                "str_cmp",
+                &[("a", I32), ("b", I32)],
+                &[I32],
+                &[("len", I32), ("i", I32), ("x", I32), ("y", I32)],
+                code![
+                    // the bytes both strings have are compared first
+                    LocalGet(0),
+                    Load(I32, 0),
+                    LocalGet(1),
+                    Load(I32, 0),
+                    LocalGet(0),
+                    Load(I32, 0),
+                    LocalGet(1),
+                    Load(I32, 0),
+                    I32LtU,
+                    Select,
+                    LocalSet(2),
+                    Block(Empty),
+                    Loop(Empty),
+                    LocalGet(3),
+                    LocalGet(2),
+                    I32GeU,
+                    BrIf(1),
+                    LocalGet(0),
+                    LocalGet(3),
+                    I32Add,
+                    Load8U(4),
+                    LocalSet(4),
+                    LocalGet(1),
+                    LocalGet(3),
+                    I32Add,
+                    Load8U(4),
+                    LocalSet(5),
+                    LocalGet(4),
+                    LocalGet(5),
+                    I32Ne,
+                    If(Empty),
+                    I32Const(-1),
+                    I32Const(1),
+                    LocalGet(4),
+                    LocalGet(5),
+                    I32LtU,
+                    Select,
+                    Return,
+                    End,
+                    LocalGet(3),
+                    I32Const(1),
+                    I32Add,
+                    LocalSet(3),
+                    Br(0),
+                    End,
+                    End,
+                    // and then the shorter string is the smaller one
+                    LocalGet(0),
+                    Load(I32, 0),
+                    LocalGet(1),
+                    Load(I32, 0),
+                    I32GtU,
+                    LocalGet(0),
+                    Load(I32, 0),
+                    LocalGet(1),
+                    Load(I32, 0),
+                    I32LtU,
+                    I32Sub,
+                ],
+            ),
+            Helper::PanicBegin => {
+                let mut body = Vec::new();
+                self.write_str(&mut body, STDERR, "error: ");
+                func("panic_begin", &[], &[], &[], body)
+            }
+            Helper::PanicEnd => {
+                let mut body = Vec::new();
+                self.write_str(&mut body, STDERR, "\n  --> ");
+                let write_str = self.call_helper(Helper::WriteStr);
+                body.extend([STDERR, LocalGet(0), write_str]);
+                self.write_str(&mut body, STDERR, "\n");
+                body.extend(code![I32Const(1), Call(EXIT), Unreachable]);
+                func("panic_end", &[("at", I32)], &[], &[], body)
+            }
+            Helper::Panic => {
+                let begin = self.call_helper(Helper::PanicBegin);
+                let write_str = self.call_helper(Helper::WriteStr);
+                let end = self.call_helper(Helper::PanicEnd);
+                func(
+                    "panic",
+                    &[("message", I32), ("at", I32)],
+                    &[],
+                    &[],
+                    code![begin, STDERR, LocalGet(0), write_str, LocalGet(1), end],
+                )
+            }
+            Helper::Arithmetic(op, ty) => self.arithmetic(op, ty),
+            Helper::Shift(left, ty, amount) => self.shift(left, ty, amount),
+            Helper::Fmod(ty) => fmod(ty),
+        }
+    }
+
+    fn arithmetic(&mut self, op: Arithmetic, ty: IntTy) -> Func {
+        let vt = val_type(&Type::Int(ty));
+        let wide = vt == I64;
+        let pick = |narrow: Op, wide_op: Op| Instr::from(if wide { wide_op } else { narrow });
+        let constant = |value: i128| match wide {
+            true => I64Const(value as i64),
+            false => I32Const(value as i32),
+        };
+        let (min, max) = (IntTy::min(ty), IntTy::max(ty));
+        let signed = ty.is_signed();
+
+        // the checks run in order, each with its message, before the result is computed
+        let mut setup = Vec::new();
+        let mut checks: Vec<(Vec<Instr>, &str)> = Vec::new();
+        let result;
+        let (name, at) = match op {
+            Arithmetic::Add => ("add", 2),
+            Arithmetic::Sub => ("sub", 2),
+            Arithmetic::Mul => ("mul", 2),
+            Arithmetic::Div => ("div", 2),
+            Arithmetic::Rem => ("rem", 2),
+            Arithmetic::Neg => ("neg", 1),
+        };
+        let mut locals = Vec::new();
+
+        match op {
+            Arithmetic::Add | Arithmetic::Sub | Arithmetic::Mul if !wide => {
+                // narrower types are computed in 64 bits, where the result always fits
+                let extend = if signed { I64ExtendI32S } else { I64ExtendI32U };
+                let (operation, message) = match op {
+                    Arithmetic::Add => (I64Add, "attempt to add with overflow"),
+                    Arithmetic::Sub => (I64Sub, "attempt to subtract with overflow"),
+                    _ => (I64Mul, "attempt to multiply with overflow"),
+                };
+                locals.push(("r", I64));
+                setup = code![
+                    LocalGet(0),
+                    extend,
+                    LocalGet(1),
+                    extend,
+                    operation,
+                    LocalSet(3)
+                ];
+                checks.push((
+                    code![
+                        LocalGet(3),
+                        I64Const(min as i64),
+                        I64LtS,
+                        LocalGet(3),
+                        I64Const(max as i64),
+                        I64GtS,
+                        I32Or,
+                    ],
+                    message,
+                ));
+                result = code![LocalGet(3), I32WrapI64];
+            }
+            Arithmetic::Add | Arithmetic::Sub | Arithmetic::Mul => {
+                let (operation, message) = match op {
+                    Arithmetic::Add => (I64Add, "attempt to add with overflow"),
+                    Arithmetic::Sub => (I64Sub, "attempt to subtract with overflow"),
+                    _ => (I64Mul, "attempt to multiply with overflow"),
+                };
+                locals.push(("r", I64));
+                setup = code![LocalGet(0), LocalGet(1), operation, LocalSet(3)];
+                let condition = match (op, signed) {
+                    // the sign of the result differs from those of both operands
+                    (Arithmetic::Add, true) => code![
+                        LocalGet(0),
+                        LocalGet(3),
+                        I64Xor,
+                        LocalGet(1),
+                        LocalGet(3),
+                        I64Xor,
+                        I64And,
+                        I64Const(0),
+                        I64LtS,
+                    ],
+                    (Arithmetic::Add, false) => code![LocalGet(3), LocalGet(0), I64LtU],
+                    // the operands' signs differ and the result's isn't the first one's
+                    (Arithmetic::Sub, true) => code![
+                        LocalGet(0),
+                        LocalGet(1),
+                        I64Xor,
+                        LocalGet(0),
+                        LocalGet(3),
+                        I64Xor,
+                        I64And,
+                        I64Const(0),
+                        I64LtS,
+                    ],
+                    (Arithmetic::Sub, false) => code![LocalGet(0), LocalGet(1), I64LtU],
+                    // dividing the result by one operand doesn't give back the other
+                    (_, true) => {
+                        checks.push((
+                            code![
+                                LocalGet(0),
+                                I64Const(-1),
+                                I64Eq,
+                                LocalGet(1),
+                                I64Const(i64::MIN),
+                                I64Eq,
+                                I32And,
+                            ],
+                            message,
+                        ));
+                        code![
+                            LocalGet(0),
+                            I64Eqz,
+                            If(Value(I32)),
+                            I32Const(0),
+                            Else,
+                            LocalGet(3),
+                            LocalGet(0),
+                            I64DivS,
+                            LocalGet(1),
+                            I64Ne,
+                            End,
+                        ]
+                    }
+                    (_, false) => code![
+                        LocalGet(0),
+                        I64Eqz,
+                        If(Value(I32)),
+                        I32Const(0),
+                        Else,
+                        LocalGet(3),
+                        LocalGet(0),
+                        I64DivU,
+                        LocalGet(1),
+                        I64Ne,
+                        End,
+                    ],
+                };
+                checks.push((condition, message));
+                result = code![LocalGet(3)];
+            }
+            Arithmetic::Div | Arithmetic::Rem => {
+                let (zero, overflow, operation) = match (op, signed) {
+                    (Arithmetic::Div, _) => (
+                        "attempt to divide by zero",
+                        "attempt to divide with overflow",
+                        pick(I32DivS, I64DivS),
+                    ),
+                    _ => (
+                        "attempt to calculate the remainder with a divisor of zero",
+                        "attempt to calculate the remainder with overflow",
+                        pick(I32RemS, I64RemS),
+                    ),
+                };
+                let operation = match (op, signed) {
+                    (_, true) => operation,
+                    (Arithmetic::Div, false) => pick(I32DivU, I64DivU),
+                    _ => pick(I32RemU, I64RemU),
+                };
+                checks.push((vec![LocalGet(1), pick(I32Eqz, I64Eqz)], zero));
+                if signed {
+                    checks.push((
+                        vec![
+                            LocalGet(0),
+                            constant(min),
+                            pick(I32Eq, I64Eq),
+                            LocalGet(1),
+                            constant(-1),
+                            pick(I32Eq, I64Eq),
+                            I32And.into(),
+                        ],
+                        overflow,
+                    ));
+                }
+                result = vec![LocalGet(0), LocalGet(1), operation];
+            }
+            Arithmetic::Neg => {
+                checks.push((
+                    vec![LocalGet(0), constant(min), pick(I32Eq, I64Eq)],
+                    "attempt to negate with overflow",
+                ));
+                result = vec![constant(0), LocalGet(0), pick(I32Sub, I64Sub)];
+            }
+        }
+
+        let panic = self.call_helper(Helper::Panic);
+        let mut body = setup;
+        for (condition, message) in checks {
+            body.extend(condition);
+            body.extend(code![
+                If(Empty),
+                I32Const(self.string(message)),
+                LocalGet(at),
+                panic,
+                End
+            ]);
+        }
+        body.extend(result);
+
+        let mut params = vec![("a", vt)];
+        if op != Arithmetic::Neg {
+            params.push(("b", vt));
+        }
+        params.push(("at", I32));
+        func(
+            &format!("{name}_{}", ty.name()),
+            &params,
+            &[vt],
+            &locals,
+            body,
+        )
+    }
+
+    fn shift(&mut self, left: bool, ty: IntTy, amount: IntTy) -> Func {
+        let vt = val_type(&Type::Int(ty));
+        let amount_vt = val_type(&Type::Int(amount));
+        let bits = ty.bits();
+
+        // negative amounts are out of range too, compared as unsigned
+        let mut body = match amount_vt {
+            I64 => code![LocalGet(1), I64Const(i64::from(bits)), I64GeU],
+            _ => code![LocalGet(1), I32Const(bits as i32), I32GeU],
+        };
+        body.push(If(Empty));
+        let begin = self.call_helper(Helper::PanicBegin);
+        body.push(begin);
+        self.write_str(&mut body, STDERR, "attempt to shift by `");
+        body.extend([STDERR, LocalGet(1)]);
+        if amount_vt == I32 {
+            body.push(match amount.is_signed() {
+                true => I64ExtendI32S.into(),
+                false => I64ExtendI32U.into(),
+            });
+        }
+        let write = match amount.is_signed() {
+            true => self.call_helper(Helper::WriteI64),
+            false => self.call_helper(Helper::WriteU64),
+        };
+        body.push(write);
+        self.write_str(&mut body, STDERR, "`, which is out of range");
+        let end = self.call_helper(Helper::PanicEnd);
+        body.extend([LocalGet(2), end, End]);
+
+        body.extend([LocalGet(0), LocalGet(1)]);
+        match (vt, amount_vt) {
+            (I32, I64) => body.push(I32WrapI64.into()),
+            (I64, I32) => body.push(I64ExtendI32U.into()),
+            _ => {}
+        }
+        let operation = match (left, ty.is_signed(), vt) {
+            (true, _, I64) => I64Shl,
+            (true, _, _) => I32Shl,
+            (false, true, I64) => I64ShrS,
+            (false, true, _) => I32ShrS,
+            (false, false, I64) => I64ShrU,
+            (false, false, _) => I32ShrU,
+        };
+        body.push(operation.into());
+        // the bits shifted past those of narrower types are dropped
+        if left {
+            body.extend(match ty {
+                IntTy::I8 => code![I32Extend8S],
+                IntTy::I16 => code![I32Extend16S],
+                IntTy::U8 => code![I32Const(0xff), I32And],
+                IntTy::U16 => code![I32Const(0xffff), I32And],
+                _ => Vec::new(),
+            });
+        }
+
+        let direction = if left { "shl" } else { "shr" };
+        func(
+            &format!("{direction}_{}_{}", ty.name(), amount.name()),
+            &[("a", vt), ("b", amount_vt), ("at", I32)],
+            &[vt],
+            &[],
+            body,
+        )
+    }
+}
+
+/// Stores the bytes of the character in local 1 encoded in `len` bytes in the scratch
+/// memory, and `len` in local 2.
+fn utf8(len: i32) -> Vec<Instr> {
+    let mut code = Vec::new();
+    for index in 0..len {
+        let shift = 6 * (len - 1 - index);
+        code.extend([I32Const(SCRATCH + index), LocalGet(1)]);
+        if shift > 0 {
+            code.extend(code![I32Const(shift), I32ShrU]);
+        }
+        let prefix = match (index, len) {
+            (0, 1) => None,
+            (0, 2) => Some(0xc0),
+            (0, 3) => Some(0xe0),
+            (0, _) => Some(0xf0),
+            _ => {
+                code.extend(code![I32Const(0x3f), I32And]);
+                Some(0x80)
+            }
+        };
+        if let Some(prefix) = prefix {
+            code.extend(code![I32Const(prefix), I32Or]);
+        }
+        code.push(Store8(0));
+    }
+    code.extend([I32Const(len), LocalSet(2)]);
+    code
+}
+
+/// The exact remainder of the division of the floats, found by subtracting the largest
+/// multiple of the divisor by a power of two until what remains is smaller than it. Each
+/// subtraction is exact, as the multiple is at least half of what it's subtracted from.
+fn fmod(ty: FloatTy) -> Func {
+    let vt = val_type(&Type::Float(ty));
+    let pick = |narrow: Op, wide: Op| Instr::from(if ty == FloatTy::F32 { narrow } else { wide });
+    let constant = |value: f64| match ty {
+        FloatTy::F32 => F32Const(value as f32),
+        FloatTy::F64 => F64Const(value),
+    };
+    let (abs, eq, ne, lt, gt) = (
+        pick(F32Abs, F64Abs),
+        pick(F32Eq, F64Eq),
+        pick(F32Ne, F64Ne),
+        pick(F32Lt, F64Lt),
+        pick(F32Gt, F64Gt),
+    );
+    let (mul, sub, copysign) = (
+        pick(F32Mul, F64Mul),
+        pick(F32Sub, F64Sub),
+        pick(F32Copysign, F64Copysign),
+    );
+    let body = code![
+        LocalGet(0),
+        abs,
+        LocalSet(2),
+        LocalGet(1),
+        abs,
+        LocalSet(3),
+        // infinite or NaN dividends and zero or NaN divisors have no remainder
+        LocalGet(2),
+        constant(f64::INFINITY),
+        eq,
+        LocalGet(2),
+        LocalGet(2),
+        ne,
+        I32Or,
+        LocalGet(3),
+        constant(0.0),
+        eq,
+        I32Or,
+        LocalGet(3),
+        LocalGet(3),
+        ne,
+        I32Or,
+        If(Empty),
+        constant(f64::NAN),
+        Return,
+        End,
+        Block(Empty),
+        Loop(Empty),
+        LocalGet(2),
+        LocalGet(3),
+        lt,
+        BrIf(1),
+        LocalGet(3),
+        LocalSet(4),
+        Block(Empty),
+        Loop(Empty),
+        LocalGet(4),
+        constant(2.0),
+        mul,
+        LocalGet(2),
+        gt,
+        BrIf(1),
+        LocalGet(4),
+        constant(2.0),
+        mul,
+        LocalSet(4),
+        Br(0),
+        End,
+        End,
+        LocalGet(2),
+        LocalGet(4),
+        sub,
+        LocalSet(2),
+        Br(0),
+        End,
+        End,
+        LocalGet(2),
+        LocalGet(0),
+        copysign,
+    ];
+    func(
+        &format!("fmod_{}", ty.name()),
+        &[("a", vt), ("b", vt)],
+        &[vt],
+        &[("r", vt), ("m", vt), ("t", vt)],
+        body,
+    )
+}
@@ -0,0 +1,171 @@
+//! The text format of modules, with the instructions written one per line.
+
+use std::fmt::Write;
+
+use super::{BlockType, Func, Instr, Module, ValType, HOST};
+
+pub(super) fn write(module: &Module) -> String {
+    let mut wat = "(module\n".to_string();
+    for import in &module.imports {
+        let _ = writeln!(
+            wat,
+            "  (import \"{HOST}\" \"{}\" (func ${}{}))",
+            import.name,
+            import.name,
+            signature(&import.ty.params, &[], &import.ty.results)
+        );
+    }
+    let _ = writeln!(wat, "  (memory (export \"memory\") {})", module.pages);
+    for global in &module.globals {
+        let _ = writeln!(
+            wat,
+            "  (global ${} (mut {}) (i32.const {}))",
+            global.name,
+            global.ty.name(),
+            global.init
+        );
+    }
+    for func in &module.functions {
+        function(&mut wat, module, func);
+    }
+    let _ = writeln!(
+        wat,
+        "  (export \"main\" (func ${}))",
+        module.function_name(module.main)
+    );
+    let _ = writeln!(
+        wat,
+        "  (data (i32.const {}) {})",
+        module.data_start,
+        string(&module.data)
+    );
+    wat.push_str(")\n");
+    wat
+}
+
+/// The parameters and results of a function, naming the parameters if `names` are given.
+fn signature(params: &[ValType], names: &[String], results: &[ValType]) -> String {
+    let mut text = String::new();
+    for (index, param) in params.iter().enumerate() {
+        match names.get(index) {
+            Some(name) => {
+                let _ = write!(text, " (param ${name} {})", param.name());
+            }
+            None => {
+                let _ = write!(text, " (param {})", param.name());
+            }
+        }
+    }
+    for result in results {
+        let _ = write!(text, " (result {})", result.name());
+    }
+    text
+}
+
+fn function(wat: &mut String, module: &Module, func: &Func) {
+    let _ = writeln!(
+        wat,
+        "  (func ${}{}",
+        func.name,
+        signature(&func.ty.params, &func.local_names, &func.ty.results)
+    );
+    let params = func.ty.params.len();
+    for (ty, name) in func.locals.iter().zip(&func.local_names[params..]) {
+        let _ = writeln!(wat, "    (local ${name} {})", ty.name());
+    }
+
+    let local = |index: u32| &func.local_names[index as usize];
+    let mut depth = 2;
+    for &instr in &func.body {
+        if matches!(instr, Instr::Else | Instr::End) {
+            depth -= 1;
+        }
+        let text = match instr {
+            Instr::Op(op) => op.name().to_string(),
+            Instr::Block(ty) => format!("block{}", block_type(ty)),
+            Instr::Loop(ty) => format!("loop{}", block_type(ty)),
+            Instr::If(ty) => format!("if{}", block_type(ty)),
+            Instr::Else => "else".to_string(),
+            Instr::End => "end".to_string(),
+            Instr::Br(depth) => format!("br {depth}"),
+            Instr::BrIf(depth) => format!("br_if {depth}"),
+            Instr::Call(index) => format!("call ${}", module.function_name(index)),
+            Instr::LocalGet(index) => format!("local.get ${}", local(index)),
+            Instr::LocalSet(index) => format!("local.set ${}", local(index)),
+            Instr::LocalTee(index) => format!("local.tee ${}", local(index)),
+            Instr::GlobalGet(index) => {
+                format!("global.get ${}", module.globals[index as usize].name)
+            }
+            Instr::GlobalSet(index) => {
+                format!("global.set ${}", module.globals[index as usize].name)
+            }
+            Instr::Load(ty, offset) => format!("{}.load{}", ty.name(), mem_arg(offset)),
+            Instr::Store(ty, offset) => format!("{}.store{}", ty.name(), mem_arg(offset)),
+            Instr::Load8U(offset) => format!("i32.load8_u{}", mem_arg(offset)),
+            Instr::Store8(offset) => format!("i32.store8{}", mem_arg(offset)),
+            Instr::MemorySize => "memory.size".to_string(),
+            Instr::MemoryGrow => "memory.grow".to_string(),
+            Instr::MemoryCopy => "memory.copy".to_string(),
+            Instr::I32Const(value) => format!("i32.const {value}"),
+            Instr::I64Const(value) => format!("i64.const {value}"),
+            Instr::F32Const(value) => format!(
+                "f32.const {}",
+                float(f64::from(value), format!("{value:?}"))
+            ),
+            Instr::F64Const(value) => format!("f64.const {}", float(value, format!("{value:?}"))),
+        };
+        let _ = writeln!(wat, "{}{text}", "  ".repeat(depth));
+        if matches!(
+            instr,
+            Instr::Block(_) | Instr::Loop(_) | Instr::If(_) | Instr::Else
+        ) {
+            depth += 1;
+        }
+    }
+    wat.truncate(wat.trim_end().len());
+    wat.push_str(")\n");
+}
+
+fn block_type(ty: BlockType) -> String {
+    match ty {
+        BlockType::Empty => String::new(),
+        BlockType::Value(ty) => format!(" (result {})", ty.name()),
+    }
+}
+
+fn mem_arg(offset: u32) -> String {
+    match offset {
+        0 => String::new(),
+        offset => format!(" offset={offset}"),
+    }
+}
+
+/// A float as the text format writes it, given its shortest decimal form.
+fn float(value: f64, decimal: String) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if value.is_infinite() {
+        if value < 0.0 { "-inf" } else { "inf" }.to_string()
+    } else {
+        decimal
+    }
+}
+
+/// A string literal of `bytes`, with those which aren't printable ASCII escaped in hex.
+fn string(bytes: &[u8]) -> String {
+    let mut text = String::from('"');
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => {
+                text.push('\\');
+                text.push(byte as char);
+            }
+            0x20..=0x7e => text.push(byte as char),
+            _ => {
+                let _ = write!(text, "\\{byte:02x}");
+            }
+        }
+    }
+    text.push('"');
+    text
+}
@@ -0,0 +1,136 @@
+//! Golden tests of the C backend: each program in `tests/programs` is compiled to C, which
+//! is compared with the `.c` file next to it. When a C compiler is installed, the C is also
+//! built and run, and what it prints is compared with the `.stdout` and `.stderr` files.
+//! Setting `LAMIA_BLESS` rewrites the expected files instead.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use db::Database;
+use source_map::Severity;
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/programs")
+}
+
+fn compile(name: &str) -> String {
+    let path = fixtures().join(format!("{name}.lamia"));
+    let text = fs::read_to_string(&path).unwrap();
+
+    let mut db = Database::new();
+    let root = db.add_file(format!("{name}.lamia"), text);
+    let (module_tree, mut diagnostics) = modules::build(&mut db, root);
+    diagnostics.extend(checker::check(&db, &module_tree));
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .map(|diagnostic| diagnostic.render(db.source_map()))
+        .collect::<String>();
+    assert!(errors.is_empty(), "{errors}");
+
+    let program = codegen::lower(&db, &module_tree).unwrap_or_else(|diagnostics| {
+        let errors = diagnostics.iter().map(|d| d.render(db.source_map()));
+        panic!("{}", errors.collect::<String>())
+    });
+    codegen::emit_c(&program, db.source_map())
+}
+
+/// Compares `actual` with the contents of the expected file, which is removed when
+/// blessing an empty output.
+fn expect(path: &Path, actual: &str) {
+    if env::var_os("LAMIA_BLESS").is_some() {
+        if actual.is_empty() {
+            let _ = fs::remove_file(path);
+        } else {
+            fs::write(path, actual).unwrap();
+        }
+        return;
+    }
+
+    let expected = fs::read_to_string(path).unwrap_or_default();
+    assert_eq!(expected, actual, "{} differs", path.display());
+}
+
+fn golden(name: &str) {
+    let c = compile(name);
+    expect(&fixtures().join(format!("{name}.c")), &c);
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    if Command::new(&cc).arg("--version").output().is_err() {
+        eprintln!("skipping running {name}: `{cc}` not found");
+        return;
+    }
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("c");
+    fs::create_dir_all(&dir).unwrap();
+    let source = dir.join(format!("{name}.c"));
+    let binary = dir.join(name);
+    fs::write(&source, &c).unwrap();
+
+    let output = Command::new(&cc)
+        .args(["-std=c99", "-Wall", "-Wextra", "-pedantic", "-Werror", "-o"])
+        .arg(&binary)
+        .arg(&source)
+        .arg("-lm")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = Command::new(&binary).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    expect(&fixtures().join(format!("{name}.stdout")), &stdout);
+    expect(&fixtures().join(format!("{name}.stderr")), &stderr);
+}
+
+#[test]
+fn fib() {
+    golden("fib");
+}
+
+#[test]
+fn enums() {
+    golden("enums");
+}
+
+#[test]
+fn structs() {
+    golden("structs");
+}
+
+#[test]
+fn generics() {
+    golden("generics");
+}
+
+#[test]
+fn integers() {
+    golden("integers");
+}
+
+#[test]
+fn floats() {
+    golden("floats");
+}
+
+#[test]
+fn strings() {
+    golden("strings");
+}
+
+#[test]
+fn overflow() {
+    golden("overflow");
+}
+
+#[test]
+fn assertion() {
+    golden("assertion");
+}
@@ -0,0 +1,596 @@
+//! Tests of the MIR: each program in `tests/programs` is lowered to MIR in SSA form, which
+//! is compared with the `.mir` file next to it, and which must pass the verifier before and
+//! after the conversion to SSA. The SSA form is then run by a small evaluator, and what it
+//! prints is compared with the `.stdout` and `.stderr` files the backends' tests check.
+//! Setting `LAMIA_BLESS` rewrites the `.mir` files instead.
+
+use std::{
+    cmp::Ordering,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use codegen::mir::{
+    self, BinaryOp, BlockId, EnumId, FloatTy, Form, FunctionId, IntTy, Literal, LocalId, Operand,
+    Output, Place, Program, Projection, Rvalue, StatementKind, TerminatorKind, Type, UnaryOp,
+};
+use db::Database;
+use source_map::{Severity, SourceMap};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/programs")
+}
+
+fn lower(name: &str) -> (codegen::tir::Program, Database) {
+    let path = fixtures().join(format!("{name}.lamia"));
+    let text = fs::read_to_string(&path).unwrap();
+
+    let mut db = Database::new();
+    let root = db.add_file(format!("{name}.lamia"), text);
+    let (module_tree, mut diagnostics) = modules::build(&mut db, root);
+    diagnostics.extend(checker::check(&db, &module_tree));
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .map(|diagnostic| diagnostic.render(db.source_map()))
+        .collect::<String>();
+    assert!(errors.is_empty(), "{errors}");
+
+    let program = codegen::lower(&db, &module_tree).unwrap_or_else(|diagnostics| {
+        let errors = diagnostics.iter().map(|d| d.render(db.source_map()));
+        panic!("{}", errors.collect::<String>())
+    });
+    (program, db)
+}
+
+/// The MIR of the program in SSA form, which is checked to be valid in both forms.
+fn ssa(name: &str) -> (Program, Database) {
+    let (program, db) = lower(name);
+    let locals = mir::build(&program);
+    assert_eq!(mir::verify(&locals), Ok(()));
+    let ssa = locals.into_ssa();
+    assert_eq!(mir::verify(&ssa), Ok(()));
+    (ssa, db)
+}
+
+fn expect(path: &Path, actual: &str) {
+    if env::var_os("LAMIA_BLESS").is_some() {
+        fs::write(path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(path).unwrap_or_default();
+    assert_eq!(expected, actual, "{} differs", path.display());
+}
+
+fn check(name: &str) {
+    let (program, db) = ssa(name);
+    let (tir, _) = lower(name);
+    assert_eq!(codegen::emit_mir(&tir), Ok(program.to_string()));
+    expect(
+        &fixtures().join(format!("{name}.mir")),
+        &program.to_string(),
+    );
+
+    let mut evaluator = Evaluator {
+        program: &program,
+        source_map: db.source_map(),
+        stdout: String::new(),
+        stderr: String::new(),
+    };
+    let main = program.main;
+    let ret = program.function(main).ret.clone();
+    match evaluator.call(main, Vec::new()) {
+        Ok(value) if ret != Type::Unit => {
+            let text = evaluator.display(&value);
+            evaluator.stdout.push_str(&text);
+            evaluator.stdout.push('\n');
+        }
+        Ok(_) | Err(Panicked) => {}
+    }
+
+    let read = |extension: &str| {
+        fs::read_to_string(fixtures().join(format!("{name}.{extension}"))).unwrap_or_default()
+    };
+    assert_eq!(read("stdout"), evaluator.stdout, "stdout of {name} differs");
+    assert_eq!(read("stderr"), evaluator.stderr, "stderr of {name} differs");
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Int(i128),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    Char(char),
+    Str(String),
+    Unit,
+    Tuple(Vec<Value>),
+    Variant(EnumId, usize, Vec<Value>),
+}
+
+impl Value {
+    fn bool(&self) -> bool {
+        match self {
+            Self::Bool(value) => *value,
+            _ => panic!("expected a boolean, found {self:?}"),
+        }
+    }
+
+    fn equals(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::F32(a), Self::F32(b)) => a == b,
+            (Self::F64(a), Self::F64(b)) => a == b,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Char(a), Self::Char(b)) => a == b,
+            (Self::Str(a), Self::Str(b)) => a == b,
+            (Self::Unit, Self::Unit) => true,
+            (Self::Tuple(a), Self::Tuple(b)) => a.iter().zip(b).all(|(a, b)| a.equals(b)),
+            (Self::Variant(_, a, a_fields), Self::Variant(_, b, b_fields)) => {
+                a == b && a_fields.iter().zip(b_fields).all(|(a, b)| a.equals(b))
+            }
+            _ => panic!("can't compare {self:?} with {other:?}"),
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Int(a), Self::Int(b)) => a.partial_cmp(b),
+            (Self::F32(a), Self::F32(b)) => a.partial_cmp(b),
+            (Self::F64(a), Self::F64(b)) => a.partial_cmp(b),
+            (Self::Char(a), Self::Char(b)) => a.partial_cmp(b),
+            (Self::Str(a), Self::Str(b)) => a.partial_cmp(b),
+            _ => panic!("can't order {self:?} and {other:?}"),
+        }
+    }
+}
+
+/// The program stopped with an error, which was written to stderr.
+#[derive(Debug)]
+struct Panicked;
+
+struct Evaluator<'a> {
+    program: &'a Program,
+    source_map: &'a SourceMap,
+    stdout: String,
+    stderr: String,
+}
+
+impl Evaluator<'_> {
+    fn call(&mut self, id: FunctionId, args: Vec<Value>) -> Result<Value, Panicked> {
+        let function = self.program.function(id);
+        let mut locals = vec![None; function.locals.len()];
+        for (param, arg) in function.params.iter().zip(args) {
+            locals[param.0 as usize] = Some(arg);
+        }
+
+        let mut previous = None;
+        let mut block_id = BlockId::ENTRY;
+        loop {
+            let block = function.block(block_id);
+            let values = block
+                .phis
+                .iter()
+                .map(|phi| {
+                    let (_, source) = phi
+                        .sources
+                        .iter()
+                        .find(|(block, _)| Some(*block) == previous)
+                        .expect("phis have a value for each predecessor");
+                    read(&locals, &Place::from(*source))
+                })
+                .collect::<Vec<_>>();
+            for (phi, value) in block.phis.iter().zip(values) {
+                locals[phi.local.0 as usize] = Some(value);
+            }
+
+            for statement in &block.statements {
+                let at = || self.source_map.location(statement.span);
+                match &statement.kind {
+                    StatementKind::Assign(local, rvalue) => {
+                        let ty = &function.local(*local).ty;
+                        let value = self.rvalue(&locals, rvalue, ty).map_err(|message| {
+                            self.panic(&message, &at());
+                            Panicked
+                        })?;
+                        locals[local.0 as usize] = Some(value);
+                    }
+                    StatementKind::Print(output, operands) => {
+                        let text = operands
+                            .iter()
+                            .map(|operand| self.display(&operand_value(&locals, operand)))
+                            .collect::<String>();
+                        let out = match output {
+                            Output::Stdout { .. } => &mut self.stdout,
+                            Output::Stderr => &mut self.stderr,
+                        };
+                        out.push_str(&text);
+                        if *output != (Output::Stdout { newline: false }) {
+                            out.push('\n');
+                        }
+                    }
+                }
+            }
+
+            previous = Some(block_id);
+            block_id = match &block.terminator.kind {
+                TerminatorKind::Goto(target) => *target,
+                TerminatorKind::Branch(condition, then, otherwise) => {
+                    match operand_value(&locals, condition).bool() {
+                        true => *then,
+                        false => *otherwise,
+                    }
+                }
+                TerminatorKind::Call {
+                    function,
+                    args,
+                    result,
+                    next,
+                } => {
+                    let args = args.iter().map(|arg| operand_value(&locals, arg)).collect();
+                    locals[result.0 as usize] = Some(self.call(*function, args)?);
+                    *next
+                }
+                TerminatorKind::Return(value) => return Ok(operand_value(&locals, value)),
+                TerminatorKind::Panic(operands) => {
+                    let message = operands
+                        .iter()
+                        .map(|operand| self.display(&operand_value(&locals, operand)))
+                        .collect::<String>();
+                    let at = self.source_map.location(block.terminator.span);
+                    self.panic(&message, &at);
+                    return Err(Panicked);
+                }
+                TerminatorKind::Unreachable => {
+                    panic!("reached `unreachable` in `{}`", function.name)
+                }
+            };
+        }
+    }
+
+    fn panic(&mut self, message: &str, at: &str) {
+        self.stderr
+            .push_str(&format!("error: {message}\n  --> {at}\n"));
+    }
+
+    /// The value of `rvalue`, assigned to a local of type `ty`, or the message of the
+    /// runtime error it causes.
+    fn rvalue(
+        &self,
+        locals: &[Option<Value>],
+        rvalue: &Rvalue,
+        ty: &Type,
+    ) -> Result<Value, String> {
+        let value = |operand| operand_value(locals, operand);
+        Ok(match rvalue {
+            Rvalue::Use(operand) => value(operand),
+            Rvalue::Unary(op, operand) => match (op, value(operand), ty) {
+                (UnaryOp::Not, Value::Bool(b), _) => Value::Bool(!b),
+                (UnaryOp::Neg, Value::F32(x), _) => Value::F32(-x),
+                (UnaryOp::Neg, Value::F64(x), _) => Value::F64(-x),
+                (UnaryOp::Neg, Value::Int(a), Type::Int(ty)) => {
+                    Value::Int(checked(-a, *ty, "attempt to negate with overflow")?)
+                }
+                (UnaryOp::BitNot, Value::Int(a), Type::Int(ty)) => match ty.is_signed() {
+                    true => Value::Int(!a),
+                    false => Value::Int(IntTy::max(*ty) - a),
+                },
+                (op, operand, _) => panic!("can't apply {op:?} to {operand:?}"),
+            },
+            Rvalue::Binary(op, lhs, rhs) => binary(*op, value(lhs), value(rhs), ty)?,
+            Rvalue::Tuple(elements) => Value::Tuple(elements.iter().map(value).collect()),
+            Rvalue::Variant(id, variant, fields) => {
+                Value::Variant(*id, *variant, fields.iter().map(value).collect())
+            }
+            Rvalue::Discriminant(place) => match read(locals, place) {
+                Value::Variant(_, variant, _) => Value::Int(variant as i128),
+                value => panic!("{value:?} has no discriminant"),
+            },
+        })
+    }
+
+    /// A value as `print` writes it.
+    fn display(&self, value: &Value) -> String {
+        match value {
+            Value::Int(value) => value.to_string(),
+            Value::F32(value) => format!("{value:?}"),
+            Value::F64(value) => format!("{value:?}"),
+            Value::Bool(value) => value.to_string(),
+            Value::Char(value) => value.to_string(),
+            Value::Str(value) => value.clone(),
+            Value::Unit => "()".to_string(),
+            Value::Tuple(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| self.display(element))
+                    .collect::<Vec<_>>();
+                match elements.as_slice() {
+                    [element] => format!("({element},)"),
+                    _ => format!("({})", elements.join(", ")),
+                }
+            }
+            Value::Variant(id, variant, fields) => {
+                let variant = &self.program.enum_def(*id).variants[*variant];
+                let fields = fields
+                    .iter()
+                    .map(|field| self.display(field))
+                    .collect::<Vec<_>>();
+                match &variant.field_names {
+                    _ if fields.is_empty() => variant.name.clone(),
+                    Some(names) => {
+                        let fields = names
+                            .iter()
+                            .zip(fields)
+                            .map(|(name, field)| format!("{name}: {field}"))
+                            .collect::<Vec<_>>();
+                        format!("{} {{ {} }}", variant.name, fields.join(", "))
+                    }
+                    None => format!("{}({})", variant.name, fields.join(", ")),
+                }
+            }
+        }
+    }
+}
+
+fn read(locals: &[Option<Value>], place: &Place) -> Value {
+    let mut value = locals[place.local.0 as usize]
+        .as_ref()
+        .unwrap_or_else(|| panic!("_{} is read before it's assigned", place.local.0));
+    for projection in &place.projections {
+        value = match (projection, value) {
+            (Projection::Field(index), Value::Tuple(elements)) => &elements[*index],
+            (Projection::VariantField { variant, field }, Value::Variant(_, actual, fields))
+                if variant == actual =>
+            {
+                &fields[*field]
+            }
+            (projection, value) => panic!("can't apply {projection:?} to {value:?}"),
+        };
+    }
+    value.clone()
+}
+
+fn operand_value(locals: &[Option<Value>], operand: &Operand) -> Value {
+    match operand {
+        Operand::Place(place) => read(locals, place),
+        Operand::Const(constant) => match (&constant.literal, &constant.ty) {
+            (Literal::Int(value), _) => Value::Int(*value),
+            (Literal::Float(value), Type::Float(FloatTy::F32)) => Value::F32(*value as f32),
+            (Literal::Float(value), _) => Value::F64(*value),
+            (Literal::Bool(value), _) => Value::Bool(*value),
+            (Literal::Char(value), _) => Value::Char(*value),
+            (Literal::Str(value), _) => Value::Str(value.clone()),
+            (Literal::Unit, _) => Value::Unit,
+        },
+    }
+}
+
+fn checked(value: i128, ty: IntTy, message: &str) -> Result<i128, String> {
+    match (IntTy::min(ty)..=IntTy::max(ty)).contains(&value) {
+        true => Ok(value),
+        false => Err(message.to_string()),
+    }
+}
+
+fn binary(op: BinaryOp, lhs: Value, rhs: Value, ty: &Type) -> Result<Value, String> {
+    let ordering = |expected: &[Ordering]| {
+        let ordering = lhs.compare(&rhs);
+        Value::Bool(ordering.is_some_and(|ordering| expected.contains(&ordering)))
+    };
+    Ok(match op {
+        BinaryOp::Eq => Value::Bool(lhs.equals(&rhs)),
+        BinaryOp::Ne => Value::Bool(!lhs.equals(&rhs)),
+        BinaryOp::Lt => ordering(&[Ordering::Less]),
+        BinaryOp::Le => ordering(&[Ordering::Less, Ordering::Equal]),
+        BinaryOp::Gt => ordering(&[Ordering::Greater]),
+        BinaryOp::Ge => ordering(&[Ordering::Greater, Ordering::Equal]),
+        _ => match (lhs, rhs, ty) {
+            (Value::F32(a), Value::F32(b), _) => Value::F32(match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div => a / b,
+                BinaryOp::Rem => a % b,
+                _ => panic!("can't apply {op:?} to floats"),
+            }),
+            (Value::F64(a), Value::F64(b), _) => Value::F64(match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div => a / b,
+                BinaryOp::Rem => a % b,
+                _ => panic!("can't apply {op:?} to floats"),
+            }),
+            (Value::Int(a), Value::Int(b), Type::Int(ty)) => Value::Int(int_binary(op, a, b, *ty)?),
+            (lhs, rhs, _) => panic!("can't apply {op:?} to {lhs:?} and {rhs:?}"),
+        },
+    })
+}
+
+fn int_binary(op: BinaryOp, a: i128, b: i128, ty: IntTy) -> Result<i128, String> {
+    let bits = ty.bits();
+    match op {
+        BinaryOp::Add => checked(a + b, ty, "attempt to add with overflow"),
+        BinaryOp::Sub => checked(a - b, ty, "attempt to subtract with overflow"),
+        BinaryOp::Mul => checked(a * b, ty, "attempt to multiply with overflow"),
+        BinaryOp::Div if b == 0 => Err("attempt to divide by zero".to_string()),
+        BinaryOp::Div => checked(a / b, ty, "attempt to divide with overflow"),
+        BinaryOp::Rem if b == 0 => {
+            Err("attempt to calculate the remainder with a divisor of zero".to_string())
+        }
+        BinaryOp::Rem if a == IntTy::min(ty) && b == -1 && ty.is_signed() => {
+            Err("attempt to calculate the remainder with overflow".to_string())
+        }
+        BinaryOp::Rem => Ok(a % b),
+        BinaryOp::BitAnd => Ok(a & b),
+        BinaryOp::BitOr => Ok(a | b),
+        BinaryOp::BitXor => Ok(a ^ b),
+        BinaryOp::Shl | BinaryOp::Shr if b < 0 || b >= i128::from(bits) => {
+            Err(format!("attempt to shift by `{b}`, which is out of range"))
+        }
+        BinaryOp::Shl => {
+            // the bits shifted out are lost, and the top bit left is the sign
+            let wrapped = ((a as u128) << b) & ((1u128 << bits) - 1);
+            let wrapped = wrapped as i128;
+            Ok(match ty.is_signed() && wrapped > IntTy::max(ty) {
+                true => wrapped - (1i128 << bits),
+                false => wrapped,
+            })
+        }
+        BinaryOp::Shr => Ok(a >> b),
+        _ => unreachable!("{op:?} isn't arithmetic"),
+    }
+}
+
+#[test]
+fn fib() {
+    check("fib");
+}
+
+#[test]
+fn enums() {
+    check("enums");
+}
+
+#[test]
+fn structs() {
+    check("structs");
+}
+
+#[test]
+fn generics() {
+    check("generics");
+}
+
+#[test]
+fn integers() {
+    check("integers");
+}
+
+#[test]
+fn floats() {
+    check("floats");
+}
+
+#[test]
+fn strings() {
+    check("strings");
+}
+
+#[test]
+fn overflow() {
+    check("overflow");
+}
+
+#[test]
+fn assertion() {
+    check("assertion");
+}
+
+/// Runs the verifier on the SSA form of `fib` after breaking it with `f`, returning the
+/// messages of the errors.
+fn broken(name: &str, f: impl FnOnce(&mut Program)) -> Vec<String> {
+    let (mut program, _) = ssa(name);
+    f(&mut program);
+    let errors = mir::verify(&program).expect_err("the program is invalid");
+    errors.iter().map(ToString::to_string).collect()
+}
+
+fn function_mut<'a>(program: &'a mut Program, name: &str) -> &'a mut mir::Function {
+    program
+        .functions
+        .iter_mut()
+        .find(|function| function.name == name)
+        .unwrap()
+}
+
+#[test]
+fn verify_assignments() {
+    let errors = broken("fib", |program| {
+        let fib = function_mut(program, "fib");
+        let statement = fib.blocks[0].statements[0].clone();
+        fib.blocks[0].statements.push(statement);
+    });
+    assert_eq!(errors, ["in `fib`, bb0: _1 is assigned more than once"]);
+
+    let errors = broken("fib", |program| {
+        let fib = function_mut(program, "fib");
+        let assignment = fib.blocks[2].statements.remove(0);
+        fib.blocks[3].statements.push(assignment);
+    });
+    assert_eq!(
+        errors,
+        ["in `fib`, bb2: _2 is read where its assignment in bb3 doesn't always come first"]
+    );
+
+    let errors = broken("fib", |program| {
+        let fib = function_mut(program, "fib");
+        fib.blocks[0].statements.clear();
+    });
+    assert_eq!(errors, ["in `fib`, bb0: _1 is read, but never assigned"]);
+}
+
+#[test]
+fn verify_reads_before_assignments_in_ssa_form() {
+    // the conversion to SSA form leaves reads of unassigned locals to the verifier
+    let (program, _) = lower("fib");
+    let mut locals = mir::build(&program);
+    function_mut(&mut locals, "fib").blocks[0]
+        .statements
+        .clear();
+    assert_eq!(mir::verify(&locals), Ok(()));
+    let errors = mir::verify(&locals.into_ssa()).unwrap_err();
+    assert_eq!(
+        errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        ["in `fib`, bb0: _1 is read, but never assigned"]
+    );
+}
+
+#[test]
+fn verify_phis() {
+    let errors = broken("enums", |program| {
+        let area = function_mut(program, "area");
+        let join = area.blocks.iter_mut().find(|block| !block.phis.is_empty());
+        join.unwrap().phis[0].sources.pop();
+    });
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("has no value for"), "{errors:?}");
+
+    let errors = broken("enums", |program| {
+        program.form = Form::Locals;
+    });
+    assert!(
+        errors
+            .iter()
+            .all(|error| error.ends_with("phis are only allowed in SSA form")),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn verify_structure_and_types() {
+    let errors = broken("fib", |program| {
+        let fib = function_mut(program, "fib");
+        fib.blocks[1].terminator.kind = TerminatorKind::Goto(BlockId(99));
+    });
+    assert_eq!(errors, ["in `fib`, bb1: bb99 doesn't exist"]);
+
+    let errors = broken("fib", |program| {
+        let fib = function_mut(program, "fib");
+        let TerminatorKind::Branch(condition, ..) = &mut fib.blocks[0].terminator.kind else {
+            panic!("`fib` starts with a branch");
+        };
+        *condition = Operand::from(LocalId(0));
+    });
+    assert_eq!(
+        errors,
+        ["in `fib`, bb0: the condition of the branch is of type `u64`, but `bool` is expected"]
+    );
+
+    let errors = broken("fib", |program| {
+        let fib = function_mut(program, "fib");
+        fib.blocks[1].terminator.kind = TerminatorKind::Goto(BlockId::ENTRY);
+    });
+    assert_eq!(errors, ["in `fib`: the entry block is the target of bb1"]);
+}
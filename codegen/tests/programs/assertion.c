@@ -0,0 +1,150 @@
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+
+typedef struct {
+    int32_t f0;
+    uint32_t f1;
+} tuple0;
+
+static void lm_panic_begin(void) {
+    fflush(stdout);
+    fputs("error: ", stderr);
+}
+
+static void lm_panic_end(const char *at) {
+    fprintf(stderr, "\n  --> %s\n", at);
+    exit(EXIT_FAILURE);
+}
+
+static void lm_panic(const char *message, const char *at) {
+    lm_panic_begin();
+    fputs(message, stderr);
+    lm_panic_end(at);
+}
+
+static int32_t lm_mul_i32(int32_t a, int32_t b, const char *at) {
+    if (a > 0 ? (b > 0 ? a > INT32_MAX / b : b < INT32_MIN / a) : (b > 0 ? a < INT32_MIN / b : a != 0 && b < INT32_MAX / a)) {
+        lm_panic("attempt to multiply with overflow", at);
+    }
+    return (int32_t)(a * b);
+}
+
+static void lm_print_char(FILE *out, uint32_t c) {
+    if (c < 0x80) {
+        fputc((int)c, out);
+    } else if (c < 0x800) {
+        fputc((int)(0xc0 | c >> 6), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else if (c < 0x10000) {
+        fputc((int)(0xe0 | c >> 12), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else {
+        fputc((int)(0xf0 | c >> 18), out);
+        fputc((int)(0x80 | (c >> 12 & 0x3f)), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    }
+}
+
+static bool lm_eq_tuple0(tuple0 a, tuple0 b) {
+    return a.f0 == b.f0 && a.f1 == b.f1;
+}
+
+static void lm_print_tuple0(FILE *out, tuple0 v) {
+    fputc('(', out);
+    fprintf(out, "%" PRId64, (int64_t)v.f0);
+    fputs(", ", out);
+    lm_print_char(out, v.f1);
+    fputs(")", out);
+}
+
+static lm_unit fn_main(void);
+static int32_t fn_square(int32_t);
+
+static lm_unit fn_main(void) {
+    int32_t left;
+    int32_t right;
+    tuple0 left_1;
+    tuple0 right_1;
+    int32_t t0;
+    bool t1;
+    int32_t t2;
+    bool t3;
+    int32_t t4;
+    tuple0 t5;
+    tuple0 t6;
+    bool t7;
+
+    t0 = fn_square(3);
+    t1 = (t0 == 9);
+    if (!t1) goto bb9;
+    t2 = fn_square(4);
+    left = t2;
+    right = 16;
+    t3 = (left == right);
+    if (!t3) goto bb8;
+    fputs("so far so good", stdout);
+    fputc('\n', stdout);
+    t4 = fn_square(2);
+    t5 = ((tuple0){t4, 'x'});
+    left_1 = t5;
+    t6 = ((tuple0){5, 'x'});
+    right_1 = t6;
+    t7 = (lm_eq_tuple0(left_1, right_1));
+    if (!t7) goto bb7;
+    return 0;
+bb7:
+    lm_panic_begin();
+    fputs("assertion `left == right` failed", stderr);
+    fputs(": ", stderr);
+    fputs("square of ", stderr);
+    fprintf(stderr, "%" PRId64, (int64_t)2);
+    fputs("\n  left: ", stderr);
+    lm_print_tuple0(stderr, left_1);
+    fputs("\n right: ", stderr);
+    lm_print_tuple0(stderr, right_1);
+    lm_panic_end("assertion.lamia:9:5");
+bb8:
+    lm_panic_begin();
+    fputs("assertion `left == right` failed", stderr);
+    fputs(": ", stderr);
+    fputs("squares", stderr);
+    fputs("\n  left: ", stderr);
+    fprintf(stderr, "%" PRId64, (int64_t)left);
+    fputs("\n right: ", stderr);
+    fprintf(stderr, "%" PRId64, (int64_t)right);
+    lm_panic_end("assertion.lamia:7:5");
+bb9:
+    lm_panic_begin();
+    fputs("assertion failed: `square(3) == 9`", stderr);
+    lm_panic_end("assertion.lamia:6:5");
+    abort();
+}
+
+static int32_t fn_square(int32_t n) {
+    int32_t t0;
+
+    t0 = lm_mul_i32(n, n, "assertion.lamia:2:5");
+    return t0;
+}
+
+int main(void) {
+    fn_main();
+    return 0;
+}
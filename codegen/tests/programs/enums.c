@@ -0,0 +1,470 @@
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Circle
+            double f0;
+        } v0;
+        struct { // Rect
+            double f0;
+            double f1;
+        } v1;
+    } as;
+} enum_Shape;
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Num
+            int64_t f0;
+        } v0;
+        struct { // Add
+            int64_t f0;
+            int64_t f1;
+        } v1;
+        struct { // Neg
+            int64_t f0;
+        } v2;
+    } as;
+} enum_Expr;
+
+typedef struct {
+    bool f0;
+    uint32_t f1;
+} tuple0;
+
+typedef struct {
+    tuple0 f0;
+    int64_t f1;
+} tuple1;
+
+typedef struct {
+    int64_t f0;
+    tuple0 f1;
+} tuple2;
+
+typedef struct {
+    enum_Shape f0;
+    enum_Shape f1;
+    enum_Shape f2;
+} tuple3;
+
+typedef struct {
+    int64_t f0;
+} tuple4;
+
+static void lm_panic_begin(void) {
+    fflush(stdout);
+    fputs("error: ", stderr);
+}
+
+static void lm_panic_end(const char *at) {
+    fprintf(stderr, "\n  --> %s\n", at);
+    exit(EXIT_FAILURE);
+}
+
+static void lm_panic(const char *message, const char *at) {
+    lm_panic_begin();
+    fputs(message, stderr);
+    lm_panic_end(at);
+}
+
+static int64_t lm_add_i64(int64_t a, int64_t b, const char *at) {
+    if ((b > 0 && a > INT64_MAX - b) || (b < 0 && a < INT64_MIN - b)) {
+        lm_panic("attempt to add with overflow", at);
+    }
+    return (int64_t)(a + b);
+}
+
+static int64_t lm_neg_i64(int64_t a, const char *at) {
+    if (a == INT64_MIN) {
+        lm_panic("attempt to negate with overflow", at);
+    }
+    return (int64_t)(-a);
+}
+
+static void lm_print_char(FILE *out, uint32_t c) {
+    if (c < 0x80) {
+        fputc((int)c, out);
+    } else if (c < 0x800) {
+        fputc((int)(0xc0 | c >> 6), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else if (c < 0x10000) {
+        fputc((int)(0xe0 | c >> 12), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else {
+        fputc((int)(0xf0 | c >> 18), out);
+        fputc((int)(0x80 | (c >> 12 & 0x3f)), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    }
+}
+
+static void lm_print_float(FILE *out, const char *formatted) {
+    char digits[32];
+    int count = 0;
+    int exponent;
+    int i;
+    if (*formatted == '-') {
+        fputc('-', out);
+        formatted++;
+    }
+    for (; *formatted != 'e'; formatted++) {
+        if (*formatted != '.') {
+            digits[count++] = *formatted;
+        }
+    }
+    exponent = atoi(formatted + 1);
+    while (count > 1 && digits[count - 1] == '0') {
+        count--;
+    }
+
+    if (exponent < -4 || exponent >= 16) {
+        fputc(digits[0], out);
+        if (count > 1) {
+            fputc('.', out);
+            fwrite(digits + 1, 1, (size_t)(count - 1), out);
+        }
+        fprintf(out, "e%d", exponent);
+    } else if (exponent < 0) {
+        fputs("0.", out);
+        for (i = -1; i > exponent; i--) {
+            fputc('0', out);
+        }
+        fwrite(digits, 1, (size_t)count, out);
+    } else {
+        for (i = 0; i <= exponent; i++) {
+            fputc(i < count ? digits[i] : '0', out);
+        }
+        fputc('.', out);
+        if (count > exponent + 1) {
+            fwrite(digits + exponent + 1, 1, (size_t)(count - exponent - 1), out);
+        } else {
+            fputc('0', out);
+        }
+    }
+}
+
+static void lm_print_f64(FILE *out, double x) {
+    char formatted[32];
+    int precision;
+    if (isnan(x)) {
+        fputs("NaN", out);
+        return;
+    }
+    if (isinf(x)) {
+        fputs(x < 0 ? "-inf" : "inf", out);
+        return;
+    }
+    if (x == 0) {
+        fputs(signbit(x) ? "-0.0" : "0.0", out);
+        return;
+    }
+    for (precision = 0; precision < 17; precision++) {
+        snprintf(formatted, sizeof formatted, "%.*e", precision, (double)x);
+        if ((double)strtod(formatted, NULL) == x) {
+            break;
+        }
+    }
+    lm_print_float(out, formatted);
+}
+
+static bool lm_eq_enum_Shape(enum_Shape a, enum_Shape b) {
+    if (a.tag != b.tag) {
+        return false;
+    }
+    switch (a.tag) {
+    case 0:
+        return a.as.v0.f0 == b.as.v0.f0;
+    case 1:
+        return a.as.v1.f0 == b.as.v1.f0 && a.as.v1.f1 == b.as.v1.f1;
+    default:
+        return true;
+    }
+}
+
+static void lm_print_enum_Shape(FILE *out, enum_Shape v) {
+    switch (v.tag) {
+    case 0:
+        fputs("Circle", out);
+        fputc('(', out);
+        lm_print_f64(out, v.as.v0.f0);
+        fputc(')', out);
+        break;
+    case 1:
+        fputs("Rect", out);
+        fputs(" { width: ", out);
+        lm_print_f64(out, v.as.v1.f0);
+        fputs(", height: ", out);
+        lm_print_f64(out, v.as.v1.f1);
+        fputs(" }", out);
+        break;
+    case 2:
+        fputs("Point", out);
+        break;
+    }
+}
+
+static void lm_print_tuple0(FILE *out, tuple0 v) {
+    fputc('(', out);
+    fputs(v.f0 ? "true" : "false", out);
+    fputs(", ", out);
+    lm_print_char(out, v.f1);
+    fputs(")", out);
+}
+
+static void lm_print_tuple1(FILE *out, tuple1 v) {
+    fputc('(', out);
+    lm_print_tuple0(out, v.f0);
+    fputs(", ", out);
+    fprintf(out, "%" PRId64, (int64_t)v.f1);
+    fputs(")", out);
+}
+
+static void lm_print_tuple3(FILE *out, tuple3 v) {
+    fputc('(', out);
+    lm_print_enum_Shape(out, v.f0);
+    fputs(", ", out);
+    lm_print_enum_Shape(out, v.f1);
+    fputs(", ", out);
+    lm_print_enum_Shape(out, v.f2);
+    fputs(")", out);
+}
+
+static void lm_print_tuple4(FILE *out, tuple4 v) {
+    fputc('(', out);
+    fprintf(out, "%" PRId64, (int64_t)v.f0);
+    fputs(",)", out);
+}
+
+static lm_unit fn_main(void);
+static double fn_area(enum_Shape);
+static int64_t fn_eval(enum_Expr);
+static tuple1 fn_swap(tuple2);
+
+static lm_unit fn_main(void) {
+    tuple3 shapes;
+    tuple2 pair;
+    enum_Shape t0;
+    enum_Shape t1;
+    enum_Shape t2;
+    tuple3 t3;
+    double t4;
+    double t5;
+    double t6;
+    double t7;
+    double t8;
+    enum_Shape t9;
+    bool t10;
+    enum_Shape t11;
+    bool t12;
+    enum_Expr t13;
+    int64_t t14;
+    enum_Expr t15;
+    int64_t t16;
+    enum_Expr t17;
+    int64_t t18;
+    enum_Expr t19;
+    int64_t t20;
+    tuple0 t21;
+    tuple2 t22;
+    tuple0 t23;
+    int64_t t24;
+    tuple1 t25;
+    tuple4 t26;
+
+    t0 = ((enum_Shape){.tag = 0, .as.v0 = {1.5}});
+    t1 = ((enum_Shape){.tag = 1, .as.v1 = {4.0, 2.0}});
+    t2 = ((enum_Shape){.tag = 2});
+    t3 = ((tuple3){t0, t1, t2});
+    shapes = t3;
+    lm_print_tuple3(stdout, shapes);
+    fputc('\n', stdout);
+    t4 = fn_area(shapes.f0);
+    t5 = fn_area(shapes.f1);
+    t6 = (t4 + t5);
+    t7 = fn_area(shapes.f2);
+    t8 = (t6 + t7);
+    lm_print_f64(stdout, t8);
+    fputc('\n', stdout);
+    t9 = ((enum_Shape){.tag = 1, .as.v1 = {4.0, 2.0}});
+    t10 = (lm_eq_enum_Shape(shapes.f1, t9));
+    fputs(t10 ? "true" : "false", stdout);
+    fputc('\n', stdout);
+    t11 = ((enum_Shape){.tag = 0, .as.v0 = {1.5}});
+    t12 = !(lm_eq_enum_Shape(shapes.f0, t11));
+    fputs(t12 ? "true" : "false", stdout);
+    fputc('\n', stdout);
+    t13 = ((enum_Expr){.tag = 0, .as.v0 = {4}});
+    t14 = fn_eval(t13);
+    t15 = ((enum_Expr){.tag = 1, .as.v1 = {2, 3}});
+    t16 = fn_eval(t15);
+    t17 = ((enum_Expr){.tag = 2, .as.v2 = {7}});
+    t18 = fn_eval(t17);
+    t19 = ((enum_Expr){.tag = 2, .as.v2 = {0}});
+    t20 = fn_eval(t19);
+    fprintf(stdout, "%" PRId64, (int64_t)t14);
+    fputs(" ", stdout);
+    fprintf(stdout, "%" PRId64, (int64_t)t16);
+    fputs(" ", stdout);
+    fprintf(stdout, "%" PRId64, (int64_t)t18);
+    fputs(" ", stdout);
+    fprintf(stdout, "%" PRId64, (int64_t)t20);
+    fputc('\n', stdout);
+    t21 = ((tuple0){true, 'x'});
+    t22 = ((tuple2){1, t21});
+    pair = t22;
+    t23 = ((tuple0){pair.f1.f0, 'y'});
+    pair = ((tuple2){pair.f0, t23});
+    t24 = lm_add_i64(pair.f0, 41, "enums.lamia:46:5");
+    pair = ((tuple2){t24, pair.f1});
+    t25 = fn_swap(pair);
+    lm_print_tuple1(stdout, t25);
+    fputc('\n', stdout);
+    t26 = ((tuple4){pair.f0});
+    lm_print_tuple4(stdout, t26);
+    fputc('\n', stdout);
+    return 0;
+}
+
+static double fn_area(enum_Shape shape) {
+    double r;
+    double width;
+    double height;
+    double t0;
+    size_t t1;
+    bool t2;
+    double t3;
+    double t4;
+    size_t t5;
+    bool t6;
+    double t7;
+    size_t t8;
+    bool t9;
+
+    t1 = shape.tag;
+    t2 = (t1 == 0);
+    if (!t2) goto bb2;
+    r = shape.as.v0.f0;
+    t3 = (3.0 * r);
+    t4 = (t3 * r);
+    t0 = t4;
+    goto bb6;
+bb2:
+    t5 = shape.tag;
+    t6 = (t5 == 1);
+    if (!t6) goto bb4;
+    width = shape.as.v1.f0;
+    height = shape.as.v1.f1;
+    t7 = (width * height);
+    t0 = t7;
+    goto bb6;
+bb4:
+    t8 = shape.tag;
+    t9 = (t8 == 2);
+    if (!t9) goto bb7;
+    t0 = 0.0;
+bb6:
+    return t0;
+bb7:
+    abort();
+}
+
+static int64_t fn_eval(enum_Expr expr) {
+    int64_t n;
+    int64_t n_1;
+    int64_t a;
+    int64_t b;
+    int64_t n_2;
+    int64_t t0;
+    size_t t1;
+    bool t2;
+    size_t t3;
+    bool t4;
+    bool t5;
+    size_t t6;
+    bool t7;
+    size_t t8;
+    bool t9;
+    int64_t t10;
+    size_t t11;
+    bool t12;
+    int64_t t13;
+
+    t1 = expr.tag;
+    t2 = (t1 == 0);
+    if (!t2) goto bb2;
+    n = expr.as.v0.f0;
+    goto bb4;
+bb2:
+    t3 = expr.tag;
+    t4 = (t3 == 2);
+    if (!t4) goto bb6;
+    n = expr.as.v2.f0;
+bb4:
+    t5 = (n == 0);
+    if (!t5) goto bb6;
+    t0 = 0;
+    goto bb12;
+bb6:
+    t6 = expr.tag;
+    t7 = (t6 == 0);
+    if (!t7) goto bb8;
+    n_1 = expr.as.v0.f0;
+    t0 = n_1;
+    goto bb12;
+bb8:
+    t8 = expr.tag;
+    t9 = (t8 == 1);
+    if (!t9) goto bb10;
+    a = expr.as.v1.f0;
+    b = expr.as.v1.f1;
+    t10 = lm_add_i64(a, b, "enums.lamia:25:28");
+    t0 = t10;
+    goto bb12;
+bb10:
+    t11 = expr.tag;
+    t12 = (t11 == 2);
+    if (!t12) goto bb13;
+    n_2 = expr.as.v2.f0;
+    t13 = lm_neg_i64(n_2, "enums.lamia:26:25");
+    t0 = t13;
+bb12:
+    return t0;
+bb13:
+    abort();
+}
+
+static tuple1 fn_swap(tuple2 pair) {
+    int64_t n;
+    tuple0 inner;
+    tuple1 t0;
+
+    n = pair.f0;
+    inner = pair.f1;
+    t0 = ((tuple1){inner, n});
+    return t0;
+}
+
+int main(void) {
+    fn_main();
+    return 0;
+}
@@ -0,0 +1,106 @@
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+
+static void lm_panic_begin(void) {
+    fflush(stdout);
+    fputs("error: ", stderr);
+}
+
+static void lm_panic_end(const char *at) {
+    fprintf(stderr, "\n  --> %s\n", at);
+    exit(EXIT_FAILURE);
+}
+
+static void lm_panic(const char *message, const char *at) {
+    lm_panic_begin();
+    fputs(message, stderr);
+    lm_panic_end(at);
+}
+
+static uint64_t lm_add_u64(uint64_t a, uint64_t b, const char *at) {
+    if (a > UINT64_MAX - b) {
+        lm_panic("attempt to add with overflow", at);
+    }
+    return (uint64_t)(a + b);
+}
+
+static uint64_t lm_sub_u64(uint64_t a, uint64_t b, const char *at) {
+    if (a < b) {
+        lm_panic("attempt to subtract with overflow", at);
+    }
+    return (uint64_t)(a - b);
+}
+
+static uint64_t fn_main(void);
+static lm_unit fn_print_fibs(uint64_t, uint64_t);
+static uint64_t fn_fib(uint64_t);
+
+static uint64_t fn_main(void) {
+    uint64_t t1;
+
+    fn_print_fibs(0, 10);
+    t1 = fn_fib(30);
+    return t1;
+}
+
+static lm_unit fn_print_fibs(uint64_t i, uint64_t n) {
+    bool t0;
+    uint64_t t1;
+    uint64_t t2;
+    lm_unit t3;
+
+    t0 = (i == n);
+    if (!t0) goto bb2;
+    return 0;
+bb2:
+    t1 = fn_fib(i);
+    fputs("fib(", stdout);
+    fprintf(stdout, "%" PRIu64, (uint64_t)i);
+    fputs(") = ", stdout);
+    fprintf(stdout, "%" PRIu64, (uint64_t)t1);
+    fputc('\n', stdout);
+    t2 = lm_add_u64(i, 1, "fib.lamia:14:16");
+    t3 = fn_print_fibs(t2, n);
+    return t3;
+}
+
+static uint64_t fn_fib(uint64_t n) {
+    bool t0;
+    uint64_t t1;
+    uint64_t t2;
+    uint64_t t3;
+    uint64_t t4;
+    uint64_t t5;
+
+    t0 = (n < 2);
+    if (!t0) goto bb2;
+    return n;
+bb2:
+    t1 = lm_sub_u64(n, 1, "fib.lamia:5:9");
+    t2 = fn_fib(t1);
+    t3 = lm_sub_u64(n, 2, "fib.lamia:5:22");
+    t4 = fn_fib(t3);
+    t5 = lm_add_u64(t2, t4, "fib.lamia:5:5");
+    return t5;
+}
+
+int main(void) {
+    fprintf(stdout, "%" PRIu64, (uint64_t)fn_main());
+    fputc('\n', stdout);
+    return 0;
+}
@@ -0,0 +1,183 @@
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+
+typedef struct {
+    double f0;
+    double f1;
+} tuple0;
+
+static void lm_print_float(FILE *out, const char *formatted) {
+    char digits[32];
+    int count = 0;
+    int exponent;
+    int i;
+    if (*formatted == '-') {
+        fputc('-', out);
+        formatted++;
+    }
+    for (; *formatted != 'e'; formatted++) {
+        if (*formatted != '.') {
+            digits[count++] = *formatted;
+        }
+    }
+    exponent = atoi(formatted + 1);
+    while (count > 1 && digits[count - 1] == '0') {
+        count--;
+    }
+
+    if (exponent < -4 || exponent >= 16) {
+        fputc(digits[0], out);
+        if (count > 1) {
+            fputc('.', out);
+            fwrite(digits + 1, 1, (size_t)(count - 1), out);
+        }
+        fprintf(out, "e%d", exponent);
+    } else if (exponent < 0) {
+        fputs("0.", out);
+        for (i = -1; i > exponent; i--) {
+            fputc('0', out);
+        }
+        fwrite(digits, 1, (size_t)count, out);
+    } else {
+        for (i = 0; i <= exponent; i++) {
+            fputc(i < count ? digits[i] : '0', out);
+        }
+        fputc('.', out);
+        if (count > exponent + 1) {
+            fwrite(digits + exponent + 1, 1, (size_t)(count - exponent - 1), out);
+        } else {
+            fputc('0', out);
+        }
+    }
+}
+
+static void lm_print_f32(FILE *out, float x) {
+    char formatted[32];
+    int precision;
+    if (isnan(x)) {
+        fputs("NaN", out);
+        return;
+    }
+    if (isinf(x)) {
+        fputs(x < 0 ? "-inf" : "inf", out);
+        return;
+    }
+    if (x == 0) {
+        fputs(signbit(x) ? "-0.0" : "0.0", out);
+        return;
+    }
+    for (precision = 0; precision < 9; precision++) {
+        snprintf(formatted, sizeof formatted, "%.*e", precision, (double)x);
+        if ((float)strtod(formatted, NULL) == x) {
+            break;
+        }
+    }
+    lm_print_float(out, formatted);
+}
+
+static void lm_print_f64(FILE *out, double x) {
+    char formatted[32];
+    int precision;
+    if (isnan(x)) {
+        fputs("NaN", out);
+        return;
+    }
+    if (isinf(x)) {
+        fputs(x < 0 ? "-inf" : "inf", out);
+        return;
+    }
+    if (x == 0) {
+        fputs(signbit(x) ? "-0.0" : "0.0", out);
+        return;
+    }
+    for (precision = 0; precision < 17; precision++) {
+        snprintf(formatted, sizeof formatted, "%.*e", precision, (double)x);
+        if ((double)strtod(formatted, NULL) == x) {
+            break;
+        }
+    }
+    lm_print_float(out, formatted);
+}
+
+static void lm_print_tuple0(FILE *out, tuple0 v) {
+    fputc('(', out);
+    lm_print_f64(out, v.f0);
+    fputs(", ", out);
+    lm_print_f64(out, v.f1);
+    fputs(")", out);
+}
+
+static lm_unit fn_main(void);
+
+static lm_unit fn_main(void) {
+    double t0;
+    double t1;
+    double t2;
+    double t3;
+    double t4;
+    double t5;
+    float t6;
+    tuple0 t7;
+
+    lm_print_f64(stdout, 1.0);
+    fputc('\n', stdout);
+    t0 = (0.1 + 0.2);
+    lm_print_f64(stdout, t0);
+    fputc('\n', stdout);
+    t1 = (1.0 / 3.0);
+    lm_print_f64(stdout, t1);
+    fputc('\n', stdout);
+    lm_print_f64(stdout, (-2.5));
+    fputc('\n', stdout);
+    lm_print_f64(stdout, 1e16);
+    fputc('\n', stdout);
+    lm_print_f64(stdout, 1500000000000000.0);
+    fputc('\n', stdout);
+    lm_print_f64(stdout, 0.0001);
+    fputc('\n', stdout);
+    lm_print_f64(stdout, 1e-5);
+    fputc('\n', stdout);
+    lm_print_f64(stdout, (-0.0));
+    fputc('\n', stdout);
+    t2 = (1.0 / 0.0);
+    lm_print_f64(stdout, t2);
+    fputc('\n', stdout);
+    t3 = ((-1.0) / 0.0);
+    lm_print_f64(stdout, t3);
+    fputc('\n', stdout);
+    t4 = (0.0 / 0.0);
+    lm_print_f64(stdout, t4);
+    fputc('\n', stdout);
+    t5 = fmod(7.5, 2.0);
+    lm_print_f64(stdout, t5);
+    fputc('\n', stdout);
+    t6 = (2.5f * 1.1f);
+    lm_print_f32(stdout, t6);
+    fputc('\n', stdout);
+    lm_print_f32(stdout, 0.1f);
+    fputc('\n', stdout);
+    t7 = ((tuple0){1.0, (-1.5e-7)});
+    lm_print_tuple0(stdout, t7);
+    fputc('\n', stdout);
+    return 0;
+}
+
+int main(void) {
+    fn_main();
+    return 0;
+}
@@ -0,0 +1,368 @@
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Some
+            uint8_t f0;
+        } v0;
+    } as;
+} enum_Option_u8_;
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Some
+            uint32_t f0;
+        } v0;
+    } as;
+} enum_Option_char_;
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Pair
+            bool f0;
+            double f1;
+        } v0;
+    } as;
+} enum_Pair_bool__f64_;
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Pair
+            double f0;
+            bool f1;
+        } v0;
+    } as;
+} enum_Pair_f64__bool_;
+
+typedef struct {
+    int32_t f0;
+    int32_t f1;
+} tuple0;
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Some
+            int64_t f0;
+        } v0;
+    } as;
+} enum_Option_i64_;
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Pair
+            lm_str f0;
+            enum_Option_i64_ f1;
+        } v0;
+    } as;
+} enum_Pair_String__Option_i64__;
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Some
+            enum_Pair_String__Option_i64__ f0;
+        } v0;
+    } as;
+} enum_Option_Pair_String__Option_i64___;
+
+static void lm_panic_begin(void) {
+    fflush(stdout);
+    fputs("error: ", stderr);
+}
+
+static void lm_panic_end(const char *at) {
+    fprintf(stderr, "\n  --> %s\n", at);
+    exit(EXIT_FAILURE);
+}
+
+static void lm_panic(const char *message, const char *at) {
+    lm_panic_begin();
+    fputs(message, stderr);
+    lm_panic_end(at);
+}
+
+static uint8_t lm_add_u8(uint8_t a, uint8_t b, const char *at) {
+    if (a > UINT8_MAX - b) {
+        lm_panic("attempt to add with overflow", at);
+    }
+    return (uint8_t)(a + b);
+}
+
+static void lm_print_char(FILE *out, uint32_t c) {
+    if (c < 0x80) {
+        fputc((int)c, out);
+    } else if (c < 0x800) {
+        fputc((int)(0xc0 | c >> 6), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else if (c < 0x10000) {
+        fputc((int)(0xe0 | c >> 12), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else {
+        fputc((int)(0xf0 | c >> 18), out);
+        fputc((int)(0x80 | (c >> 12 & 0x3f)), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    }
+}
+
+static void lm_print_str(FILE *out, lm_str s) {
+    fwrite(s.ptr, 1, s.len, out);
+}
+
+static void lm_print_float(FILE *out, const char *formatted) {
+    char digits[32];
+    int count = 0;
+    int exponent;
+    int i;
+    if (*formatted == '-') {
+        fputc('-', out);
+        formatted++;
+    }
+    for (; *formatted != 'e'; formatted++) {
+        if (*formatted != '.') {
+            digits[count++] = *formatted;
+        }
+    }
+    exponent = atoi(formatted + 1);
+    while (count > 1 && digits[count - 1] == '0') {
+        count--;
+    }
+
+    if (exponent < -4 || exponent >= 16) {
+        fputc(digits[0], out);
+        if (count > 1) {
+            fputc('.', out);
+            fwrite(digits + 1, 1, (size_t)(count - 1), out);
+        }
+        fprintf(out, "e%d", exponent);
+    } else if (exponent < 0) {
+        fputs("0.", out);
+        for (i = -1; i > exponent; i--) {
+            fputc('0', out);
+        }
+        fwrite(digits, 1, (size_t)count, out);
+    } else {
+        for (i = 0; i <= exponent; i++) {
+            fputc(i < count ? digits[i] : '0', out);
+        }
+        fputc('.', out);
+        if (count > exponent + 1) {
+            fwrite(digits + exponent + 1, 1, (size_t)(count - exponent - 1), out);
+        } else {
+            fputc('0', out);
+        }
+    }
+}
+
+static void lm_print_f64(FILE *out, double x) {
+    char formatted[32];
+    int precision;
+    if (isnan(x)) {
+        fputs("NaN", out);
+        return;
+    }
+    if (isinf(x)) {
+        fputs(x < 0 ? "-inf" : "inf", out);
+        return;
+    }
+    if (x == 0) {
+        fputs(signbit(x) ? "-0.0" : "0.0", out);
+        return;
+    }
+    for (precision = 0; precision < 17; precision++) {
+        snprintf(formatted, sizeof formatted, "%.*e", precision, (double)x);
+        if ((double)strtod(formatted, NULL) == x) {
+            break;
+        }
+    }
+    lm_print_float(out, formatted);
+}
+
+static void lm_print_enum_Pair_bool__f64_(FILE *out, enum_Pair_bool__f64_ v) {
+    switch (v.tag) {
+    case 0:
+        fputs("Pair", out);
+        fputs(" { first: ", out);
+        fputs(v.as.v0.f0 ? "true" : "false", out);
+        fputs(", second: ", out);
+        lm_print_f64(out, v.as.v0.f1);
+        fputs(" }", out);
+        break;
+    }
+}
+
+static lm_unit fn_main(void);
+static uint8_t fn_unwrap_or_u8_(enum_Option_u8_, uint8_t);
+static uint32_t fn_unwrap_or_char_(enum_Option_char_, uint32_t);
+static enum_Pair_bool__f64_ fn_swap_f64__bool_(enum_Pair_f64__bool_);
+static int32_t fn_first_i32_(tuple0);
+
+static lm_unit fn_main(void) {
+    enum_Option_u8_ small;
+    enum_Option_u8_ none;
+    enum_Pair_bool__f64_ pair;
+    enum_Option_Pair_String__Option_i64___ nested;
+    lm_str first;
+    int64_t n;
+    enum_Option_u8_ t0;
+    enum_Option_u8_ t1;
+    uint8_t t2;
+    uint8_t t3;
+    uint8_t t4;
+    enum_Option_char_ t5;
+    uint32_t t6;
+    enum_Pair_f64__bool_ t7;
+    enum_Pair_bool__f64_ t8;
+    double t9;
+    tuple0 t10;
+    int32_t t11;
+    enum_Option_i64_ t12;
+    enum_Pair_String__Option_i64__ t13;
+    enum_Option_Pair_String__Option_i64___ t14;
+    size_t t15;
+    bool t16;
+    size_t t17;
+    bool t18;
+    size_t t19;
+    bool t20;
+
+    t0 = ((enum_Option_u8_){.tag = 0, .as.v0 = {200}});
+    small = t0;
+    t1 = ((enum_Option_u8_){.tag = 1});
+    none = t1;
+    t2 = fn_unwrap_or_u8_(small, 1);
+    t3 = fn_unwrap_or_u8_(none, 55);
+    t4 = lm_add_u8(t2, t3, "generics.lamia:29:13");
+    fprintf(stdout, "%" PRIu64, (uint64_t)t4);
+    fputc('\n', stdout);
+    t5 = ((enum_Option_char_){.tag = 0, .as.v0 = {'x'}});
+    t6 = fn_unwrap_or_char_(t5, 'y');
+    lm_print_char(stdout, t6);
+    fputc('\n', stdout);
+    t7 = ((enum_Pair_f64__bool_){.tag = 0, .as.v0 = {1.5, true}});
+    t8 = fn_swap_f64__bool_(t7);
+    pair = t8;
+    lm_print_enum_Pair_bool__f64_(stdout, pair);
+    fputc('\n', stdout);
+    t9 = (pair.as.v0.f1 * 2.0);
+    lm_print_f64(stdout, t9);
+    fputc('\n', stdout);
+    t10 = ((tuple0){7, 8});
+    t11 = fn_first_i32_(t10);
+    fprintf(stdout, "%" PRId64, (int64_t)t11);
+    fputc('\n', stdout);
+    t12 = ((enum_Option_i64_){.tag = 0, .as.v0 = {3}});
+    t13 = ((enum_Pair_String__Option_i64__){.tag = 0, .as.v0 = {LM_STR("a"), t12}});
+    t14 = ((enum_Option_Pair_String__Option_i64___){.tag = 0, .as.v0 = {t13}});
+    nested = t14;
+    t15 = nested.tag;
+    t16 = (t15 == 0);
+    if (!t16) goto bb9;
+    t17 = nested.as.v0.f0.tag;
+    t18 = (t17 == 0);
+    if (!t18) goto bb9;
+    first = nested.as.v0.f0.as.v0.f0;
+    t19 = nested.as.v0.f0.as.v0.f1.tag;
+    t20 = (t19 == 0);
+    if (!t20) goto bb9;
+    n = nested.as.v0.f0.as.v0.f1.as.v0.f0;
+    lm_print_str(stdout, first);
+    fputs(" ", stdout);
+    fprintf(stdout, "%" PRId64, (int64_t)n);
+    fputc('\n', stdout);
+    goto bb10;
+bb9:
+    fputs("none", stdout);
+    fputc('\n', stdout);
+bb10:
+    return 0;
+}
+
+static uint8_t fn_unwrap_or_u8_(enum_Option_u8_ option, uint8_t default_) {
+    uint8_t value;
+    uint8_t t0;
+    size_t t1;
+    bool t2;
+    size_t t3;
+    bool t4;
+
+    t1 = option.tag;
+    t2 = (t1 == 0);
+    if (!t2) goto bb2;
+    value = option.as.v0.f0;
+    t0 = value;
+    goto bb4;
+bb2:
+    t3 = option.tag;
+    t4 = (t3 == 1);
+    if (!t4) goto bb5;
+    t0 = default_;
+bb4:
+    return t0;
+bb5:
+    abort();
+}
+
+static uint32_t fn_unwrap_or_char_(enum_Option_char_ option, uint32_t default_) {
+    uint32_t value;
+    uint32_t t0;
+    size_t t1;
+    bool t2;
+    size_t t3;
+    bool t4;
+
+    t1 = option.tag;
+    t2 = (t1 == 0);
+    if (!t2) goto bb2;
+    value = option.as.v0.f0;
+    t0 = value;
+    goto bb4;
+bb2:
+    t3 = option.tag;
+    t4 = (t3 == 1);
+    if (!t4) goto bb5;
+    t0 = default_;
+bb4:
+    return t0;
+bb5:
+    abort();
+}
+
+static enum_Pair_bool__f64_ fn_swap_f64__bool_(enum_Pair_f64__bool_ pair) {
+    enum_Pair_bool__f64_ t0;
+
+    t0 = ((enum_Pair_bool__f64_){.tag = 0, .as.v0 = {pair.as.v0.f1, pair.as.v0.f0}});
+    return t0;
+}
+
+static int32_t fn_first_i32_(tuple0 values) {
+    return values.f0;
+}
+
+int main(void) {
+    fn_main();
+    return 0;
+}
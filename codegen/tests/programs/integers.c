@@ -0,0 +1,220 @@
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+
+static void lm_panic_begin(void) {
+    fflush(stdout);
+    fputs("error: ", stderr);
+}
+
+static void lm_panic_end(const char *at) {
+    fprintf(stderr, "\n  --> %s\n", at);
+    exit(EXIT_FAILURE);
+}
+
+static void lm_panic(const char *message, const char *at) {
+    lm_panic_begin();
+    fputs(message, stderr);
+    lm_panic_end(at);
+}
+
+static uint8_t lm_add_u8(uint8_t a, uint8_t b, const char *at) {
+    if (a > UINT8_MAX - b) {
+        lm_panic("attempt to add with overflow", at);
+    }
+    return (uint8_t)(a + b);
+}
+
+static size_t lm_mul_usize(size_t a, size_t b, const char *at) {
+    if (b != 0 && a > SIZE_MAX / b) {
+        lm_panic("attempt to multiply with overflow", at);
+    }
+    return (size_t)(a * b);
+}
+
+static int64_t lm_div_i64(int64_t a, int64_t b, const char *at) {
+    if (b == 0) {
+        lm_panic("attempt to divide by zero", at);
+    }
+    if (a == INT64_MIN && b == -1) {
+        lm_panic("attempt to divide with overflow", at);
+    }
+    return (int64_t)(a / b);
+}
+
+static int64_t lm_rem_i64(int64_t a, int64_t b, const char *at) {
+    if (b == 0) {
+        lm_panic("attempt to calculate the remainder with a divisor of zero", at);
+    }
+    if (a == INT64_MIN && b == -1) {
+        lm_panic("attempt to calculate the remainder with overflow", at);
+    }
+    return (int64_t)(a % b);
+}
+
+static int64_t lm_shr_i64_i64(int64_t a, int64_t b, const char *at) {
+    if (b < 0 || b >= 64) {
+        lm_panic_begin();
+        fprintf(stderr, "attempt to shift by `%" PRId64 "`, which is out of range", (int64_t)b);
+        lm_panic_end(at);
+    }
+    return a < 0 ? (int64_t)~(~a >> b) : (int64_t)(a >> b);
+}
+
+static uint8_t lm_shr_u8_u8(uint8_t a, uint8_t b, const char *at) {
+    if (b >= 8) {
+        lm_panic_begin();
+        fprintf(stderr, "attempt to shift by `%" PRIu64 "`, which is out of range", (uint64_t)b);
+        lm_panic_end(at);
+    }
+    return (uint8_t)(a >> b);
+}
+
+static int32_t lm_shl_i32_i32(int32_t a, int32_t b, const char *at) {
+    if (b < 0 || b >= 32) {
+        lm_panic_begin();
+        fprintf(stderr, "attempt to shift by `%" PRId64 "`, which is out of range", (int64_t)b);
+        lm_panic_end(at);
+    }
+    return (int32_t)((uint64_t)a << b);
+}
+
+static void lm_print_str(FILE *out, lm_str s) {
+    fwrite(s.ptr, 1, s.len, out);
+}
+
+static lm_unit fn_main(void);
+static lm_str fn_describe(int16_t);
+
+static lm_unit fn_main(void) {
+    uint8_t a;
+    uint8_t b;
+    int64_t c;
+    uint8_t t0;
+    uint8_t t1;
+    uint8_t t2;
+    uint8_t t3;
+    uint8_t t4;
+    uint8_t t5;
+    int64_t t6;
+    int64_t t7;
+    int64_t t8;
+    int64_t t9;
+    int64_t t10;
+    int32_t t11;
+    size_t t12;
+    lm_str t13;
+    lm_str t14;
+    lm_str t15;
+    lm_str t16;
+
+    a = 200;
+    b = 55;
+    t0 = lm_add_u8(a, b, "integers.lamia:13:13");
+    fprintf(stdout, "%" PRIu64, (uint64_t)t0);
+    fputc('\n', stdout);
+    t1 = (uint8_t)~a;
+    fprintf(stdout, "%" PRIu64, (uint64_t)t1);
+    fputc('\n', stdout);
+    t2 = (uint8_t)(a & 15);
+    fprintf(stdout, "%" PRIu64, (uint64_t)t2);
+    fputc('\n', stdout);
+    t3 = (uint8_t)(a | 1);
+    fprintf(stdout, "%" PRIu64, (uint64_t)t3);
+    fputc('\n', stdout);
+    t4 = (uint8_t)(a ^ b);
+    fprintf(stdout, "%" PRIu64, (uint64_t)t4);
+    fputc('\n', stdout);
+    t5 = lm_shr_u8_u8(a, 3, "integers.lamia:18:13");
+    fprintf(stdout, "%" PRIu64, (uint64_t)t5);
+    fputc('\n', stdout);
+    c = (-INT64_C(9223372036854775807) - 1);
+    fprintf(stdout, "%" PRId64, (int64_t)c);
+    fputc('\n', stdout);
+    t6 = lm_div_i64(c, 3, "integers.lamia:22:13");
+    fprintf(stdout, "%" PRId64, (int64_t)t6);
+    fputc('\n', stdout);
+    t7 = lm_rem_i64(c, 7, "integers.lamia:23:13");
+    fprintf(stdout, "%" PRId64, (int64_t)t7);
+    fputc('\n', stdout);
+    t8 = lm_div_i64((-17), 5, "integers.lamia:24:13");
+    fprintf(stdout, "%" PRId64, (int64_t)t8);
+    fputc('\n', stdout);
+    t9 = lm_rem_i64((-17), 5, "integers.lamia:25:13");
+    fprintf(stdout, "%" PRId64, (int64_t)t9);
+    fputc('\n', stdout);
+    t10 = lm_shr_i64_i64((-16), 2, "integers.lamia:26:13");
+    fprintf(stdout, "%" PRId64, (int64_t)t10);
+    fputc('\n', stdout);
+    t11 = lm_shl_i32_i32(1, 31, "integers.lamia:27:13");
+    fprintf(stdout, "%" PRId64, (int64_t)t11);
+    fputc('\n', stdout);
+    fprintf(stdout, "%" PRIu64, (uint64_t)UINT64_C(18446744073709551615));
+    fputc('\n', stdout);
+    t12 = lm_mul_usize(3, 7, "integers.lamia:29:13");
+    fprintf(stdout, "%" PRIu64, (uint64_t)t12);
+    fputc('\n', stdout);
+    fprintf(stdout, "%" PRId64, (int64_t)(-3));
+    fputc('\n', stdout);
+    t13 = fn_describe((-4));
+    t14 = fn_describe(0);
+    t15 = fn_describe(7);
+    t16 = fn_describe(300);
+    lm_print_str(stdout, t13);
+    fputs(" ", stdout);
+    lm_print_str(stdout, t14);
+    fputs(" ", stdout);
+    lm_print_str(stdout, t15);
+    fputs(" ", stdout);
+    lm_print_str(stdout, t16);
+    fputc('\n', stdout);
+    return 0;
+}
+
+static lm_str fn_describe(int16_t n) {
+    lm_str t0;
+    bool t1;
+    bool t2;
+    bool t3;
+    bool t4;
+
+    t1 = (n <= (-1));
+    if (!t1) goto bb2;
+    t0 = LM_STR("negative");
+    goto bb8;
+bb2:
+    t2 = (n == 0);
+    if (!t2) goto bb4;
+    t0 = LM_STR("zero");
+    goto bb8;
+bb4:
+    t3 = (n >= 1);
+    if (!t3) goto bb7;
+    t4 = (n <= 9);
+    if (!t4) goto bb7;
+    t0 = LM_STR("digit");
+    goto bb8;
+bb7:
+    t0 = LM_STR("large");
+bb8:
+    return t0;
+}
+
+int main(void) {
+    fn_main();
+    return 0;
+}
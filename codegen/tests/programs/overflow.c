@@ -0,0 +1,68 @@
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+
+static void lm_panic_begin(void) {
+    fflush(stdout);
+    fputs("error: ", stderr);
+}
+
+static void lm_panic_end(const char *at) {
+    fprintf(stderr, "\n  --> %s\n", at);
+    exit(EXIT_FAILURE);
+}
+
+static void lm_panic(const char *message, const char *at) {
+    lm_panic_begin();
+    fputs(message, stderr);
+    lm_panic_end(at);
+}
+
+static int8_t lm_add_i8(int8_t a, int8_t b, const char *at) {
+    if ((b > 0 && a > INT8_MAX - b) || (b < 0 && a < INT8_MIN - b)) {
+        lm_panic("attempt to add with overflow", at);
+    }
+    return (int8_t)(a + b);
+}
+
+static lm_unit fn_main(void);
+static int8_t fn_add(int8_t, int8_t);
+
+static lm_unit fn_main(void) {
+    int8_t t0;
+    int8_t t1;
+
+    t0 = fn_add(100, 27);
+    fprintf(stdout, "%" PRId64, (int64_t)t0);
+    fputc('\n', stdout);
+    t1 = fn_add(100, 28);
+    fprintf(stdout, "%" PRId64, (int64_t)t1);
+    fputc('\n', stdout);
+    return 0;
+}
+
+static int8_t fn_add(int8_t a, int8_t b) {
+    int8_t t0;
+
+    t0 = lm_add_i8(a, b, "overflow.lamia:2:5");
+    return t0;
+}
+
+int main(void) {
+    fn_main();
+    return 0;
+}
@@ -0,0 +1,134 @@
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+
+typedef struct {
+    uint32_t f0;
+    uint32_t f1;
+    uint32_t f2;
+} tuple0;
+
+static bool lm_str_eq(lm_str a, lm_str b) {
+    return a.len == b.len && memcmp(a.ptr, b.ptr, a.len) == 0;
+}
+
+static int lm_str_cmp(lm_str a, lm_str b) {
+    int result = memcmp(a.ptr, b.ptr, a.len < b.len ? a.len : b.len);
+    if (result != 0) {
+        return result;
+    }
+    return a.len < b.len ? -1 : a.len > b.len;
+}
+
+static void lm_print_char(FILE *out, uint32_t c) {
+    if (c < 0x80) {
+        fputc((int)c, out);
+    } else if (c < 0x800) {
+        fputc((int)(0xc0 | c >> 6), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else if (c < 0x10000) {
+        fputc((int)(0xe0 | c >> 12), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    } else {
+        fputc((int)(0xf0 | c >> 18), out);
+        fputc((int)(0x80 | (c >> 12 & 0x3f)), out);
+        fputc((int)(0x80 | (c >> 6 & 0x3f)), out);
+        fputc((int)(0x80 | (c & 0x3f)), out);
+    }
+}
+
+static void lm_print_str(FILE *out, lm_str s) {
+    fwrite(s.ptr, 1, s.len, out);
+}
+
+static void lm_print_tuple0(FILE *out, tuple0 v) {
+    fputc('(', out);
+    lm_print_char(out, v.f0);
+    fputs(", ", out);
+    lm_print_char(out, v.f1);
+    fputs(", ", out);
+    lm_print_char(out, v.f2);
+    fputs(")", out);
+}
+
+static lm_unit fn_main(void);
+static lm_str fn_greet(lm_str);
+
+static lm_unit fn_main(void) {
+    lm_str s;
+    lm_str t0;
+    lm_str t1;
+    bool t2;
+    bool t3;
+    bool t4;
+    tuple0 t5;
+    bool t6;
+
+    s = LM_STR("tab\there \"quoted\" \\ \?\?= \303\251 \342\234\223");
+    lm_print_str(stdout, s);
+    fputc('\n', stdout);
+    fputs("no newline, ", stdout);
+    t0 = fn_greet(LM_STR("lamia"));
+    t1 = fn_greet(LM_STR(""));
+    fputs("then {braces} and ", stdout);
+    lm_print_str(stdout, t0);
+    fputs(" and ", stdout);
+    lm_print_str(stdout, t1);
+    fputc('\n', stdout);
+    fputs("to stderr", stderr);
+    fputc('\n', stderr);
+    t2 = (lm_str_cmp(LM_STR("apple"), LM_STR("banana")) < 0);
+    fputs(t2 ? "true" : "false", stdout);
+    fputc('\n', stdout);
+    t3 = (lm_str_cmp(LM_STR("b"), LM_STR("abc")) <= 0);
+    fputs(t3 ? "true" : "false", stdout);
+    fputc('\n', stdout);
+    t4 = (lm_str_cmp(LM_STR("\303\251"), LM_STR("z")) > 0);
+    fputs(t4 ? "true" : "false", stdout);
+    fputc('\n', stdout);
+    lm_print_char(stdout, 10003u);
+    fputc('\n', stdout);
+    t5 = ((tuple0){'a', 10u, 39u});
+    lm_print_tuple0(stdout, t5);
+    fputc('\n', stdout);
+    t6 = (lm_str_eq(s, LM_STR("")));
+    if (!t6) goto bb4;
+    fputs("empty", stdout);
+    fputc('\n', stdout);
+    goto bb5;
+bb4:
+    fputs("not empty", stdout);
+    fputc('\n', stdout);
+bb5:
+    return 0;
+}
+
+static lm_str fn_greet(lm_str name) {
+    bool t0;
+
+    t0 = (lm_str_eq(name, LM_STR("")));
+    if (!t0) goto bb2;
+    return LM_STR("nobody");
+bb2:
+    return name;
+}
+
+int main(void) {
+    fn_main();
+    return 0;
+}
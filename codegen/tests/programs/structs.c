@@ -0,0 +1,200 @@
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t lm_unit;
+
+typedef struct {
+    const char *ptr;
+    size_t len;
+} lm_str;
+
+#define LM_STR(s) ((lm_str){s, sizeof(s) - 1})
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Point
+            int64_t f0;
+            int64_t f1;
+        } v0;
+    } as;
+} enum_Point;
+
+typedef struct {
+    uint32_t tag;
+    union {
+        struct { // Line
+            enum_Point f0;
+            enum_Point f1;
+        } v0;
+    } as;
+} enum_Line;
+
+static void lm_panic_begin(void) {
+    fflush(stdout);
+    fputs("error: ", stderr);
+}
+
+static void lm_panic_end(const char *at) {
+    fprintf(stderr, "\n  --> %s\n", at);
+    exit(EXIT_FAILURE);
+}
+
+static void lm_panic(const char *message, const char *at) {
+    lm_panic_begin();
+    fputs(message, stderr);
+    lm_panic_end(at);
+}
+
+static int64_t lm_add_i64(int64_t a, int64_t b, const char *at) {
+    if ((b > 0 && a > INT64_MAX - b) || (b < 0 && a < INT64_MIN - b)) {
+        lm_panic("attempt to add with overflow", at);
+    }
+    return (int64_t)(a + b);
+}
+
+static int64_t lm_sub_i64(int64_t a, int64_t b, const char *at) {
+    if ((b < 0 && a > INT64_MAX + b) || (b > 0 && a < INT64_MIN + b)) {
+        lm_panic("attempt to subtract with overflow", at);
+    }
+    return (int64_t)(a - b);
+}
+
+static int64_t lm_mul_i64(int64_t a, int64_t b, const char *at) {
+    if (a > 0 ? (b > 0 ? a > INT64_MAX / b : b < INT64_MIN / a) : (b > 0 ? a < INT64_MIN / b : a != 0 && b < INT64_MAX / a)) {
+        lm_panic("attempt to multiply with overflow", at);
+    }
+    return (int64_t)(a * b);
+}
+
+static bool lm_eq_enum_Point(enum_Point a, enum_Point b) {
+    if (a.tag != b.tag) {
+        return false;
+    }
+    switch (a.tag) {
+    case 0:
+        return a.as.v0.f0 == b.as.v0.f0 && a.as.v0.f1 == b.as.v0.f1;
+    default:
+        return true;
+    }
+}
+
+static void lm_print_enum_Point(FILE *out, enum_Point v) {
+    switch (v.tag) {
+    case 0:
+        fputs("Point", out);
+        fputs(" { x: ", out);
+        fprintf(out, "%" PRId64, (int64_t)v.as.v0.f0);
+        fputs(", y: ", out);
+        fprintf(out, "%" PRId64, (int64_t)v.as.v0.f1);
+        fputs(" }", out);
+        break;
+    }
+}
+
+static void lm_print_enum_Line(FILE *out, enum_Line v) {
+    switch (v.tag) {
+    case 0:
+        fputs("Line", out);
+        fputs(" { from: ", out);
+        lm_print_enum_Point(out, v.as.v0.f0);
+        fputs(", to: ", out);
+        lm_print_enum_Point(out, v.as.v0.f1);
+        fputs(" }", out);
+        break;
+    }
+}
+
+static lm_unit fn_main(void);
+static int64_t fn_length(enum_Line);
+
+static lm_unit fn_main(void) {
+    enum_Line line;
+    int64_t y;
+    enum_Point t0;
+    enum_Point t1;
+    enum_Line t2;
+    int64_t t3;
+    enum_Point t4;
+    int64_t t5;
+    enum_Point t6;
+    int64_t t7;
+    size_t t8;
+    bool t9;
+    bool t10;
+    size_t t11;
+    bool t12;
+    enum_Point t13;
+    bool t14;
+
+    t0 = ((enum_Point){.tag = 0, .as.v0 = {0, 0}});
+    t1 = ((enum_Point){.tag = 0, .as.v0 = {3, 4}});
+    t2 = ((enum_Line){.tag = 0, .as.v0 = {t0, t1}});
+    line = t2;
+    t3 = fn_length(line);
+    fprintf(stdout, "%" PRId64, (int64_t)t3);
+    fputc('\n', stdout);
+    t4 = ((enum_Point){.tag = 0, .as.v0 = {6, line.as.v0.f1.as.v0.f1}});
+    line = ((enum_Line){.tag = 0, .as.v0 = {line.as.v0.f0, t4}});
+    t5 = lm_sub_i64(line.as.v0.f0.as.v0.f1, 4, "structs.lamia:21:5");
+    t6 = ((enum_Point){.tag = 0, .as.v0 = {line.as.v0.f0.as.v0.f0, t5}});
+    line = ((enum_Line){.tag = 0, .as.v0 = {t6, line.as.v0.f1}});
+    lm_print_enum_Line(stdout, line);
+    fputc('\n', stdout);
+    t7 = fn_length(line);
+    fprintf(stdout, "%" PRId64, (int64_t)t7);
+    fputc('\n', stdout);
+    t8 = line.as.v0.f1.tag;
+    t9 = (t8 == 0);
+    if (!t9) goto bb5;
+    t10 = (line.as.v0.f1.as.v0.f0 == 6);
+    if (!t10) goto bb5;
+    y = line.as.v0.f1.as.v0.f1;
+    fprintf(stdout, "%" PRId64, (int64_t)y);
+    fputc('\n', stdout);
+    goto bb7;
+bb5:
+    t11 = line.as.v0.f1.tag;
+    t12 = (t11 == 0);
+    if (!t12) goto bb8;
+    fprintf(stdout, "%" PRId64, (int64_t)0);
+    fputc('\n', stdout);
+bb7:
+    t13 = ((enum_Point){.tag = 0, .as.v0 = {0, (-4)}});
+    t14 = (lm_eq_enum_Point(line.as.v0.f0, t13));
+    fputs(t14 ? "true" : "false", stdout);
+    fputc('\n', stdout);
+    return 0;
+bb8:
+    abort();
+}
+
+static int64_t fn_length(enum_Line line) {
+    int64_t dx;
+    int64_t dy;
+    int64_t t0;
+    int64_t t1;
+    int64_t t2;
+    int64_t t3;
+    int64_t t4;
+
+    t0 = lm_sub_i64(line.as.v0.f1.as.v0.f0, line.as.v0.f0.as.v0.f0, "structs.lamia:12:14");
+    dx = t0;
+    t1 = lm_sub_i64(line.as.v0.f1.as.v0.f1, line.as.v0.f0.as.v0.f1, "structs.lamia:13:14");
+    dy = t1;
+    t2 = lm_mul_i64(dx, dx, "structs.lamia:14:5");
+    t3 = lm_mul_i64(dy, dy, "structs.lamia:14:15");
+    t4 = lm_add_i64(t2, t3, "structs.lamia:14:5");
+    return t4;
+}
+
+int main(void) {
+    fn_main();
+    return 0;
+}
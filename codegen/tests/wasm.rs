@@ -0,0 +1,179 @@
+//! Tests of the WebAssembly backend: each program in `tests/programs` is compiled to a
+//! binary module and to the text format, which must both be valid. Both are run with wasmi,
+//! and what they print is compared with the `.stdout` and `.stderr` files, which the C
+//! backend's tests check as well.
+
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use codegen::tir::Program;
+use db::Database;
+use source_map::Severity;
+use wasmi::{Caller, Engine, Extern, Linker, Module, Store};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/programs")
+}
+
+fn lower(name: &str) -> (Program, Database) {
+    let path = fixtures().join(format!("{name}.lamia"));
+    let text = fs::read_to_string(&path).unwrap();
+
+    let mut db = Database::new();
+    let root = db.add_file(format!("{name}.lamia"), text);
+    let (module_tree, mut diagnostics) = modules::build(&mut db, root);
+    diagnostics.extend(checker::check(&db, &module_tree));
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .map(|diagnostic| diagnostic.render(db.source_map()))
+        .collect::<String>();
+    assert!(errors.is_empty(), "{errors}");
+
+    let program = codegen::lower(&db, &module_tree).unwrap_or_else(|diagnostics| {
+        let errors = diagnostics.iter().map(|d| d.render(db.source_map()));
+        panic!("{}", errors.collect::<String>())
+    });
+    (program, db)
+}
+
+/// What a module wrote through the functions of the host.
+#[derive(Debug, Default)]
+struct Output {
+    stdout: String,
+    stderr: String,
+}
+
+impl Output {
+    fn file(&mut self, fd: i32) -> &mut String {
+        match fd {
+            1 => &mut self.stdout,
+            _ => &mut self.stderr,
+        }
+    }
+}
+
+/// Calls the export `main` of the module, with the functions of the host writing to an
+/// [`Output`].
+fn run(wasm: &[u8]) -> Output {
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm).unwrap();
+    let mut store = Store::new(&engine, Output::default());
+    let mut linker = <Linker<Output>>::new(&engine);
+    linker
+        .func_wrap(
+            "lamia",
+            "write",
+            |mut caller: Caller<'_, Output>, fd: i32, ptr: i32, len: i32| {
+                let memory = caller
+                    .get_export("memory")
+                    .and_then(Extern::into_memory)
+                    .unwrap();
+                let bytes = &memory.data(&caller)[ptr as usize..][..len as usize];
+                let text = String::from_utf8(bytes.to_vec()).unwrap();
+                caller.data_mut().file(fd).push_str(&text);
+            },
+        )
+        .unwrap()
+        .func_wrap(
+            "lamia",
+            "write_f32",
+            |mut caller: Caller<'_, Output>, fd: i32, x: f32| {
+                let _ = write!(caller.data_mut().file(fd), "{x:?}");
+            },
+        )
+        .unwrap()
+        .func_wrap(
+            "lamia",
+            "write_f64",
+            |mut caller: Caller<'_, Output>, fd: i32, x: f64| {
+                let _ = write!(caller.data_mut().file(fd), "{x:?}");
+            },
+        )
+        .unwrap()
+        .func_wrap(
+            "lamia",
+            "exit",
+            |_: Caller<'_, Output>, code: i32| -> Result<(), wasmi::Error> {
+                Err(wasmi::Error::i32_exit(code))
+            },
+        )
+        .unwrap();
+
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .unwrap()
+        .start(&mut store)
+        .unwrap();
+    let main = instance.get_typed_func::<(), ()>(&store, "main").unwrap();
+    if let Err(err) = main.call(&mut store, ()) {
+        assert_eq!(err.i32_exit_status(), Some(1), "{err}");
+    }
+    store.into_data()
+}
+
+fn check(name: &str) {
+    let (program, db) = lower(name);
+    let binary = codegen::emit_wasm(&program, db.source_map());
+    wasmparser::validate(&binary).unwrap();
+    let text = wat::parse_str(codegen::emit_wat(&program, db.source_map())).unwrap();
+    wasmparser::validate(&text).unwrap();
+
+    let expected = |extension: &str| {
+        let path = fixtures().join(format!("{name}.{extension}"));
+        fs::read_to_string(path).unwrap_or_default()
+    };
+    for wasm in [binary, text] {
+        let output = run(&wasm);
+        assert_eq!(expected("stdout"), output.stdout);
+        assert_eq!(expected("stderr"), output.stderr);
+    }
+}
+
+#[test]
+fn fib() {
+    check("fib");
+}
+
+#[test]
+fn enums() {
+    check("enums");
+}
+
+#[test]
+fn structs() {
+    check("structs");
+}
+
+#[test]
+fn generics() {
+    check("generics");
+}
+
+#[test]
+fn integers() {
+    check("integers");
+}
+
+#[test]
+fn floats() {
+    check("floats");
+}
+
+#[test]
+fn strings() {
+    check("strings");
+}
+
+#[test]
+fn overflow() {
+    check("overflow");
+}
+
+#[test]
+fn assertion() {
+    check("assertion");
+}
@@ -0,0 +1,288 @@
+use parser::{
+    ast::{AstNode, Expr, GenericParamList, ItemList, Root, Stmt},
+    GreenTree,
+};
+
+/// The items declared in a file, without their bodies or positions, so that edits inside
+/// function bodies or whitespace changes leave it untouched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemTree {
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Function(Function),
+    Module(Module),
+    Use(Use),
+    Enum(Enum),
+    Struct(Struct),
+    Trait(Trait),
+    Impl(Impl),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name: String,
+    pub is_pub: bool,
+    pub generics: Vec<String>,
+    pub params: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub name: String,
+    pub is_pub: bool,
+    /// The items of `mod foo { ... }`, `None` for `mod foo;`.
+    pub items: Option<ItemTree>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enum {
+    pub name: String,
+    pub is_pub: bool,
+    pub generics: Vec<String>,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    pub name: String,
+    /// The names of the fields, `0`, `1`, ... for tuple variants.
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Struct {
+    pub name: String,
+    pub is_pub: bool,
+    pub generics: Vec<String>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trait {
+    pub name: String,
+    pub is_pub: bool,
+    pub functions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Impl {
+    pub generics: Vec<String>,
+    /// The implemented trait as written, `None` for inherent impls.
+    pub trait_type: Option<String>,
+    pub self_type: String,
+    pub functions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Use {
+    pub path: Vec<String>,
+    pub is_pub: bool,
+}
+
+impl ItemTree {
+    pub fn lower(green_tree: &GreenTree) -> Self {
+        let root = Root::cast(green_tree.syntax()).expect("the parser always produces a root");
+        Self::from_stmts(root.stmts())
+    }
+
+    fn from_stmts(stmts: impl Iterator<Item = Stmt>) -> Self {
+        let items = stmts
+            .filter_map(|stmt| {
+                let item = match stmt {
+                    Stmt::Expr(stmt) => {
+                        let Some(Expr::Function(function)) = stmt.expr() else {
+                            return None;
+                        };
+
+                        Item::Function(Function {
+                            name: function.name()?.to_string(),
+                            is_pub: function.visibility().is_some_and(|v| v.is_pub()),
+                            generics: generics(function.generic_param_list()),
+                            params: function
+                                .param_list()
+                                .into_iter()
+                                .flat_map(|list| list.params())
+                                .filter_map(|param| Some(param.name()?.to_string()))
+                                .collect(),
+                        })
+                    }
+                    Stmt::Mod(stmt) => Item::Module(Module {
+                        name: stmt.name()?.to_string(),
+                        is_pub: stmt.visibility().is_some_and(|v| v.is_pub()),
+                        items: stmt
+                            .item_list()
+                            .map(|item_list| Self::from_stmts(item_list.stmts())),
+                    }),
+                    Stmt::Use(stmt) => Item::Use(Use {
+                        path: stmt.segments().map(|s| s.to_string()).collect(),
+                        is_pub: stmt.visibility().is_some_and(|v| v.is_pub()),
+                    }),
+                    Stmt::Enum(stmt) => Item::Enum(Enum {
+                        name: stmt.name()?.to_string(),
+                        is_pub: stmt.visibility().is_some_and(|v| v.is_pub()),
+                        generics: generics(stmt.generic_param_list()),
+                        variants: stmt
+                            .variant_list()
+                            .into_iter()
+                            .flat_map(|list| list.variants())
+                            .filter_map(|variant| {
+                                let fields = match (variant.tuple_fields(), variant.record_fields())
+                                {
+                                    (Some(types), _) => {
+                                        (0..types.types().count()).map(|i| i.to_string()).collect()
+                                    }
+                                    (None, Some(list)) => list
+                                        .fields()
+                                        .filter_map(|field| Some(field.name()?.to_string()))
+                                        .collect(),
+                                    (None, None) => Vec::new(),
+                                };
+                                Some(Variant {
+                                    name: variant.name()?.to_string(),
+                                    fields,
+                                })
+                            })
+                            .collect(),
+                    }),
+                    Stmt::Struct(stmt) => Item::Struct(Struct {
+                        name: stmt.name()?.to_string(),
+                        is_pub: stmt.visibility().is_some_and(|v| v.is_pub()),
+                        generics: generics(stmt.generic_param_list()),
+                        fields: stmt
+                            .record_fields()
+                            .into_iter()
+                            .flat_map(|list| list.fields())
+                            .filter_map(|field| Some(field.name()?.to_string()))
+                            .collect(),
+                    }),
+                    Stmt::Trait(stmt) => Item::Trait(Trait {
+                        name: stmt.name()?.to_string(),
+                        is_pub: stmt.visibility().is_some_and(|v| v.is_pub()),
+                        functions: functions(stmt.item_list()),
+                    }),
+                    Stmt::Impl(stmt) => Item::Impl(Impl {
+                        generics: generics(stmt.generic_param_list()),
+                        trait_type: stmt.trait_type().map(|ty| ty.syntax().to_string()),
+                        self_type: stmt.self_type()?.syntax().to_string(),
+                        functions: functions(stmt.item_list()),
+                    }),
+                    Stmt::Let(_) | Stmt::Return(_) => return None,
+                };
+
+                Some(item)
+            })
+            .collect();
+
+        Self { items }
+    }
+}
+
+fn functions(list: Option<ItemList>) -> Vec<String> {
+    list.into_iter()
+        .flat_map(|list| list.functions())
+        .filter_map(|function| Some(function.name()?.to_string()))
+        .collect()
+}
+
+fn generics(list: Option<GenericParamList>) -> Vec<String> {
+    list.into_iter()
+        .flat_map(|list| list.params())
+        .filter_map(|param| Some(param.name()?.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::Parser;
+
+    use super::{Enum, Function, Impl, Item, ItemTree, Module, Struct, Trait, Use, Variant};
+
+    #[test]
+    fn lower_items() {
+        let green_tree = Parser::new(
+            "pub fn f<T>(a, b) { a }\nmod m { use f; }\nlet x = 1;\nenum E { A(u8), B { c: u8 } }\npub struct S<T> { a: T, b: u8 }",
+        )
+        .parse();
+
+        assert_eq!(
+            ItemTree::lower(&green_tree),
+            ItemTree {
+                items: vec![
+                    Item::Function(Function {
+                        name: "f".to_string(),
+                        is_pub: true,
+                        generics: vec!["T".to_string()],
+                        params: vec!["a".to_string(), "b".to_string()],
+                    }),
+                    Item::Module(Module {
+                        name: "m".to_string(),
+                        is_pub: false,
+                        items: Some(ItemTree {
+                            items: vec![Item::Use(Use {
+                                path: vec!["f".to_string()],
+                                is_pub: false,
+                            })],
+                        }),
+                    }),
+                    Item::Enum(Enum {
+                        name: "E".to_string(),
+                        is_pub: false,
+                        generics: Vec::new(),
+                        variants: vec![
+                            Variant {
+                                name: "A".to_string(),
+                                fields: vec!["0".to_string()],
+                            },
+                            Variant {
+                                name: "B".to_string(),
+                                fields: vec!["c".to_string()],
+                            },
+                        ],
+                    }),
+                    Item::Struct(Struct {
+                        name: "S".to_string(),
+                        is_pub: true,
+                        generics: vec!["T".to_string()],
+                        fields: vec!["a".to_string(), "b".to_string()],
+                    }),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn lower_traits_and_impls() {
+        let green_tree = Parser::new(
+            "pub trait Show { fn show(self); }\nimpl<T> Show for [T] { fn show(self) {} }\nimpl E { fn new() {} }",
+        )
+        .parse();
+
+        assert_eq!(
+            ItemTree::lower(&green_tree),
+            ItemTree {
+                items: vec![
+                    Item::Trait(Trait {
+                        name: "Show".to_string(),
+                        is_pub: true,
+                        functions: vec!["show".to_string()],
+                    }),
+                    Item::Impl(Impl {
+                        generics: vec!["T".to_string()],
+                        trait_type: Some("Show".to_string()),
+                        self_type: "[T]".to_string(),
+                        functions: vec!["show".to_string()],
+                    }),
+                    Item::Impl(Impl {
+                        generics: Vec::new(),
+                        trait_type: None,
+                        self_type: "E".to_string(),
+                        functions: vec!["new".to_string()],
+                    }),
+                ],
+            }
+        );
+    }
+}
@@ -0,0 +1,541 @@
+//! A query database in the spirit of salsa: derived values are memoized per key and only
+//! recomputed when one of the inputs they read changed since they were last verified.
+//!
+//! The inputs are the text of each file and the set of files. Queries implement [`Query`]
+//! and are run with [`Database::query`], which records every input and query they read as
+//! their dependencies. Every memo remembers the revision it was last verified at and the
+//! revision its value last changed at. When an input is edited the revision is bumped; a
+//! stale memo is then re-verified by bringing its dependencies up to date in turn, and is
+//! only recomputed when one of them actually changed after the memo was verified. A
+//! recomputed value equal to the previous one keeps its old `changed_at` (early cutoff), so
+//! e.g. editing a function body reparses the file but doesn't invalidate anything depending
+//! only on the file's items.
+//!
+//! The crates after this one add their own queries on top, such as the module tree of a
+//! program and the inference of each function. Values read outside of [`Database::query`],
+//! such as [`Database::source_map`], aren't tracked, and queries must not depend on them.
+
+pub mod item_tree;
+
+use std::{
+    any::{Any, TypeId},
+    cell::{RefCell, RefMut},
+    collections::HashMap,
+    fmt, fs,
+    hash::Hash,
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use item_tree::{Item, ItemTree};
+use parser::{
+    syntax::{SyntaxKind, SyntaxNode},
+    GreenTree, Parser,
+};
+use source_map::{FileId, SourceMap, Span};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Revision(u64);
+
+/// A value derived from the inputs of the database and from other queries.
+pub trait Query: 'static {
+    type Key: Clone + Eq + Hash + fmt::Debug + 'static;
+    type Value: Eq + 'static;
+
+    /// The name of the query in the log of executions.
+    const NAME: &'static str;
+
+    fn execute(db: &Database, key: &Self::Key) -> Self::Value;
+}
+
+/// Something a query read, which brings itself up to date when called and returns the
+/// revision it last changed at.
+type Dependency = Rc<dyn Fn(&Database) -> Revision>;
+
+struct Memo<T> {
+    value: Arc<T>,
+    verified_at: Revision,
+    changed_at: Revision,
+    dependencies: Rc<[Dependency]>,
+}
+
+type MemoTable<Q> = HashMap<<Q as Query>::Key, Memo<<Q as Query>::Value>>;
+
+/// The memos of every query, by the type of the query.
+#[derive(Default)]
+struct Memos(RefCell<HashMap<TypeId, Box<dyn Any>>>);
+
+impl fmt::Debug for Memos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Memos")
+            .field("queries", &self.0.borrow().len())
+            .finish()
+    }
+}
+
+#[derive(Default)]
+pub struct Database {
+    source_map: SourceMap,
+    revision: Revision,
+    /// The revision at which the text of each file was last set.
+    text_changed_at: HashMap<FileId, Revision>,
+    /// The revision at which the last file was added.
+    files_changed_at: Revision,
+
+    memos: Memos,
+    /// The dependencies of the queries being executed, innermost last.
+    active: RefCell<Vec<Vec<Dependency>>>,
+
+    /// Every query execution, e.g. `parse(FileId(0))`, in the order they finished.
+    executed: RefCell<Vec<String>>,
+}
+
+impl fmt::Debug for Database {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Database")
+            .field("source_map", &self.source_map)
+            .field("revision", &self.revision)
+            .field("memos", &self.memos)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Database {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    pub fn revision(&self) -> Revision {
+        self.revision
+    }
+
+    pub fn add_file(&mut self, path: impl Into<PathBuf>, text: impl Into<String>) -> FileId {
+        self.revision.0 += 1;
+        let file = self.source_map.add(path, text);
+        self.text_changed_at.insert(file, self.revision);
+        self.files_changed_at = self.revision;
+        file
+    }
+
+    /// Reads the file from disk, reusing its id if it was already loaded.
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> io::Result<FileId> {
+        let path = path.as_ref();
+        if let Some(file) = self.source_map.find(path) {
+            return Ok(file);
+        }
+
+        let text = fs::read_to_string(path)?;
+        Ok(self.add_file(path, text))
+    }
+
+    /// Replaces the text of a file, invalidating the queries that depend on it.
+    pub fn set_file_text(&mut self, file: FileId, text: impl Into<String>) {
+        let text = text.into();
+        if self.source_map.text(file) == text {
+            return;
+        }
+
+        self.revision.0 += 1;
+        self.source_map.set_text(file, text);
+        self.text_changed_at.insert(file, self.revision);
+    }
+
+    /// The text of a file, as an input of the query being executed.
+    pub fn file_text(&self, file: FileId) -> &str {
+        self.record(Rc::new(move |db| db.text_changed_at[&file]));
+        self.source_map.text(file)
+    }
+
+    /// The file at `path` if it was added, as an input of the query being executed.
+    pub fn find_file(&self, path: &Path) -> Option<FileId> {
+        self.record(Rc::new(|db| db.files_changed_at));
+        self.source_map.find(path)
+    }
+
+    /// The syntax tree of a file.
+    pub fn parse(&self, file: FileId) -> Arc<GreenTree> {
+        self.query::<Parse>(&file)
+    }
+
+    /// The items declared in a file, unaffected by edits inside function bodies.
+    pub fn item_tree(&self, file: FileId) -> Arc<ItemTree> {
+        self.query::<LowerItemTree>(&file)
+    }
+
+    /// The paths of every function and module declared in a file, e.g. `m::f`.
+    pub fn file_symbols(&self, file: FileId) -> Arc<Vec<String>> {
+        self.query::<FileSymbols>(&file)
+    }
+
+    /// The syntax of the item containing `span`, which is a function, a type, a trait or an
+    /// impl declared in a module. Queries reading items through it are only rerun when the
+    /// item changes, rather than whenever its file does.
+    pub fn item_syntax(&self, span: Span) -> Option<SyntaxNode> {
+        self.query::<ItemSyntaxQuery>(&span)
+            .as_ref()
+            .as_ref()
+            .map(|item| item.0.clone())
+    }
+
+    /// Returns and clears the log of query executions.
+    pub fn take_executed(&self) -> Vec<String> {
+        self.executed.take()
+    }
+
+    /// The value of a query, recorded as a dependency of the query being executed.
+    pub fn query<Q: Query>(&self, key: &Q::Key) -> Arc<Q::Value> {
+        let (value, _) = self.fetch::<Q>(key);
+        let key = key.clone();
+        self.record(Rc::new(move |db| db.fetch::<Q>(&key).1));
+        value
+    }
+
+    fn record(&self, dependency: Dependency) {
+        if let Some(dependencies) = self.active.borrow_mut().last_mut() {
+            dependencies.push(dependency);
+        }
+    }
+
+    fn memos<Q: Query>(&self) -> RefMut<'_, MemoTable<Q>> {
+        RefMut::map(self.memos.0.borrow_mut(), |memos| {
+            memos
+                .entry(TypeId::of::<Q>())
+                .or_insert_with(|| Box::new(MemoTable::<Q>::new()))
+                .downcast_mut()
+                .expect("memo tables are keyed by the type of their query")
+        })
+    }
+
+    /// Returns the memoized value of a query along with the revision it last changed at,
+    /// executing the query only when one of its dependencies changed after the memo was
+    /// last verified.
+    fn fetch<Q: Query>(&self, key: &Q::Key) -> (Arc<Q::Value>, Revision) {
+        let memo = self.memos::<Q>().get(key).map(|memo| {
+            (
+                memo.value.clone(),
+                memo.verified_at,
+                memo.changed_at,
+                memo.dependencies.clone(),
+            )
+        });
+        if let Some((value, verified_at, changed_at, dependencies)) = memo {
+            if verified_at == self.revision {
+                return (value, changed_at);
+            }
+            // the dependencies are brought up to date in the order they were read, as the
+            // later ones may not exist anymore once an earlier one changed
+            if dependencies
+                .iter()
+                .all(|dependency| dependency(self) <= verified_at)
+            {
+                if let Some(memo) = self.memos::<Q>().get_mut(key) {
+                    memo.verified_at = self.revision;
+                }
+                return (value, changed_at);
+            }
+        }
+
+        self.active.borrow_mut().push(Vec::new());
+        let value = Q::execute(self, key);
+        let dependencies = self
+            .active
+            .borrow_mut()
+            .pop()
+            .expect("the query pushed its dependencies");
+        // queries are logged once done, after the queries they ran
+        self.executed
+            .borrow_mut()
+            .push(format!("{}({key:?})", Q::NAME));
+
+        let mut memos = self.memos::<Q>();
+        let memo = match memos.remove(key) {
+            Some(old) if *old.value == value => Memo {
+                value: old.value,
+                verified_at: self.revision,
+                changed_at: old.changed_at,
+                dependencies: dependencies.into(),
+            },
+            _ => Memo {
+                value: Arc::new(value),
+                verified_at: self.revision,
+                changed_at: self.revision,
+                dependencies: dependencies.into(),
+            },
+        };
+
+        let result = (memo.value.clone(), memo.changed_at);
+        memos.insert(key.clone(), memo);
+        result
+    }
+}
+
+struct Parse;
+
+impl Query for Parse {
+    type Key = FileId;
+    type Value = GreenTree;
+    const NAME: &'static str = "parse";
+
+    fn execute(db: &Database, file: &FileId) -> GreenTree {
+        Parser::new(db.file_text(*file)).parse()
+    }
+}
+
+struct LowerItemTree;
+
+impl Query for LowerItemTree {
+    type Key = FileId;
+    type Value = ItemTree;
+    const NAME: &'static str = "item_tree";
+
+    fn execute(db: &Database, file: &FileId) -> ItemTree {
+        ItemTree::lower(&db.parse(*file))
+    }
+}
+
+struct FileSymbols;
+
+impl Query for FileSymbols {
+    type Key = FileId;
+    type Value = Vec<String>;
+    const NAME: &'static str = "file_symbols";
+
+    fn execute(db: &Database, file: &FileId) -> Vec<String> {
+        let mut symbols = Vec::new();
+        collect_symbols(&db.item_tree(*file), "", &mut symbols);
+        symbols
+    }
+}
+
+/// An item's syntax, equal to another when both have the same text at the same position.
+struct ItemSyntax(SyntaxNode);
+
+impl PartialEq for ItemSyntax {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.text_range() == other.0.text_range() && self.0.green() == other.0.green()
+    }
+}
+
+impl Eq for ItemSyntax {}
+
+struct ItemSyntaxQuery;
+
+impl Query for ItemSyntaxQuery {
+    type Key = Span;
+    type Value = Option<ItemSyntax>;
+    const NAME: &'static str = "item_syntax";
+
+    fn execute(db: &Database, span: &Span) -> Option<ItemSyntax> {
+        let root = db.parse(span.file).syntax();
+        if !root.text_range().contains_range(span.range) {
+            return None;
+        }
+        // the outermost item within the innermost module, such as the impl of a method
+        let item = root
+            .covering_element(span.range)
+            .ancestors()
+            .take_while(|node| node.kind() != SyntaxKind::ModStmt)
+            .filter(|node| {
+                matches!(
+                    node.kind(),
+                    SyntaxKind::FunctionExpr
+                        | SyntaxKind::EnumStmt
+                        | SyntaxKind::StructStmt
+                        | SyntaxKind::TraitStmt
+                        | SyntaxKind::ImplStmt
+                )
+            })
+            .last()?;
+        Some(ItemSyntax(item))
+    }
+}
+
+fn collect_symbols(item_tree: &ItemTree, prefix: &str, symbols: &mut Vec<String>) {
+    for item in &item_tree.items {
+        match item {
+            Item::Function(function) => symbols.push(format!("{prefix}{}", function.name)),
+            Item::Module(module) => {
+                let path = format!("{prefix}{}", module.name);
+                if let Some(items) = &module.items {
+                    collect_symbols(items, &format!("{path}::"), symbols);
+                }
+                symbols.push(path);
+            }
+            Item::Enum(enum_item) => {
+                let path = format!("{prefix}{}", enum_item.name);
+                for variant in &enum_item.variants {
+                    symbols.push(format!("{path}::{}", variant.name));
+                }
+                symbols.push(path);
+            }
+            Item::Struct(struct_item) => symbols.push(format!("{prefix}{}", struct_item.name)),
+            Item::Trait(trait_item) => {
+                let path = format!("{prefix}{}", trait_item.name);
+                for function in &trait_item.functions {
+                    symbols.push(format!("{path}::{function}"));
+                }
+                symbols.push(path);
+            }
+            // methods are listed under the type they're implemented for, as written
+            Item::Impl(impl_item) => {
+                for function in &impl_item.functions {
+                    symbols.push(format!("{prefix}{}::{function}", impl_item.self_type));
+                }
+            }
+            Item::Use(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use source_map::{Span, TextRange};
+
+    use super::{Database, Query};
+
+    const MAIN: &str = "fn main() { helper(1) }\nfn helper(x) { x + 1 }\n";
+
+    #[test]
+    fn queries_are_memoized() {
+        let mut db = Database::new();
+        let file = db.add_file("main.lamia", MAIN);
+
+        assert_eq!(*db.file_symbols(file), ["main", "helper"]);
+        assert_eq!(
+            db.take_executed(),
+            [
+                "parse(FileId(0))",
+                "item_tree(FileId(0))",
+                "file_symbols(FileId(0))"
+            ]
+        );
+
+        db.file_symbols(file);
+        db.item_tree(file);
+        db.parse(file);
+        assert!(db.take_executed().is_empty());
+    }
+
+    #[test]
+    fn body_edit_stops_at_item_tree() {
+        let mut db = Database::new();
+        let file = db.add_file("main.lamia", MAIN);
+        db.file_symbols(file);
+        db.take_executed();
+
+        db.set_file_text(file, MAIN.replace("x + 1", "x * 2"));
+        assert_eq!(*db.file_symbols(file), ["main", "helper"]);
+        assert_eq!(
+            db.take_executed(),
+            ["parse(FileId(0))", "item_tree(FileId(0))"]
+        );
+    }
+
+    #[test]
+    fn signature_edit_reruns_everything() {
+        let mut db = Database::new();
+        let file = db.add_file("main.lamia", MAIN);
+        db.file_symbols(file);
+        db.take_executed();
+
+        db.set_file_text(file, MAIN.replace("helper", "help"));
+        assert_eq!(*db.file_symbols(file), ["main", "help"]);
+        assert_eq!(
+            db.take_executed(),
+            [
+                "parse(FileId(0))",
+                "item_tree(FileId(0))",
+                "file_symbols(FileId(0))"
+            ]
+        );
+    }
+
+    #[test]
+    fn editing_another_file() {
+        let mut db = Database::new();
+        let main = db.add_file("main.lamia", MAIN);
+        let other = db.add_file("other.lamia", "mod inner { fn f() {} }");
+        db.file_symbols(main);
+        assert_eq!(*db.file_symbols(other), ["inner::f", "inner"]);
+        db.take_executed();
+
+        db.set_file_text(other, "mod inner { fn g() {} }");
+        db.file_symbols(main);
+        assert!(db.take_executed().is_empty());
+
+        assert_eq!(*db.file_symbols(other), ["inner::g", "inner"]);
+        assert_eq!(
+            db.take_executed(),
+            [
+                "parse(FileId(1))",
+                "item_tree(FileId(1))",
+                "file_symbols(FileId(1))"
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_symbols() {
+        let mut db = Database::new();
+        let file = db.add_file("main.lamia", "mod m { enum E { A, B(u8) } }");
+        assert_eq!(*db.file_symbols(file), ["m::E::A", "m::E::B", "m::E", "m"]);
+    }
+
+    #[test]
+    fn setting_the_same_text() {
+        let mut db = Database::new();
+        let file = db.add_file("main.lamia", MAIN);
+        db.parse(file);
+        let revision = db.revision();
+
+        db.set_file_text(file, MAIN);
+        assert_eq!(db.revision(), revision);
+        db.parse(file);
+        assert_eq!(db.take_executed(), ["parse(FileId(0))"]);
+    }
+
+    /// The length of the item at a span, as queries of other crates read items.
+    struct ItemLen;
+
+    impl Query for ItemLen {
+        type Key = Span;
+        type Value = usize;
+        const NAME: &'static str = "item_len";
+
+        fn execute(db: &Database, span: &Span) -> usize {
+            db.item_syntax(*span)
+                .map_or(0, |item| item.text().len().into())
+        }
+    }
+
+    #[test]
+    fn queries_of_other_crates() {
+        let mut db = Database::new();
+        let file = db.add_file("main.lamia", MAIN);
+        let main = Span::new(file, TextRange::new(3.into(), 7.into()));
+        let helper = Span::new(file, TextRange::new(27.into(), 33.into()));
+        assert_eq!(*db.query::<ItemLen>(&main), 23);
+        assert_eq!(*db.query::<ItemLen>(&helper), 22);
+        db.take_executed();
+
+        // only the queries reading the edited item are rerun
+        db.set_file_text(file, MAIN.replace("x + 1", "x * 2"));
+        db.query::<ItemLen>(&main);
+        db.query::<ItemLen>(&helper);
+        assert_eq!(
+            db.take_executed(),
+            [
+                "parse(FileId(0))",
+                "item_syntax(Span { file: FileId(0), range: 3..7 })",
+                "item_syntax(Span { file: FileId(0), range: 27..33 })",
+                "item_len(Span { file: FileId(0), range: 27..33 })"
+            ]
+        );
+    }
+}
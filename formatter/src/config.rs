@@ -0,0 +1,124 @@
+use std::fmt;
+
+/// Name of the configuration file looked up by `lamia fmt`.
+pub const CONFIG_FILE_NAME: &str = "lamiafmt.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// Number of spaces per indentation level.
+    pub indent_width: usize,
+    /// Width after which argument and parameter lists are split over multiple lines.
+    pub max_width: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            max_width: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Parses the small TOML subset used by `lamiafmt.toml`:
+    /// `key = value` pairs with integer values and `#` comments.
+    pub fn parse(source: &str) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+
+        for (index, line) in source.lines().enumerate() {
+            let error = |message: String| ConfigError {
+                line: index + 1,
+                message,
+            };
+
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(error(format!("expected `key = value`, found `{line}`")));
+            };
+
+            let (key, value) = (key.trim(), value.trim());
+            let value = value
+                .parse::<usize>()
+                .ok()
+                .filter(|&value| value > 0)
+                .ok_or_else(|| error(format!("expected a positive integer for `{key}`")))?;
+
+            match key {
+                "indent_width" => config.indent_width = value,
+                "max_width" => config.max_width = value,
+                _ => return Err(error(format!("unknown option `{key}`"))),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, ConfigError};
+
+    #[test]
+    fn parse_empty_config() {
+        assert_eq!(Config::parse(""), Ok(Config::default()));
+    }
+
+    #[test]
+    fn parse_config() {
+        assert_eq!(
+            Config::parse("# lamia\nindent_width = 2\nmax_width = 80 # columns\n"),
+            Ok(Config {
+                indent_width: 2,
+                max_width: 80,
+            })
+        );
+    }
+
+    #[test]
+    fn fail_unknown_option() {
+        assert_eq!(
+            Config::parse("\ntab_width = 2"),
+            Err(ConfigError {
+                line: 2,
+                message: "unknown option `tab_width`".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn fail_invalid_value() {
+        assert_eq!(
+            Config::parse("max_width = wide"),
+            Err(ConfigError {
+                line: 1,
+                message: "expected a positive integer for `max_width`".to_string(),
+            })
+        );
+        assert_eq!(
+            Config::parse("indent_width = 0"),
+            Err(ConfigError {
+                line: 1,
+                message: "expected a positive integer for `indent_width`".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,798 @@
+mod config;
+
+pub use config::{Config, ConfigError, CONFIG_FILE_NAME};
+
+use std::collections::HashSet;
+
+use parser::{
+    syntax::{SyntaxKind, SyntaxNode, SyntaxToken},
+    ParseError, Parser,
+};
+
+/// Formats `source`, refusing to touch code that doesn't parse.
+pub fn format(source: &str, config: &Config) -> Result<String, Vec<ParseError>> {
+    let green_tree = Parser::new(source).parse();
+    if !green_tree.errors.is_empty() {
+        return Err(green_tree.errors);
+    }
+
+    let items = items(&green_tree.syntax());
+    Ok(Printer::new(&items, config).print())
+}
+
+/// A non-whitespace token along with the context needed to lay it out.
+#[derive(Debug)]
+struct Item {
+    kind: SyntaxKind,
+    text: String,
+    parent: SyntaxKind,
+    /// Number of line breaks between this item and the previous one in the original source.
+    newlines_before: usize,
+    /// Whether this is the last token of a statement.
+    ends_stmt: bool,
+    /// Whether this is the first token of its parent, e.g. the opening `|` of closure parameters.
+    first_in_parent: bool,
+    /// Whether a `,` is missing after this item, which ends a match arm.
+    needs_comma: bool,
+    /// Whether this opens a list written one element per line even if it fits on one, like
+    /// the variants of an enum and the fields of a struct.
+    one_per_line: bool,
+}
+
+fn items(root: &SyntaxNode) -> Vec<Item> {
+    let stmt_ends = root
+        .descendants()
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                SyntaxKind::LetStmt
+                    | SyntaxKind::ReturnStmt
+                    | SyntaxKind::ExprStmt
+                    | SyntaxKind::ModStmt
+                    | SyntaxKind::UseStmt
+                    | SyntaxKind::EnumStmt
+                    | SyntaxKind::StructStmt
+                    | SyntaxKind::TraitStmt
+                    | SyntaxKind::ImplStmt
+                    | SyntaxKind::MatchArm
+                    // attributes go on their own line
+                    | SyntaxKind::Attr
+            )
+        })
+        .filter_map(|node| node.last_token())
+        .collect::<HashSet<SyntaxToken>>();
+
+    // like rustfmt, arms whose expression doesn't end with a block are followed by a comma
+    let arms_without_comma = root
+        .descendants()
+        .filter(|node| node.kind() == SyntaxKind::MatchArm)
+        .filter_map(|node| node.last_token())
+        .filter(|token| !matches!(token.kind(), SyntaxKind::Comma | SyntaxKind::CloseBrace))
+        .collect::<HashSet<SyntaxToken>>();
+
+    let mut items = Vec::new();
+    let mut newlines = 0;
+
+    for token in root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+    {
+        // like rustfmt with format strings, interpolated strings are kept as written
+        let string = token
+            .parent_ancestors()
+            .filter(|node| node.kind() == SyntaxKind::InterpolatedString)
+            .last();
+        if let Some(string) = string {
+            if string.first_token().as_ref() == Some(&token) {
+                let last = string.last_token();
+                items.push(Item {
+                    kind: SyntaxKind::InterpolatedString,
+                    text: string.text().to_string(),
+                    parent: string.parent().map_or(SyntaxKind::Root, |node| node.kind()),
+                    newlines_before: newlines,
+                    ends_stmt: last.as_ref().is_some_and(|last| stmt_ends.contains(last)),
+                    first_in_parent: string.prev_sibling_or_token().is_none(),
+                    needs_comma: last.is_some_and(|last| arms_without_comma.contains(&last)),
+                    one_per_line: false,
+                });
+                newlines = 0;
+            }
+            continue;
+        }
+
+        let kind = token.kind();
+        if kind == SyntaxKind::Whitespace {
+            newlines += token.text().matches('\n').count();
+            continue;
+        }
+
+        items.push(Item {
+            kind,
+            text: match kind {
+                // line comments own their trailing newline
+                SyntaxKind::LineComment => token.text().trim_end().to_string(),
+                _ => token.text().to_string(),
+            },
+            parent: token.parent().map_or(SyntaxKind::Root, |node| node.kind()),
+            newlines_before: newlines,
+            ends_stmt: stmt_ends.contains(&token),
+            first_in_parent: token.prev_sibling_or_token().is_none(),
+            needs_comma: arms_without_comma.contains(&token),
+            one_per_line: token.parent().is_some_and(|parent| match parent.kind() {
+                SyntaxKind::VariantList => true,
+                SyntaxKind::RecordFieldList => {
+                    parent.parent().map(|node| node.kind()) == Some(SyntaxKind::StructStmt)
+                }
+                _ => false,
+            }),
+        });
+
+        newlines = token.text().ends_with('\n') as usize;
+    }
+
+    items
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Separator {
+    None,
+    Space,
+    Newline { blank_line: bool },
+}
+
+/// An open bracket whose closing counterpart hasn't been printed yet.
+#[derive(Debug)]
+struct Group {
+    open: usize,
+    close: usize,
+    /// Whether the list is split with one element per line.
+    broken: bool,
+}
+
+struct Printer<'a> {
+    items: &'a [Item],
+    config: &'a Config,
+    matching: Vec<Option<usize>>,
+    groups: Vec<Group>,
+    indent: usize,
+    column: usize,
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    fn new(items: &'a [Item], config: &'a Config) -> Self {
+        Self {
+            items,
+            config,
+            matching: matching_brackets(items),
+            groups: Vec::new(),
+            indent: 0,
+            column: 0,
+            out: String::new(),
+        }
+    }
+
+    fn print(mut self) -> String {
+        let mut prev = None;
+
+        for index in 0..self.items.len() {
+            if self.is_redundant_comma(index) {
+                continue;
+            }
+
+            let item = &self.items[index];
+
+            if let Some(prev) = prev {
+                let separator = self.separator(prev, index);
+                if is_close(item.kind) {
+                    self.close_group(prev);
+                }
+
+                match separator {
+                    Separator::None => {}
+                    Separator::Space => self.write(" "),
+                    Separator::Newline { blank_line } => {
+                        self.write(if blank_line { "\n\n" } else { "\n" });
+                        self.write(&" ".repeat(self.indent * self.config.indent_width));
+                    }
+                }
+            }
+
+            self.write(&item.text);
+            if item.needs_comma {
+                self.write(",");
+            }
+
+            if is_open(item.kind) {
+                self.open_group(index);
+            }
+
+            prev = Some(index);
+        }
+
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out
+    }
+
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.column = match text.rfind('\n') {
+            Some(newline) => text.len() - newline - 1,
+            None => self.column + text.len(),
+        };
+    }
+
+    fn open_group(&mut self, open: usize) {
+        let Some(close) = self.matching[open] else {
+            return;
+        };
+
+        let parent = self.items[open].parent;
+        let fits = |width: usize| self.column - 1 + width <= self.config.max_width;
+        let broken = is_list(parent)
+            && close > open + 1
+            && (self.items[open].one_per_line
+                || !(self.flat_width(open, close).is_some_and(fits)
+                    || self.hugged_width(open, close).is_some_and(fits)));
+
+        if broken || self.items[open].kind == SyntaxKind::OpenBrace {
+            self.indent += 1;
+        }
+
+        self.groups.push(Group {
+            open,
+            close,
+            broken,
+        });
+    }
+
+    /// Pops the innermost group, adding the trailing comma of broken lists.
+    fn close_group(&mut self, prev: usize) {
+        let Some(group) = self.groups.pop() else {
+            return;
+        };
+
+        if group.broken {
+            let prev = &self.items[prev];
+            if prev.kind != SyntaxKind::Comma && !is_comment(prev.kind) {
+                self.write(",");
+            }
+        }
+
+        if group.broken || self.items[group.open].kind == SyntaxKind::OpenBrace {
+            self.indent -= 1;
+        }
+    }
+
+    /// Width of the group between `open` and `close` when printed on one line,
+    /// or `None` if it contains something forcing a line break.
+    fn flat_width(&self, open: usize, close: usize) -> Option<usize> {
+        let mut width = self.items[open].text.len();
+        let mut prev = open;
+
+        for index in open + 1..=close {
+            if self.is_trailing_comma(index) {
+                continue;
+            }
+
+            let item = &self.items[index];
+            if item.text.contains('\n') {
+                return None;
+            }
+
+            match self.flat_separator(prev, index)? {
+                Separator::Space => width += 1,
+                Separator::None => {}
+                Separator::Newline { .. } => return None,
+            }
+
+            width += item.text.len();
+            prev = index;
+        }
+
+        Some(width)
+    }
+
+    /// Width of the group between `open` and `close` up to the opening brace of the block
+    /// its last element ends with, which stays on the line as in `f(a, |x| {`, or `None`
+    /// if it doesn't end with a block or something before the block forces a line break.
+    fn hugged_width(&self, open: usize, close: usize) -> Option<usize> {
+        if self.items[open].parent != SyntaxKind::ArgList {
+            return None;
+        }
+
+        let mut last = close - 1;
+        if self.is_trailing_comma(last) {
+            last -= 1;
+        }
+        if self.items[last].kind != SyntaxKind::CloseBrace || is_field_list(self.items[last].parent)
+        {
+            return None;
+        }
+
+        self.flat_width(open, self.matching[last]?)
+    }
+
+    fn is_trailing_comma(&self, index: usize) -> bool {
+        self.items[index].kind == SyntaxKind::Comma
+            && self
+                .items
+                .get(index + 1)
+                .is_some_and(|next| is_close(next.kind))
+    }
+
+    /// Trailing commas are dropped from lists printed on one line.
+    fn is_redundant_comma(&self, index: usize) -> bool {
+        self.is_trailing_comma(index)
+            && self.groups.last().is_some_and(|group| {
+                group.close == index + 1 && !group.broken && is_list(self.items[group.open].parent)
+            })
+    }
+
+    fn separator(&self, prev: usize, next: usize) -> Separator {
+        let (a, b) = (&self.items[prev], &self.items[next]);
+
+        if let Some(group) = self.groups.last().filter(|group| group.broken) {
+            if prev == group.open || next == group.close || a.kind == SyntaxKind::Comma {
+                if b.kind == SyntaxKind::LineComment && b.newlines_before == 0 {
+                    return Separator::Space;
+                }
+                return Separator::Newline { blank_line: false };
+            }
+        }
+
+        self.flat_separator(prev, next)
+            .unwrap_or(Separator::Newline {
+                // blank lines are kept between statements but not at the edges of blocks
+                blank_line: b.newlines_before >= 2
+                    && a.kind != SyntaxKind::OpenBrace
+                    && b.kind != SyntaxKind::CloseBrace,
+            })
+    }
+
+    /// Whether the item is a comment on the line of the opening brace of a block, possibly
+    /// after other comments.
+    fn follows_block_open(&self, index: usize) -> bool {
+        let before = self.items[..=index]
+            .iter()
+            .rev()
+            .find(|item| item.kind != SyntaxKind::BlockComment || item.newlines_before > 0);
+        before.is_some_and(|item| item.kind == SyntaxKind::OpenBrace && !is_field_list(item.parent))
+    }
+
+    /// The separator between two items regardless of the enclosing group,
+    /// or `None` if they must be on separate lines, possibly with a blank line.
+    fn flat_separator(&self, prev: usize, next: usize) -> Option<Separator> {
+        let (a, b) = (&self.items[prev], &self.items[next]);
+
+        if a.kind == SyntaxKind::LineComment {
+            return None;
+        }
+
+        if is_comment(b.kind) || a.kind == SyntaxKind::BlockComment {
+            // the closing brace of a block goes on its own line, even after a comment
+            if b.kind == SyntaxKind::CloseBrace && !is_field_list(b.parent) {
+                return None;
+            }
+            // and so does the body of a block after a comment following its opening brace
+            if !is_comment(b.kind) && self.follows_block_open(prev) {
+                return None;
+            }
+            return (b.newlines_before == 0).then_some(Separator::Space);
+        }
+
+        // unlike blocks, `S { a, b }` patterns and records stay on one line
+        if a.kind == SyntaxKind::OpenBrace && is_field_list(a.parent) {
+            return Some(match b.kind {
+                SyntaxKind::CloseBrace => Separator::None,
+                _ => Separator::Space,
+            });
+        }
+        if b.kind == SyntaxKind::CloseBrace && is_field_list(b.parent) {
+            return Some(Separator::Space);
+        }
+
+        if a.kind == SyntaxKind::OpenBrace {
+            return (b.kind == SyntaxKind::CloseBrace).then_some(Separator::None);
+        }
+
+        if b.kind == SyntaxKind::CloseBrace || a.ends_stmt {
+            return match b.kind {
+                // `} else {`, `};` and `})`
+                SyntaxKind::ElseKeyword => Some(Separator::Space),
+                SyntaxKind::Semicolon
+                | SyntaxKind::Comma
+                | SyntaxKind::CloseParen
+                | SyntaxKind::CloseSquare => Some(Separator::None),
+                _ => None,
+            };
+        }
+
+        let separator = match (a.kind, b.kind) {
+            (
+                _,
+                SyntaxKind::Comma
+                | SyntaxKind::Semicolon
+                | SyntaxKind::Colon
+                | SyntaxKind::CloseParen
+                | SyntaxKind::CloseSquare,
+            ) => Separator::None,
+            (SyntaxKind::OpenParen | SyntaxKind::OpenSquare, _) => Separator::None,
+            (SyntaxKind::ColonColon, _) | (_, SyntaxKind::ColonColon) => Separator::None,
+            (SyntaxKind::Dot, _) | (_, SyntaxKind::Dot) => Separator::None,
+            // `f::<T>` and `Option<T>`
+            (_, SyntaxKind::LessThan | SyntaxKind::GreaterThan) if is_generic_list(b.parent) => {
+                Separator::None
+            }
+            (SyntaxKind::LessThan, _) if is_generic_list(a.parent) => Separator::None,
+            _ if a.parent == SyntaxKind::PrefixExpr && is_operator(a.kind) => Separator::None,
+            // `&T`, `&mut T` and `T?`
+            (SyntaxKind::Ampersand, _) if a.parent == SyntaxKind::RefType => Separator::None,
+            (_, SyntaxKind::Question) if b.parent == SyntaxKind::OptionalType => Separator::None,
+            // `#[a]` and `#![a]`
+            (SyntaxKind::Pound, _) => Separator::None,
+            (SyntaxKind::Bang, _) if a.parent == SyntaxKind::Attr => Separator::None,
+            // `-1` in patterns
+            (SyntaxKind::Minus, _) if a.parent == SyntaxKind::LiteralPattern => Separator::None,
+            // `1..=5`
+            (SyntaxKind::DotDot | SyntaxKind::DotDotEqual, _)
+                if a.parent == SyntaxKind::RangePattern =>
+            {
+                Separator::None
+            }
+            (_, SyntaxKind::DotDot | SyntaxKind::DotDotEqual)
+                if b.parent == SyntaxKind::RangePattern =>
+            {
+                Separator::None
+            }
+            // `f(a)` and `Some(a)`, but `let (a, b)`
+            (_, SyntaxKind::OpenParen)
+                if is_list(b.parent) && b.parent != SyntaxKind::TuplePattern =>
+            {
+                Separator::None
+            }
+            (_, SyntaxKind::OpenSquare) if b.parent == SyntaxKind::IndexExpr => Separator::None,
+            // `|a, b|`
+            (SyntaxKind::Pipe, _) if a.parent == SyntaxKind::ParamList && a.first_in_parent => {
+                Separator::None
+            }
+            (_, SyntaxKind::Pipe) if b.parent == SyntaxKind::ParamList && !b.first_in_parent => {
+                Separator::None
+            }
+            _ => Separator::Space,
+        };
+
+        Some(separator)
+    }
+}
+
+fn matching_brackets(items: &[Item]) -> Vec<Option<usize>> {
+    let mut matching = vec![None; items.len()];
+    let mut stack = Vec::new();
+
+    for (index, item) in items.iter().enumerate() {
+        if is_open(item.kind) {
+            stack.push(index);
+        } else if is_close(item.kind) {
+            if let Some(open) = stack.pop() {
+                matching[open] = Some(index);
+                matching[index] = Some(open);
+            }
+        }
+    }
+
+    matching
+}
+
+fn is_open(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::OpenParen | SyntaxKind::OpenSquare | SyntaxKind::OpenBrace
+    )
+}
+
+fn is_close(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::CloseParen | SyntaxKind::CloseSquare | SyntaxKind::CloseBrace
+    )
+}
+
+fn is_comment(kind: SyntaxKind) -> bool {
+    matches!(kind, SyntaxKind::LineComment | SyntaxKind::BlockComment)
+}
+
+fn is_operator(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::Minus | SyntaxKind::Bang | SyntaxKind::Tilde
+    )
+}
+
+/// Whether the node is a comma separated list that can be split over multiple lines.
+fn is_list(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::ArgList
+            | SyntaxKind::ParamList
+            | SyntaxKind::ArrayExpr
+            | SyntaxKind::TypeList
+            | SyntaxKind::TuplePattern
+            | SyntaxKind::PatternList
+            | SyntaxKind::FieldPatternList
+            | SyntaxKind::VariantList
+            | SyntaxKind::RecordFieldList
+            | SyntaxKind::RecordExprFieldList
+            | SyntaxKind::AttrArgList
+    )
+}
+
+fn is_generic_list(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::GenericParamList | SyntaxKind::GenericArgList
+    )
+}
+
+/// Whether the node is a list of fields between braces, such as `S { a, b }`.
+fn is_field_list(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::FieldPatternList
+            | SyntaxKind::RecordFieldList
+            | SyntaxKind::RecordExprFieldList
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, Config};
+
+    fn check(input: &str, expected: &str) {
+        check_with(&Config::default(), input, expected);
+    }
+
+    fn check_with(config: &Config, input: &str, expected: &str) {
+        let formatted = format(input, config).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(
+            format(&formatted, config).unwrap(),
+            formatted,
+            "not idempotent"
+        );
+    }
+
+    #[test]
+    fn format_nothing() {
+        check("", "");
+        check(" \n\n ", "");
+    }
+
+    #[test]
+    fn format_operator_spacing() {
+        check("let a=1+-2*(3%b)||!c;", "let a = 1 + -2 * (3 % b) || !c;\n");
+        check(
+            "let m=~a&b|c^d<<2>>e;",
+            "let m = ~a & b | c ^ d << 2 >> e;\n",
+        );
+    }
+
+    #[test]
+    fn format_function() {
+        check(
+            "fn add( a:i32,b:i32 )->i32{a+b}",
+            "fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+        );
+    }
+
+    #[test]
+    fn format_brace_placement() {
+        check(
+            "if a\n{\n1\n}\nelse\n{\n\n2\n\n}",
+            "if a {\n    1\n} else {\n    2\n}\n",
+        );
+        check("fn f() {\n\n}", "fn f() {}\n");
+    }
+
+    #[test]
+    fn format_nested_blocks() {
+        check(
+            "fn f(){let a=if b{c(1,2,)}else{d};return a;}",
+            "fn f() {\n    let a = if b {\n        c(1, 2)\n    } else {\n        d\n    };\n    return a;\n}\n",
+        );
+    }
+
+    #[test]
+    fn format_arrays() {
+        check(
+            "let a:[ i32;3 ]=[ 1,2 , 3, ];a [0]=[0 ;2][ 1];",
+            "let a: [i32; 3] = [1, 2, 3];\na[0] = [0; 2][1];\n",
+        );
+    }
+
+    #[test]
+    fn format_closures() {
+        check(
+            "let f=| a,b:i32 |a+b;g(||1,fn (x){x});fn h(f:fn( i32 )->i32){}",
+            "let f = |a, b: i32| a + b;\ng(|| 1, fn(x) {\n    x\n});\nfn h(f: fn(i32) -> i32) {}\n",
+        );
+        check("g(|x| { x })", "g(|x| {\n    x\n})\n");
+        check("g({ x },)", "g({\n    x\n})\n");
+    }
+
+    #[test]
+    fn format_match() {
+        check(
+            "match x{-1|0=>a,1 ..= 5 if b=>{c}(_,..)=>d,S{a,b:_}=>e,_=>f}",
+            "match x {\n    -1 | 0 => a,\n    1..=5 if b => {\n        c\n    }\n    (_, ..) => d,\n    S { a, b: _ } => e,\n    _ => f,\n}\n",
+        );
+    }
+
+    #[test]
+    fn format_modules() {
+        check(
+            "pub   mod  a{pub fn f(){}}\nmod b;use a :: f;",
+            "pub mod a {\n    pub fn f() {}\n}\nmod b;\nuse a::f;\n",
+        );
+    }
+
+    #[test]
+    fn format_enums() {
+        check(
+            "pub enum Shape{Circle (f64),Rect{w:f64,h :f64},Empty}",
+            "pub enum Shape {\n    Circle(f64),\n    Rect { w: f64, h: f64 },\n    Empty,\n}\n",
+        );
+        check("enum E{}", "enum E {}\n");
+        check(
+            "let s=Shape::Rect{w:1.0,h};let c=Shape :: Circle( 2.0 );",
+            "let s = Shape::Rect { w: 1.0, h };\nlet c = Shape::Circle(2.0);\n",
+        );
+    }
+
+    #[test]
+    fn format_structs() {
+        check(
+            "pub struct Pair<T>{first:T,second :T}fn f(){}",
+            "pub struct Pair<T> {\n    first: T,\n    second: T,\n}\nfn f() {}\n",
+        );
+        check("struct E{}", "struct E {}\n");
+        check(
+            "let p=Pair{first:1,second};p . first=2;",
+            "let p = Pair { first: 1, second };\np.first = 2;\n",
+        );
+    }
+
+    #[test]
+    fn format_generics() {
+        check(
+            "fn id < T,U > ( x : T ) -> Option < Option<T> > { f :: < T > ( x ) < y }",
+            "fn id<T, U>(x: T) -> Option<Option<T>> {\n    f::<T>(x) < y\n}\n",
+        );
+        check(
+            "enum Option<T>{Some(T),None}",
+            "enum Option<T> {\n    Some(T),\n    None,\n}\n",
+        );
+    }
+
+    #[test]
+    fn format_blank_lines() {
+        check(
+            "let a = 1;\n\n\n\nlet b = 2;\nlet c = 3;",
+            "let a = 1;\n\nlet b = 2;\nlet c = 3;\n",
+        );
+    }
+
+    #[test]
+    fn format_comments() {
+        check(
+            "// header\n\n\nlet a = 1; // trailing\n/* block */ let b=2;\nfn f() {\n  // inside\n  a\n}",
+            "// header\n\nlet a = 1; // trailing\n/* block */ let b = 2;\nfn f() {\n    // inside\n    a\n}\n",
+        );
+        check("fn f() { a /* x */ }", "fn f() {\n    a /* x */\n}\n");
+        check(
+            "fn add(a:i32)->i32{ /* inner */ let z = 1;\nz }",
+            "fn add(a: i32) -> i32 { /* inner */\n    let z = 1;\n    z\n}\n",
+        );
+        check(
+            "/* multi\n   line */\nlet a = 1;",
+            "/* multi\n   line */\nlet a = 1;\n",
+        );
+    }
+
+    #[test]
+    fn format_tuples() {
+        check(
+            "let (a,b):(i32,( ),(u8,))=(1 ,(2,));t.0 .1",
+            "let (a, b): (i32, (), (u8,)) = (1, (2,));\nt.0.1\n",
+        );
+    }
+
+    #[test]
+    fn format_assignments() {
+        check(
+            "fn f(mut n:i64){let mut a=n;a+=1;a<<=2;let(mut b,c)=(a,n);b=c;}",
+            "fn f(mut n: i64) {\n    let mut a = n;\n    a += 1;\n    a <<= 2;\n    let (mut b, c) = (a, n);\n    b = c;\n}\n",
+        );
+    }
+
+    #[test]
+    fn format_interpolated_strings() {
+        check(
+            r#"let s=f"a {b+1} { {c} }";match x{_=>f"{x}"}"#,
+            "let s = f\"a {b+1} { {c} }\";\nmatch x {\n    _ => f\"{x}\",\n}\n",
+        );
+    }
+
+    #[test]
+    fn format_ref_and_optional_types() {
+        check(
+            "fn f(a:&  mut T,b:& [u8]? ,c:&&i32)->Option < T > ? {}",
+            "fn f(a: &mut T, b: &[u8]?, c: &&i32) -> Option<T>? {}\n",
+        );
+    }
+
+    #[test]
+    fn format_traits_and_impls() {
+        check(
+            "trait Show{fn show(self)->String;}impl<T :Show+Eq> Show for W<T>{fn show(self)->String{self . get() .show()}}",
+            "trait Show {\n    fn show(self) -> String;\n}\nimpl<T: Show + Eq> Show for W<T> {\n    fn show(self) -> String {\n        self.get().show()\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn format_attrs() {
+        check(
+            "#! [inline]\n#[ test ] #[deprecated (note=\"old\")]fn f(){}\nimpl A{#[inline(always)]fn g(){}}",
+            "#![inline]\n#[test]\n#[deprecated(note = \"old\")]\nfn f() {}\nimpl A {\n    #[inline(always)]\n    fn g() {}\n}\n",
+        );
+    }
+
+    #[test]
+    fn format_indent_width() {
+        let config = Config {
+            indent_width: 2,
+            ..Config::default()
+        };
+        check_with(&config, "fn f() { a }", "fn f() {\n  a\n}\n");
+    }
+
+    #[test]
+    fn format_long_argument_list() {
+        let config = Config {
+            max_width: 20,
+            ..Config::default()
+        };
+        check_with(
+            &config,
+            "let a = call(first, second, third);",
+            "let a = call(\n    first,\n    second,\n    third,\n);\n",
+        );
+        check_with(&config, "f(a,\n  b,\n)", "f(a, b)\n");
+        check_with(
+            &config,
+            "let a = [first, second, third];",
+            "let a = [\n    first,\n    second,\n    third,\n];\n",
+        );
+    }
+
+    #[test]
+    fn format_long_file() {
+        let input = (1..=12)
+            .map(|n| format!("foo{n}(1, 2);\n"))
+            .collect::<String>();
+        assert!(input.len() > Config::default().max_width);
+        check(&input, &input);
+    }
+
+    #[test]
+    fn format_list_with_comments() {
+        check("f(a, // first\n b)", "f(\n    a, // first\n    b,\n)\n");
+    }
+
+    #[test]
+    fn refuse_invalid_code() {
+        assert!(format("let = ;", &Config::default()).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{highlight, Tag};
+
+const RESET: &str = "\x1b[0m";
+
+fn style(tag: Tag) -> &'static str {
+    match tag {
+        Tag::Keyword => "\x1b[35m",
+        Tag::Operator | Tag::Punctuation => "",
+        Tag::Number | Tag::Bool => "\x1b[33m",
+        Tag::String | Tag::Character => "\x1b[32m",
+        Tag::Comment => "\x1b[2;3m",
+        Tag::Function => "\x1b[34m",
+        Tag::Module => "\x1b[96m",
+        Tag::Variable => "\x1b[31m",
+        Tag::Parameter => "\x1b[3;31m",
+        Tag::Type => "\x1b[36m",
+        Tag::Attribute => "\x1b[94m",
+    }
+}
+
+/// Renders `source` with ANSI escape codes for terminal output.
+pub fn to_ansi(source: &str) -> String {
+    let mut out = String::new();
+    let mut offset = 0;
+
+    for highlighted in highlight(source) {
+        let (start, end) = (
+            usize::from(highlighted.range.start()),
+            usize::from(highlighted.range.end()),
+        );
+
+        out.push_str(&source[offset..start]);
+        match style(highlighted.tag) {
+            "" => out.push_str(&source[start..end]),
+            style => {
+                out.push_str(style);
+                out.push_str(&source[start..end]);
+                out.push_str(RESET);
+            }
+        }
+        offset = end;
+    }
+
+    out.push_str(&source[offset..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_ansi;
+
+    #[test]
+    fn render_ansi() {
+        assert_eq!(
+            to_ansi("let a = 1;"),
+            "\x1b[35mlet\x1b[0m \x1b[31ma\x1b[0m = \x1b[33m1\x1b[0m;"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use crate::highlight;
+
+const STYLE: &str = "\
+body { background: #fafafa; }
+pre.lamia { color: #383a42; font-family: monospace; }
+.keyword { color: #a626a4; }
+.operator, .punctuation { color: #383a42; }
+.number, .bool { color: #986801; }
+.string, .character { color: #50a14f; }
+.comment { color: #a0a1a7; font-style: italic; }
+.function { color: #4078f2; }
+.module { color: #0184bc; }
+.variable { color: #e45649; }
+.parameter { color: #e45649; font-style: italic; }
+.type { color: #c18401; }
+.attribute { color: #0997b3; }";
+
+/// Renders `source` as a standalone HTML document, with one `<span>` per highlighted token.
+pub fn to_html(source: &str) -> String {
+    let mut body = String::new();
+    let mut offset = 0;
+
+    for highlighted in highlight(source) {
+        let (start, end) = (
+            usize::from(highlighted.range.start()),
+            usize::from(highlighted.range.end()),
+        );
+
+        push_escaped(&mut body, &source[offset..start]);
+        body.push_str(&format!(
+            r#"<span class="{}">"#,
+            highlighted.tag.css_class()
+        ));
+        push_escaped(&mut body, &source[start..end]);
+        body.push_str("</span>");
+        offset = end;
+    }
+    push_escaped(&mut body, &source[offset..]);
+
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<style>
+{STYLE}
+</style>
+</head>
+<body>
+<pre class=\"lamia\"><code>{body}</code></pre>
+</body>
+</html>
+"
+    )
+}
+
+fn push_escaped(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_html, STYLE};
+    use crate::Tag;
+
+    #[test]
+    fn every_tag_is_styled() {
+        for tag in [
+            Tag::Keyword,
+            Tag::Operator,
+            Tag::Punctuation,
+            Tag::Number,
+            Tag::String,
+            Tag::Character,
+            Tag::Bool,
+            Tag::Comment,
+            Tag::Function,
+            Tag::Module,
+            Tag::Variable,
+            Tag::Parameter,
+            Tag::Type,
+            Tag::Attribute,
+        ] {
+            let selector = format!(".{}", tag.css_class());
+            assert!(STYLE.contains(&selector), "{tag:?} has no style");
+        }
+    }
+
+    #[test]
+    fn render_html() {
+        let html = to_html("a < \"b\"");
+        assert!(html.contains(
+            r#"<code><span class="variable">a</span> <span class="operator">&lt;</span> <span class="string">&quot;b&quot;</span></code>"#
+        ));
+    }
+}
@@ -0,0 +1,435 @@
+mod ansi;
+mod html;
+mod textmate;
+
+pub use ansi::to_ansi;
+pub use html::to_html;
+pub use textmate::textmate_grammar;
+
+use std::collections::HashSet;
+
+use parser::{
+    syntax::{SyntaxKind, SyntaxToken},
+    Parser,
+};
+use rowan::TextRange;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tag {
+    Keyword,
+    Operator,
+    Punctuation,
+    Number,
+    String,
+    Character,
+    Bool,
+    Comment,
+    Function,
+    Module,
+    Variable,
+    Parameter,
+    Type,
+    Attribute,
+}
+
+impl Tag {
+    /// Class used by the HTML renderer, also the last component of the TextMate scope.
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Operator => "operator",
+            Self::Punctuation => "punctuation",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Character => "character",
+            Self::Bool => "bool",
+            Self::Comment => "comment",
+            Self::Function => "function",
+            Self::Module => "module",
+            Self::Variable => "variable",
+            Self::Parameter => "parameter",
+            Self::Type => "type",
+            Self::Attribute => "attribute",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightedRange {
+    pub range: TextRange,
+    pub tag: Tag,
+}
+
+/// Classifies the tokens of `source`. Whitespace and unrecognized tokens are left out.
+pub fn highlight(source: &str) -> Vec<HighlightedRange> {
+    let root = Parser::new(source).parse().syntax();
+
+    let enums = root
+        .descendants()
+        .filter(|node| matches!(node.kind(), SyntaxKind::EnumStmt | SyntaxKind::StructStmt))
+        .filter_map(|node| {
+            node.children_with_tokens()
+                .filter_map(|element| element.into_token())
+                .find(|token| token.kind() == SyntaxKind::Identifier)
+        })
+        .map(|token| token.text().to_string())
+        .collect::<HashSet<_>>();
+
+    root.descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter_map(|token| {
+            let tag = match token.kind() {
+                SyntaxKind::Identifier => identifier_tag(&token, &enums),
+                kind => token_tag(kind)?,
+            };
+
+            Some(HighlightedRange {
+                range: token.text_range(),
+                tag,
+            })
+        })
+        .collect()
+}
+
+/// Tags tokens that can be classified by their kind alone.
+fn token_tag(kind: SyntaxKind) -> Option<Tag> {
+    let tag = match kind {
+        SyntaxKind::LineComment | SyntaxKind::BlockComment => Tag::Comment,
+
+        SyntaxKind::FunctionKeyword
+        | SyntaxKind::LetKeyword
+        | SyntaxKind::IfKeyword
+        | SyntaxKind::ElseKeyword
+        | SyntaxKind::ReturnKeyword
+        | SyntaxKind::ModKeyword
+        | SyntaxKind::UseKeyword
+        | SyntaxKind::PubKeyword
+        | SyntaxKind::MatchKeyword
+        | SyntaxKind::EnumKeyword
+        | SyntaxKind::StructKeyword
+        | SyntaxKind::TraitKeyword
+        | SyntaxKind::ImplKeyword
+        | SyntaxKind::ForKeyword
+        | SyntaxKind::MutKeyword => Tag::Keyword,
+
+        SyntaxKind::Equal
+        | SyntaxKind::NotEqual
+        | SyntaxKind::LessThan
+        | SyntaxKind::GreaterThan
+        | SyntaxKind::LessThanEqual
+        | SyntaxKind::GreaterThanEqual
+        | SyntaxKind::LogicalAnd
+        | SyntaxKind::LogicalOr
+        | SyntaxKind::Plus
+        | SyntaxKind::Minus
+        | SyntaxKind::Bang
+        | SyntaxKind::Star
+        | SyntaxKind::Slash
+        | SyntaxKind::Modulo
+        | SyntaxKind::Ampersand
+        | SyntaxKind::Pipe
+        | SyntaxKind::Caret
+        | SyntaxKind::Tilde
+        | SyntaxKind::ShiftLeft
+        | SyntaxKind::ShiftRight
+        | SyntaxKind::Question
+        | SyntaxKind::SingleEqual
+        | SyntaxKind::PlusEqual
+        | SyntaxKind::MinusEqual
+        | SyntaxKind::SlashEqual
+        | SyntaxKind::StarEqual
+        | SyntaxKind::ModuloEqual
+        | SyntaxKind::AmpersandEqual
+        | SyntaxKind::PipeEqual
+        | SyntaxKind::CaretEqual
+        | SyntaxKind::ShiftLeftEqual
+        | SyntaxKind::ShiftRightEqual
+        | SyntaxKind::FatArrow
+        | SyntaxKind::DotDot
+        | SyntaxKind::DotDotEqual => Tag::Operator,
+
+        SyntaxKind::Pound => Tag::Attribute,
+
+        SyntaxKind::Comma
+        | SyntaxKind::Semicolon
+        | SyntaxKind::Colon
+        | SyntaxKind::ColonColon
+        | SyntaxKind::Dot
+        | SyntaxKind::RightArrow
+        | SyntaxKind::Underscore
+        | SyntaxKind::OpenParen
+        | SyntaxKind::CloseParen
+        | SyntaxKind::OpenSquare
+        | SyntaxKind::CloseSquare
+        | SyntaxKind::OpenBrace
+        | SyntaxKind::CloseBrace => Tag::Punctuation,
+
+        SyntaxKind::Number => Tag::Number,
+        SyntaxKind::String
+        | SyntaxKind::InterpolatedStringPlain
+        | SyntaxKind::InterpolatedStringStart
+        | SyntaxKind::InterpolatedStringMiddle
+        | SyntaxKind::InterpolatedStringEnd => Tag::String,
+        SyntaxKind::Character => Tag::Character,
+        SyntaxKind::Bool => Tag::Bool,
+        SyntaxKind::Identifier => Tag::Variable,
+
+        _ => return None,
+    };
+
+    Some(tag)
+}
+
+/// Tells identifiers apart from their position in the tree, since there is no name resolution yet.
+/// `enums` holds the names of the enums and structs declared in the source.
+fn identifier_tag(token: &SyntaxToken, enums: &HashSet<String>) -> Tag {
+    // leading path segments name modules, or enums as in `Shape::Circle`, which are told
+    // apart by their declarations and by types being capitalized unlike modules
+    let next = std::iter::successors(token.next_token(), |token| token.next_token())
+        .find(|token| !token.kind().is_trivia());
+    if next.is_some_and(|next| next.kind() == SyntaxKind::ColonColon) {
+        let text = token.text();
+        if enums.contains(text) || text.starts_with(|c: char| c.is_uppercase()) {
+            return Tag::Type;
+        }
+        return Tag::Module;
+    }
+
+    let Some(parent) = token.parent() else {
+        return Tag::Variable;
+    };
+
+    match parent.kind() {
+        SyntaxKind::FunctionExpr | SyntaxKind::MethodCallExpr => Tag::Function,
+        SyntaxKind::ModStmt => Tag::Module,
+        SyntaxKind::Attr => Tag::Attribute,
+        SyntaxKind::Param => Tag::Parameter,
+        SyntaxKind::PathType | SyntaxKind::EnumStmt | SyntaxKind::StructStmt => Tag::Type,
+        SyntaxKind::VariableRef => {
+            // the callee is the first child of a call, the arguments come after it
+            let is_callee = parent.parent().is_some_and(|call| {
+                call.kind() == SyntaxKind::CallExpr && call.first_child().as_ref() == Some(&parent)
+            });
+
+            if is_callee {
+                Tag::Function
+            } else {
+                Tag::Variable
+            }
+        }
+        _ => Tag::Variable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{highlight, token_tag, Tag};
+    use parser::syntax::SyntaxKind;
+
+    fn check(input: &str, expected: &[(&str, Tag)]) {
+        let highlighted = highlight(input)
+            .into_iter()
+            .map(|h| (&input[h.range], h.tag))
+            .collect::<Vec<_>>();
+        assert_eq!(highlighted, expected);
+    }
+
+    #[test]
+    fn every_fixed_token_has_a_tag() {
+        for kind in SyntaxKind::all().filter(|kind| kind.fixed_text().is_some()) {
+            assert!(token_tag(kind).is_some(), "{kind:?} has no tag");
+        }
+    }
+
+    #[test]
+    fn highlight_literals() {
+        check(
+            r#"1.5 "a" 'b' true // done"#,
+            &[
+                ("1.5", Tag::Number),
+                ("\"a\"", Tag::String),
+                ("'b'", Tag::Character),
+                ("true", Tag::Bool),
+                ("// done", Tag::Comment),
+            ],
+        );
+    }
+
+    #[test]
+    fn highlight_interpolated_string() {
+        check(
+            r#"f"a{b + 1}c""#,
+            &[
+                ("f\"a{", Tag::String),
+                ("b", Tag::Variable),
+                ("+", Tag::Operator),
+                ("1", Tag::Number),
+                ("}c\"", Tag::String),
+            ],
+        );
+    }
+
+    #[test]
+    fn highlight_identifiers() {
+        check(
+            "fn f(a: i32) -> i32 { g(a, b) }",
+            &[
+                ("fn", Tag::Keyword),
+                ("f", Tag::Function),
+                ("(", Tag::Punctuation),
+                ("a", Tag::Parameter),
+                (":", Tag::Punctuation),
+                ("i32", Tag::Type),
+                (")", Tag::Punctuation),
+                ("->", Tag::Punctuation),
+                ("i32", Tag::Type),
+                ("{", Tag::Punctuation),
+                ("g", Tag::Function),
+                ("(", Tag::Punctuation),
+                ("a", Tag::Variable),
+                (",", Tag::Punctuation),
+                ("b", Tag::Variable),
+                (")", Tag::Punctuation),
+                ("}", Tag::Punctuation),
+            ],
+        );
+    }
+
+    #[test]
+    fn highlight_modules() {
+        check(
+            "mod a; use a::b; a::f();",
+            &[
+                ("mod", Tag::Keyword),
+                ("a", Tag::Module),
+                (";", Tag::Punctuation),
+                ("use", Tag::Keyword),
+                ("a", Tag::Module),
+                ("::", Tag::Punctuation),
+                ("b", Tag::Variable),
+                (";", Tag::Punctuation),
+                ("a", Tag::Module),
+                ("::", Tag::Punctuation),
+                ("f", Tag::Function),
+                ("(", Tag::Punctuation),
+                (")", Tag::Punctuation),
+                (";", Tag::Punctuation),
+            ],
+        );
+    }
+
+    #[test]
+    fn highlight_let_stmt() {
+        check(
+            "let x: u8 = -y;",
+            &[
+                ("let", Tag::Keyword),
+                ("x", Tag::Variable),
+                (":", Tag::Punctuation),
+                ("u8", Tag::Type),
+                ("=", Tag::Operator),
+                ("-", Tag::Operator),
+                ("y", Tag::Variable),
+                (";", Tag::Punctuation),
+            ],
+        );
+    }
+
+    #[test]
+    fn highlight_attrs() {
+        check(
+            "#![a] #[deprecated(note = \"b\")] fn f() {}",
+            &[
+                ("#", Tag::Attribute),
+                ("!", Tag::Operator),
+                ("[", Tag::Punctuation),
+                ("a", Tag::Attribute),
+                ("]", Tag::Punctuation),
+                ("#", Tag::Attribute),
+                ("[", Tag::Punctuation),
+                ("deprecated", Tag::Attribute),
+                ("(", Tag::Punctuation),
+                ("note", Tag::Variable),
+                ("=", Tag::Operator),
+                ("\"b\"", Tag::String),
+                (")", Tag::Punctuation),
+                ("]", Tag::Punctuation),
+                ("fn", Tag::Keyword),
+                ("f", Tag::Function),
+                ("(", Tag::Punctuation),
+                (")", Tag::Punctuation),
+                ("{", Tag::Punctuation),
+                ("}", Tag::Punctuation),
+            ],
+        );
+    }
+
+    #[test]
+    fn highlight_method_call() {
+        check(
+            "x.show(1)",
+            &[
+                ("x", Tag::Variable),
+                (".", Tag::Punctuation),
+                ("show", Tag::Function),
+                ("(", Tag::Punctuation),
+                ("1", Tag::Number),
+                (")", Tag::Punctuation),
+            ],
+        );
+    }
+
+    #[test]
+    fn highlight_enums() {
+        check(
+            "enum shape { Circle(f64) } shape::Circle(1.0); Shape::Circle(r)",
+            &[
+                ("enum", Tag::Keyword),
+                ("shape", Tag::Type),
+                ("{", Tag::Punctuation),
+                ("Circle", Tag::Variable),
+                ("(", Tag::Punctuation),
+                ("f64", Tag::Type),
+                (")", Tag::Punctuation),
+                ("}", Tag::Punctuation),
+                ("shape", Tag::Type),
+                ("::", Tag::Punctuation),
+                ("Circle", Tag::Function),
+                ("(", Tag::Punctuation),
+                ("1.0", Tag::Number),
+                (")", Tag::Punctuation),
+                (";", Tag::Punctuation),
+                ("Shape", Tag::Type),
+                ("::", Tag::Punctuation),
+                ("Circle", Tag::Function),
+                ("(", Tag::Punctuation),
+                ("r", Tag::Variable),
+                (")", Tag::Punctuation),
+            ],
+        );
+    }
+
+    #[test]
+    fn highlight_structs() {
+        check(
+            "struct point { x: i64 } point::new(p.x)",
+            &[
+                ("struct", Tag::Keyword),
+                ("point", Tag::Type),
+                ("{", Tag::Punctuation),
+                ("x", Tag::Variable),
+                (":", Tag::Punctuation),
+                ("i64", Tag::Type),
+                ("}", Tag::Punctuation),
+                ("point", Tag::Type),
+                ("::", Tag::Punctuation),
+                ("new", Tag::Function),
+                ("(", Tag::Punctuation),
+                ("p", Tag::Variable),
+                (".", Tag::Punctuation),
+                ("x", Tag::Variable),
+                (")", Tag::Punctuation),
+            ],
+        );
+    }
+}
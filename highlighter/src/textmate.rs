@@ -0,0 +1,233 @@
+use parser::syntax::SyntaxKind;
+
+use crate::{token_tag, Tag};
+
+/// Identifiers are `\p{XID_Start}\p{XID_Continue}*` in the lexer,
+/// which Oniguruma doesn't support, so the grammar sticks to ASCII.
+const IDENT: &str = "[A-Za-z_][A-Za-z0-9_]*";
+
+const INT_SUFFIX: &str = "(?:[iu](?:8|16|32|64|128|size))?";
+const FLOAT_SUFFIX: &str = "(?:f(?:32|64))?";
+
+/// Generates a TextMate grammar for lamia as JSON.
+///
+/// Keywords, operators and punctuation come from [`SyntaxKind::fixed_text`], so the grammar
+/// follows the lexer as tokens are added. Literals and comments mirror the lexer's regexes.
+pub fn textmate_grammar() -> String {
+    let keywords = fixed_texts(Tag::Keyword)
+        .into_iter()
+        .map(regex_escape)
+        .collect::<Vec<_>>()
+        .join("|");
+    let operators = alternation(fixed_texts(Tag::Operator));
+    let punctuation = alternation(fixed_texts(Tag::Punctuation));
+
+    let patterns = [
+        matched("comment.line.double-slash", "//.*$"),
+        spanned("comment.block", r"/\*", r"\*/", false),
+        interpolated_string(),
+        spanned("string.quoted.double", "\"", "\"", true),
+        matched("string.quoted.single", r"'(?:[^'\\]|\\.)*'"),
+        matched(
+            "constant.numeric.float",
+            &format!(
+                r"\b0[xX][0-9a-fA-F_]*\.?[0-9a-fA-F_]*[pP][+-]?[0-9][0-9_]*{FLOAT_SUFFIX}|(?<!\.)\b[0-9][0-9_]*(?:\.[0-9][0-9_]*(?:[eE][+-]?[0-9][0-9_]*)?{FLOAT_SUFFIX}|[eE][+-]?[0-9][0-9_]*{FLOAT_SUFFIX}|f(?:32|64))"
+            ),
+        ),
+        matched(
+            "constant.numeric.integer",
+            &format!(
+                r"\b(?:0[xX][0-9a-fA-F][0-9a-fA-F_]*|0[oO][0-7][0-7_]*|0[bB][01][01_]*|[0-9][0-9_]*){INT_SUFFIX}\b"
+            ),
+        ),
+        matched("constant.language.bool", r"\b(?:true|false)\b"),
+        captured(
+            &format!(r"\b(fn)\s+({IDENT})"),
+            &["keyword", "entity.name.function"],
+        ),
+        captured(
+            &format!(r"\b(mod)\s+({IDENT})"),
+            &["keyword", "entity.name.namespace"],
+        ),
+        captured(
+            &format!(r"\b(enum|struct)\s+({IDENT})"),
+            &["keyword", "entity.name.type"],
+        ),
+        // capitalized leading path segments are enums, as in `Shape::Circle`
+        matched("entity.name.type", r"\b[A-Z][A-Za-z0-9_]*(?=\s*::)"),
+        captured(
+            &format!(r"(#)(!?)\s*(\[)\s*({IDENT})"),
+            &[
+                "meta.attribute",
+                "keyword.operator",
+                "punctuation",
+                "meta.attribute",
+            ],
+        ),
+        matched("keyword", &format!(r"\b(?:{keywords})\b")),
+        captured(
+            &format!(r"(:|->)\s*({IDENT})"),
+            &["punctuation", "entity.name.type"],
+        ),
+        matched("entity.name.function.call", &format!(r"\b{IDENT}(?=\s*\()")),
+        matched("variable", &format!(r"\b{IDENT}\b")),
+        matched("keyword.operator", &operators),
+        matched("punctuation", &punctuation),
+    ];
+
+    format!(
+        r#"{{
+  "$schema": "https://raw.githubusercontent.com/martinring/tmlanguage/master/tmlanguage.json",
+  "name": "lamia",
+  "scopeName": "source.lamia",
+  "fileTypes": ["lamia"],
+  "patterns": [
+{}
+  ]
+}}
+"#,
+        patterns.join(",\n")
+    )
+}
+
+fn fixed_texts(tag: Tag) -> Vec<&'static str> {
+    SyntaxKind::all()
+        .filter(|&kind| token_tag(kind) == Some(tag))
+        .filter_map(SyntaxKind::fixed_text)
+        .collect()
+}
+
+/// Longest texts come first so that `<=` isn't matched as `<` followed by `=`.
+fn alternation(mut texts: Vec<&str>) -> String {
+    texts.sort_by_key(|text| std::cmp::Reverse(text.len()));
+    texts
+        .into_iter()
+        .map(regex_escape)
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn regex_escape(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$'
+            | '/' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+fn matched(scope: &str, regex: &str) -> String {
+    format!(
+        r#"    {{ "name": {}, "match": {} }}"#,
+        json_string(&format!("{scope}.lamia")),
+        json_string(regex)
+    )
+}
+
+fn spanned(scope: &str, begin: &str, end: &str, escapes: bool) -> String {
+    let patterns = if escapes {
+        r#", "patterns": [{ "name": "constant.character.escape.lamia", "match": "\\\\." }]"#
+    } else {
+        ""
+    };
+
+    format!(
+        r#"    {{ "name": {}, "begin": {}, "end": {}{patterns} }}"#,
+        json_string(&format!("{scope}.lamia")),
+        json_string(begin),
+        json_string(end)
+    )
+}
+
+/// `f"..."` strings, whose embedded expressions are highlighted like the rest of the code.
+/// Unlike in the lexer, an interpolation ends at its first `}`, even if it opens braces.
+fn interpolated_string() -> String {
+    format!(
+        r#"    {{ "name": "string.interpolated.lamia", "begin": {}, "end": "\"", "patterns": [
+      {{ "name": "constant.character.escape.lamia", "match": {} }},
+      {{ "name": "meta.interpolation.lamia", "begin": {}, "end": {}, "captures": {{ "0": {{ "name": "punctuation.section.interpolation.lamia" }} }}, "contentName": "source.lamia", "patterns": [{{ "include": "$self" }}] }}
+    ] }}"#,
+        json_string("\\bf\""),
+        json_string(r"\\.|\{\{|\}\}"),
+        json_string(r"\{"),
+        json_string(r"\}"),
+    )
+}
+
+fn captured(regex: &str, scopes: &[&str]) -> String {
+    let captures = scopes
+        .iter()
+        .enumerate()
+        .map(|(i, scope)| {
+            format!(
+                r#""{}": {{ "name": {} }}"#,
+                i + 1,
+                json_string(&format!("{scope}.lamia"))
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        r#"    {{ "match": {}, "captures": {{ {captures} }} }}"#,
+        json_string(regex)
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::textmate_grammar;
+
+    #[test]
+    fn generated_grammar_is_up_to_date() {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../editors/textmate/lamia.tmLanguage.json");
+        let grammar = textmate_grammar();
+
+        if fs::read_to_string(&path).ok().as_deref() != Some(grammar.as_str()) {
+            if std::env::var_os("UPDATE_GRAMMAR").is_some() {
+                fs::write(&path, grammar).unwrap();
+            } else {
+                panic!(
+                    "{} is out of date, rerun with UPDATE_GRAMMAR=1 to regenerate it",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn grammar_contains_tokens() {
+        let grammar = textmate_grammar();
+        assert!(grammar.contains(
+            r#""\\b(?:fn|let|if|else|return|mod|use|pub|match|enum|struct|trait|impl|for|mut)\\b""#
+        ));
+
+        // interpolated strings must be tried before plain ones
+        let interpolated = grammar.find("string.interpolated").unwrap();
+        let quoted = grammar.find("string.quoted.double").unwrap();
+        assert!(interpolated < quoted);
+
+        // longer operators must be tried first
+        let less_than_equal = grammar.find("|<=|").unwrap();
+        let less_than = grammar.find("|<|").unwrap();
+        assert!(less_than_equal < less_than);
+    }
+}
@@ -0,0 +1,549 @@
+//! The native functions of the prelude, which are in scope everywhere unless a local or an
+//! item shadows them.
+
+use std::{
+    fs,
+    io::{self, Write},
+    mem,
+    rc::Rc,
+};
+
+use modules::prelude::{Builtin, Kind};
+use parser::{
+    ast::{AstNode, CallExpr},
+    literal::IntTy,
+};
+
+use crate::{limits, ops, value::Key, Interpreter, Limit, RuntimeError, Unwind, Value};
+
+type Result<T> = std::result::Result<T, Unwind>;
+
+impl Interpreter {
+    pub(crate) fn eval_builtin(&mut self, builtin: Builtin, call: &CallExpr) -> Result<Value> {
+        let args = call
+            .arg_list()
+            .into_iter()
+            .flat_map(|list| list.args())
+            .collect::<Vec<_>>();
+        builtin
+            .check_arity(args.len())
+            .map_err(|message| self.error(call.syntax(), message))?;
+        if builtin.performs_io() && !self.limits.io {
+            let message = format!(
+                "function `{}` can't be called, as input and output are disabled",
+                builtin.name()
+            );
+            return Err(Unwind::Error(RuntimeError {
+                limit: Some(Limit::Io),
+                ..RuntimeError::new(self.span(call.syntax()), message)
+            }));
+        }
+
+        // the first argument of the functions that modify it is a place, which is read
+        // only once the other arguments are evaluated
+        let params = builtin.signature().params;
+        let skipped = usize::from(builtin.mutates());
+        let mut values = Vec::new();
+        for (arg, &kind) in args.iter().zip(params).skip(skipped) {
+            let value = self.eval_expr(arg)?;
+            if !has_kind(&value, kind) {
+                let message = format!("expected {kind}, found `{}`", value.type_name());
+                return Err(self.error(arg.syntax(), message));
+            }
+            values.push(value);
+        }
+
+        if builtin.mutates() {
+            let invalid = format!(
+                "`{}` modifies its first argument, which must be a variable or an element of one",
+                builtin.name()
+            );
+            let allocated = match builtin {
+                Builtin::Push => mem::size_of::<Value>(),
+                Builtin::Insert => limits::entry_size(),
+                _ => 0,
+            };
+            self.allocate(call.syntax(), allocated)?;
+
+            let kind = params[0];
+            let (value, copied) =
+                self.update_place(args.first().cloned(), call.syntax(), &invalid, |slot| {
+                    if !has_kind(slot, kind) {
+                        return Err(format!("expected {kind}, found `{}`", slot.type_name()));
+                    }
+                    let copied = limits::copied_size(slot);
+                    Ok((modify(builtin, slot, values)?, copied))
+                })?;
+            self.allocate(call.syntax(), copied)?;
+            return Ok(value);
+        }
+
+        match builtin {
+            Builtin::Assert => {
+                let Value::Bool(condition) = values[0] else {
+                    unreachable!("the kind of the condition is checked");
+                };
+                if condition {
+                    return Ok(Value::Unit);
+                }
+
+                let message = match values.get(1) {
+                    Some(message) => {
+                        format!(
+                            "assertion failed: {}",
+                            self.display(message, args[1].syntax())?
+                        )
+                    }
+                    None => format!("assertion failed: `{}`", args[0].syntax()),
+                };
+                Err(self.error(call.syntax(), message))
+            }
+            Builtin::AssertEq => {
+                if values[0] == values[1] {
+                    return Ok(Value::Unit);
+                }
+
+                let left = self.display(&values[0], args[0].syntax())?;
+                let right = self.display(&values[1], args[1].syntax())?;
+                let message = match values.get(2) {
+                    Some(message) => format!(
+                        "assertion `left == right` failed: {}",
+                        self.display(message, args[2].syntax())?
+                    ),
+                    None => "assertion `left == right` failed".to_string(),
+                };
+                Err(self.error(
+                    call.syntax(),
+                    format!("{message}\n  left: {left}\n right: {right}"),
+                ))
+            }
+            Builtin::Print | Builtin::Println | Builtin::Eprintln => {
+                let mut text = match values.first() {
+                    Some(value) => self.display(value, args[0].syntax())?,
+                    None => String::new(),
+                };
+                if builtin != Builtin::Print {
+                    text.push('\n');
+                }
+
+                let result = match builtin {
+                    Builtin::Eprintln => io::stderr().write_all(text.as_bytes()),
+                    _ => {
+                        let mut stdout = io::stdout();
+                        stdout
+                            .write_all(text.as_bytes())
+                            .and_then(|()| stdout.flush())
+                    }
+                };
+                result.map_err(|error| {
+                    self.error(call.syntax(), format!("failed printing: {error}"))
+                })?;
+                Ok(Value::Unit)
+            }
+            Builtin::ReadFile | Builtin::WriteFile => {
+                let Value::String(path) = &values[0] else {
+                    unreachable!("the kind of the path is checked");
+                };
+                let result = match &values.get(1) {
+                    Some(Value::String(contents)) => fs::write(&**path, contents.as_bytes())
+                        .map(|()| Value::Unit)
+                        .map_err(|error| format!("cannot write `{path}`: {error}")),
+                    _ => fs::read_to_string(&**path)
+                        .map(|contents| Value::String(contents.into()))
+                        .map_err(|error| format!("cannot read `{path}`: {error}")),
+                };
+                let value = result.map_err(|message| self.error(call.syntax(), message))?;
+                self.allocate(call.syntax(), limits::size(&value))?;
+                Ok(value)
+            }
+            _ => {
+                let value = compute(builtin, values)
+                    .map_err(|message| self.error(call.syntax(), message))?;
+                // `get` returns a value of the map, which it shares
+                if builtin != Builtin::Get {
+                    self.allocate(call.syntax(), limits::size(&value))?;
+                }
+                Ok(value)
+            }
+        }
+    }
+}
+
+/// Whether `value` is of `kind`.
+fn has_kind(value: &Value, kind: Kind) -> bool {
+    matches!(
+        (kind, value),
+        (Kind::Any, _)
+            | (Kind::Unit, Value::Unit)
+            | (Kind::Int | Kind::Number, Value::Int(..))
+            | (Kind::Float | Kind::Number, Value::Float(_))
+            | (Kind::Bool, Value::Bool(_))
+            | (Kind::Char, Value::Char(_))
+            | (Kind::String | Kind::Collection, Value::String(_))
+            | (Kind::Array | Kind::Collection, Value::Array(_))
+            | (Kind::Map | Kind::Collection, Value::Map(_))
+    )
+}
+
+/// The key of a map with the same value as `value`.
+fn key(value: &Value) -> std::result::Result<Key, String> {
+    Key::new(value).ok_or_else(|| {
+        format!(
+            "a value of type `{}` can't be the key of a map",
+            value.type_name()
+        )
+    })
+}
+
+/// Whether integers of types `a` and `b` can be the arguments of the same builtin, as an
+/// integer without a type takes the type of the other one.
+fn same_int_ty(a: Option<IntTy>, b: Option<IntTy>) -> bool {
+    a.is_none() || b.is_none() || a == b
+}
+
+/// The integer `value` picked among the arguments of a builtin, which must fit in the type
+/// `ty` of the arguments.
+fn int_argument(value: i128, ty: Option<IntTy>) -> std::result::Result<Value, String> {
+    ops::checked(value, ty, || {
+        format!(
+            "literal out of range for `{}`",
+            ty.map_or("i64", IntTy::name)
+        )
+    })
+}
+
+/// Evaluates the builtins which only compute a value from their arguments, whose kinds are
+/// already checked.
+fn compute(builtin: Builtin, values: Vec<Value>) -> std::result::Result<Value, String> {
+    let value = match (builtin, values.as_slice()) {
+        (Builtin::Len, [Value::String(s)]) => Value::Int(s.chars().count() as i128, None),
+        (Builtin::Len, [Value::Array(elements)]) => Value::Int(elements.len() as i128, None),
+        (Builtin::Len, [Value::Map(entries)]) => Value::Int(entries.len() as i128, None),
+        (Builtin::Concat, [Value::String(a), Value::String(b)]) => {
+            Value::String(format!("{a}{b}").into())
+        }
+        (Builtin::Split, [Value::String(s), Value::String(separator)]) => {
+            let parts = s
+                .split(&**separator)
+                .map(|part| Value::String(part.into()))
+                .collect();
+            Value::Array(Rc::new(parts))
+        }
+        (Builtin::Trim, [Value::String(s)]) => Value::String(s.trim().into()),
+        (Builtin::ToUpper, [Value::String(s)]) => Value::String(s.to_uppercase().into()),
+        (Builtin::ToLower, [Value::String(s)]) => Value::String(s.to_lowercase().into()),
+        (Builtin::Chars, [Value::String(s)]) => {
+            Value::Array(Rc::new(s.chars().map(Value::Char).collect()))
+        }
+        (Builtin::Abs, [Value::Int(n, ty)]) => ops::checked(n.abs(), *ty, || {
+            "attempt to negate with overflow".to_string()
+        })?,
+        (Builtin::Abs, [Value::Float(x)]) => Value::Float(x.abs()),
+        (Builtin::Min, [Value::Int(a, a_ty), Value::Int(b, b_ty)]) if same_int_ty(*a_ty, *b_ty) => {
+            int_argument(*a.min(b), a_ty.or(*b_ty))?
+        }
+        (Builtin::Min, [Value::Float(a), Value::Float(b)]) => Value::Float(a.min(*b)),
+        (Builtin::Max, [Value::Int(a, a_ty), Value::Int(b, b_ty)]) if same_int_ty(*a_ty, *b_ty) => {
+            int_argument(*a.max(b), a_ty.or(*b_ty))?
+        }
+        (Builtin::Max, [Value::Float(a), Value::Float(b)]) => Value::Float(a.max(*b)),
+        // the exponent is of any integer type, like the amount of shifts
+        (Builtin::Pow, [Value::Int(base, ty), Value::Int(exponent, _)]) => {
+            let exponent = u32::try_from(*exponent).map_err(|_| {
+                format!("the exponent must be a non-negative integer, found `{exponent}`")
+            })?;
+            let overflow = || "attempt to raise to a power with overflow".to_string();
+            let power = base.checked_pow(exponent).ok_or_else(overflow)?;
+            ops::checked(power, *ty, overflow)?
+        }
+        (Builtin::Pow, [Value::Float(base), Value::Float(exponent)]) => {
+            Value::Float(base.powf(*exponent))
+        }
+        (Builtin::Min | Builtin::Max | Builtin::Pow, [a, b]) => {
+            return Err(format!(
+                "expected both arguments to be of the same type, found `{}` and `{}`",
+                a.type_name(),
+                b.type_name()
+            ))
+        }
+        (Builtin::Sqrt, [Value::Float(x)]) => Value::Float(x.sqrt()),
+        (Builtin::Sin, [Value::Float(x)]) => Value::Float(x.sin()),
+        (Builtin::Cos, [Value::Float(x)]) => Value::Float(x.cos()),
+        (Builtin::Tan, [Value::Float(x)]) => Value::Float(x.tan()),
+        (Builtin::NewMap, []) => Value::Map(Rc::default()),
+        (Builtin::Get, [Value::Map(entries), k]) => match entries.get(&key(k)?) {
+            Some(value) => value.clone(),
+            None => return Err(format!("no entry for the key `{k}` in the map")),
+        },
+        (Builtin::ContainsKey, [Value::Map(entries), k]) => {
+            Value::Bool(entries.contains_key(&key(k)?))
+        }
+        (Builtin::Keys, [Value::Map(entries)]) => {
+            Value::Array(Rc::new(entries.keys().map(Key::to_value).collect()))
+        }
+        (Builtin::Values, [Value::Map(entries)]) => {
+            Value::Array(Rc::new(entries.values().cloned().collect()))
+        }
+        _ => unreachable!("the arguments of `{}` are checked", builtin.name()),
+    };
+    Ok(value)
+}
+
+/// Evaluates the builtins which modify `target`, their first argument, returning what they
+/// evaluate to.
+fn modify(
+    builtin: Builtin,
+    target: &mut Value,
+    values: Vec<Value>,
+) -> std::result::Result<Value, String> {
+    let value = match (builtin, target, values.as_slice()) {
+        (Builtin::Push, Value::Array(elements), [value]) => {
+            Rc::make_mut(elements).push(value.clone());
+            Value::Unit
+        }
+        (Builtin::Pop, Value::Array(elements), []) => Rc::make_mut(elements)
+            .pop()
+            .ok_or_else(|| "cannot pop from an empty array".to_string())?,
+        (Builtin::Insert, Value::Map(entries), [k, value]) => {
+            Rc::make_mut(entries).insert(key(k)?, value.clone());
+            Value::Unit
+        }
+        (Builtin::Remove, Value::Map(entries), [k]) => match Rc::make_mut(entries).remove(&key(k)?)
+        {
+            Some(value) => value,
+            None => return Err(format!("no entry for the key `{k}` in the map")),
+        },
+        _ => unreachable!("the arguments of `{}` are checked", builtin.name()),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        tests::{run, run_display},
+        Value,
+    };
+
+    #[test]
+    fn assert() {
+        let source = "fn main() {
+            assert(1 < 2);
+            assert(true, \"unused\");
+        }";
+        assert_eq!(run(source), Ok(Value::Unit));
+
+        assert_eq!(
+            run("fn main() { let a = 3; assert(a < 2) }"),
+            Err("assertion failed: `a < 2`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let a = 3; assert(a < 2, f\"{a} is too large\") }"),
+            Err("assertion failed: 3 is too large".to_string())
+        );
+        assert_eq!(
+            run("fn main() { assert(1) }"),
+            Err("expected `bool`, found `integer`".to_string())
+        );
+    }
+
+    #[test]
+    fn assert_eq() {
+        assert_eq!(
+            run("fn main() { assert_eq([1, 2], [1, 2]); }"),
+            Ok(Value::Unit)
+        );
+        assert_eq!(
+            run("fn main() { assert_eq(1 + 1, 3) }"),
+            Err("assertion `left == right` failed\n  left: 2\n right: 3".to_string())
+        );
+        assert_eq!(
+            run("fn main() { assert_eq(\"a\", \"b\", \"strings\") }"),
+            Err("assertion `left == right` failed: strings\n  left: a\n right: b".to_string())
+        );
+        assert_eq!(
+            run("fn main() { assert_eq(1) }"),
+            Err("function `assert_eq` takes 2 or 3 arguments but 1 was supplied".to_string())
+        );
+    }
+
+    #[test]
+    fn shadowed_builtins() {
+        let source = "fn assert(a) { a + 1 }
+        fn main() {
+            let assert_eq = |a, b| a * b;
+            assert(1) + assert_eq(2, 3)
+        }";
+        assert_eq!(run(source), Ok(Value::Int(8, None)));
+    }
+
+    #[test]
+    fn strings() {
+        let source = r#"fn main() {
+            let words = split(trim("  a,bé,c "), ",");
+            [
+                f"{len(words)} {len("bé")} {words[1]}",
+                concat(to_upper("ab"), to_lower("CD")),
+                f"{chars("hi")}",
+            ]
+        }"#;
+        assert_eq!(
+            run_display(source),
+            Ok("[3 2 bé, ABcd, [h, i]]".to_string())
+        );
+        assert_eq!(
+            run("fn main() { trim(1) }"),
+            Err("expected `string`, found `integer`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { len(1.5) }"),
+            Err("expected `string`, `array` or `map`, found `float`".to_string())
+        );
+    }
+
+    #[test]
+    fn math() {
+        let source = "fn main() {
+            [abs(-2), min(3, 4), max(3, 4), pow(2, 10)]
+        }";
+        assert_eq!(run_display(source), Ok("[2, 3, 4, 1024]".to_string()));
+
+        let source = "fn main() {
+            [abs(-2.5), min(1.0, 0.5), sqrt(16.0), pow(2.0, 0.5) * pow(2.0, 0.5), sin(0.0), cos(0.0), tan(0.0)]
+        }";
+        assert_eq!(
+            run_display(source),
+            Ok("[2.5, 0.5, 4.0, 2.0000000000000004, 0.0, 1.0, 0.0]".to_string())
+        );
+
+        assert_eq!(
+            run("fn main() { sqrt(4) }"),
+            Err("expected `float`, found `integer`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { min(1, 2.0) }"),
+            Err(
+                "expected both arguments to be of the same type, found `integer` and `float`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            run("fn main() { pow(2, -1) }"),
+            Err("the exponent must be a non-negative integer, found `-1`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { pow(2, 63) }"),
+            Err("attempt to raise to a power with overflow".to_string())
+        );
+        assert_eq!(
+            run("fn main() { pow(2u8, 8) }"),
+            Err("attempt to raise to a power with overflow".to_string())
+        );
+        assert_eq!(
+            run("fn main() { max(1u8, 2i32) }"),
+            Err("expected both arguments to be of the same type, found `u8` and `i32`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { max(1u8, 300) }"),
+            Err("literal out of range for `u8`".to_string())
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        let source = "fn main() {
+            let mut a = [1];
+            let b = a;
+            push(a, 2);
+            push(a, 3);
+            let last = pop(a);
+            let mut nested = ([0], 1);
+            push(nested.0, last);
+            [a, b, nested.0]
+        }";
+        assert_eq!(run_display(source), Ok("[[1, 2], [1], [0, 3]]".to_string()));
+
+        assert_eq!(
+            run("fn main() { let mut a = []; pop(a) }"),
+            Err("cannot pop from an empty array".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let mut a = 1; push(a, 2) }"),
+            Err("expected `array`, found `integer`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { push([1], 2) }"),
+            Err(
+                "`push` modifies its first argument, which must be a variable or an element of one"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn maps() {
+        let source = "fn main() {
+            let mut m = new_map();
+            insert(m, \"b\", 2);
+            insert(m, \"a\", 1);
+            insert(m, \"b\", 3);
+            let copy = m;
+            let removed = remove(m, \"a\");
+            [f\"{copy}\", f\"{m}\", f\"{keys(copy)} {values(copy)} {len(m)}\", f\"{get(m, \"b\") + removed} {contains_key(m, \"a\")}\"]
+        }";
+        assert_eq!(
+            run_display(source),
+            Ok("[{a: 1, b: 3}, {b: 3}, [a, b] [1, 3] 1, 4 false]".to_string())
+        );
+
+        assert_eq!(
+            run("fn main() { let mut m = new_map(); insert(m, (1, 'a'), true); get(m, (1, 'b')) }"),
+            Err("no entry for the key `(1, b)` in the map".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let mut m = new_map(); insert(m, 1.5, 0) }"),
+            Err("a value of type `float` can't be the key of a map".to_string())
+        );
+        assert_eq!(
+            run("fn f(m: Map<String, i64>) -> i64 { get(m, \"a\") } fn main() { let mut m = new_map(); insert(m, \"a\", 1); f(m) }"),
+            Ok(Value::Int(1, None))
+        );
+        assert_eq!(
+            run("fn f(m: Map<String, i64>) {} fn main() { let mut m = new_map(); insert(m, 1, 1); f(m) }"),
+            Err("mismatched types: expected `Map<String, i64>`, found `map`".to_string())
+        );
+    }
+
+    #[test]
+    fn files() {
+        let dir = std::env::temp_dir().join(format!("lamia-files-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.txt");
+        let path = path.to_str().unwrap().replace('\\', "/");
+
+        let source = format!(
+            "fn main() {{ write_file(\"{path}\", \"hello\"); concat(read_file(\"{path}\"), \"!\") }}"
+        );
+        assert_eq!(run_display(&source), Ok("hello!".to_string()));
+
+        let source = format!("fn main() {{ read_file(\"{path}.missing\") }}");
+        let error = run(&source).unwrap_err();
+        assert!(error.starts_with(&format!("cannot read `{path}.missing`: ")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn print() {
+        assert_eq!(
+            run("fn main() { print(\"\"); println(); eprintln(\"\") }"),
+            Ok(Value::Unit)
+        );
+        assert_eq!(
+            run("fn main() { println(1, 2) }"),
+            Err("function `println` takes 0 or 1 arguments but 2 were supplied".to_string())
+        );
+    }
+}
@@ -0,0 +1,317 @@
+//! Finds the variables a closure refers to from the scope it is created in.
+
+use std::collections::HashSet;
+
+use parser::ast::{
+    AstNode, BlockExpr, ElseBranch, Expr, FieldPattern, IdentPattern, IfExpr,
+    InterpolatedStringPart, MatchExpr, ParamList, Pattern, Stmt,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Capture {
+    pub(crate) name: String,
+    /// Whether the closure assigns to the variable, so it has to share it with its
+    /// environment instead of copying it.
+    pub(crate) by_ref: bool,
+}
+
+/// The free variables of a closure, in the order they are first used.
+///
+/// Names are collected syntactically, so some of them may refer to items rather than
+/// local variables; the caller keeps the ones that resolve to locals.
+pub(crate) fn captures(params: Option<ParamList>, body: &Expr) -> Vec<Capture> {
+    let mut collector = Collector::default();
+    collector.closure(params, Some(body.clone()));
+    collector.captures
+}
+
+#[derive(Default)]
+struct Collector {
+    scopes: Vec<HashSet<String>>,
+    captures: Vec<Capture>,
+}
+
+impl Collector {
+    fn bind(&mut self, name: String) {
+        self.scopes
+            .last_mut()
+            .expect("bindings happen inside a scope")
+            .insert(name);
+    }
+
+    fn use_variable(&mut self, name: &str, by_ref: bool) {
+        if self.scopes.iter().any(|scope| scope.contains(name)) {
+            return;
+        }
+
+        match self
+            .captures
+            .iter_mut()
+            .find(|capture| capture.name == name)
+        {
+            Some(capture) => capture.by_ref |= by_ref,
+            None => self.captures.push(Capture {
+                name: name.to_string(),
+                by_ref,
+            }),
+        }
+    }
+
+    fn closure(&mut self, params: Option<ParamList>, body: Option<Expr>) {
+        let params = params
+            .into_iter()
+            .flat_map(|list| list.params())
+            .filter_map(|param| Some(param.name()?.text().to_string()))
+            .collect();
+
+        self.scopes.push(params);
+        self.expr_opt(body);
+        self.scopes.pop();
+    }
+
+    fn block(&mut self, block: &BlockExpr) {
+        self.scopes.push(HashSet::new());
+        for stmt in block.stmts() {
+            match stmt {
+                Stmt::Let(stmt) => {
+                    self.expr_opt(stmt.initializer());
+                    if let Some(name) = stmt.name() {
+                        self.bind(name.text().to_string());
+                    } else if let Some(pattern) = stmt.pattern() {
+                        self.bind_pattern(&pattern);
+                    }
+                }
+                Stmt::Return(stmt) => self.expr_opt(stmt.value()),
+                Stmt::Expr(stmt) => self.expr_opt(stmt.expr()),
+                Stmt::Mod(_)
+                | Stmt::Use(_)
+                | Stmt::Enum(_)
+                | Stmt::Struct(_)
+                | Stmt::Trait(_)
+                | Stmt::Impl(_) => {}
+            }
+        }
+        self.scopes.pop();
+    }
+
+    fn expr_opt(&mut self, expr: Option<Expr>) {
+        if let Some(expr) = expr {
+            self.expr(&expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::InterpolatedString(string) => {
+                for part in string.parts() {
+                    if let InterpolatedStringPart::Expr(expr) = part {
+                        self.expr(&expr);
+                    }
+                }
+            }
+            Expr::VariableRef(variable) => {
+                let segments = variable.segments().collect::<Vec<_>>();
+                if let [name] = segments.as_slice() {
+                    self.use_variable(name.text(), false);
+                }
+            }
+            Expr::Paren(paren) => self.expr_opt(paren.expr()),
+            Expr::Tuple(tuple) => {
+                for element in tuple.elements() {
+                    self.expr(&element);
+                }
+            }
+            Expr::Field(field) => self.expr_opt(field.receiver()),
+            Expr::Prefix(prefix) => self.expr_opt(prefix.operand()),
+            Expr::Binary(binary) => {
+                let is_assignment = binary.op().is_some_and(|op| op.kind().is_assignment());
+                if is_assignment {
+                    if let Some(name) = binary.lhs().as_ref().and_then(place_root) {
+                        self.use_variable(&name, true);
+                    }
+                }
+                self.expr_opt(binary.lhs());
+                self.expr_opt(binary.rhs());
+            }
+            Expr::Call(call) => {
+                self.expr_opt(call.callee());
+                for arg in call.arg_list().into_iter().flat_map(|list| list.args()) {
+                    self.expr(&arg);
+                }
+            }
+            Expr::MethodCall(call) => {
+                self.expr_opt(call.receiver());
+                for arg in call.arg_list().into_iter().flat_map(|list| list.args()) {
+                    self.expr(&arg);
+                }
+            }
+            Expr::Block(block) => self.block(block),
+            // named functions don't capture anything, they only bind their name
+            Expr::Function(function) => match function.name() {
+                Some(name) => self.bind(name.text().to_string()),
+                None => self.closure(function.param_list(), function.body().map(Expr::Block)),
+            },
+            Expr::Closure(closure) => self.closure(closure.param_list(), closure.body()),
+            Expr::If(if_expr) => self.if_expr(if_expr),
+            Expr::Match(match_expr) => self.match_expr(match_expr),
+            Expr::Array(array) => {
+                for element in array.elements() {
+                    self.expr(&element);
+                }
+            }
+            Expr::ArrayRepeat(repeat) => {
+                self.expr_opt(repeat.value());
+                self.expr_opt(repeat.count());
+            }
+            Expr::Index(index) => {
+                self.expr_opt(index.base());
+                self.expr_opt(index.index());
+            }
+            Expr::Record(record) => {
+                for field in record
+                    .field_list()
+                    .into_iter()
+                    .flat_map(|list| list.fields())
+                {
+                    match (field.expr(), field.name()) {
+                        (Some(expr), _) => self.expr(&expr),
+                        // `field` is shorthand for `field: field`
+                        (None, Some(name)) => self.use_variable(name.text(), false),
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    fn match_expr(&mut self, match_expr: &MatchExpr) {
+        self.expr_opt(match_expr.scrutinee());
+
+        for arm in match_expr
+            .arm_list()
+            .into_iter()
+            .flat_map(|list| list.arms())
+        {
+            self.scopes.push(HashSet::new());
+            if let Some(pattern) = arm.pattern() {
+                self.bind_pattern(&pattern);
+            }
+            if let Some(guard) = arm.guard() {
+                self.expr_opt(guard.condition());
+            }
+            self.expr_opt(arm.expr());
+            self.scopes.pop();
+        }
+    }
+
+    /// Binds the variables of a pattern: plain identifiers and `field` shorthands.
+    fn bind_pattern(&mut self, pattern: &Pattern) {
+        for node in pattern.syntax().descendants() {
+            if let Some(ident) = IdentPattern::cast(node.clone()) {
+                if let Some(name) = ident.name() {
+                    self.bind(name.text().to_string());
+                }
+            } else if let Some(field) = FieldPattern::cast(node) {
+                if let (Some(name), None) = (field.name(), field.pattern()) {
+                    self.bind(name.text().to_string());
+                }
+            }
+        }
+    }
+
+    fn if_expr(&mut self, if_expr: &IfExpr) {
+        self.expr_opt(if_expr.condition());
+        if let Some(block) = if_expr.then_branch() {
+            self.block(&block);
+        }
+        match if_expr.else_branch() {
+            Some(ElseBranch::Block(block)) => self.block(&block),
+            Some(ElseBranch::If(if_expr)) => self.if_expr(&if_expr),
+            None => {}
+        }
+    }
+}
+
+/// The variable an assignment to `place` writes to, looking through indexing and fields.
+fn place_root(place: &Expr) -> Option<String> {
+    match place {
+        Expr::Index(index) => place_root(&index.base()?),
+        Expr::Field(field) => place_root(&field.receiver()?),
+        Expr::Paren(paren) => place_root(&paren.expr()?),
+        Expr::VariableRef(variable) => {
+            let segments = variable.segments().collect::<Vec<_>>();
+            match segments.as_slice() {
+                [name] => Some(name.text().to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::{
+        ast::{AstNode, ClosureExpr},
+        Parser,
+    };
+
+    use super::{captures, Capture};
+
+    fn check(input: &str, expected: &[(&str, bool)]) {
+        let root = Parser::new(input).parse().syntax();
+        let closure = root
+            .descendants()
+            .find_map(ClosureExpr::cast)
+            .expect("input contains a closure");
+
+        let captures = captures(closure.param_list(), &closure.body().unwrap());
+        let expected = expected
+            .iter()
+            .map(|&(name, by_ref)| Capture {
+                name: name.to_string(),
+                by_ref,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(captures, expected);
+    }
+
+    #[test]
+    fn free_variables() {
+        check("|a| a + b * c", &[("b", false), ("c", false)]);
+        check("|| { let a = 1; a + b }", &[("b", false)]);
+        check("|| { let a = a; a }", &[("a", false)]);
+        check("|| m::f(x)", &[("x", false)]);
+        check(r#"|| f"{a} {b + 1}""#, &[("a", false), ("b", false)]);
+    }
+
+    #[test]
+    fn assigned_variables_are_captured_by_reference() {
+        check("|| count = count + 1", &[("count", true)]);
+        check(
+            "|| { let y = x; grid[0][1] = y; }",
+            &[("x", false), ("grid", true)],
+        );
+    }
+
+    #[test]
+    fn nested_closures() {
+        check("|a| |b| a + b + c", &[("c", false)]);
+        check("|| fn() { n = 1; }", &[("n", true)]);
+        check("|| { fn f(x) { x + y } f(1) }", &[]);
+    }
+
+    #[test]
+    fn record_shorthands() {
+        check("|| E::V { a, b: c }", &[("a", false), ("c", false)]);
+    }
+
+    #[test]
+    fn match_bindings() {
+        check(
+            "|| match a { b if b > c => b, _ => b }",
+            &[("a", false), ("c", false), ("b", false)],
+        );
+    }
+}
@@ -0,0 +1,580 @@
+//! Embedding the interpreter in a Rust program.
+
+use std::{
+    any::TypeId, collections::hash_map::Entry, error, fmt, io, path::PathBuf, rc::Rc,
+    time::Duration,
+};
+
+use checker::HostNames;
+use db::Database;
+use modules::{prelude::Builtin, Def};
+use source_map::{Diagnostic, FileId, Severity, SourceMap, Span};
+
+use crate::{
+    host::{FromValue, Host, HostType, IntoArgs, IntoHostFunction, IntoHostMethod},
+    limits::Limits,
+    Interpreter, Limit, RuntimeError,
+};
+
+/// Why using an [`Engine`] failed.
+#[derive(Debug)]
+pub enum EngineError {
+    /// The file of a program couldn't be read.
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    /// The program has errors, which are reported before it runs. Render them with the
+    /// engine's [`SourceMap`].
+    Diagnostics(Vec<Diagnostic>),
+    /// A host function was registered under the name of a function of the prelude.
+    PreludeName(String),
+    /// A function, type or method was registered twice under the same name, such as `f`,
+    /// `Counter` or `Counter::get`.
+    AlreadyRegistered(String),
+    /// A function was called before a program was loaded.
+    NotLoaded,
+    FunctionNotFound(String),
+    ArityMismatch {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    /// A function returned a value which can't be converted to the requested type.
+    TypeMismatch {
+        expected: String,
+        found: String,
+    },
+    Runtime(RuntimeError),
+    /// The program was stopped at `span` for exceeding a limit set with the engine.
+    ResourceLimitExceeded {
+        limit: Limit,
+        span: Span,
+    },
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "cannot read {}: {error}", path.display()),
+            Self::Diagnostics(diagnostics) => {
+                let errors = diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.severity == Severity::Error)
+                    .count();
+                write!(
+                    f,
+                    "the program has {errors} error{}",
+                    if errors == 1 { "" } else { "s" }
+                )
+            }
+            Self::PreludeName(name) => write!(
+                f,
+                "`{name}` is a function of the prelude, so it can't be registered"
+            ),
+            Self::AlreadyRegistered(name) => write!(f, "`{name}` is already registered"),
+            Self::NotLoaded => f.write_str("no program is loaded"),
+            Self::FunctionNotFound(name) => write!(f, "cannot find function `{name}`"),
+            Self::ArityMismatch {
+                function,
+                expected,
+                found,
+            } => write!(
+                f,
+                "function `{function}` takes {expected} argument{} but {found} {} supplied",
+                if *expected == 1 { "" } else { "s" },
+                if *found == 1 { "was" } else { "were" },
+            ),
+            Self::TypeMismatch { expected, found } => {
+                write!(
+                    f,
+                    "mismatched types: expected `{expected}`, found `{found}`"
+                )
+            }
+            Self::Runtime(error) => write!(f, "{error}"),
+            Self::ResourceLimitExceeded { limit, .. } => write!(f, "{limit}"),
+        }
+    }
+}
+
+impl error::Error for EngineError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<RuntimeError> for EngineError {
+    fn from(error: RuntimeError) -> Self {
+        match error.limit {
+            Some(limit) => Self::ResourceLimitExceeded {
+                limit,
+                span: error.span,
+            },
+            None => Self::Runtime(error),
+        }
+    }
+}
+
+/// Runs lamia programs inside a Rust program, which can register functions and types for
+/// them to use and call the functions they define. Untrusted programs can be run with limits
+/// on the resources they use, which apply to each call.
+#[derive(Debug, Default)]
+pub struct Engine {
+    db: Database,
+    /// The file holding the source given to [`Engine::load`], which each call replaces.
+    source: Option<FileId>,
+    host: Rc<Host>,
+    limits: Limits,
+    interpreter: Option<Interpreter>,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The files of the loaded program, which its diagnostics and errors refer to.
+    pub fn source_map(&self) -> &SourceMap {
+        self.db.source_map()
+    }
+
+    /// Makes the function `name` callable from lamia code, unless a local or an item of the
+    /// same name shadows it. The functions of the prelude can't be replaced.
+    ///
+    /// Like the other registration methods, this fails rather than replacing what was
+    /// already registered under the same name.
+    pub fn register_fn<Args>(
+        &mut self,
+        name: &str,
+        function: impl IntoHostFunction<Args>,
+    ) -> Result<&mut Self, EngineError> {
+        if Builtin::from_name(name).is_some() {
+            return Err(EngineError::PreludeName(name.to_string()));
+        }
+        match self.host_mut().functions.entry(name.to_string()) {
+            Entry::Occupied(_) => Err(EngineError::AlreadyRegistered(name.to_string())),
+            Entry::Vacant(entry) => {
+                entry.insert(function.into_host_function());
+                Ok(self)
+            }
+        }
+    }
+
+    /// Makes the name of `T` usable in type annotations.
+    pub fn register_type<T: HostType>(&mut self) -> Result<&mut Self, EngineError> {
+        match self.host_mut().types.entry(T::NAME) {
+            Entry::Occupied(_) => Err(EngineError::AlreadyRegistered(T::NAME.to_string())),
+            Entry::Vacant(entry) => {
+                entry.insert(TypeId::of::<T>());
+                Ok(self)
+            }
+        }
+    }
+
+    /// Adds the method `name` to the values of `T`, which takes the receiver first.
+    pub fn register_method<T: HostType, Args>(
+        &mut self,
+        name: &str,
+        method: impl IntoHostMethod<T, Args>,
+    ) -> Result<&mut Self, EngineError> {
+        let key = (TypeId::of::<T>(), name.to_string());
+        match self.host_mut().methods.entry(key) {
+            Entry::Occupied(_) => Err(EngineError::AlreadyRegistered(format!(
+                "{}::{name}",
+                T::NAME
+            ))),
+            Entry::Vacant(entry) => {
+                entry.insert(method.into_host_method());
+                Ok(self)
+            }
+        }
+    }
+
+    /// Limits the number of expressions evaluated by a call.
+    pub fn set_fuel(&mut self, fuel: u64) -> &mut Self {
+        self.limits.fuel = Some(fuel);
+        self
+    }
+
+    /// Limits the number of nested calls, which is 20,000 by default. The native stack grows
+    /// with the recursion, so that only the memory of the process bounds deeper limits.
+    pub fn set_max_call_depth(&mut self, depth: usize) -> &mut Self {
+        self.limits.call_depth = Some(depth);
+        self
+    }
+
+    /// Limits the number of bytes a call can allocate for strings, arrays, tuples and maps.
+    pub fn set_max_memory(&mut self, bytes: usize) -> &mut Self {
+        self.limits.memory = Some(bytes);
+        self
+    }
+
+    /// Limits how long a call can run. Time spent in host functions counts, but they aren't
+    /// interrupted.
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.limits.time = Some(timeout);
+        self
+    }
+
+    /// Makes calling the functions of the prelude that print or access files an error.
+    pub fn disable_io(&mut self) -> &mut Self {
+        self.limits.io = false;
+        self
+    }
+
+    /// Loads the program in `source`, replacing the loaded one. Its modules declared with
+    /// `mod name;` are read relatively to the current directory. The host functions and
+    /// types it uses must be registered before.
+    pub fn load(&mut self, source: &str) -> Result<(), EngineError> {
+        let root = match self.source {
+            Some(root) => {
+                self.db.set_file_text(root, source);
+                root
+            }
+            None => *self.source.insert(self.db.add_file("main.lamia", source)),
+        };
+        self.load_root(root)
+    }
+
+    /// Loads the program rooted at the file `path`, replacing the loaded one.
+    pub fn load_file(&mut self, path: impl Into<PathBuf>) -> Result<(), EngineError> {
+        let path = path.into();
+        let root = match self.db.load_file(&path) {
+            Ok(root) => root,
+            Err(error) => return Err(EngineError::Io { path, error }),
+        };
+        self.load_root(root)
+    }
+
+    fn load_root(&mut self, root: FileId) -> Result<(), EngineError> {
+        self.interpreter = None;
+
+        let (module_tree, mut diagnostics) = modules::build(&mut self.db, root);
+        let host = HostNames {
+            functions: self.host.functions.keys().cloned().collect(),
+            types: self
+                .host
+                .types
+                .keys()
+                .map(|name| name.to_string())
+                .collect(),
+        };
+        diagnostics.extend(checker::check_with_host(&self.db, &module_tree, &host));
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+        {
+            return Err(EngineError::Diagnostics(diagnostics));
+        }
+
+        self.interpreter = Some(Interpreter::new(&self.db, module_tree));
+        Ok(())
+    }
+
+    /// Calls the function at `path` of the loaded program, such as `fib` or `math::fib`,
+    /// converting the arguments and the value it returns.
+    pub fn call<R: FromValue>(
+        &mut self,
+        path: &str,
+        args: impl IntoArgs,
+    ) -> Result<R, EngineError> {
+        let interpreter = self.interpreter.as_mut().ok_or(EngineError::NotLoaded)?;
+        interpreter.host = self.host.clone();
+        interpreter.limits = self.limits.clone();
+
+        let segments = path.split("::").collect::<Vec<_>>();
+        let root = interpreter.module_tree.root();
+        let function = match interpreter.module_tree.resolve_path(root, &segments) {
+            Ok(Def::Function(span)) => interpreter.function(span),
+            _ => None,
+        };
+        let Some(function) = function else {
+            return Err(EngineError::FunctionNotFound(path.to_string()));
+        };
+
+        let args = args.into_args();
+        if function.params.len() != args.len() {
+            return Err(EngineError::ArityMismatch {
+                function: path.to_string(),
+                expected: function.params.len(),
+                found: args.len(),
+            });
+        }
+
+        interpreter.reset_usage();
+        let value = interpreter.call(&segments, args)?;
+        R::from_value(&value).ok_or_else(|| EngineError::TypeMismatch {
+            expected: R::type_name(),
+            found: value.type_name().to_string(),
+        })
+    }
+
+    fn host_mut(&mut self) -> &mut Host {
+        Rc::make_mut(&mut self.host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Engine, EngineError};
+    use crate::{HostType, IntoArgs, Limit, Value};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Counter {
+        count: i64,
+    }
+
+    impl HostType for Counter {
+        const NAME: &'static str = "Counter";
+    }
+
+    fn load(engine: &mut Engine, source: &str) {
+        if let Err(error) = engine.load(source) {
+            panic!("{error}");
+        }
+    }
+
+    #[test]
+    fn call_functions() {
+        let mut engine = Engine::new();
+        engine.register_fn("add", |a: i64, b: i64| a + b).unwrap();
+        engine
+            .register_fn("greet", |name: String| format!("hello {name}"))
+            .unwrap();
+        engine
+            .register_fn("sum", |values: Vec<f64>| values.iter().sum::<f64>())
+            .unwrap();
+        load(
+            &mut engine,
+            "fn fib(n: i64) -> i64 { if n < 2 { n } else { add(fib(n - 1), fib(n - 2)) } }
+            mod text { pub fn hi() -> String { greet(\"lamia\") } }
+            fn total(a: f64) -> f64 { sum([a, 0.5]) }",
+        );
+
+        assert_eq!(engine.call::<i64>("fib", (10,)).unwrap(), 55);
+        assert_eq!(
+            engine.call::<String>("text::hi", ()).unwrap(),
+            "hello lamia"
+        );
+        assert_eq!(engine.call::<f64>("total", (1.0,)).unwrap(), 1.5);
+        assert_eq!(
+            engine.call::<Value>("total", (1.0,)).unwrap(),
+            Value::Float(1.5)
+        );
+    }
+
+    #[test]
+    fn host_types() {
+        let mut engine = Engine::new();
+        engine
+            .register_type::<Counter>()
+            .unwrap()
+            .register_method("add", |counter: &mut Counter, n: i64| counter.count += n)
+            .unwrap()
+            .register_method("get", |counter: &mut Counter| counter.count)
+            .unwrap();
+        engine
+            .register_fn("counter", |count: i64| Counter { count })
+            .unwrap();
+        load(
+            &mut engine,
+            "fn bump(c: Counter) -> Counter { c.add(2); c }
+            fn run() -> i64 { let c = counter(1); bump(c); c.add(3); c.get() }",
+        );
+
+        assert_eq!(engine.call::<i64>("run", ()).unwrap(), 6);
+        assert_eq!(
+            engine
+                .call::<Counter>("bump", (Counter { count: 0 },))
+                .unwrap(),
+            Counter { count: 2 }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut engine = Engine::new();
+        assert!(matches!(
+            engine.call::<()>("main", ()),
+            Err(EngineError::NotLoaded)
+        ));
+
+        engine
+            .register_type::<Counter>()
+            .unwrap()
+            .register_method("get", |counter: &mut Counter| counter.count)
+            .unwrap();
+        engine.register_fn("double", |n: i64| n * 2).unwrap();
+        assert!(matches!(
+            engine.load("fn main() { let a = 1; a = 2; }"),
+            Err(EngineError::Diagnostics(diagnostics)) if diagnostics.len() == 1
+        ));
+
+        load(
+            &mut engine,
+            "fn id(x) { x }
+            fn wrong_type() { double(\"a\") }
+            fn wrong_arity() { double(1, 2) }
+            fn wrong_method(c: Counter) { c.reset() }
+            fn wrong_annotation(c: Counter) {}",
+        );
+        let error = |result: Result<Value, EngineError>| result.unwrap_err().to_string();
+
+        assert_eq!(
+            error(engine.call("missing", ())),
+            "cannot find function `missing`"
+        );
+        assert_eq!(
+            error(engine.call("id", (1, 2))),
+            "function `id` takes 1 argument but 2 were supplied"
+        );
+        assert_eq!(
+            engine.call::<String>("id", (1,)).unwrap_err().to_string(),
+            "mismatched types: expected `string`, found `integer`"
+        );
+        assert_eq!(
+            error(engine.call("wrong_type", ())),
+            "expected `integer`, found `string`"
+        );
+        assert_eq!(
+            error(engine.call("wrong_arity", ())),
+            "function `double` takes 1 argument but 2 were supplied"
+        );
+        assert_eq!(
+            error(engine.call("wrong_method", (Counter { count: 0 },))),
+            "no method named `reset` found for `Counter`"
+        );
+        assert_eq!(
+            error(engine.call("wrong_annotation", (1,))),
+            "mismatched types: expected `Counter`, found `integer`"
+        );
+    }
+
+    /// The limit exceeded by calling `function`, and the code where it was exceeded.
+    fn limit_exceeded(engine: &mut Engine, function: &str, args: impl IntoArgs) -> (Limit, String) {
+        match engine.call::<Value>(function, args) {
+            Err(EngineError::ResourceLimitExceeded { limit, span }) => {
+                let text = &engine.source_map().text(span.file)[span.range];
+                (limit, text.to_string())
+            }
+            other => panic!("expected a limit to be exceeded, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn limits() {
+        let source = "fn count(n: i64) -> i64 { if n == 0 { 0 } else { 1 + count(n - 1) } }
+            fn big() { [0; 1000000] }
+            fn copy() { let a = [0; 100]; let mut b = a; push(b, 1); }
+            fn double(s: String, n: i64) -> String { if n == 0 { s } else { double(s + s, n - 1) } }
+            fn fib(n: i64) -> i64 { if n < 2 { n } else { fib(n - 1) + fib(n - 2) } }
+            fn shout() { println(\"hi\") }";
+
+        let mut engine = Engine::new();
+        engine.set_fuel(200);
+        load(&mut engine, source);
+        assert_eq!(engine.call::<i64>("count", (10,)).unwrap(), 10);
+        // the fuel is refilled for every call
+        assert_eq!(engine.call::<i64>("count", (10,)).unwrap(), 10);
+        assert_eq!(
+            limit_exceeded(&mut engine, "count", (20,)).0,
+            Limit::Fuel(200)
+        );
+
+        let mut engine = Engine::new();
+        engine.set_max_call_depth(50);
+        load(&mut engine, source);
+        assert_eq!(engine.call::<i64>("count", (49,)).unwrap(), 49);
+        assert_eq!(
+            limit_exceeded(&mut engine, "count", (50,)),
+            (Limit::CallDepth(50), "count(n - 1)".to_string())
+        );
+
+        let mut engine = Engine::new();
+        engine.set_max_memory(4096);
+        load(&mut engine, source);
+        assert_eq!(
+            limit_exceeded(&mut engine, "big", ()),
+            (Limit::Memory(4096), "[0; 1000000]".to_string())
+        );
+        // pushing to an array shared with another one copies it
+        assert_eq!(
+            limit_exceeded(&mut engine, "copy", ()),
+            (Limit::Memory(4096), "push(b, 1)".to_string())
+        );
+        assert_eq!(
+            limit_exceeded(&mut engine, "double", ("ab", 20)),
+            (Limit::Memory(4096), "s + s".to_string())
+        );
+
+        let mut engine = Engine::new();
+        engine.set_timeout(Duration::from_millis(20)).disable_io();
+        load(&mut engine, source);
+        assert_eq!(
+            limit_exceeded(&mut engine, "fib", (40,)).0,
+            Limit::Time(Duration::from_millis(20))
+        );
+        assert_eq!(
+            limit_exceeded(&mut engine, "shout", ()),
+            (Limit::Io, "println(\"hi\")".to_string())
+        );
+        assert_eq!(
+            engine.call::<()>("shout", ()).unwrap_err().to_string(),
+            "input and output are disabled"
+        );
+    }
+
+    #[test]
+    fn name_collisions() {
+        let mut engine = Engine::new();
+        let error = engine.register_fn("len", |n: i64| n).unwrap_err();
+        assert!(matches!(&error, EngineError::PreludeName(name) if name == "len"));
+        assert_eq!(
+            error.to_string(),
+            "`len` is a function of the prelude, so it can't be registered"
+        );
+
+        engine
+            .register_fn("f", |n: i64| n)
+            .unwrap()
+            .register_type::<Counter>()
+            .unwrap()
+            .register_method("get", |counter: &mut Counter| counter.count)
+            .unwrap();
+        let error = |result: Result<&mut Engine, EngineError>| result.unwrap_err().to_string();
+        assert_eq!(
+            error(engine.register_fn("f", |n: i64| n + 1)),
+            "`f` is already registered"
+        );
+        assert_eq!(
+            error(engine.register_type::<Counter>()),
+            "`Counter` is already registered"
+        );
+        assert_eq!(
+            error(engine.register_method("get", |_: &mut Counter| 0)),
+            "`Counter::get` is already registered"
+        );
+
+        // the first registrations are kept
+        load(&mut engine, "fn main(c: Counter) -> i64 { f(1) + c.get() }");
+        assert_eq!(
+            engine.call::<i64>("main", (Counter { count: 2 },)).unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn reload() {
+        let mut engine = Engine::new();
+        load(&mut engine, "fn main() -> i64 { 1 }");
+        load(&mut engine, "fn main() -> i64 { 2 }");
+        assert_eq!(engine.call::<i64>("main", ()).unwrap(), 2);
+        // the source replaces the text of the same file
+        assert_eq!(engine.source_map().files().count(), 1);
+    }
+}
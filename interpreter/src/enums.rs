@@ -0,0 +1,456 @@
+//! Creating enum values from their variants and checking the types of their fields.
+
+use std::rc::Rc;
+
+use modules::{Def, ResolveError, VariantFields};
+use parser::{
+    ast::{
+        AstNode, CallExpr, EnumStmt, GenericArgList, RecordExpr, RecordFieldList, StructStmt, Type,
+        VariableRef, Variant,
+    },
+    syntax::{SyntaxNode, SyntaxToken},
+};
+use source_map::Span;
+
+use crate::{
+    generic_bounds,
+    types::{fit, generic_count_mismatch, generic_params, mismatch, unify},
+    Enum, EnumValue, Interpreter, RuntimeError, Ty, Unwind, Value,
+};
+
+type Result<T> = std::result::Result<T, Unwind>;
+
+impl Interpreter {
+    /// Returns the enum or the struct whose name is at `span`.
+    pub(crate) fn enum_def(&mut self, span: Span) -> Rc<Enum> {
+        self.enums
+            .entry(span)
+            .or_insert_with(|| {
+                let data = self.module_tree.enum_data(span);
+                let generic_params = self
+                    .files
+                    .get(&span.file)
+                    .and_then(|root| root.covering_element(span.range).into_token())
+                    .and_then(|name| name.parent())
+                    .and_then(|item| match EnumStmt::cast(item.clone()) {
+                        Some(stmt) => stmt.generic_param_list(),
+                        None => StructStmt::cast(item)?.generic_param_list(),
+                    });
+                Rc::new(Enum {
+                    span,
+                    name: data.name.clone(),
+                    module: data.module,
+                    generics: data.generics.clone(),
+                    bounds: generic_bounds(generic_params),
+                    variants: data.variants.clone(),
+                    is_struct: data.is_struct,
+                })
+            })
+            .clone()
+    }
+
+    /// Creates a value of a variant, checking the bounds of the enum's generic parameters.
+    /// The generic parameters that couldn't be inferred from the fields take the types the
+    /// checker inferred, if any, or else are unknown.
+    pub(crate) fn new_variant(
+        &mut self,
+        enum_def: Rc<Enum>,
+        variant: usize,
+        fields: Vec<Value>,
+        mut type_args: Vec<Option<Ty>>,
+        node: &SyntaxNode,
+    ) -> Result<Value> {
+        let span = self.span(node);
+        self.fill_type_args(&mut type_args, span);
+        let type_args = type_args
+            .into_iter()
+            .map(|ty| ty.unwrap_or(Ty::Unknown))
+            .collect::<Vec<_>>();
+        let file = enum_def.span.file;
+        if let Some(message) =
+            self.check_bounds(&enum_def.bounds, &type_args, file, enum_def.module)?
+        {
+            return Err(self.error(node, message));
+        }
+
+        Ok(Value::Enum(Rc::new(EnumValue {
+            enum_def,
+            variant,
+            fields: fields.into_boxed_slice(),
+            type_args,
+        })))
+    }
+
+    /// Evaluates `Variant(a, b)`, which creates a value of a tuple variant.
+    pub(crate) fn eval_tuple_variant(
+        &mut self,
+        call: &CallExpr,
+        callee: &VariableRef,
+        span: Span,
+        variant: usize,
+    ) -> Result<Value> {
+        let enum_def = self.enum_def(span);
+        let path = variant_path(&enum_def, variant);
+
+        let arity = match enum_def.variants[variant].fields {
+            VariantFields::Tuple(arity) => arity,
+            VariantFields::Unit => {
+                let message = format!("expected function, found unit variant `{path}`");
+                return Err(self.error(call.syntax(), message));
+            }
+            VariantFields::Record(_) => {
+                let message = format!("expected function, found struct variant `{path}`");
+                return Err(self.error(call.syntax(), message));
+            }
+        };
+
+        let args = call
+            .arg_list()
+            .into_iter()
+            .flat_map(|list| list.args())
+            .collect::<Vec<_>>();
+        if args.len() != arity {
+            return Err(self.error(
+                call.syntax(),
+                format!(
+                    "variant `{path}` takes {arity} argument{} but {} {} supplied",
+                    if arity == 1 { "" } else { "s" },
+                    args.len(),
+                    if args.len() == 1 { "was" } else { "were" },
+                ),
+            ));
+        }
+
+        let types = self
+            .variant_syntax(&enum_def, variant)
+            .and_then(|variant| variant.tuple_fields())
+            .into_iter()
+            .flat_map(|list| list.types())
+            .collect::<Vec<_>>();
+
+        let mut type_args = self.explicit_type_args(&enum_def, callee.generic_arg_list())?;
+        let mut fields = Vec::with_capacity(arity);
+        for (arg, ty) in args.iter().zip(types) {
+            let mut value = self.eval_expr(arg)?;
+            self.check_field_type(&enum_def, &ty, &mut value, arg.syntax(), &mut type_args)?;
+            fields.push(value);
+        }
+
+        self.new_variant(enum_def, variant, fields, type_args, call.syntax())
+    }
+
+    /// Evaluates `Variant { a: 1, b }`, which creates a value of a variant with named fields.
+    pub(crate) fn eval_record(&mut self, record: &RecordExpr) -> Result<Value> {
+        let segments = record
+            .segments()
+            .map(|segment| segment.text().to_string())
+            .collect::<Vec<_>>();
+        let path = segments.join("::");
+
+        let module = self.frame().module;
+        let names = segments.iter().map(String::as_str).collect::<Vec<_>>();
+        let (span, variant) = match self.module_tree.resolve_path(module, &names) {
+            Ok(Def::Variant(span, variant)) => (span, variant),
+            Ok(Def::Struct(span)) => (span, 0),
+            Err(ResolveError::Private(index)) => {
+                let message = format!("`{}` is private", segments[index]);
+                return Err(self.error(record.syntax(), message));
+            }
+            _ => {
+                let message = format!("cannot find struct or variant `{path}` in this scope");
+                return Err(self.error(record.syntax(), message));
+            }
+        };
+
+        let enum_def = self.enum_def(span);
+        let VariantFields::Record(names) = &enum_def.variants[variant].fields else {
+            let kind = match enum_def.variants[variant].fields {
+                VariantFields::Unit => "unit",
+                _ => "tuple",
+            };
+            let message = format!("expected struct variant, found {kind} variant `{path}`");
+            return Err(self.error(record.syntax(), message));
+        };
+        let declared = self.record_fields_syntax(&enum_def, variant);
+        let mut type_args = self.explicit_type_args(&enum_def, record.generic_arg_list())?;
+
+        let mut fields = vec![None; names.len()];
+        for field in record
+            .field_list()
+            .into_iter()
+            .flat_map(|list| list.fields())
+        {
+            let Some(name) = field.name() else {
+                continue;
+            };
+            let Some(index) = names.iter().position(|other| other == name.text()) else {
+                let kind = if enum_def.is_struct {
+                    "struct"
+                } else {
+                    "variant"
+                };
+                let message = format!("{kind} `{path}` has no field named `{name}`");
+                return Err(self.error(field.syntax(), message));
+            };
+            if fields[index].is_some() {
+                let message = format!("field `{name}` specified more than once");
+                return Err(self.error(field.syntax(), message));
+            }
+
+            let mut value = match field.expr() {
+                Some(expr) => self.eval_expr(&expr)?,
+                None => match self.lookup_local(name.text()) {
+                    Some(variable) => variable.borrow().clone(),
+                    None => {
+                        let message = format!("cannot find value `{name}` in this scope");
+                        return Err(self.error(field.syntax(), message));
+                    }
+                },
+            };
+
+            let ty = declared
+                .iter()
+                .flat_map(|list| list.fields())
+                .find(|declared| declared.name().is_some_and(|n| n.text() == name.text()))
+                .and_then(|declared| declared.ty());
+            if let Some(ty) = ty {
+                let node = field
+                    .expr()
+                    .map_or_else(|| field.syntax().clone(), |expr| expr.syntax().clone());
+                self.check_field_type(&enum_def, &ty, &mut value, &node, &mut type_args)?;
+            }
+            fields[index] = Some(value);
+        }
+
+        let missing = names
+            .iter()
+            .zip(&fields)
+            .filter(|(_, value)| value.is_none())
+            .map(|(name, _)| format!("`{name}`"))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let message = format!(
+                "missing field{} {} in initializer of `{path}`",
+                if missing.len() == 1 { "" } else { "s" },
+                missing.join(", ")
+            );
+            return Err(self.error(record.syntax(), message));
+        }
+
+        self.new_variant(
+            enum_def,
+            variant,
+            fields.into_iter().flatten().collect(),
+            type_args,
+            record.syntax(),
+        )
+    }
+
+    /// The declaration of a variant, to read the types of its fields.
+    fn variant_syntax(&self, enum_def: &Enum, variant: usize) -> Option<Variant> {
+        self.variant_name(enum_def, variant)?
+            .parent()
+            .and_then(Variant::cast)
+    }
+
+    /// The declared fields of a record variant or of a struct.
+    fn record_fields_syntax(&self, enum_def: &Enum, variant: usize) -> Option<RecordFieldList> {
+        let item = self.variant_name(enum_def, variant)?.parent()?;
+        match Variant::cast(item.clone()) {
+            Some(variant) => variant.record_fields(),
+            None => StructStmt::cast(item)?.record_fields(),
+        }
+    }
+
+    fn variant_name(&self, enum_def: &Enum, variant: usize) -> Option<SyntaxToken> {
+        let span = enum_def.variants[variant].span;
+        let root = self.files.get(&span.file)?;
+        root.covering_element(span.range).into_token()
+    }
+
+    /// Checks that a field of a variant of `enum_def` holds a value of its declared type,
+    /// inferring the types of the enum's generic parameters.
+    fn check_field_type(
+        &mut self,
+        enum_def: &Enum,
+        annotation: &Type,
+        value: &mut Value,
+        node: &SyntaxNode,
+        bindings: &mut [Option<Ty>],
+    ) -> Result<()> {
+        let env = generic_params(&enum_def.generics).collect::<Vec<_>>();
+        let ty = self
+            .lower_type(annotation, enum_def.module, &env)
+            .map_err(|message| {
+                let span = Span::new(enum_def.span.file, annotation.syntax().text_range());
+                RuntimeError::new(span, message)
+            })?;
+
+        if !unify(value, &ty, bindings) {
+            let message = mismatch(annotation, &ty, bindings, &env, value);
+            return Err(self.error(node, message));
+        }
+        fit(value, &ty, bindings).map_err(|message| self.error(node, message))
+    }
+
+    /// The types of the generic parameters of an enum given in the path to one of its
+    /// variants, as in `Option::<i64>::None`, or `None` for the ones to infer.
+    pub(crate) fn explicit_type_args(
+        &mut self,
+        enum_def: &Enum,
+        list: Option<GenericArgList>,
+    ) -> Result<Vec<Option<Ty>>> {
+        let Some(list) = list else {
+            return Ok(vec![None; enum_def.generics.len()]);
+        };
+
+        let (module, env) = (self.frame().module, self.frame().type_env.clone());
+        let types = self
+            .lower_types(list.types(), module, &env)
+            .map_err(|message| self.error(list.syntax(), message))?;
+        if types.len() != enum_def.generics.len() {
+            let message = generic_count_mismatch(
+                enum_def.kind(),
+                &enum_def.name,
+                enum_def.generics.len(),
+                types.len(),
+            );
+            return Err(self.error(list.syntax(), message));
+        }
+        Ok(types.into_iter().map(Some).collect())
+    }
+}
+
+/// `Enum::Variant`, to refer to a variant in error messages, or the name of a struct.
+pub(crate) fn variant_path(enum_def: &Enum, variant: usize) -> String {
+    if enum_def.is_struct {
+        return enum_def.name.clone();
+    }
+    format!("{}::{}", enum_def.name, enum_def.variants[variant].name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::run_display;
+
+    #[test]
+    fn construct_variants() {
+        let source = "
+            enum Shape { Circle(f64), Rect { w: f64, h: f64 }, Empty }
+            fn main() {
+                let h = 2.0;
+                [Shape::Circle(1.0), Shape::Rect { h, w: 0.5 }, Shape::Empty]
+            }
+        ";
+        assert_eq!(
+            run_display(source),
+            Ok("[Circle(1.0), Rect { w: 0.5, h: 2.0 }, Empty]".to_string())
+        );
+    }
+
+    #[test]
+    fn variant_equality() {
+        let source = "
+            mod m { pub enum E { A(i32), B } }
+            use m::E;
+            use m::E::B;
+            fn main() { [E::A(1) == E::A(1), E::A(1) == E::A(2), B == E::B, E::A(1) == B] }
+        ";
+        assert_eq!(
+            run_display(source),
+            Ok("[true, false, true, false]".to_string())
+        );
+    }
+
+    #[test]
+    fn payload_types() {
+        let source = "
+            enum Tree { Leaf(i64), Node([Tree; 2]) }
+            fn main() { Tree::Node([Tree::Leaf(1), Tree::Leaf(2)]) }
+        ";
+        assert_eq!(
+            run_display(source),
+            Ok("Node([Leaf(1), Leaf(2)])".to_string())
+        );
+
+        assert_eq!(
+            run_display("enum S { C(f64) } fn main() { S::C(1) }"),
+            Err("mismatched types: expected `f64`, found `integer`".to_string())
+        );
+        assert_eq!(
+            run_display("enum A { X } enum B { Y(A) } fn main() { B::Y(B::Y(A::X)) }"),
+            Err("mismatched types: expected `A`, found `B`".to_string())
+        );
+        assert_eq!(
+            run_display("enum S { R { a: [u8] } } fn main() { S::R { a: [1, 'x'] } }"),
+            Err("mismatched types: expected `[u8]`, found `array`".to_string())
+        );
+        assert_eq!(
+            run_display("enum S { C(Foo) } fn main() { S::C(1) }"),
+            Err("cannot find type `Foo` in this scope".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_constructions() {
+        let cases = [
+            (
+                "S::C(1, 2)",
+                "variant `S::C` takes 1 argument but 2 were supplied",
+            ),
+            ("S::C", "expected value, found tuple variant `S::C`"),
+            ("S::R", "expected value, found struct variant `S::R`"),
+            ("S::U(1)", "expected function, found unit variant `S::U`"),
+            (
+                "S::C { a: 1 }",
+                "expected struct variant, found tuple variant `S::C`",
+            ),
+            (
+                "S::R { a: 1, b: 2 }",
+                "variant `S::R` has no field named `b`",
+            ),
+            ("S::R { a: 1, a: 2 }", "field `a` specified more than once"),
+            ("S::R {}", "missing field `a` in initializer of `S::R`"),
+            ("S", "expected value, found enum `S`"),
+        ];
+
+        for (expr, message) in cases {
+            let source = format!("enum S {{ C(i32), R {{ a: i32 }}, U }} fn main() {{ {expr} }}");
+            assert_eq!(run_display(&source), Err(message.to_string()), "{expr}");
+        }
+    }
+
+    #[test]
+    fn structs() {
+        let source = "
+            struct Pair<T> { first: T, second: T }
+            impl<T> Pair<T> {
+                fn swap(self) -> Pair<T> { Pair { first: self.second, second: self.first } }
+            }
+            fn main() {
+                let mut p = Pair { first: 1, second: 2 }.swap();
+                p.first = p.first * 10;
+                match p { Pair { first, second } => [first, second, p.second] }
+            }
+        ";
+        assert_eq!(run_display(source), Ok("[20, 1, 1]".to_string()));
+        assert_eq!(
+            run_display("struct P { x: i64 } fn main() { P { x: 1 } }"),
+            Ok("P { x: 1 }".to_string())
+        );
+
+        let cases = [
+            ("P { x: 1, y: 2 }", "struct `P` has no field named `y`"),
+            ("P { x: 1 }.y", "no field `y` on type `P`"),
+            ("P", "expected value, found struct `P`"),
+            (
+                "P { x: 'c' }",
+                "mismatched types: expected `i64`, found `char`",
+            ),
+        ];
+        for (expr, message) in cases {
+            let source = format!("struct P {{ x: i64 }} fn main() {{ {expr} }}");
+            assert_eq!(run_display(&source), Err(message.to_string()), "{expr}");
+        }
+    }
+}
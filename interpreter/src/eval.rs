@@ -0,0 +1,1136 @@
+use std::{
+    cell::{OnceCell, RefCell},
+    mem,
+    rc::Rc,
+};
+
+use modules::{prelude::Builtin, Def, ResolveError, VariantFields};
+use parser::{
+    ast::{
+        ArrayExpr, ArrayRepeatExpr, AstNode, BinaryExpr, BlockExpr, CallExpr, ClosureExpr,
+        ElseBranch, Expr, FieldExpr, FunctionExpr, GenericArgList, GenericParamList, IfExpr,
+        IndexExpr, InterpolatedString, InterpolatedStringPart, MatchArm, MatchExpr, ParamList,
+        PrefixExpr, Stmt, TupleExpr, Type, VariableRef,
+    },
+    syntax::{SyntaxKind, SyntaxNode, SyntaxToken},
+};
+use source_map::Span;
+
+use crate::{
+    capture, generic_bounds, generic_names, limits, literal, ops,
+    types::{fit, generic_count_mismatch, mismatch, type_of, unify},
+    Function, Interpreter, RuntimeError, Unwind, Value, Variable,
+};
+
+type Result<T> = std::result::Result<T, Unwind>;
+
+/// What the path of a variable refers to.
+enum Resolution {
+    Local(Variable),
+    Item(std::result::Result<Def, ResolveError>),
+}
+
+/// The parts of a function or closure expression describing how it's called.
+struct Signature {
+    generics: Option<GenericParamList>,
+    params: Option<ParamList>,
+    return_type: Option<Type>,
+}
+
+impl Interpreter {
+    pub(crate) fn frame(&mut self) -> &mut crate::Frame {
+        self.frames
+            .last_mut()
+            .expect("evaluation happens inside a call")
+    }
+
+    pub(crate) fn span(&self, node: &SyntaxNode) -> Span {
+        let file = self
+            .frames
+            .last()
+            .expect("evaluation happens inside a call")
+            .file;
+        Span::new(file, node.text_range())
+    }
+
+    pub(crate) fn error(&self, node: &SyntaxNode, message: impl Into<String>) -> Unwind {
+        Unwind::Error(RuntimeError::new(self.span(node), message))
+    }
+
+    /// Checks that `value`, evaluated from `node`, has the type of the `annotation` of a
+    /// `let`, as the arguments of calls are checked against their parameters.
+    fn check_annotation(
+        &mut self,
+        annotation: &Type,
+        value: &mut Value,
+        node: &SyntaxNode,
+    ) -> Result<()> {
+        let (module, env) = (self.frame().module, self.frame().type_env.clone());
+        let ty = self
+            .lower_type(annotation, module, &env)
+            .map_err(|message| self.error(annotation.syntax(), message))?;
+
+        if !unify(value, &ty, &mut []) {
+            return Err(self.error(node, mismatch(annotation, &ty, &[], &env, value)));
+        }
+        fit(value, &ty, &[]).map_err(|message| self.error(node, message))
+    }
+
+    fn define(&mut self, name: String, value: Value) {
+        let scopes = &mut self.frame().scopes;
+        scopes
+            .last_mut()
+            .expect("frames always have a scope")
+            .push((name, Rc::new(RefCell::new(value))));
+    }
+
+    pub(crate) fn lookup_local(&self, name: &str) -> Option<Variable> {
+        let frame = self.frames.last()?;
+        frame
+            .scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(other, _)| other == name)
+            .map(|(_, variable)| variable.clone())
+    }
+
+    pub(crate) fn eval_block(&mut self, block: &BlockExpr) -> Result<Value> {
+        self.frame().scopes.push(Vec::new());
+        let result = self.eval_block_stmts(block);
+        self.frame().scopes.pop();
+        result
+    }
+
+    fn eval_block_stmts(&mut self, block: &BlockExpr) -> Result<Value> {
+        let mut stmts = block.stmts().peekable();
+        while let Some(stmt) = stmts.next() {
+            // the tail expression is the last statement, which is only looked for once
+            match &stmt {
+                Stmt::Expr(tail) if stmts.peek().is_none() && !tail.has_semicolon() => {
+                    return self.eval_expr_opt(tail.expr(), tail.syntax());
+                }
+                _ => self.eval_stmt(&stmt)?,
+            }
+        }
+        Ok(Value::Unit)
+    }
+
+    fn eval_stmt(&mut self, stmt: &Stmt) -> Result<()> {
+        match stmt {
+            Stmt::Let(stmt) => {
+                let mut value = self.eval_expr_opt(stmt.initializer(), stmt.syntax())?;
+                if let Some(annotation) = stmt.ty() {
+                    let node = stmt
+                        .initializer()
+                        .map(|initializer| initializer.syntax().clone());
+                    self.check_annotation(
+                        &annotation,
+                        &mut value,
+                        node.as_ref().unwrap_or(stmt.syntax()),
+                    )?;
+                }
+                if let Some(name) = stmt.name() {
+                    self.define(name.text().to_string(), value);
+                } else if let Some(pattern) = stmt.pattern() {
+                    let mut bindings = Vec::new();
+                    if !self.match_pattern(&pattern, &value, &mut bindings)? {
+                        let message = "refutable pattern in local binding";
+                        return Err(self.error(pattern.syntax(), message));
+                    }
+                    for (name, value) in bindings {
+                        self.define(name, value);
+                    }
+                }
+            }
+            Stmt::Return(stmt) => {
+                let value = match stmt.value() {
+                    Some(value) => self.eval_expr(&value)?,
+                    None => Value::Unit,
+                };
+                return Err(Unwind::Return(value));
+            }
+            Stmt::Expr(stmt) => {
+                self.eval_expr_opt(stmt.expr(), stmt.syntax())?;
+            }
+            // items are collected when the module tree is built
+            Stmt::Mod(_)
+            | Stmt::Use(_)
+            | Stmt::Enum(_)
+            | Stmt::Struct(_)
+            | Stmt::Trait(_)
+            | Stmt::Impl(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates an expression that should be present once the program parsed without errors.
+    fn eval_expr_opt(&mut self, expr: Option<Expr>, parent: &SyntaxNode) -> Result<Value> {
+        match expr {
+            Some(expr) => self.eval_expr(&expr),
+            None => Err(self.error(parent, "expected expression")),
+        }
+    }
+
+    pub(crate) fn eval_expr(&mut self, expr: &Expr) -> Result<Value> {
+        self.step(expr.syntax())?;
+        match expr {
+            Expr::Literal(literal) => {
+                let token = literal
+                    .token()
+                    .ok_or_else(|| self.error(literal.syntax(), "expected literal"))?;
+                literal::value(token.kind(), token.text(), false)
+                    .map_err(|message| self.error(literal.syntax(), message))
+            }
+            Expr::InterpolatedString(string) => self.eval_interpolated_string(string),
+            Expr::VariableRef(variable) => self.eval_variable(variable),
+            Expr::Paren(paren) => self.eval_expr_opt(paren.expr(), paren.syntax()),
+            Expr::Tuple(tuple) => self.eval_tuple(tuple),
+            Expr::Prefix(prefix) => self.eval_prefix(prefix),
+            Expr::Binary(binary) => self.eval_binary(binary),
+            Expr::Call(call) => self.eval_call(call),
+            Expr::MethodCall(call) => self.eval_method_call(call),
+            Expr::Field(field) => self.eval_field(field),
+            Expr::Block(block) => self.eval_block(block),
+            Expr::Function(function) => Ok(self.eval_function(function)),
+            Expr::Closure(closure) => Ok(self.eval_closure(closure)),
+            Expr::Match(match_expr) => self.eval_match(match_expr),
+            Expr::If(if_expr) => self.eval_if(if_expr),
+            Expr::Array(array) => self.eval_array(array),
+            Expr::ArrayRepeat(repeat) => self.eval_array_repeat(repeat),
+            Expr::Index(index) => self.eval_index(index),
+            Expr::Record(record) => self.eval_record(record),
+        }
+    }
+
+    fn eval_variable(&mut self, variable: &VariableRef) -> Result<Value> {
+        let tokens = variable.segments().collect::<Vec<_>>();
+        let resolution = self.resolve_variable(&tokens);
+        self.eval_resolved_variable(variable, &tokens, resolution)
+    }
+
+    /// What the path of a variable, made of `tokens`, refers to.
+    fn resolve_variable(&self, tokens: &[SyntaxToken]) -> Resolution {
+        if let [name] = tokens {
+            if let Some(local) = self.lookup_local(name.text()) {
+                return Resolution::Local(local);
+            }
+        }
+
+        let module = self
+            .frames
+            .last()
+            .expect("evaluation happens inside a call")
+            .module;
+        let resolved = match tokens {
+            [name] => self.module_tree.resolve_path(module, &[name.text()]),
+            _ => {
+                let path = tokens.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+                self.module_tree.resolve_path(module, &path)
+            }
+        };
+        Resolution::Item(resolved)
+    }
+
+    fn eval_resolved_variable(
+        &mut self,
+        variable: &VariableRef,
+        tokens: &[SyntaxToken],
+        resolution: Resolution,
+    ) -> Result<Value> {
+        let segments = || tokens.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+
+        let resolved = match resolution {
+            Resolution::Local(local) => {
+                if let Some(list) = variable.generic_arg_list() {
+                    let message = format!(
+                        "type arguments are not allowed on local variable `{}`",
+                        tokens[0].text()
+                    );
+                    return Err(self.error(list.syntax(), message));
+                }
+                return Ok(local.borrow().clone());
+            }
+            Resolution::Item(resolved) => resolved,
+        };
+
+        let module = self.frame().module;
+        match resolved {
+            Ok(Def::Function(span)) => {
+                let function = self
+                    .function(span)
+                    .ok_or_else(|| self.error(variable.syntax(), "expected function"))?;
+                match variable.generic_arg_list() {
+                    Some(list) => Ok(Value::Function(
+                        self.instantiate_explicitly(&function, &list)?,
+                    )),
+                    None => Ok(Value::Function(function)),
+                }
+            }
+            Ok(Def::Module(_)) => Err(self.error(
+                variable.syntax(),
+                format!("expected value, found module `{}`", segments().join("::")),
+            )),
+            Ok(Def::Enum(_)) => Err(self.error(
+                variable.syntax(),
+                format!("expected value, found enum `{}`", segments().join("::")),
+            )),
+            Ok(Def::Struct(_)) => Err(self.error(
+                variable.syntax(),
+                format!("expected value, found struct `{}`", segments().join("::")),
+            )),
+            Ok(Def::Trait(_)) => Err(self.error(
+                variable.syntax(),
+                format!("expected value, found trait `{}`", segments().join("::")),
+            )),
+            Ok(Def::Variant(span, variant)) => {
+                let enum_def = self.enum_def(span);
+                match enum_def.variants[variant].fields {
+                    VariantFields::Unit => {
+                        let type_args =
+                            self.explicit_type_args(&enum_def, variable.generic_arg_list())?;
+                        self.new_variant(
+                            enum_def,
+                            variant,
+                            Vec::new(),
+                            type_args,
+                            variable.syntax(),
+                        )
+                    }
+                    VariantFields::Tuple(_) => Err(self.error(
+                        variable.syntax(),
+                        format!(
+                            "expected value, found tuple variant `{}`",
+                            segments().join("::")
+                        ),
+                    )),
+                    VariantFields::Record(_) => Err(self.error(
+                        variable.syntax(),
+                        format!(
+                            "expected value, found struct variant `{}`",
+                            segments().join("::")
+                        ),
+                    )),
+                }
+            }
+            Err(ResolveError::Unresolved(_) | ResolveError::NotAModule(_)) => {
+                // `Enum::function`, for the functions of the enum's impls
+                let path = segments();
+                if let Some((name, prefix)) = path.split_last() {
+                    if let Ok(Def::Enum(span) | Def::Struct(span)) =
+                        self.module_tree.resolve_path(module, prefix)
+                    {
+                        if let Some(function) =
+                            self.associated_function(span, name, variable.syntax())?
+                        {
+                            return Ok(Value::Function(function));
+                        }
+                    }
+                }
+                Err(self.error(
+                    variable.syntax(),
+                    format!(
+                        "cannot find value `{}` in this scope",
+                        segments().join("::")
+                    ),
+                ))
+            }
+            Err(ResolveError::Private(index)) => Err(self.error(
+                variable.syntax(),
+                format!("`{}` is private", tokens[index].text()),
+            )),
+        }
+    }
+
+    fn eval_prefix(&mut self, prefix: &PrefixExpr) -> Result<Value> {
+        let op = prefix
+            .op()
+            .ok_or_else(|| self.error(prefix.syntax(), "expected operator"))?;
+
+        // `-1` is a literal, so that the minimum of signed types can be written
+        if op.kind() == SyntaxKind::Minus {
+            if let Some(Expr::Literal(literal)) = prefix.operand() {
+                if let Some(token) = literal.token() {
+                    return literal::value(token.kind(), token.text(), true)
+                        .map_err(|message| self.error(prefix.syntax(), message));
+                }
+            }
+        }
+
+        let operand = self.eval_expr_opt(prefix.operand(), prefix.syntax())?;
+        ops::prefix(op.kind(), operand).map_err(|message| self.error(prefix.syntax(), message))
+    }
+
+    fn eval_binary(&mut self, binary: &BinaryExpr) -> Result<Value> {
+        let op = binary
+            .op()
+            .ok_or_else(|| self.error(binary.syntax(), "expected operator"))?;
+        if op.kind().is_assignment() {
+            let value = self.eval_expr_opt(binary.rhs(), binary.syntax())?;
+            let op = ops::compound_assignment(op.kind());
+            let allocated = self.update_place(
+                binary.lhs(),
+                binary.syntax(),
+                "invalid left-hand side of assignment",
+                |slot| {
+                    Ok(match op {
+                        Some(op) => {
+                            *slot = ops::binary(op, slot.clone(), value)?;
+                            limits::size(slot)
+                        }
+                        None => {
+                            *slot = value;
+                            0
+                        }
+                    })
+                },
+            )?;
+            self.allocate(binary.syntax(), allocated)?;
+            return Ok(Value::Unit);
+        }
+
+        let lhs = self.eval_expr_opt(binary.lhs(), binary.syntax())?;
+
+        // `&&` and `||` short-circuit, so their right-hand side may not be evaluated
+        if matches!(op.kind(), SyntaxKind::LogicalAnd | SyntaxKind::LogicalOr) {
+            let Value::Bool(lhs) = lhs else {
+                return Err(self.error(
+                    binary.syntax(),
+                    format!("expected `bool`, found `{}`", lhs.type_name()),
+                ));
+            };
+            if lhs == (op.kind() == SyntaxKind::LogicalOr) {
+                return Ok(Value::Bool(lhs));
+            }
+
+            return match self.eval_expr_opt(binary.rhs(), binary.syntax())? {
+                Value::Bool(rhs) => Ok(Value::Bool(rhs)),
+                rhs => Err(self.error(
+                    binary.syntax(),
+                    format!("expected `bool`, found `{}`", rhs.type_name()),
+                )),
+            };
+        }
+
+        let rhs = self.eval_expr_opt(binary.rhs(), binary.syntax())?;
+        let value = ops::binary(op.kind(), lhs, rhs)
+            .map_err(|message| self.error(binary.syntax(), message))?;
+        self.allocate(binary.syntax(), limits::size(&value))?;
+        Ok(value)
+    }
+
+    /// Modifies the value in a place with `update`: a variable, or an element of an array or
+    /// a tuple stored in one, possibly nested. Errors of `update` are reported at `node`, as
+    /// is `invalid` when the expression isn't a place.
+    pub(crate) fn update_place<T>(
+        &mut self,
+        place: Option<Expr>,
+        node: &SyntaxNode,
+        invalid: &str,
+        update: impl FnOnce(&mut Value) -> std::result::Result<T, String>,
+    ) -> Result<T> {
+        let invalid = || self.error(node, invalid);
+
+        let mut place = place.ok_or_else(invalid)?;
+        let mut projections = Vec::new();
+        loop {
+            place = match place {
+                Expr::Index(index) => {
+                    let base = index.base().ok_or_else(invalid)?;
+                    projections.push(Expr::Index(index));
+                    base
+                }
+                Expr::Field(field) => {
+                    let receiver = field.receiver().ok_or_else(invalid)?;
+                    projections.push(Expr::Field(field));
+                    receiver
+                }
+                Expr::Paren(paren) => paren.expr().ok_or_else(invalid)?,
+                _ => break,
+            };
+        }
+
+        let Expr::VariableRef(variable) = place else {
+            return Err(invalid());
+        };
+        let segments = variable.segments().collect::<Vec<_>>();
+        let [name] = segments.as_slice() else {
+            return Err(invalid());
+        };
+
+        // indices are evaluated from the outermost place inwards, like they're written
+        let mut indices = Vec::new();
+        for projection in projections.iter().rev() {
+            if let Expr::Index(index_expr) = projection {
+                indices.push(self.eval_expr_opt(index_expr.index(), index_expr.syntax())?);
+            }
+        }
+        let mut indices = indices.into_iter();
+
+        let Some(variable) = self.lookup_local(name.text()) else {
+            let message = format!("cannot find value `{}` in this scope", name.text());
+            return Err(self.error(variable.syntax(), message));
+        };
+
+        let mut variable = variable.borrow_mut();
+        let mut slot = &mut *variable;
+        // the bytes of the arrays and tuples on the way to the place copied on write
+        let mut copied = 0;
+        for projection in projections.iter().rev() {
+            copied += limits::copied_size(slot);
+            let result = match (projection, slot) {
+                (Expr::Index(_), Value::Array(elements)) => {
+                    let elements = Rc::make_mut(elements);
+                    let index = indices
+                        .next()
+                        .expect("indices are evaluated for every index");
+                    ops::index(elements.len(), &index).map(|index| &mut elements[index])
+                }
+                (Expr::Index(_), other) => Err(format!(
+                    "cannot index into a value of type `{}`",
+                    other.type_name()
+                )),
+                (Expr::Field(field), other) => {
+                    let name = field
+                        .field()
+                        .map_or_else(String::new, |name| name.to_string());
+                    let message = format!("no field `{name}` on type `{}`", type_of(other));
+                    match (other, name.parse::<usize>()) {
+                        (Value::Tuple(elements), Ok(index)) if index < elements.len() => {
+                            Ok(&mut Rc::make_mut(elements)[index])
+                        }
+                        (Value::Enum(value), _) => match value.field_index(&name) {
+                            Some(index) => Ok(&mut Rc::make_mut(value).fields[index]),
+                            None => Err(message),
+                        },
+                        _ => Err(message),
+                    }
+                }
+                _ => unreachable!("places only project indices and fields"),
+            };
+
+            slot = match result {
+                Ok(element) => element,
+                Err(message) => return Err(self.error(projection.syntax(), message)),
+            };
+        }
+
+        let value = update(slot).map_err(|message| self.error(node, message))?;
+        drop(variable);
+        self.allocate(node, copied)?;
+        Ok(value)
+    }
+
+    fn eval_array(&mut self, array: &ArrayExpr) -> Result<Value> {
+        let elements = array
+            .elements()
+            .map(|element| self.eval_expr(&element))
+            .collect::<Result<Vec<_>>>()?;
+        let value = Value::Array(Rc::new(elements));
+        self.allocate(array.syntax(), limits::size(&value))?;
+        Ok(value)
+    }
+
+    /// Evaluates `f"..."`, formatting the embedded values with [`Interpreter::display`].
+    fn eval_interpolated_string(&mut self, string: &InterpolatedString) -> Result<Value> {
+        let mut result = String::new();
+        for part in string.parts() {
+            match part {
+                InterpolatedStringPart::Text(text) => {
+                    let text = parser::literal::interpolated_text(text.text())
+                        .map_err(|message| self.error(string.syntax(), message))?;
+                    result.push_str(&text);
+                }
+                InterpolatedStringPart::Expr(expr) => {
+                    let value = self.eval_expr(&expr)?;
+                    result.push_str(&self.display(&value, expr.syntax())?);
+                }
+            }
+        }
+        self.allocate(string.syntax(), result.len())?;
+        Ok(Value::String(result.into()))
+    }
+
+    fn eval_tuple(&mut self, tuple: &TupleExpr) -> Result<Value> {
+        let elements = tuple
+            .elements()
+            .map(|element| self.eval_expr(&element))
+            .collect::<Result<Vec<_>>>()?;
+        if elements.is_empty() {
+            return Ok(Value::Unit);
+        }
+        let value = Value::Tuple(Rc::new(elements));
+        self.allocate(tuple.syntax(), limits::size(&value))?;
+        Ok(value)
+    }
+
+    /// Evaluates `t.0` or `p.x`, which reads an element of a tuple or a field of a struct.
+    fn eval_field(&mut self, field: &FieldExpr) -> Result<Value> {
+        let receiver = self.eval_expr_opt(field.receiver(), field.syntax())?;
+        let Some(name) = field.field() else {
+            return Err(self.error(field.syntax(), "expected field"));
+        };
+
+        let element = match &receiver {
+            Value::Tuple(elements) => name
+                .text()
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| elements.get(index)),
+            Value::Enum(value) => value
+                .field_index(name.text())
+                .map(|index| &value.fields[index]),
+            _ => None,
+        };
+        match element {
+            Some(element) => Ok(element.clone()),
+            None => {
+                let message = format!(
+                    "no field `{}` on type `{}`",
+                    name.text(),
+                    type_of(&receiver)
+                );
+                Err(self.error(field.syntax(), message))
+            }
+        }
+    }
+
+    fn eval_array_repeat(&mut self, repeat: &ArrayRepeatExpr) -> Result<Value> {
+        let value = self.eval_expr_opt(repeat.value(), repeat.syntax())?;
+        let count = self.eval_expr_opt(repeat.count(), repeat.syntax())?;
+
+        let Some(count) = (match count {
+            Value::Int(count, _) => usize::try_from(count).ok(),
+            _ => None,
+        }) else {
+            return Err(self.error(
+                repeat.syntax(),
+                format!("repeat count must be a non-negative integer, found `{count}`"),
+            ));
+        };
+
+        // the array is accounted for before it's allocated, as its size is unbounded
+        let bytes = count.saturating_mul(mem::size_of::<Value>());
+        self.allocate(repeat.syntax(), bytes)?;
+        let mut elements = Vec::new();
+        if elements.try_reserve_exact(count).is_err() {
+            let message = format!("cannot allocate an array of {count} elements");
+            return Err(self.error(repeat.syntax(), message));
+        }
+        elements.resize(count, value);
+        Ok(Value::Array(Rc::new(elements)))
+    }
+
+    fn eval_index(&mut self, index_expr: &IndexExpr) -> Result<Value> {
+        let base = self.eval_expr_opt(index_expr.base(), index_expr.syntax())?;
+        let index = self.eval_expr_opt(index_expr.index(), index_expr.syntax())?;
+
+        let Value::Array(elements) = base else {
+            return Err(self.error(
+                index_expr.syntax(),
+                format!("cannot index into a value of type `{}`", base.type_name()),
+            ));
+        };
+
+        ops::index(elements.len(), &index)
+            .map(|index| elements[index].clone())
+            .map_err(|message| self.error(index_expr.syntax(), message))
+    }
+
+    /// The name a call calls through the path `callee` if it's a single name that doesn't
+    /// refer to a local or an item, in which case it may be a builtin or a host function.
+    fn global_callee(&mut self, callee: &[SyntaxToken]) -> Option<String> {
+        let [name] = callee else {
+            return None;
+        };
+        let is_global = Builtin::from_name(name.text()).is_some()
+            || self.host.functions.contains_key(name.text());
+        if !is_global {
+            return None;
+        }
+
+        let module = self.frame().module;
+        let shadowed = self.lookup_local(name.text()).is_some()
+            || self
+                .module_tree
+                .resolve_path(module, &[name.text()])
+                .is_ok();
+        (!shadowed).then(|| name.text().to_string())
+    }
+
+    fn eval_call(&mut self, call: &CallExpr) -> Result<Value> {
+        let callee = match call.callee() {
+            Some(Expr::VariableRef(variable)) => {
+                let tokens = variable.segments().collect::<Vec<_>>();
+                if let Some(name) = self.global_callee(&tokens) {
+                    if let Some(builtin) = Builtin::from_name(&name) {
+                        return self.eval_builtin(builtin, call);
+                    }
+                    if self.host.functions.contains_key(&name) {
+                        return self.eval_host_call(&name, call);
+                    }
+                }
+
+                // the callee is only resolved once, as calls are what programs do most
+                let resolution = self.resolve_variable(&tokens);
+                if let Resolution::Item(Ok(Def::Variant(span, variant))) = resolution {
+                    return self.eval_tuple_variant(call, &variable, span, variant);
+                }
+                self.step(variable.syntax())?;
+                self.eval_resolved_variable(&variable, &tokens, resolution)?
+            }
+            callee => self.eval_expr_opt(callee, call.syntax())?,
+        };
+        let args = call
+            .arg_list()
+            .into_iter()
+            .flat_map(|list| list.args())
+            .map(|arg| self.eval_expr(&arg))
+            .collect::<Result<Vec<_>>>()?;
+
+        let Value::Function(function) = callee else {
+            return Err(self.error(
+                call.syntax(),
+                format!("expected function, found `{}`", callee.type_name()),
+            ));
+        };
+
+        let span = self.span(call.syntax());
+        Ok(self.call_function(&function, args, span)?)
+    }
+
+    /// Evaluates a function expression. Named functions are bound in the current scope and
+    /// only see their parameters, while anonymous ones capture variables like closures.
+    fn eval_function(&mut self, node: &FunctionExpr) -> Value {
+        let name = node.name();
+        let body = node.body().map(Expr::Block);
+        let captures = match name {
+            Some(_) => Vec::new(),
+            None => self.capture(node.param_list(), body.as_ref()),
+        };
+
+        let signature = Signature {
+            generics: node.generic_param_list(),
+            params: node.param_list(),
+            return_type: node.return_type(),
+        };
+        let function = self.new_function(name.clone(), signature, body, captures);
+        if let Some(name) = name {
+            self.define(name.text().to_string(), function.clone());
+        }
+        function
+    }
+
+    fn eval_closure(&mut self, closure: &ClosureExpr) -> Value {
+        let body = closure.body();
+        let captures = self.capture(closure.param_list(), body.as_ref());
+        let signature = Signature {
+            generics: None,
+            params: closure.param_list(),
+            return_type: closure.return_type(),
+        };
+        self.new_function(None, signature, body, captures)
+    }
+
+    /// Captures the local variables a closure refers to. Variables the closure assigns to
+    /// are shared with the current scope, the others are copied as they are right now.
+    fn capture(&self, params: Option<ParamList>, body: Option<&Expr>) -> Vec<(String, Variable)> {
+        let Some(body) = body else {
+            return Vec::new();
+        };
+
+        capture::captures(params, body)
+            .into_iter()
+            .filter_map(|capture| {
+                let variable = self.lookup_local(&capture.name)?;
+                let variable = if capture.by_ref {
+                    variable
+                } else {
+                    Rc::new(RefCell::new(variable.borrow().clone()))
+                };
+                Some((capture.name, variable))
+            })
+            .collect()
+    }
+
+    /// Creates a function value. Like items in Rust, named functions can't refer to the
+    /// generic parameters of the function they're in, unlike closures.
+    fn new_function(
+        &self,
+        name: Option<SyntaxToken>,
+        signature: Signature,
+        body: Option<Expr>,
+        captures: Vec<(String, Variable)>,
+    ) -> Value {
+        let frame = self
+            .frames
+            .last()
+            .expect("evaluation happens inside a call");
+        let type_env = match name {
+            Some(_) => Rc::new([]),
+            None => frame.type_env.clone(),
+        };
+
+        Value::Function(Rc::new(Function {
+            name: name.as_ref().map(|name| name.text().to_string()),
+            span: name.map(|name| Span::new(frame.file, name.text_range())),
+            file: frame.file,
+            module: frame.module,
+            generics: generic_names(signature.generics.clone()),
+            bounds: generic_bounds(signature.generics),
+            params: signature
+                .params
+                .into_iter()
+                .flat_map(|list| list.params())
+                .collect(),
+            return_type: signature.return_type,
+            body,
+            type_env,
+            captures,
+            signature: OnceCell::new(),
+        }))
+    }
+
+    /// Instantiates a generic function with the types given in `f::<A, B>`.
+    fn instantiate_explicitly(
+        &mut self,
+        function: &Rc<Function>,
+        list: &GenericArgList,
+    ) -> Result<Rc<Function>> {
+        let (module, env) = (self.frame().module, self.frame().type_env.clone());
+        let types = self
+            .lower_types(list.types(), module, &env)
+            .map_err(|message| self.error(list.syntax(), message))?;
+        if types.len() != function.generics.len() {
+            let message = generic_count_mismatch(
+                "function",
+                function.name.as_deref().unwrap_or_default(),
+                function.generics.len(),
+                types.len(),
+            );
+            return Err(self.error(list.syntax(), message));
+        }
+        Ok(self.instantiate(function, types))
+    }
+
+    fn eval_match(&mut self, match_expr: &MatchExpr) -> Result<Value> {
+        let scrutinee = self.eval_expr_opt(match_expr.scrutinee(), match_expr.syntax())?;
+
+        for arm in match_expr
+            .arm_list()
+            .into_iter()
+            .flat_map(|list| list.arms())
+        {
+            let Some(pattern) = arm.pattern() else {
+                return Err(self.error(arm.syntax(), "expected pattern"));
+            };
+            let mut bindings = Vec::new();
+            if !self.match_pattern(&pattern, &scrutinee, &mut bindings)? {
+                continue;
+            }
+
+            // the bindings are visible in the guard and the arm's expression
+            let scope = bindings
+                .into_iter()
+                .map(|(name, value)| (name, Rc::new(RefCell::new(value))))
+                .collect();
+            self.frame().scopes.push(scope);
+            let result = self.eval_arm(&arm);
+            self.frame().scopes.pop();
+
+            if let Some(value) = result? {
+                return Ok(value);
+            }
+        }
+
+        Err(self.error(
+            match_expr.syntax(),
+            format!("no match arm matches the value `{scrutinee}`"),
+        ))
+    }
+
+    /// Evaluates the arm whose pattern matched, or returns `None` if its guard doesn't hold.
+    fn eval_arm(&mut self, arm: &MatchArm) -> Result<Option<Value>> {
+        if let Some(guard) = arm.guard() {
+            match self.eval_expr_opt(guard.condition(), guard.syntax())? {
+                Value::Bool(true) => {}
+                Value::Bool(false) => return Ok(None),
+                condition => {
+                    return Err(self.error(
+                        guard.syntax(),
+                        format!("expected `bool`, found `{}`", condition.type_name()),
+                    ))
+                }
+            }
+        }
+
+        self.eval_expr_opt(arm.expr(), arm.syntax()).map(Some)
+    }
+
+    fn eval_if(&mut self, if_expr: &IfExpr) -> Result<Value> {
+        let condition = self.eval_expr_opt(if_expr.condition(), if_expr.syntax())?;
+        let Value::Bool(condition) = condition else {
+            return Err(self.error(
+                if_expr.syntax(),
+                format!("expected `bool`, found `{}`", condition.type_name()),
+            ));
+        };
+
+        if condition {
+            return match if_expr.then_branch() {
+                Some(block) => self.eval_block(&block),
+                None => Err(self.error(if_expr.syntax(), "expected block")),
+            };
+        }
+
+        match if_expr.else_branch() {
+            Some(ElseBranch::Block(block)) => self.eval_block(&block),
+            Some(ElseBranch::If(if_expr)) => self.eval_if(&if_expr),
+            None => Ok(Value::Unit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::run, Value};
+
+    #[test]
+    fn blocks_and_scopes() {
+        let source = "fn main() { let a = 1; let b = { let a = 2; a + 1 }; a + b }";
+        assert_eq!(run(source), Ok(Value::Int(4, None)));
+    }
+
+    #[test]
+    fn local_functions() {
+        let source = "fn main() { fn twice(x) { x * 2 } twice(21) }";
+        assert_eq!(run(source), Ok(Value::Int(42, None)));
+    }
+
+    #[test]
+    fn closures() {
+        let source = "
+            fn apply(f: fn(i32) -> i32, x: i32) -> i32 { f(x) }
+            fn adder(n) { |x| x + n }
+            fn main() {
+                let k = 3;
+                let add = adder(40);
+                apply(|x| x * k, 2) + apply(fn(x) { x + 1 }, 1) + add(2)
+            }
+        ";
+        assert_eq!(run(source), Ok(Value::Int(50, None)));
+        assert_eq!(
+            run("fn main() { let f = |a, b| a; f(1) }"),
+            Err("closure takes 2 arguments but 1 was supplied".to_string())
+        );
+    }
+
+    #[test]
+    fn closure_captures() {
+        // `count` is assigned by the closure so it is shared, `step` is copied
+        let source = "
+            fn main() {
+                let mut count = 0;
+                let mut step = 1;
+                let increment = || count += step;
+                step = 10;
+                increment();
+                increment();
+                [count, step]
+            }
+        ";
+        assert_eq!(
+            run(source).map(|value| value.to_string()),
+            Ok("[2, 10]".to_string())
+        );
+    }
+
+    #[test]
+    fn short_circuit() {
+        let source = "fn boom() { 1 / 0 == 0 } fn main() { false && boom() || true }";
+        assert_eq!(run(source), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn if_condition_must_be_bool() {
+        assert_eq!(
+            run("fn main() { if 1 { 2 } }"),
+            Err("expected `bool`, found `integer`".to_string())
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        let source = "fn main() { let a = [1, 2, 3]; let b = [a; 2]; b[1][2] + a[0] }";
+        assert_eq!(run(source), Ok(Value::Int(4, None)));
+        assert_eq!(run("fn main() { [0; 2] == [0, 0] }"), Ok(Value::Bool(true)));
+        assert_eq!(
+            run("fn main() { [0; 1000000000000000] }"),
+            Err("cannot allocate an array of 1000000000000000 elements".to_string())
+        );
+    }
+
+    #[test]
+    fn interpolated_strings() {
+        let source = r#"
+            fn main() {
+                let name = "Ada";
+                let age = 36;
+                f"Hello {name}, you are {age + 1}! {{{[1.5, 2.0]}}} {f"{(true, 'c')}"}\t{""}"
+            }
+        "#;
+        assert_eq!(
+            run(source),
+            Ok(Value::String(
+                "Hello Ada, you are 37! {[1.5, 2.0]} (true, c)\t".into()
+            ))
+        );
+        assert_eq!(
+            run(r#"fn main() { f"plain\n" }"#),
+            Ok(Value::String("plain\n".into()))
+        );
+        assert_eq!(
+            run(r#"fn main() { f"{1 / 0}" }"#),
+            Err("attempt to divide by zero".to_string())
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        let source =
+            "fn main() { let t = (1, (2.5, 'c'), [true]); [t.0 == 1, t.1.1 == 'c', t.2[0]] }";
+        assert_eq!(
+            run(source).map(|value| value.to_string()),
+            Ok("[true, true, true]".to_string())
+        );
+        assert_eq!(
+            run("fn main() { [(), ((1,)), (1)] }").map(|value| value.to_string()),
+            Ok("[(), (1,), 1]".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let t = (1, 2); t.2 }"),
+            Err("no field `2` on type `(integer, integer)`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { [1].0 }"),
+            Err("no field `0` on type `[integer]`".to_string())
+        );
+    }
+
+    #[test]
+    fn destructuring_let() {
+        let source = "
+            fn swap(pair: (i64, bool)) -> (bool, i64) { let (a, b) = pair; (b, a) }
+            fn main() {
+                let (x, (y, ..), _) = (swap((1, true)), (2, 3, 4), ());
+                let f = || y;
+                [x.1, f()]
+            }
+        ";
+        assert_eq!(
+            run(source).map(|value| value.to_string()),
+            Ok("[1, 2]".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let (a, 1) = (1, 2); }"),
+            Err("refutable pattern in local binding".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let (a, b) = (1, 2, 3); }"),
+            Err(
+                "mismatched types: expected a tuple with 3 elements, found one with 2 elements"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            run("fn f() -> (i64, i64) { (1, true) } fn main() { f() }"),
+            Err("mismatched types: expected `(i64, i64)`, found `tuple`".to_string())
+        );
+    }
+
+    #[test]
+    fn index_assignment() {
+        let source = "
+            fn main() {
+                let mut grid = [[0; 2]; 2];
+                let copy = grid;
+                grid[1][0] = 5;
+                grid[0] = [1, 2];
+                [grid, copy]
+            }
+        ";
+        assert_eq!(
+            run(source).map(|value| value.to_string()),
+            Ok("[[[1, 2], [5, 0]], [[0, 0], [0, 0]]]".to_string())
+        );
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let source = "
+            fn main() {
+                let mut n = 7;
+                n += 3;
+                n *= 2;
+                n -= 1;
+                n /= 2;
+                n %= 5;
+                n <<= 4;
+                n |= 1;
+                let mut xs = [1, 2];
+                xs[1] += 40;
+                let mut t = (1, (2.0, [3]));
+                t.1.0 *= 1.5;
+                t.1.1[0] = 4;
+                let u = t;
+                t.0 = 0;
+                (n, xs, t, u)
+            }
+        ";
+        assert_eq!(
+            run(source).map(|value| value.to_string()),
+            Ok("(65, [1, 42], (0, (3.0, [4])), (1, (3.0, [4])))".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let mut a = 1; a += true; }"),
+            Err("cannot apply `+` to `integer` and `bool`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let mut t = (1, 2); t.2 = 3; }"),
+            Err("no field `2` on type `(integer, integer)`".to_string())
+        );
+    }
+
+    #[test]
+    fn index_out_of_bounds() {
+        assert_eq!(
+            run("fn main() { let a = [1, 2]; let i = 2; a[i] }"),
+            Err("index out of bounds: the length is 2 but the index is 2".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let mut a = [1]; a[-1] = 0; }"),
+            Err("index out of bounds: the length is 1 but the index is -1".to_string())
+        );
+        assert_eq!(
+            run("fn main() { let a = 1; a[0] }"),
+            Err("cannot index into a value of type `integer`".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_assignment() {
+        assert_eq!(
+            run("fn main() { 1 = 2; }"),
+            Err("invalid left-hand side of assignment".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_variable() {
+        assert_eq!(
+            run("fn main() { a }"),
+            Err("cannot find value `a` in this scope".to_string())
+        );
+    }
+}
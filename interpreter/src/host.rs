@@ -0,0 +1,426 @@
+//! The functions, methods and types that a Rust program embedding the interpreter makes
+//! available to lamia code, and the conversions of values between the two languages.
+
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    rc::Rc,
+};
+
+use parser::ast::{AstNode, CallExpr, MethodCallExpr};
+
+use crate::{Interpreter, Unwind, Value};
+
+type Result<T> = std::result::Result<T, Unwind>;
+
+/// A Rust type whose values lamia code can hold and call the registered methods of, but
+/// can't look inside of.
+pub trait HostType: Any {
+    /// The name of the type in annotations and error messages.
+    const NAME: &'static str;
+}
+
+/// A value of a [`HostType`], which is shared rather than copied by lamia code.
+pub struct HostValue {
+    name: &'static str,
+    value: RefCell<Box<dyn Any>>,
+}
+
+impl HostValue {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn type_id(&self) -> TypeId {
+        (**self.value.borrow()).type_id()
+    }
+}
+
+impl fmt::Debug for HostValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HostValue({})", self.name)
+    }
+}
+
+/// Conversion of Rust values to lamia values.
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+/// Conversion of lamia values to Rust values.
+pub trait FromValue: Sized {
+    /// The name of the type in error messages.
+    fn type_name() -> String;
+
+    /// The Rust value, or `None` if `value` isn't of this type.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl FromValue for Value {
+    fn type_name() -> String {
+        "_".to_string()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+/// Implements the conversions of a type to and from a variant of [`Value`].
+macro_rules! convert_primitive {
+    ($ty:ty, $name:literal, $variant:ident) => {
+        impl IntoValue for $ty {
+            fn into_value(self) -> Value {
+                Value::$variant(self.into())
+            }
+        }
+
+        impl FromValue for $ty {
+            fn type_name() -> String {
+                $name.to_string()
+            }
+
+            fn from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::$variant(value) => (*value).try_into().ok(),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+convert_primitive!(f64, "float", Float);
+convert_primitive!(bool, "bool", Bool);
+convert_primitive!(char, "char", Char);
+
+/// Implements the conversions of an integer type to and from [`Value::Int`]. The integers
+/// of the host have no type in programs, so they take the types of the annotations they
+/// meet, and integers of any type convert to the host's as long as they fit.
+macro_rules! convert_int {
+    ($ty:ty) => {
+        impl IntoValue for $ty {
+            fn into_value(self) -> Value {
+                Value::Int(self as i128, None)
+            }
+        }
+
+        impl FromValue for $ty {
+            fn type_name() -> String {
+                "integer".to_string()
+            }
+
+            fn from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::Int(n, _) => (*n).try_into().ok(),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+convert_int!(i64);
+convert_int!(i32);
+convert_int!(usize);
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::Unit
+    }
+}
+
+impl FromValue for () {
+    fn type_name() -> String {
+        "()".to_string()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        matches!(value, Value::Unit).then_some(())
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::String(self.into())
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        Value::String(self.into())
+    }
+}
+
+impl FromValue for String {
+    fn type_name() -> String {
+        "string".to_string()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) => Some(s.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Value {
+        Value::Array(Rc::new(self.into_iter().map(T::into_value).collect()))
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn type_name() -> String {
+        format!("[{}]", T::type_name())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Array(elements) => elements.iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: HostType> IntoValue for T {
+    fn into_value(self) -> Value {
+        Value::Host(Rc::new(HostValue {
+            name: T::NAME,
+            value: RefCell::new(Box::new(self)),
+        }))
+    }
+}
+
+/// Host values are cloned when they're passed to host functions by value.
+impl<T: HostType + Clone> FromValue for T {
+    fn type_name() -> String {
+        T::NAME.to_string()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Host(host) => host.value.borrow().downcast_ref::<T>().cloned(),
+            _ => None,
+        }
+    }
+}
+
+/// The arguments of a lamia function called from Rust, as a tuple such as `(1, "a")`.
+pub trait IntoArgs {
+    fn into_args(self) -> Vec<Value>;
+}
+
+impl IntoArgs for Vec<Value> {
+    fn into_args(self) -> Vec<Value> {
+        self
+    }
+}
+
+/// An argument of a host function that isn't of the type of its parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ArgMismatch {
+    /// The index of the argument, not counting the receiver of methods.
+    pub(crate) index: usize,
+    pub(crate) expected: String,
+    pub(crate) found: String,
+}
+
+type NativeFn = dyn Fn(&[Value]) -> std::result::Result<Value, ArgMismatch>;
+
+/// A Rust function callable from lamia, which takes the receiver first if it's a method.
+#[derive(Clone)]
+pub struct HostFunction {
+    /// The number of arguments, not counting the receiver of methods.
+    arity: usize,
+    call: Rc<NativeFn>,
+}
+
+/// Conversion of Rust closures to functions lamia can call, for closures whose parameters
+/// are [`FromValue`] and whose return type is [`IntoValue`].
+pub trait IntoHostFunction<Args> {
+    fn into_host_function(self) -> HostFunction;
+}
+
+/// Conversion of Rust closures taking `&mut T` first to methods of the host type `T`.
+pub trait IntoHostMethod<T, Args> {
+    fn into_host_method(self) -> HostFunction;
+}
+
+fn arg<T: FromValue>(args: &[Value], index: usize) -> std::result::Result<T, ArgMismatch> {
+    T::from_value(&args[index]).ok_or_else(|| ArgMismatch {
+        index,
+        expected: T::type_name(),
+        found: args[index].type_name().to_string(),
+    })
+}
+
+macro_rules! host_functions {
+    ($arity:literal; $($arg:ident $index:tt),*) => {
+        impl<F, R, $($arg),*> IntoHostFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + 'static,
+            R: IntoValue,
+            $($arg: FromValue,)*
+        {
+            fn into_host_function(self) -> HostFunction {
+                HostFunction {
+                    arity: $arity,
+                    call: Rc::new(move |_args| Ok(self($(arg::<$arg>(_args, $index)?),*).into_value())),
+                }
+            }
+        }
+
+        impl<F, R, T, $($arg),*> IntoHostMethod<T, ($($arg,)*)> for F
+        where
+            F: Fn(&mut T, $($arg),*) -> R + 'static,
+            R: IntoValue,
+            T: HostType,
+            $($arg: FromValue,)*
+        {
+            fn into_host_method(self) -> HostFunction {
+                HostFunction {
+                    arity: $arity,
+                    call: Rc::new(move |args| {
+                        let Value::Host(receiver) = &args[0] else {
+                            unreachable!("methods are looked up by the type of their receiver");
+                        };
+                        // the arguments are converted before the receiver is borrowed mutably,
+                        // as they can be the receiver itself
+                        let _converted = ($(arg::<$arg>(&args[1..], $index)?,)*);
+                        let mut receiver = receiver.value.borrow_mut();
+                        let receiver = receiver
+                            .downcast_mut::<T>()
+                            .expect("methods are looked up by the type of their receiver");
+                        Ok(self(receiver, $(_converted.$index),*).into_value())
+                    }),
+                }
+            }
+        }
+
+        impl<$($arg: IntoValue),*> IntoArgs for ($($arg,)*) {
+            #[allow(non_snake_case)]
+            fn into_args(self) -> Vec<Value> {
+                let ($($arg,)*) = self;
+                vec![$($arg.into_value()),*]
+            }
+        }
+    };
+}
+
+host_functions!(0;);
+host_functions!(1; A 0);
+host_functions!(2; A 0, B 1);
+host_functions!(3; A 0, B 1, C 2);
+host_functions!(4; A 0, B 1, C 2, D 3);
+host_functions!(5; A 0, B 1, C 2, D 3, E 4);
+host_functions!(6; A 0, B 1, C 2, D 3, E 4, G 5);
+
+/// What the embedding program registered.
+#[derive(Clone, Default)]
+pub(crate) struct Host {
+    pub(crate) functions: HashMap<String, HostFunction>,
+    /// The registered types, by name.
+    pub(crate) types: HashMap<&'static str, TypeId>,
+    pub(crate) methods: HashMap<(TypeId, String), HostFunction>,
+}
+
+impl fmt::Debug for Host {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Host")
+            .field("functions", &self.functions.keys())
+            .field("types", &self.types.keys())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Interpreter {
+    /// Calls the host function `name`, which `call` refers to.
+    pub(crate) fn eval_host_call(&mut self, name: &str, call: &CallExpr) -> Result<Value> {
+        let function = self.host.functions[name].clone();
+        let args = call
+            .arg_list()
+            .into_iter()
+            .flat_map(|list| list.args())
+            .collect::<Vec<_>>();
+        let values = args
+            .iter()
+            .map(|arg| self.eval_expr(arg))
+            .collect::<Result<Vec<_>>>()?;
+
+        call_host(
+            &function,
+            &format!("function `{name}`"),
+            &values,
+            values.len(),
+        )
+        .map_err(|(index, message)| match index {
+            Some(index) => self.error(args[index].syntax(), message),
+            None => self.error(call.syntax(), message),
+        })
+    }
+
+    /// Calls the method `name` of a host value with `args`, which start with the receiver,
+    /// or returns `None` if its type has no such method.
+    pub(crate) fn eval_host_method_call(
+        &mut self,
+        receiver: &HostValue,
+        name: &str,
+        args: &[Value],
+        call: &MethodCallExpr,
+    ) -> Result<Option<Value>> {
+        let key = (receiver.type_id(), name.to_string());
+        let Some(method) = self.host.methods.get(&key).cloned() else {
+            return Ok(None);
+        };
+
+        let callee = format!("method `{name}`");
+        match call_host(&method, &callee, args, args.len() - 1) {
+            Ok(value) => Ok(Some(value)),
+            Err((Some(index), message)) => {
+                let arg = call
+                    .arg_list()
+                    .and_then(|list| list.args().nth(index))
+                    .expect("every argument is converted");
+                Err(self.error(arg.syntax(), message))
+            }
+            Err((None, message)) => Err(self.error(call.syntax(), message)),
+        }
+    }
+}
+
+/// Calls a host function with `supplied` arguments, returning the error along with the index
+/// of the argument it's about, if any.
+fn call_host(
+    function: &HostFunction,
+    callee: &str,
+    args: &[Value],
+    supplied: usize,
+) -> std::result::Result<Value, (Option<usize>, String)> {
+    if supplied != function.arity {
+        let message = format!(
+            "{callee} takes {} argument{} but {supplied} {} supplied",
+            function.arity,
+            if function.arity == 1 { "" } else { "s" },
+            if supplied == 1 { "was" } else { "were" },
+        );
+        return Err((None, message));
+    }
+
+    (function.call)(args).map_err(|mismatch| {
+        let message = format!(
+            "expected `{}`, found `{}`",
+            mismatch.expected, mismatch.found
+        );
+        (Some(mismatch.index), message)
+    })
+}
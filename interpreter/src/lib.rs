@@ -0,0 +1,538 @@
+//! A tree-walking interpreter running programs straight from their syntax trees.
+
+mod builtins;
+mod capture;
+mod engine;
+mod enums;
+mod eval;
+mod host;
+mod limits;
+mod literal;
+mod ops;
+mod pattern;
+mod testing;
+mod traits;
+mod types;
+mod value;
+
+pub use engine::{Engine, EngineError};
+pub use host::{
+    FromValue, HostFunction, HostType, HostValue, IntoArgs, IntoHostFunction, IntoHostMethod,
+    IntoValue,
+};
+pub use limits::Limit;
+pub use testing::{discover_tests, Test};
+pub use types::Ty;
+pub use value::{Enum, EnumValue, Function, Key, Value};
+
+use host::Host;
+use limits::{Limits, Usage};
+use traits::Impl;
+use types::{fit, generic_params, len_mismatch, mismatch, unify, SignatureTypes, TypeEnv};
+use value::Variable;
+
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::HashMap,
+    fmt,
+    rc::Rc,
+};
+
+use db::Database;
+use modules::{Def, ModuleId, ModuleTree};
+use parser::{
+    ast::{AstNode, Expr, FunctionExpr, GenericParamList, PathType, Type},
+    syntax::SyntaxNode,
+};
+use source_map::{Diagnostic, FileId, Span, TextRange};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeError {
+    pub message: String,
+    pub span: Span,
+    /// The limit on the resources of the program that stopped it, if that's why it failed.
+    pub limit: Option<Limit>,
+}
+
+impl RuntimeError {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            limit: None,
+        }
+    }
+
+    pub fn limit_exceeded(span: Span, limit: Limit) -> Self {
+        Self {
+            message: limit.to_string(),
+            span,
+            limit: Some(limit),
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::error(self.span, &self.message)
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Why evaluation stopped before producing a value.
+#[derive(Debug)]
+enum Unwind {
+    Return(Value),
+    Error(RuntimeError),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Self::Error(error)
+    }
+}
+
+/// The native stack left when a call starts, below which the call gets a new segment of
+/// stack. It's enough for the expressions of a function body, which are evaluated
+/// recursively, even in unoptimized builds.
+const STACK_RED_ZONE: usize = 1024 * 1024;
+/// The size of the segments of stack calls get.
+const STACK_SEGMENT: usize = 16 * 1024 * 1024;
+
+/// The state of a function call: where names are resolved and its local variables.
+#[derive(Debug)]
+struct Frame {
+    file: FileId,
+    module: ModuleId,
+    type_env: TypeEnv,
+    /// The variables of each scope in the order they're defined, so that the ones defined
+    /// later shadow the others. Scopes are small enough for this to beat hashing names.
+    scopes: Vec<Vec<(String, Variable)>>,
+}
+
+#[derive(Debug)]
+pub struct Interpreter {
+    module_tree: ModuleTree,
+    files: HashMap<FileId, SyntaxNode>,
+    functions: HashMap<Span, Rc<Function>>,
+    /// The instances of generic functions and methods, by the types they see.
+    instances: HashMap<(Option<Span>, Vec<Ty>), Rc<Function>>,
+    enums: HashMap<Span, Rc<Enum>>,
+    /// The generic arguments the checker inferred, by the span of the call, the path or the
+    /// record using a generic item, for those which the values don't tell.
+    type_args: HashMap<Span, Vec<checker::Ty>>,
+    /// The impls of the program, once their types are lowered.
+    impls: Option<Rc<[Rc<Impl>]>>,
+    frames: Vec<Frame>,
+    /// What the program embedding the interpreter registered, if any.
+    host: Rc<Host>,
+    limits: Limits,
+    usage: Usage,
+}
+
+impl Interpreter {
+    /// Creates an interpreter for a program whose module tree was built without errors.
+    pub fn new(db: &Database, module_tree: ModuleTree) -> Self {
+        let files = module_tree
+            .modules()
+            .map(|module| module_tree.get(module).file)
+            .map(|file| (file, db.parse(file).syntax()))
+            .collect();
+        let type_args = checker::type_args(db, &module_tree);
+
+        Self {
+            module_tree,
+            files,
+            functions: HashMap::new(),
+            instances: HashMap::new(),
+            enums: HashMap::new(),
+            type_args,
+            impls: None,
+            frames: Vec::new(),
+            host: Rc::default(),
+            limits: Limits::default(),
+            usage: Usage::default(),
+        }
+    }
+
+    /// Calls the function at `path`, resolved from the root module.
+    pub fn call(&mut self, path: &[&str], args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let root = self.module_tree.root();
+        let file = self.module_tree.get(root).file;
+        let not_found = || {
+            RuntimeError::new(
+                Span::new(file, TextRange::default()),
+                format!("cannot find function `{}`", path.join("::")),
+            )
+        };
+
+        let Ok(Def::Function(span)) = self.module_tree.resolve_path(root, path) else {
+            return Err(not_found());
+        };
+        let function = self.function(span).ok_or_else(not_found)?;
+
+        self.call_function(&function, args, span)
+    }
+
+    /// Returns the function whose name is at `span`.
+    fn function(&mut self, span: Span) -> Option<Rc<Function>> {
+        if let Some(function) = self.functions.get(&span) {
+            return Some(function.clone());
+        }
+
+        let root = self.files.get(&span.file)?;
+        let name = root.covering_element(span.range).into_token()?;
+        let node = name.parent().and_then(FunctionExpr::cast)?;
+
+        let function = Rc::new(Function {
+            name: Some(name.text().to_string()),
+            span: Some(span),
+            file: span.file,
+            module: self.module_tree.module_at(span),
+            generics: generic_names(node.generic_param_list()),
+            bounds: generic_bounds(node.generic_param_list()),
+            params: node
+                .param_list()
+                .into_iter()
+                .flat_map(|list| list.params())
+                .collect(),
+            return_type: node.return_type(),
+            body: node.body().map(Expr::Block),
+            type_env: Rc::new([]),
+            captures: Vec::new(),
+            signature: OnceCell::new(),
+        });
+        self.functions.insert(span, function.clone());
+        Some(function)
+    }
+
+    fn call_function(
+        &mut self,
+        function: &Rc<Function>,
+        mut args: Vec<Value>,
+        call_span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let params = &function.params;
+        if params.len() != args.len() {
+            let callee = match &function.name {
+                Some(name) => format!("function `{name}`"),
+                None => "closure".to_string(),
+            };
+            return Err(RuntimeError::new(
+                call_span,
+                format!(
+                    "{callee} takes {} argument{} but {} {} supplied",
+                    params.len(),
+                    if params.len() == 1 { "" } else { "s" },
+                    args.len(),
+                    if args.len() == 1 { "was" } else { "were" },
+                ),
+            ));
+        }
+
+        if let Some(depth) = self.limits.call_depth {
+            if self.frames.len() >= depth {
+                return Err(RuntimeError::limit_exceeded(
+                    call_span,
+                    Limit::CallDepth(depth),
+                ));
+            }
+        }
+
+        let function = self.check_args(function, &mut args, call_span)?;
+
+        // parameters shadow captured variables of the same name
+        let arguments = function.params.iter().zip(args).filter_map(|(param, arg)| {
+            let name = param.name()?.text().to_string();
+            Some((name, Rc::new(RefCell::new(arg))))
+        });
+        let scope = function.captures.iter().cloned().chain(arguments).collect();
+        self.frames.push(Frame {
+            file: function.file,
+            module: function.module,
+            type_env: function.type_env.clone(),
+            scopes: vec![scope],
+        });
+
+        // each call evaluates its body on the native stack, which is extended on the heap
+        // when it runs low, so that only the call depth limit bounds the recursion
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || match &function.body {
+            Some(body) => self.eval_expr(body),
+            None => Ok(Value::Unit),
+        });
+        self.frames.pop();
+
+        let mut value = match result {
+            Ok(value) | Err(Unwind::Return(value)) => value,
+            Err(Unwind::Error(error)) => return Err(error),
+        };
+        let return_type = self.signature_types(&function).return_type.as_ref();
+        if let (Some(annotation), Some(ty)) = (&function.return_type, return_type) {
+            if let Some(message) =
+                self.check_signature_type(&function, annotation, ty, &mut value, &mut [])?
+            {
+                let span = Span::new(function.file, annotation.syntax().text_range());
+                return Err(RuntimeError::new(span, message));
+            }
+        }
+        Ok(value)
+    }
+
+    /// Checks the arguments of a call against the types of the parameters. For generic
+    /// functions, this infers the types of the generic parameters and returns the instance
+    /// of the function for them.
+    fn check_args(
+        &mut self,
+        function: &Rc<Function>,
+        args: &mut [Value],
+        call_span: Span,
+    ) -> Result<Rc<Function>, RuntimeError> {
+        let types = &self.signature_types(function).params;
+        let mut bindings = vec![None; function.generics.len()];
+        for ((param, arg), ty) in function.params.iter().zip(args.iter_mut()).zip(types) {
+            let (Some(annotation), Some(ty)) = (param.ty(), ty) else {
+                continue;
+            };
+            if let Some(message) =
+                self.check_signature_type(function, &annotation, ty, arg, &mut bindings)?
+            {
+                return Err(RuntimeError::new(call_span, message));
+            }
+        }
+
+        if function.generics.is_empty() {
+            return Ok(function.clone());
+        }
+        self.fill_type_args(&mut bindings, call_span);
+
+        let mut types = Vec::with_capacity(bindings.len());
+        for (ty, param) in bindings.into_iter().zip(&function.generics) {
+            let Some(ty) = ty else {
+                return Err(RuntimeError::new(
+                    call_span,
+                    format!(
+                        "type annotations needed: cannot infer type of the type parameter `{param}` declared on the function `{}`",
+                        function.name.as_deref().unwrap_or_default()
+                    ),
+                ));
+            };
+            types.push(ty);
+        }
+        if let Some(message) =
+            self.check_bounds(&function.bounds, &types, function.file, function.module)?
+        {
+            return Err(RuntimeError::new(call_span, message));
+        }
+        Ok(self.instantiate(function, types))
+    }
+
+    /// The types of the annotations in the signature of `function`, in which its generic
+    /// parameters, if it's not instantiated, are [`Ty::Param`]s.
+    fn signature_types<'f>(&mut self, function: &'f Function) -> &'f SignatureTypes {
+        if let Some(types) = function.signature.get() {
+            return types;
+        }
+
+        let env = signature_env(function);
+        let params = function
+            .params
+            .iter()
+            .map(|param| Some(self.lower_type(&param.ty()?, function.module, &env)))
+            .collect();
+        let return_type = function
+            .return_type
+            .as_ref()
+            .map(|ty| self.lower_type(ty, function.module, &env));
+        function.signature.get_or_init(|| SignatureTypes {
+            params,
+            return_type,
+        })
+    }
+
+    /// Checks that `value` has the type `ty` of an annotation in the signature of `function`
+    /// and gives its integers the types of the annotation, returning the message of the
+    /// mismatch to report where the value comes from.
+    fn check_signature_type(
+        &mut self,
+        function: &Function,
+        annotation: &Type,
+        ty: &Result<Ty, String>,
+        value: &mut Value,
+        bindings: &mut [Option<Ty>],
+    ) -> Result<Option<String>, RuntimeError> {
+        let ty = ty.as_ref().map_err(|message| {
+            let span = Span::new(function.file, annotation.syntax().text_range());
+            RuntimeError::new(span, message.clone())
+        })?;
+
+        if !unify(value, ty, bindings) {
+            let env = signature_env(function);
+            return Ok(Some(mismatch(annotation, ty, bindings, &env, value)));
+        }
+        if let Some(message) = len_mismatch(annotation, value) {
+            return Ok(Some(message));
+        }
+        Ok(fit(value, ty, bindings).err())
+    }
+
+    /// Returns the instance of a generic function for the types of its generic parameters,
+    /// which is only created once for each combination of types.
+    fn instantiate(&mut self, function: &Function, types: Vec<Ty>) -> Rc<Function> {
+        let type_env = function
+            .type_env
+            .iter()
+            .cloned()
+            .chain(function.generics.iter().cloned().zip(types))
+            .collect();
+        self.with_type_env(function, type_env, false)
+    }
+
+    /// Returns the copy of `function` whose signature and body see the types in `type_env`,
+    /// which is only created once for each environment. The copy is still generic if
+    /// `keep_generics` is set, as methods are once the types of their impl are known.
+    fn with_type_env(
+        &mut self,
+        function: &Function,
+        type_env: Vec<(String, Ty)>,
+        keep_generics: bool,
+    ) -> Rc<Function> {
+        let key = (
+            function.span,
+            type_env.iter().map(|(_, ty)| ty.clone()).collect(),
+        );
+        if let Some(instance) = self.instances.get(&key) {
+            return instance.clone();
+        }
+
+        let (generics, bounds) = match keep_generics {
+            true => (function.generics.clone(), function.bounds.clone()),
+            false => (Vec::new(), Vec::new()),
+        };
+        let instance = Rc::new(Function {
+            name: function.name.clone(),
+            span: function.span,
+            file: function.file,
+            module: function.module,
+            generics,
+            bounds,
+            params: function.params.clone(),
+            return_type: function.return_type.clone(),
+            body: function.body.clone(),
+            type_env: type_env.into(),
+            captures: function.captures.clone(),
+            signature: OnceCell::new(),
+        });
+        self.instances.insert(key, instance.clone());
+        instance
+    }
+}
+
+/// The generic parameters the signature of `function` can refer to.
+fn signature_env(function: &Function) -> Vec<(String, Ty)> {
+    function
+        .type_env
+        .iter()
+        .cloned()
+        .chain(generic_params(&function.generics))
+        .collect()
+}
+
+fn generic_names(list: Option<GenericParamList>) -> Vec<String> {
+    list.into_iter()
+        .flat_map(|list| list.params())
+        .filter_map(|param| Some(param.name()?.text().to_string()))
+        .collect()
+}
+
+fn generic_bounds(list: Option<GenericParamList>) -> Vec<(usize, PathType)> {
+    list.into_iter()
+        .flat_map(|list| list.params())
+        .enumerate()
+        .flat_map(|(index, param)| param.bounds().map(move |bound| (index, bound)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use db::Database;
+
+    use crate::{Interpreter, Value};
+
+    pub(crate) fn run(source: &str) -> Result<Value, String> {
+        let mut db = Database::new();
+        let root = db.add_file("/p/main.lamia", source);
+        let (module_tree, diagnostics) = modules::build(&mut db, root);
+        assert_eq!(diagnostics, []);
+
+        Interpreter::new(&db, module_tree)
+            .call(&["main"], Vec::new())
+            .map_err(|error| error.message)
+    }
+
+    /// Runs `main`, returning its value as `println` writes it.
+    pub(crate) fn run_display(source: &str) -> Result<String, String> {
+        run(source).map(|value| value.to_string())
+    }
+
+    #[test]
+    fn call_main() {
+        assert_eq!(run("fn main() { 1 }"), Ok(Value::Int(1, None)));
+        assert_eq!(
+            run("fn f() {}"),
+            Err("cannot find function `main`".to_string())
+        );
+    }
+
+    #[test]
+    fn call_through_modules() {
+        let source = "
+            mod math {
+                pub fn double(x) { helper(x) * 2 }
+                fn helper(x) { x }
+            }
+            use math::double;
+            fn main() { double(3) + math::double(1) }
+        ";
+        assert_eq!(run(source), Ok(Value::Int(8, None)));
+    }
+
+    #[test]
+    fn arity_mismatch() {
+        assert_eq!(
+            run("fn f(a, b) { a } fn main() { f(1) }"),
+            Err("function `f` takes 2 arguments but 1 was supplied".to_string())
+        );
+    }
+
+    #[test]
+    fn recursion() {
+        let source = "
+            fn fib(n) { if n < 2 { n } else { fib(n - 1) + fib(n - 2) } }
+            fn main() { fib(15) }
+        ";
+        assert_eq!(run(source), Ok(Value::Int(610, None)));
+
+        // the native stack grows as deep as the recursion goes
+        let source = "
+            fn sum(n) { if n == 0 { 0 } else { n + sum(n - 1) } }
+            fn main() { sum(10000) }
+        ";
+        assert_eq!(run(source), Ok(Value::Int(50005000, None)));
+
+        // runaway recursion stops at the default depth rather than exhausting the memory
+        let source = "fn f(n) { f(n + 1) } fn main() { f(0) }";
+        assert_eq!(
+            run(source),
+            Err("recursion limit of 20000 calls reached".to_string())
+        );
+    }
+
+    #[test]
+    fn early_return() {
+        let source = "fn f(a) { if a { return 1; } 2 } fn main() { f(true) - f(false) }";
+        assert_eq!(run(source), Ok(Value::Int(-1, None)));
+    }
+}
@@ -0,0 +1,146 @@
+//! Bounds on the resources a program can use, so that untrusted programs can be run.
+
+use std::{
+    fmt, mem,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use parser::syntax::SyntaxNode;
+
+use crate::{value::Key, Interpreter, RuntimeError, Unwind, Value};
+
+/// A bound on the resources of a program, which stopped it when exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// The number of expressions the program can evaluate.
+    Fuel(u64),
+    /// The number of calls that can be in progress at once.
+    CallDepth(usize),
+    /// The number of bytes the program can allocate for strings, arrays, tuples and maps.
+    Memory(usize),
+    /// How long the program can run.
+    Time(Duration),
+    /// Printing and accessing files, which the program isn't allowed to do.
+    Io,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fuel(fuel) => write!(f, "ran out of fuel after evaluating {fuel} expressions"),
+            Self::CallDepth(depth) => write!(f, "recursion limit of {depth} calls reached"),
+            Self::Memory(bytes) => write!(f, "memory limit of {bytes} bytes exceeded"),
+            Self::Time(duration) => write!(f, "time limit of {duration:?} exceeded"),
+            Self::Io => f.write_str("input and output are disabled"),
+        }
+    }
+}
+
+/// How many calls can be in progress at once unless the embedder sets another depth. Deep
+/// enough for recursive programs, while stopping runaway recursion long before the stack,
+/// which grows on the heap, runs the process out of memory.
+pub(crate) const DEFAULT_CALL_DEPTH: usize = 20_000;
+
+/// The limits set by the program embedding the interpreter.
+#[derive(Debug, Clone)]
+pub(crate) struct Limits {
+    pub(crate) fuel: Option<u64>,
+    pub(crate) call_depth: Option<usize>,
+    pub(crate) memory: Option<usize>,
+    pub(crate) time: Option<Duration>,
+    /// Whether the functions of the prelude printing and accessing files can be called.
+    pub(crate) io: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            fuel: None,
+            call_depth: Some(DEFAULT_CALL_DEPTH),
+            memory: None,
+            time: None,
+            io: true,
+        }
+    }
+}
+
+/// The resources used since the program was last called.
+#[derive(Debug, Default)]
+pub(crate) struct Usage {
+    evaluated: u64,
+    allocated: usize,
+    deadline: Option<Instant>,
+}
+
+impl Interpreter {
+    /// Starts counting the resources used afresh, as the program is called.
+    pub(crate) fn reset_usage(&mut self) {
+        self.usage = Usage {
+            deadline: self.limits.time.map(|time| Instant::now() + time),
+            ..Usage::default()
+        };
+    }
+
+    /// Accounts for the evaluation of the expression `node`.
+    pub(crate) fn step(&mut self, node: &SyntaxNode) -> Result<(), Unwind> {
+        self.usage.evaluated += 1;
+        if let Some(fuel) = self.limits.fuel {
+            if self.usage.evaluated > fuel {
+                return Err(self.limit_exceeded(node, Limit::Fuel(fuel)));
+            }
+        }
+        if let (Some(deadline), Some(time)) = (self.usage.deadline, self.limits.time) {
+            if Instant::now() >= deadline {
+                return Err(self.limit_exceeded(node, Limit::Time(time)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Accounts for `bytes` allocated by the expression `node`.
+    pub(crate) fn allocate(&mut self, node: &SyntaxNode, bytes: usize) -> Result<(), Unwind> {
+        self.usage.allocated = self.usage.allocated.saturating_add(bytes);
+        match self.limits.memory {
+            Some(memory) if self.usage.allocated > memory => {
+                Err(self.limit_exceeded(node, Limit::Memory(memory)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn limit_exceeded(&self, node: &SyntaxNode, limit: Limit) -> Unwind {
+        Unwind::Error(RuntimeError::limit_exceeded(self.span(node), limit))
+    }
+}
+
+/// The number of bytes of the strings, arrays, tuples and maps `value` owns, not counting
+/// those of its elements, which are shared with the values they were created from.
+pub(crate) fn size(value: &Value) -> usize {
+    match value {
+        Value::String(s) => s.len(),
+        Value::Array(elements) | Value::Tuple(elements) => elements.len() * mem::size_of::<Value>(),
+        Value::Map(entries) => entries.len() * entry_size(),
+        _ => 0,
+    }
+}
+
+/// The number of bytes copied to modify `value` in place, as it's copied on write when its
+/// elements are shared with other values.
+pub(crate) fn copied_size(value: &Value) -> usize {
+    let shared = match value {
+        Value::Array(elements) | Value::Tuple(elements) => Rc::strong_count(elements) > 1,
+        Value::Map(entries) => Rc::strong_count(entries) > 1,
+        _ => false,
+    };
+    if shared {
+        size(value)
+    } else {
+        0
+    }
+}
+
+/// The number of bytes of an entry of a map.
+pub(crate) fn entry_size() -> usize {
+    mem::size_of::<Key>() + mem::size_of::<Value>()
+}
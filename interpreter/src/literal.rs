@@ -0,0 +1,131 @@
+//! Conversion of literal tokens to values.
+
+use parser::{
+    literal::{self, Number},
+    syntax::SyntaxKind,
+};
+
+use crate::{ops, Value};
+
+/// The value of a literal, negated if it follows a `-`, so that the minimum of signed types
+/// can be written.
+pub(crate) fn value(kind: SyntaxKind, text: &str, negative: bool) -> Result<Value, String> {
+    match kind {
+        SyntaxKind::Number => number(text, negative),
+        _ if negative => ops::prefix(SyntaxKind::Minus, value(kind, text, false)?),
+        SyntaxKind::Bool => Ok(Value::Bool(text == "true")),
+        SyntaxKind::String => Ok(Value::String(literal::string(text)?.into())),
+        SyntaxKind::Character => literal::char(text).map(Value::Char),
+        _ => Err(format!("{kind} is not a literal")),
+    }
+}
+
+fn number(text: &str, negative: bool) -> Result<Value, String> {
+    match literal::number(text)? {
+        Number::Int(_, Some(suffix)) if negative && !suffix.is_signed() => Err(format!(
+            "cannot apply unary operator {} to `{}`",
+            SyntaxKind::Minus,
+            suffix.name()
+        )),
+        Number::Int(value, suffix) => {
+            let value = if negative {
+                -i128::from(value)
+            } else {
+                i128::from(value)
+            };
+            // literals without a suffix are `i64`s unless they meet another type
+            let (min, max) = suffix.map_or((i64::MIN.into(), i64::MAX.into()), |suffix| {
+                (suffix.min(), suffix.max())
+            });
+            match suffix {
+                _ if (min..=max).contains(&value) => Ok(Value::Int(value, suffix)),
+                Some(suffix) => Err(format!("literal out of range for `{}`", suffix.name())),
+                None if negative => Err(format!("integer literal `-{text}` is too large")),
+                None => Err(format!("integer literal `{text}` is too large")),
+            }
+        }
+        Number::Float(value, _) => Ok(Value::Float(if negative { -value } else { value })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::{literal::IntTy, syntax::SyntaxKind};
+
+    use super::value;
+    use crate::Value;
+
+    fn number(text: &str) -> Result<Value, String> {
+        value(SyntaxKind::Number, text, false)
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(number("1_000"), Ok(Value::Int(1000, None)));
+        assert_eq!(number("0xff"), Ok(Value::Int(255, None)));
+        assert_eq!(number("0o17"), Ok(Value::Int(15, None)));
+        assert_eq!(
+            number("0b1010_1010u8"),
+            Ok(Value::Int(170, Some(IntTy::U8)))
+        );
+        assert_eq!(number("255u8"), Ok(Value::Int(255, Some(IntTy::U8))));
+        assert_eq!(number("3usize"), Ok(Value::Int(3, Some(IntTy::Usize))));
+        assert_eq!(
+            number("256u8"),
+            Err("literal out of range for `u8`".to_string())
+        );
+        assert_eq!(
+            number("18446744073709551615u64"),
+            Ok(Value::Int(u64::MAX.into(), Some(IntTy::U64)))
+        );
+        assert_eq!(
+            number("9223372036854775808"),
+            Err("integer literal `9223372036854775808` is too large".to_string())
+        );
+        assert!(number("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn negative_integers() {
+        let negative = |text| value(SyntaxKind::Number, text, true);
+        assert_eq!(negative("128i8"), Ok(Value::Int(-128, Some(IntTy::I8))));
+        assert_eq!(
+            negative("9223372036854775808"),
+            Ok(Value::Int(i64::MIN.into(), None))
+        );
+        assert_eq!(
+            negative("129i8"),
+            Err("literal out of range for `i8`".to_string())
+        );
+        assert_eq!(
+            negative("9223372036854775809"),
+            Err("integer literal `-9223372036854775809` is too large".to_string())
+        );
+        assert!(negative("1u8").is_err());
+        assert_eq!(negative("1.5"), Ok(Value::Float(-1.5)));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(number("1.5"), Ok(Value::Float(1.5)));
+        assert_eq!(number("1e3"), Ok(Value::Float(1000.0)));
+        assert_eq!(number("2f32"), Ok(Value::Float(2.0)));
+        assert_eq!(number("0x1.8p1"), Ok(Value::Float(3.0)));
+    }
+
+    #[test]
+    fn strings_and_characters() {
+        assert_eq!(
+            value(SyntaxKind::String, r#""a\n\"b\" \u{e9}""#, false),
+            Ok(Value::String("a\n\"b\" é".into()))
+        );
+        assert_eq!(
+            value(SyntaxKind::Character, r"'\''", false),
+            Ok(Value::Char('\''))
+        );
+        assert_eq!(
+            value(SyntaxKind::String, r#""\q""#, false),
+            Err(r"unknown character escape `\q`".to_string())
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Operators on values, returning the error message when the operands don't fit.
+//!
+//! Integers are computed on as `i128`s and checked against the range of their type, with
+//! integers without a type taken as `i64`s.
+
+use parser::{literal::IntTy, syntax::SyntaxKind};
+
+use crate::Value;
+
+pub(crate) fn prefix(op: SyntaxKind, operand: Value) -> Result<Value, String> {
+    match (op, operand) {
+        (SyntaxKind::Minus, Value::Int(_, Some(ty))) if !ty.is_signed() => Err(format!(
+            "cannot apply unary operator {op} to `{}`",
+            ty.name()
+        )),
+        (SyntaxKind::Minus, Value::Int(n, ty)) => {
+            checked(-n, ty, || "attempt to negate with overflow".to_string())
+        }
+        (SyntaxKind::Minus, Value::Float(n)) => Ok(Value::Float(-n)),
+        (SyntaxKind::Bang, Value::Bool(b)) => Ok(Value::Bool(!b)),
+        (SyntaxKind::Tilde, Value::Int(n, ty)) => Ok(Value::Int(wrap(!n, ty), ty)),
+        (op, operand) => Err(format!(
+            "cannot apply unary operator {op} to `{}`",
+            operand.type_name()
+        )),
+    }
+}
+
+pub(crate) fn binary(op: SyntaxKind, lhs: Value, rhs: Value) -> Result<Value, String> {
+    let mismatch = |lhs: &Value, rhs: &Value| {
+        format!(
+            "cannot apply {op} to `{}` and `{}`",
+            lhs.type_name(),
+            rhs.type_name()
+        )
+    };
+
+    // the operands of operators other than shifts are integers of the same type
+    let ty = match (&lhs, &rhs) {
+        (Value::Int(a, a_ty), Value::Int(b, b_ty))
+            if !matches!(op, SyntaxKind::ShiftLeft | SyntaxKind::ShiftRight) =>
+        {
+            common_ty(op, (*a, *a_ty), (*b, *b_ty))?
+        }
+        _ => None,
+    };
+
+    let value = match op {
+        SyntaxKind::Equal | SyntaxKind::NotEqual => {
+            if std::mem::discriminant(&lhs) != std::mem::discriminant(&rhs) {
+                return Err(mismatch(&lhs, &rhs));
+            }
+            Value::Bool((lhs == rhs) == (op == SyntaxKind::Equal))
+        }
+        SyntaxKind::LessThan
+        | SyntaxKind::GreaterThan
+        | SyntaxKind::LessThanEqual
+        | SyntaxKind::GreaterThanEqual => {
+            let ordering = match (&lhs, &rhs) {
+                (Value::Int(a, _), Value::Int(b, _)) => a.partial_cmp(b),
+                (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+                (Value::Char(a), Value::Char(b)) => a.partial_cmp(b),
+                (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+                _ => return Err(mismatch(&lhs, &rhs)),
+            };
+
+            Value::Bool(ordering.is_some_and(|ordering| match op {
+                SyntaxKind::LessThan => ordering.is_lt(),
+                SyntaxKind::GreaterThan => ordering.is_gt(),
+                SyntaxKind::LessThanEqual => ordering.is_le(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        SyntaxKind::Plus
+        | SyntaxKind::Minus
+        | SyntaxKind::Star
+        | SyntaxKind::Slash
+        | SyntaxKind::Modulo => match (lhs, rhs) {
+            (Value::Int(a, _), Value::Int(b, _)) => arithmetic(op, a, b, ty)?,
+            (Value::Float(a), Value::Float(b)) => Value::Float(match op {
+                SyntaxKind::Plus => a + b,
+                SyntaxKind::Minus => a - b,
+                SyntaxKind::Star => a * b,
+                SyntaxKind::Slash => a / b,
+                _ => a % b,
+            }),
+            (Value::String(a), Value::String(b)) if op == SyntaxKind::Plus => {
+                Value::String(format!("{a}{b}").into())
+            }
+            (lhs, rhs) => return Err(mismatch(&lhs, &rhs)),
+        },
+        // bitwise operators are only defined on integers
+        SyntaxKind::Ampersand
+        | SyntaxKind::Pipe
+        | SyntaxKind::Caret
+        | SyntaxKind::ShiftLeft
+        | SyntaxKind::ShiftRight => match (lhs, rhs) {
+            (Value::Int(a, a_ty), Value::Int(b, _)) => bitwise(op, a, b, ty.or(a_ty))?,
+            (lhs, rhs) => return Err(mismatch(&lhs, &rhs)),
+        },
+        _ => return Err(format!("{op} is not a binary operator")),
+    };
+
+    Ok(value)
+}
+
+/// The operator a compound assignment such as `+=` applies, `None` for other operators.
+pub(crate) fn compound_assignment(op: SyntaxKind) -> Option<SyntaxKind> {
+    let op = match op {
+        SyntaxKind::PlusEqual => SyntaxKind::Plus,
+        SyntaxKind::MinusEqual => SyntaxKind::Minus,
+        SyntaxKind::StarEqual => SyntaxKind::Star,
+        SyntaxKind::SlashEqual => SyntaxKind::Slash,
+        SyntaxKind::ModuloEqual => SyntaxKind::Modulo,
+        SyntaxKind::AmpersandEqual => SyntaxKind::Ampersand,
+        SyntaxKind::PipeEqual => SyntaxKind::Pipe,
+        SyntaxKind::CaretEqual => SyntaxKind::Caret,
+        SyntaxKind::ShiftLeftEqual => SyntaxKind::ShiftLeft,
+        SyntaxKind::ShiftRightEqual => SyntaxKind::ShiftRight,
+        _ => return None,
+    };
+    Some(op)
+}
+
+/// Checks that `index` is an integer within the bounds of an array of length `len`.
+pub(crate) fn index(len: usize, index: &Value) -> Result<usize, String> {
+    let Value::Int(index, _) = *index else {
+        return Err(format!(
+            "array indices must be integers, found `{}`",
+            index.type_name()
+        ));
+    };
+
+    usize::try_from(index)
+        .ok()
+        .filter(|&index| index < len)
+        .ok_or_else(|| format!("index out of bounds: the length is {len} but the index is {index}"))
+}
+
+/// The type of the operands of `op`: an integer without a type takes the type of the other
+/// operand, which it must fit in.
+fn common_ty(
+    op: SyntaxKind,
+    (a, a_ty): (i128, Option<IntTy>),
+    (b, b_ty): (i128, Option<IntTy>),
+) -> Result<Option<IntTy>, String> {
+    let (value, ty) = match (a_ty, b_ty) {
+        (Some(a_ty), Some(b_ty)) if a_ty != b_ty => {
+            return Err(format!(
+                "cannot apply {op} to `{}` and `{}`",
+                a_ty.name(),
+                b_ty.name()
+            ))
+        }
+        (None, Some(ty)) => (a, ty),
+        (Some(ty), None) => (b, ty),
+        _ => return Ok(a_ty),
+    };
+
+    if !ty.contains(value) {
+        return Err(format!("literal out of range for `{}`", ty.name()));
+    }
+    Ok(Some(ty))
+}
+
+/// The integer `value` of type `ty`, or the error of `message` if it's out of its range.
+pub(crate) fn checked(
+    value: i128,
+    ty: Option<IntTy>,
+    message: impl FnOnce() -> String,
+) -> Result<Value, String> {
+    if ty.unwrap_or(IntTy::I64).contains(value) {
+        Ok(Value::Int(value, ty))
+    } else {
+        Err(message())
+    }
+}
+
+/// Truncates `value` to the bits of `ty`, as the conversion to it does in C.
+fn wrap(value: i128, ty: Option<IntTy>) -> i128 {
+    let ty = ty.unwrap_or(IntTy::I64);
+    let unused = i128::BITS - ty.bits();
+    match ty.is_signed() {
+        true => (value << unused) >> unused,
+        false => ((value << unused) as u128 >> unused) as i128,
+    }
+}
+
+fn arithmetic(op: SyntaxKind, a: i128, b: i128, ty: Option<IntTy>) -> Result<Value, String> {
+    let (result, verb) = match op {
+        SyntaxKind::Plus => (a + b, "add"),
+        SyntaxKind::Minus => (a - b, "subtract"),
+        SyntaxKind::Star => (a * b, "multiply"),
+        SyntaxKind::Slash if b == 0 => return Err("attempt to divide by zero".to_string()),
+        SyntaxKind::Slash => (a / b, "divide"),
+        SyntaxKind::Modulo if b == 0 => {
+            return Err("attempt to calculate the remainder with a divisor of zero".to_string())
+        }
+        // the remainder can't overflow, but C's `%` does where `/` does
+        _ if a == ty.unwrap_or(IntTy::I64).min() && b == -1 => {
+            return Err("attempt to calculate the remainder with overflow".to_string())
+        }
+        _ => (a % b, "calculate the remainder"),
+    };
+
+    checked(result, ty, || format!("attempt to {verb} with overflow"))
+}
+
+/// Applies the bitwise operator `op` to `a` of type `ty` and `b`, which is of the same type
+/// except for the amount of shifts.
+fn bitwise(op: SyntaxKind, a: i128, b: i128, ty: Option<IntTy>) -> Result<Value, String> {
+    let shift = || {
+        u32::try_from(b)
+            .ok()
+            .filter(|&b| b < ty.unwrap_or(IntTy::I64).bits())
+            .ok_or_else(|| format!("attempt to shift by `{b}`, which is out of range"))
+    };
+
+    let result = match op {
+        SyntaxKind::Ampersand => a & b,
+        SyntaxKind::Pipe => a | b,
+        SyntaxKind::Caret => a ^ b,
+        // bits shifted out of the type are dropped, and right shifts of signed integers
+        // keep their sign
+        SyntaxKind::ShiftLeft => wrap(a << shift()?, ty),
+        _ => a >> shift()?,
+    };
+
+    Ok(Value::Int(result, ty))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{run, run_display},
+        Value,
+    };
+
+    #[test]
+    fn bitwise_operators() {
+        let source = "fn main() { (0b1100 & 0b1010) | (1 << 4) ^ ~0 >> 60 }";
+        assert_eq!(
+            run(source),
+            Ok(Value::Int(0b1000 | (16 ^ (!0 >> 60)), None))
+        );
+    }
+
+    #[test]
+    fn bitwise_precedence() {
+        // `&` binds tighter than `==`, unlike in C
+        assert_eq!(run("fn main() { 6 & 3 == 2 }"), Ok(Value::Bool(true)));
+        assert_eq!(run("fn main() { 1 + 1 << 2 }"), Ok(Value::Int(8, None)));
+    }
+
+    #[test]
+    fn bitwise_operators_require_integers() {
+        assert_eq!(
+            run("fn main() { 1.0 & 2 }"),
+            Err("cannot apply `&` to `float` and `integer`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { ~true }"),
+            Err("cannot apply unary operator `~` to `bool`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { 1 << 64 }"),
+            Err("attempt to shift by `64`, which is out of range".to_string())
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(
+            run("fn main() { 7 / 2 * 2 + 7 % 2 }"),
+            Ok(Value::Int(7, None))
+        );
+        assert_eq!(run("fn main() { 1.5 * 2.0 }"), Ok(Value::Float(3.0)));
+        assert_eq!(
+            run("fn main() { \"a\" + \"b\" }"),
+            Ok(Value::String("ab".into()))
+        );
+        assert_eq!(
+            run("fn main() { 1 / 0 }"),
+            Err("attempt to divide by zero".to_string())
+        );
+        assert_eq!(
+            run("fn main() { 9223372036854775807 + 1 }"),
+            Err("attempt to add with overflow".to_string())
+        );
+        assert_eq!(
+            run("fn main() { 1 + 1.0 }"),
+            Err("cannot apply `+` to `integer` and `float`".to_string())
+        );
+    }
+
+    #[test]
+    fn integer_widths() {
+        let cases = [
+            (
+                "let a: u8 = 200; [a + 55u8, ~a, a & 0x0f, a >> 3]",
+                "[255, 55, 8, 25]",
+            ),
+            (
+                "let c = -9223372036854775808; [c, c / 3, c % 7]",
+                "[-9223372036854775808, -3074457345618258602, -1]",
+            ),
+            (
+                "[-128i8, -32768i16, -2147483648i32]",
+                "[-128, -32768, -2147483648]",
+            ),
+            (
+                "[-17 / 5, -17 % 5, -16 >> 2, 1i32 << 31, 255u8 << 1]",
+                "[-3, -2, -4, -2147483648, 254]",
+            ),
+            (
+                "[18446744073709551615u64, 3usize * 7, -3isize]",
+                "[18446744073709551615, 21, -3]",
+            ),
+        ];
+        for (body, expected) in cases {
+            let source = format!("fn main() {{ {body} }}");
+            assert_eq!(run_display(&source), Ok(expected.to_string()), "{body}");
+        }
+
+        let errors = [
+            ("255u8 + 1u8", "attempt to add with overflow"),
+            ("0u32 - 1", "attempt to subtract with overflow"),
+            (
+                "let x: i8 = -128; x / -1",
+                "attempt to divide with overflow",
+            ),
+            (
+                "let x: i16 = -32768; x % -1",
+                "attempt to calculate the remainder with overflow",
+            ),
+            (
+                "-9223372036854775807 - 2",
+                "attempt to subtract with overflow",
+            ),
+            ("-(1u8)", "cannot apply unary operator `-` to `u8`"),
+            ("-1u8", "cannot apply unary operator `-` to `u8`"),
+            ("-129i8", "literal out of range for `i8`"),
+            ("1u8 + 1i32", "cannot apply `+` to `u8` and `i32`"),
+            ("1u8 < 300", "literal out of range for `u8`"),
+            (
+                "1i32 << 32",
+                "attempt to shift by `32`, which is out of range",
+            ),
+            (
+                "1u8 >> -1",
+                "attempt to shift by `-1`, which is out of range",
+            ),
+        ];
+        for (body, message) in errors {
+            let source = format!("fn main() {{ {body} }}");
+            assert_eq!(run(&source), Err(message.to_string()), "{body}");
+        }
+    }
+
+    #[test]
+    fn parameters_give_integers_their_types() {
+        // like `tests/programs/overflow.lamia` of the compiler
+        let source = "
+            fn add(a: i8, b: i8) -> i8 { a + b }
+            fn main() { add(100, 27) }
+        ";
+        assert_eq!(run_display(source), Ok("127".to_string()));
+        assert_eq!(
+            run(&source.replace("27", "28")),
+            Err("attempt to add with overflow".to_string())
+        );
+        assert_eq!(
+            run("fn f(a: u8) {} fn main() { f(256) }"),
+            Err("literal out of range for `u8`".to_string())
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(run("fn main() { 'a' < 'b' }"), Ok(Value::Bool(true)));
+        assert_eq!(run("fn main() { \"b\" >= \"a\" }"), Ok(Value::Bool(true)));
+        assert_eq!(run("fn main() { 1 != 2 }"), Ok(Value::Bool(true)));
+        assert_eq!(
+            run("fn main() { 1 == true }"),
+            Err("cannot apply `==` to `integer` and `bool`".to_string())
+        );
+    }
+}
@@ -0,0 +1,474 @@
+//! Matching values against the patterns of `match` arms and `let` statements.
+
+use std::{cmp::Ordering, rc::Rc};
+
+use modules::{Def, VariantFields};
+use parser::{
+    ast::{AstNode, LiteralPattern, Pattern},
+    syntax::{SyntaxNode, SyntaxToken},
+};
+
+use crate::{enums::variant_path, literal, Enum, Interpreter, Unwind, Value};
+
+type Result<T> = std::result::Result<T, Unwind>;
+
+impl Interpreter {
+    /// Whether `value` matches `pattern`, collecting the variables it binds.
+    pub(crate) fn match_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: &Value,
+        bindings: &mut Vec<(String, Value)>,
+    ) -> Result<bool> {
+        match pattern {
+            Pattern::Wildcard(_) | Pattern::Rest(_) => Ok(true),
+            Pattern::Ident(ident) => {
+                let Some(name) = ident.name() else {
+                    return Ok(true);
+                };
+
+                // like in Rust, a name matches the unit variant it refers to instead of binding
+                if let Some(Def::Variant(span, variant)) =
+                    self.resolve_pattern_path(std::slice::from_ref(&name))
+                {
+                    let enum_def = self.enum_def(span);
+                    if enum_def.variants[variant].fields == VariantFields::Unit {
+                        return self.match_variant(pattern.syntax(), &enum_def, variant, value);
+                    }
+                }
+
+                bindings.push((name.text().to_string(), value.clone()));
+                Ok(true)
+            }
+            Pattern::Literal(literal) => {
+                let expected = self.literal_pattern(literal)?;
+                self.check_type(pattern, value, &expected)?;
+                Ok(*value == expected)
+            }
+            Pattern::Range(range) => {
+                let (Some(start), Some(end)) = (range.start(), range.end()) else {
+                    return Err(self.error(range.syntax(), "expected range end"));
+                };
+                let start = self.literal_pattern(&start)?;
+                let end = self.literal_pattern(&end)?;
+                self.check_type(pattern, value, &start)?;
+
+                let after_start = compare(value, &start).is_some_and(Ordering::is_ge);
+                let before_end = compare(value, &end).is_some_and(|ordering| {
+                    ordering.is_lt() || (range.is_inclusive() && ordering.is_eq())
+                });
+                Ok(after_start && before_end)
+            }
+            Pattern::Or(or) => {
+                for alternative in or.patterns() {
+                    let len = bindings.len();
+                    if self.match_pattern(&alternative, value, bindings)? {
+                        return Ok(true);
+                    }
+                    bindings.truncate(len);
+                }
+                Ok(false)
+            }
+            Pattern::Path(path) => {
+                let segments = path.segments().collect::<Vec<_>>();
+                let (enum_def, variant) = self.pattern_variant(pattern.syntax(), &segments)?;
+                if enum_def.variants[variant].fields != VariantFields::Unit {
+                    let message = format!(
+                        "expected unit variant, found {} variant `{}`",
+                        variant_kind(&enum_def.variants[variant].fields),
+                        variant_path(&enum_def, variant)
+                    );
+                    return Err(self.error(pattern.syntax(), message));
+                }
+
+                self.match_variant(pattern.syntax(), &enum_def, variant, value)
+            }
+            Pattern::TupleStruct(tuple) => {
+                let segments = tuple.segments().collect::<Vec<_>>();
+                let (enum_def, variant) = self.pattern_variant(pattern.syntax(), &segments)?;
+                let VariantFields::Tuple(arity) = enum_def.variants[variant].fields else {
+                    let message = format!(
+                        "expected tuple variant, found {} variant `{}`",
+                        variant_kind(&enum_def.variants[variant].fields),
+                        variant_path(&enum_def, variant)
+                    );
+                    return Err(self.error(pattern.syntax(), message));
+                };
+
+                let patterns = tuple
+                    .pattern_list()
+                    .into_iter()
+                    .flat_map(|list| list.patterns())
+                    .collect::<Vec<_>>();
+                let rest = patterns
+                    .iter()
+                    .position(|pattern| matches!(pattern, Pattern::Rest(_)));
+                let len = patterns.len() - usize::from(rest.is_some());
+                if len > arity || (rest.is_none() && len < arity) {
+                    let message = format!(
+                        "this pattern has {len} field{}, but the corresponding tuple variant has {arity} field{}",
+                        if len == 1 { "" } else { "s" },
+                        if arity == 1 { "" } else { "s" },
+                    );
+                    return Err(self.error(pattern.syntax(), message));
+                }
+
+                if !self.match_variant(pattern.syntax(), &enum_def, variant, value)? {
+                    return Ok(false);
+                }
+                let Value::Enum(value) = value else {
+                    unreachable!("`match_variant` checks the value is an enum");
+                };
+
+                self.match_fields(&patterns, rest, &value.fields, bindings)
+            }
+            Pattern::Struct(record) => {
+                let segments = record.segments().collect::<Vec<_>>();
+                let (enum_def, variant) = self.pattern_variant(pattern.syntax(), &segments)?;
+                let path = variant_path(&enum_def, variant);
+                let VariantFields::Record(names) = &enum_def.variants[variant].fields else {
+                    let message = format!(
+                        "expected struct variant, found {} variant `{path}`",
+                        variant_kind(&enum_def.variants[variant].fields),
+                    );
+                    return Err(self.error(pattern.syntax(), message));
+                };
+
+                let field_list = record.field_list();
+                let fields = field_list
+                    .iter()
+                    .flat_map(|list| list.fields())
+                    .collect::<Vec<_>>();
+                let mut indices = Vec::with_capacity(fields.len());
+                for field in &fields {
+                    let Some(name) = field.name() else {
+                        continue;
+                    };
+                    let Some(index) = names.iter().position(|other| other == name.text()) else {
+                        let message =
+                            format!("variant `{path}` does not have a field named `{name}`");
+                        return Err(self.error(field.syntax(), message));
+                    };
+                    indices.push((field, name, index));
+                }
+
+                let has_rest = field_list.is_some_and(|list| list.has_rest());
+                if !has_rest {
+                    let missing = names
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, _)| indices.iter().all(|(_, _, i)| i != index))
+                        .map(|(_, name)| format!("`{name}`"))
+                        .collect::<Vec<_>>();
+                    if !missing.is_empty() {
+                        let message = format!(
+                            "pattern does not mention field{} {}",
+                            if missing.len() == 1 { "" } else { "s" },
+                            missing.join(", ")
+                        );
+                        return Err(self.error(pattern.syntax(), message));
+                    }
+                }
+
+                if !self.match_variant(pattern.syntax(), &enum_def, variant, value)? {
+                    return Ok(false);
+                }
+                let Value::Enum(value) = value else {
+                    unreachable!("`match_variant` checks the value is an enum");
+                };
+
+                for (field, name, index) in indices {
+                    let field_value = &value.fields[index];
+                    match field.pattern() {
+                        Some(pattern) => {
+                            if !self.match_pattern(&pattern, field_value, bindings)? {
+                                return Ok(false);
+                            }
+                        }
+                        None => bindings.push((name.text().to_string(), field_value.clone())),
+                    }
+                }
+                Ok(true)
+            }
+            Pattern::Tuple(tuple) => {
+                let patterns = tuple.patterns().collect::<Vec<_>>();
+                let elements = match value {
+                    Value::Tuple(elements) => elements.as_slice(),
+                    Value::Unit => &[],
+                    _ => {
+                        return Err(self.error(
+                            pattern.syntax(),
+                            format!(
+                                "mismatched types: expected `{}`, found `{}`",
+                                value.type_name(),
+                                pattern.syntax()
+                            ),
+                        ))
+                    }
+                };
+
+                let rest = patterns
+                    .iter()
+                    .position(|pattern| matches!(pattern, Pattern::Rest(_)));
+                let len = patterns.len() - usize::from(rest.is_some());
+                let arity = elements.len();
+                if len > arity || (rest.is_none() && len < arity) {
+                    let message = format!(
+                        "mismatched types: expected a tuple with {arity} element{}, found one with {len} element{}",
+                        if arity == 1 { "" } else { "s" },
+                        if len == 1 { "" } else { "s" },
+                    );
+                    return Err(self.error(pattern.syntax(), message));
+                }
+
+                self.match_fields(&patterns, rest, elements, bindings)
+            }
+        }
+    }
+
+    /// Matches the fields of a tuple or a tuple variant against `patterns`, in which `rest`
+    /// is the index of `..`, if any.
+    fn match_fields(
+        &mut self,
+        patterns: &[Pattern],
+        rest: Option<usize>,
+        fields: &[Value],
+        bindings: &mut Vec<(String, Value)>,
+    ) -> Result<bool> {
+        // the fields after `..` are matched from the end
+        let arity = fields.len();
+        let indices = match rest {
+            Some(rest) => (0..rest)
+                .chain(arity + rest + 1 - patterns.len()..arity)
+                .collect(),
+            None => (0..arity).collect::<Vec<_>>(),
+        };
+        let patterns = patterns
+            .iter()
+            .filter(|pattern| !matches!(pattern, Pattern::Rest(_)));
+        for (pattern, index) in patterns.zip(indices) {
+            if !self.match_pattern(pattern, &fields[index], bindings)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn resolve_pattern_path(&self, segments: &[SyntaxToken]) -> Option<Def> {
+        let module = self.frames.last()?.module;
+        let path = segments.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+        self.module_tree.resolve_path(module, &path).ok()
+    }
+
+    /// The variant named by the path of a pattern.
+    fn pattern_variant(
+        &mut self,
+        node: &SyntaxNode,
+        segments: &[SyntaxToken],
+    ) -> Result<(Rc<Enum>, usize)> {
+        match self.resolve_pattern_path(segments) {
+            Some(Def::Variant(span, variant)) => Ok((self.enum_def(span), variant)),
+            Some(Def::Struct(span)) => Ok((self.enum_def(span), 0)),
+            _ => {
+                let path = segments
+                    .iter()
+                    .map(SyntaxToken::text)
+                    .collect::<Vec<_>>()
+                    .join("::");
+                Err(self.error(node, format!("cannot find variant `{path}` in this scope")))
+            }
+        }
+    }
+
+    /// Whether `value` is of the given variant, which is an error for values of other types.
+    fn match_variant(
+        &self,
+        node: &SyntaxNode,
+        enum_def: &Rc<Enum>,
+        variant: usize,
+        value: &Value,
+    ) -> Result<bool> {
+        match value {
+            Value::Enum(value) if value.enum_def.span == enum_def.span => {
+                Ok(value.variant == variant)
+            }
+            _ => Err(self.error(
+                node,
+                format!(
+                    "mismatched types: expected `{}`, found `{}`",
+                    value.type_name(),
+                    enum_def.name
+                ),
+            )),
+        }
+    }
+
+    fn literal_pattern(&self, pattern: &LiteralPattern) -> Result<Value> {
+        let token = pattern
+            .token()
+            .ok_or_else(|| self.error(pattern.syntax(), "expected literal"))?;
+        literal::value(token.kind(), token.text(), pattern.is_negative())
+            .map_err(|message| self.error(pattern.syntax(), message))
+    }
+
+    /// Literals only match values of their own type, anything else is a mistake in the program.
+    fn check_type(&self, pattern: &Pattern, value: &Value, expected: &Value) -> Result<()> {
+        if std::mem::discriminant(value) == std::mem::discriminant(expected) {
+            return Ok(());
+        }
+
+        Err(self.error(
+            pattern.syntax(),
+            format!(
+                "mismatched types: expected `{}`, found `{}`",
+                value.type_name(),
+                expected.type_name()
+            ),
+        ))
+    }
+}
+
+fn variant_kind(fields: &VariantFields) -> &'static str {
+    match fields {
+        VariantFields::Unit => "unit",
+        VariantFields::Tuple(_) => "tuple",
+        VariantFields::Record(_) => "struct",
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Int(a, _), Value::Int(b, _)) => Some(a.cmp(b)),
+        (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+        (Value::Char(a), Value::Char(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::run, Value};
+
+    #[test]
+    fn match_literals_and_ranges() {
+        let source = "
+            fn classify(n) {
+                match n {
+                    0 => 'z',
+                    -9..=-1 | 1..10 => 'd',
+                    10 | 100 => 't',
+                    _ => 'o',
+                }
+            }
+            fn main() { [classify(0), classify(-3), classify(9), classify(10), classify(11)] }
+        ";
+        assert_eq!(
+            run(source).map(|value| value.to_string()),
+            Ok("[z, d, d, t, o]".to_string())
+        );
+    }
+
+    #[test]
+    fn match_bindings_and_guards() {
+        let source = "
+            fn main() {
+                let c = 'q';
+                let kind = match c { 'a'..='z' if c != 'q' => 1, x => x == 'q' };
+                let n = match 7 { n if n % 2 == 0 => n, n => n * 2 };
+                [kind, n]
+            }
+        ";
+        assert_eq!(
+            run(source).map(|value| value.to_string()),
+            Ok("[true, 14]".to_string())
+        );
+    }
+
+    #[test]
+    fn match_without_matching_arm() {
+        assert_eq!(
+            run("fn main() { match 3 { 1 => 1, 2 => 2 } }"),
+            Err("no match arm matches the value `3`".to_string())
+        );
+        assert_eq!(
+            run(r#"fn main() { match "a" { 1 => 1, _ => 2 } }"#),
+            Err("mismatched types: expected `string`, found `integer`".to_string())
+        );
+        assert_eq!(
+            run("fn main() { match true { true => 1, false => 0 } }"),
+            Ok(Value::Int(1, None))
+        );
+    }
+
+    #[test]
+    fn match_variants() {
+        let source = "
+            enum Shape { Circle(f64), Rect { w: f64, h: f64 }, Line(i64, i64, i64), Empty }
+            use Shape::Empty;
+            fn area(s) {
+                match s {
+                    Shape::Circle(r) => 3.0 * r * r,
+                    Shape::Rect { w, h: 1.0 } => w,
+                    Shape::Rect { w, .. } => w * 2.0,
+                    Shape::Line(a, .., b) => a + b,
+                    Empty => 0.0,
+                }
+            }
+            fn main() {
+                [
+                    area(Shape::Circle(1.0)),
+                    area(Shape::Rect { w: 2.0, h: 1.0 }),
+                    area(Shape::Rect { w: 2.0, h: 3.0 }),
+                    area(Shape::Empty),
+                ]
+            }
+        ";
+        assert_eq!(
+            run(source).map(|value| value.to_string()),
+            Ok("[3.0, 2.0, 4.0, 0.0]".to_string())
+        );
+        let source = "
+            enum E { A(i64, i64, i64) }
+            fn main() { match E::A(1, 2, 3) { E::A(a, .., b) => a * 10 + b } }
+        ";
+        assert_eq!(run(source), Ok(Value::Int(13, None)));
+    }
+
+    #[test]
+    fn invalid_variant_patterns() {
+        let run_match = |arms: &str| {
+            let source = format!(
+                "enum E {{ A, B(i64), C {{ x: i64, y: i64 }} }}
+                fn main() {{ match E::B(1) {{ {arms} }} }}"
+            );
+            run(&source)
+        };
+        assert_eq!(
+            run_match("E::B(a, b) => a"),
+            Err(
+                "this pattern has 2 fields, but the corresponding tuple variant has 1 field"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            run_match("E::B => 1"),
+            Err("expected unit variant, found tuple variant `E::B`".to_string())
+        );
+        assert_eq!(
+            run_match("E::C { z, .. } => 1"),
+            Err("variant `E::C` does not have a field named `z`".to_string())
+        );
+        assert_eq!(
+            run_match("E::C { x } => 1"),
+            Err("pattern does not mention field `y`".to_string())
+        );
+        assert_eq!(
+            run_match("E::D => 1"),
+            Err("cannot find variant `E::D` in this scope".to_string())
+        );
+        assert_eq!(
+            run("enum E { A } fn main() { match 1 { E::A => 1, _ => 2 } }"),
+            Err("mismatched types: expected `integer`, found `E`".to_string())
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Finding the functions marked `#[test]` and running them.
+
+use std::collections::HashMap;
+
+use db::Database;
+use modules::{Def, ModuleTree};
+use parser::ast::{AstNode, FunctionExpr};
+use source_map::{Span, TextRange};
+
+use crate::{Interpreter, RuntimeError};
+
+/// A function marked `#[test]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Test {
+    /// The path of the function from the root module, such as `math::adds`.
+    pub path: String,
+    /// The span of the function's name, which identifies it.
+    pub span: Span,
+    /// Whether the function is also marked `#[ignore]`, in which case it isn't run.
+    pub ignored: bool,
+}
+
+/// Finds the tests in every module of the program, sorted by path.
+pub fn discover_tests(db: &Database, module_tree: &ModuleTree) -> Vec<Test> {
+    let mut roots = HashMap::new();
+    let mut tests = Vec::new();
+
+    for module in module_tree.modules() {
+        let data = module_tree.get(module);
+        let root = roots
+            .entry(data.file)
+            .or_insert_with(|| db.parse(data.file).syntax());
+
+        for (name, entry) in &data.scope {
+            let Def::Function(span) = entry.def else {
+                continue;
+            };
+            if entry.imported {
+                continue;
+            }
+
+            let function = root
+                .covering_element(span.range)
+                .into_token()
+                .and_then(|name| name.parent())
+                .and_then(FunctionExpr::cast);
+            let Some(function) = function else {
+                continue;
+            };
+
+            let attrs = function
+                .attrs()
+                .filter_map(|attr| attr.name())
+                .map(|name| name.text().to_string())
+                .collect::<Vec<_>>();
+            if !attrs.iter().any(|attr| attr == "test") {
+                continue;
+            }
+
+            let path = match data.parent {
+                Some(_) => format!("{}::{name}", module_tree.path(module)),
+                None => name.clone(),
+            };
+            tests.push(Test {
+                path,
+                span,
+                ignored: attrs.iter().any(|attr| attr == "ignore"),
+            });
+        }
+    }
+
+    tests.sort_by(|a, b| a.path.cmp(&b.path));
+    tests
+}
+
+impl Interpreter {
+    /// Runs a test, which passes if it returns without an error.
+    pub fn run_test(&mut self, test: &Test) -> Result<(), RuntimeError> {
+        let function = self.function(test.span).ok_or_else(|| {
+            RuntimeError::new(
+                Span::new(test.span.file, TextRange::default()),
+                format!("cannot find test `{}`", test.path),
+            )
+        })?;
+
+        self.call_function(&function, Vec::new(), test.span)
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use db::Database;
+
+    use super::discover_tests;
+    use crate::Interpreter;
+
+    #[test]
+    fn discover_and_run_tests() {
+        let source = "
+            #[test]
+            fn passes() { assert_eq(helper(), 2) }
+            #[test]
+            fn fails() { assert(helper() > 2) }
+            #[test]
+            #[ignore]
+            fn ignored() {}
+            fn helper() { 2 }
+            mod inner {
+                #[test]
+                fn nested() { assert(true) }
+                pub fn not_a_test() {}
+            }
+            use inner::not_a_test;
+        ";
+        let mut db = Database::new();
+        let root = db.add_file("/p/main.lamia", source);
+        let (module_tree, diagnostics) = modules::build(&mut db, root);
+        assert_eq!(diagnostics, []);
+
+        let tests = discover_tests(&db, &module_tree);
+        let summary = tests
+            .iter()
+            .map(|test| (test.path.as_str(), test.ignored))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("fails", false),
+                ("ignored", true),
+                ("inner::nested", false),
+                ("passes", false),
+            ]
+        );
+
+        let mut interpreter = Interpreter::new(&db, module_tree);
+        let results = tests
+            .iter()
+            .map(|test| interpreter.run_test(test).map_err(|error| error.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            [
+                Err("assertion failed: `helper() > 2`".to_string()),
+                Ok(()),
+                Ok(()),
+                Ok(()),
+            ]
+        );
+    }
+
+    #[test]
+    fn files_are_parsed_once() {
+        let mut db = Database::new();
+        let root = db.add_file("/p/main.lamia", "#[test]\nfn passes() {}\nfn main() { 1 }");
+        let (module_tree, _) = modules::build(&mut db, root);
+        db.take_executed();
+
+        let tests = discover_tests(&db, &module_tree);
+        let mut interpreter = Interpreter::new(&db, module_tree);
+        assert_eq!(interpreter.run_test(&tests[0]), Ok(()));
+        assert!(!db
+            .take_executed()
+            .iter()
+            .any(|query| query.starts_with("parse(")));
+    }
+}
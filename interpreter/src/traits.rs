@@ -0,0 +1,645 @@
+//! Traits and impls: finding the methods of values and checking the bounds of generic
+//! parameters. Like the other types, impls apply to values as they are at runtime.
+
+use std::rc::Rc;
+
+use modules::{Def, ModuleId, ResolveError};
+use parser::{
+    ast::{AstNode, ImplStmt, MethodCallExpr, PathType},
+    literal::IntTy,
+    syntax::SyntaxNode,
+};
+use source_map::{FileId, Span};
+
+use crate::{
+    generic_bounds, generic_names,
+    types::{generic_params, substitute, type_of, unify, unify_types},
+    Function, Interpreter, RuntimeError, Ty, Unwind, Value,
+};
+
+type Result<T> = std::result::Result<T, Unwind>;
+
+/// An impl whose self type has been lowered.
+#[derive(Debug)]
+pub(crate) struct Impl {
+    /// The implemented trait, by the span of its name. `None` for inherent impls.
+    trait_def: Option<Span>,
+    file: FileId,
+    module: ModuleId,
+    generics: Vec<String>,
+    bounds: Vec<(usize, PathType)>,
+    /// The type whose values get the functions, in terms of the generic parameters.
+    self_ty: Ty,
+    /// The functions of the impl and the ones it gets from its trait, by the spans of
+    /// their names.
+    functions: Vec<(String, Span)>,
+}
+
+/// An impl applying to a value, with the types of its generic parameters.
+struct Candidate {
+    impl_def: Rc<Impl>,
+    function: Span,
+    bindings: Vec<Option<Ty>>,
+}
+
+impl Interpreter {
+    /// Evaluates `receiver.method(args)`, passing the receiver as the `self` parameter.
+    pub(crate) fn eval_method_call(&mut self, call: &MethodCallExpr) -> Result<Value> {
+        let receiver = match call.receiver() {
+            Some(receiver) => self.eval_expr(&receiver)?,
+            None => return Err(self.error(call.syntax(), "expected expression")),
+        };
+        let Some(name) = call.name() else {
+            return Err(self.error(call.syntax(), "expected method name"));
+        };
+
+        let mut args = vec![receiver];
+        for arg in call.arg_list().into_iter().flat_map(|list| list.args()) {
+            args.push(self.eval_expr(&arg)?);
+        }
+
+        if let Value::Host(receiver) = &args[0] {
+            let receiver = receiver.clone();
+            if let Some(value) = self.eval_host_method_call(&receiver, name.text(), &args, call)? {
+                return Ok(value);
+            }
+        }
+
+        let method = self.method(&args[0], name.text(), call)?;
+        let expected = method.params.len() - 1;
+        let supplied = args.len() - 1;
+        if expected != supplied {
+            return Err(self.error(
+                call.syntax(),
+                format!(
+                    "method `{name}` takes {expected} argument{} but {supplied} {} supplied",
+                    if expected == 1 { "" } else { "s" },
+                    if supplied == 1 { "was" } else { "were" },
+                ),
+            ));
+        }
+
+        let span = self.span(call.syntax());
+        Ok(self.call_function(&method, args, span)?)
+    }
+
+    /// Finds the method `name` of `receiver`.
+    fn method(
+        &mut self,
+        receiver: &Value,
+        name: &str,
+        call: &MethodCallExpr,
+    ) -> Result<Rc<Function>> {
+        match self.lookup_method(receiver, name, call.syntax())? {
+            Some(method) => Ok(method),
+            None => {
+                let ty = type_of(receiver);
+                let message = format!("no method named `{name}` found for `{ty}`");
+                Err(self.error(call.syntax(), message))
+            }
+        }
+    }
+
+    /// Finds the method `name` of `receiver`, or `None` if its type has no function `name`.
+    fn lookup_method(
+        &mut self,
+        receiver: &Value,
+        name: &str,
+        node: &SyntaxNode,
+    ) -> Result<Option<Rc<Function>>> {
+        let mut candidates = Vec::new();
+        for impl_def in self.impls()?.iter() {
+            let Some(&(_, function)) = impl_def.functions.iter().find(|(n, _)| n == name) else {
+                continue;
+            };
+
+            let mut bindings = vec![None; impl_def.generics.len()];
+            if unify(receiver, &impl_def.self_ty, &mut bindings)
+                && self.satisfies_bounds(impl_def, &bindings, &mut Vec::new())?
+            {
+                candidates.push(Candidate {
+                    impl_def: impl_def.clone(),
+                    function,
+                    bindings,
+                });
+            }
+        }
+
+        // an integer without a type is an `i64` when the impls of several types apply to it
+        let i64_ty = Ty::Int(Some(IntTy::I64));
+        if matches!(receiver, Value::Int(_, None))
+            && candidates.iter().any(|c| c.impl_def.self_ty == i64_ty)
+        {
+            candidates.retain(|c| c.impl_def.self_ty == i64_ty);
+        }
+
+        let ty = type_of(receiver).to_string();
+        let Some(candidate) = self.select(candidates, name, &ty, node)? else {
+            return Ok(None);
+        };
+
+        let method = self.impl_function(&candidate)?;
+        let is_method = method
+            .params
+            .first()
+            .and_then(|param| param.name())
+            .is_some_and(|param| param.text() == "self");
+        if !is_method {
+            let message = format!("`{name}` is an associated function of `{ty}`, not a method");
+            return Err(self.error(node, message));
+        }
+        Ok(Some(method))
+    }
+
+    /// Formats a value embedded in an interpolated string: with the `to_string` method of
+    /// its type if there's one, much like a `Display` impl, or like it's printed otherwise.
+    pub(crate) fn display(&mut self, value: &Value, node: &SyntaxNode) -> Result<String> {
+        let Some(method) = self.lookup_method(value, "to_string", node)? else {
+            return Ok(value.to_string());
+        };
+        if method.params.len() != 1 {
+            let message = format!(
+                "`to_string` of `{}` must take no arguments besides `self`",
+                type_of(value)
+            );
+            return Err(self.error(node, message));
+        }
+
+        let span = self.span(node);
+        match self.call_function(&method, vec![value.clone()], span)? {
+            Value::String(string) => Ok(string.to_string()),
+            other => Err(self.error(
+                node,
+                format!(
+                    "expected `to_string` to return `string`, found `{}`",
+                    other.type_name()
+                ),
+            )),
+        }
+    }
+
+    /// Returns the function `Enum::name` defined by the impls of an enum, or `None` if
+    /// there's no such function.
+    pub(crate) fn associated_function(
+        &mut self,
+        enum_span: Span,
+        name: &str,
+        node: &SyntaxNode,
+    ) -> Result<Option<Rc<Function>>> {
+        let mut candidates = Vec::new();
+        for impl_def in self.impls()?.iter() {
+            let Ty::Enum(enum_def, _) = &impl_def.self_ty else {
+                continue;
+            };
+            let Some(&(_, function)) = impl_def.functions.iter().find(|(n, _)| n == name) else {
+                continue;
+            };
+
+            // without a value, the types of the impl's generic parameters are unknown
+            if enum_def.span == enum_span {
+                candidates.push(Candidate {
+                    impl_def: impl_def.clone(),
+                    function,
+                    bindings: vec![None; impl_def.generics.len()],
+                });
+            }
+        }
+
+        let ty = self.enum_def(enum_span).name.clone();
+        match self.select(candidates, name, &ty, node)? {
+            Some(candidate) => Ok(Some(self.impl_function(&candidate)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Picks the function to call among the ones of the impls applying to `ty`. Inherent
+    /// impls are preferred over traits, and it's an error for several traits to apply.
+    fn select(
+        &self,
+        mut candidates: Vec<Candidate>,
+        name: &str,
+        ty: &str,
+        node: &SyntaxNode,
+    ) -> Result<Option<Candidate>> {
+        if candidates.iter().any(|c| c.impl_def.trait_def.is_none()) {
+            candidates.retain(|c| c.impl_def.trait_def.is_none());
+        }
+        if candidates.len() <= 1 {
+            return Ok(candidates.pop());
+        }
+
+        let trait_name = |trait_def| &self.module_tree.trait_data(trait_def).name;
+        let first = candidates[0].impl_def.trait_def;
+        let message = if candidates.iter().all(|c| c.impl_def.trait_def == first) {
+            match first {
+                Some(trait_def) => format!(
+                    "conflicting implementations of trait `{}` for type `{ty}`",
+                    trait_name(trait_def)
+                ),
+                None => format!("duplicate definitions with name `{name}` for type `{ty}`"),
+            }
+        } else {
+            let traits = candidates
+                .iter()
+                .filter_map(|c| c.impl_def.trait_def)
+                .map(|trait_def| format!("`{}`", trait_name(trait_def)))
+                .collect::<Vec<_>>();
+            format!(
+                "multiple applicable items in scope: `{name}` is defined by the traits {}",
+                traits.join(", ")
+            )
+        };
+        Err(self.error(node, message))
+    }
+
+    /// Returns the function of an impl seeing the types of the impl's generic parameters
+    /// and `Self`.
+    fn impl_function(
+        &mut self,
+        candidate: &Candidate,
+    ) -> std::result::Result<Rc<Function>, RuntimeError> {
+        let not_found = || RuntimeError::new(candidate.function, "expected function");
+        let function = self.function(candidate.function).ok_or_else(not_found)?;
+
+        let impl_def = &candidate.impl_def;
+        let self_ty = substitute(&impl_def.self_ty, &candidate.bindings);
+        let type_env = impl_def
+            .generics
+            .iter()
+            .cloned()
+            .zip(
+                candidate
+                    .bindings
+                    .iter()
+                    .map(|ty| ty.clone().unwrap_or(Ty::Unknown)),
+            )
+            .chain([("Self".to_string(), self_ty)])
+            .collect();
+        Ok(self.with_type_env(&function, type_env, true))
+    }
+
+    /// The impls of the program, whose types are lowered the first time they're needed.
+    fn impls(&mut self) -> std::result::Result<Rc<[Rc<Impl>]>, RuntimeError> {
+        if let Some(impls) = &self.impls {
+            return Ok(impls.clone());
+        }
+
+        let mut impls = Vec::new();
+        for data in self.module_tree.impls().to_vec() {
+            let Some(stmt) = self
+                .files
+                .get(&data.span.file)
+                .and_then(|root| root.covering_element(data.span.range).into_token())
+                .and_then(|keyword| keyword.parent())
+                .and_then(ImplStmt::cast)
+            else {
+                continue;
+            };
+            let Some(self_type) = stmt.self_type() else {
+                continue;
+            };
+
+            let generics = generic_names(stmt.generic_param_list());
+            let env = generic_params(&generics).collect::<Vec<_>>();
+            let self_ty = self
+                .lower_type(&self_type, data.module, &env)
+                .map_err(|message| {
+                    let span = Span::new(data.span.file, self_type.syntax().text_range());
+                    RuntimeError::new(span, message)
+                })?;
+
+            // the functions the trait provides are used unless the impl overrides them
+            let mut functions = data.functions.clone();
+            if let Some(trait_def) = data.trait_def {
+                for function in &self.module_tree.trait_data(trait_def).functions {
+                    if function.has_body
+                        && !functions.iter().any(|(name, _)| name == &function.name)
+                    {
+                        functions.push((function.name.clone(), function.span));
+                    }
+                }
+            }
+
+            impls.push(Rc::new(Impl {
+                trait_def: data.trait_def,
+                file: data.span.file,
+                module: data.module,
+                bounds: generic_bounds(stmt.generic_param_list()),
+                generics,
+                self_ty,
+                functions,
+            }));
+        }
+
+        let impls = Rc::<[_]>::from(impls);
+        self.impls = Some(impls.clone());
+        Ok(impls)
+    }
+
+    /// Checks that the types of generic parameters implement the traits they're bound by,
+    /// returning the error for the first one which doesn't. The bounds are written in `file`
+    /// and resolved in `module`.
+    pub(crate) fn check_bounds(
+        &mut self,
+        bounds: &[(usize, PathType)],
+        types: &[Ty],
+        file: FileId,
+        module: ModuleId,
+    ) -> std::result::Result<Option<String>, RuntimeError> {
+        for (index, bound) in bounds {
+            let trait_def = self.resolve_trait(bound, file, module)?;
+            if !self.implements(&types[*index], trait_def, &mut Vec::new())? {
+                return Ok(Some(format!(
+                    "the trait bound `{}: {}` is not satisfied",
+                    types[*index],
+                    self.module_tree.trait_data(trait_def).name
+                )));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether the type implements the trait. Unknown types implement every trait, since
+    /// no value of them can tell otherwise.
+    ///
+    /// `visiting` holds the types and traits being checked, as an impl whose bounds need
+    /// the impl itself doesn't apply.
+    fn implements(
+        &mut self,
+        ty: &Ty,
+        trait_def: Span,
+        visiting: &mut Vec<(Ty, Span)>,
+    ) -> std::result::Result<bool, RuntimeError> {
+        if *ty == Ty::Unknown {
+            return Ok(true);
+        }
+        if visiting
+            .iter()
+            .any(|(other, span)| other == ty && *span == trait_def)
+        {
+            return Ok(false);
+        }
+
+        visiting.push((ty.clone(), trait_def));
+        let mut implemented = false;
+        for impl_def in self.impls()?.iter() {
+            if impl_def.trait_def != Some(trait_def) {
+                continue;
+            }
+
+            let mut bindings = vec![None; impl_def.generics.len()];
+            if unify_types(&impl_def.self_ty, ty, &mut bindings)
+                && self.satisfies_bounds(impl_def, &bindings, visiting)?
+            {
+                implemented = true;
+                break;
+            }
+        }
+        visiting.pop();
+
+        Ok(implemented)
+    }
+
+    /// Whether the types bound to the generic parameters of an impl satisfy its bounds.
+    fn satisfies_bounds(
+        &mut self,
+        impl_def: &Impl,
+        bindings: &[Option<Ty>],
+        visiting: &mut Vec<(Ty, Span)>,
+    ) -> std::result::Result<bool, RuntimeError> {
+        for (index, bound) in &impl_def.bounds {
+            let trait_def = self.resolve_trait(bound, impl_def.file, impl_def.module)?;
+            let ty = bindings[*index].clone().unwrap_or(Ty::Unknown);
+            if !self.implements(&ty, trait_def, visiting)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Resolves the trait of a bound written in `file`.
+    fn resolve_trait(
+        &self,
+        bound: &PathType,
+        file: FileId,
+        module: ModuleId,
+    ) -> std::result::Result<Span, RuntimeError> {
+        let segments = bound
+            .segments()
+            .map(|segment| segment.text().to_string())
+            .collect::<Vec<_>>();
+        let path = segments.join("::");
+        let names = segments.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let message = match self.module_tree.resolve_path(module, &names) {
+            Ok(Def::Trait(span)) => return Ok(span),
+            Ok(_) => format!("expected trait, found `{path}`"),
+            Err(ResolveError::Private(index)) => format!("`{}` is private", segments[index]),
+            Err(_) => format!("cannot find trait `{path}` in this scope"),
+        };
+        let span = Span::new(file, bound.syntax().text_range());
+        Err(RuntimeError::new(span, message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::run_display;
+
+    #[test]
+    fn call_methods() {
+        let source = r#"
+            enum Point { P { x: i64, y: i64 } }
+            trait Show {
+                fn show(self) -> String;
+                fn twice(self) -> String { self.show() + self.show() }
+            }
+            impl Point {
+                fn new(x: i64, y: i64) -> Self { Point::P { x, y } }
+                fn sum(self, z: i64) -> i64 { match self { Point::P { x, y } => x + y + z } }
+            }
+            impl Show for Point {
+                fn show(self) -> String { "p" }
+            }
+            impl Show for i64 {
+                fn show(self) -> String { "i" }
+                fn twice(self) -> String { "ii" }
+            }
+            fn main() {
+                let p = Point::new(1, 2);
+                [p.show(), p.twice(), 3.show(), 3.twice(), Point::show(p)]
+            }
+        "#;
+        assert_eq!(run_display(source), Ok("[p, pp, i, ii, p]".to_string()));
+        assert_eq!(
+            run_display(
+                "enum P { A } impl P { fn sum(self, z: i64) -> i64 { z } } fn main() { P::A.sum(1) }"
+            ),
+            Ok("1".to_string())
+        );
+    }
+
+    #[test]
+    fn generic_impls() {
+        let source = r#"
+            enum Wrap<T> { W(T) }
+            trait Show { fn show(self) -> String; }
+            impl Show for bool { fn show(self) -> String { "b" } }
+            impl<T: Show> Show for Wrap<T> {
+                fn show(self) -> String { match self { Wrap::W(x) => "w" + x.show() } }
+            }
+            impl<T> Wrap<T> {
+                fn get(self) -> T { match self { Wrap::W(x) => x } }
+            }
+            fn show_all<T: Show>(items: [T]) -> String {
+                if items == [] { "" } else { items[0].show() }
+            }
+            fn main() { [Wrap::W(Wrap::W(true)).show(), show_all([Wrap::W(false)]), Wrap::W(1).get().show()] }
+        "#;
+        assert_eq!(
+            run_display(source),
+            Err("no method named `show` found for `integer`".to_string())
+        );
+        assert_eq!(
+            run_display(&source.replace(".get().show()", ".get()")),
+            Ok("[wwb, wb, 1]".to_string())
+        );
+    }
+
+    #[test]
+    fn check_bounds() {
+        let source = r#"
+            trait Show { fn show(self) -> String; }
+            impl Show for bool { fn show(self) -> String { "b" } }
+            fn show<T: Show>(x: T) -> String { x.show() }
+            enum Shown<T: Show> { S(T) }
+        "#;
+        let cases = [
+            ("show(true)", Ok("b")),
+            (
+                "show(1)",
+                Err("the trait bound `integer: Show` is not satisfied"),
+            ),
+            (
+                "Shown::S(1)",
+                Err("the trait bound `integer: Show` is not satisfied"),
+            ),
+            (
+                "1.show()",
+                Err("no method named `show` found for `integer`"),
+            ),
+        ];
+
+        for (expr, expected) in cases {
+            let source = format!("{source} fn main() {{ {expr} }}");
+            let expected = expected.map(str::to_string).map_err(str::to_string);
+            assert_eq!(run_display(&source), expected, "{expr}");
+        }
+    }
+
+    #[test]
+    fn display_with_to_string() {
+        let source = r#"
+            enum Point { P { x: i64, y: i64 } }
+            impl Point {
+                fn to_string(self) -> String { match self { Point::P { x, y } => f"<{x}; {y}>" } }
+            }
+            enum Plain { A }
+            fn main() { f"{Point::P { x: 1, y: 2 }} {Plain::A}" }
+        "#;
+        assert_eq!(run_display(source), Ok("<1; 2> A".to_string()));
+
+        let source = "
+            enum E { A }
+            impl E { fn to_string(self) -> i64 { 1 } }
+            fn main() { f\"{E::A}\" }
+        ";
+        assert_eq!(
+            run_display(source),
+            Err("expected `to_string` to return `string`, found `i64`".to_string())
+        );
+    }
+
+    #[test]
+    fn ambiguous_methods() {
+        let source = r#"
+            trait A { fn f(self) -> i64; }
+            trait B { fn f(self) -> i64; }
+            impl A for bool { fn f(self) -> i64 { 1 } }
+            impl B for bool { fn f(self) -> i64 { 2 } }
+            fn main() { true.f() }
+        "#;
+        assert_eq!(
+            run_display(source),
+            Err(
+                "multiple applicable items in scope: `f` is defined by the traits `A`, `B`"
+                    .to_string()
+            )
+        );
+
+        // inherent methods take precedence over the ones of traits
+        let source = source.replace(
+            "fn main()",
+            "impl bool { fn f(self) -> i64 { 0 } } fn main()",
+        );
+        assert_eq!(run_display(&source), Ok("0".to_string()));
+
+        let source = r#"
+            enum E<T> { V(T) }
+            trait A { fn f(self) -> i64; }
+            impl<T> A for E<T> { fn f(self) -> i64 { 1 } }
+            impl A for E<bool> { fn f(self) -> i64 { 2 } }
+            fn main() { E::V(true).f() }
+        "#;
+        assert_eq!(
+            run_display(source),
+            Err("conflicting implementations of trait `A` for type `E<bool>`".to_string())
+        );
+    }
+
+    #[test]
+    fn impls_for_integer_types() {
+        let source = r#"
+            trait Show { fn show(self) -> String; }
+            impl Show for i32 { fn show(self) -> String { "i32" } }
+            impl Show for u8 { fn show(self) -> String { "u8" } }
+            impl Show for i64 { fn show(self) -> String { "i64" } }
+            fn main() { let a: u8 = 1; f"{a.show()} {2i32.show()} {3.show()}" }
+        "#;
+        assert_eq!(run_display(source), Ok("u8 i32 i64".to_string()));
+    }
+
+    #[test]
+    fn invalid_method_calls() {
+        let source = r#"
+            enum P { A }
+            impl P {
+                fn new() -> Self { P::A }
+                fn get(self, x: i64) -> i64 { x }
+            }
+        "#;
+        let cases = [
+            (
+                "P::A.get()",
+                "method `get` takes 1 argument but 0 were supplied",
+            ),
+            (
+                "P::A.new()",
+                "`new` is an associated function of `P`, not a method",
+            ),
+            (
+                "P::A.get(true)",
+                "mismatched types: expected `i64`, found `bool`",
+            ),
+            (
+                "P::missing()",
+                "cannot find value `P::missing` in this scope",
+            ),
+        ];
+
+        for (expr, message) in cases {
+            let source = format!("{source} fn main() {{ {expr} }}");
+            assert_eq!(run_display(&source), Err(message.to_string()), "{expr}");
+        }
+    }
+}
@@ -0,0 +1,822 @@
+//! Types at runtime. Annotations are checked as values flow through them, which is also
+//! how the arguments of generic functions and enums are inferred.
+
+use std::{fmt, rc::Rc};
+
+use modules::{Def, ModuleId};
+use parser::{
+    ast::{AstNode, Expr, PathType, Type},
+    literal::{self, IntTy, Number},
+};
+use source_map::Span;
+
+use crate::{Enum, Interpreter, Value};
+
+/// A type whose generic parameters have been replaced by their arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Ty {
+    /// An integer type, `None` for the type of integers whose type isn't told yet.
+    Int(Option<IntTy>),
+    Float,
+    Bool,
+    Char,
+    String,
+    Unit,
+    /// Arrays and slices, whose length isn't checked.
+    Array(Box<Ty>),
+    /// Tuples of at least one element, as `()` is [`Ty::Unit`].
+    Tuple(Vec<Ty>),
+    /// Functions and closures, whose signature isn't checked.
+    Fn,
+    /// Maps, by the types of their keys and their values.
+    Map(Box<Ty>, Box<Ty>),
+    /// A type of the program embedding the interpreter, by name.
+    Host(&'static str),
+    Enum(Rc<Enum>, Vec<Ty>),
+    /// The generic parameter with this index while its argument is being inferred.
+    Param(usize, Rc<str>),
+    /// A type which can't be told, such as the elements of an empty array. Any value has it.
+    Unknown,
+}
+
+/// The generic parameters in scope and their types.
+pub(crate) type TypeEnv = Rc<[(String, Ty)]>;
+
+/// The types of the annotations of a function's signature, lowered on its first call so
+/// that calls don't resolve them again. Each one is `None` when it isn't annotated.
+#[derive(Debug)]
+pub(crate) struct SignatureTypes {
+    pub(crate) params: Vec<Option<Result<Ty, String>>>,
+    pub(crate) return_type: Option<Result<Ty, String>>,
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(ty) => f.write_str(ty.map_or("integer", IntTy::name)),
+            Self::Float => f.write_str("float"),
+            Self::Bool => f.write_str("bool"),
+            Self::Char => f.write_str("char"),
+            Self::String => f.write_str("string"),
+            Self::Unit => f.write_str("()"),
+            Self::Array(element) => write!(f, "[{element}]"),
+            Self::Tuple(elements) => {
+                f.write_str("(")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{element}")?;
+                }
+                if elements.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+            Self::Fn => f.write_str("function"),
+            Self::Map(key, value) => write!(f, "Map<{key}, {value}>"),
+            Self::Host(name) => f.write_str(name),
+            Self::Enum(enum_def, args) => {
+                f.write_str(&enum_def.name)?;
+                if !args.is_empty() {
+                    f.write_str("<")?;
+                    for (index, arg) in args.iter().enumerate() {
+                        if index > 0 {
+                            f.write_str(", ")?;
+                        }
+                        write!(f, "{arg}")?;
+                    }
+                    f.write_str(">")?;
+                }
+                Ok(())
+            }
+            Self::Param(_, name) => f.write_str(name),
+            Self::Unknown => f.write_str("_"),
+        }
+    }
+}
+
+impl Interpreter {
+    /// Lowers a type annotation whose names are resolved in `module`, replacing the generic
+    /// parameters in `env` by their types.
+    pub(crate) fn lower_type(
+        &mut self,
+        ty: &Type,
+        module: ModuleId,
+        env: &[(String, Ty)],
+    ) -> Result<Ty, String> {
+        match ty {
+            Type::Path(path) => self.lower_path_type(path, module, env),
+            Type::Array(array) => self.lower_element_type(array.element_type(), module, env),
+            Type::Slice(slice) => self.lower_element_type(slice.element_type(), module, env),
+            Type::Fn(_) => Ok(Ty::Fn),
+            Type::Tuple(tuple) => {
+                let fields = self.lower_types(tuple.fields(), module, env)?;
+                Ok(match fields.len() {
+                    0 => Ty::Unit,
+                    1 if tuple.is_paren() => fields.into_iter().next().unwrap_or(Ty::Unknown),
+                    _ => Ty::Tuple(fields),
+                })
+            }
+            // values are copied on write, so references are the values themselves
+            Type::Ref(reference) => match reference.ty() {
+                Some(ty) => self.lower_type(&ty, module, env),
+                None => Ok(Ty::Unknown),
+            },
+            Type::Optional(optional) => {
+                let ty = match optional.ty() {
+                    Some(ty) => self.lower_type(&ty, module, env)?,
+                    None => Ty::Unknown,
+                };
+                self.lower_enum_type(&["Option"], vec![ty], module)
+            }
+        }
+    }
+
+    /// Binds the generic parameters which the values didn't tell to the generic arguments
+    /// the checker inferred for the use of the item at `span`.
+    pub(crate) fn fill_type_args(&mut self, bindings: &mut [Option<Ty>], span: Span) {
+        if bindings.iter().all(Option::is_some) {
+            return;
+        }
+        let Some(args) = self.type_args.get(&span).cloned() else {
+            return;
+        };
+        for (binding, arg) in bindings.iter_mut().zip(&args) {
+            if binding.is_none() {
+                *binding = Some(self.checked_type(arg));
+            }
+        }
+    }
+
+    /// The type of the values of a type the checker told.
+    fn checked_type(&mut self, ty: &checker::Ty) -> Ty {
+        match ty {
+            checker::Ty::Int(ty) => Ty::Int(*ty),
+            checker::Ty::Float => Ty::Float,
+            checker::Ty::Bool => Ty::Bool,
+            checker::Ty::Char => Ty::Char,
+            checker::Ty::String => Ty::String,
+            checker::Ty::Unit => Ty::Unit,
+            checker::Ty::Array(element) => Ty::Array(Box::new(self.checked_type(element))),
+            checker::Ty::Tuple(types) => {
+                Ty::Tuple(types.iter().map(|ty| self.checked_type(ty)).collect())
+            }
+            checker::Ty::Function => Ty::Fn,
+            checker::Ty::Map => Ty::Map(Box::new(Ty::Unknown), Box::new(Ty::Unknown)),
+            checker::Ty::Enum(span, args) => {
+                let enum_def = self.enum_def(*span);
+                let args = match args.is_empty() {
+                    true => vec![Ty::Unknown; enum_def.generics.len()],
+                    false => args.iter().map(|arg| self.checked_type(arg)).collect(),
+                };
+                Ty::Enum(enum_def, args)
+            }
+            checker::Ty::Unknown | checker::Ty::Var(_) => Ty::Unknown,
+        }
+    }
+
+    /// Lowers the generic arguments in `f::<A, B>` or `Option<A>`.
+    pub(crate) fn lower_types(
+        &mut self,
+        types: impl Iterator<Item = Type>,
+        module: ModuleId,
+        env: &[(String, Ty)],
+    ) -> Result<Vec<Ty>, String> {
+        types.map(|ty| self.lower_type(&ty, module, env)).collect()
+    }
+
+    fn lower_element_type(
+        &mut self,
+        element_type: Option<Type>,
+        module: ModuleId,
+        env: &[(String, Ty)],
+    ) -> Result<Ty, String> {
+        let element = match element_type {
+            Some(ty) => self.lower_type(&ty, module, env)?,
+            None => Ty::Unknown,
+        };
+        Ok(Ty::Array(Box::new(element)))
+    }
+
+    fn lower_path_type(
+        &mut self,
+        path: &PathType,
+        module: ModuleId,
+        env: &[(String, Ty)],
+    ) -> Result<Ty, String> {
+        let segments = path
+            .segments()
+            .map(|segment| segment.text().to_string())
+            .collect::<Vec<_>>();
+        let args = path
+            .generic_arg_list()
+            .map(|list| self.lower_types(list.types(), module, env))
+            .transpose()?;
+
+        if let [name] = segments.as_slice() {
+            // generic parameters shadow the ones of enclosing functions and other types
+            if let Some((_, ty)) = env.iter().rev().find(|(param, _)| param == name) {
+                return match args {
+                    Some(_) => Err(format!(
+                        "type arguments are not allowed on type parameter `{name}`"
+                    )),
+                    None => Ok(ty.clone()),
+                };
+            }
+            if let Some(ty) = primitive(name) {
+                return match args {
+                    Some(_) => Err(format!(
+                        "type arguments are not allowed on builtin type `{name}`"
+                    )),
+                    None => Ok(ty),
+                };
+            }
+            // like builtin types, host types don't shadow the program's enums
+            let is_enum = self.module_tree.resolve_path(module, &[name]).is_ok();
+            if let Some((&host, _)) = self.host.types.get_key_value(name.as_str()) {
+                if !is_enum {
+                    return match args {
+                        Some(_) => Err(format!(
+                            "type arguments are not allowed on host type `{name}`"
+                        )),
+                        None => Ok(Ty::Host(host)),
+                    };
+                }
+            }
+            // enums named `Map` take precedence over the builtin type
+            if name == "Map" && self.module_tree.resolve_path(module, &[name]).is_err() {
+                return match args.as_deref() {
+                    Some([key, value]) => {
+                        Ok(Ty::Map(Box::new(key.clone()), Box::new(value.clone())))
+                    }
+                    Some(args) => Err(generic_count_mismatch("type", name, 2, args.len())),
+                    None => Err(format!("missing generics for type `{name}`")),
+                };
+            }
+        }
+
+        let names = segments.iter().map(String::as_str).collect::<Vec<_>>();
+        self.lower_enum_type(&names, args.unwrap_or_default(), module)
+    }
+
+    /// Lowers the enum at `segments` with the generic arguments `args`, such as `Option<A>`.
+    fn lower_enum_type(
+        &mut self,
+        segments: &[&str],
+        args: Vec<Ty>,
+        module: ModuleId,
+    ) -> Result<Ty, String> {
+        let path = segments.join("::");
+        let Ok(Def::Enum(span) | Def::Struct(span)) =
+            self.module_tree.resolve_path(module, segments)
+        else {
+            return Err(format!("cannot find type `{path}` in this scope"));
+        };
+
+        let enum_def = self.enum_def(span);
+        if args.len() != enum_def.generics.len() {
+            if args.is_empty() {
+                return Err(format!("missing generics for {} `{path}`", enum_def.kind()));
+            }
+            return Err(generic_count_mismatch(
+                enum_def.kind(),
+                &path,
+                enum_def.generics.len(),
+                args.len(),
+            ));
+        }
+
+        Ok(Ty::Enum(enum_def, args))
+    }
+}
+
+/// The primitive type `name`, or `None` if `name` isn't primitive.
+fn primitive(name: &str) -> Option<Ty> {
+    if let Some(ty) = IntTy::from_name(name) {
+        return Some(Ty::Int(Some(ty)));
+    }
+    let ty = match name {
+        "f32" | "f64" => Ty::Float,
+        "bool" => Ty::Bool,
+        "char" => Ty::Char,
+        "str" | "String" => Ty::String,
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// The type of a value, as far as it can be told from the value itself.
+pub(crate) fn type_of(value: &Value) -> Ty {
+    match value {
+        Value::Unit => Ty::Unit,
+        Value::Int(_, ty) => Ty::Int(*ty),
+        Value::Float(_) => Ty::Float,
+        Value::Bool(_) => Ty::Bool,
+        Value::Char(_) => Ty::Char,
+        Value::String(_) => Ty::String,
+        Value::Array(elements) => {
+            Ty::Array(Box::new(elements.first().map_or(Ty::Unknown, type_of)))
+        }
+        Value::Tuple(elements) => Ty::Tuple(elements.iter().map(type_of).collect()),
+        Value::Function(_) => Ty::Fn,
+        Value::Enum(value) => Ty::Enum(value.enum_def.clone(), value.type_args.clone()),
+        Value::Map(entries) => match entries.first_key_value() {
+            Some((key, value)) => {
+                Ty::Map(Box::new(type_of(&key.to_value())), Box::new(type_of(value)))
+            }
+            None => Ty::Map(Box::new(Ty::Unknown), Box::new(Ty::Unknown)),
+        },
+        Value::Host(value) => Ty::Host(value.name()),
+    }
+}
+
+/// The generic parameters `names`, as the types whose arguments are inferred.
+pub(crate) fn generic_params(names: &[String]) -> impl Iterator<Item = (String, Ty)> + '_ {
+    names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name.clone(), Ty::Param(index, name.as_str().into())))
+}
+
+/// Whether `value` has type `ty`, binding the generic parameters of `ty` that aren't bound
+/// yet to the types of the corresponding parts of `value`.
+pub(crate) fn unify(value: &Value, ty: &Ty, bindings: &mut [Option<Ty>]) -> bool {
+    match (ty, value) {
+        (Ty::Array(element), Value::Array(elements)) => {
+            elements.iter().all(|value| unify(value, element, bindings))
+        }
+        (Ty::Tuple(types), Value::Tuple(elements)) => {
+            types.len() == elements.len()
+                && elements
+                    .iter()
+                    .zip(types)
+                    .all(|(value, ty)| unify(value, ty, bindings))
+        }
+        (Ty::Map(key, value), Value::Map(entries)) => entries
+            .iter()
+            .all(|(k, v)| unify(&k.to_value(), key, bindings) && unify(v, value, bindings)),
+        // the fields of enum values have been checked against their type arguments already
+        (Ty::Enum(enum_def, args), Value::Enum(value)) => {
+            enum_def.span == value.enum_def.span
+                && args
+                    .iter()
+                    .zip(&value.type_args)
+                    .all(|(expected, actual)| unify_types(expected, actual, bindings))
+        }
+        _ => unify_types(ty, &type_of(value), bindings),
+    }
+}
+
+/// Whether values of type `actual` have type `expected`, binding generic parameters like
+/// [`unify`] does.
+pub(crate) fn unify_types(expected: &Ty, actual: &Ty, bindings: &mut [Option<Ty>]) -> bool {
+    match (expected, actual) {
+        (Ty::Unknown, _) | (_, Ty::Unknown) => true,
+        (Ty::Param(index, _), _) => match bindings[*index].clone() {
+            // the type of an integer is bound once it's told
+            Some(Ty::Int(None)) if matches!(actual, Ty::Int(_)) => {
+                bindings[*index] = Some(actual.clone());
+                true
+            }
+            Some(bound) => unify_types(&bound, actual, bindings),
+            None => {
+                bindings[*index] = Some(actual.clone());
+                true
+            }
+        },
+        (Ty::Array(expected), Ty::Array(actual)) => unify_types(expected, actual, bindings),
+        (Ty::Map(expected_key, expected_value), Ty::Map(actual_key, actual_value)) => {
+            unify_types(expected_key, actual_key, bindings)
+                && unify_types(expected_value, actual_value, bindings)
+        }
+        (Ty::Tuple(expected), Ty::Tuple(actual)) => {
+            expected.len() == actual.len()
+                && expected
+                    .iter()
+                    .zip(actual)
+                    .all(|(expected, actual)| unify_types(expected, actual, bindings))
+        }
+        (Ty::Enum(expected, expected_args), Ty::Enum(actual, actual_args)) => {
+            expected.span == actual.span
+                && expected_args
+                    .iter()
+                    .zip(actual_args)
+                    .all(|(expected, actual)| unify_types(expected, actual, bindings))
+        }
+        (Ty::Int(expected), Ty::Int(actual)) => {
+            expected.is_none() || actual.is_none() || expected == actual
+        }
+        _ => expected == actual,
+    }
+}
+
+/// Gives the integers of `value` without a type the integer types at the same places in
+/// `ty`, which `value` has as told by [`unify`]. Fails for the integers that don't fit in
+/// their type.
+pub(crate) fn fit(value: &mut Value, ty: &Ty, bindings: &[Option<Ty>]) -> Result<(), String> {
+    if !has_untyped_int(value) {
+        return Ok(());
+    }
+
+    match (ty, value) {
+        (Ty::Param(index, _), value) => match &bindings[*index] {
+            Some(bound) => fit(value, bound, bindings),
+            None => Ok(()),
+        },
+        (Ty::Int(Some(ty)), Value::Int(n, untyped)) => {
+            if !ty.contains(*n) {
+                return Err(format!("literal out of range for `{}`", ty.name()));
+            }
+            *untyped = Some(*ty);
+            Ok(())
+        }
+        (Ty::Array(element), Value::Array(elements)) => Rc::make_mut(elements)
+            .iter_mut()
+            .try_for_each(|value| fit(value, element, bindings)),
+        (Ty::Tuple(types), Value::Tuple(elements)) => Rc::make_mut(elements)
+            .iter_mut()
+            .zip(types)
+            .try_for_each(|(value, ty)| fit(value, ty, bindings)),
+        (Ty::Map(_, ty), Value::Map(entries)) => Rc::make_mut(entries)
+            .values_mut()
+            .try_for_each(|value| fit(value, ty, bindings)),
+        _ => Ok(()),
+    }
+}
+
+/// Whether `value` has integers without a type that [`fit`] would give one.
+fn has_untyped_int(value: &Value) -> bool {
+    match value {
+        Value::Int(_, ty) => ty.is_none(),
+        Value::Array(elements) | Value::Tuple(elements) => elements.iter().any(has_untyped_int),
+        Value::Map(entries) => entries.values().any(has_untyped_int),
+        _ => false,
+    }
+}
+
+/// Replaces the generic parameters of `ty` which are bound by their types.
+pub(crate) fn substitute(ty: &Ty, bindings: &[Option<Ty>]) -> Ty {
+    match ty {
+        Ty::Param(index, _) => match &bindings[*index] {
+            Some(bound) => bound.clone(),
+            None => ty.clone(),
+        },
+        Ty::Array(element) => Ty::Array(Box::new(substitute(element, bindings))),
+        Ty::Map(key, value) => Ty::Map(
+            Box::new(substitute(key, bindings)),
+            Box::new(substitute(value, bindings)),
+        ),
+        Ty::Tuple(elements) => Ty::Tuple(
+            elements
+                .iter()
+                .map(|element| substitute(element, bindings))
+                .collect(),
+        ),
+        Ty::Enum(enum_def, args) => Ty::Enum(
+            enum_def.clone(),
+            args.iter().map(|arg| substitute(arg, bindings)).collect(),
+        ),
+        _ => ty.clone(),
+    }
+}
+
+/// The error for `value` not having the type of `annotation`, which is shown as written
+/// unless it mentions generic parameters, whose types are shown instead.
+pub(crate) fn mismatch(
+    annotation: &Type,
+    ty: &Ty,
+    bindings: &[Option<Ty>],
+    env: &[(String, Ty)],
+    value: &Value,
+) -> String {
+    let is_generic = annotation
+        .syntax()
+        .descendants()
+        .filter_map(PathType::cast)
+        .filter(|path| path.segments().count() == 1)
+        .filter_map(|path| path.name())
+        .any(|name| env.iter().any(|(param, _)| param == name.text()));
+
+    let expected = if is_generic {
+        substitute(ty, bindings).to_string()
+    } else {
+        annotation.syntax().to_string()
+    };
+    format!(
+        "mismatched types: expected `{expected}`, found `{}`",
+        value.type_name()
+    )
+}
+
+/// The error for an array not having the length `N` of an annotation `[T; N]`, whose
+/// length is only known when written as a literal.
+pub(crate) fn len_mismatch(annotation: &Type, value: &Value) -> Option<String> {
+    let expected = match annotation {
+        Type::Array(array) => match array.len()? {
+            Expr::Literal(len) => match literal::number(len.token()?.text()) {
+                Ok(Number::Int(len, _)) => len,
+                _ => return None,
+            },
+            _ => return None,
+        },
+        Type::Ref(reference) => return len_mismatch(&reference.ty()?, value),
+        _ => return None,
+    };
+    let Value::Array(elements) = value else {
+        return None;
+    };
+    let found = elements.len() as u64;
+    (expected != found).then(|| {
+        format!(
+            "mismatched types: expected an array of length {expected}, found one of length {found}"
+        )
+    })
+}
+
+/// The error for the wrong number of generic arguments given to a function or an enum.
+pub(crate) fn generic_count_mismatch(
+    kind: &str,
+    name: &str,
+    expected: usize,
+    found: usize,
+) -> String {
+    format!(
+        "{kind} `{name}` takes {expected} generic argument{} but {found} generic argument{} {} supplied",
+        if expected == 1 { "" } else { "s" },
+        if found == 1 { "" } else { "s" },
+        if found == 1 { "was" } else { "were" },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use db::Database;
+
+    use crate::{
+        tests::{run, run_display},
+        Interpreter,
+    };
+
+    const OPTION: &str = "
+        enum Option<T> { Some(T), None }
+        use Option::Some;
+        use Option::None;
+    ";
+
+    #[test]
+    fn generic_functions() {
+        let source = "
+            enum Option<T> { Some(T), None }
+            fn id<T>(x: T) -> T { x }
+            fn first<T>(a: [T]) -> Option<T> {
+                if a == [] { Option::None } else { Option::Some(a[0]) }
+            }
+            fn unwrap_or<T>(o: Option<T>, default: T) -> T {
+                match o { Option::Some(x) => x, Option::None => default }
+            }
+            fn main() {
+                [
+                    id(1),
+                    unwrap_or(first([2, 3]), 0),
+                    unwrap_or(first::<i64>([]), 4),
+                    unwrap_or(Option::None, 5),
+                ]
+            }
+        ";
+        assert_eq!(run_display(source), Ok("[1, 2, 4, 5]".to_string()));
+    }
+
+    #[test]
+    fn inference_errors() {
+        let cases = [
+            (
+                "fn pair<T>(a: T, b: T) {} fn main() { pair(1, 2.0) }",
+                "mismatched types: expected `integer`, found `float`",
+            ),
+            (
+                "fn f<T>(a: [T]) {} fn main() { f([1, 'a']) }",
+                "mismatched types: expected `[integer]`, found `array`",
+            ),
+            (
+                "fn make<T>() -> [T] { [] } fn main() { make() }",
+                "type annotations needed: cannot infer type of the type parameter `T` declared on the function `make`",
+            ),
+            (
+                "fn f<T>(a: [T]) {} fn main() { f([]) }",
+                "type annotations needed: cannot infer type of the type parameter `T` declared on the function `f`",
+            ),
+            (
+                "fn bad<T>(x: T) -> T { 1.0 } fn main() { bad(1) }",
+                "mismatched types: expected `integer`, found `float`",
+            ),
+            (
+                "fn f(a: i32) {} fn main() { f(1.0) }",
+                "mismatched types: expected `i32`, found `float`",
+            ),
+        ];
+
+        for (source, message) in cases {
+            assert_eq!(run(source), Err(message.to_string()), "{source}");
+        }
+    }
+
+    #[test]
+    fn explicit_generic_args() {
+        let source = "
+            fn id<T>(x: T) -> T { x }
+            fn make<T>() -> [T] { [] }
+            fn main() { [id::<f64>(1.5), make::<[bool]>()] }
+        ";
+        assert_eq!(run_display(source), Ok("[1.5, []]".to_string()));
+
+        let cases = [
+            (
+                "id::<f64>(1)",
+                "mismatched types: expected `float`, found `integer`",
+            ),
+            (
+                "id::<f64, i64>(1)",
+                "function `id` takes 1 generic argument but 2 generic arguments were supplied",
+            ),
+            ("id::<Foo>(1)", "cannot find type `Foo` in this scope"),
+            (
+                "{ let x = 1; x::<i64> }",
+                "type arguments are not allowed on local variable `x`",
+            ),
+        ];
+        for (expr, message) in cases {
+            let source = format!("fn id<T>(x: T) -> T {{ x }} fn main() {{ {expr} }}");
+            assert_eq!(run(&source), Err(message.to_string()), "{expr}");
+        }
+    }
+
+    #[test]
+    fn generic_enums() {
+        let source = format!(
+            "{OPTION}
+            enum Pair<A, B> {{ P {{ a: A, b: B }} }}
+            fn main() {{ [Some(Pair::P {{ a: 1, b: 'x' }}), Option::<Pair<i64, char>>::None] }}"
+        );
+        assert_eq!(
+            run_display(&source),
+            Ok("[Some(P { a: 1, b: x }), None]".to_string())
+        );
+
+        let cases = [
+            (
+                "enum Two<T> { P(T, T) } fn main() { Two::P(1, 2.0) }",
+                "mismatched types: expected `integer`, found `float`",
+            ),
+            (
+                "fn main() { Option::<i64>::Some(1.0) }",
+                "mismatched types: expected `i64`, found `float`",
+            ),
+            (
+                "fn main() { Option::<i64, i64>::None }",
+                "enum `Option` takes 1 generic argument but 2 generic arguments were supplied",
+            ),
+            (
+                "fn f(o: Option) {} fn main() { f(None) }",
+                "missing generics for enum `Option`",
+            ),
+            (
+                "fn f(o: Option<i64>) {} fn main() { f(Some(1.0)) }",
+                "mismatched types: expected `Option<i64>`, found `Option`",
+            ),
+            (
+                "fn f<T>(o: Option<T>, x: T) {} fn main() { f(Some(1), 'a') }",
+                "mismatched types: expected `integer`, found `char`",
+            ),
+        ];
+        for (source, message) in cases {
+            let source = format!("{OPTION} {source}");
+            assert_eq!(run(&source), Err(message.to_string()), "{source}");
+        }
+    }
+
+    #[test]
+    fn ref_and_optional_types() {
+        let source = format!(
+            "{OPTION}
+            fn first<T>(items: &[T]) -> T? {{ Some(items[0]) }}
+            fn main() {{ let a: &mut i64?? = Some(first([1, 2])); a }}"
+        );
+        assert_eq!(run_display(&source), Ok("Some(Some(1))".to_string()));
+
+        assert_eq!(
+            run(&format!(
+                "{OPTION} fn f(x: i64?) {{}} fn main() {{ f(Some('a')) }}"
+            )),
+            Err("mismatched types: expected `i64?`, found `Option`".to_string())
+        );
+        assert_eq!(
+            run("fn f(x: i64?) {} fn main() { f(1) }"),
+            Err("cannot find type `Option` in this scope".to_string())
+        );
+    }
+
+    #[test]
+    fn let_annotations() {
+        let source = "
+            fn f<T>(x: T) -> [T] { let items: [T] = [x, x]; items }
+            fn main() { let a: i32 = 1; let (b, c): (bool, [i32]) = (true, f(a)); c }
+        ";
+        assert_eq!(run_display(source), Ok("[1, 1]".to_string()));
+
+        let cases = [
+            (
+                "fn main() { let x: i32 = \"s\"; }",
+                "mismatched types: expected `i32`, found `string`",
+            ),
+            (
+                "fn f<T>(x: T) { let y: T = 1; } fn main() { f('a') }",
+                "mismatched types: expected `char`, found `integer`",
+            ),
+            (
+                "fn main() { let x: u8 = 300; }",
+                "literal out of range for `u8`",
+            ),
+            (
+                "fn main() { let x: [i8] = [1, 200]; }",
+                "literal out of range for `i8`",
+            ),
+            (
+                "fn main() { let x: i32 = 1u8; }",
+                "mismatched types: expected `i32`, found `u8`",
+            ),
+            (
+                "fn main() { let x: Foo = 1; }",
+                "cannot find type `Foo` in this scope",
+            ),
+        ];
+        for (source, message) in cases {
+            assert_eq!(run(source), Err(message.to_string()), "{source}");
+        }
+    }
+
+    #[test]
+    fn array_lengths() {
+        let source = "fn sum(a: &[i64; 3]) -> i64 { a[0] + a[2] } fn main() { sum([1, 2, 3]) }";
+        assert_eq!(run_display(source), Ok("4".to_string()));
+
+        let cases = [
+            "fn sum(a: [i64; 3]) {} fn main() { sum([1, 2]) }",
+            "fn f() -> [i64; 3] { [1, 2] } fn main() { f() }",
+        ];
+        for source in cases {
+            assert_eq!(
+                run(source),
+                Err(
+                    "mismatched types: expected an array of length 3, found one of length 2"
+                        .to_string()
+                ),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn args_inferred_by_the_checker() {
+        let source = "
+            enum Opt<T> { Some(T), None }
+            fn none<T>() -> Opt<T> { Opt::None }
+            fn take(o: Opt<i64>) -> i64 { match o { Opt::Some(x) => x, Opt::None => 0 } }
+            fn main() { let o: Opt<i64> = none(); let p = none(); [take(o), take(none()), take(p)] }
+        ";
+        assert_eq!(run_display(source), Ok("[0, 0, 0]".to_string()));
+    }
+
+    #[test]
+    fn closures_see_generic_params() {
+        let source = "
+            fn constant<T>(x: T) -> fn() -> T { || -> T { x } }
+            fn check<T>(x: T) -> fn(T) -> T { |y: T| y }
+            fn main() { [constant(1)(), check('a')('b')] }
+        ";
+        assert_eq!(run_display(source), Ok("[1, b]".to_string()));
+        assert_eq!(
+            run("fn check<T>(x: T) -> fn(T) -> T { |y: T| y } fn main() { check(1)(2.0) }"),
+            Err("mismatched types: expected `integer`, found `float`".to_string())
+        );
+    }
+
+    #[test]
+    fn instances_are_shared() {
+        let mut db = Database::new();
+        let root = db.add_file(
+            "/p/main.lamia",
+            "fn id<T>(x: T) -> T { x } fn main() { [id(1), id(2), id(3.0), id::<i64>(4)] }",
+        );
+        let (module_tree, _) = modules::build(&mut db, root);
+
+        let mut interpreter = Interpreter::new(&db, module_tree);
+        let result = interpreter.call(&["main"], Vec::new());
+        assert_eq!(
+            result.map(|value| value.to_string()),
+            Ok("[1, 2, 3.0, 4]".to_string())
+        );
+        // integers without a type have an instance of their own
+        assert_eq!(interpreter.instances.len(), 3);
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::BTreeMap,
+    fmt,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use modules::{ModuleId, VariantData, VariantFields};
+use parser::{
+    ast::{Expr, Param, PathType, Type},
+    literal::IntTy,
+};
+use source_map::{FileId, Span};
+
+use crate::{
+    host::HostValue,
+    types::{SignatureTypes, Ty, TypeEnv},
+};
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Unit,
+    /// An integer and its type, `None` for integers whose type isn't told yet, like the
+    /// ones of literals without a suffix. It takes the type of the annotations and the
+    /// operands it meets, and is an `i64` otherwise.
+    Int(i128, Option<IntTy>),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    String(Rc<str>),
+    /// Arrays have value semantics: they are copied on write when shared.
+    Array(Rc<Vec<Value>>),
+    /// Tuples of at least one element, as `()` is [`Value::Unit`].
+    Tuple(Rc<Vec<Value>>),
+    Function(Rc<Function>),
+    Enum(Rc<EnumValue>),
+    /// Maps are sorted by key, and copied on write like arrays.
+    Map(Rc<BTreeMap<Key, Value>>),
+    /// A value of a type of the program embedding the interpreter.
+    Host(Rc<HostValue>),
+}
+
+/// A value that can be the key of a map, which is one that can be ordered.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Key {
+    Unit,
+    Bool(bool),
+    /// Integers of any type, so that looking an integer up doesn't depend on its type.
+    Int(i128),
+    Char(char),
+    String(Rc<str>),
+    Tuple(Rc<[Key]>),
+}
+
+/// A local variable. Closures that assign to a variable share it with their environment.
+pub(crate) type Variable = Rc<RefCell<Value>>;
+
+/// A function along with the module its body resolves names in and, for closures, the
+/// variables captured from where it was created.
+#[derive(Debug)]
+pub struct Function {
+    /// `None` for closures and anonymous functions.
+    pub name: Option<String>,
+    /// The span of the name, which identifies the instances of generic functions.
+    pub span: Option<Span>,
+    pub file: FileId,
+    pub module: ModuleId,
+    /// The names of the generic parameters, empty once the function is instantiated.
+    pub generics: Vec<String>,
+    /// The traits that the generic parameters must implement, by the parameters' index.
+    pub bounds: Vec<(usize, PathType)>,
+    pub params: Vec<Param>,
+    pub return_type: Option<Type>,
+    pub body: Option<Expr>,
+    /// The types of the generic parameters the body can refer to.
+    pub(crate) type_env: TypeEnv,
+    pub(crate) captures: Vec<(String, Variable)>,
+    pub(crate) signature: OnceCell<SignatureTypes>,
+}
+
+/// An enum, shared by all of its values.
+#[derive(Debug)]
+pub struct Enum {
+    /// The span of the enum's name, which identifies it.
+    pub span: Span,
+    pub name: String,
+    pub module: ModuleId,
+    pub generics: Vec<String>,
+    /// The traits that the generic parameters must implement, by the parameters' index.
+    pub bounds: Vec<(usize, PathType)>,
+    pub variants: Vec<VariantData>,
+    /// Whether it's a struct, whose single variant is named after it.
+    pub is_struct: bool,
+}
+
+impl Enum {
+    /// What the type is, as said in error messages.
+    pub fn kind(&self) -> &'static str {
+        if self.is_struct {
+            "struct"
+        } else {
+            "enum"
+        }
+    }
+}
+
+impl PartialEq for Enum {
+    fn eq(&self, other: &Self) -> bool {
+        self.span == other.span
+    }
+}
+
+impl Eq for Enum {}
+
+impl Hash for Enum {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.span.hash(state);
+    }
+}
+
+/// A value of an enum: the index of its variant and its fields in declaration order,
+/// so matching on it doesn't compare names.
+#[derive(Debug, Clone)]
+pub struct EnumValue {
+    pub enum_def: Rc<Enum>,
+    pub variant: usize,
+    pub fields: Box<[Value]>,
+    /// The types of the enum's generic parameters, [`Ty::Unknown`] for those that
+    /// the fields don't tell, like in `None`.
+    pub type_args: Vec<Ty>,
+}
+
+impl EnumValue {
+    pub fn variant(&self) -> &VariantData {
+        &self.enum_def.variants[self.variant]
+    }
+
+    /// The index of the field `name` of a struct, `None` for values of enums, which only
+    /// give their fields to patterns.
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        match &self.variant().fields {
+            VariantFields::Record(names) if self.enum_def.is_struct => {
+                names.iter().position(|field| field == name)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Value {
+    /// The name of the value's type, as shown in error messages.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Self::Unit => "()",
+            Self::Int(_, ty) => ty.map_or("integer", IntTy::name),
+            Self::Float(_) => "float",
+            Self::Bool(_) => "bool",
+            Self::Char(_) => "char",
+            Self::String(_) => "string",
+            Self::Array(_) => "array",
+            Self::Tuple(_) => "tuple",
+            Self::Function(_) => "function",
+            Self::Enum(value) => &value.enum_def.name,
+            Self::Map(_) => "map",
+            Self::Host(value) => value.name(),
+        }
+    }
+}
+
+impl Key {
+    /// The key with the same value, `None` if the value's type can't be a key.
+    pub fn new(value: &Value) -> Option<Self> {
+        let key = match value {
+            Value::Unit => Self::Unit,
+            Value::Bool(b) => Self::Bool(*b),
+            Value::Int(n, _) => Self::Int(*n),
+            Value::Char(c) => Self::Char(*c),
+            Value::String(s) => Self::String(s.clone()),
+            Value::Tuple(elements) => {
+                Self::Tuple(elements.iter().map(Self::new).collect::<Option<_>>()?)
+            }
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::Unit => Value::Unit,
+            Self::Bool(b) => Value::Bool(*b),
+            Self::Int(n) => Value::Int(*n, None),
+            Self::Char(c) => Value::Char(*c),
+            Self::String(s) => Value::String(s.clone()),
+            Self::Tuple(elements) => {
+                Value::Tuple(Rc::new(elements.iter().map(Self::to_value).collect()))
+            }
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Unit, Self::Unit) => true,
+            // operators check that the types of integers match before comparing them
+            (Self::Int(a, _), Self::Int(b, _)) => a == b,
+            (Self::Float(a), Self::Float(b)) => a == b,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Char(a), Self::Char(b)) => a == b,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Array(a), Self::Array(b)) => a == b,
+            (Self::Tuple(a), Self::Tuple(b)) => a == b,
+            (Self::Function(a), Self::Function(b)) => Rc::ptr_eq(a, b),
+            (Self::Map(a), Self::Map(b)) => a == b,
+            (Self::Host(a), Self::Host(b)) => Rc::ptr_eq(a, b),
+            (Self::Enum(a), Self::Enum(b)) => {
+                a.enum_def.span == b.enum_def.span && a.variant == b.variant && a.fields == b.fields
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unit => f.write_str("()"),
+            Self::Int(n, _) => write!(f, "{n}"),
+            // `{:?}` keeps the fractional part of whole numbers, e.g. `1.0`
+            Self::Float(n) => write!(f, "{n:?}"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Char(c) => write!(f, "{c}"),
+            Self::String(s) => f.write_str(s),
+            Self::Array(elements) => {
+                f.write_str("[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{element}")?;
+                }
+                f.write_str("]")
+            }
+            Self::Tuple(elements) => {
+                f.write_str("(")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{element}")?;
+                }
+                // `(1,)` tells a tuple of one element apart from a parenthesized value
+                if elements.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+            Self::Function(function) => match &function.name {
+                Some(name) => write!(f, "fn {name}"),
+                None => f.write_str("closure"),
+            },
+            Self::Enum(value) => {
+                let variant = value.variant();
+                f.write_str(&variant.name)?;
+                match &variant.fields {
+                    VariantFields::Unit => Ok(()),
+                    VariantFields::Tuple(_) => {
+                        f.write_str("(")?;
+                        for (index, field) in value.fields.iter().enumerate() {
+                            if index > 0 {
+                                f.write_str(", ")?;
+                            }
+                            write!(f, "{field}")?;
+                        }
+                        f.write_str(")")
+                    }
+                    VariantFields::Record(names) => {
+                        f.write_str(" {")?;
+                        for (index, (name, field)) in names.iter().zip(&value.fields).enumerate() {
+                            let separator = if index > 0 { "," } else { "" };
+                            write!(f, "{separator} {name}: {field}")?;
+                        }
+                        f.write_str(" }")
+                    }
+                }
+            }
+            Self::Map(entries) => {
+                f.write_str("{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {value}", key.to_value())?;
+                }
+                f.write_str("}")
+            }
+            Self::Host(value) => write!(f, "<{}>", value.name()),
+        }
+    }
+}
@@ -1,11 +1,59 @@
 use logos::Logos;
 
 #[derive(Debug)]
-pub struct Lexer<'a>(logos::Lexer<'a, Token<'a>>);
+pub struct Lexer<'a> {
+    inner: logos::Lexer<'a, Token<'a>>,
+    /// For every interpolated string being lexed, innermost last, the number of braces
+    /// opened by its current expression, so that the `}` closing the expression resumes
+    /// the text of the string.
+    interpolations: Vec<u32>,
+}
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
-        Self(Token::lexer(input))
+        Self {
+            inner: Token::lexer(input),
+            interpolations: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    /// The lexed token (or `Err(())` for unrecognized input) along with its source text.
+    type Item = (Result<Token<'a>, ()>, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut token = self.inner.next()?;
+
+        match (&token, self.interpolations.last_mut()) {
+            (Ok(Token::InterpolatedString(InterpolatedString::Start(_))), _) => {
+                self.interpolations.push(0);
+            }
+            (Ok(Token::Grouping(Grouping::OpenBrace)), Some(depth)) => *depth += 1,
+            (Ok(Token::Grouping(Grouping::CloseBrace)), Some(depth)) if *depth > 0 => {
+                *depth -= 1;
+            }
+            (Ok(Token::Grouping(Grouping::CloseBrace)), Some(_)) => {
+                token = match string_text(&mut self.inner) {
+                    Some(true) => Ok(Token::InterpolatedString(InterpolatedString::Middle(
+                        self.inner.slice(),
+                    ))),
+                    Some(false) => {
+                        self.interpolations.pop();
+                        Ok(Token::InterpolatedString(InterpolatedString::End(
+                            self.inner.slice(),
+                        )))
+                    }
+                    None => {
+                        self.interpolations.pop();
+                        Err(())
+                    }
+                };
+            }
+            _ => {}
+        }
+
+        Some((token, self.inner.slice()))
     }
 }
 
@@ -16,6 +64,16 @@ pub enum Keyword {
     If,
     Else,
     Return,
+    Mod,
+    Use,
+    Pub,
+    Match,
+    Enum,
+    Struct,
+    Trait,
+    Impl,
+    For,
+    Mut,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -41,6 +99,15 @@ pub enum Operator {
     Star,
     Slash,
     Modulo,
+
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseNot,
+    ShiftLeft,
+    ShiftRight,
+
+    Question,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -51,6 +118,11 @@ pub enum Assign {
     SlashEqual,
     StarEqual,
     ModuloEqual,
+    BitwiseAndEqual,
+    BitwiseOrEqual,
+    BitwiseXorEqual,
+    ShiftLeftEqual,
+    ShiftRightEqual,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -58,7 +130,13 @@ pub enum Delimiter {
     Comma,
     Semicolon,
     Colon,
+    ColonColon,
     RightArrow,
+    FatArrow,
+    Dot,
+    DotDot,
+    DotDotEqual,
+    Pound,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -71,6 +149,20 @@ pub enum Grouping {
     CloseBrace,
 }
 
+/// A piece of an `f"..."` string. The expressions embedded between braces are lexed as
+/// regular tokens between a `Start` or a `Middle` and the following `Middle` or `End`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InterpolatedString<'source> {
+    /// `f"text"`, a string without embedded expressions.
+    Plain(&'source str),
+    /// `f"text{`
+    Start(&'source str),
+    /// `}text{`
+    Middle(&'source str),
+    /// `}text"`
+    End(&'source str),
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Number<'source> {
     Integer(&'source str),
@@ -101,7 +193,13 @@ pub enum Token<'source> {
     #[token(",", |_| Delimiter::Comma)]
     #[token(";", |_| Delimiter::Semicolon)]
     #[token(":", |_| Delimiter::Colon)]
+    #[token("::", |_| Delimiter::ColonColon)]
     #[token("->", |_| Delimiter::RightArrow)]
+    #[token("=>", |_| Delimiter::FatArrow)]
+    #[token(".", |_| Delimiter::Dot)]
+    #[token("..", |_| Delimiter::DotDot)]
+    #[token("..=", |_| Delimiter::DotDotEqual)]
+    #[token("#", |_| Delimiter::Pound)]
     Delimiter(Delimiter),
 
     #[regex("(?&ident)")]
@@ -114,6 +212,9 @@ pub enum Token<'source> {
     #[regex(r#""(?:[^"]|\\")*""#)]
     String(&'source str),
 
+    #[token("f\"", interpolated_string)]
+    InterpolatedString(InterpolatedString<'source>),
+
     #[token("true", |_| true)]
     #[token("false", |_| false)]
     Bool(bool),
@@ -122,7 +223,9 @@ pub enum Token<'source> {
     #[regex("0[xX](?&hex)(?&int_suffix)?", |n| Number::HexInteger(n.slice()))]
     #[regex("0[oO](?&octal)(?&int_suffix)?", |n| Number::OctalInteger(n.slice()))]
     #[regex("0[bB](?&binary)(?&int_suffix)?", |n| Number::BinaryInteger(n.slice()))]
-    #[regex(r#"(((?&decimal)\.(?&decimal)?(?&exp)?(?&float_suffix)?)|(\.(?&decimal)(?&exp)?(?&float_suffix)?)|((?&decimal)(?&exp)(?&float_suffix)?)|((?&decimal)(?&exp)?(?&float_suffix)))"#, |n| Number::Float(n.slice()))]
+    // the fractional part is required, so that `1..=5` is a range rather than `1.` followed by `.=5`
+    // unlike in C, floats can't start with `.`, so that `t.0` accesses a tuple field
+    #[regex(r#"(((?&decimal)\.(?&decimal)(?&exp)?(?&float_suffix)?)|((?&decimal)(?&exp)(?&float_suffix)?)|((?&decimal)(?&exp)?(?&float_suffix)))"#, |n| Number::Float(n.slice()))]
     #[regex(r"0[xX](((?&hex))|((?&hex)\.)|((?&hex)?\.(?&hex)))[pP][+-]?(?&decimal)(?&float_suffix)?", |n| Number::HexFloat(n.slice()))]
     Number(Number<'source>),
 
@@ -140,6 +243,13 @@ pub enum Token<'source> {
     #[token("*", |_| Operator::Star)]
     #[token("/", |_| Operator::Slash)]
     #[token("%", |_| Operator::Modulo)]
+    #[token("&", |_| Operator::BitwiseAnd)]
+    #[token("|", |_| Operator::BitwiseOr)]
+    #[token("^", |_| Operator::BitwiseXor)]
+    #[token("~", |_| Operator::BitwiseNot)]
+    #[token("<<", |_| Operator::ShiftLeft)]
+    #[token(">>", |_| Operator::ShiftRight)]
+    #[token("?", |_| Operator::Question)]
     Operator(Operator),
 
     #[token("+=", |_| Assign::PlusEqual)]
@@ -147,6 +257,11 @@ pub enum Token<'source> {
     #[token("*=", |_| Assign::StarEqual)]
     #[token("/=", |_| Assign::SlashEqual)]
     #[token("%=", |_| Assign::ModuloEqual)]
+    #[token("&=", |_| Assign::BitwiseAndEqual)]
+    #[token("|=", |_| Assign::BitwiseOrEqual)]
+    #[token("^=", |_| Assign::BitwiseXorEqual)]
+    #[token("<<=", |_| Assign::ShiftLeftEqual)]
+    #[token(">>=", |_| Assign::ShiftRightEqual)]
     #[token("=", |_| Assign::Equal)]
     Assign(Assign),
 
@@ -155,6 +270,16 @@ pub enum Token<'source> {
     #[token("if", |_| Keyword::If)]
     #[token("else", |_| Keyword::Else)]
     #[token("return", |_| Keyword::Return)]
+    #[token("mod", |_| Keyword::Mod)]
+    #[token("use", |_| Keyword::Use)]
+    #[token("pub", |_| Keyword::Pub)]
+    #[token("match", |_| Keyword::Match)]
+    #[token("enum", |_| Keyword::Enum)]
+    #[token("struct", |_| Keyword::Struct)]
+    #[token("trait", |_| Keyword::Trait)]
+    #[token("impl", |_| Keyword::Impl)]
+    #[token("for", |_| Keyword::For)]
+    #[token("mut", |_| Keyword::Mut)]
     Keyword(Keyword),
 
     #[regex(r"[ \t\n\f]+")]
@@ -169,6 +294,53 @@ pub enum Token<'source> {
     Grouping(Grouping),
 }
 
+fn interpolated_string<'source>(
+    lexer: &mut logos::Lexer<'source, Token<'source>>,
+) -> Option<InterpolatedString<'source>> {
+    match string_text(lexer)? {
+        true => Some(InterpolatedString::Start(lexer.slice())),
+        false => Some(InterpolatedString::Plain(lexer.slice())),
+    }
+}
+
+/// Extends the token over the text of an interpolated string up to the `{` starting an
+/// expression, returning `true`, or to the closing `"`, returning `false`. `{{` and `}}`
+/// stand for literal braces, and the braces of a `\u{...}` escape belong to it. Returns
+/// `None`, swallowing the rest of the input, if the string is unterminated.
+fn string_text<'source>(lexer: &mut logos::Lexer<'source, Token<'source>>) -> Option<bool> {
+    let remainder = lexer.remainder();
+    let mut chars = remainder.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        let starts_expr = match c {
+            '\\' => {
+                // a `"` ends an unterminated escape, which the parser reports
+                if chars.next_if(|&(_, next)| next == 'u').is_some()
+                    && chars.next_if(|&(_, next)| next == '{').is_some()
+                {
+                    while chars
+                        .next_if(|&(_, next)| next != '}' && next != '"')
+                        .is_some()
+                    {}
+                    chars.next_if(|&(_, next)| next == '}');
+                } else {
+                    chars.next();
+                }
+                continue;
+            }
+            '{' | '}' if chars.next_if(|&(_, next)| next == c).is_some() => continue,
+            '{' => true,
+            '"' => false,
+            _ => continue,
+        };
+        lexer.bump(index + c.len_utf8());
+        return Some(starts_expr);
+    }
+
+    lexer.bump(remainder.len());
+    None
+}
+
 #[macro_export]
 macro_rules! ok_first_token {
     ($src: expr, $expect: expr) => {
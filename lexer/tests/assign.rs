@@ -29,3 +29,28 @@ fn match_modulo() {
 fn match_equal() {
     ok_first_token!("=", Token::Assign(Assign::Equal));
 }
+
+#[test]
+fn match_bitwise_and() {
+    ok_first_token!("&=", Token::Assign(Assign::BitwiseAndEqual));
+}
+
+#[test]
+fn match_bitwise_or() {
+    ok_first_token!("|=", Token::Assign(Assign::BitwiseOrEqual));
+}
+
+#[test]
+fn match_bitwise_xor() {
+    ok_first_token!("^=", Token::Assign(Assign::BitwiseXorEqual));
+}
+
+#[test]
+fn match_shift_left() {
+    ok_first_token!("<<=", Token::Assign(Assign::ShiftLeftEqual));
+}
+
+#[test]
+fn match_shift_right() {
+    ok_first_token!(">>=", Token::Assign(Assign::ShiftRightEqual));
+}
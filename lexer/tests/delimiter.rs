@@ -15,7 +15,33 @@ fn match_colon() {
     ok_first_token!(":", Token::Delimiter(Delimiter::Colon));
 }
 
+#[test]
+fn match_colon_colon() {
+    ok_first_token!("::", Token::Delimiter(Delimiter::ColonColon));
+}
+
 #[test]
 fn match_right_arrow() {
     ok_first_token!("->", Token::Delimiter(Delimiter::RightArrow));
 }
+
+#[test]
+fn match_fat_arrow() {
+    ok_first_token!("=>", Token::Delimiter(Delimiter::FatArrow));
+}
+
+#[test]
+fn match_dot() {
+    ok_first_token!(".", Token::Delimiter(Delimiter::Dot));
+}
+
+#[test]
+fn match_dot_dot() {
+    ok_first_token!("..", Token::Delimiter(Delimiter::DotDot));
+    ok_first_token!("..=", Token::Delimiter(Delimiter::DotDotEqual));
+}
+
+#[test]
+fn match_pound() {
+    ok_first_token!("#", Token::Delimiter(Delimiter::Pound));
+}
@@ -85,3 +85,45 @@ fn match_stmt() {
         ]
     );
 }
+
+#[test]
+fn match_path() {
+    ok_all_tokens!(
+        "use foo::bar;",
+        [
+            Token::Keyword(Keyword::Use),
+            Token::Whitespace(" "),
+            Token::Identifier("foo"),
+            Token::Delimiter(Delimiter::ColonColon),
+            Token::Identifier("bar"),
+            Token::Delimiter(Delimiter::Semicolon),
+        ]
+    );
+}
+
+#[test]
+fn match_tuple_field() {
+    ok_all_tokens!(
+        "t.0",
+        [
+            Token::Identifier("t"),
+            Token::Delimiter(Delimiter::Dot),
+            Token::Number(Number::Integer("0")),
+        ]
+    );
+    ok_all_tokens!(
+        "t.0.1",
+        [
+            Token::Identifier("t"),
+            Token::Delimiter(Delimiter::Dot),
+            Token::Number(Number::Float("0.1")),
+        ]
+    );
+    ok_all_tokens!(
+        ".5",
+        [
+            Token::Delimiter(Delimiter::Dot),
+            Token::Number(Number::Integer("5")),
+        ]
+    );
+}
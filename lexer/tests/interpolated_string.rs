@@ -0,0 +1,120 @@
+use lexer::{ok_first_token, Delimiter, Grouping, InterpolatedString, Lexer, Operator, Token};
+
+fn tokens(src: &str) -> Vec<Result<Token<'_>, ()>> {
+    Lexer::new(src)
+        .map(|(token, _)| token)
+        .filter(|token| !matches!(token, Ok(Token::Whitespace(_))))
+        .collect()
+}
+
+#[test]
+fn match_plain_interpolated_string() {
+    ok_first_token!(
+        r#"f"hello""#,
+        Token::InterpolatedString(InterpolatedString::Plain(r#"f"hello""#))
+    );
+    ok_first_token!(
+        r#"f"{{literal}} \" braces""#,
+        Token::InterpolatedString(InterpolatedString::Plain(r#"f"{{literal}} \" braces""#))
+    );
+}
+
+#[test]
+fn match_interpolations() {
+    assert_eq!(
+        tokens(r#"f"Hello {name}, you are {age + 1}""#),
+        [
+            Ok(Token::InterpolatedString(InterpolatedString::Start(
+                r#"f"Hello {"#
+            ))),
+            Ok(Token::Identifier("name")),
+            Ok(Token::InterpolatedString(InterpolatedString::Middle(
+                "}, you are {"
+            ))),
+            Ok(Token::Identifier("age")),
+            Ok(Token::Operator(Operator::Plus)),
+            Ok(Token::Number(lexer::Number::Integer("1"))),
+            Ok(Token::InterpolatedString(InterpolatedString::End(r#"}""#))),
+        ]
+    );
+}
+
+#[test]
+fn match_nested_braces_and_strings() {
+    assert_eq!(
+        tokens(r#"f"{ {x} }{"}"}{f"{y}"}""#),
+        [
+            Ok(Token::InterpolatedString(InterpolatedString::Start(
+                r#"f"{"#
+            ))),
+            Ok(Token::Grouping(Grouping::OpenBrace)),
+            Ok(Token::Identifier("x")),
+            Ok(Token::Grouping(Grouping::CloseBrace)),
+            Ok(Token::InterpolatedString(InterpolatedString::Middle("}{"))),
+            Ok(Token::String(r#""}""#)),
+            Ok(Token::InterpolatedString(InterpolatedString::Middle("}{"))),
+            Ok(Token::InterpolatedString(InterpolatedString::Start(
+                r#"f"{"#
+            ))),
+            Ok(Token::Identifier("y")),
+            Ok(Token::InterpolatedString(InterpolatedString::End(r#"}""#))),
+            Ok(Token::InterpolatedString(InterpolatedString::End(r#"}""#))),
+        ]
+    );
+}
+
+#[test]
+fn match_braces_after_interpolated_string() {
+    assert_eq!(
+        tokens(r#"{ f"{a}" };"#),
+        [
+            Ok(Token::Grouping(Grouping::OpenBrace)),
+            Ok(Token::InterpolatedString(InterpolatedString::Start(
+                r#"f"{"#
+            ))),
+            Ok(Token::Identifier("a")),
+            Ok(Token::InterpolatedString(InterpolatedString::End(r#"}""#))),
+            Ok(Token::Grouping(Grouping::CloseBrace)),
+            Ok(Token::Delimiter(Delimiter::Semicolon)),
+        ]
+    );
+}
+
+#[test]
+fn match_unicode_escapes() {
+    ok_first_token!(
+        r#"f"caf\u{e9}""#,
+        Token::InterpolatedString(InterpolatedString::Plain(r#"f"caf\u{e9}""#))
+    );
+    assert_eq!(
+        tokens(r#"f"\u{1F600} {x}\u{21}""#),
+        [
+            Ok(Token::InterpolatedString(InterpolatedString::Start(
+                r#"f"\u{1F600} {"#
+            ))),
+            Ok(Token::Identifier("x")),
+            Ok(Token::InterpolatedString(InterpolatedString::End(
+                r#"}\u{21}""#
+            ))),
+        ]
+    );
+    ok_first_token!(
+        r#"f"\u{e9" x"#,
+        Token::InterpolatedString(InterpolatedString::Plain(r#"f"\u{e9""#))
+    );
+}
+
+#[test]
+fn unterminated_interpolated_string() {
+    assert_eq!(tokens(r#"f"abc"#), [Err(())]);
+    assert_eq!(
+        tokens(r#"f"{a} abc"#),
+        [
+            Ok(Token::InterpolatedString(InterpolatedString::Start(
+                r#"f"{"#
+            ))),
+            Ok(Token::Identifier("a")),
+            Err(()),
+        ]
+    );
+}
@@ -24,3 +24,53 @@ fn match_else() {
 fn match_return() {
     ok_first_token!("return", Token::Keyword(Keyword::Return));
 }
+
+#[test]
+fn match_mod() {
+    ok_first_token!("mod", Token::Keyword(Keyword::Mod));
+}
+
+#[test]
+fn match_use() {
+    ok_first_token!("use", Token::Keyword(Keyword::Use));
+}
+
+#[test]
+fn match_pub() {
+    ok_first_token!("pub", Token::Keyword(Keyword::Pub));
+}
+
+#[test]
+fn match_match() {
+    ok_first_token!("match", Token::Keyword(Keyword::Match));
+}
+
+#[test]
+fn match_enum() {
+    ok_first_token!("enum", Token::Keyword(Keyword::Enum));
+}
+
+#[test]
+fn match_struct() {
+    ok_first_token!("struct", Token::Keyword(Keyword::Struct));
+}
+
+#[test]
+fn match_trait() {
+    ok_first_token!("trait", Token::Keyword(Keyword::Trait));
+}
+
+#[test]
+fn match_impl() {
+    ok_first_token!("impl", Token::Keyword(Keyword::Impl));
+}
+
+#[test]
+fn match_for() {
+    ok_first_token!("for", Token::Keyword(Keyword::For));
+}
+
+#[test]
+fn match_mut() {
+    ok_first_token!("mut", Token::Keyword(Keyword::Mut));
+}
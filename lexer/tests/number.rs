@@ -1,4 +1,4 @@
-use lexer::{ok_first_token, Number, Token};
+use lexer::{ok_all_tokens, ok_first_token, Delimiter, Number, Token};
 
 #[test]
 fn match_underscore() {
@@ -86,3 +86,15 @@ fn match_hex_float() {
         Token::Number(Number::HexFloat("0x0.3p-10f32"))
     );
 }
+
+#[test]
+fn match_integer_before_range() {
+    ok_all_tokens!(
+        "1..=5",
+        [
+            Token::Number(Number::Integer("1")),
+            Token::Delimiter(Delimiter::DotDotEqual),
+            Token::Number(Number::Integer("5")),
+        ]
+    );
+}
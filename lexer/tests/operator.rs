@@ -69,3 +69,38 @@ fn match_logical_and() {
 fn match_logical_or() {
     ok_first_token!("||", Token::Operator(Operator::LogicalOr));
 }
+
+#[test]
+fn match_bitwise_and() {
+    ok_first_token!("&", Token::Operator(Operator::BitwiseAnd));
+}
+
+#[test]
+fn match_bitwise_or() {
+    ok_first_token!("|", Token::Operator(Operator::BitwiseOr));
+}
+
+#[test]
+fn match_bitwise_xor() {
+    ok_first_token!("^", Token::Operator(Operator::BitwiseXor));
+}
+
+#[test]
+fn match_bitwise_not() {
+    ok_first_token!("~", Token::Operator(Operator::BitwiseNot));
+}
+
+#[test]
+fn match_shift_left() {
+    ok_first_token!("<<", Token::Operator(Operator::ShiftLeft));
+}
+
+#[test]
+fn match_shift_right() {
+    ok_first_token!(">>", Token::Operator(Operator::ShiftRight));
+}
+
+#[test]
+fn match_question() {
+    ok_first_token!("?", Token::Operator(Operator::Question));
+}
@@ -0,0 +1,1127 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use db::{
+    item_tree::{Item, ItemTree},
+    Database, Query,
+};
+use parser::{
+    ast::{
+        AstNode, EnumStmt, Expr, GenericParamList, ImplStmt, ItemList, RecordFieldList, Root, Stmt,
+        StructStmt, TraitStmt, Type,
+    },
+    syntax::SyntaxToken,
+};
+use source_map::{Diagnostic, FileId, Span};
+
+use crate::{
+    Def, EnumData, ImplData, ModuleData, ModuleId, ModuleSource, ModuleTree, ResolveError,
+    ScopeEntry, TraitData, TraitFunction, VariantData, VariantFields, FILE_EXTENSION,
+};
+
+/// Builds the module tree starting from the `root` file, loading `mod foo;` declarations
+/// from `foo.lamia` or `foo/mod.lamia` and resolving `use` imports.
+///
+/// Files already in the database are used as is, others are read from disk.
+pub fn build(db: &mut Database, root: FileId) -> (ModuleTree, Vec<Diagnostic>) {
+    let dir = root_dir(db, root);
+    load_files(db, root, dir, &mut Vec::new());
+    let built = db.query::<BuildModuleTree>(&root);
+    (built.0.clone(), built.1.clone())
+}
+
+/// The module tree of the program whose root file is `root`. Unlike [`build`], only the
+/// files already in the database are used, and the errors found building it are left out,
+/// so that the queries reading the tree aren't rerun when they change.
+pub fn module_tree(db: &Database, root: FileId) -> Arc<ModuleTree> {
+    db.query::<ModuleTreeQuery>(&root)
+}
+
+struct ModuleTreeQuery;
+
+impl Query for ModuleTreeQuery {
+    type Key = FileId;
+    type Value = ModuleTree;
+    const NAME: &'static str = "module_tree";
+
+    fn execute(db: &Database, root: &FileId) -> ModuleTree {
+        db.query::<BuildModuleTree>(root).0.clone()
+    }
+}
+
+struct BuildModuleTree;
+
+impl Query for BuildModuleTree {
+    type Key = FileId;
+    type Value = (ModuleTree, Vec<Diagnostic>);
+    const NAME: &'static str = "build_module_tree";
+
+    fn execute(db: &Database, root: &FileId) -> (ModuleTree, Vec<Diagnostic>) {
+        let mut builder = Builder {
+            db,
+            tree: ModuleTree {
+                modules: Vec::new(),
+                enums: HashMap::new(),
+                traits: HashMap::new(),
+                impls: Vec::new(),
+            },
+            imports: Vec::new(),
+            impls: Vec::new(),
+            diagnostics: Vec::new(),
+        };
+
+        let module = builder.add_module(String::new(), None, *root, ModuleSource::File);
+        builder.collect_file(module, *root, root_dir(db, *root), &[]);
+        builder.resolve_imports();
+        builder.resolve_impls();
+
+        (builder.tree, builder.diagnostics)
+    }
+}
+
+/// The directory of the files of the root module's children. The paths of files never
+/// change, so they're read without being tracked.
+fn root_dir(db: &Database, root: FileId) -> PathBuf {
+    db.source_map()
+        .path(root)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// Reads the files of the `mod foo;` declarations reachable from `file` from disk, for the
+/// module tree to find them in the database. Both candidates of a module are loaded when
+/// they exist, so that the ambiguity is reported.
+fn load_files(db: &mut Database, file: FileId, dir: PathBuf, loaded: &mut Vec<FileId>) {
+    if loaded.contains(&file) {
+        return;
+    }
+    loaded.push(file);
+    let item_tree = db.item_tree(file);
+    load_items(db, &item_tree, &dir, loaded);
+}
+
+fn load_items(db: &mut Database, item_tree: &ItemTree, dir: &Path, loaded: &mut Vec<FileId>) {
+    for item in &item_tree.items {
+        let Item::Module(module) = item else {
+            continue;
+        };
+        let child_dir = dir.join(&module.name);
+        match &module.items {
+            Some(items) => load_items(db, items, &child_dir, loaded),
+            None => {
+                for path in module_candidates(dir, &module.name) {
+                    let file = match db.source_map().find(&path) {
+                        Some(file) => Some(file),
+                        None => path.is_file().then(|| db.load_file(&path).ok()).flatten(),
+                    };
+                    if let Some(file) = file {
+                        load_files(db, file, child_dir.clone(), loaded);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The files `mod name;` can be written in, which are `dir/name.lamia` and
+/// `dir/name/mod.lamia`.
+fn module_candidates(dir: &Path, name: &str) -> [PathBuf; 2] {
+    [
+        dir.join(format!("{name}.{FILE_EXTENSION}")),
+        dir.join(name).join(format!("mod.{FILE_EXTENSION}")),
+    ]
+}
+
+struct Builder<'a> {
+    db: &'a Database,
+    tree: ModuleTree,
+    imports: Vec<Import>,
+    /// Impls whose trait is resolved once the imports are.
+    impls: Vec<PendingImpl>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+struct Import {
+    module: ModuleId,
+    segments: Vec<(String, Span)>,
+    is_pub: bool,
+}
+
+struct PendingImpl {
+    data: ImplData,
+    /// The segments of the trait's path and the span of the whole path.
+    trait_path: Option<(Vec<String>, Span)>,
+}
+
+impl Builder<'_> {
+    fn add_module(
+        &mut self,
+        name: String,
+        parent: Option<ModuleId>,
+        file: FileId,
+        source: ModuleSource,
+    ) -> ModuleId {
+        let id = ModuleId(self.tree.modules.len() as u32);
+        self.tree.modules.push(ModuleData {
+            name,
+            parent,
+            file,
+            source,
+            scope: BTreeMap::new(),
+        });
+        id
+    }
+
+    /// Parses `file` and collects its items into `module`.
+    /// `ancestors` are the files of the enclosing modules, to detect cycles.
+    fn collect_file(&mut self, module: ModuleId, file: FileId, dir: PathBuf, ancestors: &[FileId]) {
+        let green_tree = self.db.parse(file);
+        for error in green_tree.errors.iter() {
+            let span = Span::new(file, error.range);
+            self.diagnostics
+                .push(Diagnostic::error(span, error.message.clone()));
+        }
+
+        let root = Root::cast(green_tree.syntax()).expect("the parser always produces a root");
+        let ancestors = [ancestors, &[file]].concat();
+        self.collect(module, file, root.stmts(), &dir, &ancestors);
+    }
+
+    fn collect(
+        &mut self,
+        module: ModuleId,
+        file: FileId,
+        stmts: impl Iterator<Item = Stmt>,
+        dir: &Path,
+        ancestors: &[FileId],
+    ) {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Expr(stmt) => {
+                    let Some(Expr::Function(function)) = stmt.expr() else {
+                        continue;
+                    };
+                    let Some(name) = function.name() else {
+                        continue;
+                    };
+
+                    self.collect_generics(file, function.generic_param_list());
+                    let is_pub = function.visibility().is_some_and(|v| v.is_pub());
+                    let span = Span::new(file, name.text_range());
+                    self.define(
+                        module,
+                        name.text(),
+                        Def::Function(span),
+                        is_pub,
+                        span,
+                        false,
+                    );
+                }
+                Stmt::Mod(stmt) => {
+                    let Some(name) = stmt.name() else {
+                        continue;
+                    };
+
+                    let span = Span::new(file, name.text_range());
+                    if self.is_defined(module, name.text(), span) {
+                        continue;
+                    }
+
+                    let child = match stmt.item_list() {
+                        Some(item_list) => {
+                            let source = ModuleSource::Inline(item_list.syntax().text_range());
+                            let child =
+                                self.add_module(name.to_string(), Some(module), file, source);
+                            let dir = dir.join(name.text());
+                            self.collect(child, file, item_list.stmts(), &dir, ancestors);
+                            child
+                        }
+                        None => match self.load_module(module, &name, span, dir, ancestors) {
+                            Some(child) => child,
+                            None => continue,
+                        },
+                    };
+
+                    let is_pub = stmt.visibility().is_some_and(|v| v.is_pub());
+                    self.define(module, name.text(), Def::Module(child), is_pub, span, false);
+                }
+                Stmt::Use(stmt) => {
+                    let segments = stmt
+                        .segments()
+                        .map(|segment| {
+                            let span = Span::new(file, segment.text_range());
+                            (segment.to_string(), span)
+                        })
+                        .collect::<Vec<_>>();
+
+                    if !segments.is_empty() {
+                        self.imports.push(Import {
+                            module,
+                            segments,
+                            is_pub: stmt.visibility().is_some_and(|v| v.is_pub()),
+                        });
+                    }
+                }
+                Stmt::Enum(stmt) => self.collect_enum(module, file, &stmt),
+                Stmt::Struct(stmt) => self.collect_struct(module, file, &stmt),
+                Stmt::Trait(stmt) => self.collect_trait(module, file, &stmt),
+                Stmt::Impl(stmt) => self.collect_impl(module, file, &stmt),
+                Stmt::Let(_) | Stmt::Return(_) => {}
+            }
+        }
+    }
+
+    fn collect_enum(&mut self, module: ModuleId, file: FileId, stmt: &EnumStmt) {
+        let Some(name) = stmt.name() else {
+            return;
+        };
+
+        let generics = self.collect_generics(file, stmt.generic_param_list());
+        let mut variants = Vec::<VariantData>::new();
+        for variant in stmt
+            .variant_list()
+            .into_iter()
+            .flat_map(|list| list.variants())
+        {
+            let Some(variant_name) = variant.name() else {
+                continue;
+            };
+
+            let span = Span::new(file, variant_name.text_range());
+            if variants
+                .iter()
+                .any(|other| other.name == variant_name.text())
+            {
+                self.diagnostics.push(Diagnostic::error(
+                    span,
+                    format!("the name `{variant_name}` is defined multiple times"),
+                ));
+                continue;
+            }
+
+            let fields = if let Some(types) = variant.tuple_fields() {
+                VariantFields::Tuple(types.types().count())
+            } else if let Some(list) = variant.record_fields() {
+                VariantFields::Record(self.collect_fields(file, &list))
+            } else {
+                VariantFields::Unit
+            };
+
+            variants.push(VariantData {
+                name: variant_name.to_string(),
+                span,
+                fields,
+            });
+        }
+
+        let span = Span::new(file, name.text_range());
+        self.tree.enums.insert(
+            span,
+            EnumData {
+                name: name.to_string(),
+                module,
+                generics,
+                variants,
+                is_struct: false,
+            },
+        );
+
+        let is_pub = stmt.visibility().is_some_and(|v| v.is_pub());
+        self.define(module, name.text(), Def::Enum(span), is_pub, span, false);
+    }
+
+    fn collect_struct(&mut self, module: ModuleId, file: FileId, stmt: &StructStmt) {
+        let Some(name) = stmt.name() else {
+            return;
+        };
+
+        let generics = self.collect_generics(file, stmt.generic_param_list());
+        let fields = stmt
+            .record_fields()
+            .map_or_else(Vec::new, |list| self.collect_fields(file, &list));
+        let span = Span::new(file, name.text_range());
+        self.tree.enums.insert(
+            span,
+            EnumData {
+                name: name.to_string(),
+                module,
+                generics,
+                variants: vec![VariantData {
+                    name: name.to_string(),
+                    span,
+                    fields: VariantFields::Record(fields),
+                }],
+                is_struct: true,
+            },
+        );
+
+        let is_pub = stmt.visibility().is_some_and(|v| v.is_pub());
+        self.define(module, name.text(), Def::Struct(span), is_pub, span, false);
+    }
+
+    /// The names of the fields of a struct or of a record variant, in declaration order.
+    fn collect_fields(&mut self, file: FileId, list: &RecordFieldList) -> Vec<String> {
+        let mut names = Vec::<String>::new();
+        for field in list.fields() {
+            let Some(field_name) = field.name() else {
+                continue;
+            };
+            if names.iter().any(|other| other == field_name.text()) {
+                self.diagnostics.push(Diagnostic::error(
+                    Span::new(file, field_name.text_range()),
+                    format!("field `{field_name}` is already declared"),
+                ));
+                continue;
+            }
+            names.push(field_name.to_string());
+        }
+        names
+    }
+
+    fn collect_trait(&mut self, module: ModuleId, file: FileId, stmt: &TraitStmt) {
+        let Some(name) = stmt.name() else {
+            return;
+        };
+
+        let mut functions = Vec::<TraitFunction>::new();
+        for function in stmt.item_list().iter().flat_map(ItemList::functions) {
+            let Some(function_name) = function.name() else {
+                continue;
+            };
+
+            let span = Span::new(file, function_name.text_range());
+            if functions
+                .iter()
+                .any(|other| other.name == function_name.text())
+            {
+                self.diagnostics.push(Diagnostic::error(
+                    span,
+                    format!("the name `{function_name}` is defined multiple times"),
+                ));
+                continue;
+            }
+
+            self.collect_generics(file, function.generic_param_list());
+            functions.push(TraitFunction {
+                name: function_name.to_string(),
+                span,
+                has_body: function.body().is_some(),
+            });
+        }
+
+        let span = Span::new(file, name.text_range());
+        self.tree.traits.insert(
+            span,
+            TraitData {
+                name: name.to_string(),
+                module,
+                functions,
+            },
+        );
+
+        let is_pub = stmt.visibility().is_some_and(|v| v.is_pub());
+        self.define(module, name.text(), Def::Trait(span), is_pub, span, false);
+    }
+
+    fn collect_impl(&mut self, module: ModuleId, file: FileId, stmt: &ImplStmt) {
+        let Some(keyword) = stmt.impl_keyword() else {
+            return;
+        };
+
+        self.collect_generics(file, stmt.generic_param_list());
+        let mut functions = Vec::<(String, Span)>::new();
+        for function in stmt.item_list().iter().flat_map(ItemList::functions) {
+            let Some(name) = function.name() else {
+                continue;
+            };
+
+            let span = Span::new(file, name.text_range());
+            if functions.iter().any(|(other, _)| other == name.text()) {
+                self.diagnostics.push(Diagnostic::error(
+                    span,
+                    format!("duplicate definitions with name `{name}`"),
+                ));
+                continue;
+            }
+
+            self.collect_generics(file, function.generic_param_list());
+            functions.push((name.to_string(), span));
+        }
+
+        let trait_path = match stmt.trait_type() {
+            Some(Type::Path(path)) => {
+                let segments = path.segments().map(|s| s.to_string()).collect();
+                Some((segments, Span::new(file, path.syntax().text_range())))
+            }
+            Some(ty) => {
+                self.diagnostics.push(Diagnostic::error(
+                    Span::new(file, ty.syntax().text_range()),
+                    format!("expected trait, found type `{}`", ty.syntax()),
+                ));
+                return;
+            }
+            None => None,
+        };
+
+        self.impls.push(PendingImpl {
+            data: ImplData {
+                span: Span::new(file, keyword.text_range()),
+                module,
+                trait_def: None,
+                functions,
+            },
+            trait_path,
+        });
+    }
+
+    /// Resolves the traits of impls and checks that the impls define the functions of
+    /// their trait.
+    fn resolve_impls(&mut self) {
+        for PendingImpl {
+            mut data,
+            trait_path,
+        } in std::mem::take(&mut self.impls)
+        {
+            if let Some((segments, span)) = trait_path {
+                let path = segments.join("::");
+                let names = segments.iter().map(String::as_str).collect::<Vec<_>>();
+                let message = match self.tree.resolve_path(data.module, &names) {
+                    Ok(Def::Trait(trait_def)) => {
+                        self.check_impl_functions(&data, trait_def, span);
+                        data.trait_def = Some(trait_def);
+                        None
+                    }
+                    Ok(def) => Some(format!("expected trait, found {} `{path}`", def_kind(def))),
+                    Err(ResolveError::Private(_)) => Some(format!("trait `{path}` is private")),
+                    Err(_) => Some(format!("cannot find trait `{path}` in this scope")),
+                };
+
+                if let Some(message) = message {
+                    self.diagnostics.push(Diagnostic::error(span, message));
+                    continue;
+                }
+            }
+
+            self.tree.impls.push(data);
+        }
+    }
+
+    fn check_impl_functions(&mut self, data: &ImplData, trait_def: Span, trait_span: Span) {
+        let trait_data = &self.tree.traits[&trait_def];
+        for (name, span) in &data.functions {
+            if !trait_data.functions.iter().any(|f| &f.name == name) {
+                self.diagnostics.push(Diagnostic::error(
+                    *span,
+                    format!(
+                        "method `{name}` is not a member of trait `{}`",
+                        trait_data.name
+                    ),
+                ));
+            }
+        }
+
+        let missing = trait_data
+            .functions
+            .iter()
+            .filter(|f| !f.has_body && !data.functions.iter().any(|(name, _)| name == &f.name))
+            .map(|f| format!("`{}`", f.name))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            self.diagnostics.push(Diagnostic::error(
+                trait_span,
+                format!(
+                    "not all trait items implemented, missing: {}",
+                    missing.join(", ")
+                ),
+            ));
+        }
+    }
+
+    /// The names of generic parameters, reporting duplicates.
+    fn collect_generics(&mut self, file: FileId, list: Option<GenericParamList>) -> Vec<String> {
+        let mut names = Vec::<String>::new();
+        for param in list.into_iter().flat_map(|list| list.params()) {
+            let Some(name) = param.name() else {
+                continue;
+            };
+            if names.iter().any(|other| other == name.text()) {
+                self.diagnostics.push(Diagnostic::error(
+                    Span::new(file, name.text_range()),
+                    format!("the name `{name}` is already used for a generic parameter"),
+                ));
+                continue;
+            }
+            names.push(name.to_string());
+        }
+        names
+    }
+
+    /// Loads the file of `mod name;`, which is either `dir/name.lamia` or `dir/name/mod.lamia`.
+    fn load_module(
+        &mut self,
+        parent: ModuleId,
+        name: &SyntaxToken,
+        span: Span,
+        dir: &Path,
+        ancestors: &[FileId],
+    ) -> Option<ModuleId> {
+        let candidates = module_candidates(dir, name.text());
+        let found = candidates
+            .iter()
+            .filter_map(|path| self.db.find_file(path))
+            .collect::<Vec<_>>();
+
+        let file = match found.as_slice() {
+            [] => {
+                self.diagnostics.push(Diagnostic::error(
+                    span,
+                    format!(
+                        "file not found for module `{name}`, expected `{}` or `{}`",
+                        candidates[0].display(),
+                        candidates[1].display()
+                    ),
+                ));
+                return None;
+            }
+            [file] => *file,
+            _ => {
+                self.diagnostics.push(Diagnostic::error(
+                    span,
+                    format!(
+                        "module `{name}` is ambiguous, both `{}` and `{}` exist",
+                        candidates[0].display(),
+                        candidates[1].display()
+                    ),
+                ));
+                return None;
+            }
+        };
+
+        if ancestors.contains(&file) {
+            let cycle = ancestors
+                .iter()
+                .skip_while(|&&ancestor| ancestor != file)
+                .chain([&file])
+                .map(|&file| format!("`{}`", self.db.source_map().path(file).display()))
+                .collect::<Vec<_>>();
+
+            self.diagnostics.push(Diagnostic::error(
+                span,
+                format!("circular module `{name}`: {}", cycle.join(" -> ")),
+            ));
+            return None;
+        }
+
+        let module = self.add_module(name.to_string(), Some(parent), file, ModuleSource::File);
+        self.collect_file(module, file, dir.join(name.text()), ancestors);
+        Some(module)
+    }
+
+    /// Whether `name` is already defined in `module`, reporting the duplicate if so.
+    fn is_defined(&mut self, module: ModuleId, name: &str, span: Span) -> bool {
+        let defined = self.tree.modules[module.0 as usize]
+            .scope
+            .contains_key(name);
+
+        if defined {
+            self.diagnostics.push(Diagnostic::error(
+                span,
+                format!("the name `{name}` is defined multiple times"),
+            ));
+        }
+
+        defined
+    }
+
+    fn define(
+        &mut self,
+        module: ModuleId,
+        name: &str,
+        def: Def,
+        is_pub: bool,
+        span: Span,
+        imported: bool,
+    ) {
+        if self.is_defined(module, name, span) {
+            return;
+        }
+
+        self.tree.modules[module.0 as usize].scope.insert(
+            name.to_string(),
+            ScopeEntry {
+                def,
+                is_pub,
+                span,
+                imported,
+            },
+        );
+    }
+
+    /// Resolves imports until no more progress is made, since an import can depend on
+    /// another one being resolved first.
+    fn resolve_imports(&mut self) {
+        let mut pending = std::mem::take(&mut self.imports);
+
+        loop {
+            let before = pending.len();
+            let mut unresolved = Vec::new();
+
+            for import in pending {
+                let segments = import
+                    .segments
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>();
+
+                match self.tree.resolve_path(import.module, &segments) {
+                    Ok(def) => {
+                        let (name, span) = import.segments.last().expect("imports aren't empty");
+                        self.define(import.module, name, def, import.is_pub, *span, true);
+                    }
+                    Err(ResolveError::Unresolved(_)) => unresolved.push(import),
+                    Err(err) => self.report_import(&import, err),
+                }
+            }
+
+            if unresolved.len() == before {
+                for import in &unresolved {
+                    let segments = import
+                        .segments
+                        .iter()
+                        .map(|(name, _)| name.as_str())
+                        .collect::<Vec<_>>();
+
+                    if let Err(err) = self.tree.resolve_path(import.module, &segments) {
+                        self.report_import(import, err);
+                    }
+                }
+                return;
+            }
+
+            pending = unresolved;
+        }
+    }
+
+    fn report_import(&mut self, import: &Import, err: ResolveError) {
+        let (index, message) = match err {
+            ResolveError::Unresolved(0) => {
+                let (name, _) = &import.segments[0];
+                (0, format!("unresolved import `{name}`"))
+            }
+            ResolveError::Unresolved(index) => {
+                let (name, _) = &import.segments[index];
+                let (parent, _) = &import.segments[index - 1];
+                (
+                    index,
+                    format!("unresolved import: no `{name}` in `{parent}`"),
+                )
+            }
+            ResolveError::NotAModule(index) => {
+                let (name, _) = &import.segments[index];
+                (index, format!("`{name}` is not a module"))
+            }
+            ResolveError::Private(index) => {
+                let (name, _) = &import.segments[index];
+                (index, format!("`{name}` is private"))
+            }
+        };
+
+        let (_, span) = import.segments[index];
+        self.diagnostics.push(Diagnostic::error(span, message));
+    }
+}
+
+/// What a definition is, as said in error messages.
+fn def_kind(def: Def) -> &'static str {
+    match def {
+        Def::Module(_) => "module",
+        Def::Function(_) => "function",
+        Def::Enum(_) => "enum",
+        Def::Variant(..) => "variant",
+        Def::Struct(_) => "struct",
+        Def::Trait(_) => "trait",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use db::Database;
+    use source_map::{Diagnostic, SourceMap};
+
+    use super::{build, module_tree};
+    use crate::{Def, ModuleSource, ModuleTree, ResolveError, VariantFields};
+
+    /// Builds the module tree of in-memory files, the first one being the root.
+    fn build_files(files: &[(&str, &str)]) -> (ModuleTree, Vec<String>, Database) {
+        let mut db = Database::new();
+        let ids = files
+            .iter()
+            .map(|(path, text)| db.add_file(*path, *text))
+            .collect::<Vec<_>>();
+
+        let (tree, diagnostics) = build(&mut db, ids[0]);
+        let messages = diagnostics
+            .iter()
+            .map(|diagnostic: &Diagnostic| {
+                format!(
+                    "{}: {}",
+                    db.source_map().location(diagnostic.span),
+                    diagnostic.message
+                )
+            })
+            .collect();
+
+        (tree, messages, db)
+    }
+
+    fn module_paths(tree: &ModuleTree, source_map: &SourceMap) -> Vec<String> {
+        tree.modules()
+            .map(|m| {
+                let data = tree.get(m);
+                format!("{} {}", tree.path(m), source_map.path(data.file).display())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn file_modules() {
+        let (tree, diagnostics, db) = build_files(&[
+            ("/p/main.lamia", "mod a;\nmod b;"),
+            ("/p/a.lamia", "pub fn f() {}"),
+            ("/p/b/mod.lamia", "mod c;"),
+            ("/p/b/c.lamia", "fn g() {}"),
+        ]);
+
+        assert_eq!(diagnostics, Vec::<String>::new());
+        assert_eq!(
+            module_paths(&tree, db.source_map()),
+            [
+                "crate /p/main.lamia",
+                "a /p/a.lamia",
+                "b /p/b/mod.lamia",
+                "b::c /p/b/c.lamia",
+            ]
+        );
+
+        let children = tree.children(tree.root()).collect::<Vec<_>>();
+        assert_eq!(children.len(), 2);
+        assert!(tree.get(children[0]).scope.contains_key("f"));
+    }
+
+    #[test]
+    fn nested_file_modules() {
+        let (tree, diagnostics, db) = build_files(&[
+            ("/p/main.lamia", "mod a;"),
+            ("/p/a.lamia", "mod b { mod c; }"),
+            ("/p/a/b/c.lamia", ""),
+        ]);
+
+        assert_eq!(diagnostics, Vec::<String>::new());
+        assert_eq!(
+            module_paths(&tree, db.source_map()),
+            [
+                "crate /p/main.lamia",
+                "a /p/a.lamia",
+                "a::b /p/a.lamia",
+                "a::b::c /p/a/b/c.lamia",
+            ]
+        );
+
+        let b = tree.modules().nth(2).unwrap();
+        assert!(matches!(tree.get(b).source, ModuleSource::Inline(_)));
+    }
+
+    #[test]
+    fn imports() {
+        let (tree, diagnostics, _) = build_files(&[(
+            "/p/main.lamia",
+            "use a::f;\nmod a {\n    pub use b::f;\n    mod b { pub fn f() {} }\n}",
+        )]);
+
+        assert_eq!(diagnostics, Vec::<String>::new());
+
+        let entry = tree.get(tree.root()).scope["f"];
+        assert!(entry.imported);
+        assert!(matches!(entry.def, Def::Function(_)));
+    }
+
+    #[test]
+    fn private_import() {
+        let (_, diagnostics, _) = build_files(&[(
+            "/p/main.lamia",
+            "mod a { fn f() {} mod b { use a::f; } }\nuse a::f;",
+        )]);
+
+        // private items are visible from descendants of their module
+        assert_eq!(diagnostics, ["/p/main.lamia:2:8: `f` is private"]);
+    }
+
+    #[test]
+    fn unresolved_imports() {
+        let (_, diagnostics, _) = build_files(&[(
+            "/p/main.lamia",
+            "fn f() {}\nmod a {}\nuse b;\nuse a::g;\nuse f::g;",
+        )]);
+
+        assert_eq!(
+            diagnostics,
+            [
+                "/p/main.lamia:5:5: `f` is not a module",
+                "/p/main.lamia:3:5: unresolved import `b`",
+                "/p/main.lamia:4:8: unresolved import: no `g` in `a`",
+            ]
+        );
+    }
+
+    #[test]
+    fn enums() {
+        let (tree, diagnostics, _) = build_files(&[(
+            "/p/main.lamia",
+            "mod a { pub enum E { X, Y(u8), Z { f: u8, f: u8 }, X } }\nuse a::E::Y;\nuse a::E::W;\nenum P<T, U, T> { A(T) }\nfn f<T, T>() {}",
+        )]);
+
+        assert_eq!(
+            diagnostics,
+            [
+                "/p/main.lamia:1:43: field `f` is already declared",
+                "/p/main.lamia:1:52: the name `X` is defined multiple times",
+                "/p/main.lamia:4:14: the name `T` is already used for a generic parameter",
+                "/p/main.lamia:5:9: the name `T` is already used for a generic parameter",
+                "/p/main.lamia:3:11: unresolved import: no `W` in `E`",
+            ]
+        );
+
+        let root = tree.root();
+        let Ok(Def::Enum(span)) = tree.resolve_path(root, &["a", "E"]) else {
+            panic!("expected an enum");
+        };
+        let data = tree.enum_data(span);
+        assert_eq!(data.name, "E");
+        assert!(data.generics.is_empty());
+        assert_eq!(
+            data.variants
+                .iter()
+                .map(|variant| variant.fields.clone())
+                .collect::<Vec<_>>(),
+            [
+                VariantFields::Unit,
+                VariantFields::Tuple(1),
+                VariantFields::Record(vec!["f".to_string()]),
+            ]
+        );
+
+        assert_eq!(tree.get(root).scope["Y"].def, Def::Variant(span, 1));
+        assert_eq!(
+            tree.resolve_path(root, &["a", "E", "X", "Y"]),
+            Err(ResolveError::NotAModule(2))
+        );
+    }
+
+    #[test]
+    fn structs() {
+        let (tree, diagnostics, _) = build_files(&[(
+            "/p/main.lamia",
+            "mod a { pub struct S<T> { x: T, y: u8, x: u8 } }\nuse a::S;\nuse a::S::x;",
+        )]);
+
+        assert_eq!(
+            diagnostics,
+            [
+                "/p/main.lamia:1:40: field `x` is already declared",
+                "/p/main.lamia:3:8: `S` is not a module",
+            ]
+        );
+
+        let root = tree.root();
+        let Def::Struct(span) = tree.get(root).scope["S"].def else {
+            panic!("expected a struct");
+        };
+        let data = tree.enum_data(span);
+        assert!(data.is_struct);
+        assert_eq!(data.generics, ["T"]);
+        assert_eq!(data.variants.len(), 1);
+        assert_eq!(data.variants[0].name, "S");
+        assert_eq!(
+            data.variants[0].fields,
+            VariantFields::Record(vec!["x".to_string(), "y".to_string()])
+        );
+    }
+
+    #[test]
+    fn traits_and_impls() {
+        let (tree, diagnostics, _) = build_files(&[(
+            "/p/main.lamia",
+            "mod a { pub trait Show { fn show(self); fn print(self) {} fn show(self); } }
+use a::Show;
+enum E { A }
+impl Show for E { fn show(self) {} fn other(self) {} fn other(self) {} }
+impl Show for [E] {}
+impl E for E {}
+impl Missing for E {}
+impl E { fn new() {} }",
+        )]);
+
+        assert_eq!(
+            diagnostics,
+            [
+                "/p/main.lamia:1:62: the name `show` is defined multiple times",
+                "/p/main.lamia:4:57: duplicate definitions with name `other`",
+                "/p/main.lamia:4:39: method `other` is not a member of trait `Show`",
+                "/p/main.lamia:5:6: not all trait items implemented, missing: `show`",
+                "/p/main.lamia:6:6: expected trait, found enum `E`",
+                "/p/main.lamia:7:6: cannot find trait `Missing` in this scope",
+            ]
+        );
+
+        let root = tree.root();
+        let Ok(Def::Trait(span)) = tree.resolve_path(root, &["Show"]) else {
+            panic!("expected a trait");
+        };
+        let data = tree.trait_data(span);
+        assert_eq!(
+            data.functions
+                .iter()
+                .map(|function| (function.name.as_str(), function.has_body))
+                .collect::<Vec<_>>(),
+            [("show", false), ("print", true)]
+        );
+
+        let impls = tree
+            .impls()
+            .iter()
+            .map(|data| (data.trait_def, data.functions.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(impls, [(Some(span), 2), (Some(span), 0), (None, 1)]);
+    }
+
+    #[test]
+    fn missing_file() {
+        let (tree, diagnostics, _) = build_files(&[("/p/main.lamia", "mod a;")]);
+
+        assert_eq!(tree.modules().count(), 1);
+        assert_eq!(
+            diagnostics,
+            ["/p/main.lamia:1:5: file not found for module `a`, expected `/p/a.lamia` or `/p/a/mod.lamia`"]
+        );
+    }
+
+    #[test]
+    fn ambiguous_file() {
+        let (_, diagnostics, _) = build_files(&[
+            ("/p/main.lamia", "mod a;"),
+            ("/p/a.lamia", ""),
+            ("/p/a/mod.lamia", ""),
+        ]);
+
+        assert_eq!(
+            diagnostics,
+            ["/p/main.lamia:1:5: module `a` is ambiguous, both `/p/a.lamia` and `/p/a/mod.lamia` exist"]
+        );
+    }
+
+    #[test]
+    fn circular_module() {
+        let (_, diagnostics, _) = build_files(&[("/p/main.lamia", "mod main;")]);
+
+        assert_eq!(
+            diagnostics,
+            ["/p/main.lamia:1:5: circular module `main`: `/p/main.lamia` -> `/p/main.lamia`"]
+        );
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let (_, diagnostics, _) =
+            build_files(&[("/p/main.lamia", "fn a() {}\nmod a {}\nmod b {}\nuse b;")]);
+
+        assert_eq!(
+            diagnostics,
+            [
+                "/p/main.lamia:2:5: the name `a` is defined multiple times",
+                "/p/main.lamia:4:5: the name `b` is defined multiple times",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors_are_reported() {
+        let (_, diagnostics, _) =
+            build_files(&[("/p/main.lamia", "mod a;"), ("/p/a.lamia", "let")]);
+
+        assert_eq!(
+            diagnostics,
+            [
+                "/p/a.lamia:1:4: expected identifier, found end of input",
+                "/p/a.lamia:1:4: expected `=`, found end of input",
+                "/p/a.lamia:1:4: expected `;`, found end of input",
+            ]
+        );
+    }
+
+    #[test]
+    fn module_tree_is_memoized() {
+        let (_, _, mut db) = build_files(&[
+            ("/p/main.lamia", "mod a;\nfn main() {}"),
+            ("/p/a.lamia", "pub fn f() { 1 }"),
+        ]);
+        assert_eq!(
+            db.take_executed(),
+            [
+                "parse(FileId(0))",
+                "item_tree(FileId(0))",
+                "parse(FileId(1))",
+                "item_tree(FileId(1))",
+                "build_module_tree(FileId(0))"
+            ]
+        );
+
+        // building again reuses the tree, and an edit only reparses its file
+        let root = db.source_map().find(Path::new("/p/main.lamia")).unwrap();
+        build(&mut db, root);
+        assert!(db.take_executed().is_empty());
+
+        let a = db.source_map().find(Path::new("/p/a.lamia")).unwrap();
+        db.set_file_text(a, "pub fn g() { 1 }");
+        let (tree, _) = build(&mut db, root);
+        assert_eq!(
+            db.take_executed(),
+            [
+                "parse(FileId(1))",
+                "item_tree(FileId(1))",
+                "build_module_tree(FileId(0))"
+            ]
+        );
+        let children = tree.children(tree.root()).collect::<Vec<_>>();
+        assert!(tree.get(children[0]).scope.contains_key("g"));
+
+        // an edit inside a body rebuilds the same tree, which the queries reading it see
+        // as unchanged
+        module_tree(&db, root);
+        db.take_executed();
+        db.set_file_text(a, "pub fn g() { 2 }");
+        assert_eq!(*module_tree(&db, root), tree);
+        assert_eq!(
+            db.take_executed(),
+            ["parse(FileId(1))", "build_module_tree(FileId(0))"]
+        );
+    }
+
+    #[test]
+    fn added_files_are_found() {
+        let mut db = Database::new();
+        let root = db.add_file("/p/main.lamia", "mod a;");
+        let (_, diagnostics) = build(&mut db, root);
+        assert_eq!(diagnostics.len(), 1);
+
+        // the tree only reads files already in the database, and notices new ones
+        db.add_file("/p/a.lamia", "pub fn f() {}");
+        let tree = module_tree(&db, root);
+        assert_eq!(tree.children(tree.root()).count(), 1);
+    }
+}
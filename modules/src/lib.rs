@@ -0,0 +1,263 @@
+//! The module tree of a program: which modules exist, where their items are and what's in scope.
+
+mod builder;
+pub mod prelude;
+
+pub use builder::{build, module_tree};
+
+use std::collections::{BTreeMap, HashMap};
+
+use source_map::{FileId, Span, TextRange};
+
+/// Extension of lamia source files.
+pub const FILE_EXTENSION: &str = "lamia";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModuleId(u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleTree {
+    modules: Vec<ModuleData>,
+    enums: HashMap<Span, EnumData>,
+    traits: HashMap<Span, TraitData>,
+    impls: Vec<ImplData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleData {
+    pub name: String,
+    pub parent: Option<ModuleId>,
+    pub file: FileId,
+    pub source: ModuleSource,
+    /// Items defined or imported in the module, by name.
+    pub scope: BTreeMap<String, ScopeEntry>,
+}
+
+/// Where the items of a module are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleSource {
+    /// The whole file, for the root module and `mod foo;`.
+    File,
+    /// The body of `mod foo { ... }`.
+    Inline(TextRange),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Def {
+    Module(ModuleId),
+    /// A function, identified by the span of its name.
+    Function(Span),
+    /// An enum, identified by the span of its name.
+    Enum(Span),
+    /// A variant of an enum, by the span of the enum's name and the variant's index.
+    Variant(Span, usize),
+    /// A struct, identified by the span of its name.
+    Struct(Span),
+    /// A trait, identified by the span of its name.
+    Trait(Span),
+}
+
+/// An enum, or a struct, which is kept as an enum with a single record variant named
+/// after it, so that both are built, matched and typed the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumData {
+    pub name: String,
+    pub module: ModuleId,
+    /// The names of the generic parameters, which the types of the fields can refer to.
+    pub generics: Vec<String>,
+    pub variants: Vec<VariantData>,
+    pub is_struct: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantData {
+    pub name: String,
+    /// The span of the variant's name.
+    pub span: Span,
+    pub fields: VariantFields,
+}
+
+/// The shape of a variant's payload. The types of the fields are read from the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariantFields {
+    Unit,
+    /// `Variant(A, B)`, with the number of fields.
+    Tuple(usize),
+    /// `Variant { a: A, b: B }`, with the names of the fields in declaration order.
+    Record(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraitData {
+    pub name: String,
+    pub module: ModuleId,
+    pub functions: Vec<TraitFunction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraitFunction {
+    pub name: String,
+    /// The span of the function's name.
+    pub span: Span,
+    /// Whether the trait provides a body, which impls can leave out.
+    pub has_body: bool,
+}
+
+/// An `impl` block. Its types are read from the syntax, since they're only checked at runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImplData {
+    /// The span of the `impl` keyword, which identifies it.
+    pub span: Span,
+    pub module: ModuleId,
+    /// The implemented trait, by the span of its name. `None` for inherent impls.
+    pub trait_def: Option<Span>,
+    /// The functions of the impl and the spans of their names, in declaration order.
+    pub functions: Vec<(String, Span)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopeEntry {
+    pub def: Def,
+    pub is_pub: bool,
+    /// Where the name is introduced: the item's name or the `use` bringing it into scope.
+    pub span: Span,
+    pub imported: bool,
+}
+
+impl ModuleTree {
+    pub fn root(&self) -> ModuleId {
+        ModuleId(0)
+    }
+
+    pub fn get(&self, id: ModuleId) -> &ModuleData {
+        &self.modules[id.0 as usize]
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = ModuleId> {
+        (0..self.modules.len() as u32).map(ModuleId)
+    }
+
+    /// Child modules, in declaration order.
+    pub fn children(&self, id: ModuleId) -> impl Iterator<Item = ModuleId> + '_ {
+        self.modules()
+            .filter(move |&m| self.get(m).parent == Some(id))
+    }
+
+    /// The enum or the struct whose name is at `span`.
+    pub fn enum_data(&self, span: Span) -> &EnumData {
+        &self.enums[&span]
+    }
+
+    /// The trait whose name is at `span`.
+    pub fn trait_data(&self, span: Span) -> &TraitData {
+        &self.traits[&span]
+    }
+
+    /// Every impl of the program, whichever module it's in.
+    pub fn impls(&self) -> &[ImplData] {
+        &self.impls
+    }
+
+    /// The innermost module whose items contain `span`.
+    pub fn module_at(&self, span: Span) -> ModuleId {
+        self.modules()
+            .filter(|&module| self.get(module).file == span.file)
+            .filter_map(|module| match self.get(module).source {
+                ModuleSource::File => Some((module, TextRange::up_to(u32::MAX.into()))),
+                ModuleSource::Inline(range) => {
+                    range.contains_range(span.range).then_some((module, range))
+                }
+            })
+            .min_by_key(|(_, range)| range.len())
+            .map_or(self.root(), |(module, _)| module)
+    }
+
+    /// The `::` separated path of a module from the root, `crate` for the root itself.
+    pub fn path(&self, id: ModuleId) -> String {
+        let mut names = Vec::new();
+        let mut current = Some(id);
+        while let Some(module) = current {
+            let data = self.get(module);
+            if data.parent.is_some() {
+                names.push(data.name.as_str());
+            }
+            current = data.parent;
+        }
+
+        if names.is_empty() {
+            return "crate".to_string();
+        }
+        names.reverse();
+        names.join("::")
+    }
+
+    /// Whether `module` is `ancestor` or nested inside it.
+    pub fn is_descendant(&self, module: ModuleId, ancestor: ModuleId) -> bool {
+        let mut current = Some(module);
+        while let Some(m) = current {
+            if m == ancestor {
+                return true;
+            }
+            current = self.get(m).parent;
+        }
+        false
+    }
+
+    /// Looks up `segments` from `from`: the first segment is searched in `from` and then in
+    /// the root module, the following ones in the module named by the previous segment.
+    /// The last segment can also name a variant of the enum before it.
+    pub fn resolve_path(&self, from: ModuleId, segments: &[&str]) -> Result<Def, ResolveError> {
+        let Some((first, rest)) = segments.split_first() else {
+            return Err(ResolveError::Unresolved(0));
+        };
+
+        let mut entry = self
+            .get(from)
+            .scope
+            .get(*first)
+            .or_else(|| self.get(self.root()).scope.get(*first))
+            .copied()
+            .ok_or(ResolveError::Unresolved(0))?;
+
+        for (index, segment) in rest.iter().enumerate() {
+            if let Def::Enum(span) = entry.def {
+                if index + 1 < rest.len() {
+                    return Err(ResolveError::NotAModule(index + 1));
+                }
+
+                // variants are as visible as their enum
+                return self.enums[&span]
+                    .variants
+                    .iter()
+                    .position(|variant| variant.name == *segment)
+                    .map(|variant| Def::Variant(span, variant))
+                    .ok_or(ResolveError::Unresolved(index + 1));
+            }
+
+            let Def::Module(module) = entry.def else {
+                return Err(ResolveError::NotAModule(index));
+            };
+
+            entry = *self
+                .get(module)
+                .scope
+                .get(*segment)
+                .ok_or(ResolveError::Unresolved(index + 1))?;
+
+            // private items are only visible in their module and its descendants
+            if !entry.is_pub && !self.is_descendant(from, module) {
+                return Err(ResolveError::Private(index + 1));
+            }
+        }
+
+        Ok(entry.def)
+    }
+}
+
+/// Why a path couldn't be resolved, with the index of the offending segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveError {
+    Unresolved(usize),
+    NotAModule(usize),
+    Private(usize),
+}
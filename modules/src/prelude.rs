@@ -0,0 +1,307 @@
+//! The functions in scope everywhere unless a local or an item shadows them, which are
+//! provided natively rather than written in lamia.
+
+use std::fmt;
+
+/// A function of the prelude.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    Assert,
+    AssertEq,
+    Print,
+    Println,
+    Eprintln,
+    Len,
+    Concat,
+    Split,
+    Trim,
+    ToUpper,
+    ToLower,
+    Chars,
+    Abs,
+    Min,
+    Max,
+    Sqrt,
+    Pow,
+    Sin,
+    Cos,
+    Tan,
+    Push,
+    Pop,
+    NewMap,
+    Insert,
+    Get,
+    Remove,
+    ContainsKey,
+    Keys,
+    Values,
+    ReadFile,
+    WriteFile,
+}
+
+/// The kinds of values the functions of the prelude take and return. Those that depend on
+/// the arguments, such as the elements of arrays, are [`Kind::Any`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Any,
+    Unit,
+    Int,
+    Float,
+    /// An integer or a float.
+    Number,
+    Bool,
+    Char,
+    String,
+    Array,
+    Map,
+    /// A string, an array or a map, which have a length.
+    Collection,
+}
+
+/// The parameters and the return kind of a function of the prelude.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub params: &'static [Kind],
+    /// How many of the parameters must be supplied, the others being optional.
+    pub required: usize,
+    pub ret: Kind,
+}
+
+impl Builtin {
+    const ALL: [Self; 31] = [
+        Self::Assert,
+        Self::AssertEq,
+        Self::Print,
+        Self::Println,
+        Self::Eprintln,
+        Self::Len,
+        Self::Concat,
+        Self::Split,
+        Self::Trim,
+        Self::ToUpper,
+        Self::ToLower,
+        Self::Chars,
+        Self::Abs,
+        Self::Min,
+        Self::Max,
+        Self::Sqrt,
+        Self::Pow,
+        Self::Sin,
+        Self::Cos,
+        Self::Tan,
+        Self::Push,
+        Self::Pop,
+        Self::NewMap,
+        Self::Insert,
+        Self::Get,
+        Self::Remove,
+        Self::ContainsKey,
+        Self::Keys,
+        Self::Values,
+        Self::ReadFile,
+        Self::WriteFile,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|builtin| builtin.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Assert => "assert",
+            Self::AssertEq => "assert_eq",
+            Self::Print => "print",
+            Self::Println => "println",
+            Self::Eprintln => "eprintln",
+            Self::Len => "len",
+            Self::Concat => "concat",
+            Self::Split => "split",
+            Self::Trim => "trim",
+            Self::ToUpper => "to_upper",
+            Self::ToLower => "to_lower",
+            Self::Chars => "chars",
+            Self::Abs => "abs",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Sqrt => "sqrt",
+            Self::Pow => "pow",
+            Self::Sin => "sin",
+            Self::Cos => "cos",
+            Self::Tan => "tan",
+            Self::Push => "push",
+            Self::Pop => "pop",
+            Self::NewMap => "new_map",
+            Self::Insert => "insert",
+            Self::Get => "get",
+            Self::Remove => "remove",
+            Self::ContainsKey => "contains_key",
+            Self::Keys => "keys",
+            Self::Values => "values",
+            Self::ReadFile => "read_file",
+            Self::WriteFile => "write_file",
+        }
+    }
+
+    pub fn signature(self) -> Signature {
+        use Kind::*;
+
+        let (params, required, ret): (&[Kind], _, _) = match self {
+            // the optional message is displayed, so it can be any value
+            Self::Assert => (&[Bool, Any], 1, Unit),
+            Self::AssertEq => (&[Any, Any, Any], 2, Unit),
+            Self::Print | Self::Eprintln => (&[Any], 1, Unit),
+            Self::Println => (&[Any], 0, Unit),
+            Self::Len => (&[Collection], 1, Int),
+            Self::Concat => (&[String, String], 2, String),
+            Self::Split => (&[String, String], 2, Array),
+            Self::Trim | Self::ToUpper | Self::ToLower => (&[String], 1, String),
+            Self::Chars => (&[String], 1, Array),
+            Self::Abs => (&[Number], 1, Number),
+            Self::Min | Self::Max | Self::Pow => (&[Number, Number], 2, Number),
+            Self::Sqrt | Self::Sin | Self::Cos | Self::Tan => (&[Float], 1, Float),
+            Self::Push => (&[Array, Any], 2, Unit),
+            Self::Pop => (&[Array], 1, Any),
+            Self::NewMap => (&[], 0, Map),
+            Self::Insert => (&[Map, Any, Any], 3, Unit),
+            Self::Get | Self::Remove => (&[Map, Any], 2, Any),
+            Self::ContainsKey => (&[Map, Any], 2, Bool),
+            Self::Keys | Self::Values => (&[Map], 1, Array),
+            Self::ReadFile => (&[String], 1, String),
+            Self::WriteFile => (&[String, String], 2, Unit),
+        };
+
+        Signature {
+            params,
+            required,
+            ret,
+        }
+    }
+
+    /// Whether the function modifies its first argument, which must then be a place such
+    /// as a variable.
+    pub fn mutates(self) -> bool {
+        matches!(self, Self::Push | Self::Pop | Self::Insert | Self::Remove)
+    }
+
+    /// Whether the function prints or accesses files, which the program embedding the
+    /// interpreter can forbid.
+    pub fn performs_io(self) -> bool {
+        matches!(
+            self,
+            Self::Print | Self::Println | Self::Eprintln | Self::ReadFile | Self::WriteFile
+        )
+    }
+
+    /// An error if the function can't be called with `supplied` arguments.
+    pub fn check_arity(self, supplied: usize) -> Result<(), String> {
+        let Signature {
+            params, required, ..
+        } = self.signature();
+        if (required..=params.len()).contains(&supplied) {
+            return Ok(());
+        }
+
+        let expected = match params.len() - required {
+            0 => format!(
+                "{required} argument{}",
+                if required == 1 { "" } else { "s" }
+            ),
+            _ => format!("{required} or {} arguments", params.len()),
+        };
+        Err(format!(
+            "function `{}` takes {expected} but {supplied} {} supplied",
+            self.name(),
+            if supplied == 1 { "was" } else { "were" },
+        ))
+    }
+}
+
+impl Kind {
+    /// Whether some values have both kinds, so that a value of one kind could be passed
+    /// where the other one is expected.
+    pub fn overlaps(self, other: Self) -> bool {
+        if self == Self::Any || other == Self::Any {
+            return true;
+        }
+        self.concrete()
+            .iter()
+            .any(|kind| other.concrete().contains(kind))
+    }
+
+    /// The kinds of values this kind is made of.
+    fn concrete(self) -> &'static [Self] {
+        match self {
+            Self::Any => &[],
+            Self::Unit => &[Self::Unit],
+            Self::Int => &[Self::Int],
+            Self::Float => &[Self::Float],
+            Self::Number => &[Self::Int, Self::Float],
+            Self::Bool => &[Self::Bool],
+            Self::Char => &[Self::Char],
+            Self::String => &[Self::String],
+            Self::Array => &[Self::Array],
+            Self::Map => &[Self::Map],
+            Self::Collection => &[Self::String, Self::Array, Self::Map],
+        }
+    }
+}
+
+/// The kind between backticks as in error messages, such as `` `integer` or `float` ``.
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => f.write_str("`_`"),
+            Self::Unit => f.write_str("`()`"),
+            Self::Int => f.write_str("`integer`"),
+            Self::Float => f.write_str("`float`"),
+            Self::Number => f.write_str("`integer` or `float`"),
+            Self::Bool => f.write_str("`bool`"),
+            Self::Char => f.write_str("`char`"),
+            Self::String => f.write_str("`string`"),
+            Self::Array => f.write_str("`array`"),
+            Self::Map => f.write_str("`map`"),
+            Self::Collection => f.write_str("`string`, `array` or `map`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Builtin, Kind};
+
+    #[test]
+    fn names() {
+        for builtin in Builtin::ALL {
+            assert_eq!(Builtin::from_name(builtin.name()), Some(builtin));
+        }
+        assert_eq!(Builtin::from_name("main"), None);
+    }
+
+    #[test]
+    fn arity() {
+        assert_eq!(Builtin::Len.check_arity(1), Ok(()));
+        assert_eq!(
+            Builtin::Len.check_arity(2),
+            Err("function `len` takes 1 argument but 2 were supplied".to_string())
+        );
+        assert_eq!(
+            Builtin::NewMap.check_arity(1),
+            Err("function `new_map` takes 0 arguments but 1 was supplied".to_string())
+        );
+        assert_eq!(Builtin::Println.check_arity(0), Ok(()));
+        assert_eq!(
+            Builtin::AssertEq.check_arity(4),
+            Err("function `assert_eq` takes 2 or 3 arguments but 4 were supplied".to_string())
+        );
+    }
+
+    #[test]
+    fn overlapping_kinds() {
+        assert!(Kind::Number.overlaps(Kind::Float));
+        assert!(Kind::Int.overlaps(Kind::Number));
+        assert!(Kind::Collection.overlaps(Kind::Map));
+        assert!(Kind::Any.overlaps(Kind::Unit));
+        assert!(!Kind::Float.overlaps(Kind::Int));
+        assert!(!Kind::Collection.overlaps(Kind::Number));
+    }
+}
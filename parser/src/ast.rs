@@ -0,0 +1,1469 @@
+//! Typed wrappers around [`SyntaxNode`]s.
+//!
+//! Accessors return `Option`s since the tree can be incomplete when the source has errors.
+
+use std::iter;
+
+use rowan::{Language, NodeOrToken, TextRange};
+
+use crate::syntax::{Lang, SyntaxKind, SyntaxNode, SyntaxToken};
+
+pub trait AstNode: Sized {
+    fn cast(node: SyntaxNode) -> Option<Self>;
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+macro_rules! ast_node {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            pub struct $name(SyntaxNode);
+
+            impl AstNode for $name {
+                fn cast(node: SyntaxNode) -> Option<Self> {
+                    (node.kind() == SyntaxKind::$name).then_some(Self(node))
+                }
+
+                fn syntax(&self) -> &SyntaxNode {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! ast_enum {
+    ($name:ident { $($variant:ident($node:ident)),* $(,)? }) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant($node),)*
+        }
+
+        impl AstNode for $name {
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                match node.kind() {
+                    $(SyntaxKind::$node => Some(Self::$variant($node(node))),)*
+                    _ => None,
+                }
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                match self {
+                    $(Self::$variant(node) => node.syntax(),)*
+                }
+            }
+        }
+    };
+}
+
+ast_node!(
+    Root,
+    LetStmt,
+    ReturnStmt,
+    ExprStmt,
+    ModStmt,
+    UseStmt,
+    EnumStmt,
+    StructStmt,
+    TraitStmt,
+    ImplStmt,
+    VariantList,
+    Variant,
+    RecordFieldList,
+    RecordField,
+    ItemList,
+    Visibility,
+    Attr,
+    AttrArgList,
+    AttrArg,
+    Literal,
+    InterpolatedString,
+    VariableRef,
+    ParenExpr,
+    TupleExpr,
+    PrefixExpr,
+    BinaryExpr,
+    CallExpr,
+    MethodCallExpr,
+    FieldExpr,
+    ArgList,
+    BlockExpr,
+    FunctionExpr,
+    ParamList,
+    Param,
+    IfExpr,
+    ArrayExpr,
+    ArrayRepeatExpr,
+    IndexExpr,
+    ClosureExpr,
+    MatchExpr,
+    MatchArmList,
+    MatchArm,
+    MatchGuard,
+    RecordExpr,
+    RecordExprFieldList,
+    RecordExprField,
+    PathType,
+    ArrayType,
+    SliceType,
+    FnType,
+    TupleType,
+    RefType,
+    OptionalType,
+    TypeList,
+    GenericParamList,
+    GenericParam,
+    TypeBoundList,
+    GenericArgList,
+    LiteralPattern,
+    WildcardPattern,
+    IdentPattern,
+    RangePattern,
+    RestPattern,
+    OrPattern,
+    PathPattern,
+    TuplePattern,
+    TupleStructPattern,
+    StructPattern,
+    PatternList,
+    FieldPatternList,
+    FieldPattern,
+);
+
+ast_enum!(Stmt {
+    Let(LetStmt),
+    Return(ReturnStmt),
+    Expr(ExprStmt),
+    Mod(ModStmt),
+    Use(UseStmt),
+    Enum(EnumStmt),
+    Struct(StructStmt),
+    Trait(TraitStmt),
+    Impl(ImplStmt),
+});
+
+ast_enum!(Expr {
+    Literal(Literal),
+    InterpolatedString(InterpolatedString),
+    VariableRef(VariableRef),
+    Paren(ParenExpr),
+    Tuple(TupleExpr),
+    Prefix(PrefixExpr),
+    Binary(BinaryExpr),
+    Call(CallExpr),
+    MethodCall(MethodCallExpr),
+    Field(FieldExpr),
+    Block(BlockExpr),
+    Function(FunctionExpr),
+    If(IfExpr),
+    Array(ArrayExpr),
+    ArrayRepeat(ArrayRepeatExpr),
+    Index(IndexExpr),
+    Closure(ClosureExpr),
+    Match(MatchExpr),
+    Record(RecordExpr),
+});
+
+ast_enum!(Type {
+    Path(PathType),
+    Array(ArrayType),
+    Slice(SliceType),
+    Fn(FnType),
+    Tuple(TupleType),
+    Ref(RefType),
+    Optional(OptionalType),
+});
+
+ast_enum!(Pattern {
+    Literal(LiteralPattern),
+    Wildcard(WildcardPattern),
+    Ident(IdentPattern),
+    Range(RangePattern),
+    Rest(RestPattern),
+    Or(OrPattern),
+    Path(PathPattern),
+    Tuple(TuplePattern),
+    TupleStruct(TupleStructPattern),
+    Struct(StructPattern),
+});
+
+fn child<N: AstNode>(parent: &SyntaxNode) -> Option<N> {
+    parent.children().find_map(N::cast)
+}
+
+fn children<N: AstNode>(parent: &SyntaxNode) -> impl Iterator<Item = N> {
+    parent.children().filter_map(N::cast)
+}
+
+fn token(parent: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    tokens(parent, kind).next()
+}
+
+fn tokens(parent: &SyntaxNode, kind: SyntaxKind) -> impl Iterator<Item = SyntaxToken> {
+    tokens_where(parent, move |other| other == kind)
+}
+
+/// The tokens among the children of `parent` whose kinds match `predicate`. They're looked
+/// for in the green tree, since creating every child, as iterating over them does, is what
+/// costs the most when the interpreter walks the tree.
+fn tokens_where(
+    parent: &SyntaxNode,
+    predicate: impl Fn(SyntaxKind) -> bool,
+) -> impl Iterator<Item = SyntaxToken> {
+    let parent = parent.clone();
+    let mut index = 0;
+    let mut offset = parent.text_range().start();
+
+    iter::from_fn(move || {
+        let green = parent.green();
+        for child in green.children().skip(index) {
+            index += 1;
+            let range = TextRange::at(offset, child.text_len());
+            offset = range.end();
+
+            if child.as_token().is_some() && predicate(Lang::kind_from_raw(child.kind())) {
+                return token_at(&parent, range);
+            }
+        }
+        None
+    })
+}
+
+/// The token child of `parent` at `range`.
+fn token_at(parent: &SyntaxNode, range: TextRange) -> Option<SyntaxToken> {
+    let is_token = |token: &SyntaxToken| token.text_range() == range;
+    parent
+        .child_or_token_at_range(range)
+        .and_then(|element| element.into_token())
+        .filter(is_token)
+        // the search by range can't tell empty nodes apart from the tokens next to them
+        .or_else(|| {
+            parent
+                .children_with_tokens()
+                .filter_map(|element| element.into_token())
+                .find(is_token)
+        })
+}
+
+impl Root {
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.0)
+    }
+
+    /// The inner attributes, such as `#![inline]`, which apply to what encloses them.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+}
+
+impl LetStmt {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn mut_keyword(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::MutKeyword)
+    }
+
+    /// The pattern destructuring the value, as in `let (a, b) = pair;`.
+    pub fn pattern(&self) -> Option<Pattern> {
+        child(&self.0)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn initializer(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl ReturnStmt {
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl ExprStmt {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn has_semicolon(&self) -> bool {
+        token(&self.0, SyntaxKind::Semicolon).is_some()
+    }
+}
+
+impl ModStmt {
+    /// The attributes written in front of the item.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<Visibility> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    /// The body of inline modules, `None` for `mod foo;`.
+    pub fn item_list(&self) -> Option<ItemList> {
+        child(&self.0)
+    }
+}
+
+impl ItemList {
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.0)
+    }
+
+    /// The inner attributes, such as `#![inline]`, which apply to what encloses them.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+
+    /// The functions declared in the list, which are all there is in traits and impls.
+    pub fn functions(&self) -> impl Iterator<Item = FunctionExpr> {
+        self.stmts().filter_map(|stmt| match stmt {
+            Stmt::Expr(stmt) => match stmt.expr()? {
+                Expr::Function(function) => Some(function),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+}
+
+impl UseStmt {
+    /// The attributes written in front of the item.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<Visibility> {
+        child(&self.0)
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        tokens(&self.0, SyntaxKind::Identifier)
+    }
+}
+
+impl EnumStmt {
+    /// The attributes written in front of the item.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<Visibility> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn generic_param_list(&self) -> Option<GenericParamList> {
+        child(&self.0)
+    }
+
+    pub fn variant_list(&self) -> Option<VariantList> {
+        child(&self.0)
+    }
+}
+
+impl StructStmt {
+    /// The attributes written in front of the item.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<Visibility> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn generic_param_list(&self) -> Option<GenericParamList> {
+        child(&self.0)
+    }
+
+    /// The fields of `struct Name { a: A, b: B }`.
+    pub fn record_fields(&self) -> Option<RecordFieldList> {
+        child(&self.0)
+    }
+}
+
+impl TraitStmt {
+    /// The attributes written in front of the item.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<Visibility> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn item_list(&self) -> Option<ItemList> {
+        child(&self.0)
+    }
+}
+
+impl ImplStmt {
+    /// The attributes written in front of the item.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+
+    pub fn impl_keyword(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::ImplKeyword)
+    }
+
+    pub fn generic_param_list(&self) -> Option<GenericParamList> {
+        child(&self.0)
+    }
+
+    /// The trait in `impl Trait for Type`, `None` for inherent impls.
+    pub fn trait_type(&self) -> Option<Type> {
+        token(&self.0, SyntaxKind::ForKeyword)?;
+        child(&self.0)
+    }
+
+    /// The type whose values get the methods, which follows the `for` of trait impls.
+    pub fn self_type(&self) -> Option<Type> {
+        match token(&self.0, SyntaxKind::ForKeyword) {
+            Some(for_keyword) => for_keyword
+                .siblings_with_tokens(rowan::Direction::Next)
+                .filter_map(|element| element.into_node())
+                .find_map(Type::cast),
+            None => child(&self.0),
+        }
+    }
+
+    pub fn item_list(&self) -> Option<ItemList> {
+        child(&self.0)
+    }
+}
+
+impl VariantList {
+    pub fn variants(&self) -> impl Iterator<Item = Variant> {
+        children(&self.0)
+    }
+}
+
+impl Variant {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    /// The types of `Variant(A, B)`.
+    pub fn tuple_fields(&self) -> Option<TypeList> {
+        child(&self.0)
+    }
+
+    /// The fields of `Variant { a: A, b: B }`.
+    pub fn record_fields(&self) -> Option<RecordFieldList> {
+        child(&self.0)
+    }
+}
+
+impl RecordFieldList {
+    pub fn fields(&self) -> impl Iterator<Item = RecordField> {
+        children(&self.0)
+    }
+}
+
+impl RecordField {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl Visibility {
+    pub fn is_pub(&self) -> bool {
+        token(&self.0, SyntaxKind::PubKeyword).is_some()
+    }
+}
+
+impl Attr {
+    /// Whether this is an inner attribute, as in `#![name]`.
+    pub fn is_inner(&self) -> bool {
+        token(&self.0, SyntaxKind::Bang).is_some()
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    /// The arguments of `#[name(args)]`, `None` for `#[name]`.
+    pub fn arg_list(&self) -> Option<AttrArgList> {
+        child(&self.0)
+    }
+}
+
+impl AttrArgList {
+    pub fn args(&self) -> impl Iterator<Item = AttrArg> {
+        children(&self.0)
+    }
+}
+
+impl AttrArg {
+    /// The name of `name` and `name = value`, `None` for a lone literal.
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    /// The literal of `name = value` or of a lone literal.
+    pub fn value(&self) -> Option<Literal> {
+        child(&self.0)
+    }
+}
+
+impl Literal {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        self.0.first_token()
+    }
+}
+
+/// A piece of an interpolated string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InterpolatedStringPart {
+    /// Text along with the delimiters around it, such as `f"` and `{` in `f"text{`.
+    Text(SyntaxToken),
+    Expr(Expr),
+}
+
+impl InterpolatedString {
+    /// The pieces of text and the embedded expressions, in source order.
+    pub fn parts(&self) -> impl Iterator<Item = InterpolatedStringPart> {
+        self.0
+            .children_with_tokens()
+            .filter_map(|element| match element {
+                NodeOrToken::Node(node) => Expr::cast(node).map(InterpolatedStringPart::Expr),
+                NodeOrToken::Token(token) => matches!(
+                    token.kind(),
+                    SyntaxKind::InterpolatedStringPlain
+                        | SyntaxKind::InterpolatedStringStart
+                        | SyntaxKind::InterpolatedStringMiddle
+                        | SyntaxKind::InterpolatedStringEnd
+                )
+                .then_some(InterpolatedStringPart::Text(token)),
+            })
+    }
+}
+
+impl VariableRef {
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        tokens(&self.0, SyntaxKind::Identifier)
+    }
+
+    /// The generic arguments after a `::` in the path, as in `f::<T>`.
+    pub fn generic_arg_list(&self) -> Option<GenericArgList> {
+        child(&self.0)
+    }
+
+    /// The last segment of the path.
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.segments().last()
+    }
+}
+
+impl ParenExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl TupleExpr {
+    /// The elements of the tuple, none for the unit value `()`.
+    pub fn elements(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}
+
+impl PrefixExpr {
+    pub fn op(&self) -> Option<SyntaxToken> {
+        self.0.first_token()
+    }
+
+    pub fn operand(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl BinaryExpr {
+    pub fn lhs(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+
+    pub fn op(&self) -> Option<SyntaxToken> {
+        tokens_where(&self.0, |kind| !kind.is_trivia()).next()
+    }
+}
+
+impl CallExpr {
+    pub fn callee(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+}
+
+impl MethodCallExpr {
+    pub fn receiver(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+}
+
+impl ArgList {
+    pub fn args(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}
+
+impl BlockExpr {
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.0)
+    }
+
+    /// The inner attributes, such as `#![inline]`, which apply to what encloses them.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+
+    /// The trailing expression without a `;` that gives the block its value.
+    pub fn tail_expr(&self) -> Option<Expr> {
+        match self.stmts().last()? {
+            Stmt::Expr(stmt) if !stmt.has_semicolon() => stmt.expr(),
+            _ => None,
+        }
+    }
+}
+
+impl FunctionExpr {
+    /// The attributes written in front of the item.
+    pub fn attrs(&self) -> impl Iterator<Item = Attr> {
+        children(&self.0)
+    }
+
+    pub fn visibility(&self) -> Option<Visibility> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn generic_param_list(&self) -> Option<GenericParamList> {
+        child(&self.0)
+    }
+
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn return_type(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<BlockExpr> {
+        child(&self.0)
+    }
+}
+
+impl ParamList {
+    pub fn params(&self) -> impl Iterator<Item = Param> {
+        children(&self.0)
+    }
+}
+
+impl Param {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn mut_keyword(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::MutKeyword)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ElseBranch {
+    Block(BlockExpr),
+    If(IfExpr),
+}
+
+impl IfExpr {
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn then_branch(&self) -> Option<BlockExpr> {
+        // the first child is the condition, which can be a block itself
+        self.0.children().nth(1).and_then(BlockExpr::cast)
+    }
+
+    pub fn else_branch(&self) -> Option<ElseBranch> {
+        let else_keyword = token(&self.0, SyntaxKind::ElseKeyword)?;
+        let branch = else_keyword
+            .siblings_with_tokens(rowan::Direction::Next)
+            .find_map(|element| element.into_node())?;
+
+        match branch.kind() {
+            SyntaxKind::BlockExpr => Some(ElseBranch::Block(BlockExpr(branch))),
+            SyntaxKind::IfExpr => Some(ElseBranch::If(IfExpr(branch))),
+            _ => None,
+        }
+    }
+}
+
+impl FieldExpr {
+    pub fn receiver(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    /// The number or name after the `.`, as in `t.0` or `p.x`.
+    pub fn field(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Number).or_else(|| token(&self.0, SyntaxKind::Identifier))
+    }
+}
+
+impl ArrayExpr {
+    pub fn elements(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}
+
+impl ArrayRepeatExpr {
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn count(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl IndexExpr {
+    pub fn base(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn index(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl ClosureExpr {
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn return_type(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl MatchExpr {
+    pub fn scrutinee(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn arm_list(&self) -> Option<MatchArmList> {
+        child(&self.0)
+    }
+}
+
+impl MatchArmList {
+    pub fn arms(&self) -> impl Iterator<Item = MatchArm> {
+        children(&self.0)
+    }
+}
+
+impl MatchArm {
+    pub fn pattern(&self) -> Option<Pattern> {
+        child(&self.0)
+    }
+
+    pub fn guard(&self) -> Option<MatchGuard> {
+        child(&self.0)
+    }
+
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl MatchGuard {
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl RecordExpr {
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        tokens(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn generic_arg_list(&self) -> Option<GenericArgList> {
+        child(&self.0)
+    }
+
+    pub fn field_list(&self) -> Option<RecordExprFieldList> {
+        child(&self.0)
+    }
+}
+
+impl RecordExprFieldList {
+    pub fn fields(&self) -> impl Iterator<Item = RecordExprField> {
+        children(&self.0)
+    }
+}
+
+impl RecordExprField {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    /// The value of the field, `None` for the `field` shorthand.
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl PathType {
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        tokens(&self.0, SyntaxKind::Identifier)
+    }
+
+    /// The last segment of the path.
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.segments().last()
+    }
+
+    pub fn generic_arg_list(&self) -> Option<GenericArgList> {
+        child(&self.0)
+    }
+}
+
+impl ArrayType {
+    pub fn element_type(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn len(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl SliceType {
+    pub fn element_type(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl FnType {
+    pub fn params(&self) -> Option<TypeList> {
+        child(&self.0)
+    }
+
+    pub fn return_type(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl TupleType {
+    /// The types of the fields, none for the unit type `()`.
+    pub fn fields(&self) -> impl Iterator<Item = Type> {
+        children(&self.0)
+    }
+
+    /// Whether this is `(T)`, a type in parentheses rather than a tuple.
+    pub fn is_paren(&self) -> bool {
+        self.fields().count() == 1 && token(&self.0, SyntaxKind::Comma).is_none()
+    }
+}
+
+impl RefType {
+    pub fn mut_keyword(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::MutKeyword)
+    }
+
+    /// The type of the referenced value.
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl OptionalType {
+    /// `T` in `T?`.
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl TypeList {
+    pub fn types(&self) -> impl Iterator<Item = Type> {
+        children(&self.0)
+    }
+}
+
+impl GenericParamList {
+    pub fn params(&self) -> impl Iterator<Item = GenericParam> {
+        children(&self.0)
+    }
+}
+
+impl GenericParam {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    /// The traits in `T: A + B`.
+    pub fn bounds(&self) -> impl Iterator<Item = PathType> {
+        child::<TypeBoundList>(&self.0)
+            .into_iter()
+            .flat_map(|list| children(&list.0))
+    }
+}
+
+impl GenericArgList {
+    pub fn types(&self) -> impl Iterator<Item = Type> {
+        children(&self.0)
+    }
+}
+
+impl LiteralPattern {
+    pub fn is_negative(&self) -> bool {
+        token(&self.0, SyntaxKind::Minus).is_some()
+    }
+
+    /// The literal itself, without the minus sign of negative numbers.
+    pub fn token(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .find(|token| token.kind() != SyntaxKind::Minus)
+    }
+}
+
+impl IdentPattern {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn mut_keyword(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::MutKeyword)
+    }
+}
+
+impl RangePattern {
+    pub fn start(&self) -> Option<LiteralPattern> {
+        child(&self.0)
+    }
+
+    pub fn end(&self) -> Option<LiteralPattern> {
+        children(&self.0).nth(1)
+    }
+
+    /// Whether the range includes its end, i.e. `a..=b` rather than `a..b`.
+    pub fn is_inclusive(&self) -> bool {
+        token(&self.0, SyntaxKind::DotDotEqual).is_some()
+    }
+}
+
+impl OrPattern {
+    pub fn patterns(&self) -> impl Iterator<Item = Pattern> {
+        children(&self.0)
+    }
+}
+
+impl PathPattern {
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        tokens(&self.0, SyntaxKind::Identifier)
+    }
+}
+
+impl TuplePattern {
+    pub fn patterns(&self) -> impl Iterator<Item = Pattern> {
+        children(&self.0)
+    }
+}
+
+impl TupleStructPattern {
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        tokens(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn pattern_list(&self) -> Option<PatternList> {
+        child(&self.0)
+    }
+}
+
+impl PatternList {
+    pub fn patterns(&self) -> impl Iterator<Item = Pattern> {
+        children(&self.0)
+    }
+}
+
+impl StructPattern {
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        tokens(&self.0, SyntaxKind::Identifier)
+    }
+
+    pub fn field_list(&self) -> Option<FieldPatternList> {
+        child(&self.0)
+    }
+}
+
+impl FieldPatternList {
+    pub fn fields(&self) -> impl Iterator<Item = FieldPattern> {
+        children(&self.0)
+    }
+
+    /// Whether the remaining fields are ignored with `..`.
+    pub fn has_rest(&self) -> bool {
+        child::<RestPattern>(&self.0).is_some()
+    }
+}
+
+impl FieldPattern {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Identifier)
+    }
+
+    /// The pattern of the field, `None` for the `field` shorthand.
+    pub fn pattern(&self) -> Option<Pattern> {
+        child(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AstNode, ElseBranch, Expr, InterpolatedStringPart, Pattern, Root, Stmt, Type};
+    use crate::Parser;
+
+    fn parse(input: &str) -> Root {
+        Root::cast(Parser::new(input).parse().syntax()).unwrap()
+    }
+
+    #[test]
+    fn let_stmt() {
+        let Some(Stmt::Let(stmt)) = parse("let a: i32 = 1;").stmts().next() else {
+            panic!("expected a let statement");
+        };
+
+        assert_eq!(stmt.name().unwrap().text(), "a");
+        assert_eq!(stmt.ty().unwrap().syntax().to_string(), "i32");
+        assert!(matches!(stmt.initializer(), Some(Expr::Literal(_))));
+    }
+
+    #[test]
+    fn interpolated_string_parts() {
+        let Some(Stmt::Expr(stmt)) = parse(r#"f"a{b}c{ d + 1 }""#).stmts().next() else {
+            panic!("expected an expression statement");
+        };
+        let Some(Expr::InterpolatedString(string)) = stmt.expr() else {
+            panic!("expected an interpolated string");
+        };
+
+        let parts = string
+            .parts()
+            .map(|part| match part {
+                InterpolatedStringPart::Text(text) => text.text().to_string(),
+                InterpolatedStringPart::Expr(expr) => format!("<{}>", expr.syntax()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(parts, ["f\"a{", "<b>", "}c{", "<d + 1>", "}\""]);
+    }
+
+    #[test]
+    fn destructuring_let_stmt() {
+        let Some(Stmt::Let(stmt)) = parse("let (a, b): ((u8), (u8,)) = t.0;").stmts().next() else {
+            panic!("expected a let statement");
+        };
+
+        assert_eq!(stmt.name(), None);
+        let Some(Pattern::Tuple(pattern)) = stmt.pattern() else {
+            panic!("expected a tuple pattern");
+        };
+        assert_eq!(pattern.patterns().count(), 2);
+
+        let Some(Type::Tuple(ty)) = stmt.ty() else {
+            panic!("expected a tuple type");
+        };
+        let fields = ty.fields().collect::<Vec<_>>();
+        assert!(!ty.is_paren());
+        assert!(matches!(&fields[0], Type::Tuple(field) if field.is_paren()));
+        assert!(matches!(&fields[1], Type::Tuple(field) if !field.is_paren()));
+
+        let Some(Expr::Field(field)) = stmt.initializer() else {
+            panic!("expected a field expression");
+        };
+        assert_eq!(field.field().unwrap().text(), "0");
+        assert!(matches!(field.receiver(), Some(Expr::VariableRef(_))));
+    }
+
+    #[test]
+    fn function_expr() {
+        let Some(Stmt::Expr(stmt)) = parse("pub fn f(a, b: u8) -> u8 { a }").stmts().next() else {
+            panic!("expected an expression statement");
+        };
+        let Some(Expr::Function(function)) = stmt.expr() else {
+            panic!("expected a function");
+        };
+
+        assert!(function.visibility().unwrap().is_pub());
+        assert_eq!(function.name().unwrap().text(), "f");
+        assert_eq!(function.return_type().unwrap().syntax().to_string(), "u8");
+
+        let params = function.param_list().unwrap().params().collect::<Vec<_>>();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].ty(), None);
+        assert_eq!(params[1].ty().unwrap().syntax().to_string(), "u8");
+
+        let tail = function.body().unwrap().tail_expr();
+        assert!(matches!(tail, Some(Expr::VariableRef(_))));
+    }
+
+    #[test]
+    fn binary_expr() {
+        let Some(Stmt::Expr(stmt)) = parse("a - -b").stmts().next() else {
+            panic!("expected an expression statement");
+        };
+        let Some(Expr::Binary(binary)) = stmt.expr() else {
+            panic!("expected a binary expression");
+        };
+
+        assert_eq!(binary.op().unwrap().text(), "-");
+        assert!(matches!(binary.lhs(), Some(Expr::VariableRef(_))));
+        assert!(matches!(binary.rhs(), Some(Expr::Prefix(_))));
+    }
+
+    #[test]
+    fn if_expr() {
+        let Some(Stmt::Expr(stmt)) = parse("if a { 1 } else if b { 2 }").stmts().next() else {
+            panic!("expected an expression statement");
+        };
+        let Some(Expr::If(if_expr)) = stmt.expr() else {
+            panic!("expected an if expression");
+        };
+
+        assert!(matches!(if_expr.condition(), Some(Expr::VariableRef(_))));
+        assert!(if_expr.then_branch().unwrap().tail_expr().is_some());
+        assert!(matches!(if_expr.else_branch(), Some(ElseBranch::If(_))));
+    }
+
+    #[test]
+    fn mod_and_use_stmts() {
+        let stmts = parse("mod a; mod b { use a::f; }")
+            .stmts()
+            .collect::<Vec<_>>();
+
+        let [Stmt::Mod(a), Stmt::Mod(b)] = stmts.as_slice() else {
+            panic!("expected two modules");
+        };
+        assert!(a.item_list().is_none());
+        assert!(a.visibility().is_none());
+
+        let Some(Stmt::Use(use_stmt)) = b.item_list().unwrap().stmts().next() else {
+            panic!("expected a use statement");
+        };
+        let segments = use_stmt
+            .segments()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(segments, ["a", "f"]);
+    }
+
+    #[test]
+    fn array_exprs() {
+        let Some(Stmt::Let(stmt)) = parse("let a: [u8; 2] = [[0; 2], [1, 2]][i];")
+            .stmts()
+            .next()
+        else {
+            panic!("expected a let statement");
+        };
+
+        let Some(Type::Array(ty)) = stmt.ty() else {
+            panic!("expected an array type");
+        };
+        assert!(matches!(ty.element_type(), Some(Type::Path(_))));
+        assert_eq!(ty.len().unwrap().syntax().to_string(), "2");
+
+        let Some(Expr::Index(index)) = stmt.initializer() else {
+            panic!("expected an index expression");
+        };
+        assert!(matches!(index.index(), Some(Expr::VariableRef(_))));
+
+        let Some(Expr::Array(array)) = index.base() else {
+            panic!("expected an array");
+        };
+        let elements = array.elements().collect::<Vec<_>>();
+        let [Expr::ArrayRepeat(repeat), Expr::Array(_)] = elements.as_slice() else {
+            panic!("expected a repeat array and an array");
+        };
+        assert_eq!(repeat.value().unwrap().syntax().to_string(), "0");
+        assert_eq!(repeat.count().unwrap().syntax().to_string(), "2");
+    }
+
+    #[test]
+    fn ref_and_optional_types() {
+        let fragment = Parser::new("&mut &[T?]?").parse_type();
+        assert_eq!(fragment.errors(), []);
+
+        let Some(Type::Ref(outer)) = fragment.node() else {
+            panic!("expected a reference type");
+        };
+        assert!(outer.mut_keyword().is_some());
+        let Some(Type::Ref(inner)) = outer.ty() else {
+            panic!("expected a reference type");
+        };
+        assert!(inner.mut_keyword().is_none());
+        let Some(Type::Optional(optional)) = inner.ty() else {
+            panic!("expected an optional type");
+        };
+        let Some(Type::Slice(slice)) = optional.ty() else {
+            panic!("expected a slice type");
+        };
+        assert!(matches!(slice.element_type(), Some(Type::Optional(_))));
+    }
+
+    #[test]
+    fn attrs() {
+        let root = parse("#![inline]\n#[test] #[deprecated(note = \"old\", 1)] fn f() {}");
+        let inner = root.attrs().collect::<Vec<_>>();
+        assert_eq!(inner.len(), 1);
+        assert!(inner[0].is_inner());
+
+        let Some(Stmt::Expr(stmt)) = root.stmts().next() else {
+            panic!("expected an expression statement");
+        };
+        let Some(Expr::Function(function)) = stmt.expr() else {
+            panic!("expected a function");
+        };
+        assert_eq!(function.name().unwrap().text(), "f");
+
+        let attrs = function.attrs().collect::<Vec<_>>();
+        let names = attrs
+            .iter()
+            .map(|attr| attr.name().unwrap().text().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["test", "deprecated"]);
+        assert!(!attrs[0].is_inner());
+        assert!(attrs[0].arg_list().is_none());
+
+        let args = attrs[1].arg_list().unwrap().args().collect::<Vec<_>>();
+        assert_eq!(args[0].name().unwrap().text(), "note");
+        assert_eq!(args[0].value().unwrap().syntax().to_string(), "\"old\"");
+        assert!(args[1].name().is_none());
+        assert_eq!(args[1].value().unwrap().syntax().to_string(), "1");
+    }
+
+    #[test]
+    fn closure_expr() {
+        let Some(Stmt::Let(stmt)) = parse("let f: fn(i32) -> i32 = |a| a;").stmts().next() else {
+            panic!("expected a let statement");
+        };
+
+        let Some(Type::Fn(ty)) = stmt.ty() else {
+            panic!("expected a function type");
+        };
+        assert_eq!(ty.params().unwrap().types().count(), 1);
+        assert!(matches!(ty.return_type(), Some(Type::Path(_))));
+
+        let Some(Expr::Closure(closure)) = stmt.initializer() else {
+            panic!("expected a closure");
+        };
+        assert_eq!(closure.param_list().unwrap().params().count(), 1);
+        assert!(closure.return_type().is_none());
+        assert!(matches!(closure.body(), Some(Expr::VariableRef(_))));
+    }
+
+    #[test]
+    fn match_expr() {
+        let Some(Stmt::Expr(stmt)) =
+            parse("match a { -1..=1 if b => 0, S { x, y: _ } | T(..) => 1 }")
+                .stmts()
+                .next()
+        else {
+            panic!("expected an expression statement");
+        };
+        let Some(Expr::Match(match_expr)) = stmt.expr() else {
+            panic!("expected a match expression");
+        };
+        assert!(matches!(match_expr.scrutinee(), Some(Expr::VariableRef(_))));
+
+        let arms = match_expr.arm_list().unwrap().arms().collect::<Vec<_>>();
+        let [first, second] = arms.as_slice() else {
+            panic!("expected two arms");
+        };
+
+        let Some(Pattern::Range(range)) = first.pattern() else {
+            panic!("expected a range pattern");
+        };
+        assert!(range.start().unwrap().is_negative());
+        assert_eq!(range.end().unwrap().token().unwrap().text(), "1");
+        assert!(range.is_inclusive());
+        assert!(first.guard().unwrap().condition().is_some());
+        assert!(matches!(first.expr(), Some(Expr::Literal(_))));
+
+        let Some(Pattern::Or(or)) = second.pattern() else {
+            panic!("expected an or pattern");
+        };
+        let patterns = or.patterns().collect::<Vec<_>>();
+        let [Pattern::Struct(s), Pattern::TupleStruct(t)] = patterns.as_slice() else {
+            panic!("expected a struct and a tuple struct pattern");
+        };
+        let fields = s.field_list().unwrap().fields().collect::<Vec<_>>();
+        assert_eq!(fields.len(), 2);
+        assert!(fields[0].pattern().is_none());
+        assert!(matches!(fields[1].pattern(), Some(Pattern::Wildcard(_))));
+        let t_patterns = t.pattern_list().unwrap().patterns().collect::<Vec<_>>();
+        assert!(matches!(t_patterns.as_slice(), [Pattern::Rest(_)]));
+    }
+
+    #[test]
+    fn enum_stmt() {
+        let stmts = parse("enum E { A(u8, [u8]), B { x: i32 }, C } E::B { x: 1, y }")
+            .stmts()
+            .collect::<Vec<_>>();
+        let [Stmt::Enum(enum_stmt), Stmt::Expr(stmt)] = stmts.as_slice() else {
+            panic!("expected an enum and an expression statement");
+        };
+
+        assert_eq!(enum_stmt.name().unwrap().text(), "E");
+        let variants = enum_stmt
+            .variant_list()
+            .unwrap()
+            .variants()
+            .collect::<Vec<_>>();
+        let [a, b, c] = variants.as_slice() else {
+            panic!("expected three variants");
+        };
+        assert_eq!(a.tuple_fields().unwrap().types().count(), 2);
+        let fields = b.record_fields().unwrap().fields().collect::<Vec<_>>();
+        assert_eq!(fields[0].name().unwrap().text(), "x");
+        assert!(matches!(fields[0].ty(), Some(Type::Path(_))));
+        assert!(c.tuple_fields().is_none() && c.record_fields().is_none());
+
+        let Some(Expr::Record(record)) = stmt.expr() else {
+            panic!("expected a record expression");
+        };
+        assert_eq!(record.segments().count(), 2);
+        let fields = record.field_list().unwrap().fields().collect::<Vec<_>>();
+        assert!(matches!(fields[0].expr(), Some(Expr::Literal(_))));
+        assert!(fields[1].expr().is_none());
+    }
+
+    #[test]
+    fn generics() {
+        let stmts = parse("fn f<T, U>(a: Option<T>) { m::g::<U>(a) }")
+            .stmts()
+            .collect::<Vec<_>>();
+        let [Stmt::Expr(stmt)] = stmts.as_slice() else {
+            panic!("expected an expression statement");
+        };
+        let Some(Expr::Function(function)) = stmt.expr() else {
+            panic!("expected a function");
+        };
+
+        assert_eq!(function.name().unwrap().text(), "f");
+        let params = function
+            .generic_param_list()
+            .unwrap()
+            .params()
+            .map(|param| param.name().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(params, ["T", "U"]);
+
+        let param = function.param_list().unwrap().params().next().unwrap();
+        let Some(Type::Path(ty)) = param.ty() else {
+            panic!("expected a path type");
+        };
+        assert_eq!(ty.name().unwrap().text(), "Option");
+        assert_eq!(ty.generic_arg_list().unwrap().types().count(), 1);
+
+        let call = function.body().unwrap().tail_expr();
+        let Some(Expr::Call(call)) = call else {
+            panic!("expected a call");
+        };
+        let Some(Expr::VariableRef(callee)) = call.callee() else {
+            panic!("expected a path");
+        };
+        assert_eq!(callee.name().unwrap().text(), "g");
+        assert_eq!(callee.generic_arg_list().unwrap().types().count(), 1);
+    }
+
+    #[test]
+    fn traits_and_impls() {
+        let stmts = parse(
+            "trait Show { fn show(self); } impl<T: Show + Eq> Show for W<T> { fn show(self) { self.get().show() } }",
+        )
+        .stmts()
+        .collect::<Vec<_>>();
+        let [Stmt::Trait(trait_stmt), Stmt::Impl(impl_stmt)] = stmts.as_slice() else {
+            panic!("expected a trait and an impl");
+        };
+
+        assert_eq!(trait_stmt.name().unwrap().text(), "Show");
+        let show = trait_stmt.item_list().unwrap().functions().next().unwrap();
+        assert!(show.body().is_none());
+
+        let param = impl_stmt
+            .generic_param_list()
+            .unwrap()
+            .params()
+            .next()
+            .unwrap();
+        let bounds = param
+            .bounds()
+            .map(|bound| bound.syntax().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(bounds, ["Show", "Eq"]);
+        assert_eq!(impl_stmt.trait_type().unwrap().syntax().to_string(), "Show");
+        assert_eq!(impl_stmt.self_type().unwrap().syntax().to_string(), "W<T>");
+
+        let show = impl_stmt.item_list().unwrap().functions().next().unwrap();
+        let Some(Expr::MethodCall(call)) = show.body().unwrap().tail_expr() else {
+            panic!("expected a method call");
+        };
+        assert_eq!(call.name().unwrap().text(), "show");
+        assert_eq!(call.arg_list().unwrap().args().count(), 0);
+    }
+}
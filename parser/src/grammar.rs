@@ -0,0 +1,197 @@
+mod expr;
+mod pattern;
+mod stmt;
+mod ty;
+
+use crate::{syntax::SyntaxKind, Parser};
+
+/// Tokens at which error recovery stops instead of swallowing them into an `Error` node.
+const RECOVERY_SET: &[SyntaxKind] = &[
+    SyntaxKind::Semicolon,
+    SyntaxKind::CloseParen,
+    SyntaxKind::CloseSquare,
+    SyntaxKind::CloseBrace,
+    SyntaxKind::FatArrow,
+    SyntaxKind::InterpolatedStringMiddle,
+    SyntaxKind::InterpolatedStringEnd,
+    SyntaxKind::LetKeyword,
+    SyntaxKind::FunctionKeyword,
+    SyntaxKind::ReturnKeyword,
+    SyntaxKind::ModKeyword,
+    SyntaxKind::UseKeyword,
+    SyntaxKind::EnumKeyword,
+    SyntaxKind::StructKeyword,
+    SyntaxKind::TraitKeyword,
+    SyntaxKind::ImplKeyword,
+    SyntaxKind::PubKeyword,
+    SyntaxKind::Pound,
+];
+
+pub(crate) fn root(p: &mut Parser) {
+    // unlike other nodes, the root owns the leading trivia
+    p.builder.start_node(SyntaxKind::Root.into());
+
+    while !p.at_end() {
+        stmt::stmt(p);
+    }
+
+    p.eat_trivia();
+    p.finish_node();
+}
+
+pub(crate) fn expr_fragment(p: &mut Parser) {
+    fragment(p, |p| {
+        expr::expr(p);
+    });
+}
+
+pub(crate) fn stmt_fragment(p: &mut Parser) {
+    fragment(p, stmt::stmt);
+}
+
+pub(crate) fn type_fragment(p: &mut Parser) {
+    fragment(p, ty::ty);
+}
+
+pub(crate) fn item_fragment(p: &mut Parser) {
+    fragment(p, stmt::lone_item);
+}
+
+/// Parses a fragment with `parse` in a `Root`, like a whole program.
+fn fragment(p: &mut Parser, parse: fn(&mut Parser)) {
+    p.builder.start_node(SyntaxKind::Root.into());
+    parse(p);
+    rest_of_input(p);
+    p.eat_trivia();
+    p.finish_node();
+}
+
+/// Reports input left after a fragment and wraps it in an `Error` node.
+fn rest_of_input(p: &mut Parser) {
+    let Some(next) = p.lexemes[p.cursor..]
+        .iter()
+        .find(|lexeme| !lexeme.kind.is_trivia())
+    else {
+        return;
+    };
+
+    // the fragment may have ended with an error about this very token
+    if p.errors.last().map(|error| error.range) != Some(next.range) {
+        p.error("expected end of input");
+    }
+    p.start_node(SyntaxKind::Error);
+    while !p.at_end() {
+        p.bump();
+    }
+    p.finish_node();
+}
+
+/// Parses a delimited, comma separated list of `item`s, such as arguments or parameters.
+fn delimited_list(
+    p: &mut Parser,
+    list: SyntaxKind,
+    close: SyntaxKind,
+    first: &[SyntaxKind],
+    item: fn(&mut Parser),
+    expected: &str,
+) {
+    p.start_node(list);
+    p.bump();
+
+    while !at_close(p, close) && !p.at_end() {
+        if p.at_set(first) {
+            item(p);
+        } else if p.at_set(RECOVERY_SET) {
+            break;
+        } else {
+            p.error_and_bump(format!("expected {expected}"));
+            continue;
+        }
+
+        if !at_close(p, close) && !p.eat(SyntaxKind::Comma) {
+            p.error("expected `,`");
+            if p.at_set(RECOVERY_SET) {
+                break;
+            }
+        }
+    }
+
+    p.expect(close);
+    p.finish_node();
+}
+
+/// Whether the next token closes a list, splitting `>>` to close nested generic lists.
+fn at_close(p: &mut Parser, close: SyntaxKind) -> bool {
+    if close == SyntaxKind::GreaterThan {
+        p.split_greater_than();
+    }
+    p.at(close)
+}
+
+/// Parses the `::` separated identifiers of a path.
+fn path(p: &mut Parser) {
+    p.expect(SyntaxKind::Identifier);
+    while p.eat(SyntaxKind::ColonColon) {
+        p.expect(SyntaxKind::Identifier);
+    }
+}
+
+/// Parses a path in an expression, where generic arguments follow a `::`, as in `f::<T>`,
+/// since a `<` right after an identifier is a comparison.
+fn expr_path(p: &mut Parser) {
+    p.expect(SyntaxKind::Identifier);
+    while p.eat(SyntaxKind::ColonColon) {
+        if p.at(SyntaxKind::LessThan) {
+            ty::generic_arg_list(p);
+        } else {
+            p.expect(SyntaxKind::Identifier);
+        }
+    }
+}
+
+/// Parses `<T, U: Show>`, the generic parameters of a function, an enum or an impl.
+fn generic_param_list(p: &mut Parser) {
+    delimited_list(
+        p,
+        SyntaxKind::GenericParamList,
+        SyntaxKind::GreaterThan,
+        &[SyntaxKind::Identifier],
+        generic_param,
+        "generic parameter",
+    );
+}
+
+fn generic_param(p: &mut Parser) {
+    p.start_node(SyntaxKind::GenericParam);
+    p.bump();
+    if p.at(SyntaxKind::Colon) {
+        type_bound_list(p);
+    }
+    p.finish_node();
+}
+
+/// Parses `: A + B`, the traits that the argument of a generic parameter must implement.
+fn type_bound_list(p: &mut Parser) {
+    p.start_node(SyntaxKind::TypeBoundList);
+    p.bump();
+
+    loop {
+        if !p.at(SyntaxKind::Identifier) {
+            p.error("expected trait");
+            break;
+        }
+        ty::path_type(p);
+
+        if !p.eat(SyntaxKind::Plus) {
+            break;
+        }
+    }
+
+    p.finish_node();
+}
+
+#[cfg(test)]
+fn check(input: &str, expected: &str) {
+    let green_tree = Parser::new(input).parse();
+    assert_eq!(green_tree.debug_tree(), expected);
+}
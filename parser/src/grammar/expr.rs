@@ -0,0 +1,1709 @@
+use rowan::Checkpoint;
+
+use super::{
+    delimited_list, expr_path, generic_param_list,
+    pattern::{self, PATTERN_FIRST},
+    stmt, ty, RECOVERY_SET,
+};
+use crate::{syntax::SyntaxKind, Parser};
+
+/// Tokens that can start an expression.
+pub(super) const EXPR_FIRST: &[SyntaxKind] = &[
+    SyntaxKind::Number,
+    SyntaxKind::String,
+    SyntaxKind::Character,
+    SyntaxKind::InterpolatedStringPlain,
+    SyntaxKind::InterpolatedStringStart,
+    SyntaxKind::Bool,
+    SyntaxKind::Identifier,
+    SyntaxKind::OpenParen,
+    SyntaxKind::OpenSquare,
+    SyntaxKind::OpenBrace,
+    SyntaxKind::Minus,
+    SyntaxKind::Bang,
+    SyntaxKind::Tilde,
+    SyntaxKind::Pipe,
+    SyntaxKind::LogicalOr,
+    SyntaxKind::IfKeyword,
+    SyntaxKind::MatchKeyword,
+    SyntaxKind::FunctionKeyword,
+];
+
+const PREFIX_BINDING_POWER: u8 = 21;
+/// Binding power of calls, method calls and indexing.
+const POSTFIX_BINDING_POWER: u8 = 23;
+
+/// Parses an expression, returning the kind of the outermost node
+/// or `None` if no expression could be parsed.
+pub(super) fn expr(p: &mut Parser) -> Option<SyntaxKind> {
+    // record expressions are allowed again inside delimiters, e.g. `if (S { a }) == b {}`
+    let no_record_exprs = std::mem::replace(&mut p.no_record_exprs, false);
+    let kind = expr_bp(p, 0);
+    p.no_record_exprs = no_record_exprs;
+    kind
+}
+
+/// Parses the condition of an `if` or the scrutinee of a `match`, which are followed by a
+/// block, so `a { .. }` can't be a record expression.
+fn condition(p: &mut Parser) {
+    let no_record_exprs = std::mem::replace(&mut p.no_record_exprs, true);
+    expr_bp(p, 0);
+    p.no_record_exprs = no_record_exprs;
+}
+
+/// Whether the expression ends with a block and can be used as a statement without a `;`.
+pub(super) fn is_block_like(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::BlockExpr
+            | SyntaxKind::IfExpr
+            | SyntaxKind::MatchExpr
+            | SyntaxKind::FunctionExpr
+    )
+}
+
+/// Binding powers follow Rust's precedence, e.g. bitwise operators bind
+/// tighter than comparisons and shifts sit between `&` and `+`.
+fn infix_binding_power(kind: SyntaxKind) -> Option<(u8, u8)> {
+    let bp = match kind {
+        // assignments are right associative
+        SyntaxKind::SingleEqual
+        | SyntaxKind::PlusEqual
+        | SyntaxKind::MinusEqual
+        | SyntaxKind::StarEqual
+        | SyntaxKind::SlashEqual
+        | SyntaxKind::ModuloEqual
+        | SyntaxKind::AmpersandEqual
+        | SyntaxKind::PipeEqual
+        | SyntaxKind::CaretEqual
+        | SyntaxKind::ShiftLeftEqual
+        | SyntaxKind::ShiftRightEqual => (2, 1),
+        SyntaxKind::LogicalOr => (3, 4),
+        SyntaxKind::LogicalAnd => (5, 6),
+        SyntaxKind::Equal
+        | SyntaxKind::NotEqual
+        | SyntaxKind::LessThan
+        | SyntaxKind::GreaterThan
+        | SyntaxKind::LessThanEqual
+        | SyntaxKind::GreaterThanEqual => (7, 8),
+        SyntaxKind::Pipe => (9, 10),
+        SyntaxKind::Caret => (11, 12),
+        SyntaxKind::Ampersand => (13, 14),
+        SyntaxKind::ShiftLeft | SyntaxKind::ShiftRight => (15, 16),
+        SyntaxKind::Plus | SyntaxKind::Minus => (17, 18),
+        SyntaxKind::Star | SyntaxKind::Slash | SyntaxKind::Modulo => (19, 20),
+        _ => return None,
+    };
+
+    Some(bp)
+}
+
+fn expr_bp(p: &mut Parser, min_bp: u8) -> Option<SyntaxKind> {
+    let checkpoint = p.checkpoint();
+    let mut kind = lhs(p)?;
+
+    loop {
+        if p.at(SyntaxKind::OpenParen) && POSTFIX_BINDING_POWER >= min_bp {
+            p.start_node_at(checkpoint, SyntaxKind::CallExpr);
+            arg_list(p);
+            p.finish_node();
+            kind = SyntaxKind::CallExpr;
+            continue;
+        }
+
+        if p.at(SyntaxKind::OpenSquare) && POSTFIX_BINDING_POWER >= min_bp {
+            p.start_node_at(checkpoint, SyntaxKind::IndexExpr);
+            p.bump();
+            expr(p);
+            p.expect(SyntaxKind::CloseSquare);
+            p.finish_node();
+            kind = SyntaxKind::IndexExpr;
+            continue;
+        }
+
+        if p.at(SyntaxKind::Dot) && POSTFIX_BINDING_POWER >= min_bp {
+            let is_field = match p.nth(1) {
+                Some(SyntaxKind::Number) => true,
+                Some(SyntaxKind::Identifier) => p.nth(2) != Some(SyntaxKind::OpenParen),
+                _ => false,
+            };
+            kind = if is_field {
+                field_expr(p, checkpoint)
+            } else {
+                method_call_expr(p, checkpoint)
+            };
+            continue;
+        }
+
+        let Some((left_bp, right_bp)) = p.peek().and_then(infix_binding_power) else {
+            break;
+        };
+
+        if left_bp < min_bp {
+            break;
+        }
+
+        p.start_node_at(checkpoint, SyntaxKind::BinaryExpr);
+        p.bump();
+        expr_bp(p, right_bp);
+        p.finish_node();
+        kind = SyntaxKind::BinaryExpr;
+    }
+
+    Some(kind)
+}
+
+/// Parses `.0` or `.name`, the access to a field of the tuple or struct starting at
+/// `checkpoint`.
+fn field_expr(p: &mut Parser, checkpoint: Checkpoint) -> SyntaxKind {
+    p.start_node_at(checkpoint, SyntaxKind::FieldExpr);
+    p.bump();
+    // in `t.0.1`, `0.1` is lexed as a float
+    p.split_float();
+    p.bump();
+    p.finish_node();
+    SyntaxKind::FieldExpr
+}
+
+/// Parses `.method(args)` after the receiver starting at `checkpoint`.
+fn method_call_expr(p: &mut Parser, checkpoint: Checkpoint) -> SyntaxKind {
+    p.start_node_at(checkpoint, SyntaxKind::MethodCallExpr);
+    p.bump();
+    p.expect(SyntaxKind::Identifier);
+
+    if p.at(SyntaxKind::OpenParen) {
+        arg_list(p);
+    } else {
+        p.error("expected `(`");
+    }
+
+    p.finish_node();
+    SyntaxKind::MethodCallExpr
+}
+
+fn lhs(p: &mut Parser) -> Option<SyntaxKind> {
+    let kind = match p.peek() {
+        Some(
+            SyntaxKind::Number | SyntaxKind::String | SyntaxKind::Character | SyntaxKind::Bool,
+        ) => literal(p),
+        Some(SyntaxKind::InterpolatedStringPlain | SyntaxKind::InterpolatedStringStart) => {
+            interpolated_string(p)
+        }
+        Some(SyntaxKind::Identifier) => variable_ref_or_record_expr(p),
+        Some(SyntaxKind::OpenParen) => paren_or_tuple_expr(p),
+        Some(SyntaxKind::OpenSquare) => array_expr(p),
+        Some(SyntaxKind::Minus | SyntaxKind::Bang | SyntaxKind::Tilde) => prefix_expr(p),
+        Some(SyntaxKind::OpenBrace) => block_expr(p),
+        Some(SyntaxKind::IfKeyword) => if_expr(p),
+        Some(SyntaxKind::MatchKeyword) => match_expr(p),
+        Some(SyntaxKind::Pipe | SyntaxKind::LogicalOr) => closure_expr(p),
+        Some(SyntaxKind::FunctionKeyword) => {
+            let checkpoint = p.checkpoint();
+            function_expr(p, checkpoint)
+        }
+        _ => {
+            if p.at_end() || p.at_set(RECOVERY_SET) {
+                p.error("expected expression");
+            } else {
+                p.error_and_bump("expected expression");
+            }
+            return None;
+        }
+    };
+
+    Some(kind)
+}
+
+pub(super) fn literal(p: &mut Parser) -> SyntaxKind {
+    p.start_node(SyntaxKind::Literal);
+    p.bump();
+    p.finish_node();
+    SyntaxKind::Literal
+}
+
+/// Parses `f"text{expr}text"`, with the embedded expressions between the pieces of text.
+fn interpolated_string(p: &mut Parser) -> SyntaxKind {
+    const CLOSE: &[SyntaxKind] = &[
+        SyntaxKind::InterpolatedStringMiddle,
+        SyntaxKind::InterpolatedStringEnd,
+    ];
+
+    p.start_node(SyntaxKind::InterpolatedString);
+    if !p.eat(SyntaxKind::InterpolatedStringPlain) {
+        p.bump();
+
+        loop {
+            expr(p);
+
+            // skip to the end of the interpolation, unless the string is unterminated
+            if !p.at_set(CLOSE) {
+                p.error("expected `}`");
+                if !p.at_end() {
+                    p.start_node(SyntaxKind::Error);
+                    while !p.at_set(CLOSE) && !p.at_end() {
+                        p.bump();
+                    }
+                    p.finish_node();
+                }
+            }
+
+            if !p.eat(SyntaxKind::InterpolatedStringMiddle) {
+                p.eat(SyntaxKind::InterpolatedStringEnd);
+                break;
+            }
+        }
+    }
+    p.finish_node();
+    SyntaxKind::InterpolatedString
+}
+
+/// Parses a path, or `Path { a: 1, b }` to create an enum variant with named fields.
+fn variable_ref_or_record_expr(p: &mut Parser) -> SyntaxKind {
+    let checkpoint = p.checkpoint();
+    expr_path(p);
+
+    let kind = if p.at(SyntaxKind::OpenBrace) && !p.no_record_exprs {
+        delimited_list(
+            p,
+            SyntaxKind::RecordExprFieldList,
+            SyntaxKind::CloseBrace,
+            &[SyntaxKind::Identifier],
+            record_expr_field,
+            "field",
+        );
+        SyntaxKind::RecordExpr
+    } else {
+        SyntaxKind::VariableRef
+    };
+
+    p.start_node_at(checkpoint, kind);
+    p.finish_node();
+    kind
+}
+
+/// Parses `field: value`, or the `field` shorthand for a variable of the same name.
+fn record_expr_field(p: &mut Parser) {
+    p.start_node(SyntaxKind::RecordExprField);
+    p.bump();
+    if p.eat(SyntaxKind::Colon) {
+        expr(p);
+    }
+    p.finish_node();
+}
+
+/// Parses `(a)`, or a tuple: `()`, `(a,)` or `(a, b)`.
+fn paren_or_tuple_expr(p: &mut Parser) -> SyntaxKind {
+    let checkpoint = p.checkpoint();
+    p.bump();
+
+    let mut kind = SyntaxKind::TupleExpr;
+    if !p.at(SyntaxKind::CloseParen) {
+        expr(p);
+
+        if p.at(SyntaxKind::Comma) {
+            while p.eat(SyntaxKind::Comma) {
+                if p.at(SyntaxKind::CloseParen) {
+                    break;
+                }
+                expr(p);
+            }
+        } else {
+            kind = SyntaxKind::ParenExpr;
+        }
+    }
+
+    p.expect(SyntaxKind::CloseParen);
+    p.start_node_at(checkpoint, kind);
+    p.finish_node();
+    kind
+}
+
+/// Parses `[a, b, c]`, or `[value; count]` for repeat arrays.
+fn array_expr(p: &mut Parser) -> SyntaxKind {
+    let checkpoint = p.checkpoint();
+    p.bump();
+
+    if p.at(SyntaxKind::CloseSquare) {
+        p.start_node_at(checkpoint, SyntaxKind::ArrayExpr);
+        p.bump();
+        p.finish_node();
+        return SyntaxKind::ArrayExpr;
+    }
+
+    expr(p);
+
+    let kind = if p.eat(SyntaxKind::Semicolon) {
+        expr(p);
+        SyntaxKind::ArrayRepeatExpr
+    } else {
+        while p.eat(SyntaxKind::Comma) {
+            if p.at(SyntaxKind::CloseSquare) {
+                break;
+            }
+            expr(p);
+        }
+        SyntaxKind::ArrayExpr
+    };
+
+    p.expect(SyntaxKind::CloseSquare);
+    p.start_node_at(checkpoint, kind);
+    p.finish_node();
+    kind
+}
+
+fn prefix_expr(p: &mut Parser) -> SyntaxKind {
+    p.start_node(SyntaxKind::PrefixExpr);
+    p.bump();
+    expr_bp(p, PREFIX_BINDING_POWER);
+    p.finish_node();
+    SyntaxKind::PrefixExpr
+}
+
+pub(super) fn block_expr(p: &mut Parser) -> SyntaxKind {
+    p.start_node(SyntaxKind::BlockExpr);
+    p.expect(SyntaxKind::OpenBrace);
+
+    while !p.at(SyntaxKind::CloseBrace) && !p.at_end() {
+        stmt::stmt(p);
+    }
+
+    p.expect(SyntaxKind::CloseBrace);
+    p.finish_node();
+    SyntaxKind::BlockExpr
+}
+
+fn if_expr(p: &mut Parser) -> SyntaxKind {
+    p.start_node(SyntaxKind::IfExpr);
+    p.bump();
+    condition(p);
+    block_expr(p);
+
+    if p.eat(SyntaxKind::ElseKeyword) {
+        if p.at(SyntaxKind::IfKeyword) {
+            if_expr(p);
+        } else {
+            block_expr(p);
+        }
+    }
+
+    p.finish_node();
+    SyntaxKind::IfExpr
+}
+
+/// Parses `match scrutinee { pattern if guard => expr, .. }`. Like in Rust, the `,`
+/// after an arm can be left out when its expression ends with a block.
+fn match_expr(p: &mut Parser) -> SyntaxKind {
+    p.start_node(SyntaxKind::MatchExpr);
+    p.bump();
+    condition(p);
+
+    if p.at(SyntaxKind::OpenBrace) {
+        match_arm_list(p);
+    } else {
+        p.error("expected `{`");
+    }
+
+    p.finish_node();
+    SyntaxKind::MatchExpr
+}
+
+fn match_arm_list(p: &mut Parser) {
+    p.start_node(SyntaxKind::MatchArmList);
+    p.bump();
+
+    while !p.at(SyntaxKind::CloseBrace) && !p.at_end() {
+        if p.at_set(PATTERN_FIRST) || p.at(SyntaxKind::FatArrow) {
+            match_arm(p);
+        } else if p.at_set(RECOVERY_SET) {
+            break;
+        } else {
+            p.error_and_bump("expected pattern");
+        }
+    }
+
+    p.expect(SyntaxKind::CloseBrace);
+    p.finish_node();
+}
+
+fn match_arm(p: &mut Parser) {
+    p.start_node(SyntaxKind::MatchArm);
+    pattern::pattern(p);
+
+    if p.at(SyntaxKind::IfKeyword) {
+        p.start_node(SyntaxKind::MatchGuard);
+        p.bump();
+        expr(p);
+        p.finish_node();
+    }
+
+    p.expect(SyntaxKind::FatArrow);
+    // a block ends the arm, so that `{ a } (b) => c` isn't parsed as a call
+    let kind = if p.at(SyntaxKind::OpenBrace) {
+        Some(block_expr(p))
+    } else {
+        expr(p)
+    };
+
+    if !p.eat(SyntaxKind::Comma)
+        && !p.at(SyntaxKind::CloseBrace)
+        && !kind.is_some_and(is_block_like)
+    {
+        p.error("expected `,`");
+    }
+
+    p.finish_node();
+}
+
+/// Parses a function starting at `checkpoint`, so that a preceding visibility is included.
+/// Functions used as values can be anonymous, e.g. `fn(a) { a }`.
+fn function_expr(p: &mut Parser, checkpoint: Checkpoint) -> SyntaxKind {
+    function(p, checkpoint, false)
+}
+
+/// Parses a function, whose body can be replaced by the `;` ending the statement when
+/// `bodyless` is set, as in the required functions of traits.
+pub(super) fn function(p: &mut Parser, checkpoint: Checkpoint, bodyless: bool) -> SyntaxKind {
+    p.start_node_at(checkpoint, SyntaxKind::FunctionExpr);
+    p.bump();
+    if p.eat(SyntaxKind::Identifier) && p.at(SyntaxKind::LessThan) {
+        generic_param_list(p);
+    }
+
+    if p.at(SyntaxKind::OpenParen) {
+        delimited_list(
+            p,
+            SyntaxKind::ParamList,
+            SyntaxKind::CloseParen,
+            PARAM_FIRST,
+            param,
+            "parameter",
+        );
+    } else {
+        p.error("expected `(`");
+    }
+
+    if p.eat(SyntaxKind::RightArrow) {
+        ty::ty(p);
+    }
+
+    if !p.at(SyntaxKind::Semicolon) {
+        block_expr(p);
+    } else if !bodyless {
+        // the `;` is left to end the statement
+        p.error("expected `{`");
+    }
+    p.finish_node();
+    SyntaxKind::FunctionExpr
+}
+
+/// Parses `|a, b| body`, where `||` is an empty parameter list. Like in Rust,
+/// the body must be a block when the return type is given.
+fn closure_expr(p: &mut Parser) -> SyntaxKind {
+    p.start_node(SyntaxKind::ClosureExpr);
+
+    if p.at(SyntaxKind::LogicalOr) {
+        p.start_node(SyntaxKind::ParamList);
+        p.bump();
+        p.finish_node();
+    } else {
+        delimited_list(
+            p,
+            SyntaxKind::ParamList,
+            SyntaxKind::Pipe,
+            PARAM_FIRST,
+            param,
+            "parameter",
+        );
+    }
+
+    if p.eat(SyntaxKind::RightArrow) {
+        ty::ty(p);
+        block_expr(p);
+    } else {
+        expr(p);
+    }
+
+    p.finish_node();
+    SyntaxKind::ClosureExpr
+}
+
+/// Tokens that can start a parameter.
+const PARAM_FIRST: &[SyntaxKind] = &[SyntaxKind::Identifier, SyntaxKind::MutKeyword];
+
+/// Parses `a: T`, or `mut a: T` for a parameter the function can assign to.
+fn param(p: &mut Parser) {
+    p.start_node(SyntaxKind::Param);
+    if p.eat(SyntaxKind::MutKeyword) {
+        p.expect(SyntaxKind::Identifier);
+    } else {
+        p.bump();
+    }
+
+    if p.eat(SyntaxKind::Colon) {
+        ty::ty(p);
+    }
+
+    p.finish_node();
+}
+
+fn arg_list(p: &mut Parser) {
+    delimited_list(
+        p,
+        SyntaxKind::ArgList,
+        SyntaxKind::CloseParen,
+        EXPR_FIRST,
+        |p| {
+            expr(p);
+        },
+        "argument",
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grammar::check;
+
+    #[test]
+    fn parse_number() {
+        check(
+            "123",
+            r#"Root@0..3
+  ExprStmt@0..3
+    Literal@0..3
+      Number@0..3 "123"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_binary_expr_precedence() {
+        check(
+            "1+2*3",
+            r#"Root@0..5
+  ExprStmt@0..5
+    BinaryExpr@0..5
+      Literal@0..1
+        Number@0..1 "1"
+      Plus@1..2 "+"
+      BinaryExpr@2..5
+        Literal@2..3
+          Number@2..3 "2"
+        Star@3..4 "*"
+        Literal@4..5
+          Number@4..5 "3"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_left_associative_binary_expr() {
+        check(
+            "a-b-c",
+            r#"Root@0..5
+  ExprStmt@0..5
+    BinaryExpr@0..5
+      BinaryExpr@0..3
+        VariableRef@0..1
+          Identifier@0..1 "a"
+        Minus@1..2 "-"
+        VariableRef@2..3
+          Identifier@2..3 "b"
+      Minus@3..4 "-"
+      VariableRef@4..5
+        Identifier@4..5 "c"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_logical_expr_precedence() {
+        check(
+            "a||b&&c==d",
+            r#"Root@0..10
+  ExprStmt@0..10
+    BinaryExpr@0..10
+      VariableRef@0..1
+        Identifier@0..1 "a"
+      LogicalOr@1..3 "||"
+      BinaryExpr@3..10
+        VariableRef@3..4
+          Identifier@3..4 "b"
+        LogicalAnd@4..6 "&&"
+        BinaryExpr@6..10
+          VariableRef@6..7
+            Identifier@6..7 "c"
+          Equal@7..9 "=="
+          VariableRef@9..10
+            Identifier@9..10 "d"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_bitwise_expr_precedence() {
+        check(
+            "a==b|c^d&e<<1+2",
+            r#"Root@0..15
+  ExprStmt@0..15
+    BinaryExpr@0..15
+      VariableRef@0..1
+        Identifier@0..1 "a"
+      Equal@1..3 "=="
+      BinaryExpr@3..15
+        VariableRef@3..4
+          Identifier@3..4 "b"
+        Pipe@4..5 "|"
+        BinaryExpr@5..15
+          VariableRef@5..6
+            Identifier@5..6 "c"
+          Caret@6..7 "^"
+          BinaryExpr@7..15
+            VariableRef@7..8
+              Identifier@7..8 "d"
+            Ampersand@8..9 "&"
+            BinaryExpr@9..15
+              VariableRef@9..10
+                Identifier@9..10 "e"
+              ShiftLeft@10..12 "<<"
+              BinaryExpr@12..15
+                Literal@12..13
+                  Number@12..13 "1"
+                Plus@13..14 "+"
+                Literal@14..15
+                  Number@14..15 "2"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_bitwise_not() {
+        check(
+            "~a&b",
+            r#"Root@0..4
+  ExprStmt@0..4
+    BinaryExpr@0..4
+      PrefixExpr@0..2
+        Tilde@0..1 "~"
+        VariableRef@1..2
+          Identifier@1..2 "a"
+      Ampersand@2..3 "&"
+      VariableRef@3..4
+        Identifier@3..4 "b"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_prefix_expr() {
+        check(
+            "-a*b",
+            r#"Root@0..4
+  ExprStmt@0..4
+    BinaryExpr@0..4
+      PrefixExpr@0..2
+        Minus@0..1 "-"
+        VariableRef@1..2
+          Identifier@1..2 "a"
+      Star@2..3 "*"
+      VariableRef@3..4
+        Identifier@3..4 "b"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_path() {
+        check(
+            "foo::bar(1)",
+            r#"Root@0..11
+  ExprStmt@0..11
+    CallExpr@0..11
+      VariableRef@0..8
+        Identifier@0..3 "foo"
+        ColonColon@3..5 "::"
+        Identifier@5..8 "bar"
+      ArgList@8..11
+        OpenParen@8..9 "("
+        Literal@9..10
+          Number@9..10 "1"
+        CloseParen@10..11 ")"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_generic_function_and_turbofish() {
+        check(
+            "fn id<T>(x: T) -> T { f::<T>(x) < Option::<T>::None }",
+            r#"Root@0..53
+  ExprStmt@0..53
+    FunctionExpr@0..53
+      FunctionKeyword@0..2 "fn"
+      Whitespace@2..3 " "
+      Identifier@3..5 "id"
+      GenericParamList@5..8
+        LessThan@5..6 "<"
+        GenericParam@6..7
+          Identifier@6..7 "T"
+        GreaterThan@7..8 ">"
+      ParamList@8..14
+        OpenParen@8..9 "("
+        Param@9..13
+          Identifier@9..10 "x"
+          Colon@10..11 ":"
+          Whitespace@11..12 " "
+          PathType@12..13
+            Identifier@12..13 "T"
+        CloseParen@13..14 ")"
+      Whitespace@14..15 " "
+      RightArrow@15..17 "->"
+      Whitespace@17..18 " "
+      PathType@18..19
+        Identifier@18..19 "T"
+      Whitespace@19..20 " "
+      BlockExpr@20..53
+        OpenBrace@20..21 "{"
+        Whitespace@21..22 " "
+        ExprStmt@22..51
+          BinaryExpr@22..51
+            CallExpr@22..31
+              VariableRef@22..28
+                Identifier@22..23 "f"
+                ColonColon@23..25 "::"
+                GenericArgList@25..28
+                  LessThan@25..26 "<"
+                  PathType@26..27
+                    Identifier@26..27 "T"
+                  GreaterThan@27..28 ">"
+              ArgList@28..31
+                OpenParen@28..29 "("
+                VariableRef@29..30
+                  Identifier@29..30 "x"
+                CloseParen@30..31 ")"
+            Whitespace@31..32 " "
+            LessThan@32..33 "<"
+            Whitespace@33..34 " "
+            VariableRef@34..51
+              Identifier@34..40 "Option"
+              ColonColon@40..42 "::"
+              GenericArgList@42..45
+                LessThan@42..43 "<"
+                PathType@43..44
+                  Identifier@43..44 "T"
+                GreaterThan@44..45 ">"
+              ColonColon@45..47 "::"
+              Identifier@47..51 "None"
+        Whitespace@51..52 " "
+        CloseBrace@52..53 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_paren_expr() {
+        check(
+            "(1+2)*3",
+            r#"Root@0..7
+  ExprStmt@0..7
+    BinaryExpr@0..7
+      ParenExpr@0..5
+        OpenParen@0..1 "("
+        BinaryExpr@1..4
+          Literal@1..2
+            Number@1..2 "1"
+          Plus@2..3 "+"
+          Literal@3..4
+            Number@3..4 "2"
+        CloseParen@4..5 ")"
+      Star@5..6 "*"
+      Literal@6..7
+        Number@6..7 "3"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_call_expr() {
+        check(
+            "-f(a, 1)",
+            r#"Root@0..8
+  ExprStmt@0..8
+    PrefixExpr@0..8
+      Minus@0..1 "-"
+      CallExpr@1..8
+        VariableRef@1..2
+          Identifier@1..2 "f"
+        ArgList@2..8
+          OpenParen@2..3 "("
+          VariableRef@3..4
+            Identifier@3..4 "a"
+          Comma@4..5 ","
+          Whitespace@5..6 " "
+          Literal@6..7
+            Number@6..7 "1"
+          CloseParen@7..8 ")"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_array_expr() {
+        check(
+            "[1, a, ]",
+            r#"Root@0..8
+  ExprStmt@0..8
+    ArrayExpr@0..8
+      OpenSquare@0..1 "["
+      Literal@1..2
+        Number@1..2 "1"
+      Comma@2..3 ","
+      Whitespace@3..4 " "
+      VariableRef@4..5
+        Identifier@4..5 "a"
+      Comma@5..6 ","
+      Whitespace@6..7 " "
+      CloseSquare@7..8 "]"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_array_repeat_expr() {
+        check(
+            "[0; n]",
+            r#"Root@0..6
+  ExprStmt@0..6
+    ArrayRepeatExpr@0..6
+      OpenSquare@0..1 "["
+      Literal@1..2
+        Number@1..2 "0"
+      Semicolon@2..3 ";"
+      Whitespace@3..4 " "
+      VariableRef@4..5
+        Identifier@4..5 "n"
+      CloseSquare@5..6 "]"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_index_expr() {
+        check(
+            "a[i][j](x)",
+            r#"Root@0..10
+  ExprStmt@0..10
+    CallExpr@0..10
+      IndexExpr@0..7
+        IndexExpr@0..4
+          VariableRef@0..1
+            Identifier@0..1 "a"
+          OpenSquare@1..2 "["
+          VariableRef@2..3
+            Identifier@2..3 "i"
+          CloseSquare@3..4 "]"
+        OpenSquare@4..5 "["
+        VariableRef@5..6
+          Identifier@5..6 "j"
+        CloseSquare@6..7 "]"
+      ArgList@7..10
+        OpenParen@7..8 "("
+        VariableRef@8..9
+          Identifier@8..9 "x"
+        CloseParen@9..10 ")"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_assignment() {
+        check(
+            "a[0] = b = c || d",
+            r#"Root@0..17
+  ExprStmt@0..17
+    BinaryExpr@0..17
+      IndexExpr@0..4
+        VariableRef@0..1
+          Identifier@0..1 "a"
+        OpenSquare@1..2 "["
+        Literal@2..3
+          Number@2..3 "0"
+        CloseSquare@3..4 "]"
+      Whitespace@4..5 " "
+      SingleEqual@5..6 "="
+      Whitespace@6..7 " "
+      BinaryExpr@7..17
+        VariableRef@7..8
+          Identifier@7..8 "b"
+        Whitespace@8..9 " "
+        SingleEqual@9..10 "="
+        Whitespace@10..11 " "
+        BinaryExpr@11..17
+          VariableRef@11..12
+            Identifier@11..12 "c"
+          Whitespace@12..13 " "
+          LogicalOr@13..15 "||"
+          Whitespace@15..16 " "
+          VariableRef@16..17
+            Identifier@16..17 "d"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_unclosed_array_expr() {
+        check(
+            "[1, 2",
+            r#"Root@0..5
+  ExprStmt@0..5
+    ArrayExpr@0..5
+      OpenSquare@0..1 "["
+      Literal@1..2
+        Number@1..2 "1"
+      Comma@2..3 ","
+      Whitespace@3..4 " "
+      Literal@4..5
+        Number@4..5 "2"
+error at 5..5: expected `]`, found end of input
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_closure_expr() {
+        check(
+            "|a, b: i32| a + b",
+            r#"Root@0..17
+  ExprStmt@0..17
+    ClosureExpr@0..17
+      ParamList@0..11
+        Pipe@0..1 "|"
+        Param@1..2
+          Identifier@1..2 "a"
+        Comma@2..3 ","
+        Whitespace@3..4 " "
+        Param@4..10
+          Identifier@4..5 "b"
+          Colon@5..6 ":"
+          Whitespace@6..7 " "
+          PathType@7..10
+            Identifier@7..10 "i32"
+        Pipe@10..11 "|"
+      Whitespace@11..12 " "
+      BinaryExpr@12..17
+        VariableRef@12..13
+          Identifier@12..13 "a"
+        Whitespace@13..14 " "
+        Plus@14..15 "+"
+        Whitespace@15..16 " "
+        VariableRef@16..17
+          Identifier@16..17 "b"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_closure_without_params() {
+        check(
+            "f(|| 1, |x| -> i32 { x })",
+            r#"Root@0..25
+  ExprStmt@0..25
+    CallExpr@0..25
+      VariableRef@0..1
+        Identifier@0..1 "f"
+      ArgList@1..25
+        OpenParen@1..2 "("
+        ClosureExpr@2..6
+          ParamList@2..4
+            LogicalOr@2..4 "||"
+          Whitespace@4..5 " "
+          Literal@5..6
+            Number@5..6 "1"
+        Comma@6..7 ","
+        Whitespace@7..8 " "
+        ClosureExpr@8..24
+          ParamList@8..11
+            Pipe@8..9 "|"
+            Param@9..10
+              Identifier@9..10 "x"
+            Pipe@10..11 "|"
+          Whitespace@11..12 " "
+          RightArrow@12..14 "->"
+          Whitespace@14..15 " "
+          PathType@15..18
+            Identifier@15..18 "i32"
+          Whitespace@18..19 " "
+          BlockExpr@19..24
+            OpenBrace@19..20 "{"
+            Whitespace@20..21 " "
+            ExprStmt@21..22
+              VariableRef@21..22
+                Identifier@21..22 "x"
+            Whitespace@22..23 " "
+            CloseBrace@23..24 "}"
+        CloseParen@24..25 ")"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_anonymous_function_expr() {
+        check(
+            "let f = fn(a) { a };",
+            r#"Root@0..20
+  LetStmt@0..20
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..5 "f"
+    Whitespace@5..6 " "
+    SingleEqual@6..7 "="
+    Whitespace@7..8 " "
+    FunctionExpr@8..19
+      FunctionKeyword@8..10 "fn"
+      ParamList@10..13
+        OpenParen@10..11 "("
+        Param@11..12
+          Identifier@11..12 "a"
+        CloseParen@12..13 ")"
+      Whitespace@13..14 " "
+      BlockExpr@14..19
+        OpenBrace@14..15 "{"
+        Whitespace@15..16 " "
+        ExprStmt@16..17
+          VariableRef@16..17
+            Identifier@16..17 "a"
+        Whitespace@17..18 " "
+        CloseBrace@18..19 "}"
+    Semicolon@19..20 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_if_else_expr() {
+        check(
+            "if a {} else if b {} else {}",
+            r#"Root@0..28
+  ExprStmt@0..28
+    IfExpr@0..28
+      IfKeyword@0..2 "if"
+      Whitespace@2..3 " "
+      VariableRef@3..4
+        Identifier@3..4 "a"
+      Whitespace@4..5 " "
+      BlockExpr@5..7
+        OpenBrace@5..6 "{"
+        CloseBrace@6..7 "}"
+      Whitespace@7..8 " "
+      ElseKeyword@8..12 "else"
+      Whitespace@12..13 " "
+      IfExpr@13..28
+        IfKeyword@13..15 "if"
+        Whitespace@15..16 " "
+        VariableRef@16..17
+          Identifier@16..17 "b"
+        Whitespace@17..18 " "
+        BlockExpr@18..20
+          OpenBrace@18..19 "{"
+          CloseBrace@19..20 "}"
+        Whitespace@20..21 " "
+        ElseKeyword@21..25 "else"
+        Whitespace@25..26 " "
+        BlockExpr@26..28
+          OpenBrace@26..27 "{"
+          CloseBrace@27..28 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_record_expr() {
+        check(
+            "Shape::Rect { w: 1.0, h }",
+            r#"Root@0..25
+  ExprStmt@0..25
+    RecordExpr@0..25
+      Identifier@0..5 "Shape"
+      ColonColon@5..7 "::"
+      Identifier@7..11 "Rect"
+      Whitespace@11..12 " "
+      RecordExprFieldList@12..25
+        OpenBrace@12..13 "{"
+        Whitespace@13..14 " "
+        RecordExprField@14..20
+          Identifier@14..15 "w"
+          Colon@15..16 ":"
+          Whitespace@16..17 " "
+          Literal@17..20
+            Number@17..20 "1.0"
+        Comma@20..21 ","
+        Whitespace@21..22 " "
+        RecordExprField@22..23
+          Identifier@22..23 "h"
+        Whitespace@23..24 " "
+        CloseBrace@24..25 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_path_before_block_in_condition() {
+        check(
+            "if a { S { b } } else { (S {}) }",
+            r#"Root@0..32
+  ExprStmt@0..32
+    IfExpr@0..32
+      IfKeyword@0..2 "if"
+      Whitespace@2..3 " "
+      VariableRef@3..4
+        Identifier@3..4 "a"
+      Whitespace@4..5 " "
+      BlockExpr@5..16
+        OpenBrace@5..6 "{"
+        Whitespace@6..7 " "
+        ExprStmt@7..14
+          RecordExpr@7..14
+            Identifier@7..8 "S"
+            Whitespace@8..9 " "
+            RecordExprFieldList@9..14
+              OpenBrace@9..10 "{"
+              Whitespace@10..11 " "
+              RecordExprField@11..12
+                Identifier@11..12 "b"
+              Whitespace@12..13 " "
+              CloseBrace@13..14 "}"
+        Whitespace@14..15 " "
+        CloseBrace@15..16 "}"
+      Whitespace@16..17 " "
+      ElseKeyword@17..21 "else"
+      Whitespace@21..22 " "
+      BlockExpr@22..32
+        OpenBrace@22..23 "{"
+        Whitespace@23..24 " "
+        ExprStmt@24..30
+          ParenExpr@24..30
+            OpenParen@24..25 "("
+            RecordExpr@25..29
+              Identifier@25..26 "S"
+              Whitespace@26..27 " "
+              RecordExprFieldList@27..29
+                OpenBrace@27..28 "{"
+                CloseBrace@28..29 "}"
+            CloseParen@29..30 ")"
+        Whitespace@30..31 " "
+        CloseBrace@31..32 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_match_expr() {
+        check(
+            "match n { x if x > 0 => { 1 } _ => 0 }",
+            r#"Root@0..38
+  ExprStmt@0..38
+    MatchExpr@0..38
+      MatchKeyword@0..5 "match"
+      Whitespace@5..6 " "
+      VariableRef@6..7
+        Identifier@6..7 "n"
+      Whitespace@7..8 " "
+      MatchArmList@8..38
+        OpenBrace@8..9 "{"
+        Whitespace@9..10 " "
+        MatchArm@10..29
+          IdentPattern@10..11
+            Identifier@10..11 "x"
+          Whitespace@11..12 " "
+          MatchGuard@12..20
+            IfKeyword@12..14 "if"
+            Whitespace@14..15 " "
+            BinaryExpr@15..20
+              VariableRef@15..16
+                Identifier@15..16 "x"
+              Whitespace@16..17 " "
+              GreaterThan@17..18 ">"
+              Whitespace@18..19 " "
+              Literal@19..20
+                Number@19..20 "0"
+          Whitespace@20..21 " "
+          FatArrow@21..23 "=>"
+          Whitespace@23..24 " "
+          BlockExpr@24..29
+            OpenBrace@24..25 "{"
+            Whitespace@25..26 " "
+            ExprStmt@26..27
+              Literal@26..27
+                Number@26..27 "1"
+            Whitespace@27..28 " "
+            CloseBrace@28..29 "}"
+        Whitespace@29..30 " "
+        MatchArm@30..36
+          WildcardPattern@30..31
+            Underscore@30..31 "_"
+          Whitespace@31..32 " "
+          FatArrow@32..34 "=>"
+          Whitespace@34..35 " "
+          Literal@35..36
+            Number@35..36 "0"
+        Whitespace@36..37 " "
+        CloseBrace@37..38 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_match_arm_without_comma() {
+        check(
+            "match n { 1 => 2 _ => 3 }",
+            r#"Root@0..25
+  ExprStmt@0..25
+    MatchExpr@0..25
+      MatchKeyword@0..5 "match"
+      Whitespace@5..6 " "
+      VariableRef@6..7
+        Identifier@6..7 "n"
+      Whitespace@7..8 " "
+      MatchArmList@8..25
+        OpenBrace@8..9 "{"
+        Whitespace@9..10 " "
+        MatchArm@10..16
+          LiteralPattern@10..11
+            Number@10..11 "1"
+          Whitespace@11..12 " "
+          FatArrow@12..14 "=>"
+          Whitespace@14..15 " "
+          Literal@15..16
+            Number@15..16 "2"
+        Whitespace@16..17 " "
+        MatchArm@17..23
+          WildcardPattern@17..18
+            Underscore@17..18 "_"
+          Whitespace@18..19 " "
+          FatArrow@19..21 "=>"
+          Whitespace@21..22 " "
+          Literal@22..23
+            Number@22..23 "3"
+        Whitespace@23..24 " "
+        CloseBrace@24..25 "}"
+error at 17..18: expected `,`, found `_`
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_function_expr() {
+        check(
+            "fn add(a: i32, b) -> i32 { a + b }",
+            r#"Root@0..34
+  ExprStmt@0..34
+    FunctionExpr@0..34
+      FunctionKeyword@0..2 "fn"
+      Whitespace@2..3 " "
+      Identifier@3..6 "add"
+      ParamList@6..17
+        OpenParen@6..7 "("
+        Param@7..13
+          Identifier@7..8 "a"
+          Colon@8..9 ":"
+          Whitespace@9..10 " "
+          PathType@10..13
+            Identifier@10..13 "i32"
+        Comma@13..14 ","
+        Whitespace@14..15 " "
+        Param@15..16
+          Identifier@15..16 "b"
+        CloseParen@16..17 ")"
+      Whitespace@17..18 " "
+      RightArrow@18..20 "->"
+      Whitespace@20..21 " "
+      PathType@21..24
+        Identifier@21..24 "i32"
+      Whitespace@24..25 " "
+      BlockExpr@25..34
+        OpenBrace@25..26 "{"
+        Whitespace@26..27 " "
+        ExprStmt@27..32
+          BinaryExpr@27..32
+            VariableRef@27..28
+              Identifier@27..28 "a"
+            Whitespace@28..29 " "
+            Plus@29..30 "+"
+            Whitespace@30..31 " "
+            VariableRef@31..32
+              Identifier@31..32 "b"
+        Whitespace@32..33 " "
+        CloseBrace@33..34 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_comments_as_trivia() {
+        check(
+            "1 /* one */ + // two\n2",
+            r#"Root@0..22
+  ExprStmt@0..22
+    BinaryExpr@0..22
+      Literal@0..1
+        Number@0..1 "1"
+      Whitespace@1..2 " "
+      BlockComment@2..11 "/* one */"
+      Whitespace@11..12 " "
+      Plus@12..13 "+"
+      Whitespace@13..14 " "
+      LineComment@14..21 "// two\n"
+      Literal@21..22
+        Number@21..22 "2"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_missing_rhs() {
+        check(
+            "1 +",
+            r#"Root@0..3
+  ExprStmt@0..3
+    BinaryExpr@0..3
+      Literal@0..1
+        Number@0..1 "1"
+      Whitespace@1..2 " "
+      Plus@2..3 "+"
+error at 3..3: expected expression, found end of input
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_compound_assignments() {
+        check(
+            "a[0] += b -= 1",
+            r#"Root@0..14
+  ExprStmt@0..14
+    BinaryExpr@0..14
+      IndexExpr@0..4
+        VariableRef@0..1
+          Identifier@0..1 "a"
+        OpenSquare@1..2 "["
+        Literal@2..3
+          Number@2..3 "0"
+        CloseSquare@3..4 "]"
+      Whitespace@4..5 " "
+      PlusEqual@5..7 "+="
+      Whitespace@7..8 " "
+      BinaryExpr@8..14
+        VariableRef@8..9
+          Identifier@8..9 "b"
+        Whitespace@9..10 " "
+        MinusEqual@10..12 "-="
+        Whitespace@12..13 " "
+        Literal@13..14
+          Number@13..14 "1"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_mut_params() {
+        check(
+            "fn f(mut a, b: u8) { |mut c| c }",
+            r#"Root@0..32
+  ExprStmt@0..32
+    FunctionExpr@0..32
+      FunctionKeyword@0..2 "fn"
+      Whitespace@2..3 " "
+      Identifier@3..4 "f"
+      ParamList@4..18
+        OpenParen@4..5 "("
+        Param@5..10
+          MutKeyword@5..8 "mut"
+          Whitespace@8..9 " "
+          Identifier@9..10 "a"
+        Comma@10..11 ","
+        Whitespace@11..12 " "
+        Param@12..17
+          Identifier@12..13 "b"
+          Colon@13..14 ":"
+          Whitespace@14..15 " "
+          PathType@15..17
+            Identifier@15..17 "u8"
+        CloseParen@17..18 ")"
+      Whitespace@18..19 " "
+      BlockExpr@19..32
+        OpenBrace@19..20 "{"
+        Whitespace@20..21 " "
+        ExprStmt@21..30
+          ClosureExpr@21..30
+            ParamList@21..28
+              Pipe@21..22 "|"
+              Param@22..27
+                MutKeyword@22..25 "mut"
+                Whitespace@25..26 " "
+                Identifier@26..27 "c"
+              Pipe@27..28 "|"
+            Whitespace@28..29 " "
+            VariableRef@29..30
+              Identifier@29..30 "c"
+        Whitespace@30..31 " "
+        CloseBrace@31..32 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_interpolated_strings() {
+        check(
+            r#"f"plain {{}}""#,
+            r#"Root@0..13
+  ExprStmt@0..13
+    InterpolatedString@0..13
+      InterpolatedStringPlain@0..13 "f\"plain {{}}\""
+"#,
+        );
+        check(
+            r#"f"Hello {name}, you are {age + 1}!""#,
+            r#"Root@0..35
+  ExprStmt@0..35
+    InterpolatedString@0..35
+      InterpolatedStringStart@0..9 "f\"Hello {"
+      VariableRef@9..13
+        Identifier@9..13 "name"
+      InterpolatedStringMiddle@13..25 "}, you are {"
+      BinaryExpr@25..32
+        VariableRef@25..28
+          Identifier@25..28 "age"
+        Whitespace@28..29 " "
+        Plus@29..30 "+"
+        Whitespace@30..31 " "
+        Literal@31..32
+          Number@31..32 "1"
+      InterpolatedStringEnd@32..35 "}!\""
+"#,
+        );
+        check(
+            r#"f"{f"{a}"}{ {b} }""#,
+            r#"Root@0..18
+  ExprStmt@0..18
+    InterpolatedString@0..18
+      InterpolatedStringStart@0..3 "f\"{"
+      InterpolatedString@3..9
+        InterpolatedStringStart@3..6 "f\"{"
+        VariableRef@6..7
+          Identifier@6..7 "a"
+        InterpolatedStringEnd@7..9 "}\""
+      InterpolatedStringMiddle@9..11 "}{"
+      Whitespace@11..12 " "
+      BlockExpr@12..15
+        OpenBrace@12..13 "{"
+        ExprStmt@13..14
+          VariableRef@13..14
+            Identifier@13..14 "b"
+        CloseBrace@14..15 "}"
+      Whitespace@15..16 " "
+      InterpolatedStringEnd@16..18 "}\""
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_interpolated_string_errors() {
+        check(
+            r#"f"{}{1 2}""#,
+            r#"Root@0..10
+  ExprStmt@0..10
+    InterpolatedString@0..10
+      InterpolatedStringStart@0..3 "f\"{"
+      InterpolatedStringMiddle@3..5 "}{"
+      Literal@5..6
+        Number@5..6 "1"
+      Whitespace@6..7 " "
+      Error@7..8
+        Number@7..8 "2"
+      InterpolatedStringEnd@8..10 "}\""
+error at 3..5: expected expression, found `}`
+error at 7..8: expected `}`, found number
+"#,
+        );
+        check(
+            r#"f"{a"#,
+            r#"Root@0..4
+  ExprStmt@0..4
+    InterpolatedString@0..4
+      InterpolatedStringStart@0..3 "f\"{"
+      VariableRef@3..4
+        Identifier@3..4 "a"
+error at 4..4: expected `}`, found end of input
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_tuple_exprs() {
+        check(
+            "((), (1,), (a, b + 1))",
+            r#"Root@0..22
+  ExprStmt@0..22
+    TupleExpr@0..22
+      OpenParen@0..1 "("
+      TupleExpr@1..3
+        OpenParen@1..2 "("
+        CloseParen@2..3 ")"
+      Comma@3..4 ","
+      Whitespace@4..5 " "
+      TupleExpr@5..9
+        OpenParen@5..6 "("
+        Literal@6..7
+          Number@6..7 "1"
+        Comma@7..8 ","
+        CloseParen@8..9 ")"
+      Comma@9..10 ","
+      Whitespace@10..11 " "
+      TupleExpr@11..21
+        OpenParen@11..12 "("
+        VariableRef@12..13
+          Identifier@12..13 "a"
+        Comma@13..14 ","
+        Whitespace@14..15 " "
+        BinaryExpr@15..20
+          VariableRef@15..16
+            Identifier@15..16 "b"
+          Whitespace@16..17 " "
+          Plus@17..18 "+"
+          Whitespace@18..19 " "
+          Literal@19..20
+            Number@19..20 "1"
+        CloseParen@20..21 ")"
+      CloseParen@21..22 ")"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_field_exprs() {
+        check(
+            "t.0.1 + f().2",
+            r#"Root@0..13
+  ExprStmt@0..13
+    BinaryExpr@0..13
+      FieldExpr@0..5
+        FieldExpr@0..3
+          VariableRef@0..1
+            Identifier@0..1 "t"
+          Dot@1..2 "."
+          Number@2..3 "0"
+        Dot@3..4 "."
+        Number@4..5 "1"
+      Whitespace@5..6 " "
+      Plus@6..7 "+"
+      Whitespace@7..8 " "
+      FieldExpr@8..13
+        CallExpr@8..11
+          VariableRef@8..9
+            Identifier@8..9 "f"
+          ArgList@9..11
+            OpenParen@9..10 "("
+            CloseParen@10..11 ")"
+        Dot@11..12 "."
+        Number@12..13 "2"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_method_call_expr() {
+        check(
+            "a.b().c(1, d)[0]",
+            r#"Root@0..16
+  ExprStmt@0..16
+    IndexExpr@0..16
+      MethodCallExpr@0..13
+        MethodCallExpr@0..5
+          VariableRef@0..1
+            Identifier@0..1 "a"
+          Dot@1..2 "."
+          Identifier@2..3 "b"
+          ArgList@3..5
+            OpenParen@3..4 "("
+            CloseParen@4..5 ")"
+        Dot@5..6 "."
+        Identifier@6..7 "c"
+        ArgList@7..13
+          OpenParen@7..8 "("
+          Literal@8..9
+            Number@8..9 "1"
+          Comma@9..10 ","
+          Whitespace@10..11 " "
+          VariableRef@11..12
+            Identifier@11..12 "d"
+          CloseParen@12..13 ")"
+      OpenSquare@13..14 "["
+      Literal@14..15
+        Number@14..15 "0"
+      CloseSquare@15..16 "]"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_named_field_exprs() {
+        check(
+            "p.x.0",
+            r#"Root@0..5
+  ExprStmt@0..5
+    FieldExpr@0..5
+      FieldExpr@0..3
+        VariableRef@0..1
+          Identifier@0..1 "p"
+        Dot@1..2 "."
+        Identifier@2..3 "x"
+      Dot@3..4 "."
+      Number@4..5 "0"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_generic_param_bounds() {
+        check(
+            "fn f<T: Show + m::Eq, U:>() {}",
+            r#"Root@0..30
+  ExprStmt@0..30
+    FunctionExpr@0..30
+      FunctionKeyword@0..2 "fn"
+      Whitespace@2..3 " "
+      Identifier@3..4 "f"
+      GenericParamList@4..25
+        LessThan@4..5 "<"
+        GenericParam@5..20
+          Identifier@5..6 "T"
+          TypeBoundList@6..20
+            Colon@6..7 ":"
+            Whitespace@7..8 " "
+            PathType@8..12
+              Identifier@8..12 "Show"
+            Whitespace@12..13 " "
+            Plus@13..14 "+"
+            Whitespace@14..15 " "
+            PathType@15..20
+              Identifier@15..16 "m"
+              ColonColon@16..18 "::"
+              Identifier@18..20 "Eq"
+        Comma@20..21 ","
+        Whitespace@21..22 " "
+        GenericParam@22..24
+          Identifier@22..23 "U"
+          TypeBoundList@23..24
+            Colon@23..24 ":"
+        GreaterThan@24..25 ">"
+      ParamList@25..27
+        OpenParen@25..26 "("
+        CloseParen@26..27 ")"
+      Whitespace@27..28 " "
+      BlockExpr@28..30
+        OpenBrace@28..29 "{"
+        CloseBrace@29..30 "}"
+error at 24..25: expected trait, found `>`
+"#,
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use super::delimited_list;
+use crate::{syntax::SyntaxKind, Parser};
+
+/// Tokens that can start a pattern.
+pub(super) const PATTERN_FIRST: &[SyntaxKind] = &[
+    SyntaxKind::Number,
+    SyntaxKind::String,
+    SyntaxKind::Character,
+    SyntaxKind::Bool,
+    SyntaxKind::Minus,
+    SyntaxKind::Identifier,
+    SyntaxKind::MutKeyword,
+    SyntaxKind::Underscore,
+    SyntaxKind::DotDot,
+    SyntaxKind::OpenParen,
+];
+
+/// Parses a pattern, including `a | b` alternatives.
+pub(super) fn pattern(p: &mut Parser) {
+    let checkpoint = p.checkpoint();
+    single_pattern(p);
+
+    if p.at(SyntaxKind::Pipe) {
+        while p.eat(SyntaxKind::Pipe) {
+            single_pattern(p);
+        }
+        p.start_node_at(checkpoint, SyntaxKind::OrPattern);
+        p.finish_node();
+    }
+}
+
+fn single_pattern(p: &mut Parser) {
+    match p.peek() {
+        Some(SyntaxKind::Underscore) => token_pattern(p, SyntaxKind::WildcardPattern),
+        Some(SyntaxKind::DotDot) => token_pattern(p, SyntaxKind::RestPattern),
+        Some(
+            SyntaxKind::Number
+            | SyntaxKind::String
+            | SyntaxKind::Character
+            | SyntaxKind::Bool
+            | SyntaxKind::Minus,
+        ) => literal_or_range_pattern(p),
+        Some(SyntaxKind::Identifier) => path_pattern(p),
+        // `mut a` binds a variable that can be assigned to
+        Some(SyntaxKind::MutKeyword) => {
+            p.start_node(SyntaxKind::IdentPattern);
+            p.bump();
+            p.expect(SyntaxKind::Identifier);
+            p.finish_node();
+        }
+        Some(SyntaxKind::OpenParen) => delimited_list(
+            p,
+            SyntaxKind::TuplePattern,
+            SyntaxKind::CloseParen,
+            PATTERN_FIRST,
+            pattern,
+            "pattern",
+        ),
+        _ => p.error("expected pattern"),
+    }
+}
+
+fn token_pattern(p: &mut Parser, kind: SyntaxKind) {
+    p.start_node(kind);
+    p.bump();
+    p.finish_node();
+}
+
+/// Parses a literal, or a range such as `1..=5` when it is followed by `..` or `..=`.
+fn literal_or_range_pattern(p: &mut Parser) {
+    let checkpoint = p.checkpoint();
+    literal_pattern(p);
+
+    if p.at(SyntaxKind::DotDot) || p.at(SyntaxKind::DotDotEqual) {
+        p.bump();
+        literal_pattern(p);
+        p.start_node_at(checkpoint, SyntaxKind::RangePattern);
+        p.finish_node();
+    }
+}
+
+fn literal_pattern(p: &mut Parser) {
+    p.start_node(SyntaxKind::LiteralPattern);
+
+    if p.eat(SyntaxKind::Minus) {
+        p.expect(SyntaxKind::Number);
+    } else if p.at_set(&[
+        SyntaxKind::Number,
+        SyntaxKind::String,
+        SyntaxKind::Character,
+        SyntaxKind::Bool,
+    ]) {
+        p.bump();
+    } else {
+        p.error("expected literal");
+    }
+
+    p.finish_node();
+}
+
+/// Parses patterns starting with a path: `a::B`, `B(x, y)` and `B { x, y: 0 }`.
+/// A single identifier on its own binds a variable.
+fn path_pattern(p: &mut Parser) {
+    let checkpoint = p.checkpoint();
+    p.bump();
+
+    let mut is_path = false;
+    while p.eat(SyntaxKind::ColonColon) {
+        p.expect(SyntaxKind::Identifier);
+        is_path = true;
+    }
+
+    let kind = match p.peek() {
+        Some(SyntaxKind::OpenParen) => {
+            delimited_list(
+                p,
+                SyntaxKind::PatternList,
+                SyntaxKind::CloseParen,
+                PATTERN_FIRST,
+                pattern,
+                "pattern",
+            );
+            SyntaxKind::TupleStructPattern
+        }
+        Some(SyntaxKind::OpenBrace) => {
+            delimited_list(
+                p,
+                SyntaxKind::FieldPatternList,
+                SyntaxKind::CloseBrace,
+                &[SyntaxKind::Identifier, SyntaxKind::DotDot],
+                field_pattern,
+                "field",
+            );
+            SyntaxKind::StructPattern
+        }
+        _ if is_path => SyntaxKind::PathPattern,
+        _ => SyntaxKind::IdentPattern,
+    };
+
+    p.start_node_at(checkpoint, kind);
+    p.finish_node();
+}
+
+/// Parses `field: pattern`, the `field` shorthand binding a variable of the same name,
+/// or `..` to ignore the remaining fields.
+fn field_pattern(p: &mut Parser) {
+    if p.at(SyntaxKind::DotDot) {
+        token_pattern(p, SyntaxKind::RestPattern);
+        return;
+    }
+
+    p.start_node(SyntaxKind::FieldPattern);
+    p.bump();
+    if p.eat(SyntaxKind::Colon) {
+        pattern(p);
+    }
+    p.finish_node();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grammar::check;
+
+    #[test]
+    fn parse_literal_and_range_patterns() {
+        check(
+            "match x { -1 | 'a'..='z' => 0, \"s\" | true => 1 }",
+            r#"Root@0..48
+  ExprStmt@0..48
+    MatchExpr@0..48
+      MatchKeyword@0..5 "match"
+      Whitespace@5..6 " "
+      VariableRef@6..7
+        Identifier@6..7 "x"
+      Whitespace@7..8 " "
+      MatchArmList@8..48
+        OpenBrace@8..9 "{"
+        Whitespace@9..10 " "
+        MatchArm@10..30
+          OrPattern@10..24
+            LiteralPattern@10..12
+              Minus@10..11 "-"
+              Number@11..12 "1"
+            Whitespace@12..13 " "
+            Pipe@13..14 "|"
+            Whitespace@14..15 " "
+            RangePattern@15..24
+              LiteralPattern@15..18
+                Character@15..18 "'a'"
+              DotDotEqual@18..21 "..="
+              LiteralPattern@21..24
+                Character@21..24 "'z'"
+          Whitespace@24..25 " "
+          FatArrow@25..27 "=>"
+          Whitespace@27..28 " "
+          Literal@28..29
+            Number@28..29 "0"
+          Comma@29..30 ","
+        Whitespace@30..31 " "
+        MatchArm@31..46
+          OrPattern@31..41
+            LiteralPattern@31..34
+              String@31..34 "\"s\""
+            Whitespace@34..35 " "
+            Pipe@35..36 "|"
+            Whitespace@36..37 " "
+            LiteralPattern@37..41
+              Bool@37..41 "true"
+          Whitespace@41..42 " "
+          FatArrow@42..44 "=>"
+          Whitespace@44..45 " "
+          Literal@45..46
+            Number@45..46 "1"
+        Whitespace@46..47 " "
+        CloseBrace@47..48 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_binding_and_wildcard_patterns() {
+        check(
+            "match x { _ => 0, y => y }",
+            r#"Root@0..26
+  ExprStmt@0..26
+    MatchExpr@0..26
+      MatchKeyword@0..5 "match"
+      Whitespace@5..6 " "
+      VariableRef@6..7
+        Identifier@6..7 "x"
+      Whitespace@7..8 " "
+      MatchArmList@8..26
+        OpenBrace@8..9 "{"
+        Whitespace@9..10 " "
+        MatchArm@10..17
+          WildcardPattern@10..11
+            Underscore@10..11 "_"
+          Whitespace@11..12 " "
+          FatArrow@12..14 "=>"
+          Whitespace@14..15 " "
+          Literal@15..16
+            Number@15..16 "0"
+          Comma@16..17 ","
+        Whitespace@17..18 " "
+        MatchArm@18..24
+          IdentPattern@18..19
+            Identifier@18..19 "y"
+          Whitespace@19..20 " "
+          FatArrow@20..22 "=>"
+          Whitespace@22..23 " "
+          VariableRef@23..24
+            Identifier@23..24 "y"
+        Whitespace@24..25 " "
+        CloseBrace@25..26 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_tuple_and_struct_patterns() {
+        check(
+            "match x { (a, ..) => 0, m::V(_, 1) => 1, S { a, b: 2, .. } => 2, m::U => 3 }",
+            r#"Root@0..76
+  ExprStmt@0..76
+    MatchExpr@0..76
+      MatchKeyword@0..5 "match"
+      Whitespace@5..6 " "
+      VariableRef@6..7
+        Identifier@6..7 "x"
+      Whitespace@7..8 " "
+      MatchArmList@8..76
+        OpenBrace@8..9 "{"
+        Whitespace@9..10 " "
+        MatchArm@10..23
+          TuplePattern@10..17
+            OpenParen@10..11 "("
+            IdentPattern@11..12
+              Identifier@11..12 "a"
+            Comma@12..13 ","
+            Whitespace@13..14 " "
+            RestPattern@14..16
+              DotDot@14..16 ".."
+            CloseParen@16..17 ")"
+          Whitespace@17..18 " "
+          FatArrow@18..20 "=>"
+          Whitespace@20..21 " "
+          Literal@21..22
+            Number@21..22 "0"
+          Comma@22..23 ","
+        Whitespace@23..24 " "
+        MatchArm@24..40
+          TupleStructPattern@24..34
+            Identifier@24..25 "m"
+            ColonColon@25..27 "::"
+            Identifier@27..28 "V"
+            PatternList@28..34
+              OpenParen@28..29 "("
+              WildcardPattern@29..30
+                Underscore@29..30 "_"
+              Comma@30..31 ","
+              Whitespace@31..32 " "
+              LiteralPattern@32..33
+                Number@32..33 "1"
+              CloseParen@33..34 ")"
+          Whitespace@34..35 " "
+          FatArrow@35..37 "=>"
+          Whitespace@37..38 " "
+          Literal@38..39
+            Number@38..39 "1"
+          Comma@39..40 ","
+        Whitespace@40..41 " "
+        MatchArm@41..64
+          StructPattern@41..58
+            Identifier@41..42 "S"
+            Whitespace@42..43 " "
+            FieldPatternList@43..58
+              OpenBrace@43..44 "{"
+              Whitespace@44..45 " "
+              FieldPattern@45..46
+                Identifier@45..46 "a"
+              Comma@46..47 ","
+              Whitespace@47..48 " "
+              FieldPattern@48..52
+                Identifier@48..49 "b"
+                Colon@49..50 ":"
+                Whitespace@50..51 " "
+                LiteralPattern@51..52
+                  Number@51..52 "2"
+              Comma@52..53 ","
+              Whitespace@53..54 " "
+              RestPattern@54..56
+                DotDot@54..56 ".."
+              Whitespace@56..57 " "
+              CloseBrace@57..58 "}"
+          Whitespace@58..59 " "
+          FatArrow@59..61 "=>"
+          Whitespace@61..62 " "
+          Literal@62..63
+            Number@62..63 "2"
+          Comma@63..64 ","
+        Whitespace@64..65 " "
+        MatchArm@65..74
+          PathPattern@65..69
+            Identifier@65..66 "m"
+            ColonColon@66..68 "::"
+            Identifier@68..69 "U"
+          Whitespace@69..70 " "
+          FatArrow@70..72 "=>"
+          Whitespace@72..73 " "
+          Literal@73..74
+            Number@73..74 "3"
+        Whitespace@74..75 " "
+        CloseBrace@75..76 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_missing_pattern() {
+        check(
+            "match x { => 0 }",
+            r#"Root@0..16
+  ExprStmt@0..16
+    MatchExpr@0..16
+      MatchKeyword@0..5 "match"
+      Whitespace@5..6 " "
+      VariableRef@6..7
+        Identifier@6..7 "x"
+      Whitespace@7..8 " "
+      MatchArmList@8..16
+        OpenBrace@8..9 "{"
+        Whitespace@9..10 " "
+        MatchArm@10..14
+          FatArrow@10..12 "=>"
+          Whitespace@12..13 " "
+          Literal@13..14
+            Number@13..14 "0"
+        Whitespace@14..15 " "
+        CloseBrace@15..16 "}"
+error at 10..12: expected pattern, found `=>`
+"#,
+        );
+    }
+}
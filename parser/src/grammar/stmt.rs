@@ -0,0 +1,1197 @@
+use rowan::Checkpoint;
+
+use super::{
+    delimited_list,
+    expr::{self, EXPR_FIRST},
+    generic_param_list, pattern,
+    ty::{self, TYPE_FIRST},
+    RECOVERY_SET,
+};
+use crate::{syntax::SyntaxKind, Parser};
+
+pub(super) fn stmt(p: &mut Parser) {
+    match p.peek() {
+        Some(SyntaxKind::LetKeyword) => let_stmt(p),
+        Some(SyntaxKind::ReturnKeyword) => return_stmt(p),
+        // inner attributes apply to the enclosing module or block
+        Some(SyntaxKind::Pound) if p.nth(1) == Some(SyntaxKind::Bang) => attr(p),
+        Some(
+            SyntaxKind::Pound
+            | SyntaxKind::PubKeyword
+            | SyntaxKind::ModKeyword
+            | SyntaxKind::UseKeyword
+            | SyntaxKind::EnumKeyword
+            | SyntaxKind::StructKeyword
+            | SyntaxKind::TraitKeyword
+            | SyntaxKind::ImplKeyword,
+        ) => item(p),
+        _ => expr_stmt(p),
+    }
+}
+
+/// Parses a declaration on its own, without other statements around it.
+pub(super) fn lone_item(p: &mut Parser) {
+    match p.peek() {
+        Some(
+            SyntaxKind::Pound
+            | SyntaxKind::PubKeyword
+            | SyntaxKind::ModKeyword
+            | SyntaxKind::UseKeyword
+            | SyntaxKind::EnumKeyword
+            | SyntaxKind::StructKeyword
+            | SyntaxKind::TraitKeyword
+            | SyntaxKind::ImplKeyword
+            | SyntaxKind::FunctionKeyword,
+        ) => item(p),
+        _ => p.error("expected item"),
+    }
+}
+
+/// Parses a declaration which can be preceded by attributes and a visibility.
+fn item(p: &mut Parser) {
+    let checkpoint = p.checkpoint();
+    outer_attrs(p);
+
+    let is_pub = p.at(SyntaxKind::PubKeyword);
+    if is_pub {
+        p.start_node(SyntaxKind::Visibility);
+        p.bump();
+        p.finish_node();
+    }
+
+    match p.peek() {
+        Some(SyntaxKind::ModKeyword) => mod_stmt(p, checkpoint),
+        Some(SyntaxKind::UseKeyword) => use_stmt(p, checkpoint),
+        Some(SyntaxKind::EnumKeyword) => enum_stmt(p, checkpoint),
+        Some(SyntaxKind::StructKeyword) => struct_stmt(p, checkpoint),
+        Some(SyntaxKind::TraitKeyword) => trait_stmt(p, checkpoint),
+        // impls have no visibility, only their functions do
+        Some(SyntaxKind::ImplKeyword) if !is_pub => impl_stmt(p, checkpoint),
+        Some(SyntaxKind::FunctionKeyword) => function_stmt(p, checkpoint, false),
+        _ if is_pub => p.error("expected `fn`, `mod`, `use`, `enum`, `struct` or `trait`"),
+        _ => p.error("expected `fn`, `mod`, `use`, `enum`, `struct`, `trait` or `impl`"),
+    }
+}
+
+/// Parses the attributes in front of an item, such as `#[test]`.
+fn outer_attrs(p: &mut Parser) {
+    while p.at(SyntaxKind::Pound) && p.nth(1) != Some(SyntaxKind::Bang) {
+        attr(p);
+    }
+}
+
+/// Parses `#[name]` or `#[name(args)]`, and `#![name]` for inner attributes.
+fn attr(p: &mut Parser) {
+    p.start_node(SyntaxKind::Attr);
+    p.bump();
+    p.eat(SyntaxKind::Bang);
+    p.expect(SyntaxKind::OpenSquare);
+    p.expect(SyntaxKind::Identifier);
+
+    if p.at(SyntaxKind::OpenParen) {
+        delimited_list(
+            p,
+            SyntaxKind::AttrArgList,
+            SyntaxKind::CloseParen,
+            ATTR_ARG_FIRST,
+            attr_arg,
+            "attribute argument",
+        );
+    }
+
+    p.expect(SyntaxKind::CloseSquare);
+    p.finish_node();
+}
+
+const ATTR_ARG_FIRST: &[SyntaxKind] = &[
+    SyntaxKind::Identifier,
+    SyntaxKind::Number,
+    SyntaxKind::String,
+    SyntaxKind::Character,
+    SyntaxKind::Bool,
+];
+
+/// Parses an argument of an attribute: a name, as in `inline(always)`, a literal, or a
+/// name given a literal, as in `deprecated(note = "...")`.
+fn attr_arg(p: &mut Parser) {
+    p.start_node(SyntaxKind::AttrArg);
+
+    if !p.eat(SyntaxKind::Identifier) {
+        expr::literal(p);
+    } else if p.eat(SyntaxKind::SingleEqual) {
+        if p.at_set(&ATTR_ARG_FIRST[1..]) {
+            expr::literal(p);
+        } else {
+            p.error("expected literal");
+        }
+    }
+
+    p.finish_node();
+}
+
+/// Parses a function declaration, whose body can be left out in traits.
+fn function_stmt(p: &mut Parser, checkpoint: Checkpoint, in_trait: bool) {
+    expr::function(p, checkpoint, in_trait);
+    p.start_node_at(checkpoint, SyntaxKind::ExprStmt);
+    stmt_end(p, true);
+    p.finish_node();
+}
+
+fn mod_stmt(p: &mut Parser, checkpoint: Checkpoint) {
+    p.start_node_at(checkpoint, SyntaxKind::ModStmt);
+    p.bump();
+    p.expect(SyntaxKind::Identifier);
+
+    if p.at(SyntaxKind::OpenBrace) {
+        p.start_node(SyntaxKind::ItemList);
+        p.bump();
+
+        while !p.at(SyntaxKind::CloseBrace) && !p.at_end() {
+            stmt(p);
+        }
+
+        p.expect(SyntaxKind::CloseBrace);
+        p.finish_node();
+    } else {
+        p.expect(SyntaxKind::Semicolon);
+    }
+
+    p.finish_node();
+}
+
+fn use_stmt(p: &mut Parser, checkpoint: Checkpoint) {
+    p.start_node_at(checkpoint, SyntaxKind::UseStmt);
+    p.bump();
+    super::path(p);
+    p.expect(SyntaxKind::Semicolon);
+    p.finish_node();
+}
+
+/// Parses `enum Name { A, B(T), C { x: T } }`.
+fn enum_stmt(p: &mut Parser, checkpoint: Checkpoint) {
+    p.start_node_at(checkpoint, SyntaxKind::EnumStmt);
+    p.bump();
+    p.expect(SyntaxKind::Identifier);
+    if p.at(SyntaxKind::LessThan) {
+        generic_param_list(p);
+    }
+
+    if p.at(SyntaxKind::OpenBrace) {
+        delimited_list(
+            p,
+            SyntaxKind::VariantList,
+            SyntaxKind::CloseBrace,
+            &[SyntaxKind::Identifier],
+            variant,
+            "variant",
+        );
+    } else {
+        p.error("expected `{`");
+    }
+
+    p.finish_node();
+}
+
+/// Parses `struct Name { a: A, b: B }`.
+fn struct_stmt(p: &mut Parser, checkpoint: Checkpoint) {
+    p.start_node_at(checkpoint, SyntaxKind::StructStmt);
+    p.bump();
+    p.expect(SyntaxKind::Identifier);
+    if p.at(SyntaxKind::LessThan) {
+        generic_param_list(p);
+    }
+
+    if p.at(SyntaxKind::OpenBrace) {
+        record_field_list(p);
+    } else {
+        p.error("expected `{`");
+    }
+
+    p.finish_node();
+}
+
+/// Parses `trait Name { fn required(self); fn provided(self) { .. } }`.
+fn trait_stmt(p: &mut Parser, checkpoint: Checkpoint) {
+    p.start_node_at(checkpoint, SyntaxKind::TraitStmt);
+    p.bump();
+    p.expect(SyntaxKind::Identifier);
+    assoc_item_list(p, true);
+    p.finish_node();
+}
+
+/// Parses `impl Type { .. }`, or `impl Trait for Type { .. }` to implement a trait.
+fn impl_stmt(p: &mut Parser, checkpoint: Checkpoint) {
+    p.start_node_at(checkpoint, SyntaxKind::ImplStmt);
+    p.bump();
+    if p.at(SyntaxKind::LessThan) {
+        generic_param_list(p);
+    }
+
+    ty::ty(p);
+    if p.eat(SyntaxKind::ForKeyword) {
+        ty::ty(p);
+    }
+
+    assoc_item_list(p, false);
+    p.finish_node();
+}
+
+/// Parses the functions in the body of a trait or an impl.
+fn assoc_item_list(p: &mut Parser, in_trait: bool) {
+    if !p.at(SyntaxKind::OpenBrace) {
+        p.error("expected `{`");
+        return;
+    }
+
+    p.start_node(SyntaxKind::ItemList);
+    p.bump();
+
+    while !p.at(SyntaxKind::CloseBrace) && !p.at_end() {
+        if p.at(SyntaxKind::Pound) && p.nth(1) == Some(SyntaxKind::Bang) {
+            attr(p);
+            continue;
+        }
+
+        let checkpoint = p.checkpoint();
+        outer_attrs(p);
+        if p.at(SyntaxKind::PubKeyword) {
+            p.start_node(SyntaxKind::Visibility);
+            p.bump();
+            p.finish_node();
+        }
+
+        if p.at(SyntaxKind::FunctionKeyword) {
+            function_stmt(p, checkpoint, in_trait);
+        } else if p.at_set(RECOVERY_SET) {
+            break;
+        } else {
+            p.error_and_bump("expected `fn`");
+        }
+    }
+
+    p.expect(SyntaxKind::CloseBrace);
+    p.finish_node();
+}
+
+fn variant(p: &mut Parser) {
+    p.start_node(SyntaxKind::Variant);
+    p.bump();
+
+    match p.peek() {
+        Some(SyntaxKind::OpenParen) => delimited_list(
+            p,
+            SyntaxKind::TypeList,
+            SyntaxKind::CloseParen,
+            TYPE_FIRST,
+            ty::ty,
+            "type",
+        ),
+        Some(SyntaxKind::OpenBrace) => record_field_list(p),
+        _ => {}
+    }
+
+    p.finish_node();
+}
+
+fn record_field_list(p: &mut Parser) {
+    delimited_list(
+        p,
+        SyntaxKind::RecordFieldList,
+        SyntaxKind::CloseBrace,
+        &[SyntaxKind::Identifier],
+        record_field,
+        "field",
+    );
+}
+
+fn record_field(p: &mut Parser) {
+    p.start_node(SyntaxKind::RecordField);
+    p.bump();
+    p.expect(SyntaxKind::Colon);
+    ty::ty(p);
+    p.finish_node();
+}
+
+/// Parses `let a = b;`, `let mut a = b;` for a variable that can be assigned to, or
+/// `let (a, b) = c;` to destructure a tuple.
+fn let_stmt(p: &mut Parser) {
+    p.start_node(SyntaxKind::LetStmt);
+    p.bump();
+    if p.at(SyntaxKind::OpenParen) {
+        pattern::pattern(p);
+    } else {
+        p.eat(SyntaxKind::MutKeyword);
+        p.expect(SyntaxKind::Identifier);
+    }
+
+    if p.eat(SyntaxKind::Colon) {
+        ty::ty(p);
+    }
+
+    if p.eat(SyntaxKind::SingleEqual) {
+        expr::expr(p);
+    } else {
+        p.error("expected `=`");
+    }
+
+    p.expect(SyntaxKind::Semicolon);
+    p.finish_node();
+}
+
+fn return_stmt(p: &mut Parser) {
+    p.start_node(SyntaxKind::ReturnStmt);
+    p.bump();
+
+    if p.at_set(EXPR_FIRST) {
+        expr::expr(p);
+    }
+
+    stmt_end(p, false);
+    p.finish_node();
+}
+
+fn expr_stmt(p: &mut Parser) {
+    if !p.at_set(EXPR_FIRST) {
+        p.error_and_bump("expected statement");
+        return;
+    }
+
+    let checkpoint = p.checkpoint();
+    let kind = expr::expr(p);
+    p.start_node_at(checkpoint, SyntaxKind::ExprStmt);
+    stmt_end(p, kind.is_some_and(expr::is_block_like));
+    p.finish_node();
+}
+
+/// Eats the `;` ending a statement, which can be omitted after block-like
+/// expressions and before the end of a block.
+fn stmt_end(p: &mut Parser, block_like: bool) {
+    if p.eat(SyntaxKind::Semicolon) || block_like {
+        return;
+    }
+
+    if !p.at(SyntaxKind::CloseBrace) && !p.at_end() {
+        p.error("expected `;`");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grammar::check;
+
+    #[test]
+    fn parse_let_mut() {
+        check(
+            "let mut a = 1; let (mut b, c) = d;",
+            r#"Root@0..34
+  LetStmt@0..14
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    MutKeyword@4..7 "mut"
+    Whitespace@7..8 " "
+    Identifier@8..9 "a"
+    Whitespace@9..10 " "
+    SingleEqual@10..11 "="
+    Whitespace@11..12 " "
+    Literal@12..13
+      Number@12..13 "1"
+    Semicolon@13..14 ";"
+  Whitespace@14..15 " "
+  LetStmt@15..34
+    LetKeyword@15..18 "let"
+    Whitespace@18..19 " "
+    TuplePattern@19..29
+      OpenParen@19..20 "("
+      IdentPattern@20..25
+        MutKeyword@20..23 "mut"
+        Whitespace@23..24 " "
+        Identifier@24..25 "b"
+      Comma@25..26 ","
+      Whitespace@26..27 " "
+      IdentPattern@27..28
+        Identifier@27..28 "c"
+      CloseParen@28..29 ")"
+    Whitespace@29..30 " "
+    SingleEqual@30..31 "="
+    Whitespace@31..32 " "
+    VariableRef@32..33
+      Identifier@32..33 "d"
+    Semicolon@33..34 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_destructuring_let() {
+        check(
+            "let (x, (y, _)): (i32, (u8, char)) = p;",
+            r#"Root@0..39
+  LetStmt@0..39
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    TuplePattern@4..15
+      OpenParen@4..5 "("
+      IdentPattern@5..6
+        Identifier@5..6 "x"
+      Comma@6..7 ","
+      Whitespace@7..8 " "
+      TuplePattern@8..14
+        OpenParen@8..9 "("
+        IdentPattern@9..10
+          Identifier@9..10 "y"
+        Comma@10..11 ","
+        Whitespace@11..12 " "
+        WildcardPattern@12..13
+          Underscore@12..13 "_"
+        CloseParen@13..14 ")"
+      CloseParen@14..15 ")"
+    Colon@15..16 ":"
+    Whitespace@16..17 " "
+    TupleType@17..34
+      OpenParen@17..18 "("
+      PathType@18..21
+        Identifier@18..21 "i32"
+      Comma@21..22 ","
+      Whitespace@22..23 " "
+      TupleType@23..33
+        OpenParen@23..24 "("
+        PathType@24..26
+          Identifier@24..26 "u8"
+        Comma@26..27 ","
+        Whitespace@27..28 " "
+        PathType@28..32
+          Identifier@28..32 "char"
+        CloseParen@32..33 ")"
+      CloseParen@33..34 ")"
+    Whitespace@34..35 " "
+    SingleEqual@35..36 "="
+    Whitespace@36..37 " "
+    VariableRef@37..38
+      Identifier@37..38 "p"
+    Semicolon@38..39 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_let_stmt() {
+        check(
+            "let foo = 1;",
+            r#"Root@0..12
+  LetStmt@0..12
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..7 "foo"
+    Whitespace@7..8 " "
+    SingleEqual@8..9 "="
+    Whitespace@9..10 " "
+    Literal@10..11
+      Number@10..11 "1"
+    Semicolon@11..12 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_let_stmt_with_annotation() {
+        check(
+            "let foo: i32 = 1;",
+            r#"Root@0..17
+  LetStmt@0..17
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..7 "foo"
+    Colon@7..8 ":"
+    Whitespace@8..9 " "
+    PathType@9..12
+      Identifier@9..12 "i32"
+    Whitespace@12..13 " "
+    SingleEqual@13..14 "="
+    Whitespace@14..15 " "
+    Literal@15..16
+      Number@15..16 "1"
+    Semicolon@16..17 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_let_stmt_without_initializer() {
+        check(
+            "let foo;",
+            r#"Root@0..8
+  LetStmt@0..8
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..7 "foo"
+    Semicolon@7..8 ";"
+error at 7..8: expected `=`, found `;`
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_return_stmt() {
+        check(
+            "return;",
+            r#"Root@0..7
+  ReturnStmt@0..7
+    ReturnKeyword@0..6 "return"
+    Semicolon@6..7 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_missing_semicolon() {
+        check(
+            "a b",
+            r#"Root@0..3
+  ExprStmt@0..1
+    VariableRef@0..1
+      Identifier@0..1 "a"
+  Whitespace@1..2 " "
+  ExprStmt@2..3
+    VariableRef@2..3
+      Identifier@2..3 "b"
+error at 2..3: expected `;`, found identifier
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_tail_expr_in_block() {
+        check(
+            "{ let a = 1; a }",
+            r#"Root@0..16
+  ExprStmt@0..16
+    BlockExpr@0..16
+      OpenBrace@0..1 "{"
+      Whitespace@1..2 " "
+      LetStmt@2..12
+        LetKeyword@2..5 "let"
+        Whitespace@5..6 " "
+        Identifier@6..7 "a"
+        Whitespace@7..8 " "
+        SingleEqual@8..9 "="
+        Whitespace@9..10 " "
+        Literal@10..11
+          Number@10..11 "1"
+        Semicolon@11..12 ";"
+      Whitespace@12..13 " "
+      ExprStmt@13..14
+        VariableRef@13..14
+          Identifier@13..14 "a"
+      Whitespace@14..15 " "
+      CloseBrace@15..16 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_mod_stmt() {
+        check(
+            "mod foo;",
+            r#"Root@0..8
+  ModStmt@0..8
+    ModKeyword@0..3 "mod"
+    Whitespace@3..4 " "
+    Identifier@4..7 "foo"
+    Semicolon@7..8 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_inline_mod_stmt() {
+        check(
+            "pub mod foo { fn f() {} }",
+            r#"Root@0..25
+  ModStmt@0..25
+    Visibility@0..3
+      PubKeyword@0..3 "pub"
+    Whitespace@3..4 " "
+    ModKeyword@4..7 "mod"
+    Whitespace@7..8 " "
+    Identifier@8..11 "foo"
+    Whitespace@11..12 " "
+    ItemList@12..25
+      OpenBrace@12..13 "{"
+      Whitespace@13..14 " "
+      ExprStmt@14..23
+        FunctionExpr@14..23
+          FunctionKeyword@14..16 "fn"
+          Whitespace@16..17 " "
+          Identifier@17..18 "f"
+          ParamList@18..20
+            OpenParen@18..19 "("
+            CloseParen@19..20 ")"
+          Whitespace@20..21 " "
+          BlockExpr@21..23
+            OpenBrace@21..22 "{"
+            CloseBrace@22..23 "}"
+      Whitespace@23..24 " "
+      CloseBrace@24..25 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_enum_stmt() {
+        check(
+            "pub enum Shape { Circle(f64), Rect { w: f64, h: f64 }, Empty }",
+            r#"Root@0..62
+  EnumStmt@0..62
+    Visibility@0..3
+      PubKeyword@0..3 "pub"
+    Whitespace@3..4 " "
+    EnumKeyword@4..8 "enum"
+    Whitespace@8..9 " "
+    Identifier@9..14 "Shape"
+    Whitespace@14..15 " "
+    VariantList@15..62
+      OpenBrace@15..16 "{"
+      Whitespace@16..17 " "
+      Variant@17..28
+        Identifier@17..23 "Circle"
+        TypeList@23..28
+          OpenParen@23..24 "("
+          PathType@24..27
+            Identifier@24..27 "f64"
+          CloseParen@27..28 ")"
+      Comma@28..29 ","
+      Whitespace@29..30 " "
+      Variant@30..53
+        Identifier@30..34 "Rect"
+        Whitespace@34..35 " "
+        RecordFieldList@35..53
+          OpenBrace@35..36 "{"
+          Whitespace@36..37 " "
+          RecordField@37..43
+            Identifier@37..38 "w"
+            Colon@38..39 ":"
+            Whitespace@39..40 " "
+            PathType@40..43
+              Identifier@40..43 "f64"
+          Comma@43..44 ","
+          Whitespace@44..45 " "
+          RecordField@45..51
+            Identifier@45..46 "h"
+            Colon@46..47 ":"
+            Whitespace@47..48 " "
+            PathType@48..51
+              Identifier@48..51 "f64"
+          Whitespace@51..52 " "
+          CloseBrace@52..53 "}"
+      Comma@53..54 ","
+      Whitespace@54..55 " "
+      Variant@55..60
+        Identifier@55..60 "Empty"
+      Whitespace@60..61 " "
+      CloseBrace@61..62 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_generic_enum() {
+        check(
+            "enum Option<T> { Some(T), None }",
+            r#"Root@0..32
+  EnumStmt@0..32
+    EnumKeyword@0..4 "enum"
+    Whitespace@4..5 " "
+    Identifier@5..11 "Option"
+    GenericParamList@11..14
+      LessThan@11..12 "<"
+      GenericParam@12..13
+        Identifier@12..13 "T"
+      GreaterThan@13..14 ">"
+    Whitespace@14..15 " "
+    VariantList@15..32
+      OpenBrace@15..16 "{"
+      Whitespace@16..17 " "
+      Variant@17..24
+        Identifier@17..21 "Some"
+        TypeList@21..24
+          OpenParen@21..22 "("
+          PathType@22..23
+            Identifier@22..23 "T"
+          CloseParen@23..24 ")"
+      Comma@24..25 ","
+      Whitespace@25..26 " "
+      Variant@26..30
+        Identifier@26..30 "None"
+      Whitespace@30..31 " "
+      CloseBrace@31..32 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_generic_struct() {
+        check(
+            "pub struct Pair<T> { a: T, b: i64 }",
+            r#"Root@0..35
+  StructStmt@0..35
+    Visibility@0..3
+      PubKeyword@0..3 "pub"
+    Whitespace@3..4 " "
+    StructKeyword@4..10 "struct"
+    Whitespace@10..11 " "
+    Identifier@11..15 "Pair"
+    GenericParamList@15..18
+      LessThan@15..16 "<"
+      GenericParam@16..17
+        Identifier@16..17 "T"
+      GreaterThan@17..18 ">"
+    Whitespace@18..19 " "
+    RecordFieldList@19..35
+      OpenBrace@19..20 "{"
+      Whitespace@20..21 " "
+      RecordField@21..25
+        Identifier@21..22 "a"
+        Colon@22..23 ":"
+        Whitespace@23..24 " "
+        PathType@24..25
+          Identifier@24..25 "T"
+      Comma@25..26 ","
+      Whitespace@26..27 " "
+      RecordField@27..33
+        Identifier@27..28 "b"
+        Colon@28..29 ":"
+        Whitespace@29..30 " "
+        PathType@30..33
+          Identifier@30..33 "i64"
+      Whitespace@33..34 " "
+      CloseBrace@34..35 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_use_stmt() {
+        check(
+            "pub use foo::bar;",
+            r#"Root@0..17
+  UseStmt@0..17
+    Visibility@0..3
+      PubKeyword@0..3 "pub"
+    Whitespace@3..4 " "
+    UseKeyword@4..7 "use"
+    Whitespace@7..8 " "
+    Identifier@8..11 "foo"
+    ColonColon@11..13 "::"
+    Identifier@13..16 "bar"
+    Semicolon@16..17 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_pub_fn() {
+        check(
+            "pub fn f() {}",
+            r#"Root@0..13
+  ExprStmt@0..13
+    FunctionExpr@0..13
+      Visibility@0..3
+        PubKeyword@0..3 "pub"
+      Whitespace@3..4 " "
+      FunctionKeyword@4..6 "fn"
+      Whitespace@6..7 " "
+      Identifier@7..8 "f"
+      ParamList@8..10
+        OpenParen@8..9 "("
+        CloseParen@9..10 ")"
+      Whitespace@10..11 " "
+      BlockExpr@11..13
+        OpenBrace@11..12 "{"
+        CloseBrace@12..13 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_pub_without_item() {
+        check(
+            "pub let a = 1;",
+            r#"Root@0..14
+  Visibility@0..3
+    PubKeyword@0..3 "pub"
+  Whitespace@3..4 " "
+  LetStmt@4..14
+    LetKeyword@4..7 "let"
+    Whitespace@7..8 " "
+    Identifier@8..9 "a"
+    Whitespace@9..10 " "
+    SingleEqual@10..11 "="
+    Whitespace@11..12 " "
+    Literal@12..13
+      Number@12..13 "1"
+    Semicolon@13..14 ";"
+error at 4..7: expected `fn`, `mod`, `use`, `enum`, `struct` or `trait`, found `let`
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_stray_closing_brace() {
+        check(
+            "}",
+            r#"Root@0..1
+  Error@0..1
+    CloseBrace@0..1 "}"
+error at 0..1: expected statement, found `}`
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_trait_stmt() {
+        check(
+            "pub trait Show { fn show(self) -> str; fn print(self) { show(self) } }",
+            r#"Root@0..70
+  TraitStmt@0..70
+    Visibility@0..3
+      PubKeyword@0..3 "pub"
+    Whitespace@3..4 " "
+    TraitKeyword@4..9 "trait"
+    Whitespace@9..10 " "
+    Identifier@10..14 "Show"
+    Whitespace@14..15 " "
+    ItemList@15..70
+      OpenBrace@15..16 "{"
+      Whitespace@16..17 " "
+      ExprStmt@17..38
+        FunctionExpr@17..37
+          FunctionKeyword@17..19 "fn"
+          Whitespace@19..20 " "
+          Identifier@20..24 "show"
+          ParamList@24..30
+            OpenParen@24..25 "("
+            Param@25..29
+              Identifier@25..29 "self"
+            CloseParen@29..30 ")"
+          Whitespace@30..31 " "
+          RightArrow@31..33 "->"
+          Whitespace@33..34 " "
+          PathType@34..37
+            Identifier@34..37 "str"
+        Semicolon@37..38 ";"
+      Whitespace@38..39 " "
+      ExprStmt@39..68
+        FunctionExpr@39..68
+          FunctionKeyword@39..41 "fn"
+          Whitespace@41..42 " "
+          Identifier@42..47 "print"
+          ParamList@47..53
+            OpenParen@47..48 "("
+            Param@48..52
+              Identifier@48..52 "self"
+            CloseParen@52..53 ")"
+          Whitespace@53..54 " "
+          BlockExpr@54..68
+            OpenBrace@54..55 "{"
+            Whitespace@55..56 " "
+            ExprStmt@56..66
+              CallExpr@56..66
+                VariableRef@56..60
+                  Identifier@56..60 "show"
+                ArgList@60..66
+                  OpenParen@60..61 "("
+                  VariableRef@61..65
+                    Identifier@61..65 "self"
+                  CloseParen@65..66 ")"
+            Whitespace@66..67 " "
+            CloseBrace@67..68 "}"
+      Whitespace@68..69 " "
+      CloseBrace@69..70 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_impl_stmts() {
+        check(
+            "impl Point { pub fn new() {} } impl<T: Show> Show for Wrapper<T> { fn show(self) {} }",
+            r#"Root@0..85
+  ImplStmt@0..30
+    ImplKeyword@0..4 "impl"
+    Whitespace@4..5 " "
+    PathType@5..10
+      Identifier@5..10 "Point"
+    Whitespace@10..11 " "
+    ItemList@11..30
+      OpenBrace@11..12 "{"
+      Whitespace@12..13 " "
+      ExprStmt@13..28
+        FunctionExpr@13..28
+          Visibility@13..16
+            PubKeyword@13..16 "pub"
+          Whitespace@16..17 " "
+          FunctionKeyword@17..19 "fn"
+          Whitespace@19..20 " "
+          Identifier@20..23 "new"
+          ParamList@23..25
+            OpenParen@23..24 "("
+            CloseParen@24..25 ")"
+          Whitespace@25..26 " "
+          BlockExpr@26..28
+            OpenBrace@26..27 "{"
+            CloseBrace@27..28 "}"
+      Whitespace@28..29 " "
+      CloseBrace@29..30 "}"
+  Whitespace@30..31 " "
+  ImplStmt@31..85
+    ImplKeyword@31..35 "impl"
+    GenericParamList@35..44
+      LessThan@35..36 "<"
+      GenericParam@36..43
+        Identifier@36..37 "T"
+        TypeBoundList@37..43
+          Colon@37..38 ":"
+          Whitespace@38..39 " "
+          PathType@39..43
+            Identifier@39..43 "Show"
+      GreaterThan@43..44 ">"
+    Whitespace@44..45 " "
+    PathType@45..49
+      Identifier@45..49 "Show"
+    Whitespace@49..50 " "
+    ForKeyword@50..53 "for"
+    Whitespace@53..54 " "
+    PathType@54..64
+      Identifier@54..61 "Wrapper"
+      GenericArgList@61..64
+        LessThan@61..62 "<"
+        PathType@62..63
+          Identifier@62..63 "T"
+        GreaterThan@63..64 ">"
+    Whitespace@64..65 " "
+    ItemList@65..85
+      OpenBrace@65..66 "{"
+      Whitespace@66..67 " "
+      ExprStmt@67..83
+        FunctionExpr@67..83
+          FunctionKeyword@67..69 "fn"
+          Whitespace@69..70 " "
+          Identifier@70..74 "show"
+          ParamList@74..80
+            OpenParen@74..75 "("
+            Param@75..79
+              Identifier@75..79 "self"
+            CloseParen@79..80 ")"
+          Whitespace@80..81 " "
+          BlockExpr@81..83
+            OpenBrace@81..82 "{"
+            CloseBrace@82..83 "}"
+      Whitespace@83..84 " "
+      CloseBrace@84..85 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_bodyless_function_outside_trait() {
+        check(
+            "impl A { fn f(); }",
+            r#"Root@0..18
+  ImplStmt@0..18
+    ImplKeyword@0..4 "impl"
+    Whitespace@4..5 " "
+    PathType@5..6
+      Identifier@5..6 "A"
+    Whitespace@6..7 " "
+    ItemList@7..18
+      OpenBrace@7..8 "{"
+      Whitespace@8..9 " "
+      ExprStmt@9..16
+        FunctionExpr@9..15
+          FunctionKeyword@9..11 "fn"
+          Whitespace@11..12 " "
+          Identifier@12..13 "f"
+          ParamList@13..15
+            OpenParen@13..14 "("
+            CloseParen@14..15 ")"
+        Semicolon@15..16 ";"
+      Whitespace@16..17 " "
+      CloseBrace@17..18 "}"
+error at 15..16: expected `{`, found `;`
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_attrs() {
+        check(
+            "#![inline]\n#[test] #[deprecated(note = \"old\", 1)] pub fn f() {}",
+            r##"Root@0..63
+  Attr@0..10
+    Pound@0..1 "#"
+    Bang@1..2 "!"
+    OpenSquare@2..3 "["
+    Identifier@3..9 "inline"
+    CloseSquare@9..10 "]"
+  Whitespace@10..11 "\n"
+  ExprStmt@11..63
+    FunctionExpr@11..63
+      Attr@11..18
+        Pound@11..12 "#"
+        OpenSquare@12..13 "["
+        Identifier@13..17 "test"
+        CloseSquare@17..18 "]"
+      Whitespace@18..19 " "
+      Attr@19..49
+        Pound@19..20 "#"
+        OpenSquare@20..21 "["
+        Identifier@21..31 "deprecated"
+        AttrArgList@31..48
+          OpenParen@31..32 "("
+          AttrArg@32..44
+            Identifier@32..36 "note"
+            Whitespace@36..37 " "
+            SingleEqual@37..38 "="
+            Whitespace@38..39 " "
+            Literal@39..44
+              String@39..44 "\"old\""
+          Comma@44..45 ","
+          Whitespace@45..46 " "
+          AttrArg@46..47
+            Literal@46..47
+              Number@46..47 "1"
+          CloseParen@47..48 ")"
+        CloseSquare@48..49 "]"
+      Whitespace@49..50 " "
+      Visibility@50..53
+        PubKeyword@50..53 "pub"
+      Whitespace@53..54 " "
+      FunctionKeyword@54..56 "fn"
+      Whitespace@56..57 " "
+      Identifier@57..58 "f"
+      ParamList@58..60
+        OpenParen@58..59 "("
+        CloseParen@59..60 ")"
+      Whitespace@60..61 " "
+      BlockExpr@61..63
+        OpenBrace@61..62 "{"
+        CloseBrace@62..63 "}"
+"##,
+        );
+    }
+
+    #[test]
+    fn parse_attrs_on_impls() {
+        check(
+            "#[inline(always)] impl A { #![a] #[b] fn f() {} }",
+            r##"Root@0..49
+  ImplStmt@0..49
+    Attr@0..17
+      Pound@0..1 "#"
+      OpenSquare@1..2 "["
+      Identifier@2..8 "inline"
+      AttrArgList@8..16
+        OpenParen@8..9 "("
+        AttrArg@9..15
+          Identifier@9..15 "always"
+        CloseParen@15..16 ")"
+      CloseSquare@16..17 "]"
+    Whitespace@17..18 " "
+    ImplKeyword@18..22 "impl"
+    Whitespace@22..23 " "
+    PathType@23..24
+      Identifier@23..24 "A"
+    Whitespace@24..25 " "
+    ItemList@25..49
+      OpenBrace@25..26 "{"
+      Whitespace@26..27 " "
+      Attr@27..32
+        Pound@27..28 "#"
+        Bang@28..29 "!"
+        OpenSquare@29..30 "["
+        Identifier@30..31 "a"
+        CloseSquare@31..32 "]"
+      Whitespace@32..33 " "
+      ExprStmt@33..47
+        FunctionExpr@33..47
+          Attr@33..37
+            Pound@33..34 "#"
+            OpenSquare@34..35 "["
+            Identifier@35..36 "b"
+            CloseSquare@36..37 "]"
+          Whitespace@37..38 " "
+          FunctionKeyword@38..40 "fn"
+          Whitespace@40..41 " "
+          Identifier@41..42 "f"
+          ParamList@42..44
+            OpenParen@42..43 "("
+            CloseParen@43..44 ")"
+          Whitespace@44..45 " "
+          BlockExpr@45..47
+            OpenBrace@45..46 "{"
+            CloseBrace@46..47 "}"
+      Whitespace@47..48 " "
+      CloseBrace@48..49 "}"
+"##,
+        );
+    }
+
+    #[test]
+    fn parse_attr_errors() {
+        check(
+            "#[a(b = c)] #[] enum E {} #[d] let a = 1;",
+            r##"Root@0..41
+  EnumStmt@0..25
+    Attr@0..11
+      Pound@0..1 "#"
+      OpenSquare@1..2 "["
+      Identifier@2..3 "a"
+      AttrArgList@3..10
+        OpenParen@3..4 "("
+        AttrArg@4..7
+          Identifier@4..5 "b"
+          Whitespace@5..6 " "
+          SingleEqual@6..7 "="
+        Whitespace@7..8 " "
+        AttrArg@8..9
+          Identifier@8..9 "c"
+        CloseParen@9..10 ")"
+      CloseSquare@10..11 "]"
+    Whitespace@11..12 " "
+    Attr@12..15
+      Pound@12..13 "#"
+      OpenSquare@13..14 "["
+      CloseSquare@14..15 "]"
+    Whitespace@15..16 " "
+    EnumKeyword@16..20 "enum"
+    Whitespace@20..21 " "
+    Identifier@21..22 "E"
+    Whitespace@22..23 " "
+    VariantList@23..25
+      OpenBrace@23..24 "{"
+      CloseBrace@24..25 "}"
+  Whitespace@25..26 " "
+  Attr@26..30
+    Pound@26..27 "#"
+    OpenSquare@27..28 "["
+    Identifier@28..29 "d"
+    CloseSquare@29..30 "]"
+  Whitespace@30..31 " "
+  LetStmt@31..41
+    LetKeyword@31..34 "let"
+    Whitespace@34..35 " "
+    Identifier@35..36 "a"
+    Whitespace@36..37 " "
+    SingleEqual@37..38 "="
+    Whitespace@38..39 " "
+    Literal@39..40
+      Number@39..40 "1"
+    Semicolon@40..41 ";"
+error at 8..9: expected literal, found identifier
+error at 8..9: expected `,`, found identifier
+error at 14..15: expected identifier, found `]`
+error at 31..34: expected `fn`, `mod`, `use`, `enum`, `struct`, `trait` or `impl`, found `let`
+"##,
+        );
+    }
+}
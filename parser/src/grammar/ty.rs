@@ -0,0 +1,529 @@
+use super::{delimited_list, expr, path};
+use crate::{syntax::SyntaxKind, Parser};
+
+/// Tokens that can start a type.
+pub(super) const TYPE_FIRST: &[SyntaxKind] = &[
+    SyntaxKind::Identifier,
+    SyntaxKind::OpenSquare,
+    SyntaxKind::OpenParen,
+    SyntaxKind::FunctionKeyword,
+    SyntaxKind::Ampersand,
+    SyntaxKind::LogicalAnd,
+];
+
+pub(super) fn ty(p: &mut Parser) {
+    p.split_logical_and();
+    let checkpoint = p.checkpoint();
+
+    match p.peek() {
+        Some(SyntaxKind::Identifier) => path_type(p),
+        Some(SyntaxKind::OpenSquare) => array_or_slice_type(p),
+        Some(SyntaxKind::FunctionKeyword) => fn_type(p),
+        // `()` is the unit type, `(T)` is `T` and `(T,)` is a tuple of one element
+        Some(SyntaxKind::OpenParen) => delimited_list(
+            p,
+            SyntaxKind::TupleType,
+            SyntaxKind::CloseParen,
+            TYPE_FIRST,
+            ty,
+            "type",
+        ),
+        Some(SyntaxKind::Ampersand) => ref_type(p),
+        _ => {
+            p.error("expected type");
+            return;
+        }
+    }
+
+    // `T?` is `Option<T>`, and `&T?` is a reference to an optional value
+    while p.at(SyntaxKind::Question) {
+        p.start_node_at(checkpoint, SyntaxKind::OptionalType);
+        p.bump();
+        p.finish_node();
+    }
+}
+
+/// Parses `&T` or `&mut T`.
+fn ref_type(p: &mut Parser) {
+    p.start_node(SyntaxKind::RefType);
+    p.bump();
+    p.eat(SyntaxKind::MutKeyword);
+    ty(p);
+    p.finish_node();
+}
+
+/// Parses `m::T`, or `T<A, B>` with generic arguments. Unlike in expressions, a `<` after
+/// a path always starts generic arguments.
+pub(super) fn path_type(p: &mut Parser) {
+    p.start_node(SyntaxKind::PathType);
+    path(p);
+    if p.at(SyntaxKind::LessThan) {
+        generic_arg_list(p);
+    }
+    p.finish_node();
+}
+
+/// Parses `<A, B>`, the generic arguments of a path.
+pub(super) fn generic_arg_list(p: &mut Parser) {
+    delimited_list(
+        p,
+        SyntaxKind::GenericArgList,
+        SyntaxKind::GreaterThan,
+        TYPE_FIRST,
+        ty,
+        "type",
+    );
+}
+
+/// Parses `[T; N]` arrays and `[T]` slices.
+fn array_or_slice_type(p: &mut Parser) {
+    let checkpoint = p.checkpoint();
+    p.bump();
+    ty(p);
+
+    let kind = if p.eat(SyntaxKind::Semicolon) {
+        expr::expr(p);
+        SyntaxKind::ArrayType
+    } else {
+        SyntaxKind::SliceType
+    };
+
+    p.expect(SyntaxKind::CloseSquare);
+    p.start_node_at(checkpoint, kind);
+    p.finish_node();
+}
+
+/// Parses `fn(A, B) -> C`, the type of functions and closures.
+fn fn_type(p: &mut Parser) {
+    p.start_node(SyntaxKind::FnType);
+    p.bump();
+
+    if p.at(SyntaxKind::OpenParen) {
+        delimited_list(
+            p,
+            SyntaxKind::TypeList,
+            SyntaxKind::CloseParen,
+            TYPE_FIRST,
+            ty,
+            "type",
+        );
+    } else {
+        p.error("expected `(`");
+    }
+
+    if p.eat(SyntaxKind::RightArrow) {
+        ty(p);
+    }
+
+    p.finish_node();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{grammar::check, Parser};
+
+    fn check_type(input: &str, expected: &str) {
+        let fragment = Parser::new(input).parse_type();
+        assert_eq!(fragment.green_tree.debug_tree(), expected);
+    }
+
+    #[test]
+    fn parse_path_type() {
+        check(
+            "let a: m::T = b;",
+            r#"Root@0..16
+  LetStmt@0..16
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..5 "a"
+    Colon@5..6 ":"
+    Whitespace@6..7 " "
+    PathType@7..11
+      Identifier@7..8 "m"
+      ColonColon@8..10 "::"
+      Identifier@10..11 "T"
+    Whitespace@11..12 " "
+    SingleEqual@12..13 "="
+    Whitespace@13..14 " "
+    VariableRef@14..15
+      Identifier@14..15 "b"
+    Semicolon@15..16 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_array_and_slice_types() {
+        check(
+            "fn f(a: [[u8; 4]]) -> [i32; N] {}",
+            r#"Root@0..33
+  ExprStmt@0..33
+    FunctionExpr@0..33
+      FunctionKeyword@0..2 "fn"
+      Whitespace@2..3 " "
+      Identifier@3..4 "f"
+      ParamList@4..18
+        OpenParen@4..5 "("
+        Param@5..17
+          Identifier@5..6 "a"
+          Colon@6..7 ":"
+          Whitespace@7..8 " "
+          SliceType@8..17
+            OpenSquare@8..9 "["
+            ArrayType@9..16
+              OpenSquare@9..10 "["
+              PathType@10..12
+                Identifier@10..12 "u8"
+              Semicolon@12..13 ";"
+              Whitespace@13..14 " "
+              Literal@14..15
+                Number@14..15 "4"
+              CloseSquare@15..16 "]"
+            CloseSquare@16..17 "]"
+        CloseParen@17..18 ")"
+      Whitespace@18..19 " "
+      RightArrow@19..21 "->"
+      Whitespace@21..22 " "
+      ArrayType@22..30
+        OpenSquare@22..23 "["
+        PathType@23..26
+          Identifier@23..26 "i32"
+        Semicolon@26..27 ";"
+        Whitespace@27..28 " "
+        VariableRef@28..29
+          Identifier@28..29 "N"
+        CloseSquare@29..30 "]"
+      Whitespace@30..31 " "
+      BlockExpr@31..33
+        OpenBrace@31..32 "{"
+        CloseBrace@32..33 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_fn_type() {
+        check(
+            "fn apply(f: fn(i32, [u8]) -> fn() -> i32) {}",
+            r#"Root@0..44
+  ExprStmt@0..44
+    FunctionExpr@0..44
+      FunctionKeyword@0..2 "fn"
+      Whitespace@2..3 " "
+      Identifier@3..8 "apply"
+      ParamList@8..41
+        OpenParen@8..9 "("
+        Param@9..40
+          Identifier@9..10 "f"
+          Colon@10..11 ":"
+          Whitespace@11..12 " "
+          FnType@12..40
+            FunctionKeyword@12..14 "fn"
+            TypeList@14..25
+              OpenParen@14..15 "("
+              PathType@15..18
+                Identifier@15..18 "i32"
+              Comma@18..19 ","
+              Whitespace@19..20 " "
+              SliceType@20..24
+                OpenSquare@20..21 "["
+                PathType@21..23
+                  Identifier@21..23 "u8"
+                CloseSquare@23..24 "]"
+              CloseParen@24..25 ")"
+            Whitespace@25..26 " "
+            RightArrow@26..28 "->"
+            Whitespace@28..29 " "
+            FnType@29..40
+              FunctionKeyword@29..31 "fn"
+              TypeList@31..33
+                OpenParen@31..32 "("
+                CloseParen@32..33 ")"
+              Whitespace@33..34 " "
+              RightArrow@34..36 "->"
+              Whitespace@36..37 " "
+              PathType@37..40
+                Identifier@37..40 "i32"
+        CloseParen@40..41 ")"
+      Whitespace@41..42 " "
+      BlockExpr@42..44
+        OpenBrace@42..43 "{"
+        CloseBrace@43..44 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_generic_args() {
+        check(
+            "let a: m::Pair<i64, Option<[T]>>= b;",
+            r#"Root@0..36
+  LetStmt@0..36
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..5 "a"
+    Colon@5..6 ":"
+    Whitespace@6..7 " "
+    PathType@7..32
+      Identifier@7..8 "m"
+      ColonColon@8..10 "::"
+      Identifier@10..14 "Pair"
+      GenericArgList@14..32
+        LessThan@14..15 "<"
+        PathType@15..18
+          Identifier@15..18 "i64"
+        Comma@18..19 ","
+        Whitespace@19..20 " "
+        PathType@20..31
+          Identifier@20..26 "Option"
+          GenericArgList@26..31
+            LessThan@26..27 "<"
+            SliceType@27..30
+              OpenSquare@27..28 "["
+              PathType@28..29
+                Identifier@28..29 "T"
+              CloseSquare@29..30 "]"
+            GreaterThan@30..31 ">"
+        GreaterThan@31..32 ">"
+    SingleEqual@32..33 "="
+    Whitespace@33..34 " "
+    VariableRef@34..35
+      Identifier@34..35 "b"
+    Semicolon@35..36 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_missing_type() {
+        check(
+            "let a: = 1;",
+            r#"Root@0..11
+  LetStmt@0..11
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..5 "a"
+    Colon@5..6 ":"
+    Whitespace@6..7 " "
+    SingleEqual@7..8 "="
+    Whitespace@8..9 " "
+    Literal@9..10
+      Number@9..10 "1"
+    Semicolon@10..11 ";"
+error at 7..8: expected type, found `=`
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_tuple_types() {
+        check(
+            "let a: ((), (i32,), (bool), (u8, T)) = b;",
+            r#"Root@0..41
+  LetStmt@0..41
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..5 "a"
+    Colon@5..6 ":"
+    Whitespace@6..7 " "
+    TupleType@7..36
+      OpenParen@7..8 "("
+      TupleType@8..10
+        OpenParen@8..9 "("
+        CloseParen@9..10 ")"
+      Comma@10..11 ","
+      Whitespace@11..12 " "
+      TupleType@12..18
+        OpenParen@12..13 "("
+        PathType@13..16
+          Identifier@13..16 "i32"
+        Comma@16..17 ","
+        CloseParen@17..18 ")"
+      Comma@18..19 ","
+      Whitespace@19..20 " "
+      TupleType@20..26
+        OpenParen@20..21 "("
+        PathType@21..25
+          Identifier@21..25 "bool"
+        CloseParen@25..26 ")"
+      Comma@26..27 ","
+      Whitespace@27..28 " "
+      TupleType@28..35
+        OpenParen@28..29 "("
+        PathType@29..31
+          Identifier@29..31 "u8"
+        Comma@31..32 ","
+        Whitespace@32..33 " "
+        PathType@33..34
+          Identifier@33..34 "T"
+        CloseParen@34..35 ")"
+      CloseParen@35..36 ")"
+    Whitespace@36..37 " "
+    SingleEqual@37..38 "="
+    Whitespace@38..39 " "
+    VariableRef@39..40
+      Identifier@39..40 "b"
+    Semicolon@40..41 ";"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_ref_types() {
+        check(
+            "fn f(a: &T, b: &mut [u8]) -> &&i32 {}",
+            r#"Root@0..37
+  ExprStmt@0..37
+    FunctionExpr@0..37
+      FunctionKeyword@0..2 "fn"
+      Whitespace@2..3 " "
+      Identifier@3..4 "f"
+      ParamList@4..25
+        OpenParen@4..5 "("
+        Param@5..10
+          Identifier@5..6 "a"
+          Colon@6..7 ":"
+          Whitespace@7..8 " "
+          RefType@8..10
+            Ampersand@8..9 "&"
+            PathType@9..10
+              Identifier@9..10 "T"
+        Comma@10..11 ","
+        Whitespace@11..12 " "
+        Param@12..24
+          Identifier@12..13 "b"
+          Colon@13..14 ":"
+          Whitespace@14..15 " "
+          RefType@15..24
+            Ampersand@15..16 "&"
+            MutKeyword@16..19 "mut"
+            Whitespace@19..20 " "
+            SliceType@20..24
+              OpenSquare@20..21 "["
+              PathType@21..23
+                Identifier@21..23 "u8"
+              CloseSquare@23..24 "]"
+        CloseParen@24..25 ")"
+      Whitespace@25..26 " "
+      RightArrow@26..28 "->"
+      Whitespace@28..29 " "
+      RefType@29..34
+        Ampersand@29..30 "&"
+        RefType@30..34
+          Ampersand@30..31 "&"
+          PathType@31..34
+            Identifier@31..34 "i32"
+      Whitespace@34..35 " "
+      BlockExpr@35..37
+        OpenBrace@35..36 "{"
+        CloseBrace@36..37 "}"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_optional_types() {
+        check(
+            "let a: Option<T>?? = b;",
+            r#"Root@0..23
+  LetStmt@0..23
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..5 "a"
+    Colon@5..6 ":"
+    Whitespace@6..7 " "
+    OptionalType@7..18
+      OptionalType@7..17
+        PathType@7..16
+          Identifier@7..13 "Option"
+          GenericArgList@13..16
+            LessThan@13..14 "<"
+            PathType@14..15
+              Identifier@14..15 "T"
+            GreaterThan@15..16 ">"
+        Question@16..17 "?"
+      Question@17..18 "?"
+    Whitespace@18..19 " "
+    SingleEqual@19..20 "="
+    Whitespace@20..21 " "
+    VariableRef@21..22
+      Identifier@21..22 "b"
+    Semicolon@22..23 ";"
+"#,
+        );
+        check_type(
+            "&T?",
+            r#"Root@0..3
+  RefType@0..3
+    Ampersand@0..1 "&"
+    OptionalType@1..3
+      PathType@1..2
+        Identifier@1..2 "T"
+      Question@2..3 "?"
+"#,
+        );
+        check_type(
+            "fn() -> T?",
+            r#"Root@0..10
+  FnType@0..10
+    FunctionKeyword@0..2 "fn"
+    TypeList@2..4
+      OpenParen@2..3 "("
+      CloseParen@3..4 ")"
+    Whitespace@4..5 " "
+    RightArrow@5..7 "->"
+    Whitespace@7..8 " "
+    OptionalType@8..10
+      PathType@8..9
+        Identifier@8..9 "T"
+      Question@9..10 "?"
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_type_entry_point() {
+        check_type(
+            " (i32, m::P<u8>) ",
+            r#"Root@0..17
+  Whitespace@0..1 " "
+  TupleType@1..16
+    OpenParen@1..2 "("
+    PathType@2..5
+      Identifier@2..5 "i32"
+    Comma@5..6 ","
+    Whitespace@6..7 " "
+    PathType@7..15
+      Identifier@7..8 "m"
+      ColonColon@8..10 "::"
+      Identifier@10..11 "P"
+      GenericArgList@11..15
+        LessThan@11..12 "<"
+        PathType@12..14
+          Identifier@12..14 "u8"
+        GreaterThan@14..15 ">"
+    CloseParen@15..16 ")"
+  Whitespace@16..17 " "
+"#,
+        );
+        check_type(
+            "i32 + 1",
+            r#"Root@0..7
+  PathType@0..3
+    Identifier@0..3 "i32"
+  Whitespace@3..4 " "
+  Error@4..7
+    Plus@4..5 "+"
+    Whitespace@5..6 " "
+    Number@6..7 "1"
+error at 4..5: expected end of input, found `+`
+"#,
+        );
+        check_type(
+            "",
+            r#"Root@0..0
+error at 0..0: expected type, found end of input
+"#,
+        );
+    }
+}
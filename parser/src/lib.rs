@@ -1,42 +1,372 @@
+pub mod ast;
+mod grammar;
+pub mod literal;
 pub mod syntax;
 
+use std::{fmt, marker::PhantomData};
+
+use ast::{AstNode, Expr, Stmt, Type};
 use lexer::Lexer;
-use rowan::{GreenNode, GreenNodeBuilder};
-use syntax::SyntaxKind;
+use rowan::{Checkpoint, GreenNode, GreenNodeBuilder, TextRange, TextSize};
+use syntax::{SyntaxKind, SyntaxNode};
+
+/// A token as seen by the parser: its kind, source text and position.
+#[derive(Debug, Clone, Copy)]
+struct Lexeme<'a> {
+    kind: SyntaxKind,
+    text: &'a str,
+    range: TextRange,
+}
 
 #[derive(Debug)]
 pub struct Parser<'a> {
-    pub lexer: Lexer<'a>,
-    pub builder: GreenNodeBuilder<'static>,
+    lexemes: Vec<Lexeme<'a>>,
+    cursor: usize,
+    builder: GreenNodeBuilder<'static>,
+    errors: Vec<ParseError>,
+    /// Set while parsing conditions, where `a { .. }` is a path followed by a block
+    /// rather than a record expression.
+    no_record_exprs: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub range: TextRange,
 }
 
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error at {}..{}: {}",
+            u32::from(self.range.start()),
+            u32::from(self.range.end()),
+            self.message
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GreenTree {
     pub green_node: GreenNode,
-    pub errors: Vec<String>, // TODO: create custom error
+    pub errors: Vec<ParseError>,
+}
+
+impl GreenTree {
+    pub fn syntax(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green_node.clone())
+    }
+
+    /// Renders the tree followed by the errors, one per line. Mostly useful for tests.
+    pub fn debug_tree(&self) -> String {
+        let mut s = format!("{:#?}", self.syntax());
+        for error in &self.errors {
+            s.push_str(&format!("{error}\n"));
+        }
+        s
+    }
+}
+
+/// A fragment of a program parsed on its own, such as an expression typed in a REPL.
+/// Input left after the fragment is reported as an error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment<N> {
+    /// A `Root` holding the fragment, the trivia around it and any input left after it.
+    pub green_tree: GreenTree,
+    node: PhantomData<N>,
+}
+
+impl<N: AstNode> Fragment<N> {
+    /// The parsed fragment, or `None` if the input doesn't start with one.
+    pub fn node(&self) -> Option<N> {
+        self.green_tree.syntax().children().find_map(N::cast)
+    }
+
+    pub fn errors(&self) -> &[ParseError] {
+        &self.green_tree.errors
+    }
 }
 
 impl<'a> Parser<'a> {
     pub fn new(input: &'a str) -> Self {
+        let mut lexemes = Vec::new();
+        let mut errors = Vec::new();
+        let mut offset = TextSize::from(0);
+
+        for (token, text) in Lexer::new(input) {
+            let range = TextRange::at(offset, TextSize::of(text));
+            offset = range.end();
+
+            let kind = match token {
+                Ok(token) => token.into(),
+                Err(()) => {
+                    errors.push(ParseError {
+                        message: format!("unrecognized token `{text}`"),
+                        range,
+                    });
+                    SyntaxKind::Error
+                }
+            };
+            lexemes.push(Lexeme { kind, text, range });
+        }
+
         Self {
-            lexer: Lexer::new(input),
+            lexemes,
+            cursor: 0,
             builder: GreenNodeBuilder::new(),
+            errors,
+            no_record_exprs: false,
         }
     }
 
     pub fn parse(mut self) -> GreenTree {
-        self.builder.start_node(SyntaxKind::Root.into());
-        self.builder.finish_node();
+        grammar::root(&mut self);
+        self.finish()
+    }
+
+    /// Parses the input as a single expression, such as `a + f(b)`.
+    pub fn parse_expr(self) -> Fragment<Expr> {
+        self.parse_fragment(grammar::expr_fragment)
+    }
+
+    /// Parses the input as a single statement, such as `let a = 1;`.
+    pub fn parse_stmt(self) -> Fragment<Stmt> {
+        self.parse_fragment(grammar::stmt_fragment)
+    }
+
+    /// Parses the input as a single type, such as `&mut [Option<T>; 4]`.
+    pub fn parse_type(self) -> Fragment<Type> {
+        self.parse_fragment(grammar::type_fragment)
+    }
+
+    /// Parses the input as a single declaration, such as a function or an `impl`.
+    pub fn parse_item(self) -> Fragment<Stmt> {
+        self.parse_fragment(grammar::item_fragment)
+    }
 
+    fn parse_fragment<N>(mut self, fragment: fn(&mut Self)) -> Fragment<N> {
+        fragment(&mut self);
+        Fragment {
+            green_tree: self.finish(),
+            node: PhantomData,
+        }
+    }
+
+    fn finish(self) -> GreenTree {
         GreenTree {
             green_node: self.builder.finish(),
-            errors: vec![],
+            errors: self.errors,
+        }
+    }
+
+    /// Returns the kind of the next non-trivia token.
+    fn peek(&self) -> Option<SyntaxKind> {
+        self.nth(0)
+    }
+
+    /// Returns the kind of the `n`-th non-trivia token after the cursor.
+    fn nth(&self, n: usize) -> Option<SyntaxKind> {
+        self.lexemes[self.cursor..]
+            .iter()
+            .filter(|lexeme| !lexeme.kind.is_trivia())
+            .nth(n)
+            .map(|lexeme| lexeme.kind)
+    }
+
+    fn at(&self, kind: SyntaxKind) -> bool {
+        self.peek() == Some(kind)
+    }
+
+    fn at_set(&self, set: &[SyntaxKind]) -> bool {
+        self.peek().is_some_and(|kind| set.contains(&kind))
+    }
+
+    fn at_end(&self) -> bool {
+        self.peek().is_none()
+    }
+
+    fn bump(&mut self) {
+        self.eat_trivia();
+        let lexeme = self.lexemes[self.cursor];
+        self.builder.token(lexeme.kind.into(), lexeme.text);
+        self.cursor += 1;
+    }
+
+    fn eat(&mut self, kind: SyntaxKind) -> bool {
+        if self.at(kind) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: SyntaxKind) {
+        if !self.eat(kind) {
+            self.error(format!("expected {kind}"));
+        }
+    }
+
+    /// Splits a float such as `0.1` into `0`, `.` and `1`, so that `t.0.1` accesses a field
+    /// of a field of `t`.
+    fn split_float(&mut self) {
+        let Some(index) =
+            (self.cursor..self.lexemes.len()).find(|&index| !self.lexemes[index].kind.is_trivia())
+        else {
+            return;
+        };
+
+        let lexeme = self.lexemes[index];
+        let Some((first, second)) = lexeme.text.split_once('.') else {
+            return;
+        };
+        if lexeme.kind != SyntaxKind::Number || !second.bytes().all(|b| b.is_ascii_digit()) {
+            return;
+        }
+
+        let dot = lexeme.range.start() + TextSize::of(first);
+        let second_start = dot + TextSize::of('.');
+        let split = [
+            Lexeme {
+                kind: SyntaxKind::Number,
+                text: first,
+                range: TextRange::new(lexeme.range.start(), dot),
+            },
+            Lexeme {
+                kind: SyntaxKind::Dot,
+                text: ".",
+                range: TextRange::new(dot, second_start),
+            },
+            Lexeme {
+                kind: SyntaxKind::Number,
+                text: second,
+                range: TextRange::new(second_start, lexeme.range.end()),
+            },
+        ];
+        self.lexemes.splice(index..=index, split);
+    }
+
+    /// Splits a token starting with `>`, such as `>>`, so that its first `>` can close
+    /// generic arguments, as in `Option<Option<T>>`.
+    fn split_greater_than(&mut self) {
+        self.split_first_char(SyntaxKind::GreaterThan, |kind| match kind {
+            SyntaxKind::ShiftRight => Some(SyntaxKind::GreaterThan),
+            SyntaxKind::GreaterThanEqual => Some(SyntaxKind::SingleEqual),
+            SyntaxKind::ShiftRightEqual => Some(SyntaxKind::GreaterThanEqual),
+            _ => None,
+        });
+    }
+
+    /// Splits `&&` into two `&`, so that `&&T` is a reference to a reference.
+    fn split_logical_and(&mut self) {
+        self.split_first_char(SyntaxKind::Ampersand, |kind| {
+            (kind == SyntaxKind::LogicalAnd).then_some(SyntaxKind::Ampersand)
+        });
+    }
+
+    /// Splits the first character off the next token into a `first` token, if `rest` gives
+    /// the kind of the remaining characters.
+    fn split_first_char(
+        &mut self,
+        first: SyntaxKind,
+        rest: impl FnOnce(SyntaxKind) -> Option<SyntaxKind>,
+    ) {
+        let Some(index) =
+            (self.cursor..self.lexemes.len()).find(|&index| !self.lexemes[index].kind.is_trivia())
+        else {
+            return;
+        };
+
+        let lexeme = self.lexemes[index];
+        let Some(rest) = rest(lexeme.kind) else {
+            return;
+        };
+
+        let (first_text, rest_text) = lexeme.text.split_at(1);
+        let middle = lexeme.range.start() + TextSize::of(first_text);
+        let split = [
+            Lexeme {
+                kind: first,
+                text: first_text,
+                range: TextRange::new(lexeme.range.start(), middle),
+            },
+            Lexeme {
+                kind: rest,
+                text: rest_text,
+                range: TextRange::new(middle, lexeme.range.end()),
+            },
+        ];
+        self.lexemes.splice(index..=index, split);
+    }
+
+    fn eat_trivia(&mut self) {
+        while let Some(lexeme) = self.lexemes.get(self.cursor) {
+            if !lexeme.kind.is_trivia() {
+                break;
+            }
+            self.builder.token(lexeme.kind.into(), lexeme.text);
+            self.cursor += 1;
         }
     }
+
+    /// Starts a node at the next non-trivia token, leaving leading trivia to the parent.
+    fn start_node(&mut self, kind: SyntaxKind) {
+        self.eat_trivia();
+        self.builder.start_node(kind.into());
+    }
+
+    fn finish_node(&mut self) {
+        self.builder.finish_node();
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        self.eat_trivia();
+        self.builder.checkpoint()
+    }
+
+    fn start_node_at(&mut self, checkpoint: Checkpoint, kind: SyntaxKind) {
+        self.builder.start_node_at(checkpoint, kind.into());
+    }
+
+    /// Records an error at the next non-trivia token, appending a description of it.
+    fn error(&mut self, message: impl Into<String>) {
+        let next = self.lexemes[self.cursor..]
+            .iter()
+            .find(|lexeme| !lexeme.kind.is_trivia());
+
+        let (found, range) = match next {
+            // lexing errors have already been reported
+            Some(lexeme) if lexeme.kind == SyntaxKind::Error => return,
+            Some(lexeme) => (lexeme.kind.to_string(), lexeme.range),
+            None => {
+                let end = self.lexemes.last().map_or(0.into(), |l| l.range.end());
+                ("end of input".to_string(), TextRange::empty(end))
+            }
+        };
+
+        self.errors.push(ParseError {
+            message: format!("{}, found {found}", message.into()),
+            range,
+        });
+    }
+
+    /// Records an error and wraps the next token in an `Error` node.
+    fn error_and_bump(&mut self, message: impl Into<String>) {
+        self.error(message);
+        self.start_node(SyntaxKind::Error);
+        self.bump();
+        self.finish_node();
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{syntax::SyntaxNode, Parser};
+    use crate::{
+        ast::{AstNode, Expr, Stmt},
+        syntax::SyntaxNode,
+        Parser,
+    };
 
     #[test]
     fn parse_nothing() {
@@ -45,6 +375,157 @@ mod tests {
         assert_eq!(
             format!("{:#?}", SyntaxNode::new_root(green_tree.green_node)),
             r#"Root@0..0
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_whitespace() {
+        let green_tree = Parser::new("   ").parse();
+
+        assert_eq!(
+            green_tree.debug_tree(),
+            r#"Root@0..3
+  Whitespace@0..3 "   "
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_unrecognized_token() {
+        let green_tree = Parser::new("@").parse();
+
+        assert_eq!(
+            green_tree.debug_tree(),
+            r#"Root@0..1
+  Error@0..1
+    Error@0..1 "@"
+error at 0..1: unrecognized token `@`
+"#,
+        );
+    }
+
+    #[test]
+    fn parse_expr_fragment() {
+        let fragment = Parser::new(" a + f(b) ").parse_expr();
+
+        assert_eq!(
+            fragment.green_tree.debug_tree(),
+            r#"Root@0..10
+  Whitespace@0..1 " "
+  BinaryExpr@1..9
+    VariableRef@1..2
+      Identifier@1..2 "a"
+    Whitespace@2..3 " "
+    Plus@3..4 "+"
+    Whitespace@4..5 " "
+    CallExpr@5..9
+      VariableRef@5..6
+        Identifier@5..6 "f"
+      ArgList@6..9
+        OpenParen@6..7 "("
+        VariableRef@7..8
+          Identifier@7..8 "b"
+        CloseParen@8..9 ")"
+  Whitespace@9..10 " "
+"#,
+        );
+        assert!(matches!(fragment.node(), Some(Expr::Binary(_))));
+    }
+
+    #[test]
+    fn parse_stmt_fragment() {
+        let fragment = Parser::new("let a = 1;").parse_stmt();
+
+        assert_eq!(
+            fragment.green_tree.debug_tree(),
+            r#"Root@0..10
+  LetStmt@0..10
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..5 "a"
+    Whitespace@5..6 " "
+    SingleEqual@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..9
+      Number@8..9 "1"
+    Semicolon@9..10 ";"
+"#,
+        );
+        assert!(matches!(fragment.node(), Some(Stmt::Let(_))));
+    }
+
+    #[test]
+    fn parse_item_fragment() {
+        let fragment = Parser::new("impl A { fn f() {} }").parse_item();
+        assert_eq!(fragment.errors(), []);
+        assert!(matches!(fragment.node(), Some(Stmt::Impl(_))));
+
+        let fragment = Parser::new("1 + 2").parse_item();
+        assert_eq!(
+            fragment.green_tree.debug_tree(),
+            r#"Root@0..5
+  Error@0..5
+    Number@0..1 "1"
+    Whitespace@1..2 " "
+    Plus@2..3 "+"
+    Whitespace@3..4 " "
+    Number@4..5 "2"
+error at 0..1: expected item, found number
+"#,
+        );
+        assert_eq!(fragment.node(), None);
+    }
+
+    #[test]
+    fn parse_fragment_with_trailing_input() {
+        let fragment = Parser::new("a + b c d").parse_expr();
+
+        assert_eq!(
+            fragment.green_tree.debug_tree(),
+            r#"Root@0..9
+  BinaryExpr@0..5
+    VariableRef@0..1
+      Identifier@0..1 "a"
+    Whitespace@1..2 " "
+    Plus@2..3 "+"
+    Whitespace@3..4 " "
+    VariableRef@4..5
+      Identifier@4..5 "b"
+  Whitespace@5..6 " "
+  Error@6..9
+    Identifier@6..7 "c"
+    Whitespace@7..8 " "
+    Identifier@8..9 "d"
+error at 6..7: expected end of input, found identifier
+"#,
+        );
+        assert_eq!(
+            fragment.node().map(|expr| expr.syntax().to_string()),
+            Some("a + b".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_stmt_fragment_with_trailing_input() {
+        let fragment = Parser::new("let a = 1 2").parse_stmt();
+
+        assert_eq!(
+            fragment.green_tree.debug_tree(),
+            r#"Root@0..11
+  LetStmt@0..9
+    LetKeyword@0..3 "let"
+    Whitespace@3..4 " "
+    Identifier@4..5 "a"
+    Whitespace@5..6 " "
+    SingleEqual@6..7 "="
+    Whitespace@7..8 " "
+    Literal@8..9
+      Number@8..9 "1"
+  Whitespace@9..10 " "
+  Error@10..11
+    Number@10..11 "2"
+error at 10..11: expected `;`, found number
 "#,
         );
     }
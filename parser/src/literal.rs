@@ -0,0 +1,335 @@
+//! Values of literal tokens, shared by everything that needs to know what a literal means.
+
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IntTy {
+    I8,
+    I16,
+    I32,
+    I64,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    Usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FloatTy {
+    F32,
+    F64,
+}
+
+impl IntTy {
+    pub const ALL: [Self; 10] = [
+        Self::I8,
+        Self::I16,
+        Self::I32,
+        Self::I64,
+        Self::Isize,
+        Self::U8,
+        Self::U16,
+        Self::U32,
+        Self::U64,
+        Self::Usize,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|ty| ty.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::Isize => "isize",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::Usize => "usize",
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::Isize
+        )
+    }
+
+    /// The width of the type, with `isize` and `usize` taken as 64 bits wide.
+    pub fn bits(self) -> u32 {
+        match self {
+            Self::I8 | Self::U8 => 8,
+            Self::I16 | Self::U16 => 16,
+            Self::I32 | Self::U32 => 32,
+            Self::I64 | Self::Isize | Self::U64 | Self::Usize => 64,
+        }
+    }
+
+    pub fn min(self) -> i128 {
+        if self.is_signed() {
+            -(1 << (self.bits() - 1))
+        } else {
+            0
+        }
+    }
+
+    pub fn max(self) -> i128 {
+        if self.is_signed() {
+            (1 << (self.bits() - 1)) - 1
+        } else {
+            (1 << self.bits()) - 1
+        }
+    }
+
+    /// Whether `value` is within the range of the type.
+    pub fn contains(self, value: i128) -> bool {
+        (self.min()..=self.max()).contains(&value)
+    }
+}
+
+impl FloatTy {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+        }
+    }
+}
+
+/// The value of a number literal and the type of its suffix, if any.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(u64, Option<IntTy>),
+    Float(f64, Option<FloatTy>),
+}
+
+/// The value of a number literal, which the lexer accepted, so it only fails for values the
+/// types can't hold and the few forms the lexer's patterns let through.
+pub fn number(text: &str) -> Result<Number, String> {
+    // literals are decoded every time the interpreter evaluates them, so they're only
+    // copied when they need to be
+    let text = match text.contains('_') {
+        true => Cow::Owned(text.replace('_', "")),
+        false => Cow::Borrowed(text),
+    };
+    let lower = match text.bytes().any(|b| b.is_ascii_uppercase()) {
+        true => Cow::Owned(text.to_ascii_lowercase()),
+        false => text.clone(),
+    };
+
+    let (radix, digits) = match lower.get(..2) {
+        Some("0x") => (16, &lower[2..]),
+        Some("0o") => (8, &lower[2..]),
+        Some("0b") => (2, &lower[2..]),
+        _ => (10, &*lower),
+    };
+
+    if radix == 16 && digits.contains('p') {
+        let (digits, suffix) = float_suffix(digits);
+        return hex_float(digits)
+            .map(|value| Number::Float(value, suffix))
+            .ok_or_else(|| format!("invalid float literal `{text}`"));
+    }
+
+    // integer suffixes start with `i` or `u`, which can't appear anywhere else
+    let (digits, suffix) = match digits.find(['i', 'u']) {
+        Some(index) => digits.split_at(index),
+        None => (digits, ""),
+    };
+
+    // hex digits include `e` and `f`, so only decimal literals can be floats
+    if radix == 10 && digits.contains(['.', 'e', 'f']) {
+        let (digits, suffix) = float_suffix(digits);
+        return is_decimal_float(digits)
+            .then(|| digits.parse().ok())
+            .flatten()
+            .map(|value| Number::Float(value, suffix))
+            .ok_or_else(|| format!("invalid float literal `{text}`"));
+    }
+
+    let suffix = match suffix {
+        "" => None,
+        "i128" | "u128" => return Err(format!("`{suffix}` isn't supported")),
+        suffix => {
+            Some(IntTy::from_name(suffix).ok_or_else(|| format!("invalid suffix `{suffix}`"))?)
+        }
+    };
+    let value = u64::from_str_radix(digits, radix)
+        .map_err(|_| format!("integer literal `{text}` is too large"))?;
+    Ok(Number::Int(value, suffix))
+}
+
+/// Whether `digits` has the shape of a float, such as `1.5e-3`, which Rust's own parsing is
+/// more lenient about, accepting `.5` and `1.`.
+fn is_decimal_float(digits: &str) -> bool {
+    let (mantissa, exponent) = match digits.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (digits, None),
+    };
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None),
+    };
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    is_digits(int)
+        && frac.is_none_or(is_digits)
+        && exponent
+            .is_none_or(|exponent| is_digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)))
+}
+
+fn float_suffix(digits: &str) -> (&str, Option<FloatTy>) {
+    if let Some(digits) = digits.strip_suffix("f32") {
+        (digits, Some(FloatTy::F32))
+    } else if let Some(digits) = digits.strip_suffix("f64") {
+        (digits, Some(FloatTy::F64))
+    } else {
+        (digits, None)
+    }
+}
+
+/// Parses the part of a hex float after `0x`, such as `1.8p3`.
+fn hex_float(digits: &str) -> Option<f64> {
+    let (mantissa, exponent) = digits.split_once('p')?;
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let mut value = 0.0;
+    for digit in int.chars().chain(frac.chars()) {
+        value = value * 16.0 + digit.to_digit(16)? as f64;
+    }
+    let exponent = exponent.parse::<i32>().ok()?;
+    Some(value * 2f64.powi(exponent - 4 * frac.len() as i32))
+}
+
+/// The value of a character literal, including its quotes.
+pub fn char(text: &str) -> Result<char, String> {
+    let text = text
+        .strip_prefix('\'')
+        .and_then(|text| text.strip_suffix('\''))
+        .ok_or("unterminated character literal")?;
+    let unescaped = unescape(text)?;
+    let mut chars = unescaped.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        (None, _) => Err("empty character literal".to_string()),
+        _ => Err("character literal may only contain one codepoint".to_string()),
+    }
+}
+
+/// The value of a string literal, including its quotes.
+pub fn string(text: &str) -> Result<String, String> {
+    let text = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .ok_or("unterminated string literal")?;
+    unescape(text)
+}
+
+/// The text of a piece of an interpolated string, such as `}text{`, without its delimiters.
+pub fn interpolated_text(text: &str) -> Result<String, String> {
+    let text = text
+        .strip_prefix("f\"")
+        .or_else(|| text.strip_prefix('}'))
+        .unwrap_or(text);
+    let text = text
+        .strip_suffix('"')
+        .or_else(|| text.strip_suffix('{'))
+        .unwrap_or(text);
+    unescape(&text.replace("{{", "{").replace("}}", "}"))
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('\'') => '\'',
+            Some('"') => '"',
+            Some('u') => {
+                let rest = chars.as_str();
+                let hex = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(hex, _)| hex)
+                    .ok_or("expected `{` after `\\u`")?;
+                let c = u32::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid unicode escape `\\u{{{hex}}}`"))?;
+
+                chars = rest[hex.len() + 2..].chars();
+                c
+            }
+            Some(other) => return Err(format!("unknown character escape `\\{other}`")),
+            None => return Err("unterminated escape".to_string()),
+        };
+        result.push(escaped);
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{char, interpolated_text, number, string, FloatTy, IntTy, Number};
+
+    #[test]
+    fn numbers() {
+        assert_eq!(number("1_000"), Ok(Number::Int(1000, None)));
+        assert_eq!(number("0xffu8"), Ok(Number::Int(255, Some(IntTy::U8))));
+        assert_eq!(number("0b101i16"), Ok(Number::Int(5, Some(IntTy::I16))));
+        assert_eq!(
+            number("18446744073709551615u64"),
+            Ok(Number::Int(u64::MAX, Some(IntTy::U64)))
+        );
+        assert_eq!(number("1.5"), Ok(Number::Float(1.5, None)));
+        assert_eq!(number("2f32"), Ok(Number::Float(2.0, Some(FloatTy::F32))));
+        assert_eq!(number("0x1.8p1"), Ok(Number::Float(3.0, None)));
+        assert_eq!(number("0X1P4"), Ok(Number::Float(16.0, None)));
+        assert_eq!(number("1E3"), Ok(Number::Float(1000.0, None)));
+        assert_eq!(
+            number("2.5e-1f64"),
+            Ok(Number::Float(0.25, Some(FloatTy::F64)))
+        );
+        assert_eq!(number(".5"), Err("invalid float literal `.5`".to_string()));
+        assert_eq!(number("1."), Err("invalid float literal `1.`".to_string()));
+        assert_eq!(number("1u128"), Err("`u128` isn't supported".to_string()));
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(string(r#""a\n\"b\" \u{e9}""#), Ok("a\n\"b\" é".to_string()));
+        assert_eq!(
+            string(r#""\q""#),
+            Err(r"unknown character escape `\q`".to_string())
+        );
+        assert_eq!(char(r"'\''"), Ok('\''));
+        assert_eq!(char(r"'\u{1F600}'"), Ok('😀'));
+        assert_eq!(
+            char("'ab'"),
+            Err("character literal may only contain one codepoint".to_string())
+        );
+        assert_eq!(interpolated_text("}{{a}}\\t{"), Ok("{a}\t".to_string()));
+        assert_eq!(string("5"), Err("unterminated string literal".to_string()));
+        assert_eq!(string("\""), Err("unterminated string literal".to_string()));
+        assert_eq!(char("'"), Err("unterminated character literal".to_string()));
+    }
+}
@@ -1,7 +1,12 @@
-use lexer::{Assign, Comment, Delimiter, Grouping, Keyword, Operator, Token};
+use std::fmt;
+
+use rowan::Language;
+
+use lexer::{Assign, Comment, Delimiter, Grouping, InterpolatedString, Keyword, Operator, Token};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u16)]
+#[allow(clippy::manual_non_exhaustive)]
 pub enum SyntaxKind {
     // trivia kinds
     LineComment,
@@ -13,6 +18,16 @@ pub enum SyntaxKind {
     IfKeyword,
     ElseKeyword,
     ReturnKeyword,
+    ModKeyword,
+    UseKeyword,
+    PubKeyword,
+    MatchKeyword,
+    EnumKeyword,
+    StructKeyword,
+    TraitKeyword,
+    ImplKeyword,
+    ForKeyword,
+    MutKeyword,
 
     Equal,
     NotEqual,
@@ -28,6 +43,13 @@ pub enum SyntaxKind {
     Star,
     Slash,
     Modulo,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
+    Question,
 
     SingleEqual, // NOTE: maps to `lexer::Assign::Equal`
     PlusEqual,
@@ -35,11 +57,23 @@ pub enum SyntaxKind {
     SlashEqual,
     StarEqual,
     ModuloEqual,
+    AmpersandEqual,
+    PipeEqual,
+    CaretEqual,
+    ShiftLeftEqual,
+    ShiftRightEqual,
 
     Comma,
     Semicolon,
     Colon,
+    ColonColon,
     RightArrow,
+    FatArrow,
+    Dot,
+    DotDot,
+    DotDotEqual,
+    Pound,
+    Underscore,
 
     OpenParen,
     CloseParen,
@@ -53,15 +87,90 @@ pub enum SyntaxKind {
     Bool,
     String,
     Character,
+    /// `f"text"`, an interpolated string without embedded expressions.
+    InterpolatedStringPlain,
+    /// `f"text{`
+    InterpolatedStringStart,
+    /// `}text{`
+    InterpolatedStringMiddle,
+    /// `}text"`
+    InterpolatedStringEnd,
 
     LetStmt,
     ReturnStmt,
     BreakStmt,
     ContinueStmt,
     StructStmt,
+    ExprStmt,
+    ModStmt,
+    UseStmt,
+    EnumStmt,
+    TraitStmt,
+    ImplStmt,
+    VariantList,
+    Variant,
+    RecordFieldList,
+    RecordField,
+    ItemList,
+    Visibility,
+    Attr,
+    AttrArgList,
+    AttrArg,
 
+    Literal,
+    InterpolatedString,
+    VariableRef,
+    ParenExpr,
+    TupleExpr,
+    PrefixExpr,
+    BinaryExpr,
+    CallExpr,
+    MethodCallExpr,
+    FieldExpr,
+    ArgList,
+    BlockExpr,
     FunctionExpr,
+    ParamList,
+    Param,
     IfExpr,
+    ArrayExpr,
+    ArrayRepeatExpr,
+    IndexExpr,
+    ClosureExpr,
+    MatchExpr,
+    MatchArmList,
+    MatchArm,
+    MatchGuard,
+    RecordExpr,
+    RecordExprFieldList,
+    RecordExprField,
+
+    PathType,
+    ArrayType,
+    SliceType,
+    FnType,
+    TupleType,
+    RefType,
+    OptionalType,
+    TypeList,
+    GenericParamList,
+    GenericParam,
+    TypeBoundList,
+    GenericArgList,
+
+    LiteralPattern,
+    WildcardPattern,
+    IdentPattern,
+    RangePattern,
+    RestPattern,
+    OrPattern,
+    PathPattern,
+    TuplePattern,
+    TupleStructPattern,
+    StructPattern,
+    PatternList,
+    FieldPatternList,
+    FieldPattern,
 
     Error,
     Root,
@@ -82,7 +191,13 @@ impl<'source> From<Token<'source>> for SyntaxKind {
                 Delimiter::Comma => Self::Comma,
                 Delimiter::Semicolon => Self::Semicolon,
                 Delimiter::Colon => Self::Colon,
+                Delimiter::ColonColon => Self::ColonColon,
                 Delimiter::RightArrow => Self::RightArrow,
+                Delimiter::FatArrow => Self::FatArrow,
+                Delimiter::Dot => Self::Dot,
+                Delimiter::DotDot => Self::DotDot,
+                Delimiter::DotDotEqual => Self::DotDotEqual,
+                Delimiter::Pound => Self::Pound,
             },
             Token::Operator(val) => match val {
                 Operator::Equal => Self::Equal,
@@ -99,6 +214,13 @@ impl<'source> From<Token<'source>> for SyntaxKind {
                 Operator::Star => Self::Star,
                 Operator::Slash => Self::Slash,
                 Operator::Modulo => Self::Modulo,
+                Operator::BitwiseAnd => Self::Ampersand,
+                Operator::BitwiseOr => Self::Pipe,
+                Operator::BitwiseXor => Self::Caret,
+                Operator::BitwiseNot => Self::Tilde,
+                Operator::ShiftLeft => Self::ShiftLeft,
+                Operator::ShiftRight => Self::ShiftRight,
+                Operator::Question => Self::Question,
             },
             Token::Assign(val) => match val {
                 Assign::Equal => Self::SingleEqual,
@@ -107,6 +229,11 @@ impl<'source> From<Token<'source>> for SyntaxKind {
                 Assign::SlashEqual => Self::SlashEqual,
                 Assign::StarEqual => Self::StarEqual,
                 Assign::ModuloEqual => Self::ModuloEqual,
+                Assign::BitwiseAndEqual => Self::AmpersandEqual,
+                Assign::BitwiseOrEqual => Self::PipeEqual,
+                Assign::BitwiseXorEqual => Self::CaretEqual,
+                Assign::ShiftLeftEqual => Self::ShiftLeftEqual,
+                Assign::ShiftRightEqual => Self::ShiftRightEqual,
             },
             Token::Keyword(val) => match val {
                 Keyword::Function => Self::FunctionKeyword,
@@ -114,6 +241,16 @@ impl<'source> From<Token<'source>> for SyntaxKind {
                 Keyword::If => Self::IfKeyword,
                 Keyword::Else => Self::ElseKeyword,
                 Keyword::Return => Self::ReturnKeyword,
+                Keyword::Mod => Self::ModKeyword,
+                Keyword::Use => Self::UseKeyword,
+                Keyword::Pub => Self::PubKeyword,
+                Keyword::Match => Self::MatchKeyword,
+                Keyword::Enum => Self::EnumKeyword,
+                Keyword::Struct => Self::StructKeyword,
+                Keyword::Trait => Self::TraitKeyword,
+                Keyword::Impl => Self::ImplKeyword,
+                Keyword::For => Self::ForKeyword,
+                Keyword::Mut => Self::MutKeyword,
             },
             Token::Grouping(val) => match val {
                 Grouping::OpenParen => Self::OpenParen,
@@ -124,15 +261,158 @@ impl<'source> From<Token<'source>> for SyntaxKind {
                 Grouping::CloseBrace => Self::CloseBrace,
             },
             Token::Whitespace(_) => Self::Whitespace,
+            // like in Rust, a lone underscore is a wildcard rather than a name
+            Token::Identifier("_") => Self::Underscore,
             Token::Identifier(_) => Self::Identifier,
             Token::Character(_) => Self::Character,
             Token::String(_) => Self::String,
+            Token::InterpolatedString(val) => match val {
+                InterpolatedString::Plain(_) => Self::InterpolatedStringPlain,
+                InterpolatedString::Start(_) => Self::InterpolatedStringStart,
+                InterpolatedString::Middle(_) => Self::InterpolatedStringMiddle,
+                InterpolatedString::End(_) => Self::InterpolatedStringEnd,
+            },
             Token::Bool(_) => Self::Bool,
             Token::Number(_) => Self::Number,
         }
     }
 }
 
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            Self::Whitespace | Self::LineComment | Self::BlockComment
+        )
+    }
+
+    /// Whether the token is `=` or a compound assignment operator such as `+=`.
+    pub fn is_assignment(self) -> bool {
+        matches!(
+            self,
+            Self::SingleEqual
+                | Self::PlusEqual
+                | Self::MinusEqual
+                | Self::StarEqual
+                | Self::SlashEqual
+                | Self::ModuloEqual
+                | Self::AmpersandEqual
+                | Self::PipeEqual
+                | Self::CaretEqual
+                | Self::ShiftLeftEqual
+                | Self::ShiftRightEqual
+        )
+    }
+
+    /// The source text of tokens that can only be spelled one way, such as keywords and operators.
+    pub fn fixed_text(self) -> Option<&'static str> {
+        let text = match self {
+            Self::FunctionKeyword => "fn",
+            Self::LetKeyword => "let",
+            Self::IfKeyword => "if",
+            Self::ElseKeyword => "else",
+            Self::ReturnKeyword => "return",
+            Self::ModKeyword => "mod",
+            Self::UseKeyword => "use",
+            Self::PubKeyword => "pub",
+            Self::MatchKeyword => "match",
+            Self::EnumKeyword => "enum",
+            Self::StructKeyword => "struct",
+            Self::TraitKeyword => "trait",
+            Self::ImplKeyword => "impl",
+            Self::ForKeyword => "for",
+            Self::MutKeyword => "mut",
+
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::LessThan => "<",
+            Self::GreaterThan => ">",
+            Self::LessThanEqual => "<=",
+            Self::GreaterThanEqual => ">=",
+            Self::LogicalAnd => "&&",
+            Self::LogicalOr => "||",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::Bang => "!",
+            Self::Star => "*",
+            Self::Slash => "/",
+            Self::Modulo => "%",
+            Self::Ampersand => "&",
+            Self::Pipe => "|",
+            Self::Caret => "^",
+            Self::Tilde => "~",
+            Self::ShiftLeft => "<<",
+            Self::ShiftRight => ">>",
+            Self::Question => "?",
+
+            Self::SingleEqual => "=",
+            Self::PlusEqual => "+=",
+            Self::MinusEqual => "-=",
+            Self::SlashEqual => "/=",
+            Self::StarEqual => "*=",
+            Self::ModuloEqual => "%=",
+            Self::AmpersandEqual => "&=",
+            Self::PipeEqual => "|=",
+            Self::CaretEqual => "^=",
+            Self::ShiftLeftEqual => "<<=",
+            Self::ShiftRightEqual => ">>=",
+
+            Self::Comma => ",",
+            Self::Semicolon => ";",
+            Self::Colon => ":",
+            Self::ColonColon => "::",
+            Self::RightArrow => "->",
+            Self::FatArrow => "=>",
+            Self::Dot => ".",
+            Self::DotDot => "..",
+            Self::DotDotEqual => "..=",
+            Self::Pound => "#",
+            Self::Underscore => "_",
+
+            Self::OpenParen => "(",
+            Self::CloseParen => ")",
+            Self::OpenSquare => "[",
+            Self::CloseSquare => "]",
+            Self::OpenBrace => "{",
+            Self::CloseBrace => "}",
+
+            _ => return None,
+        };
+
+        Some(text)
+    }
+
+    /// Iterates over every kind, tokens and nodes alike.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..Self::_LAST as u16).map(|raw| Lang::kind_from_raw(rowan::SyntaxKind(raw)))
+    }
+}
+
+impl fmt::Display for SyntaxKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(text) = self.fixed_text() {
+            return write!(f, "`{text}`");
+        }
+
+        let s = match self {
+            Self::LineComment | Self::BlockComment => "comment",
+            Self::Whitespace => "whitespace",
+            Self::Identifier => "identifier",
+            Self::Number => "number",
+            Self::Bool => "boolean",
+            Self::String => "string",
+            Self::Character => "character",
+            Self::InterpolatedStringPlain | Self::InterpolatedStringStart => "interpolated string",
+            Self::InterpolatedStringMiddle | Self::InterpolatedStringEnd => "`}`",
+
+            // nodes are never "found" by the parser, so their debug name is good enough
+            _ => return write!(f, "{self:?}"),
+        };
+
+        f.write_str(s)
+    }
+}
+
 impl From<SyntaxKind> for rowan::SyntaxKind {
     fn from(kind: SyntaxKind) -> Self {
         Self(kind as u16)
@@ -157,3 +437,23 @@ impl rowan::Language for Lang {
 pub type SyntaxNode = rowan::SyntaxNode<Lang>;
 pub type SyntaxElement = rowan::SyntaxElement<Lang>;
 pub type SyntaxToken = rowan::SyntaxToken<Lang>;
+
+#[cfg(test)]
+mod tests {
+    use super::SyntaxKind;
+    use lexer::Lexer;
+
+    #[test]
+    fn fixed_text_matches_lexer() {
+        for kind in SyntaxKind::all() {
+            let Some(text) = kind.fixed_text() else {
+                continue;
+            };
+
+            let tokens = Lexer::new(text)
+                .map(|(token, _)| token.map(SyntaxKind::from))
+                .collect::<Vec<_>>();
+            assert_eq!(tokens, [Ok(kind)], "`{text}` doesn't lex as {kind:?}");
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt;
+
+use crate::{SourceMap, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => f.write_str("error"),
+            Self::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// A message attached to a span of source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    /// A suggestion on how to fix the problem, shown below the source.
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+            help: None,
+        }
+    }
+
+    pub fn warning(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            span,
+            help: None,
+        }
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Renders the diagnostic with the offending line and carets under the span.
+    pub fn render(&self, source_map: &SourceMap) -> String {
+        let file = source_map.get(self.span.file);
+        let start = file.line_column(self.span.range.start());
+        let end = file.line_column(self.span.range.end());
+        let line = file.line(start.line).unwrap_or_default();
+
+        let width = if end.line == start.line {
+            (end.column - start.column).max(1)
+        } else {
+            (line.chars().count() as u32 + 1 - start.column).max(1)
+        };
+
+        let number = start.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        let mut rendered = format!(
+            "{}: {}\n{gutter}--> {}\n{gutter} |\n{number} | {line}\n{gutter} | {}{}\n",
+            self.severity,
+            self.message,
+            source_map.location(self.span),
+            " ".repeat(start.column as usize - 1),
+            "^".repeat(width as usize),
+        );
+        if let Some(help) = &self.help {
+            rendered.push_str(&format!("{gutter} = help: {help}\n"));
+        }
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Diagnostic;
+    use crate::{SourceMap, Span, TextRange};
+
+    #[test]
+    fn render_diagnostic() {
+        let mut source_map = SourceMap::new();
+        let id = source_map.add("main.lamia", "let a = 1;\nlet bc d;\n");
+        let span = Span::new(id, TextRange::new(15.into(), 17.into()));
+
+        assert_eq!(
+            Diagnostic::error(span, "expected `=`").render(&source_map),
+            "error: expected `=`
+ --> main.lamia:2:5
+  |
+2 | let bc d;
+  |     ^^
+"
+        );
+    }
+
+    #[test]
+    fn render_empty_span_at_end() {
+        let mut source_map = SourceMap::new();
+        let id = source_map.add("main.lamia", "1 +");
+        let span = Span::new(id, TextRange::empty(3.into()));
+
+        assert_eq!(
+            Diagnostic::warning(span, "expected expression").render(&source_map),
+            "warning: expected expression
+ --> main.lamia:1:4
+  |
+1 | 1 +
+  |    ^
+"
+        );
+    }
+
+    #[test]
+    fn render_help() {
+        let mut source_map = SourceMap::new();
+        let id = source_map.add("main.lamia", "let a = 1;\na = 2;\n");
+        let span = Span::new(id, TextRange::new(11.into(), 16.into()));
+
+        let diagnostic = Diagnostic::error(span, "cannot assign twice to immutable variable `a`")
+            .with_help("consider making this binding mutable: `mut a`");
+        assert_eq!(
+            diagnostic.render(&source_map),
+            "error: cannot assign twice to immutable variable `a`
+ --> main.lamia:2:1
+  |
+2 | a = 2;
+  | ^^^^^
+  = help: consider making this binding mutable: `mut a`
+"
+        );
+    }
+}
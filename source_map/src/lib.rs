@@ -0,0 +1,237 @@
+mod diagnostic;
+
+pub use diagnostic::{Diagnostic, Severity};
+pub use rowan::{TextRange, TextSize};
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Handle to a file owned by a [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(u32);
+
+/// A range of source text in a given file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub file: FileId,
+    pub range: TextRange,
+}
+
+impl Span {
+    pub fn new(file: FileId, range: TextRange) -> Self {
+        Self { file, range }
+    }
+}
+
+/// A 1-based line and column, with columns counted in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineColumn {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for LineColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug)]
+pub struct SourceFile {
+    path: PathBuf,
+    text: String,
+    /// Offset of the first character of every line.
+    line_starts: Vec<TextSize>,
+}
+
+impl SourceFile {
+    fn new(path: PathBuf, text: String) -> Self {
+        let line_starts = line_starts(&text);
+        Self {
+            path,
+            text,
+            line_starts,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Converts an offset into a line and column, clamping it to the end of the file.
+    pub fn line_column(&self, offset: TextSize) -> LineColumn {
+        let offset = offset.min(TextSize::of(&self.text));
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = usize::from(self.line_starts[line]);
+        let mut offset = usize::from(offset);
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        LineColumn {
+            line: line as u32 + 1,
+            column: self.text[line_start..offset].chars().count() as u32 + 1,
+        }
+    }
+
+    /// Returns the text of the 1-based `line`, without its line ending.
+    pub fn line(&self, line: u32) -> Option<&str> {
+        let index = (line as usize).checked_sub(1)?;
+        let start = usize::from(*self.line_starts.get(index)?);
+        let end = self
+            .line_starts
+            .get(index + 1)
+            .map_or(self.text.len(), |&end| usize::from(end));
+
+        Some(self.text[start..end].trim_end_matches(['\n', '\r']))
+    }
+}
+
+fn line_starts(text: &str) -> Vec<TextSize> {
+    std::iter::once(0.into())
+        .chain(
+            text.match_indices('\n')
+                .map(|(i, _)| TextSize::from(i as u32 + 1)),
+        )
+        .collect()
+}
+
+/// Owns the source text of every file and hands out [`FileId`]s to refer to them.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, path: impl Into<PathBuf>, text: impl Into<String>) -> FileId {
+        let id = FileId(self.files.len() as u32);
+        self.files.push(SourceFile::new(path.into(), text.into()));
+        id
+    }
+
+    /// Reads the file from disk, reusing its id if it was already loaded.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<FileId> {
+        let path = path.as_ref();
+        if let Some(id) = self.find(path) {
+            return Ok(id);
+        }
+
+        let text = fs::read_to_string(path)?;
+        Ok(self.add(path, text))
+    }
+
+    /// Replaces the text of a file, e.g. after an edit.
+    pub fn set_text(&mut self, id: FileId, text: impl Into<String>) {
+        let file = &mut self.files[id.0 as usize];
+        *file = SourceFile::new(std::mem::take(&mut file.path), text.into());
+    }
+
+    pub fn find(&self, path: &Path) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|file| file.path == path)
+            .map(|index| FileId(index as u32))
+    }
+
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0 as usize]
+    }
+
+    pub fn path(&self, id: FileId) -> &Path {
+        self.get(id).path()
+    }
+
+    pub fn text(&self, id: FileId) -> &str {
+        self.get(id).text()
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = FileId> {
+        (0..self.files.len() as u32).map(FileId)
+    }
+
+    /// The text covered by `span`.
+    pub fn snippet(&self, span: Span) -> &str {
+        &self.text(span.file)[span.range]
+    }
+
+    /// Where `span` starts, as `path:line:column`.
+    pub fn location(&self, span: Span) -> String {
+        let file = self.get(span.file);
+        format!(
+            "{}:{}",
+            file.path().display(),
+            file.line_column(span.range.start())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineColumn, SourceMap, Span, TextRange, TextSize};
+
+    fn at(line: u32, column: u32) -> LineColumn {
+        LineColumn { line, column }
+    }
+
+    #[test]
+    fn line_column() {
+        let mut source_map = SourceMap::new();
+        let id = source_map.add("main.lamia", "let a = 1;\nlet é = 2;\n");
+        let file = source_map.get(id);
+
+        assert_eq!(file.line_column(0.into()), at(1, 1));
+        assert_eq!(file.line_column(4.into()), at(1, 5));
+        assert_eq!(file.line_column(10.into()), at(1, 11));
+        assert_eq!(file.line_column(11.into()), at(2, 1));
+        // `é` is two bytes but one column
+        assert_eq!(file.line_column(17.into()), at(2, 6));
+        assert_eq!(file.line_column(100.into()), at(3, 1));
+    }
+
+    #[test]
+    fn lines() {
+        let mut source_map = SourceMap::new();
+        let id = source_map.add("main.lamia", "a\r\nb\n");
+        let file = source_map.get(id);
+
+        assert_eq!(file.line(0), None);
+        assert_eq!(file.line(1), Some("a"));
+        assert_eq!(file.line(2), Some("b"));
+        assert_eq!(file.line(3), Some(""));
+        assert_eq!(file.line(4), None);
+    }
+
+    #[test]
+    fn multiple_files() {
+        let mut source_map = SourceMap::new();
+        let main = source_map.add("main.lamia", "foo();");
+        let foo = source_map.add("foo.lamia", "\nfn foo() {}");
+
+        assert_ne!(main, foo);
+        assert_eq!(source_map.find("foo.lamia".as_ref()), Some(foo));
+
+        let span = Span::new(foo, TextRange::at(4.into(), TextSize::of("foo")));
+        assert_eq!(source_map.snippet(span), "foo");
+        assert_eq!(source_map.location(span), "foo.lamia:2:4");
+    }
+
+    #[test]
+    fn set_text() {
+        let mut source_map = SourceMap::new();
+        let id = source_map.add("main.lamia", "a");
+        source_map.set_text(id, "b\nc");
+
+        assert_eq!(source_map.path(id).to_str(), Some("main.lamia"));
+        assert_eq!(source_map.get(id).line(2), Some("c"));
+    }
+}
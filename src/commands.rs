@@ -0,0 +1,6 @@
+pub mod build;
+pub mod check;
+pub mod fmt;
+pub mod highlight;
+pub mod run;
+pub mod test;
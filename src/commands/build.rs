@@ -0,0 +1,119 @@
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use db::Database;
+use source_map::Severity;
+
+/// The forms `lamia build` can compile a program to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Emit {
+    C,
+    Wasm,
+    Wat,
+    Mir,
+}
+
+struct Options {
+    emit: Emit,
+    output: Option<PathBuf>,
+    file: PathBuf,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut emit = Emit::C;
+    let mut output = None;
+    let mut file = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" => {
+                let path = args.next().ok_or("`-o` expects a path")?;
+                output = Some(path.into());
+            }
+            _ if arg.starts_with("--emit=") => {
+                emit = match &arg["--emit=".len()..] {
+                    "c" => Emit::C,
+                    "wasm" => Emit::Wasm,
+                    "wat" => Emit::Wat,
+                    "mir" => Emit::Mir,
+                    other => return Err(format!("unknown output kind `{other}`")),
+                };
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ if file.is_some() => return Err("expected a single file".to_string()),
+            _ => file = Some(arg.into()),
+        }
+    }
+
+    let file = file.ok_or("expected a file")?;
+    Ok(Options { emit, output, file })
+}
+
+/// Compiles the program rooted at the given file, writing the result next to it
+/// unless `-o` gives another path.
+pub fn run(args: &[String]) -> ExitCode {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}");
+            eprintln!("usage: lamia build [--emit=c|wasm|wat|mir] [-o <path>] <file>");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut db = Database::new();
+    let root = match db.load_file(&options.file) {
+        Ok(root) => root,
+        Err(err) => {
+            eprintln!("error: cannot read {}: {err}", options.file.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (module_tree, mut diagnostics) = modules::build(&mut db, root);
+    diagnostics.extend(checker::check(&db, &module_tree));
+    let source_map = db.source_map();
+    for diagnostic in &diagnostics {
+        eprint!("{}", diagnostic.render(source_map));
+    }
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return ExitCode::FAILURE;
+    }
+
+    let program = match codegen::lower(&db, &module_tree) {
+        Ok(program) => program,
+        Err(diagnostics) => {
+            for diagnostic in &diagnostics {
+                eprint!("{}", diagnostic.render(source_map));
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (output, extension) = match options.emit {
+        Emit::C => (codegen::emit_c(&program, source_map).into_bytes(), "c"),
+        Emit::Wasm => (codegen::emit_wasm(&program, source_map), "wasm"),
+        Emit::Wat => (codegen::emit_wat(&program, source_map).into_bytes(), "wat"),
+        Emit::Mir => match codegen::emit_mir(&program) {
+            Ok(mir) => (mir.into_bytes(), "mir"),
+            Err(errors) => {
+                eprintln!("error: internal compiler error: the generated MIR is invalid");
+                for error in &errors {
+                    eprintln!("  {error}");
+                }
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let path = options
+        .output
+        .unwrap_or_else(|| options.file.with_extension(extension));
+    if let Err(err) = fs::write(&path, output) {
+        eprintln!("error: cannot write {}: {err}", path.display());
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,37 @@
+use std::process::ExitCode;
+
+use db::Database;
+use source_map::Severity;
+
+/// Loads the program rooted at the given file and reports its diagnostics.
+pub fn run(args: &[String]) -> ExitCode {
+    let [path] = args else {
+        eprintln!("usage: lamia check <file>");
+        return ExitCode::from(2);
+    };
+
+    let mut db = Database::new();
+    let root = match db.load_file(path) {
+        Ok(root) => root,
+        Err(err) => {
+            eprintln!("error: cannot read {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (module_tree, mut diagnostics) = modules::build(&mut db, root);
+    diagnostics.extend(checker::check(&db, &module_tree));
+    let source_map = db.source_map();
+    for diagnostic in &diagnostics {
+        eprint!("{}", diagnostic.render(source_map));
+    }
+
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use formatter::{Config, CONFIG_FILE_NAME};
+use source_map::{Diagnostic, FileId, SourceMap, Span};
+
+struct Options {
+    check: bool,
+    config: Option<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        check: false,
+        config: None,
+        files: Vec::new(),
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => options.check = true,
+            "--config" => {
+                let path = args.next().ok_or("`--config` expects a path")?;
+                options.config = Some(path.into());
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option `{arg}`")),
+            _ => options.files.push(arg.into()),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Looks for the configuration file in the current directory and its ancestors.
+fn find_config() -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+fn load_config(path: Option<&Path>) -> Result<Config, String> {
+    let Some(path) = path.map(Path::to_path_buf).or_else(find_config) else {
+        return Ok(Config::default());
+    };
+
+    let source = fs::read_to_string(&path)
+        .map_err(|err| format!("cannot read {}: {err}", path.display()))?;
+    Config::parse(&source).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// Formats the given files in place, or stdin to stdout when no file is given.
+/// With `--check`, nothing is written and the exit code tells whether everything is formatted.
+pub fn run(args: &[String]) -> ExitCode {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let config = match load_config(options.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut source_map = SourceMap::new();
+
+    if options.files.is_empty() {
+        let mut source = String::new();
+        if let Err(err) = io::stdin().read_to_string(&mut source) {
+            eprintln!("error: cannot read stdin: {err}");
+            return ExitCode::FAILURE;
+        }
+
+        let file = source_map.add("<stdin>", source.as_str());
+        return match format_file(&source_map, file, &config) {
+            Some(formatted) if options.check => exit_code(formatted == source),
+            Some(formatted) => {
+                let _ = io::stdout().write_all(formatted.as_bytes());
+                ExitCode::SUCCESS
+            }
+            None => ExitCode::FAILURE,
+        };
+    }
+
+    let mut ok = true;
+    for path in &options.files {
+        let file = match source_map.load(path) {
+            Ok(file) => file,
+            Err(err) => {
+                eprintln!("error: cannot read {}: {err}", path.display());
+                ok = false;
+                continue;
+            }
+        };
+
+        let Some(formatted) = format_file(&source_map, file, &config) else {
+            ok = false;
+            continue;
+        };
+
+        if formatted == source_map.text(file) {
+            continue;
+        }
+
+        if options.check {
+            println!("{} is not formatted", path.display());
+            ok = false;
+        } else if let Err(err) = fs::write(path, formatted) {
+            eprintln!("error: cannot write {}: {err}", path.display());
+            ok = false;
+        }
+    }
+
+    exit_code(ok)
+}
+
+fn format_file(source_map: &SourceMap, file: FileId, config: &Config) -> Option<String> {
+    match formatter::format(source_map.text(file), config) {
+        Ok(formatted) => Some(formatted),
+        Err(errors) => {
+            for error in errors {
+                let span = Span::new(file, error.range);
+                eprint!(
+                    "{}",
+                    Diagnostic::error(span, error.message).render(source_map)
+                );
+            }
+            None
+        }
+    }
+}
+
+fn exit_code(ok: bool) -> ExitCode {
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,59 @@
+use std::{
+    fs,
+    io::{self, Read},
+    process::ExitCode,
+};
+
+enum Output {
+    Ansi,
+    Html,
+    TextMate,
+}
+
+/// Prints the highlighted file (or stdin), or the TextMate grammar with `--textmate`.
+pub fn run(args: &[String]) -> ExitCode {
+    let mut output = Output::Ansi;
+    let mut file = None;
+
+    for arg in args {
+        match arg.as_str() {
+            "--ansi" => output = Output::Ansi,
+            "--html" => output = Output::Html,
+            "--textmate" => output = Output::TextMate,
+            _ if arg.starts_with("--") => {
+                eprintln!("error: unknown option `{arg}`");
+                return ExitCode::from(2);
+            }
+            _ => file = Some(arg),
+        }
+    }
+
+    if let Output::TextMate = output {
+        print!("{}", highlighter::textmate_grammar());
+        return ExitCode::SUCCESS;
+    }
+
+    let source = match file {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source).map(|_| source)
+        }
+    };
+
+    let source = match source {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("error: cannot read input: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match output {
+        Output::Ansi => print!("{}", highlighter::to_ansi(&source)),
+        Output::Html => print!("{}", highlighter::to_html(&source)),
+        Output::TextMate => unreachable!(),
+    }
+
+    ExitCode::SUCCESS
+}
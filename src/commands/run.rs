@@ -0,0 +1,48 @@
+use std::process::ExitCode;
+
+use db::Database;
+use interpreter::{Interpreter, Value};
+use source_map::Severity;
+
+/// Runs the `main` function of the program rooted at the given file,
+/// printing the value it returns unless it's `()`.
+pub fn run(args: &[String]) -> ExitCode {
+    let [path] = args else {
+        eprintln!("usage: lamia run <file>");
+        return ExitCode::from(2);
+    };
+
+    let mut db = Database::new();
+    let root = match db.load_file(path) {
+        Ok(root) => root,
+        Err(err) => {
+            eprintln!("error: cannot read {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (module_tree, mut diagnostics) = modules::build(&mut db, root);
+    diagnostics.extend(checker::check(&db, &module_tree));
+    let source_map = db.source_map();
+    for diagnostic in &diagnostics {
+        eprint!("{}", diagnostic.render(source_map));
+    }
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return ExitCode::FAILURE;
+    }
+
+    match Interpreter::new(&db, module_tree).call(&["main"], Vec::new()) {
+        Ok(Value::Unit) => ExitCode::SUCCESS,
+        Ok(value) => {
+            println!("{value}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprint!("{}", error.to_diagnostic().render(source_map));
+            ExitCode::FAILURE
+        }
+    }
+}
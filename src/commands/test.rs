@@ -0,0 +1,106 @@
+use std::{path::Path, process::ExitCode};
+
+use db::Database;
+use interpreter::{discover_tests, Interpreter, RuntimeError};
+use modules::FILE_EXTENSION;
+use source_map::Severity;
+
+/// Runs the `#[test]` functions of the program rooted at the given file, or at
+/// `main.lamia` if there's none, whose paths contain the filter.
+pub fn run(args: &[String]) -> ExitCode {
+    let (path, filter) = match args {
+        [path, rest @ ..]
+            if Path::new(path)
+                .extension()
+                .is_some_and(|ext| ext == FILE_EXTENSION) =>
+        {
+            (path.as_str(), rest)
+        }
+        rest => ("main.lamia", rest),
+    };
+    let filter = match filter {
+        [] => None,
+        [filter] => Some(filter.as_str()),
+        _ => {
+            eprintln!("usage: lamia test [file] [filter]");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut db = Database::new();
+    let root = match db.load_file(path) {
+        Ok(root) => root,
+        Err(err) => {
+            eprintln!("error: cannot read {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (module_tree, mut diagnostics) = modules::build(&mut db, root);
+    diagnostics.extend(checker::check(&db, &module_tree));
+    let source_map = db.source_map();
+    for diagnostic in &diagnostics {
+        eprint!("{}", diagnostic.render(source_map));
+    }
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return ExitCode::FAILURE;
+    }
+
+    let tests = discover_tests(&db, &module_tree);
+    let total = tests.len();
+    let tests = tests
+        .into_iter()
+        .filter(|test| filter.is_none_or(|filter| test.path.contains(filter)))
+        .collect::<Vec<_>>();
+    let filtered_out = total - tests.len();
+
+    println!(
+        "running {} test{}",
+        tests.len(),
+        if tests.len() == 1 { "" } else { "s" }
+    );
+    let mut failures = Vec::<(&str, RuntimeError)>::new();
+    let (mut passed, mut ignored) = (0, 0);
+    for test in &tests {
+        if test.ignored {
+            println!("test {} ... ignored", test.path);
+            ignored += 1;
+            continue;
+        }
+
+        // Every test gets its own interpreter, so none can observe another.
+        let mut interpreter = Interpreter::new(&db, module_tree.clone());
+        match interpreter.run_test(test) {
+            Ok(()) => {
+                println!("test {} ... ok", test.path);
+                passed += 1;
+            }
+            Err(error) => {
+                println!("test {} ... FAILED", test.path);
+                failures.push((&test.path, error));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:\n");
+        for (path, error) in &failures {
+            println!("---- {path} ----");
+            print!("{}", error.to_diagnostic().render(source_map));
+        }
+    }
+
+    println!(
+        "\ntest result: {}. {passed} passed; {} failed; {ignored} ignored; {filtered_out} filtered out",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        failures.len(),
+    );
+    if failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -1 +1,32 @@
-fn main() {}
+mod commands;
+
+use std::{env, process::ExitCode};
+
+const USAGE: &str = "\
+usage: lamia <command> [options]
+
+commands:
+    build [--emit=c|wasm|wat|mir] [-o <path>] <file>  compile a program to C, WebAssembly or MIR
+    check <file>                                      report errors in a program
+    fmt [--check] [--config <path>] [files...]        format lamia source files
+    highlight [--ansi | --html] [file]                print highlighted source
+    highlight --textmate                              print the TextMate grammar
+    run <file>                                        run the `main` function of a program
+    test [file] [filter]                              run the `#[test]` functions of a program";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    match args.split_first() {
+        Some((command, args)) if command == "build" => commands::build::run(args),
+        Some((command, args)) if command == "check" => commands::check::run(args),
+        Some((command, args)) if command == "fmt" => commands::fmt::run(args),
+        Some((command, args)) if command == "highlight" => commands::highlight::run(args),
+        Some((command, args)) if command == "run" => commands::run::run(args),
+        Some((command, args)) if command == "test" => commands::test::run(args),
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}